@@ -0,0 +1,92 @@
+//! Event scheduling benchmarks - a few busy generator, processor, and sink
+//! lines, among a growing number of idle processors.  Under scanning, the
+//! cost of each step grows with the model count, while under future event
+//! list scheduling, the cost of each step depends on the imminent models
+//! and message targets only.
+//!
+//! Run with `cargo bench --bench event_scheduling`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use sim::input_modeling::ContinuousRandomVariable;
+use sim::models::{Generator, Model, Processor, Sink};
+use sim::simulator::{topology, Connector, EventScheduling, Simulation};
+
+const BUSY_LINES: usize = 10;
+const STEPS: usize = 200;
+
+fn processor(job_port: &str) -> Box<Processor> {
+    Box::new(Processor::new(
+        ContinuousRandomVariable::Exp { lambda: 2.0 },
+        None,
+        job_port.to_string(),
+        String::from("processed"),
+        false,
+        None,
+    ))
+}
+
+fn simulation(idle_models: usize, scheduling: EventScheduling) -> Simulation {
+    let mut models = Vec::new();
+    let mut connectors: Vec<Connector> = Vec::new();
+    (0..BUSY_LINES).for_each(|line| {
+        let ids = [
+            format!["generator-{:02}", line],
+            format!["processor-{:02}", line],
+            format!["sink-{:02}", line],
+        ];
+        models.push(Model::new(
+            ids[0].clone(),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp {
+                    lambda: 1.0 + line as f64,
+                },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ));
+        models.push(Model::new(ids[1].clone(), processor("job")));
+        models.push(Model::new(
+            ids[2].clone(),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ));
+        connectors.extend(topology::pipeline(&[&ids[0], &ids[1]], "job", "job"));
+        connectors.extend(topology::pipeline(&[&ids[1], &ids[2]], "processed", "job"));
+    });
+    (0..idle_models).for_each(|index| {
+        models.push(Model::new(format!["idle-{:05}", index], processor("job")));
+    });
+    let mut simulation = Simulation::post(models, connectors);
+    simulation.enable_deterministic_mode();
+    simulation.set_event_scheduling(scheduling);
+    simulation
+}
+
+fn event_scheduling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("event_scheduling");
+    group.sample_size(10);
+    for idle_models in [100, 1_000, 10_000] {
+        for (name, scheduling) in [
+            ("scan", EventScheduling::Scan),
+            ("future_event_list", EventScheduling::FutureEventList),
+        ] {
+            group.bench_with_input(
+                BenchmarkId::new(name, idle_models),
+                &idle_models,
+                |b, idle_models| {
+                    b.iter_batched(
+                        || simulation(*idle_models, scheduling),
+                        |mut simulation| simulation.step_n(STEPS).unwrap(),
+                        criterion::BatchSize::LargeInput,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, event_scheduling);
+criterion_main!(benches);
@@ -0,0 +1,112 @@
+//! A call center, built with the Rust API.  Calls arrive as a Poisson
+//! process, wait in a bounded queue, and are handled by a single agent.
+//! Calls arriving to a full queue are abandoned.  The agent's waiting time
+//! records are analyzed with an `IndependentSample` confidence interval.
+
+use std::collections::HashMap;
+
+use sim::input_modeling::ContinuousRandomVariable;
+use sim::models::{Generator, Model, Processor, Storage};
+use sim::output_analysis::IndependentSample;
+use sim::simulator::{Connector, Simulation};
+use sim::utils::errors::SimulationError;
+
+fn call_center() -> Simulation {
+    let models = vec![
+        Model::new(
+            String::from("calls"),
+            Box::new(Generator::new(
+                // One call per 2 minutes, on average
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("call"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("agent"),
+            Box::new(Processor::new(
+                // Calls take 1.5 minutes to handle, on average
+                ContinuousRandomVariable::Exp { lambda: 0.6667 },
+                Some(10),
+                String::from("call"),
+                String::from("handled"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("handled-calls"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = vec![
+        Connector::new(
+            String::from("connector-01"),
+            String::from("calls"),
+            String::from("agent"),
+            String::from("call"),
+            String::from("call"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("agent"),
+            String::from("handled-calls"),
+            String::from("handled"),
+            String::from("store"),
+        ),
+    ];
+    Simulation::post(models, connectors)
+}
+
+fn run() -> Result<(), SimulationError> {
+    let mut simulation = call_center();
+    // An 8 hour shift, in minutes
+    simulation.step_until(480.0)?;
+    let mut arrivals: HashMap<String, f64> = HashMap::new();
+    let mut waiting_times: Vec<f64> = Vec::new();
+    simulation
+        .get_records("agent")?
+        .iter()
+        .for_each(|record| match record.action.as_str() {
+            "Arrival" => {
+                arrivals.insert(record.subject.clone(), record.time);
+            }
+            "Processing Start" => {
+                if let Some(arrival) = arrivals.get(&record.subject) {
+                    waiting_times.push(record.time - arrival);
+                }
+            }
+            _ => {}
+        });
+    let abandoned = simulation
+        .get_records("agent")?
+        .iter()
+        .filter(|record| record.action == "Drop")
+        .count();
+    let waiting_times = IndependentSample::post(waiting_times)?;
+    let interval = waiting_times.confidence_interval_mean(0.05)?;
+    println!(
+        "Average wait: {:.2} minutes (95% CI {:.2} to {:.2}), abandoned calls: {}",
+        waiting_times.point_estimate_mean(),
+        interval.lower(),
+        interval.upper(),
+        abandoned
+    );
+    Ok(())
+}
+
+fn main() -> Result<(), SimulationError> {
+    run()
+}
+
+#[test]
+fn call_center_example() -> Result<(), SimulationError> {
+    run()
+}
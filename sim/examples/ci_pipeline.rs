@@ -0,0 +1,131 @@
+//! A continuous integration pipeline, built with the Rust API.  Each commit
+//! is built, and the build is then tested and linted in parallel.  The
+//! pipeline completes when both the tests and lints complete.  A Sobol
+//! sensitivity analysis determines whether the test or lint duration drives
+//! the variance in pipeline throughput.
+
+use sim::experiment::{Parameter, SobolAnalysis};
+use sim::input_modeling::{dyn_rng, ContinuousRandomVariable};
+use sim::models::{Generator, Model, ParallelGateway, Processor, Storage};
+use sim::simulator::{Connector, Simulation};
+use sim::utils::errors::SimulationError;
+
+fn connector(id: &str, source: (&str, &str), target: (&str, &str)) -> Connector {
+    Connector::new(
+        String::from(id),
+        String::from(source.0),
+        String::from(target.0),
+        String::from(source.1),
+        String::from(target.1),
+    )
+}
+
+fn processor(mean_duration: f64) -> Box<Processor> {
+    Box::new(Processor::new(
+        ContinuousRandomVariable::Exp {
+            lambda: 1.0 / mean_duration,
+        },
+        None,
+        String::from("job"),
+        String::from("done"),
+        false,
+        None,
+    ))
+}
+
+fn ci_pipeline(mean_test_duration: f64, mean_lint_duration: f64) -> Simulation {
+    let models = vec![
+        Model::new(
+            String::from("commits"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.05 },
+                None,
+                String::from("commit"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(String::from("build"), processor(5.0)),
+        Model::new(
+            String::from("fork"),
+            Box::new(ParallelGateway::new(
+                vec![String::from("in")],
+                vec![String::from("test"), String::from("lint")],
+                false,
+            )),
+        ),
+        Model::new(String::from("test"), processor(mean_test_duration)),
+        Model::new(String::from("lint"), processor(mean_lint_duration)),
+        Model::new(
+            String::from("join"),
+            Box::new(ParallelGateway::new(
+                vec![String::from("test"), String::from("lint")],
+                vec![String::from("out")],
+                false,
+            )),
+        ),
+        Model::new(
+            String::from("completed"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = vec![
+        connector("connector-01", ("commits", "commit"), ("build", "job")),
+        connector("connector-02", ("build", "done"), ("fork", "in")),
+        connector("connector-03", ("fork", "test"), ("test", "job")),
+        connector("connector-04", ("fork", "lint"), ("lint", "job")),
+        connector("connector-05", ("test", "done"), ("join", "test")),
+        connector("connector-06", ("lint", "done"), ("join", "lint")),
+        connector("connector-07", ("join", "out"), ("completed", "store")),
+    ];
+    Simulation::post(models, connectors)
+}
+
+fn completed_pipelines(
+    mean_test_duration: f64,
+    mean_lint_duration: f64,
+) -> Result<f64, SimulationError> {
+    let mut simulation = ci_pipeline(mean_test_duration, mean_lint_duration);
+    Ok(simulation
+        .step_until(1000.0)?
+        .iter()
+        .filter(|message| message.target_id() == "completed")
+        .count() as f64)
+}
+
+fn run() -> Result<String, SimulationError> {
+    let analysis = SobolAnalysis::new(
+        vec![
+            Parameter::new(String::from("mean test duration"), 5.0, 30.0),
+            Parameter::new(String::from("mean lint duration"), 1.0, 2.0),
+        ],
+        vec![String::from("completed pipelines")],
+        16,
+    );
+    let sensitivities = analysis.run(dyn_rng(rand_pcg::Pcg64Mcg::new(7)), |parameters| {
+        Ok(vec![completed_pipelines(parameters[0], parameters[1])?])
+    })?;
+    let dominant = sensitivities[0]
+        .dominant_parameter()
+        .ok_or(SimulationError::InvalidExperimentConfiguration)?;
+    println!(
+        "The {} drives the variance in {}",
+        dominant.parameter, sensitivities[0].kpi
+    );
+    Ok(dominant.parameter.clone())
+}
+
+fn main() -> Result<(), SimulationError> {
+    run().map(|_| ())
+}
+
+#[test]
+fn ci_pipeline_example() -> Result<(), SimulationError> {
+    assert_eq!(run()?, "mean test duration");
+    Ok(())
+}
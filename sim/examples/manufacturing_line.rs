@@ -0,0 +1,106 @@
+//! A manufacturing line, built with the YAML API.  Parts are machined,
+//! inspected (with a 5% scrap rate), and then packaged.  The line yield is
+//! calculated from the message flows into and out of the inspection step.
+
+use sim::simulator::{Message, WebSimulation};
+
+const MODELS: &str = r#"
+- type: "Generator"
+  id: "raw-parts"
+  portsIn: {}
+  portsOut:
+    job: "part"
+  messageInterdepartureTime:
+    exp:
+      lambda: 0.2
+- type: "Processor"
+  id: "machining"
+  portsIn:
+    job: "part"
+  portsOut:
+    job: "machined part"
+  serviceTime:
+    triangular:
+      min: 2.0
+      max: 4.0
+      mode: 3.0
+- type: "StochasticGate"
+  id: "inspection"
+  portsIn:
+    job: "part"
+  portsOut:
+    job: "inspected part"
+  passDistribution:
+    bernoulli:
+      p: 0.95
+- type: "Processor"
+  id: "packaging"
+  portsIn:
+    job: "part"
+  portsOut:
+    job: "packaged part"
+  serviceTime:
+    uniform:
+      min: 1.0
+      max: 2.0
+- type: "Storage"
+  id: "warehouse"
+  portsIn:
+    put: "store"
+    get: "read"
+  portsOut:
+    stored: "stored"
+"#;
+
+const CONNECTORS: &str = r#"
+- id: "connector-01"
+  sourceID: "raw-parts"
+  targetID: "machining"
+  sourcePort: "part"
+  targetPort: "part"
+- id: "connector-02"
+  sourceID: "machining"
+  targetID: "inspection"
+  sourcePort: "machined part"
+  targetPort: "part"
+- id: "connector-03"
+  sourceID: "inspection"
+  targetID: "packaging"
+  sourcePort: "inspected part"
+  targetPort: "part"
+- id: "connector-04"
+  sourceID: "packaging"
+  targetID: "warehouse"
+  sourcePort: "packaged part"
+  targetPort: "store"
+"#;
+
+fn run() -> f64 {
+    let mut simulation = WebSimulation::post_yaml(MODELS, CONNECTORS);
+    let messages: Vec<Message> = serde_json::from_str(&simulation.step_until_json(5000.0)).unwrap();
+    let inspected = messages
+        .iter()
+        .filter(|message| message.target_id() == "inspection")
+        .count();
+    let passed = messages
+        .iter()
+        .filter(|message| message.source_id() == "inspection")
+        .count();
+    let line_yield = passed as f64 / inspected as f64;
+    println!(
+        "Inspected {} parts, with a yield of {:.1}%",
+        inspected,
+        100.0 * line_yield
+    );
+    line_yield
+}
+
+fn main() {
+    run();
+}
+
+#[test]
+fn manufacturing_line_example() {
+    let line_yield = run();
+    assert!((line_yield - 0.95).abs() < 0.05);
+}
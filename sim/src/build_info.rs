@@ -0,0 +1,69 @@
+//! Build information describes the compiled sim package - the version, and
+//! the optional features compiled in.  JavaScript consumers of a trimmed
+//! WASM build (e.g. with the `wasm-small` feature) can adapt to the
+//! available models and interfaces.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// The version and compiled features of the sim package.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub version: String,
+    pub features: Vec<String>,
+}
+
+impl BuildInfo {
+    /// Check if an optional feature is compiled in.
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|compiled| compiled == feature)
+    }
+}
+
+/// The build information of the running sim package.
+pub fn build_info() -> BuildInfo {
+    let features: [(&str, bool); 19] = [
+        ("yaml", cfg!(feature = "yaml")),
+        ("batcher", cfg!(feature = "batcher")),
+        ("delay", cfg!(feature = "delay")),
+        ("exclusive-gateway", cfg!(feature = "exclusive-gateway")),
+        ("gate", cfg!(feature = "gate")),
+        ("load-balancer", cfg!(feature = "load-balancer")),
+        ("parallel-gateway", cfg!(feature = "parallel-gateway")),
+        ("scheduler", cfg!(feature = "scheduler")),
+        ("stochastic-gate", cfg!(feature = "stochastic-gate")),
+        ("stopwatch", cfg!(feature = "stopwatch")),
+        ("simx", cfg!(feature = "simx")),
+        ("parallel", cfg!(feature = "parallel")),
+        ("sqlite", cfg!(feature = "sqlite")),
+        ("gzip", cfg!(feature = "gzip")),
+        ("zstd", cfg!(feature = "zstd")),
+        (
+            "console_error_panic_hook",
+            cfg!(feature = "console_error_panic_hook"),
+        ),
+        ("wee_alloc", cfg!(feature = "wee_alloc")),
+        ("wasm-small", cfg!(feature = "wasm-small")),
+        ("typescript", cfg!(feature = "typescript")),
+    ];
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: features
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(feature, _)| feature.to_string())
+            .collect(),
+    }
+}
+
+/// A JS/WASM interface for `build_info`, which converts the build
+/// information to a JSON string.
+#[cfg_attr(not(feature = "typescript"), wasm_bindgen(js_name = buildInfo))]
+#[cfg_attr(
+    feature = "typescript",
+    wasm_bindgen(js_name = buildInfo, unchecked_return_type = "JsonString<BuildInfo>")
+)]
+pub fn build_info_json() -> String {
+    serde_json::to_string(&build_info()).unwrap()
+}
@@ -0,0 +1,331 @@
+//! Calibration searches the input parameter space for the parameter values
+//! that best reproduce observed, real-world behavior.  The user supplies a
+//! discrepancy function - typically an evaluation of simulation KPIs against
+//! observed data - and the search minimizes that discrepancy.  Random
+//! search, Latin hypercube sampling, and Nelder-Mead simplex search are
+//! available.
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use super::Parameter;
+use crate::input_modeling::dynamic_rng::{lock_rng, DynRng};
+use crate::input_modeling::ContinuousRandomVariable;
+use crate::utils::errors::SimulationError;
+
+/// The strategy used to explore the parameter space during calibration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchStrategy {
+    /// Independent, uniformly distributed samples over the parameter ranges
+    Random { samples: usize },
+    /// Stratified samples, with each parameter range divided into `samples`
+    /// equally sized strata, and each stratum sampled exactly once
+    LatinHypercube { samples: usize },
+    /// Nelder-Mead simplex search, started from the center of the parameter
+    /// ranges, and terminated after `max_iterations` iterations or when the
+    /// discrepancy values across the simplex are within `tolerance`
+    NelderMead {
+        max_iterations: usize,
+        tolerance: f64,
+    },
+}
+
+/// A single evaluation of the discrepancy function during calibration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationTrial {
+    pub parameters: Vec<f64>,
+    pub discrepancy: f64,
+}
+
+/// The best-fit parameters of a calibration, and the full search trace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationResult {
+    pub best_parameters: Vec<f64>,
+    pub best_discrepancy: f64,
+    pub trace: Vec<CalibrationTrial>,
+}
+
+/// A calibration declares the parameters under study, and the strategy used
+/// to search the parameter space.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Calibration {
+    parameters: Vec<Parameter>,
+    strategy: SearchStrategy,
+}
+
+impl Calibration {
+    pub fn new(parameters: Vec<Parameter>, strategy: SearchStrategy) -> Self {
+        Self {
+            parameters,
+            strategy,
+        }
+    }
+
+    fn random_points(
+        &self,
+        samples: usize,
+        rng: &DynRng,
+    ) -> Result<Vec<Vec<f64>>, SimulationError> {
+        (0..samples)
+            .map(|_| {
+                self.parameters
+                    .iter()
+                    .map(|parameter| {
+                        ContinuousRandomVariable::Uniform {
+                            min: parameter.min,
+                            max: parameter.max,
+                        }
+                        .random_variate(rng.clone())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn latin_hypercube_points(
+        &self,
+        samples: usize,
+        rng: &DynRng,
+    ) -> Result<Vec<Vec<f64>>, SimulationError> {
+        let mut offset = ContinuousRandomVariable::Uniform { min: 0.0, max: 1.0 };
+        let columns = self
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let mut strata: Vec<usize> = (0..samples).collect();
+                strata.shuffle(&mut *lock_rng(rng));
+                strata
+                    .iter()
+                    .map(|stratum| {
+                        let position = (*stratum as f64 + offset.random_variate(rng.clone())?)
+                            / samples as f64;
+                        Ok(parameter.min + position * (parameter.max - parameter.min))
+                    })
+                    .collect::<Result<Vec<f64>, SimulationError>>()
+            })
+            .collect::<Result<Vec<Vec<f64>>, SimulationError>>()?;
+        Ok((0..samples)
+            .map(|sample_index| columns.iter().map(|column| column[sample_index]).collect())
+            .collect())
+    }
+
+    fn clamp(&self, point: Vec<f64>) -> Vec<f64> {
+        point
+            .into_iter()
+            .zip(self.parameters.iter())
+            .map(|(value, parameter)| value.max(parameter.min).min(parameter.max))
+            .collect()
+    }
+
+    fn nelder_mead<F>(
+        &self,
+        max_iterations: usize,
+        tolerance: f64,
+        evaluate: &mut F,
+    ) -> Result<(), SimulationError>
+    where
+        F: FnMut(&[f64]) -> Result<f64, SimulationError>,
+    {
+        // Standard reflection, expansion, contraction, and shrink coefficients
+        let (alpha, gamma, rho, sigma) = (1.0, 2.0, 0.5, 0.5);
+        let center: Vec<f64> = self
+            .parameters
+            .iter()
+            .map(|parameter| (parameter.min + parameter.max) / 2.0)
+            .collect();
+        let mut simplex: Vec<(Vec<f64>, f64)> = Vec::new();
+        simplex.push((center.clone(), evaluate(&center)?));
+        for (parameter_index, parameter) in self.parameters.iter().enumerate() {
+            let mut vertex = center.clone();
+            vertex[parameter_index] += (parameter.max - parameter.min) / 4.0;
+            let discrepancy = evaluate(&vertex)?;
+            simplex.push((vertex, discrepancy));
+        }
+        for _ in 0..max_iterations {
+            simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let best = simplex[0].1;
+            let worst = simplex[simplex.len() - 1].1;
+            if (worst - best).abs() <= tolerance {
+                break;
+            }
+            let worst_vertex = simplex[simplex.len() - 1].0.clone();
+            let centroid: Vec<f64> = (0..self.parameters.len())
+                .map(|dimension| {
+                    simplex[..simplex.len() - 1]
+                        .iter()
+                        .map(|(vertex, _)| vertex[dimension])
+                        .sum::<f64>()
+                        / (simplex.len() - 1) as f64
+                })
+                .collect();
+            let along = |coefficient: f64| -> Vec<f64> {
+                centroid
+                    .iter()
+                    .zip(worst_vertex.iter())
+                    .map(|(c, w)| c + coefficient * (c - w))
+                    .collect()
+            };
+            let reflected = self.clamp(along(alpha));
+            let reflected_discrepancy = evaluate(&reflected)?;
+            let second_worst = simplex[simplex.len() - 2].1;
+            let last = simplex.len() - 1;
+            if reflected_discrepancy < best {
+                let expanded = self.clamp(along(gamma));
+                let expanded_discrepancy = evaluate(&expanded)?;
+                simplex[last] = if expanded_discrepancy < reflected_discrepancy {
+                    (expanded, expanded_discrepancy)
+                } else {
+                    (reflected, reflected_discrepancy)
+                };
+            } else if reflected_discrepancy < second_worst {
+                simplex[last] = (reflected, reflected_discrepancy);
+            } else {
+                let contracted = self.clamp(along(-rho));
+                let contracted_discrepancy = evaluate(&contracted)?;
+                if contracted_discrepancy < worst {
+                    simplex[last] = (contracted, contracted_discrepancy);
+                } else {
+                    let best_vertex = simplex[0].0.clone();
+                    for (vertex, discrepancy) in simplex.iter_mut().skip(1) {
+                        *vertex = best_vertex
+                            .iter()
+                            .zip(vertex.iter())
+                            .map(|(b, v)| b + sigma * (v - b))
+                            .collect();
+                        *discrepancy = evaluate(vertex)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute the calibration.  The discrepancy function receives one value
+    /// per parameter (in declaration order), and returns the discrepancy
+    /// between the simulated and observed behavior, to be minimized.
+    pub fn run<F>(
+        &self,
+        rng: DynRng,
+        mut discrepancy: F,
+    ) -> Result<CalibrationResult, SimulationError>
+    where
+        F: FnMut(&[f64]) -> Result<f64, SimulationError>,
+    {
+        if self.parameters.is_empty() {
+            return Err(SimulationError::InvalidExperimentConfiguration);
+        }
+        self.parameters.iter().try_for_each(Parameter::validate)?;
+        let mut trace: Vec<CalibrationTrial> = Vec::new();
+        let mut evaluate = |parameters: &[f64]| -> Result<f64, SimulationError> {
+            let value = discrepancy(parameters)?;
+            trace.push(CalibrationTrial {
+                parameters: parameters.to_vec(),
+                discrepancy: value,
+            });
+            Ok(value)
+        };
+        match &self.strategy {
+            SearchStrategy::Random { samples } => self
+                .random_points(*samples, &rng)?
+                .iter()
+                .try_for_each(|point| evaluate(point).map(|_| ()))?,
+            SearchStrategy::LatinHypercube { samples } => self
+                .latin_hypercube_points(*samples, &rng)?
+                .iter()
+                .try_for_each(|point| evaluate(point).map(|_| ()))?,
+            SearchStrategy::NelderMead {
+                max_iterations,
+                tolerance,
+            } => self.nelder_mead(*max_iterations, *tolerance, &mut evaluate)?,
+        }
+        let best = trace
+            .iter()
+            .min_by(|a, b| a.discrepancy.total_cmp(&b.discrepancy))
+            .ok_or(SimulationError::InvalidExperimentConfiguration)?
+            .clone();
+        Ok(CalibrationResult {
+            best_parameters: best.parameters,
+            best_discrepancy: best.discrepancy,
+            trace,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_modeling::dynamic_rng::default_rng;
+
+    fn parameters() -> Vec<Parameter> {
+        vec![
+            Parameter::new(String::from("x"), -5.0, 5.0),
+            Parameter::new(String::from("y"), -5.0, 5.0),
+        ]
+    }
+
+    fn discrepancy(point: &[f64]) -> Result<f64, SimulationError> {
+        Ok((point[0] - 2.0).powi(2) + (point[1] + 1.0).powi(2))
+    }
+
+    #[test]
+    fn latin_hypercube_covers_every_stratum() {
+        let calibration =
+            Calibration::new(parameters(), SearchStrategy::LatinHypercube { samples: 10 });
+        let result = calibration.run(default_rng(), discrepancy).unwrap();
+        assert_eq!(result.trace.len(), 10);
+        (0..2).for_each(|dimension| {
+            let mut strata: Vec<usize> = result
+                .trace
+                .iter()
+                .map(|trial| ((trial.parameters[dimension] + 5.0) as usize).min(9))
+                .collect();
+            strata.sort_unstable();
+            assert_eq!(strata, (0..10).collect::<Vec<usize>>());
+        });
+    }
+
+    #[test]
+    fn nelder_mead_finds_minimum() {
+        let calibration = Calibration::new(
+            parameters(),
+            SearchStrategy::NelderMead {
+                max_iterations: 200,
+                tolerance: 1.0e-10,
+            },
+        );
+        let result = calibration.run(default_rng(), discrepancy).unwrap();
+        assert!((result.best_parameters[0] - 2.0).abs() < 1.0e-3);
+        assert!((result.best_parameters[1] + 1.0).abs() < 1.0e-3);
+        assert!(result.best_discrepancy < 1.0e-6);
+    }
+
+    #[test]
+    fn empty_parameter_ranges_are_rejected() {
+        let mut evaluations = 0;
+        let calibration = Calibration::new(
+            vec![
+                Parameter::new(String::from("x"), -5.0, 5.0),
+                Parameter::new(String::from("y"), 5.0, 5.0),
+            ],
+            SearchStrategy::Random { samples: 10 },
+        );
+        let result = calibration.run(default_rng(), |point| {
+            evaluations += 1;
+            discrepancy(point)
+        });
+        assert!(matches!(
+            result,
+            Err(SimulationError::InvalidExperimentConfiguration)
+        ));
+        assert_eq!(evaluations, 0);
+        let reversed = Calibration::new(
+            vec![Parameter::new(String::from("x"), 5.0, -5.0)],
+            SearchStrategy::LatinHypercube { samples: 10 },
+        );
+        assert!(reversed.run(default_rng(), discrepancy).is_err());
+    }
+}
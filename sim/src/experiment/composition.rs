@@ -0,0 +1,571 @@
+//! Hierarchical experiment composition builds experiments out of other
+//! experiments.  An experiment configuration file may reference other
+//! experiment files as sub-experiments, and the named outputs of each
+//! sub-experiment feed parameters of the parent - for example, calibrating
+//! a sub-model, and then using the fitted parameters upstream.  The
+//! `ExperimentRunner` orchestrates the composition, executing
+//! sub-experiments before their parents, and caching sub-experiment results,
+//! so a sub-experiment shared across the composition is executed only once.
+//! With a `ResultCache`, experiment outputs are additionally persisted
+//! across runner instances and sessions.
+//!
+//! Experiment configuration files are YAML (or JSON, as a subset of YAML).
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::replication::{ReplicationPlan, ReplicationResult};
+use super::result_cache::ResultCache;
+use super::results::ScenarioResult;
+use crate::simulator::Assertion;
+use crate::utils::errors::SimulationError;
+use crate::utils::yaml;
+
+/// The named outputs of an experiment, such as fitted parameter values or
+/// KPI estimates.
+pub type ExperimentOutputs = BTreeMap<String, f64>;
+
+/// A reference from an experiment to a sub-experiment file.  The outputs
+/// map sub-experiment output names to the parent parameter names they feed.
+/// Relative paths are resolved against the directory of the referencing
+/// experiment file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubExperiment {
+    pub path: String,
+    #[serde(default)]
+    pub outputs: BTreeMap<String, String>,
+}
+
+/// An experiment configuration.  The parameters are the parent's own
+/// parameter values, which are overridden by any sub-experiment outputs
+/// feeding the same parameter names.  The spec is free-form, and describes
+/// the experiment itself (e.g. the calibration or simulation to execute),
+/// for interpretation by the experiment executor.  The random number
+/// generator seed and simulation duration are likewise interpreted by the
+/// executor, and distinguish cached results.  A replication plan expands
+/// the experiment into independent replications, through
+/// `ExperimentRunner::run_replications`.  Assertions on the experiment
+/// KPIs are evaluated across the replications of a scenario, by
+/// `ExperimentRunner::run_scenario`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExperimentConfig {
+    pub name: String,
+    #[serde(default)]
+    pub parameters: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub sub_experiments: Vec<SubExperiment>,
+    #[serde(default)]
+    pub spec: Value,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub replications: Option<ReplicationPlan>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+}
+
+impl ExperimentConfig {
+    /// Read an experiment configuration from a YAML or JSON file, with
+    /// environment variable interpolation and includes.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, SimulationError> {
+        yaml::from_file(path)
+    }
+}
+
+/// The experiment runner executes hierarchical experiment compositions,
+/// with a cache of experiment results keyed by the canonical experiment
+/// file path.  The cache persists across runs, until cleared.  An optional
+/// result cache persists experiment outputs on disk, across sessions.
+#[derive(Debug, Clone, Default)]
+pub struct ExperimentRunner {
+    cache: HashMap<PathBuf, ExperimentOutputs>,
+    result_cache: Option<ResultCache>,
+}
+
+/// The inputs distinguishing the results of an experiment execution.
+#[derive(Serialize)]
+struct ExecutionInputs<'a> {
+    name: &'a str,
+    parameters: &'a ExperimentOutputs,
+    spec: &'a Value,
+}
+
+impl ExperimentRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reuse stored outputs from the result cache, for experiments whose
+    /// name, resolved parameters, spec, seed, and duration are unchanged.
+    /// Outputs of executed experiments are stored in the result cache.
+    pub fn with_result_cache(mut self, result_cache: ResultCache) -> Self {
+        self.result_cache = Some(result_cache);
+        self
+    }
+
+    /// The cached results of a previously executed experiment file.
+    pub fn cached<P: AsRef<Path>>(&self, path: P) -> Option<&ExperimentOutputs> {
+        fs::canonicalize(path)
+            .ok()
+            .and_then(|path| self.cache.get(&path))
+    }
+
+    /// Discard all cached experiment results.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Execute the experiment file at the provided path, after its
+    /// sub-experiments.  The executor receives each experiment
+    /// configuration and its resolved parameters, and returns the
+    /// experiment outputs.
+    pub fn run_file<P, F>(
+        &mut self,
+        path: P,
+        mut executor: F,
+    ) -> Result<ExperimentOutputs, SimulationError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(
+            &ExperimentConfig,
+            &ExperimentOutputs,
+        ) -> Result<ExperimentOutputs, SimulationError>,
+    {
+        let mut in_progress = Vec::new();
+        self.run_path(path.as_ref(), &mut executor, &mut in_progress)
+    }
+
+    /// Execute an in-memory experiment configuration, after its
+    /// sub-experiments.  Relative sub-experiment paths are resolved against
+    /// the provided base directory.  The configuration itself is not
+    /// cached, but its sub-experiments are.
+    pub fn run<P, F>(
+        &mut self,
+        config: &ExperimentConfig,
+        base_directory: P,
+        mut executor: F,
+    ) -> Result<ExperimentOutputs, SimulationError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(
+            &ExperimentConfig,
+            &ExperimentOutputs,
+        ) -> Result<ExperimentOutputs, SimulationError>,
+    {
+        let mut in_progress = Vec::new();
+        self.run_config(
+            config,
+            base_directory.as_ref(),
+            &mut executor,
+            &mut in_progress,
+        )
+    }
+
+    /// Execute every replication of an in-memory experiment configuration,
+    /// after its sub-experiments.  The executor receives the configuration
+    /// of each replication, with the replication seed and spec overrides
+    /// applied.  Without a replication plan, the experiment is executed
+    /// once.
+    pub fn run_replications<P, F>(
+        &mut self,
+        config: &ExperimentConfig,
+        base_directory: P,
+        mut executor: F,
+    ) -> Result<Vec<ReplicationResult>, SimulationError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(
+            &ExperimentConfig,
+            &ExperimentOutputs,
+        ) -> Result<ExperimentOutputs, SimulationError>,
+    {
+        (0..config.replication_count())
+            .map(|replication| {
+                let (replica, overrides) = config.replicate(replication)?;
+                let mut in_progress = Vec::new();
+                let outputs = self.run_config(
+                    &replica,
+                    base_directory.as_ref(),
+                    &mut executor,
+                    &mut in_progress,
+                )?;
+                Ok(ReplicationResult {
+                    replication,
+                    seed: config.replication_seed(replication),
+                    overrides,
+                    outputs,
+                })
+            })
+            .collect()
+    }
+
+    /// Execute every replication of an in-memory experiment configuration,
+    /// as with `run_replications`, collecting the replication outputs as a
+    /// scenario result, for analysis across replications.  The assertions
+    /// of the configuration are evaluated across the replications.
+    pub fn run_scenario<P, F>(
+        &mut self,
+        config: &ExperimentConfig,
+        base_directory: P,
+        executor: F,
+    ) -> Result<ScenarioResult, SimulationError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(
+            &ExperimentConfig,
+            &ExperimentOutputs,
+        ) -> Result<ExperimentOutputs, SimulationError>,
+    {
+        let replications = self.run_replications(config, base_directory, executor)?;
+        let mut scenario = ScenarioResult::new(
+            config.name.clone(),
+            config.parameters.clone(),
+            replications.into(),
+        );
+        scenario.assertions = scenario.check_assertions(&config.assertions)?;
+        Ok(scenario)
+    }
+
+    fn run_path<F>(
+        &mut self,
+        path: &Path,
+        executor: &mut F,
+        in_progress: &mut Vec<PathBuf>,
+    ) -> Result<ExperimentOutputs, SimulationError>
+    where
+        F: FnMut(
+            &ExperimentConfig,
+            &ExperimentOutputs,
+        ) -> Result<ExperimentOutputs, SimulationError>,
+    {
+        let path = fs::canonicalize(path)?;
+        if let Some(outputs) = self.cache.get(&path) {
+            return Ok(outputs.clone());
+        }
+        if in_progress.contains(&path) {
+            return Err(SimulationError::CyclicExperimentError);
+        }
+        let config = ExperimentConfig::from_file(&path)?;
+        let base_directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        in_progress.push(path.clone());
+        let outputs = self.run_config(&config, &base_directory, executor, in_progress);
+        in_progress.pop();
+        let outputs = outputs?;
+        self.cache.insert(path, outputs.clone());
+        Ok(outputs)
+    }
+
+    fn run_config<F>(
+        &mut self,
+        config: &ExperimentConfig,
+        base_directory: &Path,
+        executor: &mut F,
+        in_progress: &mut Vec<PathBuf>,
+    ) -> Result<ExperimentOutputs, SimulationError>
+    where
+        F: FnMut(
+            &ExperimentConfig,
+            &ExperimentOutputs,
+        ) -> Result<ExperimentOutputs, SimulationError>,
+    {
+        let mut parameters = config.parameters.clone();
+        for sub_experiment in &config.sub_experiments {
+            let sub_outputs = self.run_path(
+                &base_directory.join(&sub_experiment.path),
+                executor,
+                in_progress,
+            )?;
+            for (output, parameter) in &sub_experiment.outputs {
+                let value = sub_outputs
+                    .get(output)
+                    .ok_or(SimulationError::InvalidExperimentConfiguration)?;
+                parameters.insert(parameter.clone(), *value);
+            }
+        }
+        match &self.result_cache {
+            Some(result_cache) => {
+                let key = ResultCache::key(
+                    &ExecutionInputs {
+                        name: &config.name,
+                        parameters: &parameters,
+                        spec: &config.spec,
+                    },
+                    config.seed,
+                    config.duration,
+                )?;
+                result_cache.get_or_run(&key, || executor(config, &parameters))
+            }
+            None => executor(config, &parameters),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::{Calibration, Parameter, SearchStrategy};
+    use crate::input_modeling::dynamic_rng::default_rng;
+
+    fn experiment_directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!["sim-composition-{}-{}", name, std::process::id()]);
+        fs::create_dir_all(&directory).unwrap();
+        files.iter().for_each(|(file_name, contents)| {
+            fs::write(directory.join(file_name), contents).unwrap();
+        });
+        directory
+    }
+
+    #[test]
+    fn calibrated_sub_experiment_feeds_parent() {
+        let directory = experiment_directory(
+            "calibration",
+            &[
+                ("service.yaml", "name: service\nspec:\n  target: 2.5\n"),
+                ("arrivals.yaml", "name: arrivals\nspec:\n  target: 1.5\n"),
+                (
+                    "line.yaml",
+                    "
+name: line
+parameters:
+  serviceRate: 1.0
+  stations: 3.0
+subExperiments:
+  - path: service.yaml
+    outputs:
+      fitted: serviceRate
+  - path: arrivals.yaml
+    outputs:
+      fitted: arrivalRate
+",
+                ),
+            ],
+        );
+        let mut executions: Vec<String> = Vec::new();
+        let mut runner = ExperimentRunner::new();
+        let outputs = runner
+            .run_file(directory.join("line.yaml"), |config, parameters| {
+                executions.push(config.name.clone());
+                match config.spec.get("target").and_then(Value::as_f64) {
+                    // Leaf experiments calibrate a rate against the target
+                    Some(target) => {
+                        let result = Calibration::new(
+                            vec![Parameter::new(String::from("rate"), 0.0, 5.0)],
+                            SearchStrategy::NelderMead {
+                                max_iterations: 100,
+                                tolerance: 1.0e-12,
+                            },
+                        )
+                        .run(default_rng(), |point| Ok((point[0] - target).powi(2)))?;
+                        let mut outputs = ExperimentOutputs::new();
+                        outputs.insert(String::from("fitted"), result.best_parameters[0]);
+                        Ok(outputs)
+                    }
+                    // The parent reports the utilization of its stations
+                    None => {
+                        let mut outputs = ExperimentOutputs::new();
+                        outputs.insert(
+                            String::from("utilization"),
+                            parameters["arrivalRate"]
+                                / (parameters["serviceRate"] * parameters["stations"]),
+                        );
+                        Ok(outputs)
+                    }
+                }
+            })
+            .unwrap();
+        assert_eq!(executions, vec!["service", "arrivals", "line"]);
+        assert!((outputs["utilization"] - 0.2).abs() < 1.0e-3);
+        assert!(
+            (runner.cached(directory.join("service.yaml")).unwrap()["fitted"] - 2.5).abs() < 1.0e-3
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn shared_sub_experiments_execute_once() {
+        let directory = experiment_directory(
+            "shared",
+            &[
+                ("shared.yaml", "name: shared\n"),
+                (
+                    "left.yaml",
+                    "name: left\nsubExperiments:\n  - path: shared.yaml\n",
+                ),
+                (
+                    "right.yaml",
+                    "name: right\nsubExperiments:\n  - path: shared.yaml\n",
+                ),
+                (
+                    "top.yaml",
+                    "name: top\nsubExperiments:\n  - path: left.yaml\n  - path: right.yaml\n",
+                ),
+            ],
+        );
+        let mut executions: Vec<String> = Vec::new();
+        let mut runner = ExperimentRunner::new();
+        runner
+            .run_file(directory.join("top.yaml"), |config, _| {
+                executions.push(config.name.clone());
+                Ok(ExperimentOutputs::new())
+            })
+            .unwrap();
+        assert_eq!(executions, vec!["shared", "left", "right", "top"]);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn cyclic_composition_is_rejected() {
+        let directory = experiment_directory(
+            "cyclic",
+            &[
+                (
+                    "first.yaml",
+                    "name: first\nsubExperiments:\n  - path: second.yaml\n",
+                ),
+                (
+                    "second.yaml",
+                    "name: second\nsubExperiments:\n  - path: first.yaml\n",
+                ),
+            ],
+        );
+        let mut runner = ExperimentRunner::new();
+        let result = runner.run_file(directory.join("first.yaml"), |_, _| {
+            Ok(ExperimentOutputs::new())
+        });
+        assert!(matches!(
+            result,
+            Err(SimulationError::CyclicExperimentError)
+        ));
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn result_cache_persists_across_runners() {
+        let directory = experiment_directory(
+            "result-cache",
+            &[(
+                "grid.yaml",
+                "name: grid\nparameters:\n  stations: 2.0\nseed: 11\nduration: 500.0\n",
+            )],
+        );
+        let result_cache = ResultCache::new(directory.join("results")).unwrap();
+        let mut executions = 0;
+        let mut executor = |_: &ExperimentConfig, parameters: &ExperimentOutputs| {
+            executions += 1;
+            let mut outputs = ExperimentOutputs::new();
+            outputs.insert(String::from("capacity"), parameters["stations"] * 10.0);
+            Ok(outputs)
+        };
+        let first = ExperimentRunner::new()
+            .with_result_cache(result_cache.clone())
+            .run_file(directory.join("grid.yaml"), &mut executor)
+            .unwrap();
+        let second = ExperimentRunner::new()
+            .with_result_cache(result_cache.clone())
+            .run_file(directory.join("grid.yaml"), &mut executor)
+            .unwrap();
+        assert_eq!(first, second);
+        // A changed seed is a cache miss
+        fs::write(
+            directory.join("grid.yaml"),
+            "name: grid\nparameters:\n  stations: 2.0\nseed: 12\nduration: 500.0\n",
+        )
+        .unwrap();
+        ExperimentRunner::new()
+            .with_result_cache(result_cache)
+            .run_file(directory.join("grid.yaml"), &mut executor)
+            .unwrap();
+        assert_eq!(executions, 2);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn replications_execute_with_distinct_seeds_and_overrides() {
+        let directory = experiment_directory(
+            "replications",
+            &[
+                ("service.yaml", "name: service\n"),
+                (
+                    "line.yaml",
+                    "
+name: line
+seed: 7
+subExperiments:
+  - path: service.yaml
+replications:
+  count: 4
+  overrides:
+    /trace: line-{replication}.json
+",
+                ),
+            ],
+        );
+        let mut config = ExperimentConfig::from_file(directory.join("line.yaml")).unwrap();
+        let mut executions: Vec<String> = Vec::new();
+        let results = ExperimentRunner::new()
+            .run_replications(&config, &directory, |config, _| {
+                executions.push(config.name.clone());
+                let mut outputs = ExperimentOutputs::new();
+                if let Some(seed) = config.seed {
+                    outputs.insert(String::from("seed"), seed as f64);
+                }
+                Ok(outputs)
+            })
+            .unwrap();
+        // The shared sub-experiment executes once
+        assert_eq!(executions, vec!["service", "line", "line", "line", "line"]);
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.seed)
+                .collect::<Vec<u64>>(),
+            (0..4)
+                .map(|replication| config.replication_seed(replication))
+                .collect::<Vec<u64>>()
+        );
+        assert!(results
+            .iter()
+            .all(|result| result.outputs["seed"] == result.seed as f64));
+        assert_eq!(results[3].overrides["/trace"], "line-3.json");
+        config.assertions = serde_yaml::from_str(
+            "- metric:\n    kpi:\n      name: replication\n  comparison: \"<\"\n  threshold: 2.0\n",
+        )
+        .unwrap();
+        let scenario = ExperimentRunner::new()
+            .run_scenario(&config, &directory, |config, _| {
+                // The replication index, from the replication trace override
+                let replication = config
+                    .spec
+                    .pointer("/trace")
+                    .and_then(Value::as_str)
+                    .and_then(|trace| trace.trim_start_matches("line-").split('.').next())
+                    .and_then(|replication| replication.parse::<f64>().ok())
+                    .unwrap_or_default();
+                let mut outputs = ExperimentOutputs::new();
+                outputs.insert(String::from("replication"), replication);
+                Ok(outputs)
+            })
+            .unwrap();
+        assert_eq!(scenario.name, "line");
+        assert_eq!(
+            scenario
+                .independent_sample("replication")
+                .unwrap()
+                .point_estimate_mean(),
+            1.5
+        );
+        // Assertions on KPIs are evaluated across the replications
+        assert!(scenario.assertions.passed());
+        assert_eq!(scenario.assertions.results[0].points, 4);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,57 @@
+//! The experiment module provides tooling for designing and executing
+//! simulation experiments - structured sets of simulation runs, across
+//! varying input parameters.  Experiments are described by the input
+//! parameters under study and an evaluation function, which maps a set of
+//! parameter values to one or more key performance indicators (KPIs).
+//! Typically, the evaluation function constructs a `Simulation` from the
+//! parameter values, executes it, and then summarizes the outputs.  Static
+//! models, without any event scheduling, are analyzed by Monte Carlo
+//! sampling instead.
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::errors::SimulationError;
+
+pub mod calibration;
+pub mod composition;
+pub mod monte_carlo;
+pub mod replication;
+pub mod result_cache;
+pub mod results;
+pub mod sensitivity;
+pub mod terminating;
+
+pub use self::calibration::{Calibration, CalibrationResult, CalibrationTrial, SearchStrategy};
+pub use self::composition::{ExperimentConfig, ExperimentOutputs, ExperimentRunner, SubExperiment};
+pub use self::monte_carlo::{MonteCarlo, MonteCarloInput, OutputExpression};
+pub use self::replication::{ReplicationPlan, ReplicationResult};
+pub use self::result_cache::ResultCache;
+pub use self::results::{ReplicationSet, RunResult, ScenarioResult};
+pub use self::sensitivity::{KpiSensitivity, SobolAnalysis, SobolIndices};
+pub use self::terminating::TerminatingExperiment;
+
+/// An input parameter under study in an experiment, with the range of
+/// values to be explored.  The range is inclusive of min, exclusive of max:
+/// [min, max)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Parameter {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Parameter {
+    pub fn new(name: String, min: f64, max: f64) -> Self {
+        Self { name, min, max }
+    }
+
+    /// Parameter ranges must be finite and non-empty, with min below max.
+    pub(crate) fn validate(&self) -> Result<(), SimulationError> {
+        if self.min.is_finite() && self.max.is_finite() && self.min < self.max {
+            Ok(())
+        } else {
+            Err(SimulationError::InvalidExperimentConfiguration)
+        }
+    }
+}
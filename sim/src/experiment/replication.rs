@@ -0,0 +1,195 @@
+//! Replication plans expand an experiment configuration into independent
+//! replications.  Replication `i` uses a seed mixed from the configured
+//! experiment seed and `i`, so the replications draw independent random
+//! number streams, and spec overrides give each
+//! replication its own values (e.g. a distinct trace output path).  The
+//! seed and overrides of each replication are recorded alongside its
+//! outputs, for traceability.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::composition::{ExperimentConfig, ExperimentOutputs};
+use crate::input_modeling::dynamic_rng::{self, DEFAULT_SEED};
+use crate::utils::errors::SimulationError;
+
+/// The replications of an experiment.  Overrides map JSON pointers into the
+/// experiment spec (e.g. `/trace/path`) to templates, where `{replication}`
+/// and `{seed}` are replaced with the replication index and seed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicationPlan {
+    pub count: usize,
+    #[serde(default)]
+    pub overrides: BTreeMap<String, String>,
+}
+
+/// The outputs of a single replication, with the replication-specific
+/// configuration values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicationResult {
+    pub replication: usize,
+    pub seed: u64,
+    pub overrides: BTreeMap<String, String>,
+    pub outputs: ExperimentOutputs,
+}
+
+/// Set the value at the JSON pointer, creating any missing objects along
+/// the way.
+fn set_pointer(target: &mut Value, pointer: &str, value: Value) -> Result<(), SimulationError> {
+    let tokens: Vec<String> = pointer
+        .strip_prefix('/')
+        .ok_or(SimulationError::InvalidExperimentConfiguration)?
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    let (last, parents) = tokens
+        .split_last()
+        .ok_or(SimulationError::InvalidExperimentConfiguration)?;
+    let mut current = target;
+    for token in parents {
+        if current.is_null() {
+            *current = Value::Object(serde_json::Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .ok_or(SimulationError::InvalidExperimentConfiguration)?
+            .entry(token.clone())
+            .or_insert(Value::Null);
+    }
+    if current.is_null() {
+        *current = Value::Object(serde_json::Map::new());
+    }
+    current
+        .as_object_mut()
+        .ok_or(SimulationError::InvalidExperimentConfiguration)?
+        .insert(last.clone(), value);
+    Ok(())
+}
+
+impl ExperimentConfig {
+    /// The number of replications, which is 1 without a replication plan.
+    pub fn replication_count(&self) -> usize {
+        self.replications
+            .as_ref()
+            .map_or(1, |replications| replications.count)
+    }
+
+    /// The seed of the replication, mixed from the experiment seed (or the
+    /// default seed) and the replication index.
+    pub fn replication_seed(&self, replication: usize) -> u64 {
+        dynamic_rng::replication_seed(self.seed.unwrap_or(DEFAULT_SEED), replication as u64)
+    }
+
+    /// The configuration of a single replication, with the replication seed
+    /// and spec overrides applied, and the rendered overrides.
+    pub fn replicate(
+        &self,
+        replication: usize,
+    ) -> Result<(ExperimentConfig, BTreeMap<String, String>), SimulationError> {
+        let seed = self.replication_seed(replication);
+        let mut config = self.clone();
+        config.seed = Some(seed);
+        config.replications = None;
+        let mut overrides = BTreeMap::new();
+        if let Some(replications) = &self.replications {
+            for (pointer, template) in &replications.overrides {
+                let value = template
+                    .replace("{replication}", &replication.to_string())
+                    .replace("{seed}", &seed.to_string());
+                set_pointer(&mut config.spec, pointer, Value::String(value.clone()))?;
+                overrides.insert(pointer.clone(), value);
+            }
+        }
+        Ok((config, overrides))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_modeling::ContinuousRandomVariable;
+    use crate::models::{Generator, Model};
+    use crate::simulator::Simulation;
+
+    #[test]
+    fn replications_render_seeds_and_overrides() {
+        let config: ExperimentConfig = serde_yaml::from_str(
+            "
+name: line
+seed: 100
+spec:
+  duration: 50.0
+replications:
+  count: 3
+  overrides:
+    /trace/path: traces/line-{replication}-{seed}.json
+",
+        )
+        .unwrap();
+        assert_eq!(config.replication_count(), 3);
+        let (replica, overrides) = config.replicate(2).unwrap();
+        let seed = config.replication_seed(2);
+        assert_eq!(replica.seed, Some(seed));
+        assert!(replica.replications.is_none());
+        let path = format!["traces/line-2-{}.json", seed];
+        assert_eq!(
+            replica.spec.pointer("/trace/path"),
+            Some(&Value::from(path.as_str()))
+        );
+        assert_eq!(replica.spec.pointer("/duration"), Some(&Value::from(50.0)));
+        assert_eq!(overrides["/trace/path"], path);
+        let invalid: ExperimentConfig = serde_yaml::from_str(
+            "
+name: line
+spec:
+  duration: 50.0
+replications:
+  count: 1
+  overrides:
+    /duration/path: unreachable
+",
+        )
+        .unwrap();
+        assert!(matches!(
+            invalid.replicate(0),
+            Err(SimulationError::InvalidExperimentConfiguration)
+        ));
+    }
+
+    #[test]
+    fn replications_draw_distinct_streams() {
+        let config: ExperimentConfig = serde_yaml::from_str(
+            "
+name: line
+seed: 100
+spec: {}
+replications:
+  count: 4
+",
+        )
+        .unwrap();
+        let arrivals = |seed: u64| {
+            let generator = Model::new(
+                String::from("generator-01"),
+                Box::new(Generator::new(
+                    ContinuousRandomVariable::Exp { lambda: 1.0 },
+                    None,
+                    String::from("job"),
+                    false,
+                    None,
+                )),
+            );
+            let mut simulation = Simulation::post_with_seed(vec![generator], Vec::new(), seed);
+            simulation.step_n(10).unwrap();
+            simulation.get_global_time()
+        };
+        let replications: Vec<f64> = (0..config.replication_count())
+            .map(|replication| arrivals(config.replicate(replication).unwrap().0.seed.unwrap()))
+            .collect();
+        assert!(replications.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+}
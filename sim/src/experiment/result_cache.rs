@@ -0,0 +1,145 @@
+//! The result cache stores experiment outputs in a directory, keyed by a
+//! hash of the experiment configuration, random number generator seed, and
+//! simulation duration.  Iterative analysis sessions over large experiment
+//! grids then only execute the experiments that changed since the previous
+//! session.  Keys are stable across processes and toolchains (FNV-1a over
+//! the canonical JSON form of the inputs), and each entry is a JSON file of
+//! experiment outputs, named by its key.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::composition::ExperimentOutputs;
+use crate::utils::errors::SimulationError;
+use crate::utils::fnv1a;
+
+#[derive(Serialize)]
+struct CacheInputs<'a, C: Serialize> {
+    config: &'a C,
+    seed: Option<u64>,
+    duration: Option<f64>,
+}
+
+/// A directory-based cache of experiment outputs.
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    directory: PathBuf,
+}
+
+impl ResultCache {
+    /// Open the result cache in the provided directory, creating the
+    /// directory if it does not exist.
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, SimulationError> {
+        fs::create_dir_all(directory.as_ref())?;
+        Ok(Self {
+            directory: directory.as_ref().to_path_buf(),
+        })
+    }
+
+    /// The cache key of an experiment.  Object fields are ordered in the
+    /// canonical form, so the key is independent of field declaration and
+    /// configuration file order.
+    pub fn key<C: Serialize>(
+        config: &C,
+        seed: Option<u64>,
+        duration: Option<f64>,
+    ) -> Result<String, SimulationError> {
+        let canonical = serde_json::to_value(CacheInputs {
+            config,
+            seed,
+            duration,
+        })?;
+        Ok(format!["{:016x}", fnv1a(canonical.to_string().as_bytes())])
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!["{}.json", key])
+    }
+
+    /// The stored outputs for the key, if any.
+    pub fn get(&self, key: &str) -> Result<Option<ExperimentOutputs>, SimulationError> {
+        let path = self.entry_path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    /// Store the outputs for the key, replacing any existing entry.
+    pub fn put(&self, key: &str, outputs: &ExperimentOutputs) -> Result<(), SimulationError> {
+        fs::write(self.entry_path(key), serde_json::to_string(outputs)?)?;
+        Ok(())
+    }
+
+    /// The stored outputs for the key, or otherwise the outputs of the
+    /// experiment execution, which are then stored.
+    pub fn get_or_run<F>(&self, key: &str, run: F) -> Result<ExperimentOutputs, SimulationError>
+    where
+        F: FnOnce() -> Result<ExperimentOutputs, SimulationError>,
+    {
+        if let Some(outputs) = self.get(key)? {
+            return Ok(outputs);
+        }
+        let outputs = run()?;
+        self.put(key, &outputs)?;
+        Ok(outputs)
+    }
+
+    /// Discard all stored outputs.
+    pub fn clear(&self) -> Result<(), SimulationError> {
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outputs_are_reused_until_inputs_change() {
+        let directory =
+            std::env::temp_dir().join(format!["sim-result-cache-{}", std::process::id()]);
+        let cache = ResultCache::new(&directory).unwrap();
+        let config = serde_json::json!({"arrivalRate": 0.5, "stations": 3});
+        let reordered: serde_json::Value =
+            serde_json::from_str(r#"{"stations": 3, "arrivalRate": 0.5}"#).unwrap();
+        let key = ResultCache::key(&config, Some(7), Some(100.0)).unwrap();
+        assert_eq!(
+            key,
+            ResultCache::key(&reordered, Some(7), Some(100.0)).unwrap()
+        );
+        assert_ne!(
+            key,
+            ResultCache::key(&config, Some(8), Some(100.0)).unwrap()
+        );
+        assert_ne!(
+            key,
+            ResultCache::key(&config, Some(7), Some(200.0)).unwrap()
+        );
+        let mut executions = 0;
+        let mut run = || {
+            executions += 1;
+            let mut outputs = ExperimentOutputs::new();
+            outputs.insert(String::from("utilization"), 0.5 / 3.0);
+            Ok(outputs)
+        };
+        let first = cache.get_or_run(&key, &mut run).unwrap();
+        let second = cache.get_or_run(&key, &mut run).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(executions, 1);
+        cache.clear().unwrap();
+        assert!(cache.get(&key).unwrap().is_none());
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
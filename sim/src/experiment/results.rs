@@ -0,0 +1,334 @@
+//! Experiment result types, shared by the experiment runner and the output
+//! analysis.  A `RunResult` bundles the outputs of a single simulation run -
+//! messages, model records, KPIs, KPI time series, the seed, and timing.  A
+//! `ReplicationSet` collects the independent replications of a run, and a
+//! `ScenarioResult` labels a replication set with the scenario name and
+//! parameter values.  Replication KPIs convert into an `IndependentSample`
+//! (one point per replication), and KPI time series convert into a
+//! `SteadyStateOutput` or `TerminatingSimulationOutput`, for analysis.
+
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+use serde::{Deserialize, Serialize};
+
+use super::composition::ExperimentOutputs;
+use super::replication::ReplicationResult;
+use crate::models::ModelRecord;
+use crate::output_analysis::{
+    ConfidenceInterval, IndependentSample, SteadyStateOutput, TerminatingSimulationOutput,
+};
+use crate::simulator::{
+    Assertion, AssertionMetric, AssertionReport, Message, RunManifest, Simulation,
+};
+use crate::utils::errors::SimulationError;
+
+/// The outputs of a single simulation run.  Records are keyed by model ID,
+/// and the wall time is in milliseconds.  The manifest, when available,
+/// traces the run back to its exact inputs and engine version.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunResult {
+    pub seed: Option<u64>,
+    pub global_time: f64,
+    pub wall_time: f64,
+    #[serde(default)]
+    pub kpis: ExperimentOutputs,
+    #[serde(default)]
+    pub time_series: BTreeMap<String, Vec<f64>>,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub records: BTreeMap<String, Vec<ModelRecord>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<RunManifest>,
+}
+
+impl RunResult {
+    pub fn new(seed: Option<u64>, kpis: ExperimentOutputs) -> Self {
+        Self {
+            seed,
+            kpis,
+            ..Self::default()
+        }
+    }
+
+    /// The result of an executed simulation, with the provided KPIs.  The
+    /// messages are the retained message history, when enabled, and the
+    /// records are those of every model.
+    pub fn from_simulation(
+        simulation: &Simulation,
+        kpis: ExperimentOutputs,
+    ) -> Result<Self, SimulationError> {
+        let manifest = simulation.manifest()?;
+        let records = simulation
+            .summary()
+            .models
+            .into_iter()
+            .map(|model| {
+                let records = simulation.get_records(&model.id)?.clone();
+                Ok((model.id, records))
+            })
+            .collect::<Result<_, SimulationError>>()?;
+        Ok(Self {
+            seed: manifest.rng_seed,
+            global_time: manifest.global_time,
+            wall_time: manifest.wall_time,
+            kpis,
+            time_series: BTreeMap::new(),
+            messages: simulation
+                .get_message_history()
+                .map(<[Message]>::to_vec)
+                .unwrap_or_default(),
+            records,
+            manifest: Some(manifest),
+        })
+    }
+
+    /// Add a named KPI time series, such as the per-job waiting times.
+    pub fn with_time_series(mut self, name: &str, time_series: Vec<f64>) -> Self {
+        self.time_series.insert(name.to_string(), time_series);
+        self
+    }
+
+    pub fn kpi(&self, name: &str) -> Result<f64, SimulationError> {
+        self.kpis
+            .get(name)
+            .copied()
+            .ok_or_else(|| SimulationError::UnknownOutput(name.to_string()))
+    }
+
+    pub fn series(&self, name: &str) -> Result<&[f64], SimulationError> {
+        self.time_series
+            .get(name)
+            .map(Vec::as_slice)
+            .ok_or_else(|| SimulationError::UnknownOutput(name.to_string()))
+    }
+
+    /// The KPI time series, for steady-state analysis of a single long run.
+    pub fn steady_state_output(
+        &self,
+        name: &str,
+    ) -> Result<SteadyStateOutput<f64>, SimulationError> {
+        Ok(SteadyStateOutput::post(self.series(name)?.to_vec()))
+    }
+}
+
+impl From<ReplicationResult> for RunResult {
+    fn from(replication: ReplicationResult) -> Self {
+        Self::new(Some(replication.seed), replication.outputs)
+    }
+}
+
+/// The independent replications of a simulation run, in replication order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicationSet {
+    pub runs: Vec<RunResult>,
+}
+
+impl ReplicationSet {
+    pub fn new(runs: Vec<RunResult>) -> Self {
+        Self { runs }
+    }
+
+    pub fn push(&mut self, run: RunResult) {
+        self.runs.push(run);
+    }
+
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    pub fn seeds(&self) -> Vec<Option<u64>> {
+        self.runs.iter().map(|run| run.seed).collect()
+    }
+
+    /// The values of a KPI, one per replication.
+    pub fn kpi_values(&self, name: &str) -> Result<Vec<f64>, SimulationError> {
+        self.runs.iter().map(|run| run.kpi(name)).collect()
+    }
+
+    /// The KPI across replications, as an IID sample.
+    pub fn independent_sample(
+        &self,
+        name: &str,
+    ) -> Result<IndependentSample<f64>, SimulationError> {
+        IndependentSample::post(self.kpi_values(name)?)
+    }
+
+    /// The KPI time series of every replication, for terminating simulation
+    /// analysis.
+    pub fn terminating_output(
+        &self,
+        name: &str,
+    ) -> Result<TerminatingSimulationOutput<f64>, SimulationError> {
+        let mut series = self.runs.iter().map(|run| run.series(name));
+        let mut output = TerminatingSimulationOutput::post(
+            series
+                .next()
+                .ok_or(SimulationError::EmptyReplicationSet)??
+                .to_vec(),
+        );
+        series.try_for_each(|time_series| {
+            output.put_time_series(time_series?.to_vec());
+            Ok::<(), SimulationError>(())
+        })?;
+        Ok(output)
+    }
+}
+
+impl FromIterator<RunResult> for ReplicationSet {
+    fn from_iter<I: IntoIterator<Item = RunResult>>(runs: I) -> Self {
+        Self::new(runs.into_iter().collect())
+    }
+}
+
+impl From<Vec<ReplicationResult>> for ReplicationSet {
+    fn from(replications: Vec<ReplicationResult>) -> Self {
+        replications.into_iter().map(RunResult::from).collect()
+    }
+}
+
+/// The replications of an experiment scenario - a named set of parameter
+/// values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioResult {
+    pub name: String,
+    #[serde(default)]
+    pub parameters: BTreeMap<String, f64>,
+    pub replications: ReplicationSet,
+    #[serde(default, skip_serializing_if = "AssertionReport::is_empty")]
+    pub assertions: AssertionReport,
+}
+
+impl ScenarioResult {
+    pub fn new(
+        name: String,
+        parameters: BTreeMap<String, f64>,
+        replications: ReplicationSet,
+    ) -> Self {
+        Self {
+            name,
+            parameters,
+            replications,
+            assertions: AssertionReport::default(),
+        }
+    }
+
+    /// Evaluate assertions on the KPIs, across the scenario replications.
+    /// Only KPI metrics are available for scenarios.
+    pub fn check_assertions(
+        &self,
+        assertions: &[Assertion],
+    ) -> Result<AssertionReport, SimulationError> {
+        let results = assertions
+            .iter()
+            .map(|assertion| match &assertion.metric {
+                AssertionMetric::Kpi { name } => {
+                    assertion.evaluate(self.replications.kpi_values(name)?)
+                }
+                _ => Err(SimulationError::InvalidExperimentConfiguration),
+            })
+            .collect::<Result<_, SimulationError>>()?;
+        Ok(AssertionReport { results })
+    }
+
+    /// The KPI across the scenario replications, as an IID sample.
+    pub fn independent_sample(
+        &self,
+        name: &str,
+    ) -> Result<IndependentSample<f64>, SimulationError> {
+        self.replications.independent_sample(name)
+    }
+
+    /// The confidence interval of the KPI mean, across the scenario
+    /// replications.
+    pub fn confidence_interval_mean(
+        &self,
+        name: &str,
+        alpha: f64,
+    ) -> Result<ConfidenceInterval<f64>, SimulationError> {
+        self.independent_sample(name)?
+            .confidence_interval_mean(alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(seed: u64, throughput: f64) -> RunResult {
+        RunResult::new(
+            Some(seed),
+            vec![("throughput".to_string(), throughput)]
+                .into_iter()
+                .collect(),
+        )
+        .with_time_series("waitingTime", vec![throughput, throughput + 1.0])
+    }
+
+    #[test]
+    fn replication_kpis_convert_to_analysis_types() {
+        let scenario = ScenarioResult::new(
+            "baseline".to_string(),
+            BTreeMap::new(),
+            vec![run(1, 2.0), run(2, 4.0), run(3, 6.0)]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(
+            scenario.replications.seeds(),
+            vec![Some(1), Some(2), Some(3)]
+        );
+        let sample = scenario.independent_sample("throughput").unwrap();
+        assert_eq!(sample.point_estimate_mean(), 4.0);
+        assert!((sample.variance() - 8.0 / 3.0).abs() < 1.0e-12);
+        let interval = scenario
+            .confidence_interval_mean("throughput", 0.05)
+            .unwrap();
+        assert!(interval.lower() < 4.0 && interval.upper() > 4.0);
+        assert!(matches!(
+            scenario.independent_sample("latency"),
+            Err(SimulationError::UnknownOutput(name)) if name == "latency"
+        ));
+        assert!(scenario
+            .replications
+            .terminating_output("waitingTime")
+            .is_ok());
+        assert!(matches!(
+            ReplicationSet::default().terminating_output("waitingTime"),
+            Err(SimulationError::EmptyReplicationSet)
+        ));
+        let mut steady_state = run(4, 0.0)
+            .with_time_series(
+                "queueLength",
+                (0..1000).map(|index| (index % 7) as f64).collect(),
+            )
+            .steady_state_output("queueLength")
+            .unwrap();
+        assert!((steady_state.point_estimate_mean().unwrap() - 3.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn replication_results_convert_to_run_results() {
+        let replications: ReplicationSet = vec![ReplicationResult {
+            replication: 0,
+            seed: 42,
+            overrides: BTreeMap::new(),
+            outputs: vec![("throughput".to_string(), 3.0)].into_iter().collect(),
+        }]
+        .into();
+        assert_eq!(replications.len(), 1);
+        assert_eq!(replications.runs[0].seed, Some(42));
+        assert_eq!(replications.kpi_values("throughput").unwrap(), vec![3.0]);
+        let serialized = serde_json::to_string(&replications).unwrap();
+        let deserialized: ReplicationSet = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.runs[0].kpis, replications.runs[0].kpis);
+    }
+}
@@ -0,0 +1,256 @@
+//! Variance-based (Sobol) sensitivity analysis apportions the variance of
+//! each KPI to the input parameters.  The first-order index of a parameter
+//! is the fraction of KPI variance explained by that parameter alone, and
+//! the total index additionally includes all interactions with other
+//! parameters.  Parameter values are sampled with the Saltelli scheme, which
+//! requires `base_samples * (parameters + 2)` evaluations.
+
+use serde::{Deserialize, Serialize};
+
+use super::Parameter;
+use crate::input_modeling::dynamic_rng::DynRng;
+use crate::input_modeling::ContinuousRandomVariable;
+use crate::utils::errors::SimulationError;
+
+/// The first-order and total Sobol indices of a single parameter, for a
+/// single KPI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SobolIndices {
+    pub parameter: String,
+    pub first_order: f64,
+    pub total: f64,
+}
+
+/// The Sobol indices of every parameter, for a single KPI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KpiSensitivity {
+    pub kpi: String,
+    pub variance: f64,
+    pub indices: Vec<SobolIndices>,
+}
+
+impl KpiSensitivity {
+    /// The parameter with the largest total index - the parameter driving
+    /// the most KPI variance.
+    pub fn dominant_parameter(&self) -> Option<&SobolIndices> {
+        self.indices
+            .iter()
+            .max_by(|a, b| a.total.total_cmp(&b.total))
+    }
+}
+
+/// A Sobol sensitivity analysis, declaring the parameters to perturb, the
+/// KPIs reported by each evaluation, and the number of base samples.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SobolAnalysis {
+    parameters: Vec<Parameter>,
+    kpis: Vec<String>,
+    base_samples: usize,
+}
+
+impl SobolAnalysis {
+    pub fn new(parameters: Vec<Parameter>, kpis: Vec<String>, base_samples: usize) -> Self {
+        Self {
+            parameters,
+            kpis,
+            base_samples,
+        }
+    }
+
+    fn sample_matrix(&self, rng: &DynRng) -> Result<Vec<Vec<f64>>, SimulationError> {
+        (0..self.base_samples)
+            .map(|_| {
+                self.parameters
+                    .iter()
+                    .map(|parameter| {
+                        ContinuousRandomVariable::Uniform {
+                            min: parameter.min,
+                            max: parameter.max,
+                        }
+                        .random_variate(rng.clone())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn evaluate_all<F>(
+        &self,
+        points: &[Vec<f64>],
+        evaluate: &mut F,
+    ) -> Result<Vec<Vec<f64>>, SimulationError>
+    where
+        F: FnMut(&[f64]) -> Result<Vec<f64>, SimulationError>,
+    {
+        points
+            .iter()
+            .map(|point| {
+                let outputs = evaluate(point)?;
+                if outputs.len() != self.kpis.len() {
+                    return Err(SimulationError::InvalidExperimentConfiguration);
+                }
+                Ok(outputs)
+            })
+            .collect()
+    }
+
+    /// Execute the analysis.  The evaluation function receives one value
+    /// per parameter (in declaration order), and must return one value per
+    /// KPI (in declaration order).
+    pub fn run<F>(
+        &self,
+        rng: DynRng,
+        mut evaluate: F,
+    ) -> Result<Vec<KpiSensitivity>, SimulationError>
+    where
+        F: FnMut(&[f64]) -> Result<Vec<f64>, SimulationError>,
+    {
+        if self.parameters.is_empty() || self.kpis.is_empty() || self.base_samples < 2 {
+            return Err(SimulationError::InvalidExperimentConfiguration);
+        }
+        self.parameters.iter().try_for_each(Parameter::validate)?;
+        let a = self.sample_matrix(&rng)?;
+        let b = self.sample_matrix(&rng)?;
+        let f_a = self.evaluate_all(&a, &mut evaluate)?;
+        let f_b = self.evaluate_all(&b, &mut evaluate)?;
+        // For each parameter, the A matrix with that parameter's column
+        // taken from the B matrix
+        let f_ab = (0..self.parameters.len())
+            .map(|parameter_index| {
+                let ab: Vec<Vec<f64>> = a
+                    .iter()
+                    .zip(b.iter())
+                    .map(|(a_row, b_row)| {
+                        let mut ab_row = a_row.clone();
+                        ab_row[parameter_index] = b_row[parameter_index];
+                        ab_row
+                    })
+                    .collect();
+                self.evaluate_all(&ab, &mut evaluate)
+            })
+            .collect::<Result<Vec<Vec<Vec<f64>>>, SimulationError>>()?;
+        let n = self.base_samples as f64;
+        Ok(self
+            .kpis
+            .iter()
+            .enumerate()
+            .map(|(kpi_index, kpi)| {
+                let outputs: Vec<f64> = f_a
+                    .iter()
+                    .chain(f_b.iter())
+                    .map(|output| output[kpi_index])
+                    .collect();
+                let mean = outputs.iter().sum::<f64>() / outputs.len() as f64;
+                let variance = outputs
+                    .iter()
+                    .map(|output| (output - mean).powi(2))
+                    .sum::<f64>()
+                    / outputs.len() as f64;
+                let indices = self
+                    .parameters
+                    .iter()
+                    .zip(f_ab.iter())
+                    .map(|(parameter, f_ab_i)| {
+                        // Saltelli (2010) first-order and Jansen (1999) total
+                        // effect estimators
+                        let (first_order_sum, total_sum) = (0..self.base_samples).fold(
+                            (0.0, 0.0),
+                            |(first_order_sum, total_sum), sample_index| {
+                                let y_a = f_a[sample_index][kpi_index];
+                                let y_b = f_b[sample_index][kpi_index];
+                                let y_ab = f_ab_i[sample_index][kpi_index];
+                                (
+                                    first_order_sum + y_b * (y_ab - y_a),
+                                    total_sum + (y_a - y_ab).powi(2),
+                                )
+                            },
+                        );
+                        let (first_order, total) = if variance > 0.0 {
+                            (
+                                first_order_sum / n / variance,
+                                total_sum / (2.0 * n) / variance,
+                            )
+                        } else {
+                            (0.0, 0.0)
+                        };
+                        SobolIndices {
+                            parameter: parameter.name.clone(),
+                            first_order,
+                            total,
+                        }
+                    })
+                    .collect();
+                KpiSensitivity {
+                    kpi: kpi.clone(),
+                    variance,
+                    indices,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_modeling::dynamic_rng::default_rng;
+
+    #[test]
+    fn additive_function_indices() {
+        // y = 4 x1 + x2, with x1 and x2 uniform on [0, 1) and an unused x3
+        // Var(y) = 16/12 + 1/12, so S1 = 16/17 and S2 = 1/17
+        let analysis = SobolAnalysis::new(
+            vec![
+                Parameter::new(String::from("x1"), 0.0, 1.0),
+                Parameter::new(String::from("x2"), 0.0, 1.0),
+                Parameter::new(String::from("x3"), 0.0, 1.0),
+            ],
+            vec![String::from("y")],
+            4000,
+        );
+        let sensitivities = analysis
+            .run(default_rng(), |x| Ok(vec![4.0 * x[0] + x[1]]))
+            .unwrap();
+        let indices = &sensitivities[0].indices;
+        assert!((indices[0].first_order - 16.0 / 17.0).abs() < 0.05);
+        assert!((indices[0].total - 16.0 / 17.0).abs() < 0.05);
+        assert!((indices[1].first_order - 1.0 / 17.0).abs() < 0.05);
+        assert!((indices[1].total - 1.0 / 17.0).abs() < 0.05);
+        assert!(indices[2].first_order.abs() < 0.05);
+        assert!(indices[2].total.abs() < 0.05);
+        assert_eq!(
+            sensitivities[0].dominant_parameter().unwrap().parameter,
+            "x1"
+        );
+    }
+
+    #[test]
+    fn mismatched_kpi_count_is_rejected() {
+        let analysis = SobolAnalysis::new(
+            vec![Parameter::new(String::from("x1"), 0.0, 1.0)],
+            vec![String::from("y1"), String::from("y2")],
+            10,
+        );
+        assert!(analysis.run(default_rng(), |x| Ok(vec![x[0]])).is_err());
+    }
+
+    #[test]
+    fn empty_parameter_ranges_are_rejected() {
+        [(1.0, 1.0), (1.0, 0.0), (0.0, f64::INFINITY)]
+            .iter()
+            .for_each(|(min, max)| {
+                let analysis = SobolAnalysis::new(
+                    vec![Parameter::new(String::from("x1"), *min, *max)],
+                    vec![String::from("y")],
+                    10,
+                );
+                assert!(matches!(
+                    analysis.run(default_rng(), |x| Ok(vec![x[0]])),
+                    Err(SimulationError::InvalidExperimentConfiguration)
+                ));
+            });
+    }
+}
@@ -0,0 +1,307 @@
+//! Terminating experiments run independent replications of a simulation
+//! over a finite horizon, and collect a metric from each replication into
+//! an `IndependentSample` - for confidence intervals on the metric mean.
+//! Every replication starts from the same initial simulation, so models
+//! are reset between replications.  Replication `i` uses a seed mixed
+//! from the base seed and `i`, following the replication plan convention,
+//! so the replications are independent and each replication is
+//! reproducible on its own.  Models pinned to an explicit seed keep their pinned streams.
+//! Antithetic replications pair each replication with its antithetic
+//! counterpart, and scenario comparisons run the alternative scenario with
+//! the same replication seeds - common random numbers.
+
+use crate::input_modeling::dynamic_rng::{self, DEFAULT_SEED};
+use crate::output_analysis::{
+    antithetic_sample, compare_scenarios, ConfidenceInterval, IndependentSample,
+    ReplicationController, ScenarioComparison, SequentialSample,
+};
+use crate::simulator::Simulation;
+use crate::utils::errors::SimulationError;
+
+/// Independent replications of a simulation, each run until the finite
+/// horizon.  The simulation should be in its initial state - the models
+/// and any injected or scheduled inputs are replicated as-is.  The global
+/// random number generator is replaced with the built-in generator for
+/// each replication, as user-supplied generators cannot be reseeded.
+#[derive(Clone)]
+pub struct TerminatingExperiment {
+    simulation: Simulation,
+    horizon: f64,
+    seed: u64,
+}
+
+impl TerminatingExperiment {
+    /// A terminating experiment, with the base seed of the simulation (or
+    /// the default seed, for user-supplied generators).
+    pub fn new(simulation: Simulation, horizon: f64) -> Self {
+        let seed = simulation.get_rng_seed().unwrap_or(DEFAULT_SEED);
+        Self {
+            simulation,
+            horizon,
+            seed,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn horizon(&self) -> f64 {
+        self.horizon
+    }
+
+    /// The seed of the replication, mixed from the base seed and the
+    /// replication index.
+    pub fn replication_seed(&self, replication: usize) -> u64 {
+        dynamic_rng::replication_seed(self.seed, replication as u64)
+    }
+
+    /// Run a single replication until the horizon, returning the executed
+    /// simulation for inspection.
+    pub fn run_replication(&self, replication: usize) -> Result<Simulation, SimulationError> {
+        let mut simulation = self.simulation.clone();
+        simulation.set_seed(self.replication_seed(replication));
+        simulation.step_until(self.horizon)?;
+        Ok(simulation)
+    }
+
+    /// Run the replications, collecting the metric of each executed
+    /// simulation (e.g. the average waiting time at a processor) into an
+    /// IID sample.
+    pub fn run<F>(
+        &self,
+        replications: usize,
+        mut metric: F,
+    ) -> Result<IndependentSample<f64>, SimulationError>
+    where
+        F: FnMut(&Simulation) -> Result<f64, SimulationError>,
+    {
+        let points = (0..replications)
+            .map(|replication| metric(&self.run_replication(replication)?))
+            .collect::<Result<Vec<f64>, SimulationError>>()?;
+        IndependentSample::post(points)
+    }
+
+    /// Run antithetic pairs of replications - each replication, and the
+    /// same replication with antithetic variates - collecting the pair
+    /// means of the metric into an IID sample.
+    pub fn run_antithetic<F>(
+        &self,
+        pairs: usize,
+        mut metric: F,
+    ) -> Result<IndependentSample<f64>, SimulationError>
+    where
+        F: FnMut(&Simulation) -> Result<f64, SimulationError>,
+    {
+        let mut original = Vec::with_capacity(pairs);
+        let mut antithetic = Vec::with_capacity(pairs);
+        for replication in 0..pairs {
+            original.push(metric(&self.run_replication(replication)?)?);
+            let mut simulation = self.simulation.clone();
+            simulation.set_seed(self.replication_seed(replication));
+            simulation.enable_antithetic_variates();
+            simulation.step_until(self.horizon)?;
+            antithetic.push(metric(&simulation)?);
+        }
+        antithetic_sample(&original, &antithetic)
+    }
+
+    /// Compare an alternative scenario against this experiment's simulation,
+    /// with common random numbers - replication `i` of both scenarios uses
+    /// the same seed, and the same horizon.  The comparison is a paired-t
+    /// confidence interval on the difference of the metric means,
+    /// alternative less baseline.  Per-model random number streams keep the
+    /// random numbers synchronized when the scenarios differ in structure.
+    pub fn compare_scenarios<F>(
+        &self,
+        alternative: &Simulation,
+        replications: usize,
+        mut metric: F,
+        alpha: f64,
+    ) -> Result<ScenarioComparison, SimulationError>
+    where
+        F: FnMut(&Simulation) -> Result<f64, SimulationError>,
+    {
+        let alternative = Self {
+            simulation: alternative.clone(),
+            horizon: self.horizon,
+            seed: self.seed,
+        };
+        let mut baseline_points = Vec::with_capacity(replications);
+        let mut alternative_points = Vec::with_capacity(replications);
+        for replication in 0..replications {
+            baseline_points.push(metric(&self.run_replication(replication)?)?);
+            alternative_points.push(metric(&alternative.run_replication(replication)?)?);
+        }
+        compare_scenarios(&baseline_points, &alternative_points, alpha)
+    }
+
+    /// Run the replications, and estimate the confidence interval of the
+    /// metric mean.
+    pub fn confidence_interval_mean<F>(
+        &self,
+        replications: usize,
+        metric: F,
+        alpha: f64,
+    ) -> Result<ConfidenceInterval<f64>, SimulationError>
+    where
+        F: FnMut(&Simulation) -> Result<f64, SimulationError>,
+    {
+        self.run(replications, metric)?
+            .confidence_interval_mean(alpha)
+    }
+
+    /// Add replications until the half width of the confidence interval of
+    /// the metric mean is at or below the target, within the replication
+    /// limits of the controller.
+    pub fn run_until_half_width<F>(
+        &self,
+        controller: &ReplicationController,
+        metric: F,
+        target_half_width: f64,
+        alpha: f64,
+    ) -> Result<SequentialSample, SimulationError>
+    where
+        F: FnMut(&Simulation) -> Result<f64, SimulationError>,
+    {
+        controller.run_until_half_width(
+            |replication| self.run_replication(replication),
+            metric,
+            target_half_width,
+            alpha,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_modeling::ContinuousRandomVariable;
+    use crate::models::{Generator, Model, Processor, Sink};
+    use crate::simulator::{topology, RngStreams};
+
+    fn line() -> Simulation {
+        let models = vec![
+            Model::new(
+                String::from("generator-01"),
+                Box::new(Generator::new(
+                    ContinuousRandomVariable::Exp { lambda: 1.0 },
+                    None,
+                    String::from("job"),
+                    false,
+                    None,
+                )),
+            ),
+            Model::new(
+                String::from("processor-01"),
+                Box::new(Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: 2.0 },
+                    None,
+                    String::from("job"),
+                    String::from("processed"),
+                    false,
+                    None,
+                )),
+            ),
+            Model::new(
+                String::from("sink-01"),
+                Box::new(Sink::new(String::from("processed"), 10.0, false)),
+            ),
+        ];
+        let mut connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+        connectors.extend(topology::pipeline(
+            &["processor-01", "sink-01"],
+            "processed",
+            "processed",
+        ));
+        Simulation::post(models, connectors)
+    }
+
+    fn completions(simulation: &Simulation) -> Result<f64, SimulationError> {
+        Ok(simulation
+            .get_sink_summary("sink-01")?
+            .ok_or(SimulationError::InvalidModelState)?
+            .count as f64)
+    }
+
+    #[test]
+    fn replications_are_independent_and_reproducible() {
+        let experiment = TerminatingExperiment::new(line(), 100.0).with_seed(7);
+        let sample = experiment.run(10, completions).unwrap();
+        assert_eq!(sample.points().len(), 10);
+        // The replications start from the initial models, and vary only by
+        // their seeds
+        assert!(sample.points().iter().all(|count| *count > 50.0));
+        assert!(sample.variance() > 0.0);
+        assert_eq!(
+            experiment.run(10, completions).unwrap().points(),
+            sample.points()
+        );
+        let replication = experiment.run_replication(3).unwrap();
+        assert_eq!(
+            replication.get_rng_seed(),
+            Some(experiment.replication_seed(3))
+        );
+        assert_eq!(completions(&replication).unwrap(), sample.points()[3]);
+        // Completions are bounded by the arrival rate over the horizon
+        let interval = experiment
+            .confidence_interval_mean(10, completions, 0.05)
+            .unwrap();
+        assert!(interval.lower() < 100.0 && interval.upper() > 70.0);
+        // Sequential sampling replicates the same seeds, in order
+        let sequential = experiment
+            .run_until_half_width(&ReplicationController::default(), completions, 5.0, 0.05)
+            .unwrap();
+        assert!(sequential.target_reached && sequential.half_width() <= 5.0);
+        let replicated = sequential.replications().min(10);
+        assert_eq!(
+            sequential.sample.points()[..replicated],
+            sample.points()[..replicated]
+        );
+    }
+
+    #[test]
+    fn consecutive_replications_differ() {
+        let experiment = TerminatingExperiment::new(line(), 100.0).with_seed(7);
+        let sample = experiment.run(6, busy_time).unwrap();
+        assert!(sample.points().windows(2).all(|pair| pair[0] != pair[1]));
+        let pairs = experiment.run_antithetic(6, busy_time).unwrap();
+        assert!(pairs.points().windows(2).all(|pair| pair[0] != pair[1]));
+        assert_ne!(
+            experiment.replication_seed(0),
+            experiment.replication_seed(1)
+        );
+    }
+
+    fn busy_time(simulation: &Simulation) -> Result<f64, SimulationError> {
+        Ok(simulation
+            .get_utilization("processor-01")?
+            .ok_or(SimulationError::InvalidModelState)?
+            .busy_time)
+    }
+
+    #[test]
+    fn antithetic_pairs_and_scenario_comparisons_correlate_replications() {
+        let experiment = TerminatingExperiment::new(line(), 100.0).with_seed(7);
+        let pairs = experiment.run_antithetic(10, busy_time).unwrap();
+        assert_eq!(pairs.points().len(), 10);
+        // Each pair mean includes the original replication
+        let original = busy_time(&experiment.run_replication(0).unwrap()).unwrap();
+        assert_ne!(pairs.points()[0], original);
+        // A faster processor, under common random numbers with per-model
+        // streams, is significantly less busy
+        let mut baseline = line();
+        baseline.set_rng_streams(RngStreams::PerModel);
+        let mut alternative = baseline.clone();
+        alternative
+            .set_parameter("processor-01", "/serviceTime/exp/lambda", 4.0.into())
+            .unwrap();
+        let comparison = TerminatingExperiment::new(baseline, 100.0)
+            .compare_scenarios(&alternative, 10, busy_time, 0.05)
+            .unwrap();
+        assert!(comparison.significant());
+        assert!(comparison.mean_difference() < 0.0);
+        assert!(comparison.correlation() > 0.5);
+    }
+}
@@ -0,0 +1,359 @@
+//! The import module builds simulations from tabular configurations, as
+//! planners often maintain topologies in spreadsheets.  `from_csv` reads
+//! two CSV tables - one row per model, and one row per connector.
+//!
+//! Column headers are configuration field names, with dot-separated paths
+//! for nested fields (e.g. `portsIn.job`, or
+//! `messageInterdepartureTime.exp.lambda`).  A header may be suffixed with
+//! a column type - `:string`, `:number`, `:integer`, `:boolean`, or
+//! `:json` - and otherwise cell types are inferred (numbers, `true` and
+//! `false`, or strings).  Empty cells are omitted, so a models table may
+//! mix model types with different fields - although the mappings
+//! containing an empty cell are kept (e.g. an empty `portsIn.job` cell
+//! configures an empty `portsIn` mapping).  For example:
+//!
+//! ```text
+//! id,type,portsIn.job,portsOut.job,messageInterdepartureTime.exp.lambda,window
+//! generator-01,Generator,,job,0.5,
+//! sink-01,Sink,job,,,10.0
+//! ```
+//!
+//! Errors are reported per row, with spreadsheet row numbers (the header
+//! being row 1), and all the invalid rows of both tables are reported
+//! together.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use crate::models::Model;
+use crate::simulator::routing::{is_pattern, matches};
+use crate::simulator::{Connector, Simulation};
+use crate::utils::errors::SimulationError;
+
+/// An invalid row of an imported table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvRowError {
+    pub table: String,
+    pub row: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for CsvRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} row {}: {}", self.table, self.row, self.reason)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Inferred,
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Json,
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    path: Vec<String>,
+    column_type: ColumnType,
+}
+
+impl Column {
+    fn parse(header: &str) -> Result<Self, String> {
+        let (path, column_type) = match header.trim().rsplit_once(':') {
+            Some((path, column_type)) => (
+                path,
+                match column_type {
+                    "string" => ColumnType::String,
+                    "number" => ColumnType::Number,
+                    "integer" => ColumnType::Integer,
+                    "boolean" => ColumnType::Boolean,
+                    "json" => ColumnType::Json,
+                    _ => return Err(format!["unknown column type `{}`", column_type]),
+                },
+            ),
+            None => (header.trim(), ColumnType::Inferred),
+        };
+        let path: Vec<String> = path.split('.').map(str::to_string).collect();
+        if path.iter().any(String::is_empty) {
+            return Err(format!["invalid column header `{}`", header]);
+        }
+        Ok(Self { path, column_type })
+    }
+
+    fn value(&self, cell: &str) -> Result<Value, String> {
+        let invalid = |expected: &str| {
+            format![
+                "column `{}` expects {}, but found `{}`",
+                self.path.join("."),
+                expected,
+                cell
+            ]
+        };
+        match self.column_type {
+            ColumnType::Inferred => Ok(if let Ok(boolean) = cell.parse::<bool>() {
+                Value::from(boolean)
+            } else if let Ok(integer) = cell.parse::<i64>() {
+                Value::from(integer)
+            } else if let Ok(number) = cell.parse::<f64>() {
+                Value::from(number)
+            } else {
+                Value::from(cell)
+            }),
+            ColumnType::String => Ok(Value::from(cell)),
+            ColumnType::Number => cell
+                .parse::<f64>()
+                .map(Value::from)
+                .map_err(|_| invalid("a number")),
+            ColumnType::Integer => cell
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| invalid("an integer")),
+            ColumnType::Boolean => cell
+                .parse::<bool>()
+                .map(Value::from)
+                .map_err(|_| invalid("true or false")),
+            ColumnType::Json => serde_json::from_str::<serde_json::Value>(cell)
+                .map_err(|_| invalid("JSON"))
+                .and_then(|json| serde_yaml::to_value(json).map_err(|error| error.to_string())),
+        }
+    }
+}
+
+// A CSV record, with the row number of the record
+type Record = (usize, Vec<String>);
+
+/// The records of a CSV table.  Quoted fields may contain commas, escaped
+/// quotes (`""`), and line breaks.
+fn parse_records(table: &str) -> Result<Vec<Record>, (usize, String)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut row = 1;
+    let mut record_row = 1;
+    let mut characters = table.chars().peekable();
+    while let Some(character) = characters.next() {
+        match (quoted, character) {
+            (true, '"') if characters.peek() == Some(&'"') => {
+                characters.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, '\n') => {
+                row += 1;
+                field.push('\n');
+            }
+            (true, _) => field.push(character),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if characters.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_row, std::mem::take(&mut record)));
+                row += 1;
+                record_row = row;
+            }
+            (false, _) => field.push(character),
+        }
+    }
+    if quoted {
+        return Err((record_row, String::from("unterminated quoted field")));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_row, record));
+    }
+    // Blank lines are ignored
+    records.retain(|(_, record)| !(record.len() == 1 && record[0].trim().is_empty()));
+    Ok(records)
+}
+
+/// Read a CSV table into configuration values, one per row, collecting
+/// the errors of invalid rows.
+fn read_table<T: DeserializeOwned>(
+    table_name: &str,
+    table: &str,
+    errors: &mut Vec<CsvRowError>,
+) -> Vec<(usize, T)> {
+    let row_error = |row: usize, reason: String| CsvRowError {
+        table: table_name.to_string(),
+        row,
+        reason,
+    };
+    let mut records = match parse_records(table) {
+        Ok(records) => records.into_iter(),
+        Err((row, reason)) => {
+            errors.push(row_error(row, reason));
+            return Vec::new();
+        }
+    };
+    let columns: Vec<Column> = match records.next() {
+        Some((row, headers)) => {
+            match headers
+                .iter()
+                .map(|header| Column::parse(header))
+                .collect::<Result<_, _>>()
+            {
+                Ok(columns) => columns,
+                Err(reason) => {
+                    errors.push(row_error(row, reason));
+                    return Vec::new();
+                }
+            }
+        }
+        None => return Vec::new(),
+    };
+    records
+        .filter_map(|(row, record)| {
+            if record.len() != columns.len() {
+                errors.push(row_error(
+                    row,
+                    format![
+                        "expected {} fields, but found {}",
+                        columns.len(),
+                        record.len()
+                    ],
+                ));
+                return None;
+            }
+            let config = columns.iter().zip(record.iter()).try_fold(
+                Value::Mapping(Mapping::new()),
+                |mut config, (column, cell)| {
+                    let value = match cell.trim() {
+                        "" => None,
+                        cell => Some(column.value(cell)?),
+                    };
+                    insert(&mut config, &column.path, value)?;
+                    Ok(config)
+                },
+            );
+            match config.and_then(|config| {
+                serde_yaml::from_value::<T>(config).map_err(|error| error.to_string())
+            }) {
+                Ok(value) => Some((row, value)),
+                Err(reason) => {
+                    errors.push(row_error(row, reason));
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Insert a cell value into a row configuration, at the column path.  The
+/// mappings containing the value are created, even for an empty cell.
+fn insert(config: &mut Value, path: &[String], value: Option<Value>) -> Result<(), String> {
+    let mapping = match config {
+        Value::Mapping(mapping) => mapping,
+        _ => return Err(format!["field `{}` is not a mapping", path[0]]),
+    };
+    let key = Value::from(path[0].as_str());
+    if path.len() == 1 {
+        if let Some(value) = value {
+            mapping.insert(key, value);
+        }
+        return Ok(());
+    }
+    let nested = mapping
+        .entry(key)
+        .or_insert_with(|| Value::Mapping(Mapping::new()));
+    insert(nested, &path[1..], value)
+}
+
+/// Build a simulation from a models table and a connectors table.  Model
+/// IDs must be unique, and connectors must reference models of the models
+/// table.  All invalid rows are reported together, through
+/// `SimulationError::CsvImportError`.
+pub fn from_csv(models_csv: &str, connectors_csv: &str) -> Result<Simulation, SimulationError> {
+    let mut errors = Vec::new();
+    let models: Vec<(usize, Model)> = read_table("models", models_csv, &mut errors);
+    let connectors: Vec<(usize, Connector)> = read_table("connectors", connectors_csv, &mut errors);
+    let mut model_ids = HashSet::new();
+    models.iter().for_each(|(row, model)| {
+        if !model_ids.insert(model.id()) {
+            errors.push(CsvRowError {
+                table: String::from("models"),
+                row: *row,
+                reason: format!["duplicate model ID `{}`", model.id()],
+            });
+        }
+    });
+    connectors.iter().for_each(|(row, connector)| {
+        [connector.source_id(), connector.target_id()]
+            .iter()
+            .filter(|model_id| {
+                if is_pattern(model_id) {
+                    !model_ids.iter().any(|id| matches(model_id, id))
+                } else {
+                    !model_ids.contains(*model_id)
+                }
+            })
+            .for_each(|model_id| {
+                errors.push(CsvRowError {
+                    table: String::from("connectors"),
+                    row: *row,
+                    reason: format!["unknown model ID `{}`", model_id],
+                })
+            });
+    });
+    if !errors.is_empty() {
+        return Err(SimulationError::CsvImportError(errors));
+    }
+    Ok(Simulation::post(
+        models.into_iter().map(|(_, model)| model).collect(),
+        connectors
+            .into_iter()
+            .map(|(_, connector)| connector)
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_and_row_numbers() {
+        let records = parse_records("a,b\n\"x, \"\"y\"\"\",\"multi\nline\"\n\nc,d\n").unwrap();
+        assert_eq!(
+            records,
+            [
+                (1, vec![String::from("a"), String::from("b")]),
+                (
+                    2,
+                    vec![String::from("x, \"y\""), String::from("multi\nline")]
+                ),
+                (5, vec![String::from("c"), String::from("d")]),
+            ]
+        );
+        assert_eq!(parse_records("a\n\"open\n").unwrap_err().0, 2);
+    }
+
+    #[test]
+    fn typed_columns() {
+        let column = Column::parse("capacity:integer").unwrap();
+        assert_eq!(column.path, ["capacity"]);
+        assert_eq!(column.value("3").unwrap(), Value::from(3));
+        assert!(column.value("3.5").is_err());
+        assert_eq!(
+            Column::parse("portsIn.job:string")
+                .unwrap()
+                .value("001")
+                .unwrap(),
+            Value::from("001")
+        );
+        assert_eq!(
+            Column::parse("window").unwrap().value("1.5").unwrap(),
+            Value::from(1.5)
+        );
+        assert!(Column::parse("window:decimal").is_err());
+    }
+}
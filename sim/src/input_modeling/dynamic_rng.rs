@@ -1,17 +1,113 @@
-use std::{cell::RefCell, rc::Rc};
+use std::any::Any;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
-pub trait SimulationRng: std::fmt::Debug + rand_core::RngCore {}
-impl<T: std::fmt::Debug + rand_core::RngCore> SimulationRng for T {}
-pub type DynRng = Rc<RefCell<dyn SimulationRng>>;
+use rand_core::SeedableRng;
+
+use crate::utils::fnv1a;
+
+/// Simulation random number generators are `Send`, and shared behind a
+/// mutex, so simulations remain thread-safe.  Any `RngCore` implementation
+/// may be supplied - a cryptographic generator, or a generator replaying a
+/// recorded stream of random numbers, for example.
+pub trait SimulationRng: rand_core::RngCore + Send {
+    /// The generator, for recognizing the built-in generator type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: rand_core::RngCore + Send + 'static> SimulationRng for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The built-in random number generator type.
+pub type BuiltinRng = rand_pcg::Pcg64Mcg;
+pub type DynRng = Arc<Mutex<dyn SimulationRng>>;
+
+// User-supplied generators are not required to implement `Debug`
+impl fmt::Debug for dyn SimulationRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SimulationRng")
+    }
+}
+
+/// The seed of the random number generator used when a simulation is not
+/// supplied with a random number generator.
+pub const DEFAULT_SEED: u64 = 42;
 
 pub(crate) fn default_rng() -> DynRng {
-    Rc::new(RefCell::new(rand_pcg::Pcg64Mcg::new(42)))
+    Arc::new(Mutex::new(BuiltinRng::new(u128::from(DEFAULT_SEED))))
+}
+
+/// The built-in random number generator, seeded from a `u64` seed.  Seeds
+/// are mixed through `SeedableRng::seed_from_u64`, as the generator's own
+/// constructor discards the low bit of the state, so adjacent seeds would
+/// otherwise produce identical streams.  The default seed keeps the stream
+/// of the default generator, so unseeded simulations replay with their
+/// reported seed.
+pub(crate) fn seeded_rng(seed: u64) -> DynRng {
+    if seed == DEFAULT_SEED {
+        return default_rng();
+    }
+    Arc::new(Mutex::new(BuiltinRng::seed_from_u64(seed)))
+}
+
+/// The current state of a built-in random number generator, or `None` for
+/// a user-supplied generator, whose state is opaque.
+pub(crate) fn builtin_rng_state(rng: &DynRng) -> Option<BuiltinRng> {
+    lock_rng(rng).as_any().downcast_ref::<BuiltinRng>().cloned()
+}
+
+/// The seed of a replication, derived from a base seed - the output of a
+/// SplitMix64 generator, seeded with the base seed, for the replication.
+/// Unlike consecutive seeds, the derived seeds share no structure, so the
+/// replications draw statistically independent streams.
+pub fn replication_seed(seed: u64, replication: u64) -> u64 {
+    let mut z = seed.wrapping_add(
+        replication
+            .wrapping_add(1)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15),
+    );
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
 
 pub fn dyn_rng<Rng: SimulationRng + 'static>(rng: Rng) -> DynRng {
-    Rc::new(RefCell::new(rng))
+    Arc::new(Mutex::new(rng))
+}
+
+/// A random number generator of a user-supplied type, seeded from a `u64`
+/// seed through `SeedableRng::seed_from_u64`.
+pub fn seeded_dyn_rng<Rng: SimulationRng + SeedableRng + 'static>(seed: u64) -> DynRng {
+    dyn_rng(Rng::seed_from_u64(seed))
 }
 
 pub fn some_dyn_rng<Rng: SimulationRng + 'static>(rng: Rng) -> Option<DynRng> {
     Some(dyn_rng(rng))
 }
+
+/// Lock a random number generator, for drawing random numbers.  The state
+/// of a generator remains valid after a panic elsewhere, so a poisoned lock
+/// is recovered.
+pub fn lock_rng(rng: &DynRng) -> MutexGuard<'_, dyn SimulationRng + 'static> {
+    rng.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The random number generator for a single draw from a named stream.
+/// Models drawing from the same stream, with the same seed, receive
+/// identical random number generators at each stream position - so their
+/// variates share the same underlying uniforms, even when the models
+/// transform the uniforms through different distributions.  This provides
+/// declarative correlation across models (e.g. correlated arrival
+/// streams), independent of how many uniforms each distribution consumes.
+pub fn stream_rng(name: &str, seed: u64, position: u64) -> DynRng {
+    let key = [
+        name.as_bytes(),
+        &seed.to_le_bytes()[..],
+        &position.to_le_bytes()[..],
+    ]
+    .concat();
+    dyn_rng(BuiltinRng::seed_from_u64(fnv1a(&key)))
+}
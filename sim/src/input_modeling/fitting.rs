@@ -0,0 +1,418 @@
+//! Distribution fitting bridges observed data and model configuration.
+//! Given observed interarrival times, the fitting report ranks candidate
+//! distributions - each with estimated parameters - by the Kolmogorov-Smirnov
+//! distance between the fitted and empirical distribution functions.  The
+//! observation period is also segmented, to detect a non-stationary arrival
+//! rate, in which case a schedule thinning function is suggested.  The report
+//! renders as a ready-to-paste `Generator` configuration.
+
+use serde::{Deserialize, Serialize};
+
+use super::random_variable::{ln_gamma, Continuous};
+use super::thinning::Thinning;
+use crate::models::{Generator, Model};
+use crate::utils::errors::SimulationError;
+
+// The standard normal quantile of the 5% significance level, for the
+// one-sided rate dispersion test
+const DISPERSION_Z: f64 = 1.644_853_626_951_472_2;
+const MAX_GAMMA_ITERATIONS: usize = 500;
+
+/// A candidate distribution, with parameters estimated from the
+/// observations, and its Kolmogorov-Smirnov distance from the
+/// observations.  Smaller distances are better fits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateFit {
+    pub distribution: Continuous,
+    pub ks_statistic: f64,
+}
+
+/// The arrival rate of a segment of the observation period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateSegment {
+    pub start: f64,
+    pub end: f64,
+    pub arrivals: usize,
+    pub rate: f64,
+}
+
+/// The fitting report of observed interarrival times.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FitReport {
+    candidates: Vec<CandidateFit>,
+    segments: Vec<RateSegment>,
+    dispersion_statistic: f64,
+    dispersion_critical_value: f64,
+}
+
+impl FitReport {
+    /// The candidate fits, best first.
+    pub fn candidates(&self) -> &[CandidateFit] {
+        &self.candidates
+    }
+
+    pub fn best(&self) -> &CandidateFit {
+        &self.candidates[0]
+    }
+
+    pub fn segments(&self) -> &[RateSegment] {
+        &self.segments
+    }
+
+    /// Whether the arrival rate varies across the segments more than
+    /// chance allows - by a chi-square dispersion test of the segment
+    /// arrival counts, at the 5% significance level.
+    pub fn nonstationary(&self) -> bool {
+        self.dispersion_statistic > self.dispersion_critical_value
+    }
+
+    /// The suggested thinning function for non-stationary arrivals - a
+    /// piecewise-constant schedule of the segment rates, normalized by the
+    /// peak rate.
+    pub fn thinning(&self) -> Option<Thinning> {
+        if !self.nonstationary() {
+            return None;
+        }
+        let peak_rate = self.peak_rate();
+        Some(Thinning::schedule(
+            self.segments
+                .iter()
+                .map(|segment| (segment.start, segment.rate / peak_rate))
+                .collect(),
+            false,
+            None,
+        ))
+    }
+
+    fn peak_rate(&self) -> f64 {
+        self.segments
+            .iter()
+            .map(|segment| segment.rate)
+            .fold(0.0, f64::max)
+    }
+
+    /// The suggested interdeparture time distribution.  Thinned arrivals
+    /// are modeled as a non-homogeneous Poisson process, with exponential
+    /// interdeparture times at the peak rate.  Otherwise, the best fit is
+    /// suggested.
+    pub fn interdeparture_time(&self) -> Continuous {
+        if self.nonstationary() {
+            Continuous::Exp {
+                lambda: self.peak_rate(),
+            }
+        } else {
+            self.best().distribution.clone()
+        }
+    }
+
+    /// The suggested generator configuration.
+    pub fn generator(&self, id: &str, job_port: &str) -> Model {
+        Model::new(
+            id.to_string(),
+            Box::new(Generator::new(
+                self.interdeparture_time(),
+                self.thinning(),
+                job_port.to_string(),
+                false,
+                None,
+            )),
+        )
+    }
+
+    /// The suggested generator configuration as YAML, without any model
+    /// state, ready to paste into a simulation configuration.
+    pub fn generator_yaml(&self, id: &str, job_port: &str) -> Result<String, SimulationError> {
+        let mut config = serde_json::to_value(self.generator(id, job_port))?;
+        if let Some(fields) = config.as_object_mut() {
+            fields.remove("state");
+        }
+        Ok(serde_yaml::to_string(&config)?)
+    }
+}
+
+/// Fit candidate distributions to observed interarrival times, and segment
+/// the observation period into `segments` equal durations, for the
+/// non-stationarity check.  Observations must be non-negative and finite.
+pub fn fit_interarrivals(
+    interarrivals: &[f64],
+    segments: usize,
+) -> Result<FitReport, SimulationError> {
+    if let Some(invalid) = interarrivals
+        .iter()
+        .find(|observation| !observation.is_finite() || **observation < 0.0)
+    {
+        return Err(SimulationError::InvalidObservation(*invalid));
+    }
+    if interarrivals.is_empty() {
+        return Err(SimulationError::EmptySample);
+    }
+    let mut sorted = interarrivals.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mut candidates: Vec<CandidateFit> = candidate_distributions(&sorted)
+        .into_iter()
+        .map(|distribution| CandidateFit {
+            ks_statistic: ks_statistic(&sorted, |point| cdf(&distribution, point)),
+            distribution,
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.ks_statistic.total_cmp(&b.ks_statistic));
+    let segments = rate_segments(interarrivals, segments.max(1));
+    let (dispersion_statistic, dispersion_critical_value) = dispersion_test(&segments);
+    Ok(FitReport {
+        candidates,
+        segments,
+        dispersion_statistic,
+        dispersion_critical_value,
+    })
+}
+
+/// The candidate distributions, by maximum likelihood where available, and
+/// by the method of moments otherwise.  Observations without any spread
+/// only fit a constant.
+fn candidate_distributions(sorted: &[f64]) -> Vec<Continuous> {
+    let count = sorted.len() as f64;
+    let mean = sorted.iter().sum::<f64>() / count;
+    let variance = sorted
+        .iter()
+        .map(|point| (point - mean).powi(2))
+        .sum::<f64>()
+        / (count - 1.0).max(1.0);
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+    if variance <= 0.0 {
+        return vec![Continuous::Constant { value: mean }];
+    }
+    let mut candidates = vec![
+        Continuous::Exp { lambda: 1.0 / mean },
+        Continuous::Gamma {
+            shape: mean * mean / variance,
+            scale: variance / mean,
+        },
+        Continuous::Uniform { min, max },
+    ];
+    if min > 0.0 {
+        let logs: Vec<f64> = sorted.iter().map(|point| point.ln()).collect();
+        let mu = logs.iter().sum::<f64>() / count;
+        let sigma = (logs.iter().map(|log| (log - mu).powi(2)).sum::<f64>() / count).sqrt();
+        if sigma > 0.0 {
+            candidates.push(Continuous::LogNormal { mu, sigma });
+        }
+    }
+    candidates
+}
+
+/// The cumulative distribution function of a candidate distribution.
+fn cdf(distribution: &Continuous, point: f64) -> f64 {
+    match distribution {
+        Continuous::Exp { lambda } => (1.0 - (-lambda * point).exp()).max(0.0),
+        Continuous::Gamma { shape, scale } => regularized_lower_gamma(*shape, point / scale),
+        Continuous::Uniform { min, max } => ((point - min) / (max - min)).clamp(0.0, 1.0),
+        Continuous::LogNormal { mu, sigma } if point > 0.0 => {
+            standard_normal_cdf((point.ln() - mu) / sigma)
+        }
+        Continuous::Constant { value } if point >= *value => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// The Kolmogorov-Smirnov statistic - the largest distance between the
+/// empirical distribution function of the sorted points and the provided
+/// distribution function.
+fn ks_statistic<F: Fn(f64) -> f64>(sorted: &[f64], cdf: F) -> f64 {
+    let count = sorted.len() as f64;
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(index, point)| {
+            let probability = cdf(*point);
+            (probability - index as f64 / count).max((index as f64 + 1.0) / count - probability)
+        })
+        .fold(0.0, f64::max)
+}
+
+/// The standard normal distribution function, from the Abramowitz and
+/// Stegun approximation (7.1.26) of the error function.
+pub(crate) fn standard_normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let polynomial = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - polynomial * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+/// The regularized lower incomplete gamma function, by its series below
+/// `shape + 1`, and by its continued fraction (modified Lentz) otherwise.
+fn regularized_lower_gamma(shape: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let ln_prefactor = shape * x.ln() - x - ln_gamma(shape);
+    if x < shape + 1.0 {
+        let mut term = 1.0 / shape;
+        let mut sum = term;
+        for iteration in 1..MAX_GAMMA_ITERATIONS {
+            term *= x / (shape + iteration as f64);
+            sum += term;
+            if term.abs() < sum.abs() * f64::EPSILON {
+                break;
+            }
+        }
+        (sum * ln_prefactor.exp()).min(1.0)
+    } else {
+        let tiny = f64::MIN_POSITIVE / f64::EPSILON;
+        let mut b = x + 1.0 - shape;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut fraction = d;
+        for iteration in 1..MAX_GAMMA_ITERATIONS {
+            let a = -(iteration as f64) * (iteration as f64 - shape);
+            b += 2.0;
+            d = a * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + a / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            fraction *= delta;
+            if (delta - 1.0).abs() < f64::EPSILON {
+                break;
+            }
+        }
+        (1.0 - ln_prefactor.exp() * fraction).max(0.0)
+    }
+}
+
+/// The arrival counts and rates of equal-duration segments of the
+/// observation period, which starts at time zero.
+fn rate_segments(interarrivals: &[f64], segments: usize) -> Vec<RateSegment> {
+    let horizon: f64 = interarrivals.iter().sum();
+    let duration = horizon / segments as f64;
+    let mut counts = vec![0; segments];
+    let mut time = 0.0;
+    interarrivals.iter().for_each(|interarrival| {
+        time += interarrival;
+        let segment = if duration > 0.0 {
+            ((time / duration) as usize).min(segments - 1)
+        } else {
+            0
+        };
+        counts[segment] += 1;
+    });
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(index, arrivals)| RateSegment {
+            start: index as f64 * duration,
+            end: (index + 1) as f64 * duration,
+            arrivals,
+            rate: if duration > 0.0 {
+                arrivals as f64 / duration
+            } else {
+                0.0
+            },
+        })
+        .collect()
+}
+
+/// The chi-square dispersion statistic of the segment arrival counts, and
+/// its critical value by the Wilson-Hilferty approximation.
+fn dispersion_test(segments: &[RateSegment]) -> (f64, f64) {
+    let total: usize = segments.iter().map(|segment| segment.arrivals).sum();
+    let expected = total as f64 / segments.len() as f64;
+    if segments.len() < 2 || expected <= 0.0 {
+        return (0.0, f64::INFINITY);
+    }
+    let statistic = segments
+        .iter()
+        .map(|segment| (segment.arrivals as f64 - expected).powi(2) / expected)
+        .sum();
+    let df = (segments.len() - 1) as f64;
+    let critical_value =
+        df * (1.0 - 2.0 / (9.0 * df) + DISPERSION_Z * (2.0 / (9.0 * df)).sqrt()).powi(3);
+    (statistic, critical_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_modeling::dynamic_rng::seeded_rng;
+
+    fn sample(distribution: &mut Continuous, count: usize) -> Vec<f64> {
+        let rng = seeded_rng(11);
+        (0..count)
+            .map(|_| distribution.random_variate(rng.clone()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn fits_rank_the_generating_distribution_first() {
+        let exponential = sample(&mut Continuous::Exp { lambda: 2.0 }, 2000);
+        let report = fit_interarrivals(&exponential, 8).unwrap();
+        assert!(!report.nonstationary());
+        assert!(report.thinning().is_none());
+        match &report.best().distribution {
+            // The exponential is the gamma with a shape of 1
+            Continuous::Exp { lambda } => assert!((lambda - 2.0).abs() < 0.15),
+            Continuous::Gamma { shape, .. } => assert!((shape - 1.0).abs() < 0.15),
+            _ => panic!("the best fit should be exponential"),
+        }
+        let lognormal = sample(
+            &mut Continuous::LogNormal {
+                mu: 0.0,
+                sigma: 0.5,
+            },
+            2000,
+        );
+        let report = fit_interarrivals(&lognormal, 8).unwrap();
+        assert!(matches!(
+            report.best().distribution,
+            Continuous::LogNormal { .. }
+        ));
+        assert!(report.best().ks_statistic < 0.05);
+        let yaml = report.generator_yaml("generator-01", "job").unwrap();
+        assert!(yaml.contains("logNormal"));
+        assert!(!yaml.contains("state"));
+        let model: Model = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(model.id(), "generator-01");
+    }
+
+    #[test]
+    fn rate_shifts_suggest_thinning() {
+        // A busy first half, followed by a quiet second half
+        let mut interarrivals = sample(&mut Continuous::Exp { lambda: 4.0 }, 400);
+        interarrivals.extend(sample(&mut Continuous::Exp { lambda: 1.0 }, 100));
+        let report = fit_interarrivals(&interarrivals, 4).unwrap();
+        assert!(report.nonstationary());
+        let thinning = report.thinning().unwrap();
+        assert!(thinning.evaluate(0.0).unwrap() > 0.8);
+        assert!(thinning.evaluate(report.segments()[3].start).unwrap() < 0.5);
+        assert!(matches!(
+            report.interdeparture_time(),
+            Continuous::Exp { lambda } if lambda > 3.0
+        ));
+        let yaml = report.generator_yaml("generator-01", "job").unwrap();
+        assert!(yaml.contains("thinning"));
+        assert!(matches!(
+            fit_interarrivals(&[1.0, -1.0], 4),
+            Err(SimulationError::InvalidObservation(_))
+        ));
+        let constant = fit_interarrivals(&[2.0; 10], 2).unwrap();
+        assert!(matches!(
+            constant.best().distribution,
+            Continuous::Constant { value } if value == 2.0
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+//! Global variables are simulation-level named values, which distribution
+//! parameters may reference as `"$name"` strings (e.g. `lambda:
+//! "$arrival_rate"`).  References are resolved at sampling time, so
+//! parameter sweeps and controllers can adjust many models by changing a
+//! single global variable.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::errors::SimulationError;
+
+/// The prefix marking a string as a global variable reference.
+pub const REFERENCE_PREFIX: char = '$';
+
+/// The named global variables of a simulation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Globals {
+    variables: BTreeMap<String, f64>,
+}
+
+impl Globals {
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.variables.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &str, value: f64) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<f64> {
+        self.variables.remove(name)
+    }
+
+    /// The global variables, ordered by name.
+    pub fn variables(&self) -> impl Iterator<Item = (&String, &f64)> {
+        self.variables.iter()
+    }
+
+    /// Substitute the current values of the global variables for every
+    /// reference in the template.
+    pub fn resolve(&self, template: &Value) -> Result<Value, SimulationError> {
+        match template {
+            Value::String(reference) => match reference.strip_prefix(REFERENCE_PREFIX) {
+                Some(name) => self
+                    .get(name)
+                    .map(Value::from)
+                    .ok_or_else(|| SimulationError::UnknownGlobalVariable(name.to_string())),
+                None => Ok(template.clone()),
+            },
+            Value::Array(values) => Ok(Value::Array(
+                values
+                    .iter()
+                    .map(|value| self.resolve(value))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Value::Object(fields) => Ok(Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), self.resolve(value)?)))
+                    .collect::<Result<_, SimulationError>>()?,
+            )),
+            _ => Ok(template.clone()),
+        }
+    }
+}
+
+/// Whether the template references any global variables.
+pub fn has_references(template: &Value) -> bool {
+    match template {
+        Value::String(reference) => reference.starts_with(REFERENCE_PREFIX),
+        Value::Array(values) => values.iter().any(has_references),
+        Value::Object(fields) => fields.values().any(has_references),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_resolve_to_current_values() {
+        let mut globals = Globals::default();
+        globals.set("arrival_rate", 2.5);
+        let template: Value =
+            serde_json::from_str(r#"{"exp": {"lambda": "$arrival_rate"}}"#).unwrap();
+        assert!(has_references(&template));
+        assert_eq!(
+            globals.resolve(&template).unwrap(),
+            serde_json::json!({"exp": {"lambda": 2.5}})
+        );
+        globals.set("arrival_rate", 4.0);
+        assert_eq!(
+            globals.resolve(&template).unwrap(),
+            serde_json::json!({"exp": {"lambda": 4.0}})
+        );
+        globals.remove("arrival_rate");
+        assert!(matches!(
+            globals.resolve(&template),
+            Err(SimulationError::UnknownGlobalVariable(name)) if name == "arrival_rate"
+        ));
+        assert!(!has_references(
+            &serde_json::json!({"exp": {"lambda": 1.0}})
+        ));
+    }
+}
@@ -2,15 +2,30 @@
 //! behaviors, whether that is deterministic or stochastic.  The module
 //! includes a set of random variable distributions for use in atomic models,
 //! a system around "thinning" for non-stationary model behaviors, and a
-//! structure around random number generation.
+//! structure around random number generation.  For ultra-hot models,
+//! continuous distributions may be wrapped as `Buffered`, to precompute
+//! variates in blocks of a configured size.  Time-dependent behaviors, such
+//! as service times that differ by shift, are configured as a `Schedule`.
+//! Distribution parameters may reference simulation-level global variables,
+//! resolved at sampling time.  Named random number streams provide the same
+//! underlying uniforms to multiple models, for correlated behaviors, and
+//! antithetic generators complement a random number stream, for variance
+//! reduction.  Observed interarrival times are fitted to candidate
+//! distributions, for generator configurations grounded in data.
 
+pub mod dynamic_rng;
+pub mod fitting;
+pub mod globals;
 pub mod random_variable;
 pub mod thinning;
-pub mod dynamic_rng;
+pub mod variance_reduction;
 
+pub use dynamic_rng::{dyn_rng, seeded_dyn_rng, some_dyn_rng, stream_rng};
+pub use fitting::{fit_interarrivals, FitReport};
+pub use globals::Globals;
 pub use random_variable::Boolean as BooleanRandomVariable;
 pub use random_variable::Continuous as ContinuousRandomVariable;
 pub use random_variable::Discrete as DiscreteRandomVariable;
 pub use random_variable::Index as IndexRandomVariable;
 pub use thinning::Thinning;
-pub use dynamic_rng::{dyn_rng, some_dyn_rng};
+pub use variance_reduction::{antithetic_rng, AntitheticRng};
@@ -4,27 +4,402 @@
 //! common parameterizations, are wrapped in enums `Continuous`, `Boolean`,
 //! `Discrete`, and `Index`.
 
+use std::sync::OnceLock;
+
 use rand::distributions::Distribution;
-use serde::{Deserialize, Serialize};
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 // Continuous distributions
 use rand_distr::{Beta, Exp, Gamma, LogNormal, Normal, Triangular, Uniform, Weibull};
 // Discrete distributions
 use rand_distr::{Bernoulli, Geometric, Poisson, WeightedIndex};
 
-use super::dynamic_rng::DynRng;
+use super::dynamic_rng::{lock_rng, seeded_dyn_rng, BuiltinRng, DynRng, DEFAULT_SEED};
+use super::fitting::standard_normal_cdf;
+use super::globals::{has_references, Globals};
 use crate::utils::errors::SimulationError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Continuous {
-    Beta { alpha: f64, beta: f64 },
-    Exp { lambda: f64 },
-    Gamma { shape: f64, scale: f64 },
-    LogNormal { mu: f64, sigma: f64 },
-    Normal { mean: f64, std_dev: f64 },
-    Triangular { min: f64, max: f64, mode: f64 },
-    Uniform { min: f64, max: f64 },
-    Weibull { shape: f64, scale: f64 },
+    Beta {
+        alpha: f64,
+        beta: f64,
+    },
+    Exp {
+        lambda: f64,
+    },
+    Gamma {
+        shape: f64,
+        scale: f64,
+    },
+    LogNormal {
+        mu: f64,
+        sigma: f64,
+    },
+    Normal {
+        mean: f64,
+        std_dev: f64,
+    },
+    Triangular {
+        min: f64,
+        max: f64,
+        mode: f64,
+    },
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+    Weibull {
+        shape: f64,
+        scale: f64,
+    },
+    /// A deterministic value, sampled without drawing from the random
+    /// number generator.
+    Constant {
+        value: f64,
+    },
+    /// Variates of the distribution are precomputed in blocks of
+    /// `block_size`, and served from a buffer.  Each block is sampled with a
+    /// single distribution construction, reducing the per-event sampling
+    /// overhead of hot models.  Buffering changes the order of draws from
+    /// the random number generator, and so the variate sequence differs
+    /// from that of the unbuffered distribution.
+    #[serde(rename_all = "camelCase")]
+    Buffered {
+        distribution: Box<Continuous>,
+        #[serde(default = "default_block_size")]
+        block_size: usize,
+        #[serde(skip)]
+        buffer: Vec<f64>,
+    },
+    /// Resampling of observed values (e.g. traced service times), each
+    /// drawn with equal probability.
+    Empirical {
+        values: Vec<f64>,
+    },
+    /// A piecewise-linear cumulative distribution, through `(value,
+    /// cumulative probability)` points in ascending order, sampled by
+    /// inversion.  The cumulative probability of the first point is a
+    /// probability mass at its value.  Probabilities are normalized by the
+    /// final cumulative probability, so cumulative counts are accepted as
+    /// well.
+    EmpiricalCdf {
+        points: Vec<(f64, f64)>,
+    },
+    /// A weighted mixture of distributions (e.g. bimodal service times),
+    /// as `(weight, distribution)` components.  Each variate is drawn from
+    /// a single component, chosen with probability proportional to its
+    /// weight.
+    Mixture {
+        components: Vec<(f64, Continuous)>,
+    },
+    /// A distribution restricted to `[min, max]`, by rejection sampling.
+    /// Either bound may be omitted.  Bounds excluding (nearly) all of the
+    /// inner distribution are reported as a configuration error, after
+    /// `MAX_TRUNCATION_ATTEMPTS` rejected draws.  The mean is exact for
+    /// truncated constant, exponential, normal, and uniform distributions,
+    /// and otherwise estimated once, and cached in `estimated_mean`.
+    #[serde(rename_all = "camelCase")]
+    Truncated {
+        inner: Box<Continuous>,
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+        #[serde(skip)]
+        estimated_mean: OnceLock<Option<f64>>,
+    },
+    /// A piecewise-deterministic schedule, where the variate is the value of
+    /// the latest entry starting at or before the current simulation time
+    /// (e.g. service times that differ by shift).  With a `period`, the
+    /// schedule repeats, and entry start times are relative to the start of
+    /// each period.  Otherwise, times before the first entry take the value
+    /// of the first entry.
+    Schedule {
+        entries: Vec<ScheduleEntry>,
+        #[serde(default)]
+        period: Option<f64>,
+    },
+    /// A distribution with parameters referencing simulation global
+    /// variables, as `"$name"` strings (e.g. `{"exp": {"lambda":
+    /// "$arrival_rate"}}`).  References are resolved at sampling time, so
+    /// changes to the global variables apply to subsequent draws.
+    #[serde(untagged, deserialize_with = "deserialize_expression")]
+    Expression(Value),
+}
+
+/// Distributions that fail to deserialize as one of the concrete variants
+/// are only accepted as expressions if they reference global variables, so
+/// configuration errors still surface at load time.
+fn deserialize_expression<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+    let template = Value::deserialize(deserializer)?;
+    if has_references(&template) {
+        Ok(template)
+    } else {
+        Err(serde::de::Error::custom(
+            "invalid continuous random variable configuration",
+        ))
+    }
+}
+
+/// The simulation context of a random variate draw - the simulation clock
+/// for schedules, and the global variables for parameter references.
+#[derive(Debug, Clone, Copy)]
+pub struct SamplingContext<'a> {
+    pub time: f64,
+    pub globals: &'a Globals,
+}
+
+/// A single entry of a schedule random variable, holding `value` from
+/// `start` until the start of the next entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleEntry {
+    pub start: f64,
+    pub value: f64,
+}
+
+/// The value of the schedule at the provided time.
+fn scheduled_value(
+    entries: &[ScheduleEntry],
+    period: Option<f64>,
+    time: f64,
+) -> Result<f64, SimulationError> {
+    let time = match period {
+        Some(period) if period > 0.0 => time.rem_euclid(period),
+        Some(_) => return Err(SimulationError::InvalidModelConfiguration),
+        None => time,
+    };
+    let current = entries
+        .iter()
+        .filter(|entry| entry.start <= time)
+        .max_by(|a, b| a.start.total_cmp(&b.start));
+    // Before the first entry, repeating schedules continue the final entry
+    // of the previous period
+    let fallback = || match period {
+        Some(_) => entries.iter().max_by(|a, b| a.start.total_cmp(&b.start)),
+        None => entries.iter().min_by(|a, b| a.start.total_cmp(&b.start)),
+    };
+    current
+        .or_else(fallback)
+        .map(|entry| entry.value)
+        .ok_or(SimulationError::InvalidModelConfiguration)
+}
+
+/// A value of the empirical distribution, by the uniform variate.
+fn empirical_value(values: &[f64], uniform: f64) -> Result<f64, SimulationError> {
+    if values.is_empty() {
+        return Err(SimulationError::InvalidModelConfiguration);
+    }
+    let index = ((uniform * values.len() as f64) as usize).min(values.len() - 1);
+    Ok(values[index])
+}
+
+/// Validate the points of an empirical cumulative distribution, returning
+/// the final cumulative probability.
+fn validate_cdf(points: &[(f64, f64)]) -> Result<f64, SimulationError> {
+    let ascending = points
+        .windows(2)
+        .all(|pair| pair[0].0 <= pair[1].0 && pair[0].1 <= pair[1].1);
+    match (points.first(), points.last()) {
+        (Some(first), Some(last)) if ascending && first.1 >= 0.0 && last.1 > 0.0 => Ok(last.1),
+        _ => Err(SimulationError::InvalidModelConfiguration),
+    }
+}
+
+/// A value of the empirical cumulative distribution, by inversion of the
+/// uniform variate.
+fn empirical_cdf_value(points: &[(f64, f64)], uniform: f64) -> Result<f64, SimulationError> {
+    let probability = uniform * validate_cdf(points)?;
+    let segment = points.partition_point(|point| point.1 <= probability);
+    if segment == 0 {
+        return Ok(points[0].0);
+    }
+    let (lower, upper) = match points.get(segment) {
+        Some(upper) => (points[segment - 1], *upper),
+        None => return Ok(points[points.len() - 1].0),
+    };
+    Ok(lower.0 + (upper.0 - lower.0) * (probability - lower.1) / (upper.1 - lower.1))
+}
+
+/// The mean of the empirical cumulative distribution - the probability
+/// mass of the first point, and the uniform mass of each segment.
+fn empirical_cdf_mean(points: &[(f64, f64)]) -> f64 {
+    match validate_cdf(points) {
+        Ok(total) => {
+            let segments: f64 = points
+                .windows(2)
+                .map(|pair| (pair[1].1 - pair[0].1) * (pair[0].0 + pair[1].0) / 2.0)
+                .sum();
+            (points[0].1 * points[0].0 + segments) / total
+        }
+        Err(_) => f64::NAN,
+    }
+}
+
+/// The maximum number of consecutive rejected draws of a truncated
+/// distribution.
+pub const MAX_TRUNCATION_ATTEMPTS: usize = 10_000;
+
+/// The number of draws in the mean estimate of a truncated distribution.
+const TRUNCATED_MEAN_SAMPLES: usize = 10_000;
+
+/// The total weight of the mixture components, which must be finite and
+/// non-negative, with at least one positive weight.
+fn mixture_weight(components: &[(f64, Continuous)]) -> Result<f64, SimulationError> {
+    let valid_weights = components
+        .iter()
+        .all(|(weight, _)| weight.is_finite() && *weight >= 0.0);
+    let total_weight: f64 = components.iter().map(|(weight, _)| weight).sum();
+    if valid_weights && total_weight > 0.0 {
+        Ok(total_weight)
+    } else {
+        Err(SimulationError::InvalidModelConfiguration)
+    }
+}
+
+/// The mixture component chosen by the uniform variate.
+fn mixture_index(components: &[(f64, Continuous)], uniform: f64) -> Result<usize, SimulationError> {
+    let mut remaining = uniform * mixture_weight(components)?;
+    for (index, (weight, _)) in components.iter().enumerate() {
+        if remaining < *weight {
+            return Ok(index);
+        }
+        remaining -= weight;
+    }
+    // Floating point round-off leaves the final positive weight component
+    components
+        .iter()
+        .rposition(|(weight, _)| *weight > 0.0)
+        .ok_or(SimulationError::InvalidModelConfiguration)
+}
+
+/// The weighted mean of the mixture components' means.
+fn mixture_mean<F>(components: &[(f64, Continuous)], mut mean: F) -> Result<f64, SimulationError>
+where
+    F: FnMut(&Continuous) -> Result<f64, SimulationError>,
+{
+    let total_weight = mixture_weight(components)?;
+    components
+        .iter()
+        .filter(|(weight, _)| *weight > 0.0)
+        .map(|(weight, component)| Ok(weight * mean(component)?))
+        .sum::<Result<f64, SimulationError>>()
+        .map(|weighted_sum| weighted_sum / total_weight)
+}
+
+fn within_bounds(variate: f64, min: Option<f64>, max: Option<f64>) -> bool {
+    min.is_none_or(|min| variate >= min) && max.is_none_or(|max| variate <= max)
+}
+
+/// The exact mean of a truncated distribution, for inner distributions
+/// with a closed form, or `None` otherwise.  Bounds excluding the support
+/// of the inner distribution have an undefined (NaN) mean.
+fn truncated_mean(inner: &Continuous, min: Option<f64>, max: Option<f64>) -> Option<f64> {
+    let min = min.unwrap_or(f64::NEG_INFINITY);
+    let max = max.unwrap_or(f64::INFINITY);
+    match inner {
+        Continuous::Constant { value } => Some(if within_bounds(*value, Some(min), Some(max)) {
+            *value
+        } else {
+            f64::NAN
+        }),
+        Continuous::Uniform {
+            min: lower,
+            max: upper,
+        } => {
+            let (lower, upper) = (lower.max(min), upper.min(max));
+            Some(if lower <= upper {
+                (lower + upper) / 2.0
+            } else {
+                f64::NAN
+            })
+        }
+        // Memorylessness - the excess over the lower bound is exponential,
+        // truncated at the width of the bounds
+        Continuous::Exp { lambda } => {
+            let lower = min.max(0.0);
+            let width = max - lower;
+            Some(if width < 0.0 {
+                f64::NAN
+            } else if width.is_infinite() {
+                lower + 1.0 / lambda
+            } else {
+                lower + 1.0 / lambda - width / (lambda * width).exp_m1()
+            })
+        }
+        Continuous::Normal { mean, std_dev } => {
+            let density = |z: f64| {
+                if z.is_infinite() {
+                    0.0
+                } else {
+                    (-z * z / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt()
+                }
+            };
+            let (alpha, beta) = ((min - mean) / std_dev, (max - mean) / std_dev);
+            let probability = standard_normal_cdf(beta) - standard_normal_cdf(alpha);
+            Some(if probability > 0.0 {
+                mean + std_dev * (density(alpha) - density(beta)) / probability
+            } else {
+                f64::NAN
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The mean of a truncated distribution, estimated from a fixed-seed
+/// sample, so repeated estimates agree.
+fn estimate_truncated_mean(
+    truncated: &Continuous,
+    context: &SamplingContext,
+) -> Result<f64, SimulationError> {
+    let mut truncated = truncated.clone();
+    let uniform_rng = seeded_dyn_rng::<BuiltinRng>(DEFAULT_SEED);
+    let sum = (0..TRUNCATED_MEAN_SAMPLES)
+        .map(|_| truncated.random_variate_in(uniform_rng.clone(), context))
+        .sum::<Result<f64, SimulationError>>()?;
+    Ok(sum / TRUNCATED_MEAN_SAMPLES as f64)
+}
+
+/// The long-run average value of the schedule - the time-weighted average
+/// over a period for repeating schedules, and otherwise the value of the
+/// final entry.
+fn scheduled_mean(entries: &[ScheduleEntry], period: Option<f64>) -> f64 {
+    let mut sorted: Vec<&ScheduleEntry> = entries.iter().collect();
+    sorted.sort_by(|a, b| a.start.total_cmp(&b.start));
+    match (period, sorted.last()) {
+        (_, None) => f64::NAN,
+        (None, Some(last)) => last.value,
+        (Some(period), Some(last)) => {
+            // The final entry wraps around to the start of the next period
+            let mut weighted_sum = last.value * sorted[0].start.max(0.0);
+            sorted.windows(2).for_each(|pair| {
+                weighted_sum += pair[0].value * (pair[1].start - pair[0].start);
+            });
+            weighted_sum += last.value * (period - last.start);
+            weighted_sum / period
+        }
+    }
+}
+
+/// The default block size of buffered continuous random variables.
+pub const DEFAULT_BLOCK_SIZE: usize = 256;
+
+fn default_block_size() -> usize {
+    DEFAULT_BLOCK_SIZE
+}
+
+fn sample_block<D, R>(distribution: D, rng: &mut R, block_size: usize) -> Vec<f64>
+where
+    D: Distribution<f64>,
+    R: Rng + ?Sized,
+{
+    (0..block_size)
+        .map(|_| distribution.sample(&mut *rng))
+        .collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +411,11 @@ pub enum Boolean {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Discrete {
+    /// A deterministic value, sampled without drawing from the random
+    /// number generator.
+    Constant {
+        value: u64,
+    },
     Geometric {
         p: f64,
     },
@@ -52,6 +432,11 @@ pub enum Discrete {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Index {
+    /// A deterministic index, sampled without drawing from the random
+    /// number generator.
+    Constant {
+        value: usize,
+    },
     /// Range is inclusive of min, exclusive of max: [min, max)
     Uniform {
         min: usize,
@@ -62,12 +447,87 @@ pub enum Index {
     },
 }
 
+// Lanczos approximation coefficients (g = 7, n = 9)
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+fn lanczos_series(x: f64) -> f64 {
+    LANCZOS_COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS_COEFFICIENTS[0], |sum, (index, coefficient)| {
+            sum + coefficient / (x + index as f64 + 1.0)
+        })
+}
+
+/// The gamma function, by the Lanczos approximation (g = 7, n = 9), with
+/// the reflection formula for arguments below 0.5.
+fn gamma_function(x: f64) -> f64 {
+    if x < 0.5 {
+        std::f64::consts::PI / ((std::f64::consts::PI * x).sin() * gamma_function(1.0 - x))
+    } else {
+        let x = x - 1.0;
+        let t = x + 7.5;
+        (2.0 * std::f64::consts::PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * lanczos_series(x)
+    }
+}
+
+/// The natural logarithm of the gamma function, for positive arguments -
+/// without the overflow of the gamma function for large arguments.
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        gamma_function(x).ln()
+    } else {
+        let x = x - 1.0;
+        let t = x + 7.5;
+        0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + lanczos_series(x).ln()
+    }
+}
+
+/// Resolve the global variable references of an expression, to a concrete
+/// distribution.
+fn resolve_expression(template: &Value, globals: &Globals) -> Result<Continuous, SimulationError> {
+    Ok(serde_json::from_value(globals.resolve(template)?)?)
+}
+
 impl Continuous {
     /// The generation of random variates drives stochastic behaviors during
     /// simulation execution.  This function requires the random number
     /// generator of the simulation, and produces a f64 random variate.
+    /// Schedules are evaluated at time zero, without any global variables -
+    /// models provide the simulation context through `random_variate_in`.
     pub fn random_variate(&mut self, uniform_rng: DynRng) -> Result<f64, SimulationError> {
-        let mut rng = (*uniform_rng).borrow_mut();
+        let globals = Globals::default();
+        self.random_variate_in(
+            uniform_rng,
+            &SamplingContext {
+                time: 0.0,
+                globals: &globals,
+            },
+        )
+    }
+
+    /// Generate a random variate in the provided simulation context, for
+    /// time-dependent random variables (i.e. schedules) and parameters
+    /// referencing global variables.
+    pub fn random_variate_in(
+        &mut self,
+        uniform_rng: DynRng,
+        context: &SamplingContext,
+    ) -> Result<f64, SimulationError> {
+        if let Continuous::Constant { value } = self {
+            return Ok(*value);
+        }
+        let mut rng = lock_rng(&uniform_rng);
         match self {
             Continuous::Beta { alpha, beta } => Ok(Beta::new(*alpha, *beta)?.sample(&mut *rng)),
             Continuous::Exp { lambda } => Ok(Exp::new(*lambda)?.sample(&mut *rng)),
@@ -85,6 +545,232 @@ impl Continuous {
             Continuous::Weibull { shape, scale } => {
                 Ok(Weibull::new(*shape, *scale)?.sample(&mut *rng))
             }
+            Continuous::Buffered {
+                distribution,
+                block_size,
+                buffer,
+            } => {
+                if buffer.is_empty() {
+                    *buffer =
+                        distribution.block_variates(&mut *rng, *block_size, context.globals)?;
+                    // Serve the block in sampling order
+                    buffer.reverse();
+                }
+                buffer
+                    .pop()
+                    .ok_or(SimulationError::InvalidModelConfiguration)
+            }
+            Continuous::Constant { value } => Ok(*value),
+            Continuous::Empirical { values } => empirical_value(values, rng.gen()),
+            Continuous::EmpiricalCdf { points } => empirical_cdf_value(points, rng.gen()),
+            Continuous::Mixture { components } => {
+                let index = mixture_index(components, rng.gen())?;
+                // The lock is released before sampling the component
+                drop(rng);
+                components[index]
+                    .1
+                    .random_variate_in(uniform_rng.clone(), context)
+            }
+            Continuous::Truncated {
+                inner, min, max, ..
+            } => {
+                drop(rng);
+                for _ in 0..MAX_TRUNCATION_ATTEMPTS {
+                    let variate = inner.random_variate_in(uniform_rng.clone(), context)?;
+                    if within_bounds(variate, *min, *max) {
+                        return Ok(variate);
+                    }
+                }
+                Err(SimulationError::InvalidModelConfiguration)
+            }
+            Continuous::Schedule { entries, period } => {
+                scheduled_value(entries, *period, context.time)
+            }
+            Continuous::Expression(template) => {
+                // The lock is released before sampling the resolved
+                // distribution
+                drop(rng);
+                resolve_expression(template, context.globals)?
+                    .random_variate_in(uniform_rng.clone(), context)
+            }
+        }
+    }
+
+    /// Generate a block of random variates, with a single construction of
+    /// the distribution.
+    fn block_variates<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        block_size: usize,
+        globals: &Globals,
+    ) -> Result<Vec<f64>, SimulationError> {
+        match self {
+            Continuous::Beta { alpha, beta } => {
+                Ok(sample_block(Beta::new(*alpha, *beta)?, rng, block_size))
+            }
+            Continuous::Exp { lambda } => Ok(sample_block(Exp::new(*lambda)?, rng, block_size)),
+            Continuous::Gamma { shape, scale } => {
+                Ok(sample_block(Gamma::new(*shape, *scale)?, rng, block_size))
+            }
+            Continuous::LogNormal { mu, sigma } => {
+                Ok(sample_block(LogNormal::new(*mu, *sigma)?, rng, block_size))
+            }
+            Continuous::Normal { mean, std_dev } => {
+                Ok(sample_block(Normal::new(*mean, *std_dev)?, rng, block_size))
+            }
+            Continuous::Triangular { min, max, mode } => Ok(sample_block(
+                Triangular::new(*min, *max, *mode)?,
+                rng,
+                block_size,
+            )),
+            Continuous::Uniform { min, max } => {
+                Ok(sample_block(Uniform::new(*min, *max), rng, block_size))
+            }
+            Continuous::Weibull { shape, scale } => {
+                Ok(sample_block(Weibull::new(*shape, *scale)?, rng, block_size))
+            }
+            Continuous::Buffered { distribution, .. } => {
+                distribution.block_variates(rng, block_size, globals)
+            }
+            Continuous::Constant { value } => Ok(vec![*value; block_size]),
+            Continuous::Empirical { values } => (0..block_size)
+                .map(|_| empirical_value(values, rng.gen()))
+                .collect(),
+            Continuous::EmpiricalCdf { points } => (0..block_size)
+                .map(|_| empirical_cdf_value(points, rng.gen()))
+                .collect(),
+            Continuous::Mixture { components } => (0..block_size)
+                .map(|_| {
+                    let index = mixture_index(components, rng.gen())?;
+                    Ok(components[index].1.block_variates(rng, 1, globals)?[0])
+                })
+                .collect(),
+            Continuous::Truncated {
+                inner, min, max, ..
+            } => {
+                let mut variates = Vec::with_capacity(block_size);
+                let mut attempts = 0;
+                while variates.len() < block_size {
+                    if attempts >= MAX_TRUNCATION_ATTEMPTS {
+                        return Err(SimulationError::InvalidModelConfiguration);
+                    }
+                    let inner_block = inner.block_variates(rng, block_size, globals)?;
+                    let accepted = inner_block
+                        .into_iter()
+                        .filter(|variate| within_bounds(*variate, *min, *max));
+                    variates.extend(accepted.take(block_size - variates.len()));
+                    attempts += block_size;
+                }
+                Ok(variates)
+            }
+            // Schedules depend on the time of each draw, and so cannot be
+            // precomputed
+            Continuous::Schedule { .. } => Err(SimulationError::InvalidModelConfiguration),
+            // Global variable references are resolved once per block
+            Continuous::Expression(template) => {
+                resolve_expression(template, globals)?.block_variates(rng, block_size, globals)
+            }
+        }
+    }
+
+    /// The mean of the distribution.  In deterministic mode, models use the
+    /// mean in place of random variates.  The mean of a truncated
+    /// distribution without a closed form is estimated from a fixed-seed
+    /// sample, once.
+    pub fn mean(&self) -> f64 {
+        match self {
+            Continuous::Beta { alpha, beta } => alpha / (alpha + beta),
+            Continuous::Exp { lambda } => 1.0 / lambda,
+            Continuous::Gamma { shape, scale } => shape * scale,
+            Continuous::LogNormal { mu, sigma } => (mu + sigma.powi(2) / 2.0).exp(),
+            Continuous::Normal { mean, .. } => *mean,
+            Continuous::Triangular { min, max, mode } => (min + max + mode) / 3.0,
+            Continuous::Uniform { min, max } => (min + max) / 2.0,
+            // Consistent with sampling, where the fields are passed to
+            // `rand_distr::Weibull::new(scale, shape)` in declaration order
+            Continuous::Weibull { shape, scale } => shape * gamma_function(1.0 + 1.0 / scale),
+            Continuous::Buffered { distribution, .. } => distribution.mean(),
+            Continuous::Constant { value } => *value,
+            Continuous::Empirical { values } => values.iter().sum::<f64>() / values.len() as f64,
+            Continuous::EmpiricalCdf { points } => empirical_cdf_mean(points),
+            Continuous::Mixture { components } => {
+                mixture_mean(components, |component| Ok(component.mean())).unwrap_or(f64::NAN)
+            }
+            // Estimated at time zero, without any global variables
+            Continuous::Truncated { .. } => self.truncated_mean().unwrap_or(f64::NAN),
+            Continuous::Schedule { entries, period } => scheduled_mean(entries, *period),
+            // Undefined without the global variables
+            Continuous::Expression(_) => f64::NAN,
+        }
+    }
+
+    /// The mean of the distribution in the provided simulation context.
+    /// Schedules take the scheduled value as the mean, and global variable
+    /// references are resolved to their current values.
+    pub fn mean_in(&self, context: &SamplingContext) -> Result<f64, SimulationError> {
+        match self {
+            Continuous::Buffered { distribution, .. } => distribution.mean_in(context),
+            Continuous::Mixture { components } => {
+                mixture_mean(components, |component| component.mean_in(context))
+            }
+            // Estimated for each context, as the inner distribution depends
+            // on the context
+            Continuous::Truncated { inner, .. } if inner.depends_on_context() => {
+                estimate_truncated_mean(self, context)
+            }
+            Continuous::Truncated { .. } => self
+                .truncated_mean()
+                .filter(|mean| !mean.is_nan())
+                .ok_or(SimulationError::InvalidModelConfiguration),
+            Continuous::Schedule { entries, period } => {
+                scheduled_value(entries, *period, context.time)
+            }
+            Continuous::Expression(template) => {
+                resolve_expression(template, context.globals)?.mean_in(context)
+            }
+            _ => Ok(self.mean()),
+        }
+    }
+
+    /// The mean of a truncated distribution - exact, where the inner
+    /// distribution has a closed form, and otherwise estimated at time
+    /// zero, without any global variables, on first use.  `None` when the
+    /// estimate fails, as the bounds exclude the inner distribution.
+    fn truncated_mean(&self) -> Option<f64> {
+        match self {
+            Continuous::Truncated {
+                inner,
+                min,
+                max,
+                estimated_mean,
+            } => truncated_mean(inner, *min, *max).or_else(|| {
+                *estimated_mean.get_or_init(|| {
+                    let globals = Globals::default();
+                    estimate_truncated_mean(
+                        self,
+                        &SamplingContext {
+                            time: 0.0,
+                            globals: &globals,
+                        },
+                    )
+                    .ok()
+                })
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the variates depend on the sampling context - the simulation
+    /// time, or the global variables.
+    fn depends_on_context(&self) -> bool {
+        match self {
+            Continuous::Schedule { .. } | Continuous::Expression(_) => true,
+            Continuous::Buffered { distribution, .. } => distribution.depends_on_context(),
+            Continuous::Mixture { components } => components
+                .iter()
+                .any(|(_, component)| component.depends_on_context()),
+            Continuous::Truncated { inner, .. } => inner.depends_on_context(),
+            _ => false,
         }
     }
 }
@@ -94,7 +780,7 @@ impl Boolean {
     /// simulation execution.  This function requires the random number
     /// generator of the simulation, and produces a boolean random variate.
     pub fn random_variate(&mut self, uniform_rng: DynRng) -> Result<bool, SimulationError> {
-        let mut rng = (*uniform_rng).borrow_mut();
+        let mut rng = lock_rng(&uniform_rng);
         match self {
             Boolean::Bernoulli { p } => Ok(Bernoulli::new(*p)?.sample(&mut *rng)),
         }
@@ -106,13 +792,28 @@ impl Discrete {
     /// simulation execution.  This function requires the random number
     /// generator of the simulation, and produces a u64 random variate.
     pub fn random_variate(&mut self, uniform_rng: DynRng) -> Result<u64, SimulationError> {
-        let mut rng = (*uniform_rng).borrow_mut();
+        if let Discrete::Constant { value } = self {
+            return Ok(*value);
+        }
+        let mut rng = lock_rng(&uniform_rng);
         match self {
+            Discrete::Constant { value } => Ok(*value),
             Discrete::Geometric { p } => Ok(Geometric::new(*p)?.sample(&mut *rng)),
             Discrete::Poisson { lambda } => Ok(Poisson::new(*lambda)?.sample(&mut *rng) as u64),
             Discrete::Uniform { min, max } => Ok(Uniform::new(*min, *max).sample(&mut *rng)),
         }
     }
+
+    /// The mean of the distribution, which is not necessarily an integer.
+    pub fn mean(&self) -> f64 {
+        match self {
+            Discrete::Constant { value } => *value as f64,
+            // The number of failures before the first success
+            Discrete::Geometric { p } => (1.0 - p) / p,
+            Discrete::Poisson { lambda } => *lambda,
+            Discrete::Uniform { min, max } => (*min as f64 + *max as f64 - 1.0) / 2.0,
+        }
+    }
 }
 
 impl Index {
@@ -120,8 +821,12 @@ impl Index {
     /// simulation execution.  This function requires the random number
     /// generator of the simulation, and produces a usize random variate.
     pub fn random_variate(&mut self, uniform_rng: DynRng) -> Result<usize, SimulationError> {
-        let mut rng = (*uniform_rng).borrow_mut();
+        if let Index::Constant { value } = self {
+            return Ok(*value);
+        }
+        let mut rng = lock_rng(&uniform_rng);
         match self {
+            Index::Constant { value } => Ok(*value),
             Index::Uniform { min, max } => Ok(Uniform::new(*min, *max).sample(&mut *rng)),
             Index::WeightedIndex { weights } => {
                 Ok(WeightedIndex::new(weights.clone())?.sample(&mut *rng))
@@ -229,6 +934,317 @@ mod tests {
         assert!((mean - expected).abs() / expected < 0.025);
     }
 
+    #[test]
+    fn buffered_samples_match_expectation() {
+        let mut variable = Continuous::Buffered {
+            distribution: Box::new(Continuous::Exp { lambda: 7.0 }),
+            block_size: 64,
+            buffer: Vec::new(),
+        };
+        let uniform_rng = default_rng();
+        variable.random_variate(uniform_rng.clone()).unwrap();
+        match &variable {
+            Continuous::Buffered { buffer, .. } => assert_eq!(buffer.len(), 63),
+            _ => unreachable!(),
+        }
+        let mean = empirical_mean(&mut RandomVariable::Continuous(variable), 10000);
+        let expected = 1.0 / 7.0;
+        assert!((mean - expected).abs() / expected < 0.025);
+        let configured: Continuous =
+            serde_json::from_str(r#"{"buffered": {"distribution": {"exp": {"lambda": 7.0}}}}"#)
+                .unwrap();
+        match configured {
+            Continuous::Buffered { block_size, .. } => assert_eq!(block_size, DEFAULT_BLOCK_SIZE),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn empirical_samples_follow_the_data() {
+        let mut variable: Continuous =
+            serde_json::from_str(r#"{"empirical": {"values": [1.0, 2.0, 2.0, 5.0]}}"#).unwrap();
+        let uniform_rng = default_rng();
+        let variates: Vec<f64> = (0..1000)
+            .map(|_| variable.random_variate(uniform_rng.clone()).unwrap())
+            .collect();
+        assert!(variates
+            .iter()
+            .all(|variate| [1.0, 2.0, 5.0].contains(variate)));
+        assert_eq!(variable.mean(), 2.5);
+        // A point mass of 0.2 at 1.0, then uniform over [1, 3] and [3, 4]
+        let variable: Continuous = serde_json::from_str(
+            r#"{"empiricalCdf": {"points": [[1.0, 0.2], [3.0, 0.6], [4.0, 1.0]]}}"#,
+        )
+        .unwrap();
+        assert!((variable.mean() - (0.2 * 1.0 + 0.4 * 2.0 + 0.4 * 3.5)).abs() < 1.0e-12);
+        let points = [(1.0, 0.2), (3.0, 0.6), (4.0, 1.0)];
+        assert_eq!(empirical_cdf_value(&points, 0.1).unwrap(), 1.0);
+        assert_eq!(empirical_cdf_value(&points, 0.4).unwrap(), 2.0);
+        assert_eq!(empirical_cdf_value(&points, 0.8).unwrap(), 3.5);
+        let mean = variable.mean();
+        let mut random_variable = RandomVariable::Continuous(variable);
+        assert!((empirical_mean(&mut random_variable, 10000) - mean).abs() / mean < 0.025);
+        let mut invalid = Continuous::EmpiricalCdf {
+            points: vec![(2.0, 0.5), (1.0, 1.0)],
+        };
+        assert!(matches!(
+            invalid.random_variate(uniform_rng),
+            Err(SimulationError::InvalidModelConfiguration)
+        ));
+    }
+
+    #[test]
+    fn constant_samples_are_deterministic() {
+        let mut continuous: Continuous =
+            serde_json::from_str(r#"{"constant": {"value": 2.5}}"#).unwrap();
+        let mut discrete: Discrete = serde_json::from_str(r#"{"constant": {"value": 3}}"#).unwrap();
+        let mut index: Index = serde_json::from_str(r#"{"constant": {"value": 1}}"#).unwrap();
+        let uniform_rng = default_rng();
+        let reference_rng = default_rng();
+        (0..10).for_each(|_| {
+            assert_eq!(continuous.random_variate(uniform_rng.clone()).unwrap(), 2.5);
+            assert_eq!(discrete.random_variate(uniform_rng.clone()).unwrap(), 3);
+            assert_eq!(index.random_variate(uniform_rng.clone()).unwrap(), 1);
+        });
+        assert_eq!(continuous.mean(), 2.5);
+        assert_eq!(discrete.mean(), 3.0);
+        // Constants leave the random number stream untouched
+        assert_eq!(
+            lock_rng(&uniform_rng).gen::<u64>(),
+            lock_rng(&reference_rng).gen::<u64>()
+        );
+    }
+
+    #[test]
+    fn mixture_and_truncated_samples_match_expectation() {
+        let variable: Continuous = serde_json::from_str(
+            r#"{"mixture": {"components": [[3.0, {"constant": {"value": 1.0}}], [1.0, {"constant": {"value": 10.0}}]]}}"#,
+        )
+        .unwrap();
+        assert_eq!(variable.mean(), 3.25);
+        let mut random_variable = RandomVariable::Continuous(variable);
+        assert!((empirical_mean(&mut random_variable, 10000) - 3.25).abs() / 3.25 < 0.025);
+        // A half-normal distribution, with a mean of sqrt(2 / pi)
+        let mut variable: Continuous = serde_json::from_str(
+            r#"{"truncated": {"inner": {"normal": {"mean": 0.0, "std_dev": 1.0}}, "min": 0.0}}"#,
+        )
+        .unwrap();
+        let half_normal_mean = (2.0 / std::f64::consts::PI).sqrt();
+        assert!((variable.mean() - half_normal_mean).abs() < 1.0e-6);
+        let uniform_rng = default_rng();
+        assert!((0..1000).all(|_| variable.random_variate(uniform_rng.clone()).unwrap() >= 0.0));
+        let mut buffered = Continuous::Buffered {
+            distribution: Box::new(variable),
+            block_size: 64,
+            buffer: Vec::new(),
+        };
+        assert!((0..1000).all(|_| buffered.random_variate(uniform_rng.clone()).unwrap() >= 0.0));
+        let mut unreachable = Continuous::Truncated {
+            inner: Box::new(Continuous::Uniform { min: 0.0, max: 1.0 }),
+            min: Some(2.0),
+            max: None,
+            estimated_mean: OnceLock::new(),
+        };
+        assert!(matches!(
+            unreachable.random_variate(uniform_rng.clone()),
+            Err(SimulationError::InvalidModelConfiguration)
+        ));
+        assert!(unreachable.mean().is_nan());
+        assert!(matches!(
+            unreachable.mean_in(&context(0.0, &Globals::default())),
+            Err(SimulationError::InvalidModelConfiguration)
+        ));
+        let mut unweighted = Continuous::Mixture {
+            components: vec![(0.0, Continuous::Exp { lambda: 1.0 })],
+        };
+        assert!(unweighted.random_variate(uniform_rng).is_err());
+        assert!(unweighted.mean().is_nan());
+    }
+
+    #[test]
+    fn truncated_means_are_exact_or_cached() {
+        let truncated = |inner: &str, bounds: &str| -> Continuous {
+            serde_json::from_str(&format!(
+                r#"{{"truncated": {{"inner": {}, {}}}}}"#,
+                inner, bounds
+            ))
+            .unwrap()
+        };
+        let exponential = r#"{"exp": {"lambda": 2.0}}"#;
+        // Memorylessness - the mean excess over the lower bound is 1 / lambda
+        assert_eq!(truncated(exponential, r#""min": 3.0"#).mean(), 3.5);
+        let width: f64 = 1.0;
+        let expected = 0.5 - width / (2.0 * width).exp_m1();
+        assert!((truncated(exponential, r#""max": 1.0"#).mean() - expected).abs() < 1.0e-12);
+        let uniform = r#"{"uniform": {"min": 0.0, "max": 10.0}}"#;
+        assert_eq!(truncated(uniform, r#""min": 4.0, "max": 20.0"#).mean(), 7.0);
+        let symmetric = truncated(
+            r#"{"normal": {"mean": 5.0, "std_dev": 2.0}}"#,
+            r#""min": 3.0, "max": 7.0"#,
+        );
+        assert!((symmetric.mean() - 5.0).abs() < 1.0e-9);
+        let mut random_variable = RandomVariable::Continuous(symmetric.clone());
+        assert!((empirical_mean(&mut random_variable, 10000) - 5.0).abs() < 0.05);
+        let triangular = truncated(
+            r#"{"triangular": {"min": 0.0, "max": 4.0, "mode": 1.0}}"#,
+            r#""max": 2.0"#,
+        );
+        let estimate = triangular.mean();
+        assert!((estimate - 7.0 / 6.0).abs() < 0.02);
+        match &triangular {
+            Continuous::Truncated { estimated_mean, .. } => {
+                assert_eq!(estimated_mean.get(), Some(&Some(estimate)))
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(triangular.mean(), estimate);
+        assert_eq!(
+            triangular
+                .mean_in(&context(5.0, &Globals::default()))
+                .unwrap(),
+            estimate
+        );
+    }
+
+    fn context(time: f64, globals: &Globals) -> SamplingContext<'_> {
+        SamplingContext { time, globals }
+    }
+
+    #[test]
+    fn schedule_variates_follow_the_clock() {
+        let mut variable: Continuous = serde_json::from_str(
+            r#"{"schedule": {"entries": [{"start": 8.0, "value": 2.0}, {"start": 16.0, "value": 5.0}], "period": 24.0}}"#,
+        )
+        .unwrap();
+        let uniform_rng = default_rng();
+        let globals = Globals::default();
+        [
+            (0.0, 5.0),
+            (7.9, 5.0),
+            (8.0, 2.0),
+            (15.0, 2.0),
+            (16.0, 5.0),
+            (32.0, 2.0),
+            (47.0, 5.0),
+        ]
+        .iter()
+        .for_each(|(time, expected)| {
+            assert_eq!(
+                variable
+                    .random_variate_in(uniform_rng.clone(), &context(*time, &globals))
+                    .unwrap(),
+                *expected
+            );
+            assert_eq!(
+                variable.mean_in(&context(*time, &globals)).unwrap(),
+                *expected
+            );
+        });
+        // 8 hours at 2.0, and 16 hours at 5.0
+        assert!((variable.mean() - 4.0).abs() < 1.0e-12);
+        let mut unbounded = Continuous::Schedule {
+            entries: vec![
+                ScheduleEntry {
+                    start: 10.0,
+                    value: 1.0,
+                },
+                ScheduleEntry {
+                    start: 20.0,
+                    value: 3.0,
+                },
+            ],
+            period: None,
+        };
+        assert_eq!(
+            unbounded
+                .random_variate_in(uniform_rng.clone(), &context(0.0, &globals))
+                .unwrap(),
+            1.0
+        );
+        assert_eq!(
+            unbounded
+                .random_variate_in(uniform_rng.clone(), &context(100.0, &globals))
+                .unwrap(),
+            3.0
+        );
+        assert_eq!(unbounded.mean(), 3.0);
+        let mut empty = Continuous::Schedule {
+            entries: Vec::new(),
+            period: None,
+        };
+        assert!(empty
+            .random_variate_in(uniform_rng, &context(0.0, &globals))
+            .is_err());
+    }
+
+    #[test]
+    fn expressions_resolve_global_variables_at_sampling_time() {
+        let mut variable: Continuous =
+            serde_json::from_str(r#"{"uniform": {"min": "$low", "max": 2.0}}"#).unwrap();
+        assert!(matches!(variable, Continuous::Expression(_)));
+        let uniform_rng = default_rng();
+        let mut globals = Globals::default();
+        assert!(matches!(
+            variable.random_variate_in(uniform_rng.clone(), &context(0.0, &globals)),
+            Err(SimulationError::UnknownGlobalVariable(_))
+        ));
+        globals.set("low", 1.0);
+        assert_eq!(variable.mean_in(&context(0.0, &globals)).unwrap(), 1.5);
+        (0..100).for_each(|_| {
+            let variate = variable
+                .random_variate_in(uniform_rng.clone(), &context(0.0, &globals))
+                .unwrap();
+            assert!((1.0..2.0).contains(&variate));
+        });
+        globals.set("low", 1.9);
+        assert!((variable.mean_in(&context(0.0, &globals)).unwrap() - 1.95).abs() < 1.0e-12);
+        // Invalid configurations without references are still rejected
+        assert!(serde_json::from_str::<Continuous>(r#"{"exp": {"lambda": "fast"}}"#).is_err());
+        assert_eq!(
+            serde_json::to_value(&variable).unwrap(),
+            serde_json::json!({"uniform": {"min": "$low", "max": 2.0}})
+        );
+    }
+
+    #[test]
+    fn means_match_empirical_means() {
+        [
+            Continuous::Beta {
+                alpha: 2.0,
+                beta: 5.0,
+            },
+            Continuous::Gamma {
+                shape: 2.0,
+                scale: 3.0,
+            },
+            Continuous::LogNormal {
+                mu: 0.5,
+                sigma: 0.4,
+            },
+            Continuous::Triangular {
+                min: 1.0,
+                max: 4.0,
+                mode: 2.0,
+            },
+            Continuous::Weibull {
+                shape: 1.5,
+                scale: 2.0,
+            },
+        ]
+        .iter()
+        .for_each(|variable| {
+            let mean = variable.mean();
+            let mut random_variable = RandomVariable::Continuous(variable.clone());
+            assert!((empirical_mean(&mut random_variable, 10000) - mean).abs() / mean < 0.025);
+        });
+        let geometric = Discrete::Geometric { p: 0.2 };
+        let mean = geometric.mean();
+        assert!(
+            (empirical_mean(&mut RandomVariable::Discrete(geometric), 10000) - mean).abs() / mean
+                < 0.025
+        );
+    }
+
     #[test]
     fn gamma_samples_match_expectation() {
         let variable = Continuous::Gamma {
@@ -283,7 +1299,7 @@ mod tests {
         let expected_counts: [usize; 8] = [20, 210, 1360, 3410, 3410, 1360, 210, 20];
         let chi_square_actual = chi_square(
             &mut ChiSquareTest::Continuous {
-                variable: variable,
+                variable,
                 bin_mapping_fn: bins_mapping,
             },
             &expected_counts,
@@ -308,7 +1324,7 @@ mod tests {
         let expected_counts: [usize; 4] = [125, 375, 375, 125];
         let chi_square_actual = chi_square(
             &mut ChiSquareTest::Continuous {
-                variable: variable,
+                variable,
                 bin_mapping_fn: bins_mapping,
             },
             &expected_counts,
@@ -334,7 +1350,7 @@ mod tests {
         let expected_counts: [usize; 40] = [250; 40];
         let chi_square_actual = chi_square(
             &mut ChiSquareTest::Continuous {
-                variable: variable,
+                variable,
                 bin_mapping_fn: bins_mapping,
             },
             &expected_counts,
@@ -366,7 +1382,7 @@ mod tests {
         let expected_counts: [usize; 2] = [7000, 3000];
         let chi_square_actual = chi_square(
             &mut ChiSquareTest::Boolean {
-                variable: variable,
+                variable,
                 bin_mapping_fn: bins_mapping,
             },
             &expected_counts,
@@ -404,7 +1420,7 @@ mod tests {
         let expected_counts: [usize; 4] = [2500; 4];
         let chi_square_actual = chi_square(
             &mut ChiSquareTest::Discrete {
-                variable: variable,
+                variable,
                 bin_mapping_fn: bins_mapping,
             },
             &expected_counts,
@@ -427,7 +1443,7 @@ mod tests {
         let expected_counts: [usize; 4] = [1000, 2000, 3000, 4000];
         let chi_square_actual = chi_square(
             &mut ChiSquareTest::Index {
-                variable: variable,
+                variable,
                 bin_mapping_fn: bins_mapping,
             },
             &expected_counts,
@@ -449,7 +1465,7 @@ mod tests {
         let expected_counts: [usize; 4] = [2500; 4];
         let chi_square_actual = chi_square(
             &mut ChiSquareTest::Index {
-                variable: variable,
+                variable,
                 bin_mapping_fn: bins_mapping,
             },
             &expected_counts,
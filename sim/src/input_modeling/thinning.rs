@@ -1,5 +1,7 @@
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use super::dynamic_rng::{lock_rng, DynRng};
 use crate::utils::errors::SimulationError;
 use crate::utils::evaluate_polynomial;
 
@@ -7,7 +9,17 @@ use crate::utils::evaluate_polynomial;
 #[serde(rename_all = "camelCase")]
 enum ThinningFunction {
     // Coefficients, from the highest order coefficient to the zero order coefficient
-    Polynomial { coefficients: Vec<f64> },
+    Polynomial {
+        coefficients: Vec<f64>,
+    },
+    // Breakpoints, as (time, normalized rate) pairs
+    Schedule {
+        breakpoints: Vec<(f64, f64)>,
+        #[serde(default)]
+        linear: bool,
+        #[serde(default)]
+        period: Option<f64>,
+    },
 }
 
 /// Thinning provides a means for non-stationary stochastic model behaviors.
@@ -17,6 +29,24 @@ enum ThinningFunction {
 /// non-stationary stochastic behaviors, it is very inefficient for models
 /// where there is "heavy thinning" during large portions of the simulation
 /// execution.
+///
+/// The thinning function is either a polynomial, or a schedule of
+/// `(time, rate)` breakpoints - for example, a day/night arrival pattern:
+///
+/// ```yaml
+/// thinning:
+///   function:
+///     schedule:
+///       breakpoints: [[0.0, 0.25], [8.0, 1.0], [20.0, 0.25]]
+///       period: 24.0
+/// ```
+///
+/// Schedules are piecewise-constant by default, holding the rate of the
+/// latest breakpoint at or before the current time.  With `linear`, the
+/// rate is interpolated between adjacent breakpoints.  With a `period`, the
+/// schedule repeats, and breakpoint times are relative to the start of each
+/// period.  Otherwise, times outside the breakpoints take the rate of the
+/// nearest breakpoint.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Thinning {
     // Normalized thinning function with max(fn) = 1 over the support
@@ -24,11 +54,111 @@ pub struct Thinning {
 }
 
 impl Thinning {
-    pub fn evaluate(self, point: f64) -> Result<f64, SimulationError> {
+    /// A polynomial thinning function, with coefficients from the highest
+    /// order coefficient to the zero order coefficient.
+    pub fn polynomial(coefficients: Vec<f64>) -> Self {
+        Self {
+            function: ThinningFunction::Polynomial { coefficients },
+        }
+    }
+
+    /// A schedule thinning function, from `(time, rate)` breakpoints -
+    /// piecewise-linear if `linear`, and piecewise-constant otherwise.
+    pub fn schedule(breakpoints: Vec<(f64, f64)>, linear: bool, period: Option<f64>) -> Self {
+        Self {
+            function: ThinningFunction::Schedule {
+                breakpoints,
+                linear,
+                period,
+            },
+        }
+    }
+
+    pub fn evaluate(&self, point: f64) -> Result<f64, SimulationError> {
         match &self.function {
             ThinningFunction::Polynomial { coefficients } => {
                 evaluate_polynomial(coefficients, point)
             }
+            ThinningFunction::Schedule {
+                breakpoints,
+                linear,
+                period,
+            } => evaluate_schedule(breakpoints, *linear, *period, point),
         }
     }
+
+    /// Whether an event at the provided time is kept, rather than thinned -
+    /// with a probability of the thinning function value at that time.
+    pub fn accepts(&self, point: f64, uniform_rng: DynRng) -> Result<bool, SimulationError> {
+        let rate = self.evaluate(point)?;
+        Ok(lock_rng(&uniform_rng).gen::<f64>() < rate)
+    }
+}
+
+/// The rate of the schedule at the provided time.
+fn evaluate_schedule(
+    breakpoints: &[(f64, f64)],
+    linear: bool,
+    period: Option<f64>,
+    time: f64,
+) -> Result<f64, SimulationError> {
+    let mut sorted = breakpoints.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (first, last) = match (sorted.first(), sorted.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return Err(SimulationError::InvalidModelConfiguration),
+    };
+    let time = match period {
+        Some(period) if period > 0.0 => time.rem_euclid(period),
+        Some(_) => return Err(SimulationError::InvalidModelConfiguration),
+        None => time,
+    };
+    // The adjacent breakpoints - repeating schedules wrap around the period
+    // boundary, and other schedules hold the nearest breakpoint
+    let next_index = sorted.partition_point(|breakpoint| breakpoint.0 <= time);
+    let (previous, next) = match (next_index, period) {
+        (0, Some(period)) => ((last.0 - period, last.1), first),
+        (0, None) => (first, first),
+        (index, Some(period)) if index == sorted.len() => (last, (first.0 + period, first.1)),
+        (index, None) if index == sorted.len() => (last, last),
+        (index, _) => (sorted[index - 1], sorted[index]),
+    };
+    if !linear || next.0 <= previous.0 {
+        return Ok(previous.1);
+    }
+    let fraction = (time - previous.0) / (next.0 - previous.0);
+    Ok(previous.1 + fraction * (next.1 - previous.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedules_step_or_interpolate_between_breakpoints() {
+        let breakpoints = vec![(8.0, 1.0), (0.0, 0.2), (20.0, 0.2)];
+        let constant = Thinning::schedule(breakpoints.clone(), false, Some(24.0));
+        assert_eq!(constant.evaluate(4.0).unwrap(), 0.2);
+        assert_eq!(constant.evaluate(8.0).unwrap(), 1.0);
+        assert_eq!(constant.evaluate(19.9).unwrap(), 1.0);
+        assert_eq!(constant.evaluate(32.0).unwrap(), 1.0);
+        let linear = Thinning::schedule(breakpoints, true, None);
+        assert!((linear.evaluate(4.0).unwrap() - 0.6).abs() < 1.0e-12);
+        assert!((linear.evaluate(14.0).unwrap() - 0.6).abs() < 1.0e-12);
+        assert_eq!(linear.evaluate(-1.0).unwrap(), 0.2);
+        assert_eq!(linear.evaluate(30.0).unwrap(), 0.2);
+        // Repeating schedules interpolate across the period boundary
+        let wrapping = Thinning::schedule(vec![(6.0, 1.0), (18.0, 0.0)], true, Some(24.0));
+        assert!((wrapping.evaluate(0.0).unwrap() - 0.5).abs() < 1.0e-12);
+        assert!((wrapping.evaluate(21.0).unwrap() - 0.25).abs() < 1.0e-12);
+        let parsed: Thinning = serde_yaml::from_str(
+            "function:\n  schedule:\n    breakpoints: [[0.0, 0.5], [12.0, 1.0]]\n    period: 24.0\n",
+        )
+        .unwrap();
+        assert_eq!(parsed.evaluate(36.0).unwrap(), 1.0);
+        assert!(matches!(
+            Thinning::schedule(Vec::new(), false, None).evaluate(0.0),
+            Err(SimulationError::InvalidModelConfiguration)
+        ));
+    }
 }
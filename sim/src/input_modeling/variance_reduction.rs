@@ -0,0 +1,75 @@
+//! Variance reduction techniques draw correlated random numbers across
+//! simulation runs, so comparisons and estimates converge with fewer
+//! replications.  Antithetic variates pair each replication with a
+//! complementary replication - where one run draws the uniform `u`, its
+//! antithetic partner draws `1 - u` - so the negatively correlated pair
+//! mean has a lower variance than the mean of two independent runs.  Common
+//! random numbers (CRN) drive each scenario of a comparison with the same
+//! seeds, so differences between the scenarios reflect the scenario
+//! changes rather than sampling noise.  Per-model random number streams
+//! keep the random numbers of each model synchronized across scenarios.
+
+use rand_core::{impls, RngCore};
+
+use super::dynamic_rng::{lock_rng, DynRng};
+
+/// A random number generator producing the complement of an underlying
+/// generator's output - each bit is inverted, so uniforms `u` become
+/// (approximately) `1 - u`.  The underlying generator advances as usual, so
+/// the antithetic stream shares its state (and checkpoints) with the
+/// original stream.  Distributions sampled by inversion yield perfectly
+/// antithetic variates, while those sampled by rejection (e.g. normal and
+/// exponential variates) are negatively correlated, but less strongly.
+#[derive(Debug, Clone)]
+pub struct AntitheticRng {
+    inner: DynRng,
+}
+
+impl AntitheticRng {
+    pub fn new(inner: DynRng) -> Self {
+        Self { inner }
+    }
+}
+
+impl RngCore for AntitheticRng {
+    fn next_u32(&mut self) -> u32 {
+        !lock_rng(&self.inner).next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        !lock_rng(&self.inner).next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The antithetic counterpart of a random number generator.
+pub fn antithetic_rng(rng: DynRng) -> DynRng {
+    super::dyn_rng(AntitheticRng::new(rng))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::input_modeling::dynamic_rng::seeded_rng;
+
+    #[test]
+    fn antithetic_uniforms_complement_the_original_stream() {
+        let original = seeded_rng(7);
+        let antithetic = antithetic_rng(seeded_rng(7));
+        (0..100).for_each(|_| {
+            let u = lock_rng(&original).gen::<f64>();
+            let v = lock_rng(&antithetic).gen::<f64>();
+            assert!((u + v - 1.0).abs() < 1.0e-12);
+        });
+    }
+}
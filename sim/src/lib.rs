@@ -5,18 +5,38 @@
 //! This repository contains:
 //!
 //! * Random variable framework, for easy specification of stochastic model
-//! behaviors.
+//!   behaviors.
 //! * Pre-built atomic models, for quickly building out simulations of
-//! dynamic systems with common modular components.
+//!   dynamic systems with common modular components.
 //! * Output analysis framework, for analyzing simulation outputs
-//! statistically.
+//!   statistically.
 //! * Simulator engine, for managing and executing discrete event
-//! simulations.
+//!   simulations.
+//! * Experiment framework, for studying simulation behavior across varying
+//!   input parameters.
+//! * Reference models, with analytic expectations, for validating the
+//!   statistical correctness of the engine and models.
+//! * Import framework, for building simulations from tabular (CSV)
+//!   configurations.
 //!
 //! Sim is compatible with a wide variety of compilation targets, including
-//! WASM. Sim does not require nightly Rust.
+//! WASM. Sim does not require nightly Rust.  For size-constrained WASM
+//! builds, the `wasm-small` feature (without the default features) trims
+//! the YAML methods of web simulations and the optional models - serde_yaml
+//! is still compiled in, as models serialize through YAML values
+//! internally.  `build_info` reports the compiled features at runtime.
+pub mod build_info;
+pub mod experiment;
+pub mod import;
 pub mod input_modeling;
 pub mod models;
 pub mod output_analysis;
+pub mod reference;
 pub mod simulator;
 pub mod utils;
+
+pub use build_info::{build_info, BuildInfo};
+
+#[cfg(all(feature = "wee_alloc", target_arch = "wasm32"))]
+#[global_allocator]
+static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
@@ -0,0 +1,63 @@
+//! Model assumptions record the statistical and behavioral assumptions of
+//! the built-in models (e.g. FIFO queueing, or jobs waiting indefinitely) in
+//! a machine-readable form.  The assumptions of an assembled simulation are
+//! reported by `Simulation::validate_with`, for model reviews and
+//! verification and validation (V&V) documentation.  Custom models document
+//! their assumptions through `Reportable::assumptions`.
+
+use serde::{Deserialize, Serialize};
+
+/// The aspect of model behavior covered by an assumption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssumptionTopic {
+    ArrivalProcess,
+    ServiceTimes,
+    QueueDiscipline,
+    QueueCapacity,
+    Patience,
+    Servers,
+    Routing,
+    Blocking,
+    Batching,
+    Delays,
+    Storage,
+    Measurement,
+    Independence,
+    MessagePassing,
+}
+
+/// A single assumption - the topic, a short machine-readable value (e.g.
+/// `fifo` or `infinite`), and a human-readable description.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Assumption {
+    pub topic: AssumptionTopic,
+    pub value: String,
+    pub description: String,
+}
+
+impl Assumption {
+    pub fn new(topic: AssumptionTopic, value: &str, description: &str) -> Self {
+        Self {
+            topic,
+            value: value.to_string(),
+            description: description.to_string(),
+        }
+    }
+}
+
+/// The independence assumption of a model drawing from a named random
+/// number stream, shared with any other models naming the same stream.
+pub(crate) fn rng_stream_assumption(rng_stream: &Option<String>) -> Option<Assumption> {
+    rng_stream.as_ref().map(|rng_stream| {
+        Assumption::new(
+            AssumptionTopic::Independence,
+            "shared-stream",
+            &format![
+                "Random variates are drawn from the named stream {}, and are correlated with any other models drawing from the same stream",
+                rng_stream
+            ],
+        )
+    })
+}
@@ -1,8 +1,10 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
 use super::{ModelMessage, ModelRecord};
 use crate::simulator::Services;
 use crate::utils::errors::SimulationError;
@@ -52,6 +54,8 @@ struct State {
     phase: Phase,
     until_next_event: f64,
     jobs: Vec<String>,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
@@ -59,8 +63,9 @@ impl Default for State {
     fn default() -> Self {
         State {
             phase: Phase::Passive,
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             jobs: Vec::new(),
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -126,7 +131,7 @@ impl Batcher {
 
     fn release_full_queue(&mut self, services: &mut Services) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         (0..self.state.jobs.len())
             .map(|_| {
                 self.record(
@@ -137,6 +142,10 @@ impl Batcher {
                 ModelMessage {
                     port_name: self.ports_out.job.clone(),
                     content: self.state.jobs.remove(0),
+                    job_id: None,
+                    metadata: HashMap::new(),
+                    payload: None,
+                    priority: 0,
                 }
             })
             .collect()
@@ -155,6 +164,10 @@ impl Batcher {
                 ModelMessage {
                     port_name: self.ports_out.job.clone(),
                     content: self.state.jobs.remove(0),
+                    job_id: None,
+                    metadata: HashMap::new(),
+                    payload: None,
+                    priority: 0,
                 }
             })
             .collect()
@@ -173,6 +186,10 @@ impl Batcher {
                 ModelMessage {
                     port_name: self.ports_out.job.clone(),
                     content: self.state.jobs.remove(0),
+                    job_id: None,
+                    metadata: HashMap::new(),
+                    payload: None,
+                    priority: 0,
                 }
             })
             .collect()
@@ -191,11 +208,15 @@ impl Batcher {
 
 #[cfg_attr(feature = "simx", event_rules)]
 impl DevsModel for Batcher {
+    #[allow(clippy::unit_arg)]
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         match (
             &self.state.phase,
             self.state.jobs.len() + 1 < self.max_batch_size,
@@ -211,7 +232,7 @@ impl DevsModel for Batcher {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match (
+        let outgoing_messages = match (
             self.state.jobs.len() <= self.max_batch_size,
             self.state.jobs.len() >= 2 * self.max_batch_size,
         ) {
@@ -219,7 +240,11 @@ impl DevsModel for Batcher {
             (false, true) => Ok(self.release_multiple(services)),
             (false, false) => Ok(self.release_partial_queue(services)),
             (true, true) => Err(SimulationError::InvalidModelState),
-        }
+        }?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -243,6 +268,29 @@ impl Reportable for Batcher {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn queue_depth(&self) -> Option<usize> {
+        Some(self.state.jobs.len())
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            AssumptionTopic::Batching,
+            "size-or-time",
+            &format![
+                "Batches are released at {} jobs or {} time units, whichever comes first, and excess simultaneous arrivals spill over into the next batch",
+                self.max_batch_size, self.max_batch_time
+            ],
+        )]
+    }
 }
 
 impl ReportableModel for Batcher {}
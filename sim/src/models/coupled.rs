@@ -1,18 +1,40 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::Assumption;
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
 use super::{Model, ModelMessage, ModelRecord};
 
-use crate::simulator::Services;
-use crate::utils::errors::SimulationError;
+use crate::simulator::coupling::is_default_priority;
+use crate::simulator::{JobId, Payload, Services};
+use crate::utils::errors::{ErrorContext, SimulationError};
 
 use sim_derive::SerializableModel;
 
 #[cfg(feature = "simx")]
 use simx::event_rules;
 
+/// The coupled model composes component models into a single model, with
+/// couplings from its input ports to the components (external input
+/// couplings), between the components (internal couplings), and from the
+/// components to its output ports (external output couplings).  Coupled
+/// models may themselves be components, for hierarchical models.  The
+/// couplings are compiled into a routing table on first use, flattened
+/// through the nested coupled models to the atomic components, so each
+/// message is routed to its final targets with a single lookup, however
+/// deep the hierarchy.  Nested coupled models are then containers only, and
+/// the outermost coupled model keeps the port statistics.  Couplings
+/// referencing unknown components are invalid.
+///
+/// The coupled model coordinates its components as the simulator
+/// coordinates top-level models - messages on internal couplings are
+/// delivered at the time they are sent, before the internal transitions of
+/// that time - so a sub-network executes the same whether coupled or flat.
+/// Component IDs are local to the coupled model, so a sub-network may be
+/// instantiated many times, and nested components are addressed by path
+/// in simulation queries (e.g. `line-01/processor-01`).
 #[derive(Clone, Deserialize, Serialize, SerializableModel)]
 #[serde(rename_all = "camelCase")]
 pub struct Coupled {
@@ -24,6 +46,8 @@ pub struct Coupled {
     internal_couplings: Vec<InternalCoupling>,
     #[serde(default)]
     state: State,
+    #[serde(skip)]
+    routes: Option<RoutingTable>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,15 +95,331 @@ pub struct InternalCoupling {
 #[serde(rename_all = "camelCase")]
 struct State {
     parked_messages: Vec<ParkedMessage>,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
+/// An atomic component of the hierarchy, by its index path through the
+/// nested coupled models, and its path ID (e.g. `line-01/processor-01`).
+#[derive(Clone, Debug)]
+struct FlatComponent {
+    path: Vec<usize>,
+    id: String,
+    parent_ids: Vec<String>,
+}
+
+/// A coupling target - an atomic component, by flattened index, and its
+/// input port.
+#[derive(Clone, Debug)]
+struct FlatTarget {
+    component: usize,
+    port: String,
+}
+
+/// The compiled couplings, flattened to the atomic components - the final
+/// component targets of each input port, and the final component and output
+/// port targets of each component output port.  Targets are in coupling
+/// order, innermost coupling layer first.
+#[derive(Clone, Debug, Default)]
+struct RoutingTable {
+    components: Vec<FlatComponent>,
+    component_indices: HashMap<String, usize>,
+    input_routes: HashMap<String, Vec<FlatTarget>>,
+    internal_routes: HashMap<(usize, String), Vec<FlatTarget>>,
+    output_routes: HashMap<(usize, String), Vec<String>>,
+}
+
+/// The component indices of each coupled model in the hierarchy, and the
+/// flattened index of each atomic component, by index path - used only
+/// while compiling the routing table.
+#[derive(Default)]
+struct Hierarchy {
+    layer_indices: HashMap<Vec<usize>, HashMap<String, usize>>,
+    flat_indices: HashMap<Vec<usize>, usize>,
+}
+
+impl RoutingTable {
+    fn compile(coupled: &Coupled) -> Result<Self, SimulationError> {
+        let mut table = Self::default();
+        let mut hierarchy = Hierarchy::default();
+        table.flatten(coupled, None, &[], &[], &mut hierarchy)?;
+        for coupling in &coupled.external_input_couplings {
+            let mut targets = Vec::new();
+            hierarchy.resolve_input(
+                coupled,
+                &[],
+                &coupling.target_id,
+                &coupling.target_port,
+                &mut targets,
+            );
+            table
+                .input_routes
+                .entry(coupling.source_port.clone())
+                .or_default()
+                .extend(targets);
+        }
+        for (component_index, component) in table.components.iter().enumerate() {
+            // The coupled models containing the component, outermost first
+            let mut layers = vec![coupled];
+            for index in &component.path[..component.path.len() - 1] {
+                if let Some(layer) = layers[layers.len() - 1].components[*index].as_coupled() {
+                    layers.push(layer);
+                }
+            }
+            let container = layers[layers.len() - 1];
+            let source_id = container.components[component.path[component.path.len() - 1]].id();
+            let mut source_ports: Vec<&String> = container
+                .internal_couplings
+                .iter()
+                .filter(|coupling| coupling.source_id == source_id)
+                .map(|coupling| &coupling.source_port)
+                .chain(
+                    container
+                        .external_output_couplings
+                        .iter()
+                        .filter(|coupling| coupling.source_id == source_id)
+                        .map(|coupling| &coupling.source_port),
+                )
+                .collect();
+            source_ports.sort();
+            source_ports.dedup();
+            for source_port in source_ports {
+                let mut targets = Vec::new();
+                let mut target_ports = Vec::new();
+                hierarchy.resolve_output(
+                    &layers,
+                    &component.path[..component.path.len() - 1],
+                    source_id,
+                    source_port,
+                    &mut targets,
+                    &mut target_ports,
+                );
+                let route = (component_index, source_port.clone());
+                if !targets.is_empty() {
+                    table.internal_routes.insert(route.clone(), targets);
+                }
+                if !target_ports.is_empty() {
+                    table.output_routes.insert(route, target_ports);
+                }
+            }
+        }
+        table.component_indices = table
+            .components
+            .iter()
+            .enumerate()
+            .map(|(index, component)| (component.id.clone(), index))
+            .collect();
+        Ok(table)
+    }
+
+    /// Collect the atomic components of a coupled model, depth first, and
+    /// validate the couplings of each layer.  Errors of nested layers are
+    /// attributed to the nested coupled model.
+    fn flatten(
+        &mut self,
+        coupled: &Coupled,
+        layer: Option<&Model>,
+        path: &[usize],
+        parent_ids: &[String],
+        hierarchy: &mut Hierarchy,
+    ) -> Result<(), SimulationError> {
+        let component_indices: HashMap<String, usize> = coupled
+            .components
+            .iter()
+            .enumerate()
+            .map(|(index, component)| (component.id().to_string(), index))
+            .collect();
+        let sources = coupled
+            .internal_couplings
+            .iter()
+            .map(|coupling| (&coupling.source_id, &coupling.source_port))
+            .chain(
+                coupled
+                    .external_output_couplings
+                    .iter()
+                    .map(|coupling| (&coupling.source_id, &coupling.source_port)),
+            );
+        let targets = coupled
+            .external_input_couplings
+            .iter()
+            .map(|coupling| (&coupling.target_id, &coupling.source_port))
+            .chain(
+                coupled
+                    .internal_couplings
+                    .iter()
+                    .map(|coupling| (&coupling.target_id, &coupling.source_port)),
+            );
+        if let Some((component_id, port)) = targets
+            .chain(sources)
+            .find(|(component_id, _)| !component_indices.contains_key(*component_id))
+        {
+            let error = SimulationError::ModelNotFound(component_id.to_string());
+            return Err(match layer {
+                Some(layer) => error.with_context(
+                    ErrorContext::new(layer.model_type())
+                        .with_model_id(&parent_ids.join("/"))
+                        .with_port(port),
+                ),
+                None => error,
+            });
+        }
+        hierarchy
+            .layer_indices
+            .insert(path.to_vec(), component_indices);
+        for (index, component) in coupled.components.iter().enumerate() {
+            let component_path = [path, &[index]].concat();
+            match component.as_coupled() {
+                Some(nested) => {
+                    let nested_parent_ids = [parent_ids, &[component.id().to_string()]].concat();
+                    self.flatten(
+                        nested,
+                        Some(component),
+                        &component_path,
+                        &nested_parent_ids,
+                        hierarchy,
+                    )?;
+                }
+                None => {
+                    hierarchy
+                        .flat_indices
+                        .insert(component_path.clone(), self.components.len());
+                    self.components.push(FlatComponent {
+                        path: component_path,
+                        id: parent_ids
+                            .iter()
+                            .map(String::as_str)
+                            .chain(std::iter::once(component.id()))
+                            .collect::<Vec<&str>>()
+                            .join("/"),
+                        parent_ids: parent_ids.to_vec(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Hierarchy {
+    /// Resolve a message to an input port of a component, through the
+    /// external input couplings of nested coupled models, to the final
+    /// atomic component targets.
+    fn resolve_input(
+        &self,
+        layer: &Coupled,
+        layer_path: &[usize],
+        component_id: &str,
+        port: &str,
+        targets: &mut Vec<FlatTarget>,
+    ) {
+        let index = self.layer_indices[layer_path][component_id];
+        let path = [layer_path, &[index]].concat();
+        match layer.components[index].as_coupled() {
+            Some(nested) => {
+                for coupling in &nested.external_input_couplings {
+                    if coupling.source_port == port {
+                        self.resolve_input(
+                            nested,
+                            &path,
+                            &coupling.target_id,
+                            &coupling.target_port,
+                            targets,
+                        );
+                    }
+                }
+            }
+            None => targets.push(FlatTarget {
+                component: self.flat_indices[&path],
+                port: port.to_string(),
+            }),
+        }
+    }
+
+    /// Resolve a message from an output port of a component, through the
+    /// internal couplings of its coupled model and the external output
+    /// couplings up the hierarchy, to the final atomic component targets and
+    /// the output ports of the outermost coupled model.
+    fn resolve_output(
+        &self,
+        layers: &[&Coupled],
+        layer_path: &[usize],
+        component_id: &str,
+        port: &str,
+        targets: &mut Vec<FlatTarget>,
+        target_ports: &mut Vec<String>,
+    ) {
+        let layer = layers[layers.len() - 1];
+        for coupling in &layer.internal_couplings {
+            if coupling.source_id == component_id && coupling.source_port == port {
+                self.resolve_input(
+                    layer,
+                    layer_path,
+                    &coupling.target_id,
+                    &coupling.target_port,
+                    targets,
+                );
+            }
+        }
+        for coupling in &layer.external_output_couplings {
+            if coupling.source_id != component_id || coupling.source_port != port {
+                continue;
+            }
+            match layer_path.split_last() {
+                Some((layer_index, parent_path)) => {
+                    let parent_layers = &layers[..layers.len() - 1];
+                    let layer_id =
+                        parent_layers[parent_layers.len() - 1].components[*layer_index].id();
+                    self.resolve_output(
+                        parent_layers,
+                        parent_path,
+                        layer_id,
+                        &coupling.target_port,
+                        targets,
+                        target_ports,
+                    );
+                }
+                None => target_ports.push(coupling.target_port.clone()),
+            }
+        }
+    }
+}
+
+impl FlatComponent {
+    /// Attribute an error of the component to its path, as the nested
+    /// coupled models would.
+    fn nest_error(&self, error: SimulationError) -> SimulationError {
+        self.parent_ids
+            .iter()
+            .rev()
+            .fold(error, |error, parent_id| error.nested_in(parent_id))
+    }
+}
+
+/// The atomic component at an index path, through the nested coupled
+/// models.
+fn component_mut<'a>(mut components: &'a mut [Model], path: &[usize]) -> Option<&'a mut Model> {
+    let (index, parent_path) = path.split_last()?;
+    for parent_index in parent_path {
+        components = components.get_mut(*parent_index)?.components_mut();
+    }
+    components.get_mut(*index)
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ParkedMessage {
     component_id: String,
     port: String,
     content: String,
+    #[serde(default)]
+    job_id: Option<JobId>,
+    #[serde(default)]
+    metadata: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<Payload>,
+    #[serde(default, skip_serializing_if = "is_default_priority")]
+    priority: i32,
 }
 
 #[cfg_attr(feature = "simx", event_rules)]
@@ -104,176 +444,154 @@ impl Coupled {
             external_output_couplings,
             internal_couplings,
             state: State::default(),
+            routes: None,
         }
     }
 
-    fn park_incoming_messages(
-        &self,
-        incoming_message: &ModelMessage,
-    ) -> Option<Vec<ParkedMessage>> {
-        let parked_messages: Vec<ParkedMessage> = self
-            .external_input_couplings
-            .iter()
-            .filter_map(|coupling| {
-                if coupling.source_port == incoming_message.port_name {
-                    Some(ParkedMessage {
-                        component_id: coupling.target_id.to_string(),
-                        port: coupling.target_port.to_string(),
-                        content: incoming_message.content.to_string(),
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        if parked_messages.is_empty() {
-            None
-        } else {
-            Some(parked_messages)
+    /// Compile the routing table, if not already compiled.
+    fn compile_routes(&mut self) -> Result<(), SimulationError> {
+        if self.routes.is_none() {
+            self.routes = Some(RoutingTable::compile(self)?);
         }
+        Ok(())
     }
 
-    fn external_output_targets(&self, source_id: &str, source_port: &str) -> Vec<String> {
-        // Vec<target_port>
-
-        self.external_output_couplings
-            .iter()
-            .filter_map(|coupling| {
-                if coupling.source_id == source_id && coupling.source_port == source_port {
-                    Some(coupling.target_port.to_string())
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
-
-    fn internal_targets(&self, source_id: &str, source_port: &str) -> Vec<(String, String)> {
-        // Vec<(target_id, target_port)>
-
-        self.internal_couplings
-            .iter()
-            .filter_map(|coupling| {
-                if coupling.source_id == source_id && coupling.source_port == source_port {
-                    Some((
-                        coupling.target_id.to_string(),
-                        coupling.target_port.to_string(),
-                    ))
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
-
+    /// Deliver an incoming message to the atomic components on the
+    /// flattened external input couplings of its port, immediately.
     fn distribute_events_ext(
         &mut self,
-        parked_messages: &[ParkedMessage],
+        incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
-        parked_messages.iter().try_for_each(|parked_message| {
-            self.components
-                .iter_mut()
-                .find(|component| component.id() == parked_message.component_id)
-                .unwrap()
-                .events_ext(
-                    &ModelMessage {
-                        port_name: parked_message.port.to_string(),
-                        content: parked_message.content.to_string(),
-                    },
-                    services,
-                )
-        })
+        let routes = self
+            .routes
+            .as_ref()
+            .ok_or(SimulationError::PrerequisiteCalcError)?;
+        let components = &mut self.components;
+        routes
+            .input_routes
+            .get(&incoming_message.port_name)
+            .map_or(Ok(()), |targets| {
+                targets.iter().try_for_each(|target| {
+                    routes.deliver(
+                        components,
+                        target.component,
+                        &ModelMessage {
+                            port_name: target.port.clone(),
+                            ..incoming_message.clone()
+                        },
+                        services,
+                    )
+                })
+            })
     }
 
     fn distribute_events_int(
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        // Find the (internal message) events_ext relevant models (parked message id == component id)
-        let ext_transitioning_component_triggers: Vec<(usize, String, String)> = (0..self
-            .components
-            .len())
-            .flat_map(|component_index| -> Vec<(usize, String, String)> {
-                self.state
-                    .parked_messages
-                    .iter()
-                    .filter_map(|parked_message| {
-                        if parked_message.component_id == self.components[component_index].id() {
-                            Some((
-                                component_index,
-                                parked_message.port.to_string(),
-                                parked_message.content.to_string(),
-                            ))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect()
+        let routes = self
+            .routes
+            .as_ref()
+            .ok_or(SimulationError::PrerequisiteCalcError)?;
+        let components = &mut self.components;
+        // Deliver the internal messages of the previous step, in component
+        // order
+        let mut parked_messages = self
+            .state
+            .parked_messages
+            .drain(..)
+            .map(|parked_message| {
+                routes
+                    .component_indices
+                    .get(&parked_message.component_id)
+                    .map(|component_index| (*component_index, parked_message.to_message()))
+                    .ok_or(SimulationError::ModelNotFound(parked_message.component_id))
             })
-            .collect();
-        ext_transitioning_component_triggers
+            .collect::<Result<Vec<(usize, ModelMessage)>, SimulationError>>()?;
+        parked_messages.sort_by_key(|(component_index, _)| *component_index);
+        parked_messages
             .iter()
-            .map(
-                |(component_index, message_port, message_content)| -> Result<(), SimulationError> {
-                    self.components[*component_index].events_ext(
-                        &ModelMessage {
-                            port_name: message_port.to_string(),
-                            content: message_content.to_string(),
-                        },
-                        services,
-                    )
-                },
-            )
-            .collect::<Result<Vec<()>, SimulationError>>()?;
-        self.state.parked_messages = Vec::new();
-        // Find the events_int relevant models (until_next_event == 0.0)
-        // Run events_int for each model, and compile the internal and external messages
-        // Store the internal messages in the Coupled model struct, and output the external messages
-        let int_transitioning_component_indexes: Vec<usize> = (0..self.components.len())
-            .filter(|component_index| self.components[*component_index].until_next_event() == 0.0)
-            .collect();
-        Ok(int_transitioning_component_indexes
-            .iter()
-            .flat_map(
-                |component_index| -> Result<Vec<ModelMessage>, SimulationError> {
-                    Ok(self.components[*component_index]
-                        .events_int(services)?
-                        .iter()
-                        .flat_map(|outgoing_message| -> Vec<ModelMessage> {
-                            // For internal messages (those transmitted on internal couplings), store the messages
-                            // as Parked Messages, to be ingested by the target components on the next simulation step
-                            self.internal_targets(
-                                self.components[*component_index].id(),
-                                &outgoing_message.port_name,
-                            )
-                            .iter()
-                            .for_each(|(target_id, target_port)| {
-                                self.state.parked_messages.push(ParkedMessage {
-                                    component_id: target_id.to_string(),
-                                    port: target_port.to_string(),
-                                    content: outgoing_message.content.clone(),
-                                });
-                            });
-                            // For external messages (those transmitted on external output couplings), prepare the
-                            // output as standard events_int output
-                            self.external_output_targets(
-                                self.components[*component_index].id(),
-                                &outgoing_message.port_name,
+            .try_for_each(|(component_index, message)| {
+                routes.deliver(components, *component_index, message, services)
+            })?;
+        // Run the internal transitions of the imminent components, parking
+        // the messages on internal couplings for the next step, and
+        // outputting the messages on external output couplings
+        let mut outgoing_messages = Vec::new();
+        for (component_index, flat_component) in routes.components.iter().enumerate() {
+            let component = component_mut(components, &flat_component.path)
+                .ok_or_else(|| SimulationError::ModelNotFound(flat_component.id.clone()))?;
+            if component.until_next_event() != 0.0 {
+                continue;
+            }
+            let messages = component
+                .events_int(services)
+                .map_err(|error| flat_component.nest_error(error))?;
+            for outgoing_message in messages {
+                let route = (component_index, outgoing_message.port_name.clone());
+                if let Some(targets) = routes.internal_routes.get(&route) {
+                    self.state
+                        .parked_messages
+                        .extend(targets.iter().map(|target| {
+                            ParkedMessage::new(
+                                &routes.components[target.component].id,
+                                &target.port,
+                                &outgoing_message,
                             )
-                            .iter()
-                            .map(|target_port| ModelMessage {
-                                port_name: target_port.to_string(),
-                                content: outgoing_message.content.clone(),
-                            })
-                            .collect()
-                        })
-                        .collect())
-                },
-            )
-            .flatten()
-            .collect())
+                        }));
+                }
+                if let Some(target_ports) = routes.output_routes.get(&route) {
+                    outgoing_messages.extend(target_ports.iter().map(|target_port| ModelMessage {
+                        port_name: target_port.to_string(),
+                        ..outgoing_message.clone()
+                    }));
+                }
+            }
+        }
+        Ok(outgoing_messages)
+    }
+}
+
+impl RoutingTable {
+    /// Deliver a message to an atomic component, by flattened index.
+    fn deliver(
+        &self,
+        components: &mut [Model],
+        component_index: usize,
+        message: &ModelMessage,
+        services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        let flat_component = &self.components[component_index];
+        component_mut(components, &flat_component.path)
+            .ok_or_else(|| SimulationError::ModelNotFound(flat_component.id.clone()))?
+            .events_ext(message, services)
+            .map_err(|error| flat_component.nest_error(error))
+    }
+}
+
+impl ParkedMessage {
+    fn new(component_id: &str, port: &str, message: &ModelMessage) -> Self {
+        Self {
+            component_id: component_id.to_string(),
+            port: port.to_string(),
+            content: message.content.clone(),
+            job_id: message.job_id.clone(),
+            metadata: message.metadata.clone(),
+            payload: message.payload.clone(),
+            priority: message.priority,
+        }
+    }
+
+    fn to_message(&self) -> ModelMessage {
+        ModelMessage {
+            port_name: self.port.clone(),
+            content: self.content.clone(),
+            job_id: self.job_id.clone(),
+            metadata: self.metadata.clone(),
+            payload: self.payload.clone(),
+            priority: self.priority,
+        }
     }
 }
 
@@ -284,17 +602,23 @@ impl DevsModel for Coupled {
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
-        match self.park_incoming_messages(incoming_message) {
-            None => Ok(()),
-            Some(parked_messages) => self.distribute_events_ext(&parked_messages, services),
-        }
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
+        self.compile_routes()?;
+        self.distribute_events_ext(incoming_message, services)
     }
 
     fn events_int(
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        self.distribute_events_int(services)
+        self.compile_routes()?;
+        let outgoing_messages = self.distribute_events_int(services)?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -303,25 +627,80 @@ impl DevsModel for Coupled {
         });
     }
 
+    /// Messages on internal couplings are delivered at the time they are
+    /// sent, in the next step, as between top-level models.
     fn until_next_event(&self) -> f64 {
-        self.components.iter().fold(INFINITY, |min, component| {
-            f64::min(min, component.until_next_event())
-        })
+        if !self.state.parked_messages.is_empty() {
+            return 0.0;
+        }
+        self.components
+            .iter()
+            .fold(f64::INFINITY, |min, component| {
+                f64::min(min, component.until_next_event())
+            })
+    }
+
+    /// Jobs are held while parked on an internal coupling, or while held
+    /// by any component.
+    fn held_jobs(&self) -> Vec<&JobId> {
+        self.state
+            .parked_messages
+            .iter()
+            .filter_map(|parked_message| parked_message.job_id.as_ref())
+            .chain(
+                self.components
+                    .iter()
+                    .flat_map(|component| component.held_jobs()),
+            )
+            .collect()
+    }
+
+    fn components(&self) -> &[Model] {
+        &self.components
+    }
+
+    fn components_mut(&mut self) -> &mut [Model] {
+        &mut self.components
+    }
+
+    fn as_coupled(&self) -> Option<&Coupled> {
+        Some(self)
     }
 }
 
 impl Reportable for Coupled {
     fn status(&self) -> String {
         if self.state.parked_messages.is_empty() {
-            format!["Processing {} messages", self.state.parked_messages.len()]
-        } else {
             String::from("Processing no messages")
+        } else {
+            format!["Processing {} messages", self.state.parked_messages.len()]
         }
     }
 
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    /// The assumptions of the components, prefixed with the component IDs.
+    fn assumptions(&self) -> Vec<Assumption> {
+        self.components
+            .iter()
+            .flat_map(|component| {
+                component
+                    .assumptions()
+                    .into_iter()
+                    .map(move |mut assumption| {
+                        assumption.description =
+                            format!["{}: {}", component.id(), assumption.description];
+                        assumption
+                    })
+            })
+            .collect()
+    }
 }
 
 impl ReportableModel for Coupled {}
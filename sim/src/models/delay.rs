@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::assumption::{rng_stream_assumption, Assumption, AssumptionTopic};
+use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
+use super::{is_zero, ModelMessage, ModelRecord};
+use crate::input_modeling::dynamic_rng::DynRng;
+use crate::input_modeling::ContinuousRandomVariable;
+use crate::simulator::coupling::is_default_priority;
+use crate::simulator::{JobId, Payload, Services};
+use crate::utils::errors::SimulationError;
+
+use sim_derive::SerializableModel;
+
+#[cfg(feature = "simx")]
+use simx::event_rules;
+
+/// The delay holds each arriving job for a period of time, and then outputs
+/// the job - a pure transport element, such as a conveyor or a network
+/// link.  Unlike the processor, there is no queue and no contention, so any
+/// number of jobs may be in flight at once.  A random variable distribution
+/// dictates the delay of each job, drawn on arrival, so jobs may overtake
+/// one another.  Jobs with the same release time depart in arrival order.
+/// The metadata, payload, and priority of each job are carried through the
+/// delay.
+#[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
+#[serde(rename_all = "camelCase")]
+pub struct Delay {
+    delay: ContinuousRandomVariable,
+    ports_in: PortsIn,
+    ports_out: PortsOut,
+    // Named random number stream, and explicit seed, overriding the global
+    // stream assignment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rng_stream: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(default)]
+    store_records: bool,
+    #[serde(default)]
+    state: State,
+    #[serde(skip)]
+    rng: Option<DynRng>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PortsIn {
+    job: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum ArrivalPort {
+    Job,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PortsOut {
+    job: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    until_next_event: f64,
+    in_flight: Vec<InFlightJob>,
+    // Draws from the named or seeded random number stream, if any
+    #[serde(default, skip_serializing_if = "is_zero")]
+    stream_position: u64,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
+    records: Vec<ModelRecord>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            until_next_event: f64::INFINITY,
+            in_flight: Vec::new(),
+            stream_position: 0,
+            port_stats: PortStats::default(),
+            records: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InFlightJob {
+    content: String,
+    until_release: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    job_id: Option<JobId>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    metadata: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<Payload>,
+    #[serde(default, skip_serializing_if = "is_default_priority")]
+    priority: i32,
+}
+
+#[cfg_attr(feature = "simx", event_rules)]
+impl Delay {
+    pub fn new(
+        delay: ContinuousRandomVariable,
+        job_port: String,
+        delayed_job_port: String,
+        store_records: bool,
+        rng: Option<DynRng>,
+    ) -> Self {
+        Self {
+            delay,
+            ports_in: PortsIn { job: job_port },
+            ports_out: PortsOut {
+                job: delayed_job_port,
+            },
+            rng_stream: None,
+            seed: None,
+            store_records,
+            state: State::default(),
+            rng,
+        }
+    }
+
+    /// Draw the delays from a named random number stream, shared with any other
+    /// models configured with the same stream.
+    pub fn with_rng_stream(mut self, name: String) -> Self {
+        self.rng_stream = Some(name);
+        self
+    }
+
+    /// Pin the delays to an explicit seed, independent of the global seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    fn arrival_port(&self, message_port: &str) -> ArrivalPort {
+        if message_port == self.ports_in.job {
+            ArrivalPort::Job
+        } else {
+            ArrivalPort::Unknown
+        }
+    }
+
+    fn draw_delay(&mut self, services: &mut Services) -> Result<f64, SimulationError> {
+        let rng = services.model_rng(
+            self.seed,
+            self.rng_stream.as_deref(),
+            &mut self.state.stream_position,
+            self.rng.as_ref(),
+        );
+        let variate = if services.deterministic_mode() {
+            self.delay.mean_in(&services.sampling_context())?
+        } else {
+            self.delay
+                .random_variate_in(rng, &services.sampling_context())?
+        };
+        services.record_variate(&self.delay, variate);
+        Ok(variate)
+    }
+
+    fn schedule_next(&mut self) {
+        self.state.until_next_event = self
+            .state
+            .in_flight
+            .iter()
+            .map(|job| job.until_release)
+            .fold(f64::INFINITY, f64::min);
+    }
+
+    fn hold_job(
+        &mut self,
+        incoming_message: &ModelMessage,
+        services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        let delay = self.draw_delay(services)?;
+        self.state.in_flight.push(InFlightJob {
+            content: incoming_message.content.clone(),
+            until_release: delay,
+            job_id: incoming_message.job_id.clone(),
+            metadata: incoming_message.metadata.clone(),
+            payload: incoming_message.payload.clone(),
+            priority: incoming_message.priority,
+        });
+        self.schedule_next();
+        self.record(
+            services.global_time(),
+            String::from("Arrival"),
+            incoming_message.content.clone(),
+        );
+        Ok(())
+    }
+
+    fn release_jobs(&mut self, services: &mut Services) -> Vec<ModelMessage> {
+        let (released, in_flight): (Vec<InFlightJob>, Vec<InFlightJob>) = self
+            .state
+            .in_flight
+            .drain(..)
+            .partition(|job| job.until_release <= 0.0);
+        self.state.in_flight = in_flight;
+        self.schedule_next();
+        released.iter().for_each(|job| {
+            self.record(
+                services.global_time(),
+                String::from("Departure"),
+                job.content.clone(),
+            )
+        });
+        let port_name = self.ports_out.job.clone();
+        released
+            .into_iter()
+            .map(|job| ModelMessage {
+                port_name: port_name.clone(),
+                content: job.content,
+                job_id: job.job_id,
+                metadata: job.metadata,
+                payload: job.payload,
+                priority: job.priority,
+            })
+            .collect()
+    }
+
+    fn record(&mut self, time: f64, action: String, subject: String) {
+        if self.store_records {
+            self.state.records.push(ModelRecord {
+                time,
+                action,
+                subject,
+            });
+        }
+    }
+}
+
+#[cfg_attr(feature = "simx", event_rules)]
+impl DevsModel for Delay {
+    fn events_ext(
+        &mut self,
+        incoming_message: &ModelMessage,
+        services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
+        match self.arrival_port(&incoming_message.port_name) {
+            ArrivalPort::Job => self.hold_job(incoming_message, services),
+            ArrivalPort::Unknown => Err(SimulationError::InvalidMessage),
+        }
+    }
+
+    fn events_int(
+        &mut self,
+        services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        let outgoing_messages = self.release_jobs(services);
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
+    }
+
+    fn time_advance(&mut self, time_delta: f64) {
+        self.state.until_next_event -= time_delta;
+        self.state
+            .in_flight
+            .iter_mut()
+            .for_each(|job| job.until_release -= time_delta);
+    }
+
+    fn until_next_event(&self) -> f64 {
+        self.state.until_next_event
+    }
+
+    fn held_jobs(&self) -> Vec<&JobId> {
+        self.state
+            .in_flight
+            .iter()
+            .filter_map(|job| job.job_id.as_ref())
+            .collect()
+    }
+
+    fn migrate_state(&mut self, previous: &serde_yaml::Value) -> Result<(), SimulationError> {
+        // Keep the jobs in flight, so a changed delay applies from the next
+        // arrival
+        if let Some(state) = previous.get("state") {
+            self.state = serde_yaml::from_value(state.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl Reportable for Delay {
+    fn status(&self) -> String {
+        format!["Delaying {} jobs", self.state.in_flight.len()]
+    }
+
+    fn records(&self) -> &Vec<ModelRecord> {
+        &self.state.records
+    }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn queue_depth(&self) -> Option<usize> {
+        Some(self.state.in_flight.len())
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        let mut assumptions = vec![
+            Assumption::new(
+                AssumptionTopic::Delays,
+                "iid",
+                "Delays are independent and identically distributed, drawn on arrival",
+            ),
+            Assumption::new(
+                AssumptionTopic::Servers,
+                "infinite",
+                "There is no contention - any number of jobs are delayed at once, and jobs may overtake one another",
+            ),
+        ];
+        assumptions.extend(rng_stream_assumption(&self.rng_stream));
+        assumptions
+    }
+
+    fn zero_time_transit(&self) -> bool {
+        matches!(
+            self.delay,
+            ContinuousRandomVariable::Constant { value } if value == 0.0
+        )
+    }
+}
+
+impl ReportableModel for Delay {}
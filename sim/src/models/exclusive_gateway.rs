@@ -1,9 +1,11 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{rng_stream_assumption, Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
-use super::{ModelMessage, ModelRecord};
+use super::port_stats::PortStats;
+use super::{is_zero, ModelMessage, ModelRecord};
 use crate::input_modeling::dynamic_rng::DynRng;
 use crate::input_modeling::IndexRandomVariable;
 use crate::simulator::Services;
@@ -25,6 +27,12 @@ pub struct ExclusiveGateway {
     ports_in: PortsIn,
     ports_out: PortsOut,
     port_weights: IndexRandomVariable,
+    // Named random number stream, and explicit seed, overriding the global
+    // stream assignment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rng_stream: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
     #[serde(default)]
     store_records: bool,
     #[serde(default)]
@@ -50,7 +58,12 @@ struct PortsOut {
 struct State {
     phase: Phase,
     until_next_event: f64,
-    jobs: Vec<String>,         // port, message, time
+    jobs: Vec<String>, // port, message, time
+    // Draws from the named or seeded random number stream, if any
+    #[serde(default, skip_serializing_if = "is_zero")]
+    stream_position: u64,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>, // port, message, time
 }
 
@@ -58,8 +71,10 @@ impl Default for State {
     fn default() -> Self {
         State {
             phase: Phase::Passive,
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             jobs: Vec::new(),
+            stream_position: 0,
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -88,12 +103,27 @@ impl ExclusiveGateway {
                 flow_paths: flow_paths_out,
             },
             port_weights,
+            rng_stream: None,
+            seed: None,
             store_records,
             state: State::default(),
             rng,
         }
     }
 
+    /// Draw the routing decisions from a named random number stream, shared with any other
+    /// models configured with the same stream.
+    pub fn with_rng_stream(mut self, name: String) -> Self {
+        self.rng_stream = Some(name);
+        self
+    }
+
+    /// Pin the routing decisions to an explicit seed, independent of the global seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
     fn pass_job(&mut self, incoming_message: &ModelMessage, services: &mut Services) {
         self.state.phase = Phase::Pass;
         self.state.until_next_event = 0.0;
@@ -111,11 +141,15 @@ impl ExclusiveGateway {
 
     fn send_jobs(&mut self, services: &mut Services) -> Result<Vec<ModelMessage>, SimulationError> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
-        let departure_port_index = match &self.rng {
-            Some(rng) => self.port_weights.random_variate(rng.clone())?,
-            None => self.port_weights.random_variate(services.global_rng())?,
-        };
+        self.state.until_next_event = f64::INFINITY;
+        let rng = services.model_rng(
+            self.seed,
+            self.rng_stream.as_deref(),
+            &mut self.state.stream_position,
+            self.rng.as_ref(),
+        );
+        let departure_port_index = self.port_weights.random_variate(rng)?;
+        services.record_variate(&self.port_weights, departure_port_index as f64);
         Ok((0..self.state.jobs.len())
             .map(|_| {
                 self.record(
@@ -130,6 +164,10 @@ impl ExclusiveGateway {
                 ModelMessage {
                     port_name: self.ports_out.flow_paths[departure_port_index].clone(),
                     content: self.state.jobs.remove(0),
+                    job_id: None,
+                    metadata: HashMap::new(),
+                    payload: None,
+                    priority: 0,
                 }
             })
             .collect())
@@ -137,7 +175,7 @@ impl ExclusiveGateway {
 
     fn passivate(&mut self) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         Vec::new()
     }
 
@@ -154,11 +192,15 @@ impl ExclusiveGateway {
 
 #[cfg_attr(feature = "simx", event_rules)]
 impl DevsModel for ExclusiveGateway {
+    #[allow(clippy::unit_arg)]
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         Ok(self.pass_job(incoming_message, services))
     }
 
@@ -166,10 +208,14 @@ impl DevsModel for ExclusiveGateway {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match &self.state.phase {
+        let outgoing_messages = match &self.state.phase {
             Phase::Passive => Ok(self.passivate()),
             Phase::Pass => self.send_jobs(services),
-        }
+        }?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -192,6 +238,32 @@ impl Reportable for ExclusiveGateway {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn queue_depth(&self) -> Option<usize> {
+        Some(self.state.jobs.len())
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        let mut assumptions = vec![Assumption::new(
+            AssumptionTopic::Routing,
+            "probabilistic",
+            "Each job takes a single path, chosen independently by the configured weights, regardless of the downstream system state",
+        )];
+        assumptions.extend(rng_stream_assumption(&self.rng_stream));
+        assumptions
+    }
+
+    fn zero_time_transit(&self) -> bool {
+        true
+    }
 }
 
 impl ReportableModel for ExclusiveGateway {}
@@ -1,8 +1,10 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
 use super::{ModelMessage, ModelRecord};
 use crate::simulator::Services;
 use crate::utils::errors::SimulationError;
@@ -55,6 +57,8 @@ struct State {
     phase: Phase,
     until_next_event: f64,
     jobs: Vec<String>,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
@@ -62,8 +66,9 @@ impl Default for State {
     fn default() -> Self {
         Self {
             phase: Phase::Open,
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             jobs: Vec::new(),
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -111,7 +116,7 @@ impl Gate {
 
     fn activate(&mut self, incoming_message: &ModelMessage, services: &mut Services) {
         self.state.phase = Phase::Open;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         self.record(
             services.global_time(),
             String::from("Activation"),
@@ -121,7 +126,7 @@ impl Gate {
 
     fn deactivate(&mut self, incoming_message: &ModelMessage, services: &mut Services) {
         self.state.phase = Phase::Closed;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         self.record(
             services.global_time(),
             String::from("Deactivation"),
@@ -150,7 +155,7 @@ impl Gate {
 
     fn send_jobs(&mut self, services: &mut Services) -> Vec<ModelMessage> {
         self.state.phase = Phase::Open;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         (0..self.state.jobs.len())
             .map(|_| {
                 self.record(
@@ -161,6 +166,10 @@ impl Gate {
                 ModelMessage {
                     port_name: self.ports_out.job.clone(),
                     content: self.state.jobs.remove(0),
+                    job_id: None,
+                    metadata: HashMap::new(),
+                    payload: None,
+                    priority: 0,
                 }
             })
             .collect()
@@ -179,11 +188,15 @@ impl Gate {
 
 #[cfg_attr(feature = "simx", event_rules)]
 impl DevsModel for Gate {
+    #[allow(clippy::unit_arg)]
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         match (
             self.arrival_port(&incoming_message.port_name),
             self.state.phase == Phase::Closed,
@@ -200,7 +213,11 @@ impl DevsModel for Gate {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        Ok(self.send_jobs(services))
+        let outgoing_messages = self.send_jobs(services);
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -224,6 +241,30 @@ impl Reportable for Gate {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn queue_depth(&self) -> Option<usize> {
+        Some(self.state.jobs.len())
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            AssumptionTopic::Blocking,
+            "drop",
+            "Jobs arriving at the closed gate are dropped, rather than queued or redirected",
+        )]
+    }
+
+    fn zero_time_transit(&self) -> bool {
+        true
+    }
 }
 
 impl ReportableModel for Gate {}
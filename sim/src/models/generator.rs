@@ -1,11 +1,15 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{rng_stream_assumption, Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
-use super::{ModelMessage, ModelRecord};
+use super::port_stats::PortStats;
+use super::{is_zero, ModelMessage, ModelRecord};
 use crate::input_modeling::dynamic_rng::DynRng;
 use crate::input_modeling::ContinuousRandomVariable;
 use crate::input_modeling::Thinning;
-use crate::simulator::Services;
+use crate::simulator::{JobId, Services};
 use crate::utils::errors::SimulationError;
 
 use sim_derive::SerializableModel;
@@ -15,12 +19,18 @@ use simx::event_rules;
 
 /// The generator produces jobs based on a configured interarrival
 /// distribution. A normalized thinning function is used to enable
-/// non-stationary job generation. For non-stochastic generation of jobs, a
+/// non-stationary job generation - the interarrival distribution sets the
+/// peak generation rate, and each candidate job is kept with a probability
+/// of the thinning function at the candidate time. For non-stochastic generation of jobs, a
 /// random variable distribution with a single point can be used - in which
 /// case, the time between job generation is constant. This model will
 /// produce jobs through perpetuity, and the generator does not receive
 /// messages or otherwise change behavior throughout a simulation (except
-/// through the thinning function).
+/// through the thinning function).  Generators configured with the same
+/// named random number stream draw their interdeparture times from the same
+/// underlying uniforms, for correlated arrival streams (e.g. upstream demand
+/// that splits into correlated sub-streams).  An explicit seed pins the
+/// generation, independent of the global seed.
 #[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
 #[serde(rename_all = "camelCase")]
 pub struct Generator {
@@ -29,6 +39,12 @@ pub struct Generator {
     // Thinning for non-stationarity
     #[serde(default)]
     thinning: Option<Thinning>,
+    // Named random number stream, for correlation across generators
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rng_stream: Option<String>,
+    // Explicit seed, overriding the global seed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
     ports_in: PortsIn,
     ports_out: PortsOut,
     #[serde(default)]
@@ -54,6 +70,11 @@ struct State {
     until_next_event: f64,
     until_job: f64,
     last_job: usize,
+    // Draws from the named or seeded random number stream, if any
+    #[serde(default, skip_serializing_if = "is_zero")]
+    stream_position: u64,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
@@ -64,6 +85,8 @@ impl Default for State {
             until_next_event: 0.0,
             until_job: 0.0,
             last_job: 0,
+            stream_position: 0,
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -87,6 +110,8 @@ impl Generator {
         Self {
             message_interdeparture_time,
             thinning,
+            rng_stream: None,
+            seed: None,
             ports_in: PortsIn {},
             ports_out: PortsOut { job: job_port },
             store_records,
@@ -95,21 +120,68 @@ impl Generator {
         }
     }
 
+    /// Draw the interdeparture times from a named random number stream,
+    /// shared with any other generators configured with the same stream.
+    pub fn with_rng_stream(mut self, name: String) -> Self {
+        self.rng_stream = Some(name);
+        self
+    }
+
+    /// Pin the interdeparture times to an explicit seed, independent of the
+    /// global seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    fn draw_interdeparture(&mut self, services: &mut Services) -> Result<f64, SimulationError> {
+        let rng = services.model_rng(
+            self.seed,
+            self.rng_stream.as_deref(),
+            &mut self.state.stream_position,
+            self.rng.as_ref(),
+        );
+        let interdeparture = if services.deterministic_mode() {
+            self.message_interdeparture_time
+                .mean_in(&services.sampling_context())?
+        } else {
+            self.message_interdeparture_time
+                .random_variate_in(rng, &services.sampling_context())?
+        };
+        services.record_variate(&self.message_interdeparture_time, interdeparture);
+        Ok(interdeparture)
+    }
+
+    /// Whether the candidate job at the current time survives thinning.
+    /// The thinning draw remains stochastic in deterministic mode, like
+    /// other boolean variates.
+    fn accepts_job(&mut self, services: &mut Services) -> Result<bool, SimulationError> {
+        match &self.thinning {
+            Some(thinning) => {
+                let rng = services.model_rng(
+                    self.seed,
+                    self.rng_stream.as_deref(),
+                    &mut self.state.stream_position,
+                    self.rng.as_ref(),
+                );
+                thinning.accepts(services.global_time(), rng)
+            }
+            None => Ok(true),
+        }
+    }
+
     fn release_job(
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        let interdeparture = match &self.rng {
-            Some(rng) => self
-                .message_interdeparture_time
-                .random_variate(rng.clone())?,
-            None => self
-                .message_interdeparture_time
-                .random_variate(services.global_rng())?,
-        };
+        let accepted = self.accepts_job(services)?;
+        let interdeparture = self.draw_interdeparture(services)?;
         self.state.phase = Phase::Generating;
         self.state.until_next_event = interdeparture;
         self.state.until_job = interdeparture;
+        if !accepted {
+            return Ok(Vec::new());
+        }
         self.state.last_job += 1;
         self.record(
             services.global_time(),
@@ -119,6 +191,12 @@ impl Generator {
         Ok(vec![ModelMessage {
             port_name: self.ports_out.job.clone(),
             content: format!["{} {}", self.ports_out.job, self.state.last_job],
+            job_id: services
+                .current_model_id()
+                .map(|model_id| JobId::new(model_id.to_string(), self.state.last_job)),
+            metadata: HashMap::new(),
+            payload: None,
+            priority: 0,
         }])
     }
 
@@ -126,14 +204,7 @@ impl Generator {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        let interdeparture = match &self.rng {
-            Some(rng) => self
-                .message_interdeparture_time
-                .random_variate(rng.clone())?,
-            None => self
-                .message_interdeparture_time
-                .random_variate(services.global_rng())?,
-        };
+        let interdeparture = self.draw_interdeparture(services)?;
         self.state.phase = Phase::Generating;
         self.state.until_next_event = interdeparture;
         self.state.until_job = interdeparture;
@@ -160,9 +231,12 @@ impl Generator {
 impl DevsModel for Generator {
     fn events_ext(
         &mut self,
-        _incoming_message: &ModelMessage,
-        _services: &mut Services,
+        incoming_message: &ModelMessage,
+        services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         Ok(())
     }
 
@@ -170,10 +244,14 @@ impl DevsModel for Generator {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match &self.state.phase {
+        let outgoing_messages = match &self.state.phase {
             Phase::Generating => self.release_job(services),
             Phase::Initializing => self.initialize_generation(services),
-        }
+        }?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -183,6 +261,15 @@ impl DevsModel for Generator {
     fn until_next_event(&self) -> f64 {
         self.state.until_next_event
     }
+
+    fn migrate_state(&mut self, previous: &serde_yaml::Value) -> Result<(), SimulationError> {
+        // Keep the pending generation, so a changed interdeparture time applies
+        // from the next generation
+        if let Some(state) = previous.get("state") {
+            self.state = serde_yaml::from_value(state.clone())?;
+        }
+        Ok(())
+    }
 }
 
 impl Reportable for Generator {
@@ -193,6 +280,39 @@ impl Reportable for Generator {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        let arrival_process = match self.thinning {
+            None => Assumption::new(
+                AssumptionTopic::ArrivalProcess,
+                "renewal",
+                "Interdeparture times are independent and identically distributed",
+            ),
+            Some(_) => Assumption::new(
+                AssumptionTopic::ArrivalProcess,
+                "thinned",
+                "Arrivals are nonstationary - candidate arrivals at the peak rate are kept with the probability of the thinning function",
+            ),
+        };
+        let mut assumptions = vec![
+            arrival_process,
+            Assumption::new(
+                AssumptionTopic::ArrivalProcess,
+                "infinite-population",
+                "Jobs are generated indefinitely, independent of the downstream system state",
+            ),
+        ];
+        assumptions.extend(rng_stream_assumption(&self.rng_stream));
+        assumptions
+    }
 }
 
 impl ReportableModel for Generator {}
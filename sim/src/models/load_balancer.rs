@@ -1,8 +1,10 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
 use super::{ModelMessage, ModelRecord};
 use crate::simulator::Services;
 use crate::utils::errors::SimulationError;
@@ -43,6 +45,8 @@ struct State {
     until_next_event: f64,
     next_port_out: usize,
     jobs: Vec<String>,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
@@ -50,9 +54,10 @@ impl Default for State {
     fn default() -> Self {
         Self {
             phase: Phase::Passive,
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             next_port_out: 0,
             jobs: Vec::new(),
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -90,7 +95,7 @@ impl LoadBalancer {
 
     fn passivate(&mut self) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         Vec::new()
     }
 
@@ -109,6 +114,10 @@ impl LoadBalancer {
         vec![ModelMessage {
             port_name: self.ports_out.flow_paths[self.state.next_port_out].clone(),
             content: self.state.jobs.remove(0),
+            job_id: None,
+            metadata: HashMap::new(),
+            payload: None,
+            priority: 0,
         }]
     }
 
@@ -125,11 +134,15 @@ impl LoadBalancer {
 
 #[cfg_attr(feature = "simx", event_rules)]
 impl DevsModel for LoadBalancer {
+    #[allow(clippy::unit_arg)]
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         Ok(self.pass_job(incoming_message, services))
     }
 
@@ -137,10 +150,14 @@ impl DevsModel for LoadBalancer {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match self.state.jobs.len() {
-            0 => Ok(self.passivate()),
-            _ => Ok(self.send_job(services)),
-        }
+        let outgoing_messages = match self.state.jobs.len() {
+            0 => self.passivate(),
+            _ => self.send_job(services),
+        };
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -160,6 +177,30 @@ impl Reportable for LoadBalancer {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn queue_depth(&self) -> Option<usize> {
+        Some(self.state.jobs.len())
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            AssumptionTopic::Routing,
+            "round-robin",
+            "Jobs are routed round robin, regardless of the load on the downstream paths",
+        )]
+    }
+
+    fn zero_time_transit(&self) -> bool {
+        true
+    }
 }
 
 impl ReportableModel for LoadBalancer {}
@@ -3,18 +3,39 @@
 //! specifies the requirements of any additional custom models, via the
 //! `Model` trait.
 
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::simulator::{JobId, Payload};
+use crate::utils::errors::SimulationError;
+
+pub mod assumption;
+#[cfg(feature = "batcher")]
 pub mod batcher;
 pub mod coupled;
+#[cfg(feature = "delay")]
+pub mod delay;
+#[cfg(feature = "exclusive-gateway")]
 pub mod exclusive_gateway;
+#[cfg(feature = "gate")]
 pub mod gate;
 pub mod generator;
+#[cfg(feature = "load-balancer")]
 pub mod load_balancer;
 pub mod model;
+#[cfg(feature = "parallel-gateway")]
 pub mod parallel_gateway;
+pub mod port_stats;
 pub mod processor;
+pub mod query;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+pub mod sink;
+#[cfg(feature = "stochastic-gate")]
 pub mod stochastic_gate;
+#[cfg(feature = "stopwatch")]
 pub mod stopwatch;
 pub mod storage;
 
@@ -22,17 +43,32 @@ pub mod model_factory;
 pub mod model_repr;
 pub mod model_trait;
 
+pub use self::assumption::{Assumption, AssumptionTopic};
+#[cfg(feature = "batcher")]
 pub use self::batcher::Batcher;
 pub use self::coupled::{Coupled, ExternalInputCoupling, ExternalOutputCoupling, InternalCoupling};
+#[cfg(feature = "delay")]
+pub use self::delay::Delay;
+#[cfg(feature = "exclusive-gateway")]
 pub use self::exclusive_gateway::ExclusiveGateway;
+#[cfg(feature = "gate")]
 pub use self::gate::Gate;
 pub use self::generator::Generator;
+#[cfg(feature = "load-balancer")]
 pub use self::load_balancer::LoadBalancer;
 pub use self::model::Model;
 pub use self::model_trait::{DevsModel, Reportable, ReportableModel};
+#[cfg(feature = "parallel-gateway")]
 pub use self::parallel_gateway::ParallelGateway;
-pub use self::processor::Processor;
+pub use self::port_stats::{PortActivity, PortStats};
+pub use self::processor::{Processor, UtilizationSummary};
+pub use self::query::Query;
+#[cfg(feature = "scheduler")]
+pub use self::scheduler::{ScheduleRule, Scheduler};
+pub use self::sink::{Sink, SinkSummary};
+#[cfg(feature = "stochastic-gate")]
 pub use self::stochastic_gate::StochasticGate;
+#[cfg(feature = "stopwatch")]
 pub use self::stopwatch::Stopwatch;
 pub use self::storage::Storage;
 
@@ -42,6 +78,31 @@ pub use self::model_repr::ModelRepr;
 pub struct ModelMessage {
     pub port_name: String,
     pub content: String,
+    pub job_id: Option<JobId>,
+    /// Message metadata (e.g. priority, class, or custom tags) is inherited
+    /// from the incoming message of the same job, and models may augment it
+    pub metadata: HashMap<String, String>,
+    /// A structured payload, inherited from the incoming message of the
+    /// same job, unless the model sets its own
+    pub payload: Option<Payload>,
+    /// The delivery priority of the message (0 by default) - within a
+    /// simulation step, higher priority messages are delivered first, for
+    /// interrupt-style control messages
+    pub priority: i32,
+}
+
+impl ModelMessage {
+    /// The JSON payload of the message, as a typed value.
+    pub fn payload_as<T: DeserializeOwned>(&self) -> Result<T, SimulationError> {
+        self.payload
+            .as_ref()
+            .ok_or(SimulationError::InvalidPayload)?
+            .decode()
+    }
+}
+
+pub(crate) fn is_zero(value: &u64) -> bool {
+    *value == 0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,10 +1,15 @@
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use super::assumption::Assumption;
+use super::coupled::Coupled;
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
+use super::processor::UtilizationSummary;
+use super::sink::SinkSummary;
 use super::{ModelMessage, ModelRecord};
-use crate::simulator::Services;
-use crate::utils::errors::SimulationError;
+use crate::simulator::{JobId, Services};
+use crate::utils::errors::{ErrorContext, SimulationError};
 
 /// `Model` wraps `model_type` and provides common ID functionality (a struct
 /// field and associated accessor method).  The simulator requires all models
@@ -13,16 +18,71 @@ use crate::utils::errors::SimulationError;
 pub struct Model {
     id: String,
     inner: Box<dyn ReportableModel>,
+    // The registered model type of a configured model, which may be
+    // namespaced (e.g. "myco::Conveyor")
+    registered_type: Option<String>,
+    // The pinned random number stream index, under per-model streams
+    rng_stream_index: Option<u64>,
 }
 
 impl Model {
     pub fn new(id: String, inner: Box<dyn ReportableModel>) -> Self {
-        Self { id, inner }
+        Self {
+            id,
+            inner,
+            registered_type: None,
+            rng_stream_index: None,
+        }
+    }
+
+    /// Pin the model to a random number stream index, under per-model
+    /// random number streams.  The stream then follows the index, rather
+    /// than the model ID - so renamed or substituted models keep their
+    /// sample path across scenario variants.
+    pub fn with_rng_stream_index(mut self, rng_stream_index: u64) -> Self {
+        self.rng_stream_index = Some(rng_stream_index);
+        self
+    }
+
+    pub fn rng_stream_index(&self) -> Option<u64> {
+        self.rng_stream_index
     }
 
     pub fn id(&self) -> &str {
         self.id.as_str()
     }
+
+    /// The type of the wrapped model, as used in model configurations.
+    pub fn model_type(&self) -> &str {
+        self.registered_type
+            .as_deref()
+            .unwrap_or_else(|| self.inner.get_type())
+    }
+
+    /// Attach the context of an error raised in a state transition of the
+    /// model.  Errors of nested components already carry the component
+    /// model ID, which is prefixed with the model ID, for the path of the
+    /// component (e.g. `line-01/processor-01`).
+    fn contextualize(
+        &self,
+        error: SimulationError,
+        services: &Services,
+        port: Option<&str>,
+    ) -> SimulationError {
+        if error
+            .context()
+            .is_some_and(|context| context.model_id.is_some())
+        {
+            return error.nested_in(&self.id);
+        }
+        let context = ErrorContext::new(self.model_type())
+            .with_model_id(&self.id)
+            .with_time(services.global_time());
+        error.with_context(match port {
+            Some(port) => context.with_port(port),
+            None => context,
+        })
+    }
 }
 
 impl Serialize for Model {
@@ -30,7 +90,10 @@ impl Serialize for Model {
         let extra_fields: serde_yaml::Value = self.inner.serialize();
         let mut model = serializer.serialize_map(None)?;
         model.serialize_entry("id", &self.id)?;
-        model.serialize_entry("type", self.inner.get_type())?;
+        model.serialize_entry("type", self.model_type())?;
+        if let Some(rng_stream_index) = self.rng_stream_index {
+            model.serialize_entry("rngStreamIndex", &rng_stream_index)?;
+        }
         if let serde_yaml::Value::Mapping(map) = extra_fields {
             for (key, value) in map.iter() {
                 model.serialize_entry(&key, &value)?;
@@ -45,7 +108,12 @@ impl<'de> Deserialize<'de> for Model {
         let model_repr = super::ModelRepr::deserialize(deserializer)?;
         let concrete_model =
             super::model_factory::create::<D>(&model_repr.model_type[..], model_repr.extra)?;
-        Ok(Model::new(model_repr.id, concrete_model))
+        let mut model = Model::new(model_repr.id, concrete_model);
+        model.rng_stream_index = model_repr.rng_stream_index;
+        if model.inner.get_type() != model_repr.model_type {
+            model.registered_type = Some(model_repr.model_type);
+        }
+        Ok(model)
     }
 }
 
@@ -57,14 +125,27 @@ impl DevsModel for Model {
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
-        self.inner.events_ext(incoming_message, services)
+        let parent_model_id = services.current_model_id.replace(self.id.clone());
+        let parent_stream_index =
+            std::mem::replace(&mut services.current_stream_index, self.rng_stream_index);
+        let result = self.inner.events_ext(incoming_message, services);
+        services.current_model_id = parent_model_id;
+        services.current_stream_index = parent_stream_index;
+        result
+            .map_err(|error| self.contextualize(error, services, Some(&incoming_message.port_name)))
     }
 
     fn events_int(
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        self.inner.events_int(services)
+        let parent_model_id = services.current_model_id.replace(self.id.clone());
+        let parent_stream_index =
+            std::mem::replace(&mut services.current_stream_index, self.rng_stream_index);
+        let result = self.inner.events_int(services);
+        services.current_model_id = parent_model_id;
+        services.current_stream_index = parent_stream_index;
+        result.map_err(|error| self.contextualize(error, services, None))
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -75,6 +156,26 @@ impl DevsModel for Model {
         self.inner.until_next_event()
     }
 
+    fn migrate_state(&mut self, previous: &serde_yaml::Value) -> Result<(), SimulationError> {
+        self.inner.migrate_state(previous)
+    }
+
+    fn held_jobs(&self) -> Vec<&JobId> {
+        self.inner.held_jobs()
+    }
+
+    fn components(&self) -> &[Model] {
+        self.inner.components()
+    }
+
+    fn components_mut(&mut self) -> &mut [Model] {
+        self.inner.components_mut()
+    }
+
+    fn as_coupled(&self) -> Option<&Coupled> {
+        self.inner.as_coupled()
+    }
+
     #[cfg(feature = "simx")]
     fn event_rules_scheduling(&self) -> &str {
         self.inner.event_rules_scheduling()
@@ -94,6 +195,38 @@ impl Reportable for Model {
     fn records(&self) -> &Vec<ModelRecord> {
         self.inner.records()
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.inner.set_store_records(store_records)
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        self.inner.port_stats()
+    }
+
+    fn queue_depth(&self) -> Option<usize> {
+        self.inner.queue_depth()
+    }
+
+    fn status_detail(&self, time: f64) -> String {
+        self.inner.status_detail(time)
+    }
+
+    fn utilization(&self, time: f64) -> Option<UtilizationSummary> {
+        self.inner.utilization(time)
+    }
+
+    fn sink_summary(&self, time: f64) -> Option<SinkSummary> {
+        self.inner.sink_summary(time)
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        self.inner.assumptions()
+    }
+
+    fn zero_time_transit(&self) -> bool {
+        self.inner.zero_time_transit()
+    }
 }
 
 impl ReportableModel for Model {}
@@ -1,4 +1,5 @@
 use super::model_trait::ReportableModel;
+use crate::utils::errors::SimulationError;
 use serde::de;
 use serde::Deserializer;
 use std::collections::HashMap;
@@ -7,71 +8,150 @@ use lazy_static::lazy_static;
 
 use std::sync::Mutex;
 
+/// The separator between the namespace and the name of a namespaced model
+/// type (e.g. "myco::Conveyor").
+pub const NAMESPACE_SEPARATOR: &str = "::";
+
 pub type ModelConstructor = fn(serde_yaml::Value) -> Option<Box<dyn ReportableModel>>;
 lazy_static! {
-    static ref CONSTRUCTORS: Mutex<HashMap<&'static str, ModelConstructor>> = {
+    static ref CONSTRUCTORS: Mutex<HashMap<String, ModelConstructor>> = {
         let mut m = HashMap::new();
-        m.insert("Batcher", super::Batcher::from_value as ModelConstructor);
+        #[cfg(feature = "batcher")]
+        m.insert(
+            String::from("Batcher"),
+            super::Batcher::from_value as ModelConstructor,
+        );
+        #[cfg(feature = "delay")]
         m.insert(
-            "ExclusiveGateway",
+            String::from("Delay"),
+            super::Delay::from_value as ModelConstructor,
+        );
+        #[cfg(feature = "exclusive-gateway")]
+        m.insert(
+            String::from("ExclusiveGateway"),
             super::ExclusiveGateway::from_value as ModelConstructor,
         );
-        m.insert("Gate", super::Gate::from_value as ModelConstructor);
+        #[cfg(feature = "gate")]
+        m.insert(
+            String::from("Gate"),
+            super::Gate::from_value as ModelConstructor,
+        );
         m.insert(
-            "Generator",
+            String::from("Generator"),
             super::Generator::from_value as ModelConstructor,
         );
+        #[cfg(feature = "load-balancer")]
         m.insert(
-            "LoadBalancer",
+            String::from("LoadBalancer"),
             super::LoadBalancer::from_value as ModelConstructor,
         );
+        #[cfg(feature = "parallel-gateway")]
         m.insert(
-            "ParallelGateway",
+            String::from("ParallelGateway"),
             super::ParallelGateway::from_value as ModelConstructor,
         );
         m.insert(
-            "Processor",
+            String::from("Processor"),
             super::Processor::from_value as ModelConstructor,
         );
+        #[cfg(feature = "scheduler")]
         m.insert(
-            "StochasticGate",
+            String::from("Scheduler"),
+            super::Scheduler::from_value as ModelConstructor,
+        );
+        #[cfg(feature = "stochastic-gate")]
+        m.insert(
+            String::from("StochasticGate"),
             super::StochasticGate::from_value as ModelConstructor,
         );
+        #[cfg(feature = "stopwatch")]
         m.insert(
-            "Stopwatch",
+            String::from("Stopwatch"),
             super::Stopwatch::from_value as ModelConstructor,
         );
-        m.insert("Storage", super::Storage::from_value as ModelConstructor);
+        m.insert(
+            String::from("Sink"),
+            super::Sink::from_value as ModelConstructor,
+        );
+        m.insert(
+            String::from("Storage"),
+            super::Storage::from_value as ModelConstructor,
+        );
         Mutex::new(m)
     };
-    static ref VARIANTS: Vec<&'static str> = {
-        CONSTRUCTORS
-            .lock()
-            .unwrap()
-            .iter()
-            .map(|(k, _)| k)
-            .copied()
-            .collect::<Vec<_>>()
-    };
 }
 
-pub fn register(model_type: &'static str, model_constructor: ModelConstructor) {
-    CONSTRUCTORS
-        .lock()
-        .unwrap()
-        .insert(model_type, model_constructor);
+fn is_valid_model_type(model_type: &str) -> bool {
+    model_type.split(NAMESPACE_SEPARATOR).all(|segment| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|character| character.is_alphanumeric() || character == '_')
+    })
+}
+
+/// Register a model constructor, for configurations to reference the model
+/// by type.  Third-party model types may be namespaced (e.g.
+/// "myco::Conveyor"), to avoid collisions between model ecosystems.
+/// Registering a type that is already registered, including the built-in
+/// model types, is an error.
+pub fn register(
+    model_type: &str,
+    model_constructor: ModelConstructor,
+) -> Result<(), SimulationError> {
+    if !is_valid_model_type(model_type) {
+        return Err(SimulationError::InvalidModelType(model_type.to_string()));
+    }
+    let mut constructors = CONSTRUCTORS.lock().unwrap();
+    if constructors.contains_key(model_type) {
+        return Err(SimulationError::DuplicateModelType(model_type.to_string()));
+    }
+    constructors.insert(model_type.to_string(), model_constructor);
+    Ok(())
+}
+
+/// Register a model constructor under a namespace, as the model type
+/// "namespace::model_type".
+pub fn register_namespaced(
+    namespace: &str,
+    model_type: &str,
+    model_constructor: ModelConstructor,
+) -> Result<(), SimulationError> {
+    register(
+        &format!["{}{}{}", namespace, NAMESPACE_SEPARATOR, model_type],
+        model_constructor,
+    )
+}
+
+/// Whether a constructor is registered for the model type.
+pub fn is_registered(model_type: &str) -> bool {
+    CONSTRUCTORS.lock().unwrap().contains_key(model_type)
+}
+
+/// The registered model types, including the built-in model types, in
+/// sorted order.
+pub fn registered() -> Vec<String> {
+    let mut model_types: Vec<String> = CONSTRUCTORS.lock().unwrap().keys().cloned().collect();
+    model_types.sort();
+    model_types
 }
 
 pub fn create<'de, D: Deserializer<'de>>(
     model_type: &str,
     extra_fields: serde_yaml::Value,
 ) -> Result<Box<dyn ReportableModel>, D::Error> {
-    let model = match CONSTRUCTORS.lock().unwrap().get(model_type) {
-        Some(constructor) => constructor(extra_fields),
-        None => None,
-    };
-    match model {
-        Some(model) => Ok(model),
-        None => Err(de::Error::unknown_variant(model_type, &VARIANTS)),
+    let constructor = CONSTRUCTORS.lock().unwrap().get(model_type).copied();
+    match constructor {
+        Some(constructor) => constructor(extra_fields).ok_or_else(|| {
+            de::Error::custom(format![
+                "invalid configuration for model type `{}`",
+                model_type
+            ])
+        }),
+        None => Err(de::Error::custom(format![
+            "unknown model type `{}`, expected one of `{}`",
+            model_type,
+            registered().join("`, `")
+        ])),
     }
 }
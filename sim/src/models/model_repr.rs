@@ -5,6 +5,8 @@ pub struct ModelRepr {
     pub id: String,
     #[serde(rename = "type")]
     pub model_type: String,
+    #[serde(default, rename = "rngStreamIndex")]
+    pub rng_stream_index: Option<u64>,
     #[serde(flatten)]
     pub extra: serde_yaml::Value,
 }
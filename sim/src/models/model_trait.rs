@@ -1,5 +1,10 @@
-use super::{ModelMessage, ModelRecord};
-use crate::simulator::Services;
+use super::assumption::Assumption;
+use super::coupled::Coupled;
+use super::port_stats::PortStats;
+use super::processor::UtilizationSummary;
+use super::sink::SinkSummary;
+use super::{Model, ModelMessage, ModelRecord};
+use crate::simulator::{JobId, Services};
 use crate::utils::errors::SimulationError;
 
 pub trait ModelClone {
@@ -34,7 +39,7 @@ pub trait SerializableModel {
 /// within the discrete event simulation.  The simulator formalism (Discrete
 /// Event System Specification) requires `events_ext`, `events_int`,
 /// `time_advance`, and `until_next_event`.
-pub trait DevsModel: ModelClone + SerializableModel {
+pub trait DevsModel: ModelClone + SerializableModel + Send + Sync {
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
@@ -44,6 +49,35 @@ pub trait DevsModel: ModelClone + SerializableModel {
         -> Result<Vec<ModelMessage>, SimulationError>;
     fn time_advance(&mut self, time_delta: f64);
     fn until_next_event(&self) -> f64;
+    /// Migrate the dynamic state of a previous instance of the model, when
+    /// the model configuration is patched or replaced mid-run.  The
+    /// previous instance is provided in its serialized form.  By default,
+    /// the state is reset - the new instance keeps its initial state.
+    fn migrate_state(&mut self, _previous: &serde_yaml::Value) -> Result<(), SimulationError> {
+        Ok(())
+    }
+    /// The IDs of the jobs the model holds (e.g. queued, in service, or in
+    /// flight), to be emitted later with the same job IDs.  The simulator
+    /// releases the correlation context of a job once the model no longer
+    /// holds it.  By default, models hold no jobs across transitions.
+    fn held_jobs(&self) -> Vec<&JobId> {
+        Vec::new()
+    }
+    /// The component models of a coupled model, for hierarchical model
+    /// lookups.  Atomic models have no components.
+    fn components(&self) -> &[Model] {
+        &[]
+    }
+    /// The mutable component models of a coupled model, for the delivery
+    /// of flattened routes.  Atomic models have no components.
+    fn components_mut(&mut self) -> &mut [Model] {
+        &mut []
+    }
+    /// The model as a coupled model, for couplings flattened through the
+    /// model hierarchy.  Atomic models are not coupled.
+    fn as_coupled(&self) -> Option<&Coupled> {
+        None
+    }
     #[cfg(feature = "simx")]
     fn event_rules_scheduling(&self) -> &str;
     #[cfg(feature = "simx")]
@@ -56,6 +90,45 @@ pub trait DevsModel: ModelClone + SerializableModel {
 pub trait Reportable {
     fn status(&self) -> String;
     fn records(&self) -> &Vec<ModelRecord>;
+    /// Switch record keeping on or off at runtime, for models with
+    /// optional record keeping.
+    fn set_store_records(&mut self, _store_records: bool) {}
+    /// The per-port message traffic of the model, if tracked.
+    fn port_stats(&self) -> Option<&PortStats> {
+        None
+    }
+    /// The number of jobs currently held by the model (queued, or in
+    /// service), for models with an internal queue.
+    fn queue_depth(&self) -> Option<usize> {
+        None
+    }
+    /// The status, with the key performance indicators of the model, as
+    /// of the provided simulation time.  Models without indicators provide
+    /// the plain status.
+    fn status_detail(&self, _time: f64) -> String {
+        self.status()
+    }
+    /// The utilization of a processing model, as of the provided simulation
+    /// time.  Models without service provide `None`.
+    fn utilization(&self, _time: f64) -> Option<UtilizationSummary> {
+        None
+    }
+    /// The running departure statistics of a sink model, as of the provided
+    /// simulation time.  Models other than sinks provide `None`.
+    fn sink_summary(&self, _time: f64) -> Option<SinkSummary> {
+        None
+    }
+    /// The statistical and behavioral assumptions of the model, for model
+    /// reviews and V&V documentation.
+    fn assumptions(&self) -> Vec<Assumption> {
+        Vec::new()
+    }
+    /// Whether the model passes messages on without advancing the
+    /// simulation time (e.g. gateways), so a cycle of such models never
+    /// advances the simulation clock.
+    fn zero_time_transit(&self) -> bool {
+        false
+    }
 }
 
 /// A `ReportableModel` has the required Discrete Event System Specification
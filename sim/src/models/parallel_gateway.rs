@@ -1,9 +1,10 @@
 use std::collections::HashMap;
-use std::f64::INFINITY;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
 use super::{ModelMessage, ModelRecord};
 use crate::simulator::Services;
 use crate::utils::errors::SimulationError;
@@ -51,14 +52,17 @@ struct PortsOut {
 struct State {
     until_next_event: f64,
     collections: HashMap<String, usize>,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             collections: HashMap::new(),
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -138,13 +142,17 @@ impl ParallelGateway {
                 messages.push(ModelMessage {
                     port_name: flow_path.clone(),
                     content: completed_collection.clone(),
+                    job_id: None,
+                    metadata: HashMap::new(),
+                    payload: None,
+                    priority: 0,
                 });
                 messages
             }))
     }
 
     fn passivate(&mut self) -> Vec<ModelMessage> {
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         Vec::new()
     }
 
@@ -161,11 +169,15 @@ impl ParallelGateway {
 
 #[cfg_attr(feature = "simx", event_rules)]
 impl DevsModel for ParallelGateway {
+    #[allow(clippy::unit_arg)]
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         match self.arrival_port(&incoming_message.port_name) {
             ArrivalPort::FlowPath => Ok(self.increment_collection(incoming_message, services)),
             ArrivalPort::Unknown => Err(SimulationError::InvalidMessage),
@@ -176,10 +188,14 @@ impl DevsModel for ParallelGateway {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match self.full_collection() {
+        let outgoing_messages = match self.full_collection() {
             Some(_) => self.send_job(services),
             None => Ok(self.passivate()),
-        }
+        }?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -199,6 +215,26 @@ impl Reportable for ParallelGateway {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            AssumptionTopic::Routing,
+            "fork-join",
+            "Each job is duplicated to every path, and joins wait for a copy from every path",
+        )]
+    }
+
+    fn zero_time_transit(&self) -> bool {
+        true
+    }
 }
 
 impl ReportableModel for ParallelGateway {}
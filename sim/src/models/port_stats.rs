@@ -0,0 +1,64 @@
+//! Port statistics track the message traffic of each model port - the
+//! messages received, the messages sent, and the time of the latest
+//! activity.  Saturation and starvation of specific ports is then directly
+//! observable, through `Reportable::port_stats`.  The built-in models share
+//! this `PortStats` helper, recording incoming messages in `events_ext` and
+//! outgoing messages in `events_int`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::ModelMessage;
+
+/// The message traffic of a single model port.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortActivity {
+    pub messages_in: usize,
+    pub messages_out: usize,
+    pub last_activity: Option<f64>,
+}
+
+/// The message traffic of every active port of a model, keyed by port name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PortStats {
+    ports: BTreeMap<String, PortActivity>,
+}
+
+impl PortStats {
+    pub fn is_empty(&self) -> bool {
+        self.ports.is_empty()
+    }
+
+    /// Record a message received on the port, at the provided time.
+    pub fn record_in(&mut self, port_name: &str, time: f64) {
+        let activity = self.ports.entry(port_name.to_string()).or_default();
+        activity.messages_in += 1;
+        activity.last_activity = Some(time);
+    }
+
+    /// Record the messages sent by a single internal transition, at the
+    /// provided time.
+    pub fn record_out(&mut self, outgoing_messages: &[ModelMessage], time: f64) {
+        outgoing_messages.iter().for_each(|outgoing_message| {
+            let activity = self
+                .ports
+                .entry(outgoing_message.port_name.clone())
+                .or_default();
+            activity.messages_out += 1;
+            activity.last_activity = Some(time);
+        });
+    }
+
+    /// The traffic of a single port, if the port has seen any activity.
+    pub fn port(&self, port_name: &str) -> Option<&PortActivity> {
+        self.ports.get(port_name)
+    }
+
+    /// The traffic of every active port, ordered by port name.
+    pub fn ports(&self) -> impl Iterator<Item = (&String, &PortActivity)> {
+        self.ports.iter()
+    }
+}
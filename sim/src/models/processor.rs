@@ -1,12 +1,14 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{rng_stream_assumption, Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
-use super::{ModelMessage, ModelRecord};
+use super::port_stats::PortStats;
+use super::{is_zero, ModelMessage, ModelRecord};
 use crate::input_modeling::dynamic_rng::DynRng;
 use crate::input_modeling::ContinuousRandomVariable;
-use crate::simulator::Services;
+use crate::simulator::{JobId, Services};
 use crate::utils::errors::SimulationError;
 
 use sim_derive::SerializableModel;
@@ -22,15 +24,32 @@ use simx::event_rules;
 /// variable distribution dictates the amount of time required to process a
 /// job. For non-stochastic behavior, a random variable distribution with a
 /// single point can be used - in which case, every job takes exactly the
-/// specified amount of time to process.
+/// specified amount of time to process.  Service times may depend on the
+/// queue length, through thresholds - when the number of jobs waiting at the
+/// start of service reaches a threshold, the threshold's service time
+/// distribution is used instead, or the service time is scaled (e.g. servers
+/// speeding up under pressure).  The processor tracks its busy time, for
+/// utilization reporting, excluding an optional warm-up period at the start
+/// of the simulation.
 #[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
 #[serde(rename_all = "camelCase")]
 pub struct Processor {
     service_time: ContinuousRandomVariable,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    service_time_thresholds: Vec<ServiceTimeThreshold>,
     #[serde(default = "max_usize")]
     queue_capacity: usize,
+    // The simulation time before which busy time is not counted
+    #[serde(default, skip_serializing_if = "is_zero_time")]
+    warm_up: f64,
     ports_in: PortsIn,
     ports_out: PortsOut,
+    // Named random number stream, and explicit seed, overriding the global
+    // stream assignment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rng_stream: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
     #[serde(default)]
     store_records: bool,
     #[serde(default)]
@@ -43,6 +62,44 @@ fn max_usize() -> usize {
     usize::MAX
 }
 
+fn is_zero_time(time: &f64) -> bool {
+    *time == 0.0
+}
+
+/// The utilization of a processor, as of a given simulation time - the
+/// fraction of the observed time (after the warm-up period) spent serving
+/// jobs.  The utilization is unavailable until the warm-up period ends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UtilizationSummary {
+    pub time: f64,
+    pub warm_up: f64,
+    pub observed_time: f64,
+    pub busy_time: f64,
+    pub utilization: Option<f64>,
+}
+
+/// A queue length threshold for state-dependent service.  The threshold
+/// applies when at least `queue_length` jobs are waiting at the start of
+/// service, and the highest applicable threshold takes precedence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceTimeThreshold {
+    pub queue_length: usize,
+    #[serde(flatten)]
+    pub adjustment: ServiceTimeAdjustment,
+}
+
+/// The service time adjustment of a queue length threshold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ServiceTimeAdjustment {
+    /// A replacement service time distribution
+    ServiceTime(ContinuousRandomVariable),
+    /// A multiplier on the service time drawn from the base distribution
+    Scale(f64),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct PortsIn {
@@ -67,6 +124,20 @@ struct State {
     phase: Phase,
     until_next_event: f64,
     queue: Vec<String>,
+    // Structured job IDs of the queued jobs, aligned with the queue
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    job_ids: Vec<Option<JobId>>,
+    // Draws from the named or seeded random number stream, if any
+    #[serde(default, skip_serializing_if = "is_zero")]
+    stream_position: u64,
+    // Busy time of completed services, after the warm-up period, and the
+    // start time of the service in progress
+    #[serde(default, skip_serializing_if = "is_zero_time")]
+    busy_time: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    service_start: Option<f64>,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
@@ -74,8 +145,13 @@ impl Default for State {
     fn default() -> Self {
         State {
             phase: Phase::Passive,
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             queue: Vec::new(),
+            job_ids: Vec::new(),
+            stream_position: 0,
+            busy_time: 0.0,
+            service_start: None,
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -99,17 +175,48 @@ impl Processor {
     ) -> Self {
         Self {
             service_time,
+            service_time_thresholds: Vec::new(),
             queue_capacity: queue_capacity.unwrap_or(usize::MAX),
+            warm_up: 0.0,
             ports_in: PortsIn { job: job_port },
             ports_out: PortsOut {
                 job: processed_job_port,
             },
+            rng_stream: None,
+            seed: None,
             store_records,
             state: State::default(),
             rng,
         }
     }
 
+    /// Draw the service times from a named random number stream, shared with any other
+    /// models configured with the same stream.
+    pub fn with_rng_stream(mut self, name: String) -> Self {
+        self.rng_stream = Some(name);
+        self
+    }
+
+    /// Pin the service times to an explicit seed, independent of the global seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Exclude the busy time before the warm-up time from the utilization,
+    /// so the utilization reflects steady-state operation.
+    pub fn with_warm_up(mut self, warm_up: f64) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    /// Make the service time dependent on the queue length, through queue
+    /// length thresholds.
+    pub fn with_service_time_thresholds(mut self, thresholds: Vec<ServiceTimeThreshold>) -> Self {
+        self.service_time_thresholds = thresholds;
+        self
+    }
+
     fn arrival_port(&self, message_port: &str) -> ArrivalPort {
         if message_port == self.ports_in.job {
             ArrivalPort::Job
@@ -118,8 +225,79 @@ impl Processor {
         }
     }
 
-    fn add_job(&mut self, incoming_message: &ModelMessage, services: &mut Services) {
+    fn enqueue(&mut self, incoming_message: &ModelMessage) {
+        // Queues configured directly (e.g. from a serialized state) may lack
+        // job IDs
+        self.state.job_ids.resize(self.state.queue.len(), None);
         self.state.queue.push(incoming_message.content.clone());
+        self.state.job_ids.push(incoming_message.job_id.clone());
+    }
+
+    fn dequeue(&mut self) -> (String, Option<JobId>) {
+        self.state.job_ids.resize(self.state.queue.len(), None);
+        (self.state.queue.remove(0), self.state.job_ids.remove(0))
+    }
+
+    /// Draw a service time for the job at the head of the queue, with the
+    /// adjustment of the applicable queue length threshold.
+    fn draw_service_time(&mut self, services: &mut Services) -> Result<f64, SimulationError> {
+        let waiting = self.state.queue.len().saturating_sub(1);
+        let threshold_index = self
+            .service_time_thresholds
+            .iter()
+            .enumerate()
+            .filter(|(_, threshold)| waiting >= threshold.queue_length)
+            .max_by_key(|(_, threshold)| threshold.queue_length)
+            .map(|(index, _)| index);
+        let rng = services.model_rng(
+            self.seed,
+            self.rng_stream.as_deref(),
+            &mut self.state.stream_position,
+            self.rng.as_ref(),
+        );
+        let thresholds = &mut self.service_time_thresholds;
+        let (service_time, scale) =
+            match threshold_index.map(|index| &mut thresholds[index].adjustment) {
+                Some(ServiceTimeAdjustment::ServiceTime(service_time)) => (service_time, 1.0),
+                Some(ServiceTimeAdjustment::Scale(scale)) => (&mut self.service_time, *scale),
+                None => (&mut self.service_time, 1.0),
+            };
+        let variate = if services.deterministic_mode() {
+            service_time.mean_in(&services.sampling_context())?
+        } else {
+            service_time.random_variate_in(rng, &services.sampling_context())?
+        };
+        services.record_variate(service_time, variate);
+        Ok(scale * variate)
+    }
+
+    /// The busy time after the warm-up period, including the service in
+    /// progress, as of the simulation time.
+    fn busy_time(&self, time: f64) -> f64 {
+        self.state.busy_time
+            + self.state.service_start.map_or(0.0, |service_start| {
+                (time - service_start.max(self.warm_up)).max(0.0)
+            })
+    }
+
+    fn utilization(&self, time: f64) -> UtilizationSummary {
+        let observed_time = (time - self.warm_up).max(0.0);
+        let busy_time = self.busy_time(time);
+        UtilizationSummary {
+            time,
+            warm_up: self.warm_up,
+            observed_time,
+            busy_time,
+            utilization: if observed_time > 0.0 {
+                Some(busy_time / observed_time)
+            } else {
+                None
+            },
+        }
+    }
+
+    fn add_job(&mut self, incoming_message: &ModelMessage, services: &mut Services) {
+        self.enqueue(incoming_message);
         self.record(
             services.global_time(),
             String::from("Arrival"),
@@ -132,12 +310,10 @@ impl Processor {
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
-        self.state.queue.push(incoming_message.content.clone());
+        self.enqueue(incoming_message);
         self.state.phase = Phase::Active;
-        self.state.until_next_event = match &self.rng {
-            Some(rng) => self.service_time.random_variate(rng.clone())?,
-            None => self.service_time.random_variate(services.global_rng())?,
-        };
+        self.state.until_next_event = self.draw_service_time(services)?;
+        self.state.service_start = Some(services.global_time());
         self.record(
             services.global_time(),
             String::from("Arrival"),
@@ -164,10 +340,8 @@ impl Processor {
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
         self.state.phase = Phase::Active;
-        self.state.until_next_event = match &self.rng {
-            Some(rng) => self.service_time.random_variate(rng.clone())?,
-            None => self.service_time.random_variate(services.global_rng())?,
-        };
+        self.state.until_next_event = self.draw_service_time(services)?;
+        self.state.service_start = Some(services.global_time());
         self.record(
             services.global_time(),
             String::from("Processing Start"),
@@ -177,23 +351,33 @@ impl Processor {
     }
 
     fn release_job(&mut self, services: &mut Services) -> Vec<ModelMessage> {
-        let job = self.state.queue.remove(0);
+        let (job, job_id) = self.dequeue();
         self.state.phase = Phase::Passive;
         self.state.until_next_event = 0.0;
-        self.record(
-            services.global_time(),
-            String::from("Departure"),
-            job.clone(),
-        );
+        let time = services.global_time();
+        self.state.busy_time = self.busy_time(time);
+        self.state.service_start = None;
+        self.record(time, String::from("Departure"), job.clone());
+        if let Some(utilization) = self.utilization(time).utilization {
+            self.record(
+                time,
+                String::from("Utilization"),
+                format!["{}", utilization],
+            );
+        }
         vec![ModelMessage {
             content: job,
             port_name: self.ports_out.job.clone(),
+            job_id,
+            metadata: HashMap::new(),
+            payload: None,
+            priority: 0,
         }]
     }
 
     fn passivate(&mut self) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         Vec::new()
     }
 
@@ -210,11 +394,15 @@ impl Processor {
 
 #[cfg_attr(feature = "simx", event_rules)]
 impl DevsModel for Processor {
+    #[allow(clippy::unit_arg)]
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         match (
             self.arrival_port(&incoming_message.port_name),
             self.state.queue.is_empty(),
@@ -232,11 +420,15 @@ impl DevsModel for Processor {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match (&self.state.phase, self.state.queue.is_empty()) {
+        let outgoing_messages = match (&self.state.phase, self.state.queue.is_empty()) {
             (Phase::Passive, true) => Ok(self.passivate()),
             (Phase::Passive, false) => self.process_next(services),
             (Phase::Active, _) => Ok(self.release_job(services)),
-        }
+        }?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -246,6 +438,19 @@ impl DevsModel for Processor {
     fn until_next_event(&self) -> f64 {
         self.state.until_next_event
     }
+
+    fn held_jobs(&self) -> Vec<&JobId> {
+        self.state.job_ids.iter().flatten().collect()
+    }
+
+    fn migrate_state(&mut self, previous: &serde_yaml::Value) -> Result<(), SimulationError> {
+        // Keep the queue and the job in service, so a changed service time
+        // applies from the next service start
+        if let Some(state) = previous.get("state") {
+            self.state = serde_yaml::from_value(state.clone())?;
+        }
+        Ok(())
+    }
 }
 
 impl Reportable for Processor {
@@ -256,9 +461,95 @@ impl Reportable for Processor {
         }
     }
 
+    fn status_detail(&self, time: f64) -> String {
+        match self.utilization(time).utilization {
+            Some(utilization) => format![
+                "{} - {:.1}% utilization since {}",
+                self.status(),
+                utilization * 100.0,
+                self.warm_up
+            ],
+            None => format!["{} - warming up until {}", self.status(), self.warm_up],
+        }
+    }
+
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn queue_depth(&self) -> Option<usize> {
+        Some(self.state.queue.len())
+    }
+
+    fn utilization(&self, time: f64) -> Option<UtilizationSummary> {
+        Some(self.utilization(time))
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        let service_times = if self.service_time_thresholds.is_empty() {
+            Assumption::new(
+                AssumptionTopic::ServiceTimes,
+                "iid",
+                "Service times are independent and identically distributed, regardless of the queue length",
+            )
+        } else {
+            Assumption::new(
+                AssumptionTopic::ServiceTimes,
+                "state-dependent",
+                "Service times depend on the queue length at the start of service, through thresholds",
+            )
+        };
+        let queue_capacity = if self.queue_capacity == usize::MAX {
+            Assumption::new(
+                AssumptionTopic::QueueCapacity,
+                "infinite",
+                "The queue is unbounded - no arrivals are lost",
+            )
+        } else {
+            Assumption::new(
+                AssumptionTopic::QueueCapacity,
+                &self.queue_capacity.to_string(),
+                "Arrivals to a full queue are dropped (lost), rather than blocked upstream",
+            )
+        };
+        let mut assumptions = vec![
+            Assumption::new(
+                AssumptionTopic::Servers,
+                "1",
+                "A single server processes one job at a time",
+            ),
+            Assumption::new(
+                AssumptionTopic::QueueDiscipline,
+                "fifo",
+                "Queued jobs are served first in, first out, regardless of job priority",
+            ),
+            queue_capacity,
+            Assumption::new(
+                AssumptionTopic::Patience,
+                "infinite",
+                "Queued jobs wait indefinitely - there is no balking or reneging",
+            ),
+            service_times,
+        ];
+        assumptions.extend(rng_stream_assumption(&self.rng_stream));
+        assumptions
+    }
+
+    fn zero_time_transit(&self) -> bool {
+        self.service_time_thresholds.is_empty()
+            && matches!(
+                self.service_time,
+                ContinuousRandomVariable::Constant { value } if value == 0.0
+            )
+    }
 }
 
 impl ReportableModel for Processor {}
@@ -0,0 +1,69 @@
+//! The query protocol is a tiny, standard request/response protocol for
+//! stateful reporting models (e.g. `Storage` and `Stopwatch`), so
+//! controllers and UIs can query any reporting model uniformly.  Queries
+//! arrive on the model's request port (e.g. the `Storage` get port, or the
+//! `Stopwatch` metric port), and the message content selects the request:
+//!
+//! * `get` - respond with the model's current value, such as the stored job
+//! * `summary` - respond with a JSON summary of the model's state
+//! * `clear` - reset the model's reported state, and respond with `cleared`
+//!
+//! For compatibility with existing simulations, any other content is
+//! treated as a `get` request.  Use `Query::validate` to reject unknown
+//! requests before injecting them.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::errors::SimulationError;
+
+/// The response content of a `clear` request.
+pub const CLEARED: &str = "cleared";
+
+/// A request of the query protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Query {
+    #[default]
+    Get,
+    Summary,
+    Clear,
+}
+
+impl Query {
+    /// Interpret the content of a request port message, treating unknown
+    /// requests as `get` requests.
+    pub fn from_content(content: &str) -> Self {
+        content.parse().unwrap_or_default()
+    }
+
+    /// Check that message content is a known request of the protocol.
+    pub fn validate(content: &str) -> Result<(), SimulationError> {
+        content.parse::<Query>().map(|_| ())
+    }
+}
+
+impl FromStr for Query {
+    type Err = SimulationError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        match content {
+            "get" => Ok(Query::Get),
+            "summary" => Ok(Query::Summary),
+            "clear" => Ok(Query::Clear),
+            _ => Err(SimulationError::InvalidMessage),
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Query::Get => write!(f, "get"),
+            Query::Summary => write!(f, "summary"),
+            Query::Clear => write!(f, "clear"),
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::assumption::{Assumption, AssumptionTopic};
+use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
+use super::{ModelMessage, ModelRecord};
+use crate::simulator::coupling::is_default_priority;
+use crate::simulator::{JobId, Services};
+use crate::utils::errors::SimulationError;
+
+use sim_derive::SerializableModel;
+
+#[cfg(feature = "simx")]
+use simx::event_rules;
+
+// The relative tolerance for matching the simulation time to a scheduled
+// time, absorbing the rounding of accumulated time advances
+const TIME_TOLERANCE: f64 = 1.0e-9;
+
+/// The scheduler emits configured messages at explicit simulation times,
+/// for control events such as shift changes, gate openings, or
+/// maintenance windows.  Each rule emits its message once, at the `at`
+/// time, or - with an `every` period - recurs from the `at` time, until
+/// the optional `until` time or `count` of occurrences.  Recurrences are
+/// computed from the `at` time, rather than accumulated, so long-running
+/// schedules do not drift.  Messages scheduled at the same time are
+/// emitted together, in rule order.  The scheduler does not receive
+/// messages or otherwise change behavior throughout a simulation.
+#[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
+#[serde(rename_all = "camelCase")]
+pub struct Scheduler {
+    rules: Vec<ScheduleRule>,
+    #[serde(default)]
+    store_records: bool,
+    #[serde(default)]
+    state: State,
+}
+
+/// A one-shot or recurring scheduled message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRule {
+    port: String,
+    content: String,
+    at: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    every: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    until: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    count: Option<usize>,
+    #[serde(default, skip_serializing_if = "is_default_priority")]
+    priority: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    until_next_event: f64,
+    // Occurrences emitted so far, by rule
+    occurrences: Vec<usize>,
+    last_job: usize,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
+    records: Vec<ModelRecord>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            until_next_event: 0.0,
+            occurrences: Vec::new(),
+            last_job: 0,
+            port_stats: PortStats::default(),
+            records: Vec::new(),
+        }
+    }
+}
+
+impl ScheduleRule {
+    /// A message emitted once, at the specified time.
+    pub fn once(port: String, content: String, at: f64) -> Self {
+        Self {
+            port,
+            content,
+            at,
+            every: None,
+            until: None,
+            count: None,
+            priority: 0,
+        }
+    }
+
+    /// A message emitted at the start time, and then once every period.
+    pub fn recurring(port: String, content: String, start: f64, every: f64) -> Self {
+        Self {
+            every: Some(every),
+            ..Self::once(port, content, start)
+        }
+    }
+
+    /// Stop recurring after the specified time.
+    pub fn until(mut self, until: f64) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Stop recurring after the specified number of occurrences.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Deliver the message ahead of lower priority messages in the same
+    /// step, as an interrupt-style control message.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    fn validate(&self) -> Result<(), SimulationError> {
+        let valid_period = self
+            .every
+            .is_none_or(|every| every.is_finite() && every > 0.0);
+        if self.at.is_finite() && valid_period {
+            Ok(())
+        } else {
+            Err(SimulationError::InvalidModelConfiguration)
+        }
+    }
+
+    /// The time of the next occurrence, if the rule has not finished.
+    fn next_time(&self, occurrences: usize) -> Option<f64> {
+        let limit = match self.every {
+            Some(_) => self.count.unwrap_or(usize::MAX),
+            None => 1,
+        };
+        if occurrences >= limit {
+            return None;
+        }
+        let time = self.at + self.every.unwrap_or(0.0) * occurrences as f64;
+        match self.until {
+            Some(until) if time > until => None,
+            _ => Some(time),
+        }
+    }
+}
+
+fn is_due(time: f64, global_time: f64) -> bool {
+    time - global_time <= TIME_TOLERANCE * global_time.abs().max(1.0)
+}
+
+#[cfg_attr(feature = "simx", event_rules)]
+impl Scheduler {
+    pub fn new(rules: Vec<ScheduleRule>, store_records: bool) -> Self {
+        Self {
+            rules,
+            store_records,
+            state: State::default(),
+        }
+    }
+
+    fn release_messages(
+        &mut self,
+        services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        self.rules.iter().try_for_each(ScheduleRule::validate)?;
+        self.state.occurrences.resize(self.rules.len(), 0);
+        let global_time = services.global_time();
+        let mut outgoing_messages = Vec::new();
+        for (rule, occurrences) in self.rules.iter().zip(self.state.occurrences.iter_mut()) {
+            // Occurrences before the scheduler entered the simulation are
+            // emitted late, rather than skipped
+            while let Some(time) = rule.next_time(*occurrences) {
+                if !is_due(time, global_time) {
+                    break;
+                }
+                *occurrences += 1;
+                self.state.last_job += 1;
+                let job_number = self.state.last_job;
+                outgoing_messages.push(ModelMessage {
+                    port_name: rule.port.clone(),
+                    content: rule.content.clone(),
+                    job_id: services
+                        .current_model_id()
+                        .map(|model_id| JobId::new(model_id.to_string(), job_number)),
+                    metadata: HashMap::new(),
+                    payload: None,
+                    priority: rule.priority,
+                });
+            }
+        }
+        let next_time = self
+            .rules
+            .iter()
+            .zip(self.state.occurrences.iter())
+            .filter_map(|(rule, occurrences)| rule.next_time(*occurrences))
+            .fold(f64::INFINITY, f64::min);
+        self.state.until_next_event = (next_time - global_time).max(0.0);
+        let subjects: Vec<String> = outgoing_messages
+            .iter()
+            .map(|message| format!["{} {}", message.port_name, message.content])
+            .collect();
+        subjects
+            .into_iter()
+            .for_each(|subject| self.record(global_time, String::from("Emission"), subject));
+        Ok(outgoing_messages)
+    }
+
+    fn record(&mut self, time: f64, action: String, subject: String) {
+        if self.store_records {
+            self.state.records.push(ModelRecord {
+                time,
+                action,
+                subject,
+            });
+        }
+    }
+}
+
+#[cfg_attr(feature = "simx", event_rules)]
+impl DevsModel for Scheduler {
+    fn events_ext(
+        &mut self,
+        incoming_message: &ModelMessage,
+        services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
+        Ok(())
+    }
+
+    fn events_int(
+        &mut self,
+        services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        let outgoing_messages = self.release_messages(services)?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
+    }
+
+    fn time_advance(&mut self, time_delta: f64) {
+        self.state.until_next_event -= time_delta;
+    }
+
+    fn until_next_event(&self) -> f64 {
+        self.state.until_next_event
+    }
+}
+
+impl Reportable for Scheduler {
+    fn status(&self) -> String {
+        format!["Emitted {} scheduled messages", self.state.last_job]
+    }
+
+    fn records(&self) -> &Vec<ModelRecord> {
+        &self.state.records
+    }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            AssumptionTopic::ArrivalProcess,
+            "deterministic",
+            "Messages are emitted exactly at the scheduled times, independent of the system state",
+        )]
+    }
+}
+
+impl ReportableModel for Scheduler {}
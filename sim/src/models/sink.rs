@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::assumption::{Assumption, AssumptionTopic};
+use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
+use super::{ModelMessage, ModelRecord};
+use crate::output_analysis::Welford;
+use crate::simulator::Services;
+use crate::utils::errors::SimulationError;
+
+use sim_derive::SerializableModel;
+
+#[cfg(feature = "simx")]
+use simx::event_rules;
+
+/// The sink absorbs jobs, and maintains running statistics on the
+/// departures from the system - the total count, the overall and rolling
+/// window throughputs, and the mean and standard deviation of the
+/// interarrival times.  Statistics are
+/// updated incrementally, and the rolling window retains only the arrival
+/// times within the configured window, so the summary is cheap to report
+/// at any time (e.g. for dashboard polling).
+#[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
+#[serde(rename_all = "camelCase")]
+pub struct Sink {
+    // Duration of the rolling window, in simulation time
+    window: f64,
+    ports_in: PortsIn,
+    #[serde(default)]
+    store_records: bool,
+    #[serde(default)]
+    state: State,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PortsIn {
+    job: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    count: usize,
+    first_arrival: Option<f64>,
+    last_arrival: Option<f64>,
+    // Arrival times within the rolling window, as of the latest arrival
+    recent_arrivals: VecDeque<f64>,
+    #[serde(default, skip_serializing_if = "Welford::is_empty")]
+    interarrivals: Welford,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
+    records: Vec<ModelRecord>,
+}
+
+/// The running statistics of a sink, as of a given simulation time.
+/// Throughputs are jobs per unit of simulation time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SinkSummary {
+    pub time: f64,
+    pub count: usize,
+    pub first_arrival: Option<f64>,
+    pub last_arrival: Option<f64>,
+    pub throughput: f64,
+    pub mean_interarrival: Option<f64>,
+    #[serde(default)]
+    pub interarrival_std_dev: Option<f64>,
+    pub window: f64,
+    pub window_count: usize,
+    pub window_throughput: f64,
+}
+
+#[cfg_attr(feature = "simx", event_rules)]
+impl Sink {
+    pub fn new(job_port: String, window: f64, store_records: bool) -> Self {
+        Self {
+            window,
+            ports_in: PortsIn { job: job_port },
+            store_records,
+            state: State::default(),
+        }
+    }
+
+    fn absorb_job(&mut self, incoming_message: &ModelMessage, services: &mut Services) {
+        let time = services.global_time();
+        self.state.count += 1;
+        self.state.first_arrival.get_or_insert(time);
+        if let Some(last_arrival) = self.state.last_arrival {
+            self.state.interarrivals.push(time - last_arrival);
+        }
+        self.state.last_arrival = Some(time);
+        self.state.recent_arrivals.push_back(time);
+        while self
+            .state
+            .recent_arrivals
+            .front()
+            .is_some_and(|arrival| *arrival < time - self.window)
+        {
+            self.state.recent_arrivals.pop_front();
+        }
+        self.record(
+            time,
+            String::from("Arrival"),
+            incoming_message.content.clone(),
+        );
+    }
+
+    /// The running statistics of the sink, as of the provided simulation
+    /// time.
+    pub fn summary(&self, time: f64) -> SinkSummary {
+        let window_count = self
+            .state
+            .recent_arrivals
+            .iter()
+            .filter(|arrival| **arrival >= time - self.window)
+            .count();
+        let window_duration = f64::min(self.window, time);
+        SinkSummary {
+            time,
+            count: self.state.count,
+            first_arrival: self.state.first_arrival,
+            last_arrival: self.state.last_arrival,
+            throughput: if time > 0.0 {
+                self.state.count as f64 / time
+            } else {
+                0.0
+            },
+            mean_interarrival: self.state.interarrivals.mean(),
+            interarrival_std_dev: self.state.interarrivals.std_dev(),
+            window: self.window,
+            window_count,
+            window_throughput: if window_duration > 0.0 {
+                window_count as f64 / window_duration
+            } else {
+                0.0
+            },
+        }
+    }
+
+    fn record(&mut self, time: f64, action: String, subject: String) {
+        if self.store_records {
+            self.state.records.push(ModelRecord {
+                time,
+                action,
+                subject,
+            });
+        }
+    }
+}
+
+#[cfg_attr(feature = "simx", event_rules)]
+impl DevsModel for Sink {
+    #[allow(clippy::unit_arg)]
+    fn events_ext(
+        &mut self,
+        incoming_message: &ModelMessage,
+        services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
+        if incoming_message.port_name == self.ports_in.job {
+            Ok(self.absorb_job(incoming_message, services))
+        } else {
+            Err(SimulationError::InvalidMessage)
+        }
+    }
+
+    fn events_int(
+        &mut self,
+        _services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        Ok(Vec::new())
+    }
+
+    fn time_advance(&mut self, _time_delta: f64) {
+        // The sink has no internal events
+    }
+
+    fn until_next_event(&self) -> f64 {
+        f64::INFINITY
+    }
+
+    fn migrate_state(&mut self, previous: &serde_yaml::Value) -> Result<(), SimulationError> {
+        // Keep the running statistics
+        if let Some(state) = previous.get("state") {
+            self.state = serde_yaml::from_value(state.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl Reportable for Sink {
+    fn status(&self) -> String {
+        format!["Absorbed {} jobs", self.state.count]
+    }
+
+    fn records(&self) -> &Vec<ModelRecord> {
+        &self.state.records
+    }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn sink_summary(&self, time: f64) -> Option<SinkSummary> {
+        Some(self.summary(time))
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            AssumptionTopic::Measurement,
+            "departures",
+            &format![
+                "Jobs leave the system on arrival, and rolling window statistics cover the last {} time units",
+                self.window
+            ],
+        )]
+    }
+}
+
+impl ReportableModel for Sink {}
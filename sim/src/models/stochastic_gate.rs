@@ -1,9 +1,11 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{rng_stream_assumption, Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
-use super::{ModelMessage, ModelRecord};
+use super::port_stats::PortStats;
+use super::{is_zero, ModelMessage, ModelRecord};
 use crate::input_modeling::dynamic_rng::DynRng;
 use crate::input_modeling::BooleanRandomVariable;
 use crate::simulator::Services;
@@ -24,6 +26,12 @@ pub struct StochasticGate {
     pass_distribution: BooleanRandomVariable,
     ports_in: PortsIn,
     ports_out: PortsOut,
+    // Named random number stream, and explicit seed, overriding the global
+    // stream assignment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rng_stream: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
     #[serde(default)]
     store_records: bool,
     #[serde(default)]
@@ -53,14 +61,21 @@ struct PortsOut {
 struct State {
     until_next_event: f64,
     jobs: Vec<Job>,
+    // Draws from the named or seeded random number stream, if any
+    #[serde(default, skip_serializing_if = "is_zero")]
+    stream_position: u64,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
 impl Default for State {
     fn default() -> Self {
         State {
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             jobs: Vec::new(),
+            stream_position: 0,
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -86,12 +101,27 @@ impl StochasticGate {
             pass_distribution,
             ports_in: PortsIn { job: job_in_port },
             ports_out: PortsOut { job: job_out_port },
+            rng_stream: None,
+            seed: None,
             store_records,
             state: State::default(),
             rng,
         }
     }
 
+    /// Draw the pass decisions from a named random number stream, shared with any other
+    /// models configured with the same stream.
+    pub fn with_rng_stream(mut self, name: String) -> Self {
+        self.rng_stream = Some(name);
+        self
+    }
+
+    /// Pin the pass decisions to an explicit seed, independent of the global seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
     fn arrival_port(&self, message_port: &str) -> ArrivalPort {
         if message_port == self.ports_in.job {
             ArrivalPort::Job
@@ -106,14 +136,17 @@ impl StochasticGate {
         services: &mut Services,
     ) -> Result<(), SimulationError> {
         self.state.until_next_event = 0.0;
+        let rng = services.model_rng(
+            self.seed,
+            self.rng_stream.as_deref(),
+            &mut self.state.stream_position,
+            self.rng.as_ref(),
+        );
+        let pass = self.pass_distribution.random_variate(rng)?;
+        services.record_variate(&self.pass_distribution, f64::from(pass as u8));
         self.state.jobs.push(Job {
             content: incoming_message.content.clone(),
-            pass: match &self.rng {
-                Some(rng) => self.pass_distribution.random_variate(rng.clone())?,
-                None => self
-                    .pass_distribution
-                    .random_variate(services.global_rng())?,
-            },
+            pass,
         });
         self.record(
             services.global_time(),
@@ -124,7 +157,7 @@ impl StochasticGate {
     }
 
     fn passivate(&mut self) -> Vec<ModelMessage> {
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         Vec::new()
     }
 
@@ -139,6 +172,10 @@ impl StochasticGate {
         vec![ModelMessage {
             content: job.content,
             port_name: self.ports_out.job.clone(),
+            job_id: None,
+            metadata: HashMap::new(),
+            payload: None,
+            priority: 0,
         }]
     }
 
@@ -167,6 +204,9 @@ impl DevsModel for StochasticGate {
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         match self.arrival_port(&incoming_message.port_name) {
             ArrivalPort::Job => self.receive_job(incoming_message, services),
             ArrivalPort::Unknown => Err(SimulationError::InvalidMessage),
@@ -177,11 +217,15 @@ impl DevsModel for StochasticGate {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match self.state.jobs.get(0) {
-            None => Ok(self.passivate()),
-            Some(Job { pass: true, .. }) => Ok(self.pass_job(services)),
-            Some(Job { pass: false, .. }) => Ok(self.block_job(services)),
-        }
+        let outgoing_messages = match self.state.jobs.first() {
+            None => self.passivate(),
+            Some(Job { pass: true, .. }) => self.pass_job(services),
+            Some(Job { pass: false, .. }) => self.block_job(services),
+        };
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -201,6 +245,32 @@ impl Reportable for StochasticGate {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn queue_depth(&self) -> Option<usize> {
+        Some(self.state.jobs.len())
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        let mut assumptions = vec![Assumption::new(
+            AssumptionTopic::Blocking,
+            "bernoulli",
+            "Each job is passed or dropped independently, with the configured pass probability",
+        )];
+        assumptions.extend(rng_stream_assumption(&self.rng_stream));
+        assumptions
+    }
+
+    fn zero_time_transit(&self) -> bool {
+        true
+    }
 }
 
 impl ReportableModel for StochasticGate {}
@@ -1,9 +1,13 @@
-use std::f64::{INFINITY, NEG_INFINITY};
+use std::collections::HashMap;
+
 use std::iter::once;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
+use super::query::{Query, CLEARED};
 use super::{ModelMessage, ModelRecord};
 use crate::simulator::Services;
 use crate::utils::errors::SimulationError;
@@ -18,7 +22,9 @@ use simx::event_rules;
 /// time 0.1, and then a "job 1" message arrives at the stop port at time
 /// 1.3.  The duration for job 1 will be saved as 1.2.  The status reporting
 /// provides the average duration across all jobs.  The maximum or minimum
-/// duration job is also accessible through the metric and job ports.
+/// duration job is also accessible through the metric and job ports.  The
+/// metric port honors the standard query protocol (`get`, `summary`, and
+/// `clear`) - see the `query` module.
 #[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
 #[serde(rename_all = "camelCase")]
 pub struct Stopwatch {
@@ -52,33 +58,45 @@ struct PortsOut {
     job: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum Metric {
+    #[default]
     Minimum,
     Maximum,
 }
 
-impl Default for Metric {
-    fn default() -> Self {
-        Metric::Minimum
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct State {
     phase: Phase,
     until_next_event: f64,
     jobs: Vec<Job>,
+    #[serde(default)]
+    query: Query,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
+/// The response content of a `summary` query, as JSON.  Durations are only
+/// summarized for jobs with both a start and a stop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    count: usize,
+    average: Option<f64>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+}
+
 impl Default for State {
     fn default() -> Self {
         State {
             phase: Phase::Passive,
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             jobs: Vec::new(),
+            query: Query::default(),
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -165,13 +183,16 @@ impl Stopwatch {
             .jobs
             .iter()
             .filter_map(some_duration)
-            .fold((None, INFINITY), |minimum, (job_name, job_duration)| {
-                if job_duration < minimum.1 {
-                    (Some(job_name), job_duration)
-                } else {
-                    minimum
-                }
-            })
+            .fold(
+                (None, f64::INFINITY),
+                |minimum, (job_name, job_duration)| {
+                    if job_duration < minimum.1 {
+                        (Some(job_name), job_duration)
+                    } else {
+                        minimum
+                    }
+                },
+            )
             .0
     }
 
@@ -180,13 +201,16 @@ impl Stopwatch {
             .jobs
             .iter()
             .filter_map(some_duration)
-            .fold((None, NEG_INFINITY), |maximum, (job_name, job_duration)| {
-                if job_duration > maximum.1 {
-                    (Some(job_name), job_duration)
-                } else {
-                    maximum
-                }
-            })
+            .fold(
+                (None, f64::NEG_INFINITY),
+                |maximum, (job_name, job_duration)| {
+                    if job_duration > maximum.1 {
+                        (Some(job_name), job_duration)
+                    } else {
+                        maximum
+                    }
+                },
+            )
             .0
     }
 
@@ -208,14 +232,15 @@ impl Stopwatch {
         self.matching_or_new_job(incoming_message).stop = Some(services.global_time());
     }
 
-    fn get_job(&mut self) {
+    fn get_job(&mut self, incoming_message: &ModelMessage) {
+        self.state.query = Query::from_content(&incoming_message.content);
         self.state.phase = Phase::JobFetch;
         self.state.until_next_event = 0.0;
     }
 
     fn release_minimum(&mut self, services: &mut Services) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         self.record(
             services.global_time(),
             String::from("Minimum Fetch"),
@@ -227,13 +252,17 @@ impl Stopwatch {
             .map(|job| ModelMessage {
                 content: job,
                 port_name: self.ports_out.job.clone(),
+                job_id: None,
+                metadata: HashMap::new(),
+                payload: None,
+                priority: 0,
             })
             .collect()
     }
 
     fn release_maximum(&mut self, services: &mut Services) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         self.record(
             services.global_time(),
             String::from("Maximum Fetch"),
@@ -245,13 +274,74 @@ impl Stopwatch {
             .map(|job| ModelMessage {
                 content: job,
                 port_name: self.ports_out.job.clone(),
+                job_id: None,
+                metadata: HashMap::new(),
+                payload: None,
+                priority: 0,
             })
             .collect()
     }
 
+    fn release_summary(
+        &mut self,
+        services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        self.state.phase = Phase::Passive;
+        self.state.until_next_event = f64::INFINITY;
+        let durations: Vec<f64> = self
+            .state
+            .jobs
+            .iter()
+            .filter_map(|job| some_duration(job).map(|duration_record| duration_record.1))
+            .collect();
+        let summary = serde_json::to_string(&Summary {
+            count: durations.len(),
+            average: if durations.is_empty() {
+                None
+            } else {
+                Some(durations.iter().sum::<f64>() / durations.len() as f64)
+            },
+            minimum: durations.iter().copied().reduce(f64::min),
+            maximum: durations.iter().copied().reduce(f64::max),
+        })
+        .map_err(|_| SimulationError::SerializationError)?;
+        self.record(
+            services.global_time(),
+            String::from("Summary"),
+            summary.clone(),
+        );
+        Ok(vec![ModelMessage {
+            port_name: self.ports_out.job.clone(),
+            content: summary,
+            job_id: None,
+            metadata: HashMap::new(),
+            payload: None,
+            priority: 0,
+        }])
+    }
+
+    fn clear_jobs(&mut self, services: &mut Services) -> Vec<ModelMessage> {
+        self.state.phase = Phase::Passive;
+        self.state.until_next_event = f64::INFINITY;
+        self.record(
+            services.global_time(),
+            String::from("Clear"),
+            format!["{} jobs", self.state.jobs.len()],
+        );
+        self.state.jobs.clear();
+        vec![ModelMessage {
+            port_name: self.ports_out.job.clone(),
+            content: String::from(CLEARED),
+            job_id: None,
+            metadata: HashMap::new(),
+            payload: None,
+            priority: 0,
+        }]
+    }
+
     fn passivate(&mut self) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         Vec::new()
     }
 
@@ -268,15 +358,19 @@ impl Stopwatch {
 
 #[cfg_attr(feature = "simx", event_rules)]
 impl DevsModel for Stopwatch {
+    #[allow(clippy::unit_arg)]
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         match self.arrival_port(&incoming_message.port_name) {
             ArrivalPort::Start => Ok(self.start_job(incoming_message, services)),
             ArrivalPort::Stop => Ok(self.stop_job(incoming_message, services)),
-            ArrivalPort::Metric => Ok(self.get_job()),
+            ArrivalPort::Metric => Ok(self.get_job(incoming_message)),
             ArrivalPort::Unknown => Err(SimulationError::InvalidMessage),
         }
     }
@@ -285,11 +379,17 @@ impl DevsModel for Stopwatch {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match (&self.state.phase, &self.metric) {
-            (Phase::JobFetch, Metric::Minimum) => Ok(self.release_minimum(services)),
-            (Phase::JobFetch, Metric::Maximum) => Ok(self.release_maximum(services)),
-            (Phase::Passive, _) => Ok(self.passivate()),
-        }
+        let outgoing_messages = match (&self.state.phase, &self.state.query, &self.metric) {
+            (Phase::JobFetch, Query::Get, Metric::Minimum) => Ok(self.release_minimum(services)),
+            (Phase::JobFetch, Query::Get, Metric::Maximum) => Ok(self.release_maximum(services)),
+            (Phase::JobFetch, Query::Summary, _) => self.release_summary(services),
+            (Phase::JobFetch, Query::Clear, _) => Ok(self.clear_jobs(services)),
+            (Phase::Passive, _, _) => Ok(self.passivate()),
+        }?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -322,6 +422,22 @@ impl Reportable for Stopwatch {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            AssumptionTopic::Measurement,
+            "matched-content",
+            "Durations match start and stop messages by content, so jobs must have unique content",
+        )]
+    }
 }
 
 impl ReportableModel for Stopwatch {}
@@ -1,8 +1,11 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::assumption::{Assumption, AssumptionTopic};
 use super::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
+use super::port_stats::PortStats;
+use super::query::{Query, CLEARED};
 use super::{ModelMessage, ModelRecord};
 use crate::simulator::Services;
 use crate::utils::errors::SimulationError;
@@ -14,6 +17,8 @@ use simx::event_rules;
 
 /// The storage model stores a value, and responds with it upon request.
 /// Values are stored and value requests are handled instantantaneously.
+/// The get port honors the standard query protocol (`get`, `summary`, and
+/// `clear`) - see the `query` module.
 #[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
 #[serde(rename_all = "camelCase")]
 pub struct Storage {
@@ -49,15 +54,28 @@ struct State {
     phase: Phase,
     until_next_event: f64,
     job: Option<String>,
+    #[serde(default)]
+    query: Query,
+    #[serde(default, skip_serializing_if = "PortStats::is_empty")]
+    port_stats: PortStats,
     records: Vec<ModelRecord>,
 }
 
+/// The response content of a `summary` query, as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    stored: Option<String>,
+}
+
 impl Default for State {
     fn default() -> Self {
         State {
             phase: Phase::Passive,
-            until_next_event: INFINITY,
+            until_next_event: f64::INFINITY,
             job: None,
+            query: Query::default(),
+            port_stats: PortStats::default(),
             records: Vec::new(),
         }
     }
@@ -100,7 +118,8 @@ impl Storage {
         }
     }
 
-    fn get_job(&mut self) {
+    fn get_job(&mut self, incoming_message: &ModelMessage) {
+        self.state.query = Query::from_content(&incoming_message.content);
         self.state.phase = Phase::JobFetch;
         self.state.until_next_event = 0.0;
     }
@@ -116,7 +135,7 @@ impl Storage {
 
     fn release_job(&mut self, services: &mut Services) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         self.record(
             services.global_time(),
             String::from("Departure"),
@@ -126,14 +145,62 @@ impl Storage {
             Some(job) => vec![ModelMessage {
                 port_name: self.ports_out.stored.clone(),
                 content: job.clone(),
+                job_id: None,
+                metadata: HashMap::new(),
+                payload: None,
+                priority: 0,
             }],
             None => Vec::new(),
         }
     }
 
+    fn release_summary(
+        &mut self,
+        services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        self.state.phase = Phase::Passive;
+        self.state.until_next_event = f64::INFINITY;
+        let summary = serde_json::to_string(&Summary {
+            stored: self.state.job.clone(),
+        })
+        .map_err(|_| SimulationError::SerializationError)?;
+        self.record(
+            services.global_time(),
+            String::from("Summary"),
+            summary.clone(),
+        );
+        Ok(vec![ModelMessage {
+            port_name: self.ports_out.stored.clone(),
+            content: summary,
+            job_id: None,
+            metadata: HashMap::new(),
+            payload: None,
+            priority: 0,
+        }])
+    }
+
+    fn clear_job(&mut self, services: &mut Services) -> Vec<ModelMessage> {
+        self.state.phase = Phase::Passive;
+        self.state.until_next_event = f64::INFINITY;
+        self.record(
+            services.global_time(),
+            String::from("Clear"),
+            self.state.job.clone().unwrap_or_else(|| "None".to_string()),
+        );
+        self.state.job = None;
+        vec![ModelMessage {
+            port_name: self.ports_out.stored.clone(),
+            content: String::from(CLEARED),
+            job_id: None,
+            metadata: HashMap::new(),
+            payload: None,
+            priority: 0,
+        }]
+    }
+
     fn passivate(&mut self) -> Vec<ModelMessage> {
         self.state.phase = Phase::Passive;
-        self.state.until_next_event = INFINITY;
+        self.state.until_next_event = f64::INFINITY;
         Vec::new()
     }
 
@@ -150,14 +217,18 @@ impl Storage {
 
 #[cfg_attr(feature = "simx", event_rules)]
 impl DevsModel for Storage {
+    #[allow(clippy::unit_arg)]
     fn events_ext(
         &mut self,
         incoming_message: &ModelMessage,
         services: &mut Services,
     ) -> Result<(), SimulationError> {
+        self.state
+            .port_stats
+            .record_in(&incoming_message.port_name, services.global_time());
         match self.arrival_port(&incoming_message.port_name) {
             ArrivalPort::Put => Ok(self.hold_job(incoming_message, services)),
-            ArrivalPort::Get => Ok(self.get_job()),
+            ArrivalPort::Get => Ok(self.get_job(incoming_message)),
             ArrivalPort::Unknown => Err(SimulationError::InvalidMessage),
         }
     }
@@ -166,10 +237,16 @@ impl DevsModel for Storage {
         &mut self,
         services: &mut Services,
     ) -> Result<Vec<ModelMessage>, SimulationError> {
-        match &self.state.phase {
-            Phase::Passive => Ok(self.passivate()),
-            Phase::JobFetch => Ok(self.release_job(services)),
-        }
+        let outgoing_messages = match (&self.state.phase, &self.state.query) {
+            (Phase::Passive, _) => Ok(self.passivate()),
+            (Phase::JobFetch, Query::Get) => Ok(self.release_job(services)),
+            (Phase::JobFetch, Query::Summary) => self.release_summary(services),
+            (Phase::JobFetch, Query::Clear) => Ok(self.clear_job(services)),
+        }?;
+        self.state
+            .port_stats
+            .record_out(&outgoing_messages, services.global_time());
+        Ok(outgoing_messages)
     }
 
     fn time_advance(&mut self, time_delta: f64) {
@@ -192,6 +269,22 @@ impl Reportable for Storage {
     fn records(&self) -> &Vec<ModelRecord> {
         &self.state.records
     }
+
+    fn set_store_records(&mut self, store_records: bool) {
+        self.store_records = store_records;
+    }
+
+    fn port_stats(&self) -> Option<&PortStats> {
+        Some(&self.state.port_stats)
+    }
+
+    fn assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            AssumptionTopic::Storage,
+            "last-write",
+            "Only the most recently stored value is retained, and values are stored and retrieved instantaneously",
+        )]
+    }
 }
 
 impl ReportableModel for Storage {}
@@ -0,0 +1,227 @@
+//! Histograms and empirical distributions summarize the distribution of a
+//! simulation output (e.g. response times), rather than its mean.  Both
+//! serialize to JSON, for plotting in the web interface or notebooks.
+
+use serde::{Deserialize, Serialize};
+
+use super::{interpolated_quantile, sorted, validate_quantile};
+use crate::utils::errors::SimulationError;
+
+/// The bin width selection of a histogram.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Binning {
+    /// The Freedman-Diaconis rule - a width of `2 IQR / n^(1/3)`, robust
+    /// to outliers.  Points without an interquartile range fall back to
+    /// Sturges' rule, and then to a single bin.
+    #[default]
+    FreedmanDiaconis,
+    /// A fixed bin width
+    Width(f64),
+    /// A fixed number of bins, spanning the range of the points
+    Count(usize),
+}
+
+/// A histogram bin, covering `[lower, upper)` - the final bin also includes
+/// its upper edge.  The density is normalized, so the bin areas sum to 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub density: f64,
+}
+
+/// A histogram of equal-width bins, starting at the minimum point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Histogram {
+    start: f64,
+    bin_width: f64,
+    counts: Vec<usize>,
+}
+
+impl Histogram {
+    pub fn post(points: &[f64], binning: Binning) -> Result<Self, SimulationError> {
+        if points.is_empty() {
+            return Err(SimulationError::EmptySample);
+        }
+        let sorted = sorted(points);
+        let start = sorted[0];
+        let range = sorted[sorted.len() - 1] - start;
+        let bin_width = match binning {
+            Binning::Width(bin_width) => bin_width,
+            Binning::Count(count) => {
+                if count == 0 {
+                    return Err(SimulationError::InvalidBucketWidth(f64::INFINITY));
+                }
+                nonzero_width(range / count as f64)
+            }
+            Binning::FreedmanDiaconis => {
+                let interquartile_range =
+                    interpolated_quantile(&sorted, 0.75)? - interpolated_quantile(&sorted, 0.25)?;
+                let points_len = sorted.len() as f64;
+                if interquartile_range > 0.0 {
+                    2.0 * interquartile_range / points_len.cbrt()
+                } else {
+                    nonzero_width(range / (points_len.log2().ceil() + 1.0))
+                }
+            }
+        };
+        if !bin_width.is_finite() || bin_width <= 0.0 {
+            return Err(SimulationError::InvalidBucketWidth(bin_width));
+        }
+        let bin_count = ((range / bin_width).ceil() as usize).max(1);
+        let mut counts = vec![0; bin_count];
+        sorted.iter().for_each(|point| {
+            let bin = (((point - start) / bin_width).floor() as usize).min(bin_count - 1);
+            counts[bin] += 1;
+        });
+        Ok(Self {
+            start,
+            bin_width,
+            counts,
+        })
+    }
+
+    pub fn bin_width(&self) -> f64 {
+        self.bin_width
+    }
+
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    /// The total number of points.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// The bin edges, from the lower edge of the first bin to the upper edge
+    /// of the last bin.
+    pub fn edges(&self) -> Vec<f64> {
+        (0..=self.counts.len())
+            .map(|edge| self.start + edge as f64 * self.bin_width)
+            .collect()
+    }
+
+    /// The normalized bin densities.
+    pub fn densities(&self) -> Vec<f64> {
+        let total = self.total() as f64;
+        self.counts
+            .iter()
+            .map(|count| *count as f64 / (total * self.bin_width))
+            .collect()
+    }
+
+    pub fn bins(&self) -> Vec<Bin> {
+        let edges = self.edges();
+        self.counts
+            .iter()
+            .zip(self.densities())
+            .enumerate()
+            .map(|(bin, (count, density))| Bin {
+                lower: edges[bin],
+                upper: edges[bin + 1],
+                count: *count,
+                density,
+            })
+            .collect()
+    }
+}
+
+// Points without a range are binned into a single, unit-width bin
+fn nonzero_width(width: f64) -> f64 {
+    if width > 0.0 {
+        width
+    } else {
+        1.0
+    }
+}
+
+/// The empirical distribution of a set of points - the step-function
+/// cumulative distribution, and the interpolated quantiles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmpiricalDistribution {
+    points: Vec<f64>,
+}
+
+impl EmpiricalDistribution {
+    pub fn post(points: &[f64]) -> Result<Self, SimulationError> {
+        if points.is_empty() {
+            return Err(SimulationError::EmptySample);
+        }
+        Ok(Self {
+            points: sorted(points),
+        })
+    }
+
+    /// The sorted points.
+    pub fn points(&self) -> &[f64] {
+        &self.points
+    }
+
+    /// The fraction of the points at or below the value.
+    pub fn cdf(&self, value: f64) -> f64 {
+        self.points.partition_point(|point| *point <= value) as f64 / self.points.len() as f64
+    }
+
+    /// The quantile, interpolated linearly between the adjacent points.
+    pub fn quantile(&self, quantile: f64) -> Result<f64, SimulationError> {
+        validate_quantile(quantile)?;
+        interpolated_quantile(&self.points, quantile)
+    }
+
+    /// The histogram of the points.
+    pub fn histogram(&self, binning: Binning) -> Result<Histogram, SimulationError> {
+        Histogram::post(&self.points, binning)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histograms_bin_points_with_normalized_densities() {
+        let points: Vec<f64> = (0..100).map(f64::from).collect();
+        let histogram = Histogram::post(&points, Binning::Width(10.0)).unwrap();
+        assert_eq!(histogram.counts(), &[10; 10][..]);
+        assert_eq!(histogram.edges().last(), Some(&100.0));
+        let area: f64 = histogram
+            .bins()
+            .iter()
+            .map(|bin| bin.density * (bin.upper - bin.lower))
+            .sum();
+        assert!((area - 1.0).abs() < 1.0e-12);
+        // The maximum point is included in the final bin
+        let histogram = Histogram::post(&points, Binning::Count(3)).unwrap();
+        assert_eq!(histogram.counts(), &[33, 33, 34][..]);
+        // IQR of 49.5, over 100^(1/3)
+        let histogram = Histogram::post(&points, Binning::FreedmanDiaconis).unwrap();
+        assert!((histogram.bin_width() - 99.0 / 100.0f64.cbrt()).abs() < 1.0e-12);
+        assert_eq!(histogram.total(), 100);
+        let constant = Histogram::post(&[2.0; 5], Binning::FreedmanDiaconis).unwrap();
+        assert_eq!(constant.counts(), &[5][..]);
+        assert!(matches!(
+            Histogram::post(&points, Binning::Width(0.0)),
+            Err(SimulationError::InvalidBucketWidth(_))
+        ));
+        assert!(serde_json::to_string(&histogram.bins()).is_ok());
+    }
+
+    #[test]
+    fn empirical_distributions_step_through_the_points() {
+        let distribution = EmpiricalDistribution::post(&[3.0, 1.0, 2.0, 4.0]).unwrap();
+        assert_eq!(distribution.cdf(0.5), 0.0);
+        assert_eq!(distribution.cdf(2.0), 0.5);
+        assert_eq!(distribution.cdf(10.0), 1.0);
+        assert_eq!(distribution.quantile(0.5).unwrap(), 2.5);
+        assert!(matches!(
+            EmpiricalDistribution::post(&[]),
+            Err(SimulationError::EmptySample)
+        ));
+    }
+}
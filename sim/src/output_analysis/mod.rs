@@ -1,15 +1,35 @@
 //! The output analysis module provides standard statistical analysis tools
 //! for analyzing simulation outputs.  Independent, identically-distributed
-//! (IID) samples are analyzed with the `IndependentSample`.  Time series
-//! (including those with initialization bias and autocorrelation) can be
-//! analyzed with `TerminatingSimulationOutput` or `SteadyStateOutput`.
-
-use std::f64::INFINITY;
+//! (IID) samples are analyzed with the `IndependentSample`, for means, and
+//! for quantiles (e.g. p95 and p99 latencies) by order statistics.  Time
+//! series (including those with initialization bias and autocorrelation)
+//! can be analyzed with `TerminatingSimulationOutput` or
+//! `SteadyStateOutput`.  Steady-state initialization bias is removed by the
+//! MSER or MSER-5 rules, or a fixed deletion point chosen with Welch's
+//! graphical method.  Residual autocorrelation is quantified with the
+//! `effective_sample_size`, and steady-state confidence intervals may be
+//! adjusted for correlated batch means.  Residual trends (e.g. from an insufficient warm-up period)
+//! are detected with the rank-based Mann-Kendall test.  The
+//! `ReplicationController` adds replications until a confidence interval
+//! reaches a target precision.  Output distributions are summarized with
+//! the `Histogram` and `EmpiricalDistribution`, for plotting.  Streaming
+//! statistics (`Welford`, `P2Quantile`, and `Ewma`) summarize outputs one
+//! point at a time, for use within model state.  Antithetic pairs and
+//! scenario comparisons under common random numbers are analyzed by pair,
+//! with `antithetic_sample` and `compare_scenarios`.
 
 use num_traits::{Float, NumAssign};
 use serde::{Deserialize, Serialize};
 
+pub mod histogram;
+pub mod sequential;
+pub mod streaming;
 pub mod t_scores;
+pub mod variance_reduction;
+pub use self::histogram::{Bin, Binning, EmpiricalDistribution, Histogram};
+pub use self::sequential::{ReplicationController, SequentialSample};
+pub use self::streaming::{Ewma, P2Quantile, Welford};
+pub use self::variance_reduction::{antithetic_sample, compare_scenarios, ScenarioComparison};
 use crate::utils::errors::SimulationError;
 use crate::utils::usize_sqrt;
 
@@ -47,6 +67,196 @@ fn usize_to_float<T: Float>(unconv: usize) -> Result<T, SimulationError> {
     T::from(unconv).ok_or(SimulationError::FloatConvError)
 }
 
+/// This function sorts a set of points, in ascending order.  Incomparable
+/// points (NaN) are treated as equal.
+fn sorted<T: Float>(points: &[T]) -> Vec<T> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    sorted
+}
+
+/// This function calculates a quantile of sorted, non-empty points,
+/// interpolated linearly between the adjacent order statistics.
+fn interpolated_quantile<T: Float>(sorted: &[T], quantile: T) -> Result<T, SimulationError> {
+    let position = quantile * usize_to_float(sorted.len() - 1)?;
+    let lower = position.floor();
+    let lower_index = lower.to_usize().ok_or(SimulationError::FloatConvError)?;
+    let upper_index = (lower_index + 1).min(sorted.len() - 1);
+    Ok(sorted[lower_index] + (position - lower) * (sorted[upper_index] - sorted[lower_index]))
+}
+
+/// This function validates a quantile, which must be in the unit interval.
+fn validate_quantile<T: Float>(quantile: T) -> Result<(), SimulationError>
+where
+    f64: Into<T>,
+{
+    if quantile >= 0.0.into() && quantile <= 1.0.into() {
+        Ok(())
+    } else {
+        Err(SimulationError::InvalidQuantile(
+            quantile.to_f64().unwrap_or(f64::NAN),
+        ))
+    }
+}
+
+/// This function calculates the sample autocorrelation of a set of points at
+/// the given lag.  Points without variation are treated as uncorrelated.
+pub fn autocorrelation<T: Float>(points: &[T], lag: usize) -> Result<T, SimulationError>
+where
+    f64: Into<T>,
+{
+    if lag >= points.len() {
+        return Ok(0.0.into());
+    }
+    let mean = sample_mean(points)?;
+    let variance = sample_variance(points, &mean)?;
+    if variance == 0.0.into() {
+        return Ok(0.0.into());
+    }
+    let autocovariance = points
+        .iter()
+        .zip(points.iter().skip(lag))
+        .fold(0.0.into(), |acc: T, (point, lagged)| {
+            acc + (*point - mean) * (*lagged - mean)
+        })
+        / usize_to_float(points.len())?;
+    Ok(autocovariance / variance)
+}
+
+/// This function estimates the effective sample size of a set of
+/// (potentially autocorrelated) points - the number of independent points
+/// carrying the same information about the mean.  The estimate sums the
+/// autocorrelation function up to the first non-positive lag, and is bounded
+/// by 1 and the number of points.
+pub fn effective_sample_size<T: Float>(points: &[T]) -> Result<T, SimulationError>
+where
+    f64: Into<T>,
+{
+    let points_len: T = usize_to_float(points.len())?;
+    let mut autocorrelation_sum: T = 0.0.into();
+    for lag in 1..points.len() / 2 {
+        let lag_autocorrelation = autocorrelation(points, lag)?;
+        if lag_autocorrelation <= 0.0.into() {
+            break;
+        }
+        autocorrelation_sum = autocorrelation_sum + lag_autocorrelation;
+    }
+    let effective_size = points_len / (1.0.into() + autocorrelation_sum * 2.0.into());
+    Ok(effective_size.max(1.0.into()).min(points_len))
+}
+
+/// This function calculates the Mann-Kendall trend statistic of a set of
+/// points - the normalized count of increasing, less decreasing, point
+/// pairs, with the variance corrected for ties.  Without a trend, the
+/// statistic is approximately standard normal, and it is positive for
+/// increasing trends, and negative for decreasing trends.  Fewer than 3
+/// points, or points without variation, show no trend.
+pub fn mann_kendall<T: Float>(points: &[T]) -> Result<T, SimulationError>
+where
+    f64: Into<T>,
+{
+    if points.len() < 3 {
+        return Ok(0.0.into());
+    }
+    let s = points.iter().enumerate().fold(0i64, |s, (index, earlier)| {
+        points[index + 1..].iter().fold(s, |s, later| {
+            if later > earlier {
+                s + 1
+            } else if later < earlier {
+                s - 1
+            } else {
+                s
+            }
+        })
+    });
+    let sorted = sorted(points);
+    let tie_count = |count: usize| -> Result<T, SimulationError> {
+        let count: T = usize_to_float(count)?;
+        Ok(count * (count - 1.0.into()) * (count * 2.0.into() + 5.0.into()))
+    };
+    let mut ties: T = 0.0.into();
+    let mut run_start = 0;
+    for index in 1..=sorted.len() {
+        if index == sorted.len() || sorted[index] != sorted[run_start] {
+            ties = ties + tie_count(index - run_start)?;
+            run_start = index;
+        }
+    }
+    let variance = (tie_count(points.len())? - ties) / 18.0.into();
+    if s == 0 || variance <= 0.0.into() {
+        return Ok(0.0.into());
+    }
+    // Continuity correction, towards zero
+    let s: T = T::from(s - s.signum()).ok_or(SimulationError::FloatConvError)?;
+    Ok(s / variance.sqrt())
+}
+
+/// This function finds the MSER deletion point of a time series - the
+/// deletion point, within the first half of the series, minimizing the
+/// marginal standard error of the remaining points, `sum((x - mean)^2) /
+/// (n - d)^2` for the `n - d` points remaining after deleting `d` points.
+fn mser_deletion_point<T: Float + NumAssign>(time_series: &[T]) -> Result<usize, SimulationError>
+where
+    f64: Into<T>,
+{
+    if time_series.len() < 2 {
+        return Err(SimulationError::InvalidExperimentConfiguration);
+    }
+    let mut s: T = 0.0.into();
+    let mut q: T = 0.0.into();
+    let mut mser = vec![0.0.into(); time_series.len()];
+    for d in (0..time_series.len()).rev() {
+        s += time_series[d];
+        q += time_series[d].powi(2);
+        let remaining: T = usize_to_float(time_series.len() - d)?;
+        mser[d] = (q - s.powi(2) / remaining) / remaining.powi(2);
+    }
+    // Find the minimum MSER in the first half of the time series
+    (0..time_series.len() / 2)
+        .fold(None, |min: Option<usize>, d| match min {
+            Some(min) if mser[min] <= mser[d] => Some(min),
+            _ => Some(d),
+        })
+        .ok_or(SimulationError::PrerequisiteCalcError)
+}
+
+/// This function provides the data for Welch's graphical method of
+/// initialization bias detection - the moving average, over a window of
+/// `window` points on either side, of the mean across replications at each
+/// observation index.  Near the start of the series, the window shrinks to
+/// the available points.  Replications are truncated to the shortest
+/// replication.  The warm-up period is then chosen (by inspection of a
+/// plot) as the index where the moving average levels off, and applied
+/// with `TruncationMethod::Fixed`.
+pub fn welch_moving_average<T: Float>(
+    replications: &[Vec<T>],
+    window: usize,
+) -> Result<Vec<T>, SimulationError>
+where
+    f64: Into<T>,
+{
+    let len = replications
+        .iter()
+        .map(Vec::len)
+        .min()
+        .ok_or(SimulationError::EmptyReplicationSet)?;
+    let means = (0..len)
+        .map(|index| {
+            let points: Vec<T> = replications
+                .iter()
+                .map(|replication| replication[index])
+                .collect();
+            sample_mean(&points)
+        })
+        .collect::<Result<Vec<T>, SimulationError>>()?;
+    (0..len.saturating_sub(window))
+        .map(|index| {
+            let half_width = index.min(window);
+            sample_mean(&means[index - half_width..=index + half_width])
+        })
+        .collect()
+}
+
 /// The confidence interval provides an upper and lower estimate on a given
 /// output, whether that output is an independent, identically-distributed
 /// sample or time series data.
@@ -73,6 +283,134 @@ where
     }
 }
 
+/// The adjusted confidence interval accounts for residual autocorrelation in
+/// steady-state batch means.  The lag-1 autocorrelation of the batch means
+/// is reported as a warning metric - batches are flagged as correlated when
+/// it exceeds the approximate 95% significance bound, `2/sqrt(k)` for `k`
+/// batches.  The interval is based on the effective batch count, so
+/// correlated batches widen the interval rather than silently providing an
+/// overconfident one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdjustedConfidenceInterval<T: Float> {
+    interval: ConfidenceInterval<T>,
+    lag1_autocorrelation: T,
+    effective_batch_count: T,
+    correlated: bool,
+}
+
+impl<T: Float> AdjustedConfidenceInterval<T>
+where
+    f64: Into<T>,
+{
+    pub fn interval(&self) -> &ConfidenceInterval<T> {
+        &self.interval
+    }
+
+    pub fn lag1_autocorrelation(&self) -> T {
+        self.lag1_autocorrelation
+    }
+
+    pub fn effective_batch_count(&self) -> T {
+        self.effective_batch_count
+    }
+
+    pub fn correlated(&self) -> bool {
+        self.correlated
+    }
+}
+
+/// The percentile summary of a sample, as commonly reported for latencies
+/// and other heavy-tailed outputs, where the tail matters more than the
+/// mean.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PercentileSummary<T: Float> {
+    pub min: T,
+    pub p25: T,
+    pub median: T,
+    pub p75: T,
+    pub p90: T,
+    pub p95: T,
+    pub p99: T,
+    pub max: T,
+}
+
+/// The initialization bias truncation method of a steady-state output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TruncationMethod {
+    /// The marginal standard error rule, on the individual points
+    #[default]
+    Mser,
+    /// The marginal standard error rule, on the means of consecutive
+    /// batches of 5 points - less sensitive to noise in the individual
+    /// points
+    Mser5,
+    /// A fixed number of deleted points (e.g. from Welch's graphical
+    /// method)
+    Fixed(usize),
+}
+
+impl TruncationMethod {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The batch means configuration of a steady-state output - the points
+/// deleted for initialization bias reduction, and the size and count of the
+/// batches of the remaining points.  Any leftover points, after batching,
+/// are deleted from the beginning of the series, and so are included in
+/// the deletion point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchConfiguration {
+    pub deletion_point: usize,
+    pub batch_size: usize,
+    pub batch_count: usize,
+}
+
+/// The stationarity test checks the batch means of a steady-state output -
+/// after the deletion point - for a remaining trend, with the Mann-Kendall
+/// test.  A trend suggests the deletion point is too early, or the run is
+/// too short to reach steady state, so the warm-up decision should be
+/// revisited.  The test statistic is compared to the two-sided critical
+/// value of the standard normal distribution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StationarityTest<T: Float> {
+    statistic: T,
+    critical_value: T,
+    deletion_point: usize,
+    batch_count: usize,
+    trend: bool,
+}
+
+impl<T: Float> StationarityTest<T>
+where
+    f64: Into<T>,
+{
+    /// The Mann-Kendall statistic of the batch means.
+    pub fn statistic(&self) -> T {
+        self.statistic
+    }
+
+    pub fn critical_value(&self) -> T {
+        self.critical_value
+    }
+
+    pub fn deletion_point(&self) -> usize {
+        self.deletion_point
+    }
+
+    pub fn batch_count(&self) -> usize {
+        self.batch_count
+    }
+
+    /// Whether the batch means show a significant trend.
+    pub fn trend(&self) -> bool {
+        self.trend
+    }
+}
+
 /// The independent sample is for independent, identically-distributed (IID)
 /// samples, or where treating the data as an IID sample is determined to be
 /// reasonable.  Typically, this will be non-time series data - no
@@ -135,6 +473,77 @@ where
     pub fn variance(&self) -> T {
         self.variance
     }
+
+    /// Return the sample points.
+    pub fn points(&self) -> &[T] {
+        &self.points
+    }
+
+    /// Return the sample quantile, interpolated linearly between the
+    /// adjacent order statistics.  The quantile is between 0 and 1 (e.g.
+    /// 0.95 for the 95th percentile).
+    pub fn point_estimate_quantile(&self, quantile: T) -> Result<T, SimulationError> {
+        validate_quantile(quantile)?;
+        if self.points.is_empty() {
+            return Err(SimulationError::EmptySample);
+        }
+        interpolated_quantile(&sorted(&self.points), quantile)
+    }
+
+    /// Return the sample median.
+    pub fn median(&self) -> Result<T, SimulationError> {
+        self.point_estimate_quantile(0.5.into())
+    }
+
+    /// Calculate the distribution-free confidence interval of the quantile,
+    /// base on the provided value of alpha.  The bounds are order
+    /// statistics, with ranks from the normal approximation to the binomial
+    /// distribution of the number of points below the quantile, so no
+    /// distributional assumptions are made about the points.  Small samples
+    /// and extreme quantiles (e.g. 10 points, for the 99th percentile) widen
+    /// to the sample extremes.
+    pub fn confidence_interval_quantile(
+        &self,
+        quantile: T,
+        alpha: T,
+    ) -> Result<ConfidenceInterval<T>, SimulationError> {
+        validate_quantile(quantile)?;
+        if self.points.is_empty() {
+            return Err(SimulationError::EmptySample);
+        }
+        let sorted = sorted(&self.points);
+        let points_len: T = usize_to_float(sorted.len())?;
+        let center = points_len * quantile;
+        let spread = t_scores::t_score(alpha, usize::MAX)
+            * (points_len * quantile * (T::one() - quantile)).sqrt();
+        // Ranks are 1-based, and clamped to the sample
+        let rank = |rank: T| -> Result<usize, SimulationError> {
+            Ok(rank
+                .max(T::one())
+                .min(points_len)
+                .to_usize()
+                .ok_or(SimulationError::FloatConvError)?
+                - 1)
+        };
+        Ok(ConfidenceInterval {
+            lower: sorted[rank((center - spread).floor())?],
+            upper: sorted[rank((center + spread).ceil())?],
+        })
+    }
+
+    /// Return the percentile summary of the sample.
+    pub fn percentile_summary(&self) -> Result<PercentileSummary<T>, SimulationError> {
+        Ok(PercentileSummary {
+            min: self.point_estimate_quantile(0.0.into())?,
+            p25: self.point_estimate_quantile(0.25.into())?,
+            median: self.median()?,
+            p75: self.point_estimate_quantile(0.75.into())?,
+            p90: self.point_estimate_quantile(0.9.into())?,
+            p95: self.point_estimate_quantile(0.95.into())?,
+            p99: self.point_estimate_quantile(0.99.into())?,
+            max: self.point_estimate_quantile(1.0.into())?,
+        })
+    }
 }
 
 /// Terminating simulations are useful when the initial and final conditions
@@ -187,6 +596,8 @@ impl<T: Float> TerminatingSimulationOutput<T> {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct SteadyStateOutput<T> {
     time_series: Vec<T>,
+    #[serde(default, skip_serializing_if = "TruncationMethod::is_default")]
+    truncation: TruncationMethod,
     /// Points are removed from the beginning of the sample for initialization
     /// bias reduction.
     deletion_point: Option<usize>,
@@ -207,6 +618,7 @@ where
     pub fn post(time_series: Vec<T>) -> SteadyStateOutput<T> {
         SteadyStateOutput {
             time_series,
+            truncation: TruncationMethod::default(),
             deletion_point: None,
             batch_size: None,
             batch_count: None,
@@ -224,27 +636,23 @@ where
     /// strategy/configuration, the `calculate_batch_statistics` then
     /// executes the processing.
     fn set_to_fixed_budget(&mut self) -> Result<(), SimulationError> {
-        let mut s = 0.0.into();
-        let mut q = 0.0.into();
-        let mut d = self.time_series.len() - 2;
-        let mut mser = vec![0.0.into(); self.time_series.len() - 1];
-        let time_series_len: T = usize_to_float(self.time_series.len())?;
-        loop {
-            s += self.time_series[d + 1];
-            q += self.time_series[d + 1].powi(2);
-            mser[d] = q - s.powi(2) / (time_series_len - usize_to_float(d)?).powi(3);
-            if d == 0 {
-                // Find the minimum MSER in the first half of the time series
-                let min_mser = (0..(self.time_series.len() - 1) / 2)
-                    .fold(INFINITY.into(), |min_mser, mser_index| {
-                        min_mser.min(mser[mser_index])
-                    });
-                // Use that point for deletion determination
-                self.deletion_point = mser.iter().position(|mser_value| *mser_value == min_mser);
-                break;
+        self.deletion_point = Some(match self.truncation {
+            TruncationMethod::Mser => mser_deletion_point(&self.time_series)?,
+            TruncationMethod::Mser5 => {
+                let batch_means = self
+                    .time_series
+                    .chunks_exact(5)
+                    .map(sample_mean)
+                    .collect::<Result<Vec<T>, SimulationError>>()?;
+                mser_deletion_point(&batch_means)? * 5
             }
-            d -= 1;
-        }
+            TruncationMethod::Fixed(deletion_point) => {
+                if deletion_point >= self.time_series.len() {
+                    return Err(SimulationError::InvalidExperimentConfiguration);
+                }
+                deletion_point
+            }
+        });
         // Schmeiser [1982] found that, for a fixed total sample size, there
         // is little benefit from dividing it into more than k = 30 batches,
         // even if we could do so and still retain independence between the
@@ -297,6 +705,40 @@ where
         Ok(())
     }
 
+    /// Select the initialization bias truncation method, in place of the
+    /// default MSER rule.
+    pub fn with_truncation(mut self, truncation: TruncationMethod) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    /// The number of points deleted from the beginning of the time series,
+    /// for initialization bias reduction.  If not already processed, the
+    /// deletion point is determined with the truncation method.
+    pub fn deletion_point(&mut self) -> Result<usize, SimulationError> {
+        Ok(self.batch_configuration()?.deletion_point)
+    }
+
+    /// The batch means configuration of the time series.  If not already
+    /// processed, the configuration is determined with the truncation
+    /// method and the fixed budget batching rule.
+    pub fn batch_configuration(&mut self) -> Result<BatchConfiguration, SimulationError> {
+        if self.batch_count.is_none() {
+            self.set_to_fixed_budget()?;
+        }
+        Ok(BatchConfiguration {
+            deletion_point: self
+                .deletion_point
+                .ok_or(SimulationError::PrerequisiteCalcError)?,
+            batch_size: self
+                .batch_size
+                .ok_or(SimulationError::PrerequisiteCalcError)?,
+            batch_count: self
+                .batch_count
+                .ok_or(SimulationError::PrerequisiteCalcError)?,
+        })
+    }
+
     /// The method provides a confidence interval on the mean, for the
     /// simuation output.  If not already processed, the raw data will first
     /// use standard approaches for initialization bias reduction and
@@ -334,6 +776,85 @@ where
         })
     }
 
+    /// The method provides a confidence interval on the mean, adjusted for
+    /// residual autocorrelation between the batch means.  The variance of
+    /// the mean and the degrees of freedom are based on the effective batch
+    /// count, rather than the nominal batch count.  For uncorrelated batch
+    /// means, the interval matches `confidence_interval_mean`.
+    pub fn adjusted_confidence_interval_mean(
+        &mut self,
+        alpha: T,
+    ) -> Result<AdjustedConfidenceInterval<T>, SimulationError> {
+        let interval = self.confidence_interval_mean(alpha)?;
+        let batch_count = self
+            .batch_count
+            .ok_or(SimulationError::PrerequisiteCalcError)?;
+        let f_batch_count: T = usize_to_float(batch_count)?;
+        let lag1_autocorrelation = autocorrelation(&self.batch_means, 1)?;
+        let effective_batch_count = effective_sample_size(&self.batch_means)?;
+        let correlated = batch_count > 1
+            && lag1_autocorrelation
+                > T::from(2.0).ok_or(SimulationError::FloatConvError)? / f_batch_count.sqrt();
+        if batch_count == 1 || effective_batch_count >= f_batch_count {
+            return Ok(AdjustedConfidenceInterval {
+                interval,
+                lag1_autocorrelation,
+                effective_batch_count,
+                correlated,
+            });
+        }
+        let batches_mean = self
+            .batches_mean
+            .ok_or(SimulationError::PrerequisiteCalcError)?;
+        let batches_variance = self
+            .batches_variance
+            .ok_or(SimulationError::PrerequisiteCalcError)?;
+        let degrees_of_freedom = effective_batch_count
+            .floor()
+            .to_usize()
+            .ok_or(SimulationError::FloatConvError)?
+            .saturating_sub(1)
+            .max(1);
+        let half_width = t_scores::t_score(alpha, degrees_of_freedom)
+            * (batches_variance / effective_batch_count).sqrt();
+        Ok(AdjustedConfidenceInterval {
+            interval: ConfidenceInterval {
+                lower: batches_mean - half_width,
+                upper: batches_mean + half_width,
+            },
+            lag1_autocorrelation,
+            effective_batch_count,
+            correlated,
+        })
+    }
+
+    /// The method tests the batch means for a remaining trend, at the
+    /// significance level alpha (two-sided).  If not already processed, the
+    /// raw data will first use standard approaches for initialization bias
+    /// reduction and autocorrelation management.
+    pub fn stationarity_test(&mut self, alpha: T) -> Result<StationarityTest<T>, SimulationError> {
+        if self.batches_mean.is_none() {
+            self.calculate_batch_statistics()?;
+        }
+        let deletion_point = self
+            .deletion_point
+            .ok_or(SimulationError::PrerequisiteCalcError)?;
+        let batch_count = self
+            .batch_count
+            .ok_or(SimulationError::PrerequisiteCalcError)?;
+        let statistic = mann_kendall(&self.batch_means)?;
+        // The normal critical value, as the t score with unbounded degrees
+        // of freedom
+        let critical_value = t_scores::t_score(alpha / 2.0.into(), usize::MAX);
+        Ok(StationarityTest {
+            statistic,
+            critical_value,
+            deletion_point,
+            batch_count,
+            trend: statistic.abs() > critical_value,
+        })
+    }
+
     /// The method provides a point estimate on the mean, for the simulation
     /// output.  If not already processed, the raw data will first use
     /// standard approaches for initialization bias reduction and
@@ -364,4 +885,138 @@ mod tests {
         assert!((confidence_interval.lower - 0.7492630635369267).abs() < epsilon());
         assert!((confidence_interval.upper - 1.534736936463073).abs() < epsilon());
     }
+
+    #[test]
+    fn quantiles_are_order_statistics() {
+        let sample = IndependentSample::post((1..=100).map(f64::from).rev().collect()).unwrap();
+        assert_eq!(sample.median().unwrap(), 50.5);
+        assert!((sample.point_estimate_quantile(0.95).unwrap() - 95.05).abs() < epsilon());
+        let summary = sample.percentile_summary().unwrap();
+        assert_eq!((summary.min, summary.max), (1.0, 100.0));
+        assert!((summary.p99 - 99.01).abs() < epsilon());
+        // Ranks 50 -/+ 1.645 * 5, to the order statistics 41 and 59
+        let interval = sample.confidence_interval_quantile(0.5, 0.05).unwrap();
+        assert_eq!((interval.lower(), interval.upper()), (41.0, 59.0));
+        // Too few points to bound an extreme quantile, within the sample
+        let small = IndependentSample::post(vec![3.0, 1.0, 2.0]).unwrap();
+        let interval = small.confidence_interval_quantile(0.99, 0.05).unwrap();
+        assert_eq!((interval.lower(), interval.upper()), (2.0, 3.0));
+        assert!(matches!(
+            sample.point_estimate_quantile(1.5),
+            Err(SimulationError::InvalidQuantile(_))
+        ));
+        assert!(matches!(
+            IndependentSample::<f64>::post(Vec::new()).unwrap().median(),
+            Err(SimulationError::EmptySample)
+        ));
+    }
+
+    /// A first-order autoregressive series, with uniform innovations
+    fn autoregressive(coefficient: f64, len: usize) -> Vec<f64> {
+        use rand::Rng;
+        let mut rng = rand_pcg::Pcg64Mcg::new(42);
+        (0..len)
+            .scan(0.0, |value, _| {
+                *value = coefficient * *value + rng.gen_range(-1.0..1.0);
+                Some(*value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn effective_sample_size_reflects_autocorrelation() {
+        let independent = autoregressive(0.0, 1000);
+        let correlated = autoregressive(0.9, 1000);
+        assert!(autocorrelation(&correlated, 1).unwrap() > 0.8);
+        assert!(effective_sample_size(&independent).unwrap() > 800.0);
+        // The expected effective sample size is n(1-phi)/(1+phi), about 53
+        assert!(effective_sample_size(&correlated).unwrap() < 150.0);
+        assert_eq!(effective_sample_size(&[1.0, 1.0, 1.0]).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn adjusted_confidence_interval_widens_for_correlated_batches() {
+        // Highly persistent series leave correlation between batch means
+        let mut output = SteadyStateOutput::post(autoregressive(0.995, 2000));
+        let interval = output.confidence_interval_mean(0.05).unwrap();
+        let adjusted = output.adjusted_confidence_interval_mean(0.05).unwrap();
+        assert!(adjusted.correlated());
+        assert!(adjusted.effective_batch_count() < 20.0);
+        assert!(adjusted.interval().half_width() > interval.half_width());
+    }
+
+    #[test]
+    fn truncation_methods_remove_initialization_bias() {
+        // A transient decaying from 20 over the first 200 points
+        let biased: Vec<f64> = autoregressive(0.0, 2000)
+            .iter()
+            .enumerate()
+            .map(|(index, value)| value + 20.0 * (1.0 - index as f64 / 200.0).max(0.0))
+            .collect();
+        let len = biased.len();
+        [TruncationMethod::Mser, TruncationMethod::Mser5]
+            .iter()
+            .for_each(|truncation| {
+                let mut output =
+                    SteadyStateOutput::post(biased.clone()).with_truncation(*truncation);
+                let configuration = output.batch_configuration().unwrap();
+                assert!(configuration.deletion_point >= 150);
+                assert!(configuration.deletion_point < len / 2);
+                assert_eq!(
+                    configuration.deletion_point
+                        + configuration.batch_size * configuration.batch_count,
+                    len
+                );
+                assert!(output.point_estimate_mean().unwrap().abs() < 0.5);
+            });
+        let mut fixed =
+            SteadyStateOutput::post(biased.clone()).with_truncation(TruncationMethod::Fixed(300));
+        assert!(fixed.deletion_point().unwrap() >= 300);
+        let mut invalid =
+            SteadyStateOutput::post(biased).with_truncation(TruncationMethod::Fixed(len));
+        assert!(matches!(
+            invalid.deletion_point(),
+            Err(SimulationError::InvalidExperimentConfiguration)
+        ));
+    }
+
+    #[test]
+    fn welch_moving_average_smooths_replication_means() {
+        let replications = vec![vec![0.0, 2.0, 4.0, 6.0, 8.0], vec![2.0, 4.0, 6.0, 8.0]];
+        // Means of 1, 3, 5, 7, with windows shrinking near the start
+        assert_eq!(
+            welch_moving_average(&replications, 1).unwrap(),
+            vec![1.0, 3.0, 5.0]
+        );
+        assert_eq!(welch_moving_average(&replications, 0).unwrap().len(), 4);
+        assert!(matches!(
+            welch_moving_average::<f64>(&[], 1),
+            Err(SimulationError::EmptyReplicationSet)
+        ));
+    }
+
+    #[test]
+    fn stationarity_test_detects_remaining_trends() {
+        // S = 6, with variance 4 * 3 * 13 / 18
+        let statistic = mann_kendall(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert!((statistic - 5.0 / (52.0f64 / 6.0).sqrt()).abs() < epsilon());
+        assert!(mann_kendall(&[4.0, 3.0, 2.0, 1.0]).unwrap() < 0.0);
+        assert_eq!(mann_kendall(&[1.0, 1.0, 1.0]).unwrap(), 0.0);
+        let mut stationary = SteadyStateOutput::post(autoregressive(0.0, 2000));
+        let test = stationary.stationarity_test(0.05).unwrap();
+        assert!(!test.trend());
+        assert!((test.critical_value() - 1.96).abs() < epsilon());
+        assert_eq!(test.batch_count(), 30);
+        // A drift throughout the run is not removed by initial data deletion
+        let drifting: Vec<f64> = autoregressive(0.0, 2000)
+            .iter()
+            .enumerate()
+            .map(|(index, value)| value + index as f64 * 0.005)
+            .collect();
+        let test = SteadyStateOutput::post(drifting)
+            .stationarity_test(0.05)
+            .unwrap();
+        assert!(test.trend());
+        assert!(test.statistic() > test.critical_value());
+    }
 }
@@ -0,0 +1,144 @@
+//! Sequential sampling adds replications until the confidence interval of
+//! the metric mean is sufficiently precise - the half width is at or below
+//! a target.  The replications are generic over the simulation type: the
+//! factory creates (and typically executes) the simulation of each
+//! replication, and the metric function summarizes it.
+
+use serde::{Deserialize, Serialize};
+
+use super::{ConfidenceInterval, IndependentSample};
+use crate::utils::errors::SimulationError;
+
+/// The sequential stopping rule.  At least `min_replications` replications
+/// are run before the half width is checked, so an early, unrepresentative
+/// pair of replications cannot stop the sampling, and at most
+/// `max_replications` are run, so an unreachable target cannot run
+/// forever.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicationController {
+    pub min_replications: usize,
+    pub max_replications: usize,
+}
+
+impl Default for ReplicationController {
+    fn default() -> Self {
+        Self {
+            min_replications: 5,
+            max_replications: 1000,
+        }
+    }
+}
+
+/// The replication sample of a sequential stopping rule, with the final
+/// confidence interval.  The target is not reached when the replications
+/// are exhausted first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequentialSample {
+    pub sample: IndependentSample<f64>,
+    pub confidence_interval: ConfidenceInterval<f64>,
+    pub target_reached: bool,
+}
+
+impl SequentialSample {
+    pub fn replications(&self) -> usize {
+        self.sample.points().len()
+    }
+
+    pub fn half_width(&self) -> f64 {
+        self.confidence_interval.half_width()
+    }
+}
+
+impl ReplicationController {
+    pub fn new(min_replications: usize, max_replications: usize) -> Self {
+        Self {
+            min_replications,
+            max_replications,
+        }
+    }
+
+    /// Add replications until the half width of the confidence interval of
+    /// the metric mean is at or below the target.  The factory receives the
+    /// replication index (e.g. for seeding), and the metric function
+    /// summarizes the replication.
+    pub fn run_until_half_width<S, F, M>(
+        &self,
+        mut simulation_factory: F,
+        mut metric: M,
+        target_half_width: f64,
+        alpha: f64,
+    ) -> Result<SequentialSample, SimulationError>
+    where
+        F: FnMut(usize) -> Result<S, SimulationError>,
+        M: FnMut(&S) -> Result<f64, SimulationError>,
+    {
+        if self.max_replications < self.min_replications.max(2) {
+            return Err(SimulationError::InvalidExperimentConfiguration);
+        }
+        let mut points: Vec<f64> = Vec::new();
+        loop {
+            points.push(metric(&simulation_factory(points.len())?)?);
+            if points.len() < self.min_replications.max(2) {
+                continue;
+            }
+            let sample = IndependentSample::post(points.clone())?;
+            let confidence_interval = sample.confidence_interval_mean(alpha)?;
+            let target_reached = confidence_interval.half_width() <= target_half_width;
+            if target_reached || points.len() == self.max_replications {
+                return Ok(SequentialSample {
+                    sample,
+                    confidence_interval,
+                    target_reached,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A deterministic stand-in for a stochastic metric, alternating about a
+    // mean of 10.0
+    fn alternating(replication: &usize) -> Result<f64, SimulationError> {
+        Ok(if replication.is_multiple_of(2) {
+            9.0
+        } else {
+            11.0
+        })
+    }
+
+    #[test]
+    fn replications_are_added_until_the_target_half_width() {
+        let controller = ReplicationController::default();
+        let result = controller
+            .run_until_half_width(Ok, alternating, 0.5, 0.05)
+            .unwrap();
+        assert!(result.target_reached);
+        assert!(result.half_width() <= 0.5);
+        assert!(result.replications() > controller.min_replications);
+        // The previous replication count did not reach the target
+        let previous = IndependentSample::post(
+            (0..result.replications() - 1)
+                .map(|replication| alternating(&replication).unwrap())
+                .collect(),
+        )
+        .unwrap()
+        .confidence_interval_mean(0.05)
+        .unwrap();
+        assert!(previous.half_width() > 0.5);
+        // An unreachable target stops at the maximum replications
+        let capped = ReplicationController::new(5, 20)
+            .run_until_half_width(Ok, alternating, 0.0, 0.05)
+            .unwrap();
+        assert!(!capped.target_reached);
+        assert_eq!(capped.replications(), 20);
+        assert!(matches!(
+            ReplicationController::new(5, 1).run_until_half_width(Ok, alternating, 0.5, 0.05),
+            Err(SimulationError::InvalidExperimentConfiguration)
+        ));
+    }
+}
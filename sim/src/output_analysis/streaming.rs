@@ -0,0 +1,328 @@
+//! Streaming statistics summarize a series of points one point at a time,
+//! in constant memory, without retaining the points.  They serialize, so
+//! models may hold them in their state (and through snapshots), rather than
+//! deriving statistics from stored records.  `Welford` accumulates the
+//! count, mean, variance, and range, `P2Quantile` estimates a single
+//! quantile with the P² algorithm of Jain and Chlamtac, and `Ewma` tracks an
+//! exponentially weighted moving average, for recent behavior.
+
+use serde::{Deserialize, Serialize};
+
+use super::{interpolated_quantile, sorted, validate_quantile};
+use crate::utils::errors::SimulationError;
+
+/// Welford's online algorithm for the mean and variance, numerically stable
+/// for long series.  Accumulators of separate series (e.g. parallel
+/// replications) can be merged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Welford {
+    count: usize,
+    mean: f64,
+    // The sum of squared deviations from the mean
+    squared_deviations: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Welford {
+    pub fn push(&mut self, point: f64) {
+        self.count += 1;
+        let delta = point - self.mean;
+        self.mean += delta / self.count as f64;
+        self.squared_deviations += delta * (point - self.mean);
+        self.min = Some(self.min.map_or(point, |min| min.min(point)));
+        self.max = Some(self.max.map_or(point, |max| max.max(point)));
+    }
+
+    /// Combine the accumulator with that of another series, as if every
+    /// point had been pushed to a single accumulator.
+    pub fn merge(&mut self, other: &Welford) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.squared_deviations += other.squared_deviations
+            + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.count = count;
+        self.min = combine(self.min, other.min, f64::min);
+        self.max = combine(self.max, other.max, f64::max);
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.count > 0 {
+            Some(self.mean)
+        } else {
+            None
+        }
+    }
+
+    /// The sample variance, with Bessel's correction - undefined for fewer
+    /// than two points.
+    pub fn variance(&self) -> Option<f64> {
+        if self.count > 1 {
+            Some(self.squared_deviations / (self.count - 1) as f64)
+        } else {
+            None
+        }
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+}
+
+fn combine(a: Option<f64>, b: Option<f64>, select: fn(f64, f64) -> f64) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(select(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// The P² estimator of a single quantile, tracking five markers - the
+/// minimum, the maximum, the target quantile, and the quantiles halfway to
+/// each extreme.  Until five points have been pushed, the quantile is
+/// interpolated from the points directly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct P2Quantile {
+    quantile: f64,
+    count: usize,
+    // Marker heights, and actual and desired marker positions
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired_positions: [f64; 5],
+}
+
+impl P2Quantile {
+    pub fn new(quantile: f64) -> Result<Self, SimulationError> {
+        validate_quantile(quantile)?;
+        Ok(Self {
+            quantile,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired_positions: [
+                1.0,
+                1.0 + 2.0 * quantile,
+                1.0 + 4.0 * quantile,
+                3.0 + 2.0 * quantile,
+                5.0,
+            ],
+        })
+    }
+
+    pub fn quantile(&self) -> f64 {
+        self.quantile
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn push(&mut self, point: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = point;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights = sorted_heights(&self.heights);
+            }
+            return;
+        }
+        self.count += 1;
+        // The cell of the new point, extending the extremes as needed
+        let cell = if point < self.heights[0] {
+            self.heights[0] = point;
+            0
+        } else if point >= self.heights[4] {
+            self.heights[4] = point;
+            3
+        } else {
+            (1..5)
+                .find(|marker| point < self.heights[*marker])
+                .map_or(3, |marker| marker - 1)
+        };
+        self.positions[cell + 1..]
+            .iter_mut()
+            .for_each(|position| *position += 1.0);
+        let quantile = self.quantile;
+        let increments = [0.0, quantile / 2.0, quantile, (1.0 + quantile) / 2.0, 1.0];
+        self.desired_positions
+            .iter_mut()
+            .zip(increments.iter())
+            .for_each(|(position, increment)| *position += increment);
+        (1..4).for_each(|marker| self.adjust(marker));
+    }
+
+    // Move a middle marker towards its desired position, if it is at least
+    // one position away and the neighboring marker is not adjacent
+    fn adjust(&mut self, marker: usize) {
+        let offset = self.desired_positions[marker] - self.positions[marker];
+        let (n, q) = (&self.positions, &self.heights);
+        if !((offset >= 1.0 && n[marker + 1] - n[marker] > 1.0)
+            || (offset <= -1.0 && n[marker - 1] - n[marker] < -1.0))
+        {
+            return;
+        }
+        let step = offset.signum();
+        let parabolic = q[marker]
+            + step / (n[marker + 1] - n[marker - 1])
+                * ((n[marker] - n[marker - 1] + step) * (q[marker + 1] - q[marker])
+                    / (n[marker + 1] - n[marker])
+                    + (n[marker + 1] - n[marker] - step) * (q[marker] - q[marker - 1])
+                        / (n[marker] - n[marker - 1]));
+        self.heights[marker] = if q[marker - 1] < parabolic && parabolic < q[marker + 1] {
+            parabolic
+        } else {
+            let neighbor = if step > 0.0 { marker + 1 } else { marker - 1 };
+            q[marker] + step * (q[neighbor] - q[marker]) / (n[neighbor] - n[marker])
+        };
+        self.positions[marker] += step;
+    }
+
+    /// The quantile estimate, if any points have been pushed.
+    pub fn estimate(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            count if count < 5 => {
+                interpolated_quantile(&sorted(&self.heights[..count]), self.quantile).ok()
+            }
+            _ => Some(self.heights[2]),
+        }
+    }
+}
+
+fn sorted_heights(heights: &[f64; 5]) -> [f64; 5] {
+    let mut sorted_heights = [0.0; 5];
+    sorted_heights.copy_from_slice(&sorted(heights));
+    sorted_heights
+}
+
+/// An exponentially weighted moving average, where each new point has a
+/// weight of `alpha` - larger values respond faster to recent points.  The
+/// first point initializes the average.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ewma {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ewma {
+    pub fn new(alpha: f64) -> Result<Self, SimulationError> {
+        if alpha > 0.0 && alpha <= 1.0 {
+            Ok(Self { alpha, value: None })
+        } else {
+            Err(SimulationError::InvalidSmoothingFactor(alpha))
+        }
+    }
+
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn push(&mut self, point: f64) {
+        self.value = Some(match self.value {
+            Some(value) => value + self.alpha * (point - value),
+            None => point,
+        });
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn welford_matches_batch_statistics() {
+        let points: Vec<f64> = (1..=100).map(|point| f64::from(point).sqrt()).collect();
+        let mut accumulator = Welford::default();
+        assert_eq!(accumulator.mean(), None);
+        points.iter().for_each(|point| accumulator.push(*point));
+        let mean = points.iter().sum::<f64>() / 100.0;
+        let variance = points
+            .iter()
+            .map(|point| (point - mean).powi(2))
+            .sum::<f64>()
+            / 99.0;
+        assert!((accumulator.mean().unwrap() - mean).abs() < 1.0e-12);
+        assert!((accumulator.variance().unwrap() - variance).abs() < 1.0e-12);
+        assert_eq!(accumulator.min(), Some(1.0));
+        assert_eq!(accumulator.max(), Some(10.0));
+        // Merged halves match the whole series
+        let (mut first, mut second) = (Welford::default(), Welford::default());
+        points[..30].iter().for_each(|point| first.push(*point));
+        points[30..].iter().for_each(|point| second.push(*point));
+        first.merge(&second);
+        assert_eq!(first.count(), 100);
+        assert!((first.mean().unwrap() - mean).abs() < 1.0e-12);
+        assert!((first.variance().unwrap() - variance).abs() < 1.0e-12);
+        assert_eq!(first.min(), Some(1.0));
+        let restored: Welford =
+            serde_json::from_str(&serde_json::to_string(&accumulator).unwrap()).unwrap();
+        assert_eq!(restored, accumulator);
+    }
+
+    #[test]
+    fn p2_quantiles_approximate_the_sample_quantile() {
+        let mut median = P2Quantile::new(0.5).unwrap();
+        let mut p90 = P2Quantile::new(0.9).unwrap();
+        assert_eq!(median.estimate(), None);
+        // A shuffled, uniform sequence over [0, 1000)
+        (0..1000u64)
+            .map(|point| ((point * 7919) % 1000) as f64)
+            .for_each(|point| {
+                median.push(point);
+                p90.push(point);
+            });
+        assert!((median.estimate().unwrap() - 500.0).abs() < 20.0);
+        assert!((p90.estimate().unwrap() - 900.0).abs() < 20.0);
+        let mut small = P2Quantile::new(0.5).unwrap();
+        [3.0, 1.0, 2.0].iter().for_each(|point| small.push(*point));
+        assert_eq!(small.estimate(), Some(2.0));
+        assert!(matches!(
+            P2Quantile::new(1.5),
+            Err(SimulationError::InvalidQuantile(_))
+        ));
+    }
+
+    #[test]
+    fn ewma_weights_recent_points() {
+        let mut average = Ewma::new(0.5).unwrap();
+        assert_eq!(average.value(), None);
+        [4.0, 8.0, 8.0]
+            .iter()
+            .for_each(|point| average.push(*point));
+        assert_eq!(average.value(), Some(7.0));
+        assert!(matches!(
+            Ewma::new(0.0),
+            Err(SimulationError::InvalidSmoothingFactor(_))
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+//! The analysis of correlated replications - antithetic pairs, and
+//! scenario comparisons under common random numbers (see
+//! `input_modeling::variance_reduction`).  Correlated replications are not
+//! independent, so the analysis works on per-pair statistics, which are.
+//! Antithetic pairs are averaged, and compared scenarios are differenced,
+//! before the usual confidence interval on the mean is applied - the
+//! paired-t approach.
+
+use serde::{Deserialize, Serialize};
+
+use super::{ConfidenceInterval, IndependentSample};
+use crate::utils::errors::SimulationError;
+
+fn validate_pairs(first: &[f64], second: &[f64]) -> Result<(), SimulationError> {
+    if first.len() != second.len() {
+        Err(SimulationError::UnpairedSamples(first.len(), second.len()))
+    } else if first.is_empty() {
+        Err(SimulationError::EmptySample)
+    } else {
+        Ok(())
+    }
+}
+
+/// The sample correlation of paired points, or zero for points without
+/// any spread.
+fn correlation(first: &[f64], second: &[f64]) -> f64 {
+    let points_len = first.len() as f64;
+    let first_mean = first.iter().sum::<f64>() / points_len;
+    let second_mean = second.iter().sum::<f64>() / points_len;
+    let (covariance, first_variance, second_variance) = first.iter().zip(second).fold(
+        (0.0, 0.0, 0.0),
+        |(covariance, first_variance, second_variance), (a, b)| {
+            (
+                covariance + (a - first_mean) * (b - second_mean),
+                first_variance + (a - first_mean).powi(2),
+                second_variance + (b - second_mean).powi(2),
+            )
+        },
+    );
+    if first_variance > 0.0 && second_variance > 0.0 {
+        covariance / (first_variance * second_variance).sqrt()
+    } else {
+        0.0
+    }
+}
+
+/// The IID sample of antithetic pair means - the mean of each replication
+/// and its antithetic partner.
+pub fn antithetic_sample(
+    original: &[f64],
+    antithetic: &[f64],
+) -> Result<IndependentSample<f64>, SimulationError> {
+    validate_pairs(original, antithetic)?;
+    IndependentSample::post(
+        original
+            .iter()
+            .zip(antithetic)
+            .map(|(original, antithetic)| (original + antithetic) / 2.0)
+            .collect(),
+    )
+}
+
+/// The paired comparison of an alternative scenario against a baseline,
+/// where replication `i` of each scenario shares its random numbers.  The
+/// differences are the alternative output less the baseline output.  The
+/// correlation of the paired outputs is positive when common random numbers
+/// are effective - the variance of the differences is then smaller than
+/// for independent scenarios.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioComparison {
+    differences: IndependentSample<f64>,
+    confidence_interval: ConfidenceInterval<f64>,
+    correlation: f64,
+}
+
+impl ScenarioComparison {
+    pub fn differences(&self) -> &IndependentSample<f64> {
+        &self.differences
+    }
+
+    /// The mean of the differences, alternative less baseline.
+    pub fn mean_difference(&self) -> f64 {
+        self.differences.point_estimate_mean()
+    }
+
+    /// The paired-t confidence interval on the difference of means.
+    pub fn confidence_interval(&self) -> &ConfidenceInterval<f64> {
+        &self.confidence_interval
+    }
+
+    /// Whether the scenarios differ significantly - the confidence interval
+    /// on the difference excludes zero.
+    pub fn significant(&self) -> bool {
+        self.confidence_interval.lower() > 0.0 || self.confidence_interval.upper() < 0.0
+    }
+
+    pub fn correlation(&self) -> f64 {
+        self.correlation
+    }
+}
+
+/// Compare the outputs of two scenarios with a paired-t confidence
+/// interval on the difference of means.  The outputs are paired by
+/// replication.
+pub fn compare_scenarios(
+    baseline: &[f64],
+    alternative: &[f64],
+    alpha: f64,
+) -> Result<ScenarioComparison, SimulationError> {
+    validate_pairs(baseline, alternative)?;
+    let differences = IndependentSample::post(
+        alternative
+            .iter()
+            .zip(baseline)
+            .map(|(alternative, baseline)| alternative - baseline)
+            .collect(),
+    )?;
+    Ok(ScenarioComparison {
+        confidence_interval: differences.confidence_interval_mean(alpha)?,
+        differences,
+        correlation: correlation(baseline, alternative),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paired_comparisons_difference_the_replications() {
+        let baseline = [10.0, 12.0, 9.0, 14.0, 11.0];
+        let alternative = [11.0, 13.5, 9.5, 15.0, 12.5];
+        let comparison = compare_scenarios(&baseline, &alternative, 0.05).unwrap();
+        assert!((comparison.mean_difference() - 1.1).abs() < 1.0e-12);
+        assert!(comparison.significant());
+        assert!(comparison.correlation() > 0.9);
+        // The spread of the baseline alone exceeds the difference
+        let unpaired = IndependentSample::post(baseline.to_vec())
+            .unwrap()
+            .confidence_interval_mean(0.05)
+            .unwrap();
+        assert!(unpaired.upper() > 11.0 + 1.1);
+        assert!(matches!(
+            compare_scenarios(&baseline, &alternative[..3], 0.05),
+            Err(SimulationError::UnpairedSamples(5, 3))
+        ));
+        let pairs = antithetic_sample(&[1.0, 4.0], &[3.0, 2.0]).unwrap();
+        assert_eq!(pairs.points(), &[2.0, 3.0][..]);
+        assert!(matches!(
+            antithetic_sample(&[], &[]),
+            Err(SimulationError::EmptySample)
+        ));
+    }
+}
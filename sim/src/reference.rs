@@ -0,0 +1,325 @@
+//! The reference module provides canonical queueing configurations, along
+//! with their analytic (steady-state) expectations.  Reference models are
+//! regression fixtures - a change to the engine or to the models should
+//! leave the simulated outputs of each reference model statistically
+//! consistent with its analytic expectations.
+//!
+//! The canonical parameters and expectations are provided as constants:
+//!
+//! * M/M/1 - a single exponential server with Poisson arrivals
+//! * M/M/c - `c` exponential servers sharing a single queue
+//! * Tandem - a series of M/M/1 stations (a Jackson network), where the
+//!   system response time is the sum of the station response times
+//!
+//! The pre-built models do not include a multi-server station, so M/M/c
+//! expectations are provided for validating custom multi-server models,
+//! without a reference configuration.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::input_modeling::ContinuousRandomVariable;
+use crate::models::{Generator, Model, Processor, Storage};
+use crate::simulator::{Connector, JobId, Message, Simulation};
+use crate::utils::errors::SimulationError;
+
+/// The canonical M/M/1 arrival rate
+pub const MM1_ARRIVAL_RATE: f64 = 0.5;
+/// The canonical M/M/1 service rate
+pub const MM1_SERVICE_RATE: f64 = 1.0;
+/// The canonical M/M/1 server utilization
+pub const MM1_UTILIZATION: f64 = 0.5;
+/// The canonical M/M/1 mean number of jobs in the system
+pub const MM1_MEAN_NUMBER_IN_SYSTEM: f64 = 1.0;
+/// The canonical M/M/1 mean number of jobs waiting in the queue
+pub const MM1_MEAN_NUMBER_IN_QUEUE: f64 = 0.5;
+/// The canonical M/M/1 mean response (sojourn) time
+pub const MM1_MEAN_RESPONSE_TIME: f64 = 2.0;
+/// The canonical M/M/1 mean waiting time in the queue
+pub const MM1_MEAN_WAITING_TIME: f64 = 1.0;
+
+/// The canonical M/M/c arrival rate
+pub const MMC_ARRIVAL_RATE: f64 = 1.5;
+/// The canonical M/M/c service rate, per server
+pub const MMC_SERVICE_RATE: f64 = 1.0;
+/// The canonical M/M/c server count
+pub const MMC_SERVERS: usize = 2;
+/// The canonical M/M/c server utilization
+pub const MMC_UTILIZATION: f64 = 0.75;
+/// The canonical M/M/c mean number of jobs in the system
+pub const MMC_MEAN_NUMBER_IN_SYSTEM: f64 = 24.0 / 7.0;
+/// The canonical M/M/c mean number of jobs waiting in the queue
+pub const MMC_MEAN_NUMBER_IN_QUEUE: f64 = 27.0 / 14.0;
+/// The canonical M/M/c mean response (sojourn) time
+pub const MMC_MEAN_RESPONSE_TIME: f64 = 16.0 / 7.0;
+/// The canonical M/M/c mean waiting time in the queue
+pub const MMC_MEAN_WAITING_TIME: f64 = 9.0 / 7.0;
+
+/// The canonical tandem queue arrival rate
+pub const TANDEM_ARRIVAL_RATE: f64 = 0.5;
+/// The canonical tandem queue service rates, by station
+pub const TANDEM_SERVICE_RATES: [f64; 2] = [1.0, 0.8];
+/// The canonical tandem queue bottleneck utilization
+pub const TANDEM_UTILIZATION: f64 = 0.625;
+/// The canonical tandem queue mean number of jobs in the system
+pub const TANDEM_MEAN_NUMBER_IN_SYSTEM: f64 = 8.0 / 3.0;
+/// The canonical tandem queue mean number of jobs waiting in the queues
+pub const TANDEM_MEAN_NUMBER_IN_QUEUE: f64 = 37.0 / 24.0;
+/// The canonical tandem queue mean (end-to-end) response time
+pub const TANDEM_MEAN_RESPONSE_TIME: f64 = 16.0 / 3.0;
+/// The canonical tandem queue mean (end-to-end) waiting time in the queues
+pub const TANDEM_MEAN_WAITING_TIME: f64 = 37.0 / 12.0;
+
+/// The analytic steady-state expectations of a queueing system.  For
+/// networks of stations, the utilization is that of the bottleneck station,
+/// and the other expectations are system-wide.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueExpectations {
+    pub utilization: f64,
+    pub mean_number_in_system: f64,
+    pub mean_number_in_queue: f64,
+    pub mean_response_time: f64,
+    pub mean_waiting_time: f64,
+}
+
+impl QueueExpectations {
+    /// The expectations of an M/M/1 queue.  Unstable queues (utilization of
+    /// 1 or more) have no steady state, and are rejected.
+    pub fn mm1(arrival_rate: f64, service_rate: f64) -> Result<Self, SimulationError> {
+        Self::mmc(arrival_rate, service_rate, 1)
+    }
+
+    /// The expectations of an M/M/c queue, from the Erlang C formula.
+    /// Unstable queues (utilization of 1 or more) have no steady state,
+    /// and are rejected.
+    pub fn mmc(
+        arrival_rate: f64,
+        service_rate: f64,
+        servers: usize,
+    ) -> Result<Self, SimulationError> {
+        let offered_load = arrival_rate / service_rate;
+        let utilization = offered_load / servers as f64;
+        if servers == 0 || !(arrival_rate > 0.0 && service_rate > 0.0 && utilization < 1.0) {
+            return Err(SimulationError::InvalidModelConfiguration);
+        }
+        // The terms a^k/k!, for k in 0..=c
+        let terms: Vec<f64> = (0..=servers)
+            .scan(1.0, |term, k| {
+                if k > 0 {
+                    *term *= offered_load / k as f64;
+                }
+                Some(*term)
+            })
+            .collect();
+        let busy_term = terms[servers] / (1.0 - utilization);
+        let empty_probability = 1.0 / (terms[..servers].iter().sum::<f64>() + busy_term);
+        let wait_probability = busy_term * empty_probability;
+        let mean_number_in_queue = wait_probability * utilization / (1.0 - utilization);
+        let mean_waiting_time = mean_number_in_queue / arrival_rate;
+        Ok(Self {
+            utilization,
+            mean_number_in_system: mean_number_in_queue + offered_load,
+            mean_number_in_queue,
+            mean_response_time: mean_waiting_time + 1.0 / service_rate,
+            mean_waiting_time,
+        })
+    }
+
+    /// The expectations of a series of M/M/1 stations.  By Jackson's
+    /// theorem, each station behaves as an independent M/M/1 queue.
+    pub fn tandem(arrival_rate: f64, service_rates: &[f64]) -> Result<Self, SimulationError> {
+        let stations = service_rates
+            .iter()
+            .map(|service_rate| Self::mm1(arrival_rate, *service_rate))
+            .collect::<Result<Vec<Self>, SimulationError>>()?;
+        if stations.is_empty() {
+            return Err(SimulationError::InvalidModelConfiguration);
+        }
+        Ok(Self {
+            utilization: stations
+                .iter()
+                .map(|station| station.utilization)
+                .fold(0.0, f64::max),
+            mean_number_in_system: stations.iter().map(|s| s.mean_number_in_system).sum(),
+            mean_number_in_queue: stations.iter().map(|s| s.mean_number_in_queue).sum(),
+            mean_response_time: stations.iter().map(|s| s.mean_response_time).sum(),
+            mean_waiting_time: stations.iter().map(|s| s.mean_waiting_time).sum(),
+        })
+    }
+}
+
+/// A reference model configuration - a Poisson generator, a series of
+/// exponential processors with unbounded queues, and a storage sink -
+/// with its analytic expectations.
+#[derive(Clone)]
+pub struct ReferenceModel {
+    pub models: Vec<Model>,
+    pub connectors: Vec<Connector>,
+    pub expectations: QueueExpectations,
+}
+
+impl ReferenceModel {
+    /// The ID of the generator model, producing the arrivals
+    pub const GENERATOR_ID: &'static str = "generator-01";
+    /// The ID of the storage model, collecting the departures
+    pub const SINK_ID: &'static str = "storage-01";
+
+    /// An M/M/1 reference model
+    pub fn mm1(arrival_rate: f64, service_rate: f64) -> Result<Self, SimulationError> {
+        Self::tandem(arrival_rate, &[service_rate])
+    }
+
+    /// A tandem (series) reference model, with one processor per service
+    /// rate.
+    pub fn tandem(arrival_rate: f64, service_rates: &[f64]) -> Result<Self, SimulationError> {
+        let expectations = QueueExpectations::tandem(arrival_rate, service_rates)?;
+        let processor_ids: Vec<String> = (1..=service_rates.len())
+            .map(|index| format!["processor-{:02}", index])
+            .collect();
+        let mut models = vec![Model::new(
+            String::from(Self::GENERATOR_ID),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp {
+                    lambda: arrival_rate,
+                },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        )];
+        models.extend(processor_ids.iter().zip(service_rates).map(|(id, rate)| {
+            Model::new(
+                id.clone(),
+                Box::new(Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: *rate },
+                    None,
+                    String::from("job"),
+                    String::from("processed"),
+                    false,
+                    None,
+                )),
+            )
+        }));
+        models.push(Model::new(
+            String::from(Self::SINK_ID),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ));
+        // Each model is connected to the next, from the generator, through
+        // the processors, to the sink
+        let model_ids: Vec<String> = models.iter().map(|model| model.id().to_string()).collect();
+        let connectors = model_ids
+            .windows(2)
+            .enumerate()
+            .map(|(index, pair)| {
+                Connector::new(
+                    format!["connector-{:02}", index + 1],
+                    pair[0].clone(),
+                    pair[1].clone(),
+                    String::from(if index == 0 { "job" } else { "processed" }),
+                    String::from(if pair[1] == Self::SINK_ID {
+                        "store"
+                    } else {
+                        "job"
+                    }),
+                )
+            })
+            .collect();
+        Ok(Self {
+            models,
+            connectors,
+            expectations,
+        })
+    }
+
+    /// A new simulation of the reference model
+    pub fn simulation(&self) -> Simulation {
+        Simulation::post(self.models.clone(), self.connectors.clone())
+    }
+
+    /// The end-to-end response times of the completed jobs, in order of
+    /// departure, matched by job ID between the generator and the sink.
+    pub fn response_times(&self, messages: &[Message]) -> Vec<f64> {
+        let arrivals: HashMap<JobId, f64> = messages
+            .iter()
+            .filter(|message| message.source_id() == Self::GENERATOR_ID)
+            .filter_map(|message| message.job_id().map(|job_id| (job_id, *message.time())))
+            .collect();
+        messages
+            .iter()
+            .filter(|message| message.target_id() == Self::SINK_ID)
+            .filter_map(|message| {
+                message
+                    .job_id()
+                    .and_then(|job_id| arrivals.get(&job_id))
+                    .map(|arrival| message.time() - arrival)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_expectations(actual: QueueExpectations, expected: QueueExpectations) {
+        let pairs = [
+            (actual.utilization, expected.utilization),
+            (actual.mean_number_in_system, expected.mean_number_in_system),
+            (actual.mean_number_in_queue, expected.mean_number_in_queue),
+            (actual.mean_response_time, expected.mean_response_time),
+            (actual.mean_waiting_time, expected.mean_waiting_time),
+        ];
+        pairs
+            .iter()
+            .for_each(|(actual, expected)| assert!((actual - expected).abs() < 1.0e-12));
+    }
+
+    #[test]
+    fn analytic_expectations_match_the_constants() -> Result<(), SimulationError> {
+        assert_expectations(
+            QueueExpectations::mm1(MM1_ARRIVAL_RATE, MM1_SERVICE_RATE)?,
+            QueueExpectations {
+                utilization: MM1_UTILIZATION,
+                mean_number_in_system: MM1_MEAN_NUMBER_IN_SYSTEM,
+                mean_number_in_queue: MM1_MEAN_NUMBER_IN_QUEUE,
+                mean_response_time: MM1_MEAN_RESPONSE_TIME,
+                mean_waiting_time: MM1_MEAN_WAITING_TIME,
+            },
+        );
+        assert_expectations(
+            QueueExpectations::mmc(MMC_ARRIVAL_RATE, MMC_SERVICE_RATE, MMC_SERVERS)?,
+            QueueExpectations {
+                utilization: MMC_UTILIZATION,
+                mean_number_in_system: MMC_MEAN_NUMBER_IN_SYSTEM,
+                mean_number_in_queue: MMC_MEAN_NUMBER_IN_QUEUE,
+                mean_response_time: MMC_MEAN_RESPONSE_TIME,
+                mean_waiting_time: MMC_MEAN_WAITING_TIME,
+            },
+        );
+        assert_expectations(
+            QueueExpectations::tandem(TANDEM_ARRIVAL_RATE, &TANDEM_SERVICE_RATES)?,
+            QueueExpectations {
+                utilization: TANDEM_UTILIZATION,
+                mean_number_in_system: TANDEM_MEAN_NUMBER_IN_SYSTEM,
+                mean_number_in_queue: TANDEM_MEAN_NUMBER_IN_QUEUE,
+                mean_response_time: TANDEM_MEAN_RESPONSE_TIME,
+                mean_waiting_time: TANDEM_MEAN_WAITING_TIME,
+            },
+        );
+        // Little's law holds for every reference system
+        assert!(
+            (MMC_MEAN_NUMBER_IN_SYSTEM - MMC_ARRIVAL_RATE * MMC_MEAN_RESPONSE_TIME).abs() < 1.0e-12
+        );
+        assert!(QueueExpectations::mm1(1.0, 1.0).is_err());
+        assert!(QueueExpectations::mmc(1.0, 1.0, 0).is_err());
+        Ok(())
+    }
+}
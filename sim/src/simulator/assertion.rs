@@ -0,0 +1,257 @@
+//! Assertions are declarative checks on simulation outputs, embedded in
+//! simulation (or experiment) configurations, for gating CI pipelines
+//! without bespoke test code.  For example, "the 95th percentile waiting
+//! time at processor-01 is below 10.0, with alpha 0.05, after a warm-up of
+//! 100" is:
+//!
+//! ```yaml
+//! assertions:
+//!   - name: processor wait
+//!     metric:
+//!       waitingTime:
+//!         modelId: processor-01
+//!     statistic:
+//!       quantile: 0.95
+//!     comparison: "<"
+//!     threshold: 10.0
+//!     alpha: 0.05
+//!     warmUp: 100.0
+//! ```
+//!
+//! With an alpha, the assertion holds only if the whole confidence interval
+//! of the statistic satisfies the comparison - the upper bound, for `<` and
+//! `<=`, and the lower bound, for `>` and `>=`.  Otherwise, the point
+//! estimate is compared.  Waiting and response times are derived from the
+//! records of processors, which must be storing records.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use super::Simulation;
+use crate::models::{ModelRecord, Reportable};
+use crate::output_analysis::IndependentSample;
+use crate::utils::errors::SimulationError;
+
+/// The output under assertion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssertionMetric {
+    /// The per-job waiting times at a processor, from arrival until the
+    /// start of processing
+    #[serde(rename_all = "camelCase")]
+    WaitingTime { model_id: String },
+    /// The per-job response times at a processor, from arrival until
+    /// departure
+    #[serde(rename_all = "camelCase")]
+    ResponseTime { model_id: String },
+    /// The utilization of a processor, after its own warm-up period
+    #[serde(rename_all = "camelCase")]
+    Utilization { model_id: String },
+    /// The throughput of a sink
+    #[serde(rename_all = "camelCase")]
+    Throughput { model_id: String },
+    /// A named experiment KPI, across the replications of a scenario
+    Kpi { name: String },
+}
+
+/// The summary of the metric points under assertion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssertionStatistic {
+    #[default]
+    Mean,
+    /// A quantile, between 0 and 1 (e.g. 0.95 for the 95th percentile)
+    Quantile(f64),
+    Min,
+    Max,
+    Count,
+}
+
+/// The comparison of the statistic with the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = "<")]
+    LessThan,
+    #[serde(rename = "<=")]
+    AtMost,
+    #[serde(rename = ">")]
+    GreaterThan,
+    #[serde(rename = ">=")]
+    AtLeast,
+}
+
+impl Comparison {
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::LessThan => value < threshold,
+            Comparison::AtMost => value <= threshold,
+            Comparison::GreaterThan => value > threshold,
+            Comparison::AtLeast => value >= threshold,
+        }
+    }
+}
+
+/// A declarative check of a simulation output.  The warm-up time applies
+/// to waiting and response times, where only the jobs arriving at or after
+/// the warm-up time are included.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Assertion {
+    #[serde(default)]
+    pub name: String,
+    pub metric: AssertionMetric,
+    #[serde(default)]
+    pub statistic: AssertionStatistic,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    #[serde(default)]
+    pub alpha: Option<f64>,
+    #[serde(default)]
+    pub warm_up: f64,
+}
+
+/// The outcome of an assertion - the point estimate of the statistic, the
+/// compared value (the point estimate, or a confidence interval bound), and
+/// whether the assertion holds.  Assertions without any metric points
+/// fail, without a value, except for count assertions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResult {
+    pub assertion: Assertion,
+    pub points: usize,
+    pub estimate: Option<f64>,
+    pub compared: Option<f64>,
+    pub passed: bool,
+}
+
+/// The outcomes of a set of assertions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionReport {
+    pub results: Vec<AssertionResult>,
+}
+
+impl AssertionReport {
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Whether every assertion holds.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+
+    /// The assertions that do not hold.
+    pub fn failures(&self) -> impl Iterator<Item = &AssertionResult> {
+        self.results.iter().filter(|result| !result.passed)
+    }
+}
+
+impl Assertion {
+    /// Evaluate the assertion over the metric points.
+    pub fn evaluate(&self, points: Vec<f64>) -> Result<AssertionResult, SimulationError> {
+        let points_len = points.len();
+        let (estimate, compared) = if self.statistic == AssertionStatistic::Count {
+            (Some(points_len as f64), Some(points_len as f64))
+        } else if points.is_empty() {
+            (None, None)
+        } else {
+            let sample = IndependentSample::post(points)?;
+            let (estimate, interval) = match self.statistic {
+                AssertionStatistic::Mean => (
+                    sample.point_estimate_mean(),
+                    self.alpha
+                        .map(|alpha| sample.confidence_interval_mean(alpha))
+                        .transpose()?,
+                ),
+                AssertionStatistic::Quantile(quantile) => (
+                    sample.point_estimate_quantile(quantile)?,
+                    self.alpha
+                        .map(|alpha| sample.confidence_interval_quantile(quantile, alpha))
+                        .transpose()?,
+                ),
+                AssertionStatistic::Min => (sample.point_estimate_quantile(0.0)?, None),
+                AssertionStatistic::Max => (sample.point_estimate_quantile(1.0)?, None),
+                AssertionStatistic::Count => (points_len as f64, None),
+            };
+            let compared = match (interval, self.comparison) {
+                (Some(interval), Comparison::LessThan | Comparison::AtMost) => interval.upper(),
+                (Some(interval), Comparison::GreaterThan | Comparison::AtLeast) => interval.lower(),
+                (None, _) => estimate,
+            };
+            (Some(estimate), Some(compared))
+        };
+        Ok(AssertionResult {
+            assertion: self.clone(),
+            points: points_len,
+            estimate,
+            compared,
+            passed: compared.is_some_and(|value| self.comparison.holds(value, self.threshold)),
+        })
+    }
+
+    /// The metric points of a simulation.  Experiment KPIs are unavailable
+    /// for a single simulation.
+    pub(crate) fn simulation_points(
+        &self,
+        simulation: &Simulation,
+    ) -> Result<Vec<f64>, SimulationError> {
+        let time = simulation.get_global_time();
+        match &self.metric {
+            AssertionMetric::WaitingTime { model_id } => Ok(job_times(
+                simulation.get_records(model_id)?,
+                "Processing Start",
+                self.warm_up,
+            )),
+            AssertionMetric::ResponseTime { model_id } => Ok(job_times(
+                simulation.get_records(model_id)?,
+                "Departure",
+                self.warm_up,
+            )),
+            AssertionMetric::Utilization { model_id } => Ok(simulation
+                .get_utilization(model_id)?
+                .and_then(|summary| summary.utilization)
+                .into_iter()
+                .collect()),
+            AssertionMetric::Throughput { model_id } => Ok(simulation
+                .models
+                .iter()
+                .find(|model| model.id() == model_id)
+                .ok_or_else(|| SimulationError::ModelNotFound(model_id.to_string()))?
+                .sink_summary(time)
+                .map(|summary| summary.throughput)
+                .into_iter()
+                .collect()),
+            AssertionMetric::Kpi { .. } => Err(SimulationError::InvalidExperimentConfiguration),
+        }
+    }
+}
+
+/// The times from each job's arrival record until its record of the
+/// provided action, for jobs arriving at or after the warm-up time.  Jobs
+/// are matched by record subject, in arrival order.
+fn job_times(records: &[ModelRecord], action: &str, warm_up: f64) -> Vec<f64> {
+    let mut arrivals: HashMap<&str, VecDeque<f64>> = HashMap::new();
+    let mut times = Vec::new();
+    records
+        .iter()
+        .for_each(|record| match record.action.as_str() {
+            "Arrival" => arrivals
+                .entry(record.subject.as_str())
+                .or_default()
+                .push_back(record.time),
+            _ if record.action == action => {
+                if let Some(arrival) = arrivals
+                    .get_mut(record.subject.as_str())
+                    .and_then(VecDeque::pop_front)
+                {
+                    if arrival >= warm_up {
+                        times.push(record.time - arrival);
+                    }
+                }
+            }
+            _ => {}
+        });
+    times
+}
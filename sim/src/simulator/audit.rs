@@ -0,0 +1,65 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::Connector;
+use crate::models::Model;
+
+/// A single mutation of a simulation's configuration or state, made
+/// through the simulation interface (as opposed to through simulation
+/// execution).  The wall time is in milliseconds since the Unix epoch, and
+/// the detail is a JSON representation of the mutation's input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub wall_time: f64,
+    pub simulation_time: f64,
+    pub action: String,
+    pub detail: String,
+}
+
+/// The audit log retains the latest audit records, up to its capacity -
+/// the oldest records are discarded first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditLog {
+    capacity: usize,
+    records: VecDeque<AuditRecord>,
+}
+
+impl AuditLog {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, record: AuditRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.records.len().saturating_sub(capacity);
+        self.records.drain(..excess);
+    }
+
+    pub(crate) fn records(&self) -> impl Iterator<Item = &AuditRecord> {
+        self.records.iter()
+    }
+}
+
+/// The audit detail of a `put` mutation - the full replacement
+/// configuration.
+#[derive(Serialize)]
+pub(crate) struct PutDetail<'a> {
+    pub(crate) models: &'a [Model],
+    pub(crate) connectors: &'a [Connector],
+}
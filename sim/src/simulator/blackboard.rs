@@ -0,0 +1,140 @@
+//! The blackboard is a shared key-value store, for coordination across
+//! models.  Models read and write blackboard entries during their state
+//! transitions, through `Services`, rather than coordinating through
+//! `Storage` models and extra connectors.  Each entry retains the
+//! simulation time of its latest change.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A typed blackboard value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlackboardValue {
+    Boolean(bool),
+    Number(f64),
+    Text(String),
+}
+
+impl From<bool> for BlackboardValue {
+    fn from(value: bool) -> Self {
+        BlackboardValue::Boolean(value)
+    }
+}
+
+impl From<f64> for BlackboardValue {
+    fn from(value: f64) -> Self {
+        BlackboardValue::Number(value)
+    }
+}
+
+impl From<String> for BlackboardValue {
+    fn from(value: String) -> Self {
+        BlackboardValue::Text(value)
+    }
+}
+
+impl From<&str> for BlackboardValue {
+    fn from(value: &str) -> Self {
+        BlackboardValue::Text(value.to_string())
+    }
+}
+
+/// A blackboard value, with the simulation time of its latest change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackboardEntry {
+    pub value: BlackboardValue,
+    pub changed_at: f64,
+}
+
+/// The blackboard entries, keyed by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Blackboard {
+    entries: BTreeMap<String, BlackboardEntry>,
+}
+
+impl Blackboard {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BlackboardEntry> {
+        self.entries.get(key)
+    }
+
+    /// The value of a boolean entry, if the entry exists with that type.
+    pub fn boolean(&self, key: &str) -> Option<bool> {
+        match self.get(key).map(|entry| &entry.value) {
+            Some(BlackboardValue::Boolean(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of a number entry, if the entry exists with that type.
+    pub fn number(&self, key: &str) -> Option<f64> {
+        match self.get(key).map(|entry| &entry.value) {
+            Some(BlackboardValue::Number(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of a text entry, if the entry exists with that type.
+    pub fn text(&self, key: &str) -> Option<&str> {
+        match self.get(key).map(|entry| &entry.value) {
+            Some(BlackboardValue::Text(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Write an entry at the provided simulation time.  The change time is
+    /// only updated when the value changes.
+    pub fn write(&mut self, key: &str, value: BlackboardValue, time: f64) {
+        match self.entries.get_mut(key) {
+            Some(entry) if entry.value == value => {}
+            Some(entry) => {
+                entry.value = value;
+                entry.changed_at = time;
+            }
+            None => {
+                self.entries.insert(
+                    key.to_string(),
+                    BlackboardEntry {
+                        value,
+                        changed_at: time,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<BlackboardEntry> {
+        self.entries.remove(key)
+    }
+
+    /// Apply the changes of another blackboard, relative to a common base
+    /// blackboard - the entries written or removed since the base.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn merge_changes(&mut self, base: &Blackboard, changed: &Blackboard) {
+        changed
+            .entries
+            .iter()
+            .filter(|(key, entry)| base.entries.get(*key) != Some(entry))
+            .for_each(|(key, entry)| {
+                self.entries.insert(key.clone(), entry.clone());
+            });
+        base.entries
+            .keys()
+            .filter(|key| !changed.entries.contains_key(*key))
+            .for_each(|key| {
+                self.entries.remove(key);
+            });
+    }
+
+    /// The blackboard entries, ordered by key.
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &BlackboardEntry)> {
+        self.entries.iter()
+    }
+}
@@ -0,0 +1,127 @@
+//! Checkpoints capture the complete state of a paused simulation - models,
+//! connectors, in-flight and scheduled messages, global time, and the state
+//! of the global random number generator and of the models' own random
+//! number streams - so execution resumes exactly
+//! where it left off.  Unlike snapshots, which reseed the global random
+//! number generator on restoration, a restored checkpoint continues the
+//! random number stream, so a restored simulation produces the same results
+//! as the original.
+//!
+//! The checkpoint format is YAML, rather than JSON, as JSON cannot
+//! represent the infinite time advances of passive models.
+
+use serde::{Deserialize, Serialize};
+
+use super::services::OwnStream;
+use super::Simulation;
+use crate::input_modeling::dynamic_rng::{builtin_rng_state, dyn_rng, BuiltinRng};
+use crate::utils::errors::SimulationError;
+
+/// The current checkpoint format version.
+pub const CHECKPOINT_FORMAT_VERSION: u16 = 1;
+
+/// A checkpoint of a paused simulation.  Only the state of the built-in
+/// global random number generator can be captured - user-supplied
+/// generators are opaque.  Debugging facilities (history, message history,
+/// state diffs, variate recording, and subscriptions) are not retained, and
+/// neither are the precomputed variates of buffered distributions, which
+/// are redrawn after restoration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    format_version: u16,
+    simulation: serde_yaml::Value,
+    rng: BuiltinRng,
+    rng_seed: Option<u64>,
+    #[serde(default)]
+    deterministic_mode: bool,
+    #[serde(default)]
+    antithetic: bool,
+    #[serde(default)]
+    injected_count: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    own_streams: Vec<OwnStreamState>,
+}
+
+/// The state of a model's own random number stream, by stream key and seed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnStreamState {
+    key: String,
+    seed: u64,
+    position: u64,
+    rng: BuiltinRng,
+}
+
+impl Checkpoint {
+    pub(crate) fn new(simulation: &Simulation) -> Result<Self, SimulationError> {
+        let mut own_streams = simulation
+            .services
+            .own_streams
+            .iter()
+            .map(|((key, seed), stream)| {
+                Ok(OwnStreamState {
+                    key: key.clone(),
+                    seed: *seed,
+                    position: stream.position,
+                    rng: builtin_rng_state(&stream.rng).ok_or(SimulationError::OpaqueRngState)?,
+                })
+            })
+            .collect::<Result<Vec<OwnStreamState>, SimulationError>>()?;
+        own_streams.sort_by(|a, b| (&a.key, a.seed).cmp(&(&b.key, b.seed)));
+        Ok(Self {
+            format_version: CHECKPOINT_FORMAT_VERSION,
+            simulation: serde_yaml::to_value(simulation)?,
+            rng: builtin_rng_state(&simulation.services.global_rng)
+                .ok_or(SimulationError::OpaqueRngState)?,
+            rng_seed: simulation.services.rng_seed,
+            deterministic_mode: simulation.services.deterministic_mode,
+            antithetic: simulation.services.antithetic,
+            injected_count: simulation.injected_count,
+            own_streams,
+        })
+    }
+
+    pub(crate) fn restore(&self) -> Result<Simulation, SimulationError> {
+        if self.format_version == 0 || self.format_version > CHECKPOINT_FORMAT_VERSION {
+            return Err(SimulationError::InvalidSnapshot);
+        }
+        let mut simulation: Simulation = serde_yaml::from_value(self.simulation.clone())?;
+        simulation.services.global_rng = dyn_rng(self.rng.clone());
+        simulation.services.rng_seed = self.rng_seed;
+        simulation.services.deterministic_mode = self.deterministic_mode;
+        simulation.services.antithetic = self.antithetic;
+        simulation.injected_count = self.injected_count;
+        simulation.services.own_streams = self
+            .own_streams
+            .iter()
+            .map(|stream| {
+                let own_stream = OwnStream {
+                    position: stream.position,
+                    rng: dyn_rng(stream.rng.clone()),
+                };
+                ((stream.key.clone(), stream.seed), own_stream)
+            })
+            .collect();
+        Ok(simulation)
+    }
+
+    /// The simulation time at which the checkpoint was taken.
+    pub fn global_time(&self) -> f64 {
+        self.simulation
+            .get("services")
+            .and_then(|services| services.get("globalTime"))
+            .and_then(serde_yaml::Value::as_f64)
+            .unwrap_or_default()
+    }
+
+    /// Encode the checkpoint in the portable (YAML) checkpoint format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SimulationError> {
+        Ok(serde_yaml::to_string(self)?.into_bytes())
+    }
+
+    /// Decode a checkpoint from the portable checkpoint format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SimulationError> {
+        Ok(serde_yaml::from_slice(bytes)?)
+    }
+}
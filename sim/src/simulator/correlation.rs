@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::coupling::{JobId, Message, Payload};
+use crate::models::ModelMessage;
+
+/// The metadata (including any correlation ID) and payload carried by a
+/// message, to be inherited by the downstream messages of the same job.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MessageContext {
+    pub(crate) metadata: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) payload: Option<Payload>,
+}
+
+impl MessageContext {
+    fn is_empty(&self) -> bool {
+        self.metadata.is_empty() && self.payload.is_none()
+    }
+}
+
+/// The default maximum number of pending contexts held by the tracker.
+pub(crate) const DEFAULT_CORRELATION_CAPACITY: usize = 10_000;
+
+/// The correlation tracker propagates metadata (including correlation IDs)
+/// and payloads through models.  When a model receives a job (a message
+/// with a job ID) carrying metadata or a payload, the message context is
+/// held until the model emits the same job, as matched by job ID.  Jobs
+/// that the model consumes or drops on arrival release their context
+/// immediately, and the pending contexts are bounded, with the oldest
+/// contexts evicted first, for jobs consumed later (e.g. by the components
+/// of a coupled model).  Emitted messages without a matching job inherit
+/// the metadata (but not the payload) of the model's most recent incoming
+/// message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CorrelationTracker {
+    capacity: usize,
+    // Pending contexts, by arrival sequence, for eviction in arrival order
+    pending: BTreeMap<u64, PendingCorrelation>,
+    // The arrival sequence of each pending context, by target model and job
+    // ID - rebuilt from the pending contexts after deserialization
+    #[serde(skip)]
+    index: HashMap<(String, JobId), u64>,
+    // The arrival sequences of the contexts received in the current step
+    #[serde(skip)]
+    received: Vec<u64>,
+    latest: HashMap<String, MessageContext>,
+    next_sequence: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingCorrelation {
+    #[serde(rename = "modelID")]
+    model_id: String,
+    job_id: JobId,
+    context: MessageContext,
+}
+
+impl Default for CorrelationTracker {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CORRELATION_CAPACITY,
+            pending: BTreeMap::new(),
+            index: HashMap::new(),
+            received: Vec::new(),
+            latest: HashMap::new(),
+            next_sequence: 0,
+        }
+    }
+}
+
+impl CorrelationTracker {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pending.is_empty() && self.latest.is_empty()
+    }
+
+    fn index(&mut self) -> &mut HashMap<(String, JobId), u64> {
+        if self.index.len() != self.pending.len() {
+            self.index = self
+                .pending
+                .iter()
+                .map(|(sequence, pending)| {
+                    (
+                        (pending.model_id.clone(), pending.job_id.clone()),
+                        *sequence,
+                    )
+                })
+                .collect();
+        }
+        &mut self.index
+    }
+
+    fn release(&mut self, model_id: &str, job_id: &JobId) {
+        if let Some(sequence) = self.index().remove(&(model_id.to_string(), job_id.clone())) {
+            self.pending.remove(&sequence);
+        }
+    }
+
+    /// Hold the context of a message, for the target model.
+    pub(crate) fn receive(&mut self, message: &Message) {
+        let context = MessageContext {
+            metadata: message.inheritable_metadata(),
+            payload: message.payload().cloned(),
+        };
+        if context.is_empty() {
+            return;
+        }
+        if let Some(job_id) = message.job_id() {
+            // A repeated arrival of a job replaces its pending context
+            self.release(message.target_id(), &job_id);
+            let sequence = self.next_sequence;
+            self.next_sequence += 1;
+            self.index()
+                .insert((message.target_id().to_string(), job_id.clone()), sequence);
+            self.pending.insert(
+                sequence,
+                PendingCorrelation {
+                    model_id: message.target_id().to_string(),
+                    job_id,
+                    context: context.clone(),
+                },
+            );
+            self.received.push(sequence);
+            while self.pending.len() > self.capacity {
+                if let Some((_, evicted)) = self.pending.pop_first() {
+                    self.index.remove(&(evicted.model_id, evicted.job_id));
+                }
+            }
+        }
+        self.latest.insert(
+            message.target_id().to_string(),
+            MessageContext {
+                payload: None,
+                ..context
+            },
+        );
+    }
+
+    /// Release the contexts received in the current step, for the jobs the
+    /// target models no longer hold after their external transitions - the
+    /// jobs consumed or dropped on arrival.
+    pub(crate) fn release_unheld<'a, F>(&mut self, held_jobs: F)
+    where
+        F: Fn(&str) -> Vec<&'a JobId>,
+    {
+        let mut held: HashMap<String, HashSet<&JobId>> = HashMap::new();
+        let unheld: Vec<(String, JobId)> = std::mem::take(&mut self.received)
+            .into_iter()
+            .filter_map(|sequence| {
+                let pending = self.pending.get(&sequence)?;
+                let model_held = held
+                    .entry(pending.model_id.clone())
+                    .or_insert_with(|| held_jobs(&pending.model_id).into_iter().collect());
+                if model_held.contains(&pending.job_id) {
+                    None
+                } else {
+                    Some((pending.model_id.clone(), pending.job_id.clone()))
+                }
+            })
+            .collect();
+        unheld
+            .iter()
+            .for_each(|(model_id, job_id)| self.release(model_id, job_id));
+    }
+
+    /// Determine the inherited context of the messages emitted by a single
+    /// model transition.  Matched contexts are released once the transition
+    /// completes, so every message of the transition (e.g. the copies sent
+    /// along multiple flow paths) shares the context.
+    pub(crate) fn correlate(
+        &mut self,
+        model_id: &str,
+        outgoing_messages: &[ModelMessage],
+    ) -> Vec<MessageContext> {
+        let mut matched: Vec<&JobId> = Vec::new();
+        let contexts = outgoing_messages
+            .iter()
+            .map(|outgoing_message| {
+                let pending = outgoing_message.job_id.as_ref().and_then(|job_id| {
+                    let sequence = *self.index().get(&(model_id.to_string(), job_id.clone()))?;
+                    matched.push(job_id);
+                    self.pending.get(&sequence)
+                });
+                match pending {
+                    Some(pending) => pending.context.clone(),
+                    None => self.latest.get(model_id).cloned().unwrap_or_default(),
+                }
+            })
+            .collect();
+        matched
+            .into_iter()
+            .for_each(|job_id| self.release(model_id, job_id));
+        contexts
+    }
+}
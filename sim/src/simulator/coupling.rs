@@ -1,9 +1,30 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+use crate::models::ModelMessage;
+use crate::utils::errors::SimulationError;
+
+/// The message metadata key of the structured job ID, in the
+/// `<source ID>/<sequence>` form.
+pub const JOB_ID_KEY: &str = "sim.jobId";
+
+/// The message metadata key of the correlation ID.
+pub const CORRELATION_ID_KEY: &str = "sim.correlationId";
+
+/// The message metadata key of the delivery priority, an integer.
+pub const PRIORITY_KEY: &str = "sim.priority";
+
 /// Connectors are configured to connect models through their ports.  During
 /// simulation, models exchange messages (as per the Discrete Event System
 /// Specification) via these connectors.
+/// The source model ID, source port, and target model ID may be `*`
+/// wildcard patterns, and a target port of `*` passes the source port
+/// through - see the `routing` module.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Connector {
@@ -33,6 +54,11 @@ impl Connector {
         }
     }
 
+    /// This accessor method returns the ID of the connector.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     /// This accessor method returns the model ID of the connector source model.
     pub fn source_id(&self) -> &str {
         &self.source_id
@@ -54,10 +80,125 @@ impl Connector {
     }
 }
 
+/// A structured job identifier - the model that created the job, and the
+/// job's sequence number within that model.  Job IDs are carried in the
+/// message metadata, under `JOB_ID_KEY`, so jobs can be correlated across
+/// models without parsing the content.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobId {
+    #[serde(rename = "sourceID")]
+    pub source_id: String,
+    pub sequence: usize,
+}
+
+impl JobId {
+    pub fn new(source_id: String, sequence: usize) -> Self {
+        Self {
+            source_id,
+            sequence,
+        }
+    }
+}
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.source_id, self.sequence)
+    }
+}
+
+impl FromStr for JobId {
+    type Err = SimulationError;
+
+    /// Parse a job ID from its `<source ID>/<sequence>` form.  The source ID
+    /// may itself contain `/` separators (e.g. component paths).
+    fn from_str(job_id: &str) -> Result<Self, Self::Err> {
+        let (source_id, sequence) = job_id
+            .rsplit_once('/')
+            .ok_or(SimulationError::InvalidMessage)?;
+        Ok(Self::new(
+            source_id.to_string(),
+            sequence
+                .parse()
+                .map_err(|_| SimulationError::InvalidMessage)?,
+        ))
+    }
+}
+
+/// A structured message payload, carried alongside the text content, so
+/// models exchange structured data without encoding it in the content.
+/// JSON payloads convert to and from any serializable type, and byte
+/// payloads carry opaque binary data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Payload {
+    Json(serde_json::Value),
+    Bytes(Vec<u8>),
+}
+
+impl Payload {
+    /// A JSON payload, from a serializable value.
+    pub fn json<T: Serialize>(value: &T) -> Result<Self, SimulationError> {
+        Ok(Self::Json(serde_json::to_value(value)?))
+    }
+
+    /// The JSON payload, as a typed value.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, SimulationError> {
+        match self {
+            Self::Json(value) => Ok(T::deserialize(value)?),
+            Self::Bytes(_) => Err(SimulationError::InvalidPayload),
+        }
+    }
+
+    /// The byte payload, if binary.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Json(_) => None,
+            Self::Bytes(bytes) => Some(bytes),
+        }
+    }
+}
+
+/// The delivery order of injected messages, relative to the model-generated
+/// messages delivered in the same step.  The order determines the sequence
+/// of external events at each target model - for example, whether an
+/// injected job joins a processor queue ahead of the jobs arriving from
+/// upstream models at the same time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InjectionPriority {
+    /// Deliver injected messages after the model-generated messages
+    #[default]
+    Generated,
+    /// Deliver injected messages before the model-generated messages
+    Injected,
+    /// Deliver all messages in message time order, with ties broken by
+    /// delivering model-generated messages first
+    Timestamp,
+}
+
+impl InjectionPriority {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// Messages are the mechanism of information exchange for models in a
 /// a simulation.  The message must contain origin information (source model
 /// ID and source model port), destination information (target model ID and
-/// target model port), and the text/content of the message.
+/// target model port), and the text/content of the message.  Any
+/// additional information is carried in the key/value metadata, alongside
+/// the content - the simulator reserves the `sim.` keys:
+/// - `sim.jobId`, the structured job ID of a message carrying a job
+/// - `sim.correlationId`, a correlation ID, set on an injected message and
+///   propagated by the simulator to every downstream message, for
+///   end-to-end tracing
+/// - `sim.priority`, the delivery priority, which orders the external
+///   events within a simulation step - higher priority messages are
+///   delivered first, with a stable order otherwise
+///
+/// An optional structured payload accompanies the content, and is inherited
+/// by the downstream messages of the same job.
 #[wasm_bindgen]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -68,6 +209,14 @@ pub struct Message {
     target_port: String,
     time: f64,
     content: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    metadata: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<Payload>,
+}
+
+pub(crate) fn is_default_priority(priority: &i32) -> bool {
+    *priority == 0
 }
 
 impl Message {
@@ -88,9 +237,64 @@ impl Message {
             target_port,
             time,
             content,
+            metadata: HashMap::new(),
+            payload: None,
         }
     }
 
+    /// This builder method attaches a structured job ID to a message, as a
+    /// metadata entry - or removes the job ID, for `None`.
+    pub fn with_job_id(self, job_id: Option<JobId>) -> Self {
+        self.with_metadata_entry(JOB_ID_KEY, job_id.map(|job_id| job_id.to_string()))
+    }
+
+    fn with_metadata_entry(mut self, key: &str, value: Option<String>) -> Self {
+        match value {
+            Some(value) => {
+                self.metadata.insert(key.to_string(), value);
+            }
+            None => {
+                self.metadata.remove(key);
+            }
+        }
+        self
+    }
+
+    /// This builder method sets the transmission time of a message.
+    pub(crate) fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// This builder method attaches a correlation ID to a message, as a
+    /// metadata entry - or removes the correlation ID, for `None`.
+    pub fn with_correlation_id(self, correlation_id: Option<String>) -> Self {
+        self.with_metadata_entry(CORRELATION_ID_KEY, correlation_id)
+    }
+
+    /// This builder method attaches key/value metadata to a message,
+    /// replacing any existing metadata (including the job ID, correlation
+    /// ID, and priority).
+    pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// This builder method attaches a structured payload to a message.
+    pub fn with_payload(mut self, payload: Option<Payload>) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// This builder method sets the delivery priority of a message, as a
+    /// metadata entry - the default priority (0) is left implicit.
+    pub fn with_priority(self, priority: i32) -> Self {
+        let priority = Some(priority)
+            .filter(|priority| !is_default_priority(priority))
+            .map(|priority| priority.to_string());
+        self.with_metadata_entry(PRIORITY_KEY, priority)
+    }
+
     /// This accessor method returns the model ID of a message source.
     pub fn source_id(&self) -> &str {
         &self.source_id
@@ -120,4 +324,69 @@ impl Message {
     pub fn content(&self) -> &str {
         &self.content
     }
+
+    /// This accessor method returns the structured job ID of a message, if
+    /// the message carries a job with a known origin.
+    pub fn job_id(&self) -> Option<JobId> {
+        self.metadata
+            .get(JOB_ID_KEY)
+            .and_then(|job_id| job_id.parse().ok())
+    }
+
+    /// This accessor method returns the correlation ID of a message, if the
+    /// message is part of a correlated transaction.
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.metadata.get(CORRELATION_ID_KEY).map(String::as_str)
+    }
+
+    /// This accessor method returns the key/value metadata of a message.
+    pub fn metadata(&self) -> &HashMap<String, String> {
+        &self.metadata
+    }
+
+    /// This accessor method returns the structured payload of a message.
+    pub fn payload(&self) -> Option<&Payload> {
+        self.payload.as_ref()
+    }
+
+    /// This accessor method returns the delivery priority of a message - the
+    /// default priority (0), unless set to an integer.
+    pub fn priority(&self) -> i32 {
+        self.metadata
+            .get(PRIORITY_KEY)
+            .and_then(|priority| priority.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// The JSON payload of a message, as a typed value.
+    pub fn payload_as<T: DeserializeOwned>(&self) -> Result<T, SimulationError> {
+        self.payload
+            .as_ref()
+            .ok_or(SimulationError::InvalidPayload)?
+            .decode()
+    }
+
+    /// The metadata of the message, other than the job ID and priority -
+    /// those are set for each message individually, and are not inherited
+    /// by downstream messages.
+    pub(crate) fn inheritable_metadata(&self) -> HashMap<String, String> {
+        let mut metadata = self.metadata.clone();
+        metadata.remove(JOB_ID_KEY);
+        metadata.remove(PRIORITY_KEY);
+        metadata
+    }
+
+    /// The message, as delivered to the target model.  The job ID and
+    /// priority are typed fields of model messages, rather than metadata
+    /// entries.
+    pub(crate) fn to_model_message(&self) -> ModelMessage {
+        ModelMessage {
+            port_name: self.target_port.clone(),
+            content: self.content.clone(),
+            job_id: self.job_id(),
+            metadata: self.inheritable_metadata(),
+            payload: self.payload.clone(),
+            priority: self.priority(),
+        }
+    }
 }
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::coupling::Connector;
+use super::routing::{expand_connectors, matches};
+use crate::input_modeling::random_variable::{Index, SamplingContext};
+use crate::input_modeling::{BooleanRandomVariable, ContinuousRandomVariable, Globals};
+use crate::models::Model;
+use crate::utils::errors::SimulationError;
+
+/// The estimated steady-state flow through a single model.  Rates are
+/// messages per unit of simulation time.  Utilization is reported for
+/// stations with a service time (e.g. processors).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelEstimate {
+    pub id: String,
+    pub model_type: String,
+    pub arrival_rate: f64,
+    pub departure_rate: f64,
+    pub utilization: Option<f64>,
+    pub expected_events: f64,
+}
+
+/// The estimated message flow through a single connector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectorEstimate {
+    pub id: String,
+    pub rate: f64,
+    pub expected_messages: f64,
+}
+
+/// The dry run report estimates the event counts and message volumes of a
+/// simulation over the estimated duration, without executing any model
+/// transitions.  Estimates are steady-state approximations from the
+/// configured generator rates, service rates, and fan-outs.  Configuration
+/// problems and overloaded stations (utilization > 1) are reported as
+/// warnings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunReport {
+    pub duration: f64,
+    pub models: Vec<ModelEstimate>,
+    pub connectors: Vec<ConnectorEstimate>,
+    pub expected_events: f64,
+    pub expected_messages: f64,
+    pub bottleneck: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// The departure rate of each output port, given the arrival rate.
+#[derive(Debug, Clone, Default)]
+struct Flow {
+    departures: HashMap<String, f64>,
+    utilization: Option<f64>,
+}
+
+fn string_at(config: &Value, pointer: &str) -> Option<String> {
+    config
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .map(String::from)
+}
+
+fn strings_at(config: &Value, pointer: &str) -> Vec<String> {
+    config
+        .pointer(pointer)
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The long-run mean of a distribution, with global variable references
+/// resolved to their current values.
+fn mean_at(config: &Value, pointer: &str, globals: &Globals) -> Option<f64> {
+    config
+        .pointer(pointer)
+        .cloned()
+        .and_then(|value| serde_json::from_value::<ContinuousRandomVariable>(value).ok())
+        .and_then(|variable| match variable {
+            ContinuousRandomVariable::Expression(_) => variable
+                .mean_in(&SamplingContext { time: 0.0, globals })
+                .ok(),
+            _ => Some(variable.mean()),
+        })
+}
+
+fn single_port(config: &Value, pointer: &str, rate: f64) -> HashMap<String, f64> {
+    string_at(config, pointer)
+        .map(|port| (port, rate))
+        .into_iter()
+        .collect()
+}
+
+/// Estimate the flow through a model, from the serialized model
+/// configuration.  Models without a flow estimate (e.g. coupled and custom
+/// models) provide `None`.
+fn model_flow(
+    model_type: &str,
+    config: &Value,
+    globals: &Globals,
+    arrival_rate: f64,
+) -> Option<Flow> {
+    match model_type {
+        "Generator" => Some(Flow {
+            departures: single_port(
+                config,
+                "/portsOut/job",
+                1.0 / mean_at(config, "/messageInterdepartureTime", globals)?,
+            ),
+            utilization: None,
+        }),
+        "Processor" => {
+            let service_rate = 1.0 / mean_at(config, "/serviceTime", globals)?;
+            Some(Flow {
+                departures: single_port(
+                    config,
+                    "/portsOut/job",
+                    f64::min(arrival_rate, service_rate),
+                ),
+                utilization: Some(arrival_rate / service_rate),
+            })
+        }
+        "Batcher" | "Delay" | "Gate" => Some(Flow {
+            departures: single_port(config, "/portsOut/job", arrival_rate),
+            utilization: None,
+        }),
+        "StochasticGate" => {
+            let pass_distribution: BooleanRandomVariable =
+                serde_json::from_value(config.pointer("/passDistribution")?.clone()).ok()?;
+            let pass_rate = match pass_distribution {
+                BooleanRandomVariable::Bernoulli { p } => p,
+            };
+            Some(Flow {
+                departures: single_port(config, "/portsOut/job", arrival_rate * pass_rate),
+                utilization: None,
+            })
+        }
+        "ExclusiveGateway" => {
+            let flow_paths = strings_at(config, "/portsOut/flowPaths");
+            let port_weights: Index =
+                serde_json::from_value(config.pointer("/portWeights")?.clone()).ok()?;
+            let weights: Vec<f64> = match port_weights {
+                Index::Constant { value } => (0..flow_paths.len())
+                    .map(|index| f64::from(u8::from(index == value)))
+                    .collect(),
+                Index::Uniform { min, max } => (0..flow_paths.len())
+                    .map(|index| f64::from(u8::from(index >= min && index < max)))
+                    .collect(),
+                Index::WeightedIndex { weights } => {
+                    weights.iter().map(|weight| *weight as f64).collect()
+                }
+            };
+            let total_weight: f64 = weights.iter().sum();
+            Some(Flow {
+                departures: flow_paths
+                    .into_iter()
+                    .zip(weights)
+                    .map(|(port, weight)| (port, arrival_rate * weight / total_weight))
+                    .collect(),
+                utilization: None,
+            })
+        }
+        "LoadBalancer" => {
+            let flow_paths = strings_at(config, "/portsOut/flowPaths");
+            let path_count = flow_paths.len() as f64;
+            Some(Flow {
+                departures: flow_paths
+                    .into_iter()
+                    .map(|port| (port, arrival_rate / path_count))
+                    .collect(),
+                utilization: None,
+            })
+        }
+        "ParallelGateway" => {
+            // Each job is collected from every inbound flow path, and then
+            // sent along every outbound flow path
+            let inbound_count = strings_at(config, "/portsIn/flowPaths").len().max(1) as f64;
+            Some(Flow {
+                departures: strings_at(config, "/portsOut/flowPaths")
+                    .into_iter()
+                    .map(|port| (port, arrival_rate / inbound_count))
+                    .collect(),
+                utilization: None,
+            })
+        }
+        "Sink" | "Storage" | "Stopwatch" => Some(Flow::default()),
+        _ => None,
+    }
+}
+
+/// Analyze the configuration of a simulation, estimating the steady-state
+/// flows.  Flows are propagated from the generators, through the
+/// connectors, until the estimates settle.
+pub(crate) fn dry_run(
+    models: &[Model],
+    connectors: &[Connector],
+    globals: &Globals,
+    duration: f64,
+) -> Result<DryRunReport, SimulationError> {
+    let configs: Vec<Value> = models
+        .iter()
+        .map(|model| serde_json::to_value(model).map_err(|_| SimulationError::SerializationError))
+        .collect::<Result<_, _>>()?;
+    let mut warnings: Vec<String> = Vec::new();
+    connectors.iter().for_each(|connector| {
+        [connector.source_id(), connector.target_id()]
+            .iter()
+            .filter(|model_id| !models.iter().any(|model| matches(model_id, model.id())))
+            .for_each(|model_id| {
+                warnings.push(format![
+                    "Connector {} references the unknown model {}",
+                    connector.id(),
+                    model_id
+                ])
+            });
+    });
+    models
+        .iter()
+        .zip(configs.iter())
+        .filter(|(model, config)| model_flow(model.model_type(), config, globals, 0.0).is_none())
+        .for_each(|(model, _)| {
+            warnings.push(format![
+                "Model {} ({}) is not analyzed, and is assumed to absorb its arrivals",
+                model.id(),
+                model.model_type()
+            ])
+        });
+    // Propagate the flows - one pass per model suffices for acyclic
+    // topologies, and feedback loops are given a bounded number of passes
+    let connectors = &expand_connectors(models, connectors);
+    let mut connector_rates = vec![0.0; connectors.len()];
+    let mut arrival_rates = vec![0.0; models.len()];
+    let mut flows: Vec<Option<Flow>> = vec![None; models.len()];
+    let mut settled = false;
+    for _ in 0..=models.len() {
+        arrival_rates = models
+            .iter()
+            .map(|model| {
+                connectors
+                    .iter()
+                    .zip(connector_rates.iter())
+                    .filter(|(connector, _)| connector.target_id() == model.id())
+                    .map(|(_, rate)| rate)
+                    .sum()
+            })
+            .collect();
+        flows = models
+            .iter()
+            .zip(configs.iter())
+            .zip(arrival_rates.iter())
+            .map(|((model, config), arrival_rate)| {
+                model_flow(model.model_type(), config, globals, *arrival_rate)
+            })
+            .collect();
+        let next_rates: Vec<f64> = connectors
+            .iter()
+            .map(|connector| {
+                models
+                    .iter()
+                    .zip(flows.iter())
+                    .find(|(model, _)| model.id() == connector.source_id())
+                    .and_then(|(_, flow)| flow.as_ref())
+                    .map_or(0.0, |flow| {
+                        flow.departures
+                            .iter()
+                            .filter(|(port, _)| matches(connector.source_port(), port))
+                            .map(|(_, rate)| rate)
+                            .sum()
+                    })
+            })
+            .collect();
+        settled = next_rates
+            .iter()
+            .zip(connector_rates.iter())
+            .all(|(next, previous)| (next - previous).abs() <= 1.0e-9 * next.abs().max(1.0));
+        connector_rates = next_rates;
+        if settled {
+            break;
+        }
+    }
+    if !settled {
+        warnings.push(String::from(
+            "Flow estimates did not settle, due to feedback loops in the topology",
+        ));
+    }
+    let model_estimates: Vec<ModelEstimate> = models
+        .iter()
+        .zip(flows.iter())
+        .zip(arrival_rates.iter())
+        .map(|((model, flow), arrival_rate)| {
+            let departure_rate = flow
+                .as_ref()
+                .map(|flow| flow.departures.values().sum())
+                .unwrap_or(0.0);
+            ModelEstimate {
+                id: model.id().to_string(),
+                model_type: model.model_type().to_string(),
+                arrival_rate: *arrival_rate,
+                departure_rate,
+                utilization: flow.as_ref().and_then(|flow| flow.utilization),
+                expected_events: (arrival_rate + departure_rate) * duration,
+            }
+        })
+        .collect();
+    model_estimates
+        .iter()
+        .for_each(|estimate| match estimate.utilization {
+            Some(utilization) if utilization > 1.0 => warnings.push(format![
+                "Station {} is overloaded, with utilization {:.3}",
+                estimate.id, utilization
+            ]),
+            Some(_) if estimate.arrival_rate == 0.0 => {
+                warnings.push(format!["Station {} receives no arrivals", estimate.id])
+            }
+            _ => {}
+        });
+    let bottleneck = model_estimates
+        .iter()
+        .filter_map(|estimate| {
+            estimate
+                .utilization
+                .map(|utilization| (estimate, utilization))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .filter(|(_, utilization)| *utilization > 0.0)
+        .map(|(estimate, _)| estimate.id.clone());
+    let connector_estimates: Vec<ConnectorEstimate> = connectors
+        .iter()
+        .zip(connector_rates.iter())
+        .map(|(connector, rate)| ConnectorEstimate {
+            id: connector.id().to_string(),
+            rate: *rate,
+            expected_messages: rate * duration,
+        })
+        .collect();
+    Ok(DryRunReport {
+        duration,
+        expected_events: model_estimates
+            .iter()
+            .map(|estimate| estimate.expected_events)
+            .sum(),
+        expected_messages: connector_estimates
+            .iter()
+            .map(|estimate| estimate.expected_messages)
+            .sum(),
+        models: model_estimates,
+        connectors: connector_estimates,
+        bottleneck,
+        warnings,
+    })
+}
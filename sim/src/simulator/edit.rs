@@ -0,0 +1,142 @@
+//! Edit transactions group topology changes - adding and removing models
+//! and connectors, and setting model parameters - so interactive editors
+//! can make several related changes at once.  The operations of a
+//! transaction are staged on a copy of the topology, and the live
+//! simulation is untouched (and may keep stepping) until the transaction
+//! is committed.  A commit first validates the staged topology, so the
+//! simulation never runs a half-edited, inconsistent topology - a rejected
+//! commit leaves the transaction open, for further edits or a rollback.
+
+use serde::{Deserialize, Serialize};
+
+use super::validation::topology_errors;
+use super::{Connector, Message};
+use crate::models::{DevsModel, Model};
+use crate::utils::errors::SimulationError;
+
+/// A single topology change of an edit transaction.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EditOperation {
+    AddModel(Model),
+    #[serde(rename_all = "camelCase")]
+    RemoveModel {
+        model_id: String,
+    },
+    AddConnector(Connector),
+    #[serde(rename_all = "camelCase")]
+    RemoveConnector {
+        connector_id: String,
+    },
+    /// Set a model configuration field, by JSON pointer, as with
+    /// `Simulation::set_parameter`
+    #[serde(rename_all = "camelCase")]
+    SetParameter {
+        model_id: String,
+        pointer: String,
+        value: serde_json::Value,
+    },
+}
+
+/// A model removed mid-run, with its connectors, and the in-flight and
+/// scheduled messages that were addressed to it.
+#[derive(Clone)]
+pub struct RemovedModel {
+    pub model: Model,
+    pub connectors: Vec<Connector>,
+    pub undelivered: Vec<Message>,
+}
+
+/// The model with a single configuration field set, identified by a JSON
+/// pointer into the model's serialized configuration.  The patched
+/// configuration is validated by re-instantiating the model, without its
+/// state.  The model ID, type, and state are not settable parameters.
+pub(crate) fn patched_model(
+    model: &Model,
+    pointer: &str,
+    value: serde_json::Value,
+) -> Result<Model, SimulationError> {
+    let field = pointer.split('/').nth(1).unwrap_or_default();
+    if ["", "id", "type", "state"].contains(&field) {
+        return Err(SimulationError::InvalidParameter(pointer.to_string()));
+    }
+    let mut config = serde_json::to_value(model)?;
+    *config
+        .pointer_mut(pointer)
+        .ok_or_else(|| SimulationError::InvalidParameter(pointer.to_string()))? = value;
+    if let Some(fields) = config.as_object_mut() {
+        fields.remove("state");
+    }
+    serde_json::from_value(config).map_err(|_| SimulationError::InvalidModelConfiguration)
+}
+
+/// An open edit transaction - the staged topology, and the operations
+/// applied so far.
+#[derive(Clone)]
+pub(crate) struct EditTransaction {
+    pub(crate) models: Vec<Model>,
+    pub(crate) connectors: Vec<Connector>,
+    pub(crate) operations: Vec<EditOperation>,
+}
+
+impl EditTransaction {
+    pub(crate) fn new(models: &[Model], connectors: &[Connector]) -> Self {
+        Self {
+            models: models.to_vec(),
+            connectors: connectors.to_vec(),
+            operations: Vec::new(),
+        }
+    }
+
+    fn model_index(&self, model_id: &str) -> Result<usize, SimulationError> {
+        self.models
+            .iter()
+            .position(|model| model.id() == model_id)
+            .ok_or_else(|| SimulationError::ModelNotFound(model_id.to_string()))
+    }
+
+    /// Apply an operation to the staged topology.  Operations referencing
+    /// missing models or connectors fail immediately, and leave the staged
+    /// topology unchanged.  Duplicate IDs and dangling connectors are only
+    /// reported by validation, as later operations may resolve them.
+    pub(crate) fn apply(&mut self, operation: EditOperation) -> Result<(), SimulationError> {
+        match &operation {
+            EditOperation::AddModel(model) => self.models.push(model.clone()),
+            EditOperation::RemoveModel { model_id } => {
+                let index = self.model_index(model_id)?;
+                self.models.remove(index);
+            }
+            EditOperation::AddConnector(connector) => self.connectors.push(connector.clone()),
+            EditOperation::RemoveConnector { connector_id } => {
+                let index = self
+                    .connectors
+                    .iter()
+                    .position(|connector| connector.id() == connector_id)
+                    .ok_or_else(|| SimulationError::ConnectorNotFound(connector_id.clone()))?;
+                self.connectors.remove(index);
+            }
+            EditOperation::SetParameter {
+                model_id,
+                pointer,
+                value,
+            } => {
+                let index = self.model_index(model_id)?;
+                let mut model = patched_model(&self.models[index], pointer, value.clone())?;
+                model.migrate_state(&serde_yaml::to_value(&self.models[index])?)?;
+                self.models[index] = model;
+            }
+        }
+        self.operations.push(operation);
+        Ok(())
+    }
+
+    /// The issues of the staged topology - duplicate model or connector
+    /// IDs, connectors referencing missing models, and in-flight or
+    /// scheduled messages addressed to removed models.
+    pub(crate) fn issues(&self, pending_messages: &[&Message]) -> Vec<String> {
+        topology_errors(&self.models, &self.connectors, pending_messages)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+}
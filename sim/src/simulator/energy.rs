@@ -0,0 +1,224 @@
+//! Energy accounting attributes a consumption to model activity, for
+//! sustainability studies of simulated infrastructure.  Each model may be
+//! given energy coefficients - a consumption per busy simulation time unit,
+//! and a consumption per message sent or received.  The simulator gathers
+//! the activity of those models over a run, and reports the consumption per
+//! model and per simulation time bucket.  Busy time is taken from the
+//! model's utilization (`Reportable::utilization`) - so, for processors,
+//! busy time within the warm-up period is not accounted for.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::coupling::Message;
+use crate::models::{Model, Reportable};
+use crate::utils::errors::SimulationError;
+
+/// The energy coefficients of a model, in arbitrary (but consistent) energy
+/// units.  Sent messages are counted before fan-out, so a message coupled
+/// to several targets is sent once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnergyCoefficients {
+    #[serde(default)]
+    pub per_busy_time: f64,
+    #[serde(default)]
+    pub per_message: f64,
+}
+
+/// The activity of a single model within a single step - the busy time
+/// over the step `[start, end]`, the messages received at the start of the
+/// step, and the messages sent at the end of the step.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EnergyUsage {
+    pub(crate) start: f64,
+    pub(crate) end: f64,
+    pub(crate) model_id: String,
+    pub(crate) busy_time: f64,
+    pub(crate) received: usize,
+    pub(crate) sent: usize,
+}
+
+/// The energy tracker retains the per-step activity of the models with
+/// energy coefficients, and the last observed busy time of each.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EnergyTracker {
+    busy_times: BTreeMap<String, f64>,
+    usage: Vec<EnergyUsage>,
+}
+
+fn busy_time(model: &Model, time: f64) -> f64 {
+    if time.is_finite() {
+        model
+            .utilization(time)
+            .map_or(0.0, |utilization| utilization.busy_time)
+    } else {
+        0.0
+    }
+}
+
+impl EnergyTracker {
+    /// Start tracking a model from its current busy time, so activity
+    /// before the coefficients were set is not accounted for.
+    pub(crate) fn track(&mut self, model: &Model, time: f64) {
+        self.busy_times
+            .insert(model.id().to_string(), busy_time(model, time));
+    }
+
+    /// Stop tracking a removed model.  The recorded activity of the model is
+    /// retained.
+    pub(crate) fn untrack(&mut self, model_id: &str) {
+        self.busy_times.remove(model_id);
+    }
+
+    /// Record the activity of the models with energy coefficients over a
+    /// step.  The sent messages are the outgoing message counts, by model
+    /// index.  Models without a tracked busy time are tracked from the
+    /// start of the run.
+    pub(crate) fn record_step(
+        &mut self,
+        start: f64,
+        end: f64,
+        models: &[Model],
+        coefficients: &BTreeMap<String, EnergyCoefficients>,
+        received: &[Message],
+        sent: &[(usize, usize)],
+    ) {
+        models.iter().enumerate().for_each(|(model_index, model)| {
+            if !coefficients.contains_key(model.id()) {
+                return;
+            }
+            let previous_busy_time = self.busy_times.entry(model.id().to_string()).or_insert(0.0);
+            let current_busy_time = busy_time(model, end).max(*previous_busy_time);
+            let usage = EnergyUsage {
+                start,
+                end: if end.is_finite() { end } else { start },
+                model_id: model.id().to_string(),
+                busy_time: current_busy_time - *previous_busy_time,
+                received: received
+                    .iter()
+                    .filter(|message| message.target_id() == model.id())
+                    .count(),
+                sent: sent
+                    .iter()
+                    .filter(|(index, _)| *index == model_index)
+                    .map(|(_, count)| count)
+                    .sum(),
+            };
+            *previous_busy_time = current_busy_time;
+            if usage.busy_time > 0.0 || usage.received > 0 || usage.sent > 0 {
+                self.usage.push(usage);
+            }
+        });
+    }
+
+    pub(crate) fn usage(&self) -> &[EnergyUsage] {
+        &self.usage
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.usage.clear();
+    }
+}
+
+/// The activity and consumption of a single model over a run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelEnergy {
+    pub busy_time: f64,
+    pub messages: usize,
+    pub busy_energy: f64,
+    pub message_energy: f64,
+    pub energy: f64,
+}
+
+/// The consumption of a single simulation time bucket `[start, end)`.
+/// Busy consumption is spread evenly over the busy step, and message
+/// consumption is attributed to the time of the message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnergyBucket {
+    pub start: f64,
+    pub end: f64,
+    pub energy: f64,
+}
+
+/// The energy report of a run - the total consumption, the consumption of
+/// each model with energy coefficients, and the consumption per simulation
+/// time bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnergyReport {
+    pub bucket_width: f64,
+    pub energy: f64,
+    pub models: BTreeMap<String, ModelEnergy>,
+    pub buckets: Vec<EnergyBucket>,
+}
+
+impl EnergyReport {
+    pub(crate) fn new(
+        bucket_width: f64,
+        end_time: f64,
+        coefficients: &BTreeMap<String, EnergyCoefficients>,
+        usage: &[EnergyUsage],
+    ) -> Result<Self, SimulationError> {
+        if !(bucket_width > 0.0 && bucket_width.is_finite()) {
+            return Err(SimulationError::InvalidBucketWidth(bucket_width));
+        }
+        let bucket_count = if end_time.is_finite() {
+            (end_time / bucket_width).floor() as usize + 1
+        } else {
+            1
+        };
+        let bucket_index = |time: f64| usize::min((time / bucket_width) as usize, bucket_count - 1);
+        let mut buckets: Vec<EnergyBucket> = (0..bucket_count)
+            .map(|index| EnergyBucket {
+                start: index as f64 * bucket_width,
+                end: (index + 1) as f64 * bucket_width,
+                energy: 0.0,
+            })
+            .collect();
+        let mut models: BTreeMap<String, ModelEnergy> = coefficients
+            .keys()
+            .map(|model_id| (model_id.clone(), ModelEnergy::default()))
+            .collect();
+        usage.iter().for_each(|usage| {
+            let coefficients = match coefficients.get(&usage.model_id) {
+                Some(coefficients) => coefficients,
+                None => return,
+            };
+            let busy_energy = usage.busy_time * coefficients.per_busy_time;
+            let model = models.entry(usage.model_id.clone()).or_default();
+            model.busy_time += usage.busy_time;
+            model.messages += usage.received + usage.sent;
+            model.busy_energy += busy_energy;
+            model.message_energy += (usage.received + usage.sent) as f64 * coefficients.per_message;
+            buckets[bucket_index(usage.start)].energy +=
+                usage.received as f64 * coefficients.per_message;
+            buckets[bucket_index(usage.end)].energy += usage.sent as f64 * coefficients.per_message;
+            // Spread the busy consumption over the buckets spanned by the step
+            let duration = usage.end - usage.start;
+            if duration > 0.0 {
+                (bucket_index(usage.start)..=bucket_index(usage.end)).for_each(|index| {
+                    let bucket = &mut buckets[index];
+                    let overlap = usage.end.min(bucket.end) - usage.start.max(bucket.start);
+                    if overlap > 0.0 {
+                        bucket.energy += busy_energy * overlap / duration;
+                    }
+                });
+            } else {
+                buckets[bucket_index(usage.start)].energy += busy_energy;
+            }
+        });
+        models
+            .values_mut()
+            .for_each(|model| model.energy = model.busy_energy + model.message_energy);
+        Ok(Self {
+            bucket_width,
+            energy: models.values().map(|model| model.energy).sum(),
+            models,
+            buckets,
+        })
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::execution_stats::{EventCount, StepTiming};
+use crate::utils::errors::SimulationError;
+
+/// Buckets with at least this multiple of the mean bucket event count are
+/// reported as bursts.
+pub const BURST_FACTOR: f64 = 3.0;
+
+/// The events of a single simulation time bucket `[start, end)`, in total
+/// and per model, with the wall time (in milliseconds) spent on the steps
+/// ending in the bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventBucket {
+    pub start: f64,
+    pub end: f64,
+    pub events: usize,
+    pub model_events: BTreeMap<String, usize>,
+    pub wall_time: f64,
+}
+
+/// A span of consecutive buckets without any events.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleSpan {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// The event density report of a run - the event counts per simulation
+/// time bucket and per model, the bursts (the indices of buckets with at
+/// least `BURST_FACTOR` times the mean bucket event count), and the idle
+/// spans.  The time compression is the simulation time advanced per second
+/// of wall time.  Event densities help in choosing warm-up periods, and in
+/// understanding where wall time is spent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDensityReport {
+    pub bucket_width: f64,
+    pub buckets: Vec<EventBucket>,
+    pub model_events: BTreeMap<String, usize>,
+    pub bursts: Vec<usize>,
+    pub idle_spans: Vec<IdleSpan>,
+    pub time_compression: Option<f64>,
+}
+
+impl EventDensityReport {
+    pub(crate) fn new(
+        bucket_width: f64,
+        end_time: f64,
+        events: &[EventCount],
+        step_timings: &[StepTiming],
+    ) -> Result<Self, SimulationError> {
+        if !(bucket_width > 0.0 && bucket_width.is_finite()) {
+            return Err(SimulationError::InvalidBucketWidth(bucket_width));
+        }
+        let bucket_count = if end_time.is_finite() {
+            (end_time / bucket_width).floor() as usize + 1
+        } else {
+            1
+        };
+        let bucket_index = |time: f64| usize::min((time / bucket_width) as usize, bucket_count - 1);
+        let mut buckets: Vec<EventBucket> = (0..bucket_count)
+            .map(|index| EventBucket {
+                start: index as f64 * bucket_width,
+                end: (index + 1) as f64 * bucket_width,
+                events: 0,
+                model_events: BTreeMap::new(),
+                wall_time: 0.0,
+            })
+            .collect();
+        let mut model_events = BTreeMap::new();
+        events.iter().for_each(|count| {
+            let bucket = &mut buckets[bucket_index(count.time)];
+            bucket.events += count.events;
+            *bucket
+                .model_events
+                .entry(count.model_id.clone())
+                .or_insert(0) += count.events;
+            *model_events.entry(count.model_id.clone()).or_insert(0) += count.events;
+        });
+        step_timings
+            .iter()
+            .filter(|timing| timing.time.is_finite())
+            .for_each(|timing| buckets[bucket_index(timing.time)].wall_time += timing.wall_time);
+        let mean_events =
+            buckets.iter().map(|bucket| bucket.events).sum::<usize>() as f64 / bucket_count as f64;
+        let bursts = buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, bucket)| {
+                mean_events > 0.0 && bucket.events as f64 >= BURST_FACTOR * mean_events
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let mut idle_spans: Vec<IdleSpan> = Vec::new();
+        buckets
+            .iter()
+            .filter(|bucket| bucket.events == 0)
+            .for_each(|bucket| match idle_spans.last_mut() {
+                Some(span) if span.end == bucket.start => span.end = bucket.end,
+                _ => idle_spans.push(IdleSpan {
+                    start: bucket.start,
+                    end: bucket.end,
+                }),
+            });
+        let wall_time: f64 = step_timings.iter().map(|timing| timing.wall_time).sum();
+        Ok(Self {
+            bucket_width,
+            buckets,
+            model_events,
+            bursts,
+            idle_spans,
+            time_compression: if wall_time > 0.0 && end_time.is_finite() {
+                Some(end_time / (wall_time / 1000.0))
+            } else {
+                None
+            },
+        })
+    }
+}
@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{DevsModel, Model};
+use crate::utils::errors::SimulationError;
+
+/// The strategy for finding the imminent models of each simulation step.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventScheduling {
+    /// Scan every model's time advance in every step
+    #[default]
+    Scan,
+    /// Keep the models' next event times in a future event list (a binary
+    /// heap), so each step only touches the imminent models and the
+    /// message targets - for networks with many mostly-idle models
+    FutureEventList,
+}
+
+impl EventScheduling {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A scheduled internal event of a model.  Rescheduling a model leaves its
+/// earlier entries in the heap, which are then recognized as stale by their
+/// generation, and discarded.
+#[derive(Debug, Clone, Copy)]
+struct ScheduledEvent {
+    time: f64,
+    model_index: usize,
+    generation: u64,
+}
+
+impl PartialEq for ScheduledEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScheduledEvent {}
+
+impl PartialOrd for ScheduledEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledEvent {
+    // Reversed, for the earliest event (and then the lowest model index) at
+    // the top of the max-heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .time
+            .total_cmp(&self.time)
+            .then_with(|| other.model_index.cmp(&self.model_index))
+            .then_with(|| other.generation.cmp(&self.generation))
+    }
+}
+
+/// The future event list of a simulation run - the absolute next event
+/// time of every model, by model index, in a binary heap.  Models are advanced lazily, when
+/// they receive messages or become imminent, so idle models are not touched
+/// in each step.  The models are synchronized to the global time with
+/// `synchronize`, before the model states are observed.
+#[derive(Debug, Clone)]
+pub(crate) struct FutureEventList {
+    heap: BinaryHeap<ScheduledEvent>,
+    scheduled: Vec<f64>,
+    generations: Vec<u64>,
+    advanced_to: Vec<f64>,
+    // The models with internal transitions in the last step
+    transitioned: Vec<usize>,
+}
+
+impl FutureEventList {
+    pub(crate) fn new(models: &[Model], global_time: f64) -> Result<Self, SimulationError> {
+        let mut event_list = Self {
+            heap: BinaryHeap::with_capacity(models.len()),
+            scheduled: vec![f64::INFINITY; models.len()],
+            generations: vec![0; models.len()],
+            advanced_to: vec![global_time; models.len()],
+            // Every model is observed in the first step
+            transitioned: (0..models.len()).collect(),
+        };
+        (0..models.len()).try_for_each(|index| event_list.schedule(models, index))?;
+        Ok(event_list)
+    }
+
+    pub(crate) fn take_transitioned(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.transitioned)
+    }
+
+    pub(crate) fn set_transitioned(&mut self, transitioned: Vec<usize>) {
+        self.transitioned = transitioned;
+    }
+
+    /// Schedule the next internal event of a model, after a state
+    /// transition, from the model's time advance.
+    pub(crate) fn schedule(
+        &mut self,
+        models: &[Model],
+        model_index: usize,
+    ) -> Result<(), SimulationError> {
+        let model = &models[model_index];
+        let until_next_event = model.until_next_event();
+        if until_next_event.is_nan() || until_next_event < 0.0 {
+            return Err(SimulationError::InvalidTimeAdvance {
+                model_id: model.id().to_string(),
+                value: until_next_event,
+            });
+        }
+        let time = self.advanced_to[model_index] + until_next_event;
+        if time == self.scheduled[model_index] {
+            return Ok(());
+        }
+        self.generations[model_index] += 1;
+        self.scheduled[model_index] = time;
+        if time.is_finite() {
+            self.heap.push(ScheduledEvent {
+                time,
+                model_index,
+                generation: self.generations[model_index],
+            });
+        }
+        // Bound the stale entries
+        if self.heap.len() > 2 * self.scheduled.len() + 64 {
+            self.compact();
+        }
+        Ok(())
+    }
+
+    fn compact(&mut self) {
+        let generations = &self.generations;
+        self.heap
+            .retain(|event| event.generation == generations[event.model_index]);
+    }
+
+    fn is_stale(&self, event: &ScheduledEvent) -> bool {
+        event.generation != self.generations[event.model_index]
+    }
+
+    /// The earliest scheduled internal event time, or infinity.
+    pub(crate) fn next_time(&mut self) -> f64 {
+        while let Some(event) = self.heap.peek() {
+            if !self.is_stale(event) {
+                return event.time;
+            }
+            self.heap.pop();
+        }
+        f64::INFINITY
+    }
+
+    /// Remove and return the models with internal events at (or before) a
+    /// time, in model order.
+    pub(crate) fn pop_imminent(&mut self, time: f64) -> Vec<usize> {
+        let mut imminent = Vec::new();
+        while let Some(event) = self.heap.peek().copied() {
+            if event.time > time {
+                break;
+            }
+            self.heap.pop();
+            if !self.is_stale(&event) {
+                self.scheduled[event.model_index] = f64::INFINITY;
+                self.generations[event.model_index] += 1;
+                imminent.push(event.model_index);
+            }
+        }
+        imminent.sort_unstable();
+        imminent
+    }
+
+    /// Advance a model to a time, ahead of an external event.
+    pub(crate) fn advance(&mut self, models: &mut [Model], model_index: usize, time: f64) {
+        let elapsed = time - self.advanced_to[model_index];
+        if elapsed > 0.0 {
+            models[model_index].time_advance(elapsed);
+        }
+        self.advanced_to[model_index] = time;
+    }
+
+    /// Advance an imminent model by its full time advance, to its internal
+    /// event time.
+    pub(crate) fn advance_imminent(&mut self, models: &mut [Model], model_index: usize, time: f64) {
+        let until_next_event = models[model_index].until_next_event();
+        models[model_index].time_advance(until_next_event);
+        self.advanced_to[model_index] = time;
+    }
+
+    /// Advance every model to a (finite) time.
+    pub(crate) fn synchronize(&mut self, models: &mut [Model], time: f64) {
+        if !time.is_finite() {
+            return;
+        }
+        (0..models.len()).for_each(|model_index| self.advance(models, model_index, time));
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Model, Reportable};
+
+/// The deepest queue observed for a single model, and the simulation time
+/// it was first reached.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueWatermark {
+    pub depth: usize,
+    pub time: f64,
+}
+
+/// A warning, raised the first time a model's queue depth exceeds the
+/// configured threshold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatermarkAlert {
+    pub model_id: String,
+    pub depth: usize,
+    pub threshold: usize,
+    pub time: f64,
+}
+
+/// Execution statistics gathered by the simulator over a run - the step
+/// count, the wall time spent stepping (in milliseconds), the queue depth
+/// high-watermarks of each model reporting a queue depth, and any threshold
+/// alerts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionStats {
+    pub steps: usize,
+    pub wall_time: f64,
+    pub queue_watermarks: BTreeMap<String, QueueWatermark>,
+    pub watermark_alerts: Vec<WatermarkAlert>,
+}
+
+/// The events of a single model within a single step, at a simulation
+/// time.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EventCount {
+    pub(crate) time: f64,
+    pub(crate) model_id: String,
+    pub(crate) events: usize,
+}
+
+/// The wall time (in milliseconds) of a single step, ending at a simulation
+/// time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StepTiming {
+    pub(crate) time: f64,
+    pub(crate) wall_time: f64,
+}
+
+/// The execution tracker counts the steps of a run, and observes model
+/// queue depths after each step's external events, where queues grow.  The
+/// events of each model, and the wall time of each step, are retained for
+/// the event density report.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExecutionTracker {
+    pub(crate) threshold: Option<usize>,
+    pub(crate) stats: ExecutionStats,
+    pub(crate) events: Vec<EventCount>,
+    pub(crate) step_timings: Vec<StepTiming>,
+}
+
+impl ExecutionTracker {
+    pub(crate) fn record_step(&mut self, time: f64, wall_time: f64) {
+        self.stats.steps += 1;
+        self.stats.wall_time += wall_time;
+        self.step_timings.push(StepTiming { time, wall_time });
+    }
+
+    pub(crate) fn record_events(&mut self, time: f64, model_id: &str, events: usize) {
+        if events > 0 {
+            self.events.push(EventCount {
+                time,
+                model_id: model_id.to_string(),
+                events,
+            });
+        }
+    }
+
+    pub(crate) fn observe<'a>(&mut self, models: impl IntoIterator<Item = &'a Model>, time: f64) {
+        models.into_iter().for_each(|model| {
+            let depth = match model.queue_depth() {
+                Some(depth) => depth,
+                None => return,
+            };
+            let watermark = self
+                .stats
+                .queue_watermarks
+                .entry(model.id().to_string())
+                .or_insert(QueueWatermark { depth: 0, time });
+            if depth <= watermark.depth {
+                return;
+            }
+            let previous_depth = watermark.depth;
+            *watermark = QueueWatermark { depth, time };
+            match self.threshold {
+                Some(threshold) if depth > threshold && previous_depth <= threshold => {
+                    self.stats.watermark_alerts.push(WatermarkAlert {
+                        model_id: model.id().to_string(),
+                        depth,
+                        threshold,
+                        time,
+                    })
+                }
+                _ => {}
+            }
+        });
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.stats = ExecutionStats::default();
+        self.events.clear();
+        self.step_timings.clear();
+    }
+}
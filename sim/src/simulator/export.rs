@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use super::coupling::Message;
+use crate::models::ModelRecord;
+
+/// The treatment of the digits beyond the exported time precision.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeRounding {
+    /// Round to the nearest exported time
+    #[default]
+    Round,
+    /// Drop the digits beyond the exported precision (decimation)
+    Truncate,
+}
+
+/// The precision of exported message and record times, in decimal places
+/// (e.g. 3 for milliseconds, when simulation time is in seconds).  Reduced
+/// precision shrinks exported output, and keeps exports stable across
+/// platforms with slightly different floating point results.  The precision
+/// applies only to exported copies - simulation execution always uses the
+/// full precision times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimePrecision {
+    pub decimals: u32,
+    #[serde(default)]
+    pub rounding: TimeRounding,
+}
+
+impl TimePrecision {
+    pub fn new(decimals: u32, rounding: TimeRounding) -> Self {
+        Self { decimals, rounding }
+    }
+
+    /// A time, at the exported precision.  Scaling by the power of ten,
+    /// rather than multiplying by the resolution, yields the closest float
+    /// to the decimal time, which serializes without trailing noise digits.
+    /// Times that cannot be scaled (e.g. infinite times) are unchanged.
+    pub fn apply(&self, time: f64) -> f64 {
+        let scale = 10f64.powi(self.decimals.min(i32::MAX as u32) as i32);
+        let scaled = time * scale;
+        if !scaled.is_finite() {
+            return time;
+        }
+        match self.rounding {
+            TimeRounding::Round => scaled.round() / scale,
+            TimeRounding::Truncate => scaled.trunc() / scale,
+        }
+    }
+
+    /// Copies of messages, with times at the exported precision.
+    pub fn messages<'a>(&self, messages: impl IntoIterator<Item = &'a Message>) -> Vec<Message> {
+        messages
+            .into_iter()
+            .map(|message| message.clone().with_time(self.apply(*message.time())))
+            .collect()
+    }
+
+    /// Copies of model records, with times at the exported precision.
+    pub fn records<'a>(
+        &self,
+        records: impl IntoIterator<Item = &'a ModelRecord>,
+    ) -> Vec<ModelRecord> {
+        records
+            .into_iter()
+            .map(|record| ModelRecord {
+                time: self.apply(record.time),
+                ..record.clone()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+use super::coupling::Message;
+use super::messages::MessageFilter;
+use super::state_diff::{apply_change, diff_values, snapshot, StateChange};
+use crate::models::Model;
+use crate::utils::errors::SimulationError;
+
+/// The full serialized state of every model, at a point in the history.
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    step: usize,
+    states: HashMap<String, Value>,
+}
+
+/// The model state changes of a single simulation step.
+#[derive(Debug, Clone)]
+struct HistoryStep {
+    time: f64,
+    changes: Vec<(String, Vec<StateChange>)>,
+}
+
+/// The retained model states, as periodic checkpoints of the full state,
+/// and the per-step state changes in between.
+#[derive(Debug, Clone)]
+struct StateHistory {
+    checkpoint_interval: usize,
+    steps: Vec<HistoryStep>,
+    checkpoints: Vec<Checkpoint>,
+    current: HashMap<String, Value>,
+}
+
+/// The retained history of a simulation - every reported message, and
+/// optionally the model states over time.  The state at any point in the
+/// history is reconstructed by replaying the state changes onto the
+/// preceding checkpoint.
+#[derive(Debug, Clone)]
+pub(crate) struct History {
+    start_time: f64,
+    messages: Vec<Message>,
+    states: Option<StateHistory>,
+}
+
+impl History {
+    pub(crate) fn new(
+        checkpoint_interval: usize,
+        start_time: f64,
+        models: &[Model],
+    ) -> Result<Self, SimulationError> {
+        let current = snapshot(models)?;
+        Ok(Self {
+            start_time,
+            messages: Vec::new(),
+            states: Some(StateHistory {
+                checkpoint_interval: checkpoint_interval.max(1),
+                steps: Vec::new(),
+                checkpoints: vec![Checkpoint {
+                    step: 0,
+                    states: current.clone(),
+                }],
+                current,
+            }),
+        })
+    }
+
+    /// A history of the messages alone, without the model states.
+    pub(crate) fn messages_only(start_time: f64) -> Self {
+        Self {
+            start_time,
+            messages: Vec::new(),
+            states: None,
+        }
+    }
+
+    /// An empty history, retaining the same detail from `start_time`.
+    pub(crate) fn restart(
+        &self,
+        start_time: f64,
+        models: &[Model],
+    ) -> Result<Self, SimulationError> {
+        match &self.states {
+            Some(states) => Self::new(states.checkpoint_interval, start_time, models),
+            None => Ok(Self::messages_only(start_time)),
+        }
+    }
+
+    pub(crate) fn retains_states(&self) -> bool {
+        self.states.is_some()
+    }
+
+    pub(crate) fn record_messages(&mut self, messages: &[Message]) {
+        self.messages.extend_from_slice(messages);
+    }
+
+    pub(crate) fn record_step(
+        &mut self,
+        time: f64,
+        models: &[Model],
+    ) -> Result<(), SimulationError> {
+        match &mut self.states {
+            Some(states) => states.record_step(time, models),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    pub(crate) fn query(&self, filter: &MessageFilter) -> Vec<&Message> {
+        self.messages
+            .iter()
+            .filter(|message| filter.matches(message))
+            .collect()
+    }
+
+    pub(crate) fn messages_between(&self, start: f64, end: f64) -> Vec<&Message> {
+        self.messages
+            .iter()
+            .filter(|message| *message.time() >= start && *message.time() <= end)
+            .collect()
+    }
+
+    pub(crate) fn messages_for_model(&self, model_id: &str) -> Vec<&Message> {
+        self.messages
+            .iter()
+            .filter(|message| message.source_id() == model_id || message.target_id() == model_id)
+            .collect()
+    }
+
+    pub(crate) fn state_at(&self, time: f64) -> Result<BTreeMap<String, Value>, SimulationError> {
+        if time < self.start_time {
+            return Err(SimulationError::HistoryUnavailable);
+        }
+        self.states
+            .as_ref()
+            .ok_or(SimulationError::HistoryUnavailable)?
+            .state_at(time)
+    }
+}
+
+impl StateHistory {
+    fn record_step(&mut self, time: f64, models: &[Model]) -> Result<(), SimulationError> {
+        let next = snapshot(models)?;
+        let mut model_ids: Vec<&String> = self.current.keys().chain(next.keys()).collect();
+        model_ids.sort();
+        model_ids.dedup();
+        let changes = model_ids
+            .into_iter()
+            .filter_map(|model_id| {
+                let mut model_changes = Vec::new();
+                diff_values(
+                    String::new(),
+                    self.current.get(model_id),
+                    next.get(model_id),
+                    &mut model_changes,
+                );
+                if model_changes.is_empty() {
+                    None
+                } else {
+                    Some((model_id.clone(), model_changes))
+                }
+            })
+            .collect();
+        self.steps.push(HistoryStep { time, changes });
+        if self.steps.len().is_multiple_of(self.checkpoint_interval) {
+            self.checkpoints.push(Checkpoint {
+                step: self.steps.len(),
+                states: next.clone(),
+            });
+        }
+        self.current = next;
+        Ok(())
+    }
+
+    fn state_at(&self, time: f64) -> Result<BTreeMap<String, Value>, SimulationError> {
+        // The number of steps completed by the requested time
+        let step = self
+            .steps
+            .iter()
+            .take_while(|step| step.time <= time)
+            .count();
+        let checkpoint = self
+            .checkpoints
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.step <= step)
+            .ok_or(SimulationError::HistoryUnavailable)?;
+        let mut states = checkpoint.states.clone();
+        self.steps[checkpoint.step..step]
+            .iter()
+            .flat_map(|history_step| history_step.changes.iter())
+            .for_each(|(model_id, changes)| {
+                let state = states.entry(model_id.clone()).or_insert(Value::Null);
+                changes
+                    .iter()
+                    .for_each(|change| apply_change(state, change));
+            });
+        Ok(states
+            .into_iter()
+            .filter(|(_, state)| !state.is_null())
+            .collect())
+    }
+}
@@ -0,0 +1,90 @@
+//! Initial conditions randomize model states at the start of a simulation,
+//! to reduce initialization bias and support steady-state starts - for
+//! example, pre-populating a processor queue with a Poisson-distributed
+//! number of jobs, or closing a gate with some probability.  Initial
+//! conditions act through the standard message system, as messages
+//! injected at time 0, so they apply to any model (including custom
+//! models) without model-specific initialization logic.
+
+use serde::{Deserialize, Serialize};
+
+use super::coupling::Message;
+use crate::input_modeling::dynamic_rng::DynRng;
+use crate::input_modeling::{BooleanRandomVariable, DiscreteRandomVariable};
+use crate::utils::errors::SimulationError;
+
+/// The source ID of initial condition messages.
+pub const INITIALIZATION_SOURCE_ID: &str = "initialization";
+
+/// A randomized initial condition for a single model input port.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitialCondition {
+    pub model_id: String,
+    pub port: String,
+    #[serde(flatten)]
+    pub injection: InitialInjection,
+}
+
+/// The messages of an initial condition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InitialInjection {
+    /// A random number of jobs, with content `{prefix} {index}` (e.g. to
+    /// pre-populate a processor queue)
+    Jobs {
+        count: DiscreteRandomVariable,
+        #[serde(default = "default_prefix")]
+        prefix: String,
+    },
+    /// A single message, injected with a random outcome (e.g. to close a
+    /// gate)
+    Signal {
+        probability: BooleanRandomVariable,
+        #[serde(default)]
+        content: String,
+    },
+}
+
+fn default_prefix() -> String {
+    String::from("initial")
+}
+
+impl InitialCondition {
+    /// Draw the initial condition messages, from the provided random
+    /// number generator.
+    pub(crate) fn messages(
+        &mut self,
+        uniform_rng: DynRng,
+    ) -> Result<Vec<Message>, SimulationError> {
+        let (model_id, port) = (self.model_id.clone(), self.port.clone());
+        let message = |content: String| {
+            Message::new(
+                INITIALIZATION_SOURCE_ID.to_string(),
+                INITIALIZATION_SOURCE_ID.to_string(),
+                model_id.clone(),
+                port.clone(),
+                0.0,
+                content,
+            )
+        };
+        match &mut self.injection {
+            InitialInjection::Jobs { count, prefix } => {
+                let count = count.random_variate(uniform_rng)?;
+                Ok((1..=count)
+                    .map(|index| message(format!["{} {}", prefix, index]))
+                    .collect())
+            }
+            InitialInjection::Signal {
+                probability,
+                content,
+            } => {
+                if probability.random_variate(uniform_rng)? {
+                    Ok(vec![message(content.clone())])
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::coupling::Connector;
+use crate::build_info::build_info;
+use crate::input_modeling::Globals;
+use crate::models::Model;
+use crate::utils::errors::SimulationError;
+use crate::utils::{fnv1a, wall_clock_time};
+
+/// The reproducibility manifest of a simulation run - the engine version
+/// and compiled features, the random number generator seed, a hash of the
+/// simulation configuration, and the execution extent.  Manifests are
+/// serializable, for embedding in result files, so results can be traced
+/// back to their exact inputs and engine version.  The wall times are in
+/// milliseconds - the creation time since the Unix epoch, and the time
+/// spent stepping the simulation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunManifest {
+    pub version: String,
+    pub features: Vec<String>,
+    pub rng_seed: Option<u64>,
+    pub config_hash: String,
+    pub global_time: f64,
+    pub steps: usize,
+    pub wall_time: f64,
+    pub created_at: f64,
+}
+
+/// The hash of a simulation configuration - the models (excluding their
+/// state), the connectors, and the global variables.  Object fields are
+/// ordered in the canonical form, so the hash is independent of field
+/// declaration and configuration file order.
+pub fn config_hash(
+    models: &[Model],
+    connectors: &[Connector],
+    globals: &Globals,
+) -> Result<String, SimulationError> {
+    let models: Vec<Value> = models
+        .iter()
+        .map(|model| {
+            let mut config = serde_json::to_value(model)?;
+            if let Some(fields) = config.as_object_mut() {
+                fields.remove("state");
+            }
+            Ok(config)
+        })
+        .collect::<Result<_, SimulationError>>()?;
+    let canonical = serde_json::to_value((models, connectors, globals))?;
+    Ok(format!["{:016x}", fnv1a(canonical.to_string().as_bytes())])
+}
+
+impl RunManifest {
+    pub(crate) fn new(
+        rng_seed: Option<u64>,
+        config_hash: String,
+        global_time: f64,
+        steps: usize,
+        wall_time: f64,
+    ) -> Self {
+        let build = build_info();
+        Self {
+            version: build.version,
+            features: build.features,
+            rng_seed,
+            config_hash,
+            global_time,
+            steps,
+            wall_time,
+            created_at: wall_clock_time(),
+        }
+    }
+}
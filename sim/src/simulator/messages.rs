@@ -0,0 +1,215 @@
+//! The columnar message container supports message analysis without
+//! repeated iterator chains over the message fields.  The messages returned
+//! by stepping are converted into a `Messages` container, filtered with a
+//! `MessageFilter`, and then analyzed column by column - for example, the
+//! transmission times of the messages arriving at a sink.
+
+use std::iter::FromIterator;
+
+use serde::{Deserialize, Serialize};
+
+use super::coupling::Message;
+
+/// The flattened fields of a message - the source ID, source port, target
+/// ID, target port, time, and content.
+pub type MessageTuple = (String, String, String, String, f64, String);
+
+/// The flattened fields of a message, borrowed from the message or the
+/// message columns.
+pub type MessageRow<'a> = (&'a str, &'a str, &'a str, &'a str, f64, &'a str);
+
+/// A message history query.  Every specified criterion must match, and the
+/// time range is inclusive.  The default filter matches every message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_port: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_port: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<f64>,
+}
+
+impl MessageFilter {
+    pub fn matches(&self, message: &Message) -> bool {
+        self.matches_fields(message.as_tuple())
+    }
+
+    /// Match the flattened fields of a message, as stored by column.
+    pub(crate) fn matches_fields(
+        &self,
+        (source_id, source_port, target_id, target_port, time, _): MessageRow<'_>,
+    ) -> bool {
+        let matches = |criterion: &Option<String>, value: &str| {
+            criterion
+                .as_ref()
+                .is_none_or(|criterion| criterion == value)
+        };
+        matches(&self.source_id, source_id)
+            && matches(&self.source_port, source_port)
+            && matches(&self.target_id, target_id)
+            && matches(&self.target_port, target_port)
+            && self.start.is_none_or(|start| time >= start)
+            && self.end.is_none_or(|end| time <= end)
+    }
+}
+
+impl Message {
+    /// The flattened fields of a message, borrowed.
+    pub fn as_tuple(&self) -> MessageRow<'_> {
+        (
+            self.source_id(),
+            self.source_port(),
+            self.target_id(),
+            self.target_port(),
+            *self.time(),
+            self.content(),
+        )
+    }
+}
+
+impl From<Message> for MessageTuple {
+    fn from(message: Message) -> Self {
+        let (source_id, source_port, target_id, target_port, time, content) = message.as_tuple();
+        (
+            source_id.to_string(),
+            source_port.to_string(),
+            target_id.to_string(),
+            target_port.to_string(),
+            time,
+            content.to_string(),
+        )
+    }
+}
+
+/// A set of messages, stored by column.  Only the flattened message fields
+/// are retained - job IDs, metadata, and payloads are not columns.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Messages {
+    source_ids: Vec<String>,
+    source_ports: Vec<String>,
+    target_ids: Vec<String>,
+    target_ports: Vec<String>,
+    times: Vec<f64>,
+    contents: Vec<String>,
+}
+
+impl Messages {
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    pub fn push(&mut self, message: &Message) {
+        self.push_row(message.as_tuple());
+    }
+
+    fn push_row(
+        &mut self,
+        (source_id, source_port, target_id, target_port, time, content): MessageRow<'_>,
+    ) {
+        self.source_ids.push(source_id.to_string());
+        self.source_ports.push(source_port.to_string());
+        self.target_ids.push(target_id.to_string());
+        self.target_ports.push(target_port.to_string());
+        self.times.push(time);
+        self.contents.push(content.to_string());
+    }
+
+    pub fn source_ids(&self) -> &[String] {
+        &self.source_ids
+    }
+
+    pub fn source_ports(&self) -> &[String] {
+        &self.source_ports
+    }
+
+    pub fn target_ids(&self) -> &[String] {
+        &self.target_ids
+    }
+
+    pub fn target_ports(&self) -> &[String] {
+        &self.target_ports
+    }
+
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    pub fn contents(&self) -> &[String] {
+        &self.contents
+    }
+
+    /// The flattened fields of the message at the index, if any.
+    pub fn get(&self, index: usize) -> Option<MessageRow<'_>> {
+        if index < self.len() {
+            Some((
+                &self.source_ids[index],
+                &self.source_ports[index],
+                &self.target_ids[index],
+                &self.target_ports[index],
+                self.times[index],
+                &self.contents[index],
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// The flattened fields of the messages, in order.
+    pub fn rows(&self) -> impl Iterator<Item = MessageRow<'_>> {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+
+    /// The messages matching the filter, in order.
+    pub fn filter(&self, filter: &MessageFilter) -> Messages {
+        let mut filtered = Messages::default();
+        self.rows()
+            .filter(|row| filter.matches_fields(*row))
+            .for_each(|row| filtered.push_row(row));
+        filtered
+    }
+}
+
+impl<'a> FromIterator<&'a Message> for Messages {
+    fn from_iter<I: IntoIterator<Item = &'a Message>>(messages: I) -> Self {
+        let mut columns = Messages::default();
+        messages
+            .into_iter()
+            .for_each(|message| columns.push(message));
+        columns
+    }
+}
+
+impl FromIterator<Message> for Messages {
+    fn from_iter<I: IntoIterator<Item = Message>>(messages: I) -> Self {
+        let mut columns = Messages::default();
+        messages
+            .into_iter()
+            .for_each(|message| columns.push(&message));
+        columns
+    }
+}
+
+impl From<&[Message]> for Messages {
+    fn from(messages: &[Message]) -> Self {
+        messages.iter().collect()
+    }
+}
+
+impl From<Vec<Message>> for Messages {
+    fn from(messages: Vec<Message>) -> Self {
+        messages.into_iter().collect()
+    }
+}
@@ -13,25 +13,143 @@
 //! and analysis of messages.  The `step`, `step_n`, and `step_until` methods
 //! return the messages generated during the execution of the simulation
 //! step(s), for use in message analysis.
+//!
+//! A configured `Simulation` is `Send` and `Sync`, for embedding in
+//! services.  Simulations may be moved into async tasks and thread pools,
+//! and shared across threads for read access.
 
-use std::f64::INFINITY;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::Sender;
 
+use rand_core::SeedableRng;
 use serde::{Deserialize, Serialize};
 
-use crate::input_modeling::dynamic_rng::SimulationRng;
-use crate::input_modeling::dyn_rng;
-use crate::models::{DevsModel, Model, ModelMessage, ModelRecord, Reportable};
+use crate::input_modeling::dynamic_rng::{DynRng, SimulationRng};
+use crate::input_modeling::Globals;
+use crate::input_modeling::{dyn_rng, seeded_dyn_rng};
+use crate::models::{
+    DevsModel, Model, ModelMessage, ModelRecord, PortStats, Reportable, SinkSummary,
+    UtilizationSummary,
+};
 use crate::utils::errors::SimulationError;
-use crate::utils::set_panic_hook;
+use crate::utils::{set_panic_hook, wall_clock_time, yaml};
 
+pub mod assertion;
+pub mod audit;
+pub mod blackboard;
+pub mod checkpoint;
+mod correlation;
 pub mod coupling;
+pub mod dry_run;
+pub mod edit;
+pub mod energy;
+pub mod event_density;
+pub mod event_list;
+mod execution_stats;
+pub mod export;
+mod history;
+pub mod initialization;
+pub mod manifest;
+pub mod messages;
+pub mod observer;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod pool;
+pub mod quota;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod real_time;
+pub mod routing;
 pub mod services;
+pub mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod state_diff;
+pub mod stop_condition;
+pub mod subscription;
+pub mod summary;
+pub mod template;
+pub mod topology;
+pub mod topology_analysis;
+pub mod trace;
+pub mod validation;
+pub mod verbosity;
 pub mod web;
 
-pub use self::coupling::{Connector, Message};
-pub use self::services::Services;
+pub use self::assertion::{
+    Assertion, AssertionMetric, AssertionReport, AssertionResult, AssertionStatistic, Comparison,
+};
+pub use self::audit::AuditRecord;
+pub use self::blackboard::{Blackboard, BlackboardEntry, BlackboardValue};
+pub use self::checkpoint::{Checkpoint, CHECKPOINT_FORMAT_VERSION};
+pub use self::coupling::{
+    Connector, InjectionPriority, JobId, Message, Payload, CORRELATION_ID_KEY, JOB_ID_KEY,
+    PRIORITY_KEY,
+};
+pub use self::dry_run::{ConnectorEstimate, DryRunReport, ModelEstimate};
+pub use self::edit::{EditOperation, RemovedModel};
+pub use self::energy::{EnergyBucket, EnergyCoefficients, EnergyReport, ModelEnergy};
+pub use self::event_density::{EventBucket, EventDensityReport, IdleSpan};
+pub use self::event_list::EventScheduling;
+pub use self::execution_stats::{ExecutionStats, QueueWatermark, WatermarkAlert};
+pub use self::export::{TimePrecision, TimeRounding};
+pub use self::initialization::{InitialCondition, InitialInjection};
+pub use self::manifest::RunManifest;
+pub use self::messages::{MessageFilter, MessageRow, MessageTuple, Messages};
+pub use self::observer::{SimulationObserver, TransitionKind};
+pub use self::pool::{PoolScheduling, PooledMessage, SimulationPool};
+pub use self::quota::{QuotaKind, Quotas};
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::real_time::RealTimeExecutor;
+pub use self::services::{RngStreams, Services, VariateLog, VariateRecord};
+pub use self::snapshot::{SnapshotCompression, SNAPSHOT_FORMAT_VERSION};
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::SqliteWriter;
+pub use self::state_diff::StateChange;
+pub use self::stop_condition::StopCondition;
+pub use self::subscription::{EventKind, SimulationEvent};
+pub use self::summary::{ModelSummary, SimulationSummary};
+pub use self::template::{ModelEntry, ModelTemplate, TemplateInstances};
+pub use self::topology_analysis::{ModelDegree, TopologyReport};
+pub use self::trace::{Trace, TraceEvent};
+pub use self::validation::{
+    AssumptionsReport, ModelAssumptions, PortDirection, ValidationError, ValidationOptions,
+    ValidationReport, ValidationSeverity,
+};
+pub use self::verbosity::Verbosity;
 pub use self::web::Simulation as WebSimulation;
 
+use self::audit::{AuditLog, PutDetail};
+use self::correlation::CorrelationTracker;
+use self::edit::{patched_model, EditTransaction};
+use self::energy::EnergyTracker;
+use self::event_list::FutureEventList;
+use self::execution_stats::ExecutionTracker;
+use self::history::History;
+use self::observer::Observers;
+use self::quota::memory_estimate;
+use self::routing::{matches, RoutingTable};
+use self::state_diff::StateSnapshots;
+use self::stop_condition::StopMonitor;
+use self::subscription::Subscriptions;
+use self::template::split_entries;
+use self::validation::{assumptions_report, validation_errors};
+
+// Compile-time assertions, keeping simulations (and everything they hold)
+// thread-safe
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Simulation>();
+    assert_send_sync::<Services>();
+    assert_send_sync::<Model>();
+    assert_send_sync::<DynRng>();
+};
+
+/// The separator of the model IDs in the path of a nested component.
+pub const COMPONENT_PATH_SEPARATOR: char = '/';
+
 /// The `Simulation` struct is the core of sim, and includes everything
 /// needed to run a simulation - models, connectors, and a random number
 /// generator.  State information, specifically global time and active
@@ -43,6 +161,67 @@ pub struct Simulation {
     connectors: Vec<Connector>,
     messages: Vec<Message>,
     services: Services,
+    #[serde(default, skip_serializing_if = "CorrelationTracker::is_empty")]
+    correlations: CorrelationTracker,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    audit_log: Option<AuditLog>,
+    // Future input injections, ordered by injection time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scheduled_inputs: Vec<Message>,
+    #[serde(default, skip_serializing_if = "InjectionPriority::is_default")]
+    injection_priority: InjectionPriority,
+    #[serde(default, skip_serializing_if = "EventScheduling::is_default")]
+    event_scheduling: EventScheduling,
+    // The verbosity of models with non-default verbosity, by model ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    verbosity: BTreeMap<String, Verbosity>,
+    // The energy coefficients of models with energy accounting, by model ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    energy_coefficients: BTreeMap<String, EnergyCoefficients>,
+    // Declarative checks of the simulation outputs, evaluated at the end
+    // of a run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    assertions: Vec<Assertion>,
+    // The future event list, under future event list scheduling, while
+    // stepping
+    #[serde(skip)]
+    event_list: Option<FutureEventList>,
+    // The number of injected messages at the front of the active messages,
+    // under injected message priority
+    #[serde(skip)]
+    injected_count: usize,
+    #[serde(skip)]
+    state_snapshots: Option<StateSnapshots>,
+    #[serde(skip)]
+    history: Option<History>,
+    #[serde(skip)]
+    subscriptions: Subscriptions,
+    #[serde(skip)]
+    observers: Observers,
+    #[serde(skip)]
+    trace: Option<Trace>,
+    #[serde(skip)]
+    execution: ExecutionTracker,
+    #[serde(skip)]
+    energy: EnergyTracker,
+    // The open edit transaction, if any
+    #[serde(skip)]
+    edit: Option<EditTransaction>,
+    // The compiled connectors, kept in sync with the models and connectors
+    #[serde(skip)]
+    routes: Option<RoutingTable>,
+    // The model indices by model ID, kept in sync with the models
+    #[serde(skip)]
+    model_indices: Option<HashMap<String, usize>>,
+    // The resource quotas, set by the host rather than the configuration
+    #[serde(skip)]
+    quotas: Quotas,
+    // The validation errors and warnings of the last posted configuration
+    #[serde(skip)]
+    configuration_errors: Vec<ValidationError>,
+    #[cfg(feature = "parallel")]
+    #[serde(skip)]
+    parallel: bool,
 }
 
 impl Simulation {
@@ -55,6 +234,7 @@ impl Simulation {
             connectors,
             ..Self::default()
         }
+        .with_compiled_topology()
     }
 
     /// This constructor method creates a simulation from a supplied
@@ -70,29 +250,231 @@ impl Simulation {
             connectors,
             services: Services {
                 global_rng: dyn_rng(global_rng),
-                global_time: 0.0,
+                rng_seed: None,
+                ..Services::default()
+            },
+            ..Self::default()
+        }
+        .with_compiled_topology()
+    }
+
+    /// This constructor method creates a simulation from a supplied
+    /// configuration (models and connectors), with the built-in global
+    /// random number generator seeded from a `u64` seed.  Simulations with
+    /// the same configuration and seed are fully deterministic, for
+    /// reproducible replications and tests.
+    pub fn post_with_seed(models: Vec<Model>, connectors: Vec<Connector>, seed: u64) -> Self {
+        let mut simulation = Self::post(models, connectors);
+        simulation.services.set_seed(seed);
+        simulation
+    }
+
+    /// This constructor method creates a simulation from a supplied
+    /// configuration (models and connectors), with a global random number
+    /// generator of a user-supplied type, seeded from a `u64` seed.  Unlike
+    /// `post_with_rng`, the seed is recorded (e.g. in run manifests).
+    pub fn post_with_seedable_rng<Rng: SimulationRng + SeedableRng + 'static>(
+        models: Vec<Model>,
+        connectors: Vec<Connector>,
+        seed: u64,
+    ) -> Self {
+        set_panic_hook();
+        Self {
+            models,
+            connectors,
+            services: Services {
+                global_rng: seeded_dyn_rng::<Rng>(seed),
+                rng_seed: Some(seed),
+                ..Services::default()
             },
             ..Self::default()
         }
+        .with_compiled_topology()
+    }
+
+    /// Compile the connectors into the routing table, and index the models
+    /// by ID, so each outgoing message is routed and delivered with
+    /// lookups, rather than scans of the connectors and models.  The table
+    /// and index are recompiled whenever the models or connectors change.
+    /// The posted configuration is validated, too.
+    fn with_compiled_topology(mut self) -> Self {
+        self.compile_topology();
+        self.configuration_errors = self.validate();
+        self
+    }
+
+    /// This constructor method creates a simulation from a supplied
+    /// configuration (models and connectors), failing if the configuration
+    /// has any validation errors (warnings are allowed).
+    pub fn try_post(
+        models: Vec<Model>,
+        connectors: Vec<Connector>,
+    ) -> Result<Self, SimulationError> {
+        let simulation = Self::post(models, connectors);
+        simulation.configuration_result()?;
+        Ok(simulation)
+    }
+
+    /// The validation errors and warnings of the configuration, as of the
+    /// last `post` or `put`.  Problems found at `post` or `put` do not fail
+    /// the simulation - use `try_post` or `try_put` to reject them.
+    pub fn configuration_errors(&self) -> &[ValidationError] {
+        &self.configuration_errors
+    }
+
+    fn configuration_result(&self) -> Result<(), SimulationError> {
+        let errors: Vec<ValidationError> = self
+            .configuration_errors
+            .iter()
+            .filter(|error| error.is_error())
+            .cloned()
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(SimulationError::InvalidConfiguration(errors))
+        }
+    }
+
+    /// Recompile the topology, and record the changed models in any
+    /// retained history, after the models or connectors change.
+    fn topology_changed(&mut self) {
+        self.compile_topology();
+        if let Some(history) = &mut self.history {
+            if history
+                .record_step(self.services.global_time(), &self.models)
+                .is_err()
+            {
+                self.history = None;
+            }
+        }
+    }
+
+    fn compile_topology(&mut self) {
+        self.routes = Some(RoutingTable::compile(&self.models, &self.connectors));
+        // The first of any duplicate model IDs receives the messages
+        let mut model_indices = HashMap::with_capacity(self.models.len());
+        self.models.iter().enumerate().for_each(|(index, model)| {
+            model_indices.entry(model.id().to_string()).or_insert(index);
+        });
+        self.model_indices = Some(model_indices);
     }
 
     pub fn set_rng(&mut self, rng: impl SimulationRng + 'static) {
-        self.services.global_rng = dyn_rng(rng)
+        self.services.global_rng = dyn_rng(rng);
+        self.services.rng_seed = None;
+        self.audit("Set RNG", String::new);
+    }
+
+    /// Reseed the global random number generator, replacing any
+    /// user-supplied generator with the built-in generator.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.services.set_seed(seed);
+        self.audit("Set Seed", || seed.to_string());
+    }
+
+    /// Set the assignment of random number streams to models.  Under
+    /// per-model streams, the built-in stochastic models draw from their
+    /// own streams, seeded from the global seed (or the default seed, for
+    /// user-supplied generators) and the model ID, rather than from the
+    /// shared global generator.  Models supplied with their own generator
+    /// keep drawing from it.
+    pub fn set_rng_streams(&mut self, rng_streams: RngStreams) {
+        self.services.rng_streams = rng_streams;
+        self.audit("Set RNG Streams", || format!["{:?}", rng_streams]);
+    }
+
+    pub fn rng_streams(&self) -> RngStreams {
+        self.services.rng_streams
+    }
+
+    /// Replace the global random number generator with a generator of a
+    /// user-supplied type, seeded from a `u64` seed.
+    pub fn set_seedable_rng<Rng: SimulationRng + SeedableRng + 'static>(&mut self, seed: u64) {
+        self.services.global_rng = seeded_dyn_rng::<Rng>(seed);
+        self.services.rng_seed = Some(seed);
+        self.audit("Set RNG", || seed.to_string());
     }
 
     /// This method sets the models and connectors of an existing simulation.
     pub fn put(&mut self, models: Vec<Model>, connectors: Vec<Connector>) {
+        self.audit("Put", || {
+            serde_json::to_string(&PutDetail {
+                models: &models,
+                connectors: &connectors,
+            })
+            .unwrap_or_default()
+        });
         self.models = models;
         self.connectors = connectors;
+        self.topology_changed();
+        self.configuration_errors = self.validate();
+    }
+
+    /// This method sets the models and connectors of an existing
+    /// simulation, failing if the configuration has any validation errors.
+    /// The invalid configuration is set regardless, as with `put`.
+    pub fn try_put(
+        &mut self,
+        models: Vec<Model>,
+        connectors: Vec<Connector>,
+    ) -> Result<(), SimulationError> {
+        self.put(models, connectors);
+        self.configuration_result()
+    }
+
+    /// Record a mutation in the audit log, if enabled.  The detail is only
+    /// built when the mutation is recorded.
+    fn audit(&mut self, action: &str, detail: impl FnOnce() -> String) {
+        if let Some(audit_log) = &mut self.audit_log {
+            audit_log.record(AuditRecord {
+                wall_time: wall_clock_time(),
+                simulation_time: self.services.global_time(),
+                action: action.to_string(),
+                detail: detail(),
+            });
+        }
+    }
+
+    /// Enable the audit log, retaining the latest `capacity` records.  An
+    /// enabled audit log is included in serialized simulations.  Enabling
+    /// an already enabled audit log changes its capacity, discarding the
+    /// oldest records beyond the new capacity.
+    pub fn enable_audit_log(&mut self, capacity: usize) {
+        match &mut self.audit_log {
+            Some(audit_log) => audit_log.set_capacity(capacity),
+            None => self.audit_log = Some(AuditLog::new(capacity)),
+        }
+    }
+
+    /// Disable the audit log, discarding any retained records.
+    pub fn disable_audit_log(&mut self) {
+        self.audit_log = None;
+    }
+
+    /// The audit log is the trail of every mutation made through the
+    /// simulation interface - configuration changes, input injections, and
+    /// resets - with wall clock and simulation timestamps.  Interactive
+    /// sessions and experiment scripts can be reviewed and reproduced from
+    /// the audit log.  The audit log is opt-in, through `enable_audit_log`,
+    /// and retains the latest records up to its capacity.
+    pub fn get_audit_log(&self) -> Result<Vec<&AuditRecord>, SimulationError> {
+        Ok(self
+            .audit_log
+            .as_ref()
+            .ok_or(SimulationError::AuditLogUnavailable)?
+            .records()
+            .collect())
     }
 
     /// Simulation steps generate messages, which are then consumed on
     /// subsequent simulation steps.  These messages between models in a
     /// simulation drive much of the discovery, analysis, and design.  This
     /// accessor method provides the list of active messages, at the current
-    /// point of time in the simulation.  Message history is not retained, so
-    /// simulation products and projects should collect messages as needed
-    /// throughout the simulation execution.
+    /// point of time in the simulation.  Message history is not retained by
+    /// default, so simulation products and projects should either collect
+    /// messages as needed throughout the simulation execution, or enable
+    /// the message history.
     pub fn get_messages(&self) -> &Vec<Message> {
         &self.messages
     }
@@ -102,28 +484,305 @@ impl Simulation {
         self.services.global_time()
     }
 
+    /// An accessor method for the seed of the global random number
+    /// generator, if known - the seed is unknown for user-supplied
+    /// generators.
+    pub fn get_rng_seed(&self) -> Option<u64> {
+        self.services.rng_seed()
+    }
+
     /// This method provides a mechanism for getting the status of any model
     /// in a simulation.  The method takes the model ID as an argument, and
     /// returns the current status string for that model.
     pub fn get_status(&self, model_id: &str) -> Result<String, SimulationError> {
+        Ok(self.model(model_id)?.status())
+    }
+
+    /// This method provides the detailed status of a model, with its key
+    /// performance indicators (e.g. processor utilization) as of the
+    /// current simulation time.
+    pub fn get_status_detail(&self, model_id: &str) -> Result<String, SimulationError> {
         Ok(self
-            .models
-            .iter()
-            .find(|model| model.id() == model_id)
-            .ok_or(SimulationError::ModelNotFound)?
-            .status())
+            .model(model_id)?
+            .status_detail(self.services.global_time()))
+    }
+
+    /// An accessor method for the per-port message traffic of a model.
+    /// Models without port statistics (e.g. custom models) provide `None`.
+    pub fn get_port_stats(&self, model_id: &str) -> Result<Option<&PortStats>, SimulationError> {
+        Ok(self.model(model_id)?.port_stats())
+    }
+
+    /// An accessor method for the running departure statistics of a sink,
+    /// as of the current simulation time.  Models other than sinks provide
+    /// `None`.
+    pub fn get_sink_summary(&self, model_id: &str) -> Result<Option<SinkSummary>, SimulationError> {
+        Ok(self
+            .model(model_id)?
+            .sink_summary(self.services.global_time()))
+    }
+
+    /// An accessor method for the utilization of a processor, excluding
+    /// its warm-up period, as of the current simulation time.  Models
+    /// without service provide `None`.
+    pub fn get_utilization(
+        &self,
+        model_id: &str,
+    ) -> Result<Option<UtilizationSummary>, SimulationError> {
+        Ok(self
+            .model(model_id)?
+            .utilization(self.services.global_time()))
     }
 
     /// This method provides a mechanism for getting the records of any model
     /// in a simulation.  The method takes the model ID as an argument, and
     /// returns the records for that model.
     pub fn get_records(&self, model_id: &str) -> Result<&Vec<ModelRecord>, SimulationError> {
-        Ok(self
-            .models
+        Ok(self.model(model_id)?.records())
+    }
+
+    /// Set the record and message detail of a model, at runtime.  Full
+    /// verbosity switches record keeping on, and summary or none verbosity
+    /// switches it off.  Summary verbosity reports the model's messages
+    /// without their correlation IDs and metadata, and none verbosity omits
+    /// the model's messages from the step outputs and message history.
+    pub fn set_verbosity(
+        &mut self,
+        model_id: &str,
+        verbosity: Verbosity,
+    ) -> Result<(), SimulationError> {
+        let index = self.model_index(model_id)?;
+        self.models[index].set_store_records(verbosity == Verbosity::Full);
+        self.audit("Set Verbosity", || {
+            format![
+                "{}: {}",
+                model_id,
+                serde_json::to_string(&verbosity).unwrap_or_default()
+            ]
+        });
+        if verbosity == Verbosity::default() {
+            self.verbosity.remove(model_id);
+        } else {
+            self.verbosity.insert(model_id.to_string(), verbosity);
+        }
+        Ok(())
+    }
+
+    /// The record and message detail of a model.
+    pub fn verbosity(&self, model_id: &str) -> Verbosity {
+        self.verbosity.get(model_id).copied().unwrap_or_default()
+    }
+
+    /// Set the energy coefficients of a model, for energy accounting from
+    /// the current simulation time onward.  Coefficients of zero disable
+    /// the energy accounting of the model.
+    pub fn set_energy_coefficients(
+        &mut self,
+        model_id: &str,
+        coefficients: EnergyCoefficients,
+    ) -> Result<(), SimulationError> {
+        let index = self.model_index(model_id)?;
+        self.energy
+            .track(&self.models[index], self.services.global_time());
+        self.audit("Set Energy Coefficients", || {
+            format![
+                "{}: {}",
+                model_id,
+                serde_json::to_string(&coefficients).unwrap_or_default()
+            ]
+        });
+        if coefficients == EnergyCoefficients::default() {
+            self.energy_coefficients.remove(model_id);
+        } else {
+            self.energy_coefficients
+                .insert(model_id.to_string(), coefficients);
+        }
+        Ok(())
+    }
+
+    /// The energy coefficients of a model.
+    pub fn energy_coefficients(&self, model_id: &str) -> EnergyCoefficients {
+        self.energy_coefficients
+            .get(model_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The energy report of the run so far, with simulation time buckets of
+    /// the provided width - the consumption of each model with energy
+    /// coefficients, from its busy time and messages, and the consumption
+    /// per bucket.
+    pub fn energy_report(&self, bucket_width: f64) -> Result<EnergyReport, SimulationError> {
+        EnergyReport::new(
+            bucket_width,
+            self.services.global_time(),
+            &self.energy_coefficients,
+            self.energy.usage(),
+        )
+    }
+
+    /// Add a declarative check of the simulation outputs.
+    pub fn add_assertion(&mut self, assertion: Assertion) {
+        self.assertions.push(assertion);
+    }
+
+    /// The declarative checks of the simulation outputs, as configured.
+    pub fn assertions(&self) -> &[Assertion] {
+        &self.assertions
+    }
+
+    /// Evaluate the assertions against the simulation outputs so far.
+    pub fn check_assertions(&self) -> Result<AssertionReport, SimulationError> {
+        let results = self
+            .assertions
+            .iter()
+            .map(|assertion| assertion.evaluate(assertion.simulation_points(self)?))
+            .collect::<Result<Vec<AssertionResult>, SimulationError>>()?;
+        Ok(AssertionReport { results })
+    }
+
+    /// Execute simulation steps until a global time has been exceeded, as
+    /// with `step_until`, and then evaluate the assertions - for gating CI
+    /// pipelines on the pass/fail results.
+    pub fn run_assertions_until(&mut self, until: f64) -> Result<AssertionReport, SimulationError> {
+        self.step_until(until)?;
+        self.check_assertions()
+    }
+
+    /// The active messages, as reported under the model verbosities.
+    fn reported_messages(&self) -> Vec<Message> {
+        if self.verbosity.is_empty() {
+            return self.messages.clone();
+        }
+        self.messages
             .iter()
-            .find(|model| model.id() == model_id)
-            .ok_or(SimulationError::ModelNotFound)?
-            .records())
+            .filter_map(|message| self.verbosity(message.source_id()).report(message))
+            .collect()
+    }
+
+    /// This method provides a quick overview of the simulation - model
+    /// counts by type, connector count, random number generator seed,
+    /// global time, active message count, and the status of every model.
+    /// The summary implements `Display` for human-readable output.
+    pub fn summary(&self) -> SimulationSummary {
+        let mut model_counts: BTreeMap<String, usize> = BTreeMap::new();
+        self.models.iter().for_each(|model| {
+            *model_counts
+                .entry(model.model_type().to_string())
+                .or_insert(0) += 1;
+        });
+        SimulationSummary {
+            global_time: self.services.global_time(),
+            rng_seed: self.services.rng_seed(),
+            model_counts,
+            connector_count: self.connectors.len(),
+            message_count: self.messages.len(),
+            models: self
+                .models
+                .iter()
+                .map(|model| ModelSummary {
+                    id: model.id().to_string(),
+                    model_type: model.model_type().to_string(),
+                    status: model.status(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Analyze the simulation topology - fan-in and fan-out per model,
+    /// connectivity, strongly connected components and feedback loops, and
+    /// the longest acyclic path - to gauge model complexity, and find the
+    /// loops where zero-delay cycles may occur, before running.
+    pub fn analyze_topology(&self) -> TopologyReport {
+        topology_analysis::analyze(&self.models, &self.connectors)
+    }
+
+    /// A dry run analyzes the simulation configuration - generator rates,
+    /// service rates, and fan-outs - without executing any transitions.
+    /// The report estimates the event counts and message volumes over the
+    /// estimated duration, identifies the likely bottleneck, and warns of
+    /// configuration problems and overloaded stations (utilization > 1).
+    pub fn dry_run(&self, duration_estimate: f64) -> Result<DryRunReport, SimulationError> {
+        dry_run::dry_run(
+            &self.models,
+            &self.connectors,
+            self.services.globals(),
+            duration_estimate,
+        )
+    }
+
+    /// Encode the simulation - configuration and state - as a versioned
+    /// snapshot, with an integrity checksum and optional compression.
+    pub fn to_snapshot(
+        &self,
+        compression: SnapshotCompression,
+    ) -> Result<Vec<u8>, SimulationError> {
+        snapshot::encode(self, compression)
+    }
+
+    /// Restore a simulation from a snapshot.  The format is auto-detected,
+    /// accepting versioned snapshots, plain simulation YAML or JSON, and raw
+    /// gzip- or zstd-compressed simulation YAML or JSON.
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, SimulationError> {
+        set_panic_hook();
+        snapshot::decode(bytes)
+    }
+
+    /// Write a versioned snapshot of the simulation to a file.
+    pub fn save_snapshot<P: AsRef<Path>>(
+        &self,
+        path: P,
+        compression: SnapshotCompression,
+    ) -> Result<(), SimulationError> {
+        fs::write(path, self.to_snapshot(compression)?)?;
+        Ok(())
+    }
+
+    /// Restore a simulation from a snapshot file, auto-detecting the
+    /// format.
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> Result<Self, SimulationError> {
+        Self::from_snapshot(&fs::read(path)?)
+    }
+
+    /// Checkpoint the complete simulation state, including the state of the
+    /// global random number generator, for resuming execution later with
+    /// `restore`.
+    pub fn checkpoint(&self) -> Result<Checkpoint, SimulationError> {
+        Checkpoint::new(self)
+    }
+
+    /// Restore a simulation from a checkpoint, resuming execution exactly
+    /// where the checkpointed simulation left off.
+    pub fn restore(checkpoint: &Checkpoint) -> Result<Self, SimulationError> {
+        set_panic_hook();
+        checkpoint.restore()
+    }
+
+    /// Write a checkpoint of the simulation to a file.
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<(), SimulationError> {
+        fs::write(path, self.checkpoint()?.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Restore a simulation from a checkpoint file.
+    pub fn load_checkpoint<P: AsRef<Path>>(path: P) -> Result<Self, SimulationError> {
+        Self::restore(&Checkpoint::from_bytes(&fs::read(path)?)?)
+    }
+
+    /// Create a simulation from YAML (or JSON) model and connector
+    /// configuration files.  The files support environment variable
+    /// interpolation (`${NAME}`), includes (`!include other.yaml`), and
+    /// merge keys - see `utils::yaml`.  Model configurations may include
+    /// template instances, whose models and connectors are added to the
+    /// configured models and connectors - see `simulator::template`.
+    pub fn load_config<P: AsRef<Path>>(
+        models_path: P,
+        connectors_path: P,
+    ) -> Result<Self, SimulationError> {
+        let entries: Vec<ModelEntry> = yaml::from_file(models_path)?;
+        let (models, mut connectors) = split_entries(entries);
+        connectors.extend(yaml::from_file::<Vec<Connector>, _>(connectors_path)?);
+        Ok(Self::post(models, connectors))
     }
 
     /// To enable simulation replications, the reset method resets the state
@@ -131,18 +790,744 @@ impl Simulation {
     /// Recreating a simulation from scratch for additional replications
     /// does not work, due to the random number generator seeding.
     pub fn reset(&mut self) {
-        self.reset_messages();
-        self.reset_global_time();
+        self.audit("Reset", String::new);
+        self.messages = Vec::new();
+        self.injected_count = 0;
+        self.scheduled_inputs = Vec::new();
+        self.services.set_global_time(0.0);
+        self.services.blackboard = Blackboard::default();
+        self.correlations = CorrelationTracker::default();
+        self.execution.clear();
+        self.energy.clear();
+        self.restart_history();
     }
 
     /// Clear the active messages in a simulation.
     pub fn reset_messages(&mut self) {
+        self.audit("Reset Messages", String::new);
         self.messages = Vec::new();
+        self.injected_count = 0;
+    }
+
+    /// Set the delivery order of injected messages, relative to the
+    /// model-generated messages delivered in the same step.  By default,
+    /// injected messages are delivered after the model-generated messages.
+    pub fn set_injection_priority(&mut self, priority: InjectionPriority) {
+        self.audit("Set Injection Priority", || {
+            serde_json::to_string(&priority).unwrap_or_default()
+        });
+        self.injection_priority = priority;
+    }
+
+    pub fn injection_priority(&self) -> InjectionPriority {
+        self.injection_priority
+    }
+
+    /// Set the strategy for finding the imminent models of each step.
+    /// Under future event list scheduling, the models' next event times
+    /// are kept in a binary heap, and idle models are advanced lazily - so
+    /// each step touches only the imminent models and the message targets.
+    /// The event list is built at the start of each `step`, `step_n`, or
+    /// `step_until` call, and the models are synchronized to the global
+    /// time at the end of the call, so batched stepping (`step_n` and
+    /// `step_until`) benefits most.  Models are advanced by the elapsed
+    /// time since their last event, rather than step by step, so the
+    /// simulation times may differ from scanning in the last bits of
+    /// floating point precision.  State transitions are serial under future
+    /// event list scheduling, even with parallel execution enabled.
+    pub fn set_event_scheduling(&mut self, scheduling: EventScheduling) {
+        self.audit("Set Event Scheduling", || {
+            serde_json::to_string(&scheduling).unwrap_or_default()
+        });
+        self.event_scheduling = scheduling;
+    }
+
+    pub fn event_scheduling(&self) -> EventScheduling {
+        self.event_scheduling
     }
 
     /// Reset the simulation global time to 0.0.
     pub fn reset_global_time(&mut self) {
+        self.audit("Reset Global Time", String::new);
         self.services.set_global_time(0.0);
+        self.restart_history();
+    }
+
+    /// Set the queue depth threshold for watermark alerts.  A warning is
+    /// recorded in the execution stats the first time each model's queue
+    /// depth exceeds the threshold.  A threshold of `None` disables alerts.
+    pub fn set_queue_depth_threshold(&mut self, threshold: Option<usize>) {
+        self.execution.threshold = threshold;
+    }
+
+    /// The execution statistics of the run so far - the step count, the
+    /// wall time spent stepping, the queue depth high-watermarks of each
+    /// model reporting a queue depth (through `Reportable::queue_depth`),
+    /// and any threshold alerts.
+    pub fn execution_stats(&self) -> &ExecutionStats {
+        &self.execution.stats
+    }
+
+    /// The event density report of the run so far, with simulation time
+    /// buckets of the provided width - the event counts (external and
+    /// internal) per bucket and per model, the bursts and idle spans, and
+    /// the wall time per bucket.
+    pub fn event_density(&self, bucket_width: f64) -> Result<EventDensityReport, SimulationError> {
+        EventDensityReport::new(
+            bucket_width,
+            self.services.global_time(),
+            &self.execution.events,
+            &self.execution.step_timings,
+        )
+    }
+
+    /// The reproducibility manifest of the run so far - the engine version
+    /// and features, the seed, the configuration hash, and the execution
+    /// extent.
+    pub fn manifest(&self) -> Result<RunManifest, SimulationError> {
+        Ok(RunManifest::new(
+            self.services.rng_seed(),
+            manifest::config_hash(&self.models, &self.connectors, self.services.globals())?,
+            self.services.global_time(),
+            self.execution.stats.steps,
+            self.execution.stats.wall_time,
+        ))
+    }
+
+    /// Enable the retention of simulation history - every message, and the
+    /// model states over time - for time-travel queries over a run.  Full
+    /// model states are checkpointed every `checkpoint_interval` steps, with
+    /// only the per-step state changes retained in between.  The messages
+    /// are retained as reported under the model verbosities.
+    pub fn enable_history(&mut self, checkpoint_interval: usize) -> Result<(), SimulationError> {
+        self.history = Some(History::new(
+            checkpoint_interval,
+            self.services.global_time(),
+            &self.models,
+        )?);
+        Ok(())
+    }
+
+    /// Disable history retention, discarding any retained history.
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    fn restart_history(&mut self) {
+        if let Some(history) = &self.history {
+            self.history = history.restart(0.0, &self.models).ok();
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
+    }
+
+    /// Enable the tracing of every model state transition, for export as
+    /// Chrome `trace_event` JSON or OpenTelemetry spans.
+    pub fn enable_tracing(&mut self) {
+        if self.trace.is_none() {
+            self.trace = Some(Trace::default());
+        }
+    }
+
+    /// Disable tracing, discarding any recorded transitions.
+    pub fn disable_tracing(&mut self) {
+        self.trace = None;
+    }
+
+    /// The recorded transitions of the simulation.
+    pub fn get_trace(&self) -> Result<&Trace, SimulationError> {
+        self.trace.as_ref().ok_or(SimulationError::TraceUnavailable)
+    }
+
+    /// The recorded transitions, as Chrome `trace_event` JSON, with the
+    /// open spans ending at the current simulation time.
+    pub fn chrome_trace(&self) -> Result<serde_json::Value, SimulationError> {
+        Ok(self
+            .get_trace()?
+            .to_chrome_trace(self.services.global_time()))
+    }
+
+    /// The recorded transitions, as OpenTelemetry spans (OTLP/JSON) of the
+    /// trace ID, with the open spans ending at the current simulation time.
+    pub fn otlp_trace(&self, trace_id: u128) -> Result<serde_json::Value, SimulationError> {
+        Ok(self
+            .get_trace()?
+            .to_otlp(self.services.global_time(), trace_id))
+    }
+
+    /// Enable the retention of every message - the routed messages of each
+    /// step, and the injected messages - for querying after a run, without
+    /// the model state retention of `enable_history`.  The messages are
+    /// retained as reported under the model verbosities.  With history
+    /// enabled, the messages are already retained.
+    pub fn enable_message_history(&mut self) {
+        if self.history.is_none() {
+            self.history = Some(History::messages_only(self.services.global_time()));
+        }
+    }
+
+    /// Disable message retention.  The messages are retained by the
+    /// simulation history, so, as with `disable_history`, any retained
+    /// history is discarded.
+    pub fn disable_message_history(&mut self) {
+        self.disable_history();
+    }
+
+    fn message_history(&self) -> Result<&History, SimulationError> {
+        self.history
+            .as_ref()
+            .ok_or(SimulationError::MessageHistoryUnavailable)
+    }
+
+    /// The retained messages, in delivery order.
+    pub fn get_message_history(&self) -> Result<&[Message], SimulationError> {
+        Ok(self.message_history()?.messages())
+    }
+
+    /// The retained messages matching a filter, in delivery order.
+    pub fn query_messages(&self, filter: &MessageFilter) -> Result<Vec<&Message>, SimulationError> {
+        Ok(self.message_history()?.query(filter))
+    }
+
+    /// This method provides the retained messages with a transmission time
+    /// between `start` and `end`, inclusive.
+    pub fn messages_between(&self, start: f64, end: f64) -> Result<Vec<&Message>, SimulationError> {
+        Ok(self.message_history()?.messages_between(start, end))
+    }
+
+    /// This method provides the retained messages sent or received by a
+    /// model.
+    pub fn messages_for_model(&self, model_id: &str) -> Result<Vec<&Message>, SimulationError> {
+        Ok(self.message_history()?.messages_for_model(model_id))
+    }
+
+    /// This method reconstructs the serialized models, as they were at a
+    /// point in time, from the retained history.  The models are keyed by
+    /// model ID.
+    pub fn state_at(
+        &self,
+        time: f64,
+    ) -> Result<BTreeMap<String, serde_json::Value>, SimulationError> {
+        self.history
+            .as_ref()
+            .ok_or(SimulationError::HistoryUnavailable)?
+            .state_at(time)
+    }
+
+    /// Enable the recording of every random variate drawn by the models,
+    /// retaining up to `capacity` of the most recent variates.  The records
+    /// support post-hoc input uncertainty and sensitivity analysis, such as
+    /// correlating long waits with specific large service time draws.
+    pub fn enable_variate_recording(&mut self, capacity: usize) {
+        self.services.variate_log = Some(VariateLog::new(capacity));
+    }
+
+    /// Disable random variate recording, discarding any retained records.
+    pub fn disable_variate_recording(&mut self) {
+        self.services.variate_log = None;
+    }
+
+    /// Enable deterministic mode, where models replace random variates with
+    /// their distribution means (where defined).  Deterministic mode
+    /// supports debugging of model wiring and logic with perfectly
+    /// predictable timings, before re-enabling stochastic behavior.
+    pub fn enable_deterministic_mode(&mut self) {
+        self.services.deterministic_mode = true;
+    }
+
+    /// Disable deterministic mode, restoring stochastic behavior.
+    pub fn disable_deterministic_mode(&mut self) {
+        self.services.deterministic_mode = false;
+    }
+
+    /// Enable antithetic variates, where every random number drawn from the
+    /// global generator or a model stream is replaced by its complement.
+    /// Running a replication both with and without antithetic variates,
+    /// from the same seed, gives a negatively correlated pair of runs.
+    /// Models supplied with their own generator are unaffected.
+    pub fn enable_antithetic_variates(&mut self) {
+        self.services.antithetic = true;
+    }
+
+    pub fn disable_antithetic_variates(&mut self) {
+        self.services.antithetic = false;
+    }
+
+    /// Set a named global variable, which distribution parameters may
+    /// reference as `"$name"`.  References are resolved at sampling time,
+    /// so the new value applies to all subsequent draws, across every
+    /// referencing model.
+    pub fn set_global(&mut self, name: &str, value: f64) {
+        self.audit("Set Global", || format!["{}={}", name, value]);
+        self.services.globals.set(name, value);
+    }
+
+    /// Set a single configuration field of a model, identified by a JSON
+    /// pointer into the model's serialized configuration (e.g.
+    /// `/serviceTime/exp/lambda`), without re-posting the simulation.  The
+    /// patched configuration is validated by re-instantiating the model,
+    /// and only that model is replaced.  The model state is migrated, as
+    /// with `replace_model`.  The model ID, type, and state are not
+    /// settable parameters.
+    pub fn set_parameter(
+        &mut self,
+        model_id: &str,
+        pointer: &str,
+        value: serde_json::Value,
+    ) -> Result<(), SimulationError> {
+        let index = self.model_index(model_id)?;
+        let model = patched_model(&self.models[index], pointer, value.clone())?;
+        self.swap_model(index, model)?;
+        self.audit("Set Parameter", || {
+            format!["{}{}={}", model_id, pointer, value]
+        });
+        Ok(())
+    }
+
+    /// Replace the model of the same ID mid-run (e.g. with a different
+    /// configuration, or a different model type), returning the previous
+    /// model.  The dynamic state of the previous model is migrated to the
+    /// replacement through `DevsModel::migrate_state` - by default, the
+    /// replacement keeps its own state, while models such as the processor
+    /// keep their queue contents.
+    pub fn replace_model(&mut self, model: Model) -> Result<Model, SimulationError> {
+        let index = self.model_index(model.id())?;
+        // The replacement is recorded as requested, before any state
+        // migration, but only once the replacement succeeds
+        let detail = self
+            .audit_log
+            .is_some()
+            .then(|| serde_json::to_string(&model).unwrap_or_default());
+        let previous = self.swap_model(index, model)?;
+        if let Some(detail) = detail {
+            self.audit("Replace Model", || detail);
+        }
+        Ok(previous)
+    }
+
+    /// Add a model mid-run (e.g. a server of an autoscaling pool).  The
+    /// model ID must be unique, and the model joins the simulation at the
+    /// current simulation time.
+    pub fn add_model(&mut self, model: Model) -> Result<(), SimulationError> {
+        if self.model_index(model.id()).is_ok() {
+            return Err(SimulationError::DuplicateModelId(model.id().to_string()));
+        }
+        self.quotas.check_models(self.models.len() + 1)?;
+        self.audit("Add Model", || {
+            serde_json::to_string(&model).unwrap_or_default()
+        });
+        self.models.push(model);
+        self.topology_changed();
+        Ok(())
+    }
+
+    /// Add `count` instances of the template, numbered from 1, with the
+    /// template parameters - returning the IDs of the added models.
+    pub fn instantiate_template(
+        &mut self,
+        template: &ModelTemplate,
+        count: usize,
+    ) -> Result<Vec<String>, SimulationError> {
+        self.add_template_instances(&TemplateInstances {
+            template: template.clone(),
+            count,
+            parameters: Vec::new(),
+        })
+    }
+
+    /// Add the template instances, with their per-instance parameters -
+    /// returning the IDs of the added models.  The instances are added
+    /// together, or not at all, when any instance model or connector ID is
+    /// already in the simulation, or any connector references a missing
+    /// model.
+    pub fn add_template_instances(
+        &mut self,
+        instances: &TemplateInstances,
+    ) -> Result<Vec<String>, SimulationError> {
+        let (models, connectors) = instances.expand()?;
+        let mut model_ids: HashSet<&str> = self.models.iter().map(|model| model.id()).collect();
+        if let Some(model) = models.iter().find(|model| !model_ids.insert(model.id())) {
+            return Err(SimulationError::DuplicateModelId(model.id().to_string()));
+        }
+        let mut connector_ids: HashSet<&str> = self
+            .connectors
+            .iter()
+            .map(|connector| connector.id())
+            .collect();
+        if let Some(connector) = connectors
+            .iter()
+            .find(|connector| !connector_ids.insert(connector.id()))
+        {
+            return Err(SimulationError::DuplicateConnectorId(
+                connector.id().to_string(),
+            ));
+        }
+        for connector in &connectors {
+            if let Some(model_id) = [connector.source_id(), connector.target_id()]
+                .iter()
+                .find(|model_id| !model_ids.iter().any(|id| matches(model_id, id)))
+            {
+                return Err(SimulationError::DanglingConnector {
+                    connector_id: connector.id().to_string(),
+                    model_id: model_id.to_string(),
+                });
+            }
+        }
+        self.quotas.check_models(self.models.len() + models.len())?;
+        self.audit("Add Template Instances", || {
+            serde_json::to_string(instances).unwrap_or_default()
+        });
+        let added: Vec<String> = models.iter().map(|model| model.id().to_string()).collect();
+        self.models.extend(models);
+        self.connectors.extend(connectors);
+        self.topology_changed();
+        Ok(added)
+    }
+
+    /// Remove a model mid-run, along with the connectors to and from the
+    /// model (connectors with model ID patterns are kept).  In-flight and
+    /// scheduled messages addressed to the model are not delivered, and
+    /// are returned with the removed model - e.g. for re-injection into
+    /// another model.  The verbosity and energy coefficients of the model
+    /// are discarded.
+    pub fn remove_model(&mut self, model_id: &str) -> Result<RemovedModel, SimulationError> {
+        let index = self.model_index(model_id)?;
+        self.audit("Remove Model", || model_id.to_string());
+        let model = self.models.remove(index);
+        let (connectors, kept_connectors) = std::mem::take(&mut self.connectors)
+            .into_iter()
+            .partition(|connector| {
+                connector.source_id() == model_id || connector.target_id() == model_id
+            });
+        self.connectors = kept_connectors;
+        let removed_injections = self.messages[..self.injected_count]
+            .iter()
+            .filter(|message| message.target_id() == model_id)
+            .count();
+        self.injected_count -= removed_injections;
+        let (mut undelivered, messages): (Vec<Message>, Vec<Message>) =
+            std::mem::take(&mut self.messages)
+                .into_iter()
+                .partition(|message| message.target_id() == model_id);
+        self.messages = messages;
+        let (scheduled, scheduled_inputs): (Vec<Message>, Vec<Message>) =
+            std::mem::take(&mut self.scheduled_inputs)
+                .into_iter()
+                .partition(|message| message.target_id() == model_id);
+        self.scheduled_inputs = scheduled_inputs;
+        undelivered.extend(scheduled);
+        self.verbosity.remove(model_id);
+        self.energy_coefficients.remove(model_id);
+        self.energy.untrack(model_id);
+        self.topology_changed();
+        Ok(RemovedModel {
+            model,
+            connectors,
+            undelivered,
+        })
+    }
+
+    /// Add a connector mid-run.  The connector ID must be unique, and the
+    /// source and target model IDs must reference existing models (or, for
+    /// patterns, match at least one model).
+    pub fn add_connector(&mut self, connector: Connector) -> Result<(), SimulationError> {
+        if self
+            .connectors
+            .iter()
+            .any(|existing| existing.id() == connector.id())
+        {
+            return Err(SimulationError::DuplicateConnectorId(
+                connector.id().to_string(),
+            ));
+        }
+        if let Some(model_id) =
+            [connector.source_id(), connector.target_id()]
+                .iter()
+                .find(|model_id| {
+                    !self
+                        .models
+                        .iter()
+                        .any(|model| matches(model_id, model.id()))
+                })
+        {
+            return Err(SimulationError::DanglingConnector {
+                connector_id: connector.id().to_string(),
+                model_id: model_id.to_string(),
+            });
+        }
+        self.audit("Add Connector", || {
+            serde_json::to_string(&connector).unwrap_or_default()
+        });
+        self.connectors.push(connector);
+        self.topology_changed();
+        Ok(())
+    }
+
+    /// Remove a connector mid-run, returning the removed connector.
+    /// Messages already routed through the connector are still delivered.
+    pub fn remove_connector(&mut self, connector_id: &str) -> Result<Connector, SimulationError> {
+        let index = self
+            .connectors
+            .iter()
+            .position(|connector| connector.id() == connector_id)
+            .ok_or_else(|| SimulationError::ConnectorNotFound(connector_id.to_string()))?;
+        self.audit("Remove Connector", || connector_id.to_string());
+        let connector = self.connectors.remove(index);
+        self.topology_changed();
+        Ok(connector)
+    }
+
+    /// Open an edit transaction, staging topology changes on a copy of the
+    /// models and connectors.  Only one transaction may be open at a time.
+    pub fn begin_edit(&mut self) -> Result<(), SimulationError> {
+        if self.edit.is_some() {
+            return Err(SimulationError::EditInProgress);
+        }
+        self.edit = Some(EditTransaction::new(&self.models, &self.connectors));
+        Ok(())
+    }
+
+    /// Whether an edit transaction is open.
+    pub fn edit_in_progress(&self) -> bool {
+        self.edit.is_some()
+    }
+
+    /// Apply an operation to the staged topology of the open edit
+    /// transaction.  The live simulation is unchanged until the commit.
+    pub fn apply_edit(&mut self, operation: EditOperation) -> Result<(), SimulationError> {
+        self.edit
+            .as_mut()
+            .ok_or(SimulationError::NoEditInProgress)?
+            .apply(operation)
+    }
+
+    /// Validate the assembled configuration of the simulation, against its
+    /// in-flight and scheduled messages, returning the errors and warnings.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        self.validate_with(&ValidationOptions::default()).errors
+    }
+
+    /// Validate the assembled topology of the simulation, with the optional
+    /// parts of the report (e.g. the assumptions of the simulation and its
+    /// models).
+    pub fn validate_with(&self, options: &ValidationOptions) -> ValidationReport {
+        let pending_messages: Vec<&Message> = self
+            .messages
+            .iter()
+            .chain(self.scheduled_inputs.iter())
+            .collect();
+        ValidationReport {
+            errors: validation_errors(&self.models, &self.connectors, &pending_messages),
+            assumptions: if options.assumptions {
+                Some(assumptions_report(&self.models, self.services.rng_streams))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Validate the staged topology of the open edit transaction, against
+    /// the in-flight and scheduled messages of the live simulation.
+    pub fn validate_edit(&self) -> Result<(), SimulationError> {
+        let edit = self
+            .edit
+            .as_ref()
+            .ok_or(SimulationError::NoEditInProgress)?;
+        let pending_messages: Vec<&Message> = self
+            .messages
+            .iter()
+            .chain(self.scheduled_inputs.iter())
+            .collect();
+        let issues = edit.issues(&pending_messages);
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(SimulationError::InvalidEdit(issues))
+        }
+    }
+
+    /// Validate and commit the open edit transaction, replacing the live
+    /// topology with the staged topology.  An invalid transaction is not
+    /// committed, and remains open.
+    pub fn commit_edit(&mut self) -> Result<(), SimulationError> {
+        self.validate_edit()?;
+        if let Some(edit) = &self.edit {
+            self.quotas.check_models(edit.models.len())?;
+        }
+        let edit = self.edit.take().ok_or(SimulationError::NoEditInProgress)?;
+        self.audit("Commit Edit", || {
+            serde_json::to_string(&edit.operations).unwrap_or_default()
+        });
+        self.models = edit.models;
+        self.connectors = edit.connectors;
+        self.topology_changed();
+        Ok(())
+    }
+
+    /// Discard the open edit transaction, leaving the live topology
+    /// unchanged.
+    pub fn rollback_edit(&mut self) -> Result<(), SimulationError> {
+        self.edit
+            .take()
+            .map(|_| ())
+            .ok_or(SimulationError::NoEditInProgress)
+    }
+
+    /// Set the resource quotas of the simulation, for hosted deployments
+    /// running untrusted configurations.  The quotas are set either way,
+    /// and the model count and memory quotas are checked immediately, so an
+    /// oversized configuration is reported before any steps.  Every step
+    /// request checks the quotas again (e.g. after a `put`), and committed
+    /// edits may not exceed the model count quota.
+    pub fn set_quotas(&mut self, quotas: Quotas) -> Result<(), SimulationError> {
+        self.audit("Set Quotas", || {
+            serde_json::to_string(&quotas).unwrap_or_default()
+        });
+        self.quotas = quotas;
+        self.check_quotas()
+    }
+
+    pub fn quotas(&self) -> Quotas {
+        self.quotas
+    }
+
+    /// Check the model count and memory quotas against the current
+    /// simulation.
+    pub fn check_quotas(&self) -> Result<(), SimulationError> {
+        self.quotas.check_models(self.models.len())?;
+        self.check_memory_quota()
+    }
+
+    fn check_memory_quota(&self) -> Result<(), SimulationError> {
+        if self.quotas.max_memory_bytes.is_some() {
+            self.quotas.check_memory(self.memory_estimate()?)?;
+        }
+        Ok(())
+    }
+
+    /// The estimated memory of the simulation, in bytes - the serialized
+    /// size of the models, the in-flight and scheduled messages, and any
+    /// message history.
+    pub fn memory_estimate(&self) -> Result<usize, SimulationError> {
+        memory_estimate(
+            &self.models,
+            self.messages
+                .iter()
+                .chain(self.scheduled_inputs.iter())
+                .chain(
+                    self.history
+                        .iter()
+                        .flat_map(|history| history.messages().iter()),
+                ),
+        )
+    }
+
+    fn model_index(&self, model_id: &str) -> Result<usize, SimulationError> {
+        match &self.model_indices {
+            Some(model_indices) => model_indices.get(model_id).copied(),
+            None => self.models.iter().position(|model| model.id() == model_id),
+        }
+        .ok_or_else(|| SimulationError::ModelNotFound(model_id.to_string()))
+    }
+
+    /// The model of the ID - or, for a path of model IDs separated by `/`
+    /// (e.g. `line-01/processor-01`), the component nested in coupled
+    /// models.
+    fn model(&self, model_id: &str) -> Result<&Model, SimulationError> {
+        if let Ok(model_index) = self.model_index(model_id) {
+            return Ok(&self.models[model_index]);
+        }
+        let mut path = model_id.split(COMPONENT_PATH_SEPARATOR);
+        let root = path.next().unwrap_or_default();
+        let not_found = || SimulationError::ModelNotFound(model_id.to_string());
+        let mut model = &self.models[self.model_index(root).map_err(|_| not_found())?];
+        for component_id in path {
+            model = model
+                .components()
+                .iter()
+                .find(|component| component.id() == component_id)
+                .ok_or_else(not_found)?;
+        }
+        Ok(model)
+    }
+
+    fn swap_model(&mut self, index: usize, mut model: Model) -> Result<Model, SimulationError> {
+        model.migrate_state(&serde_yaml::to_value(&self.models[index])?)?;
+        Ok(std::mem::replace(&mut self.models[index], model))
+    }
+
+    /// Remove a named global variable, returning the removed value.
+    pub fn remove_global(&mut self, name: &str) -> Option<f64> {
+        self.audit("Remove Global", || name.to_string());
+        self.services.globals.remove(name)
+    }
+
+    /// An accessor method for the value of a named global variable.
+    pub fn get_global(&self, name: &str) -> Option<f64> {
+        self.services.globals().get(name)
+    }
+
+    /// An accessor method for all global variables of the simulation.
+    pub fn get_globals(&self) -> &Globals {
+        self.services.globals()
+    }
+
+    /// An accessor method for the shared blackboard, which models read and
+    /// write during their state transitions.
+    pub fn get_blackboard(&self) -> &Blackboard {
+        self.services.blackboard()
+    }
+
+    /// Write a blackboard entry from outside the simulation (e.g. by an
+    /// external controller), timestamped with the current simulation time.
+    pub fn write_blackboard(&mut self, key: &str, value: impl Into<BlackboardValue>) {
+        let value = value.into();
+        self.audit("Write Blackboard", || {
+            format![
+                "{}={}",
+                key,
+                serde_json::to_string(&value).unwrap_or_default()
+            ]
+        });
+        self.services.write_blackboard(key, value);
+    }
+
+    /// An accessor method for the recorded random variates, from oldest to
+    /// newest.  The list is empty if variate recording is not enabled.
+    pub fn get_variate_records(&self) -> Vec<&VariateRecord> {
+        self.services
+            .variate_log
+            .iter()
+            .flat_map(|variate_log| variate_log.records().iter())
+            .collect()
+    }
+
+    /// Enable the state diff debugging mode, where the serialized state of
+    /// every model is captured before and after each simulation step.  The
+    /// snapshots are costly for large simulations, so this mode is intended
+    /// for debugging model transitions.
+    pub fn enable_state_diffs(&mut self) {
+        self.state_snapshots = Some(StateSnapshots::default());
+    }
+
+    /// Disable the state diff debugging mode, discarding any snapshots.
+    pub fn disable_state_diffs(&mut self) {
+        self.state_snapshots = None;
+    }
+
+    /// This method provides the structural differences in a model's
+    /// serialized state, across the most recent simulation step - exactly
+    /// which fields the step's transitions changed.  The list is empty if
+    /// the model did not change, or if the state diff debugging mode is not
+    /// enabled.
+    pub fn diff_last_step(&self, model_id: &str) -> Result<Vec<StateChange>, SimulationError> {
+        self.model_index(model_id)?;
+        Ok(self
+            .state_snapshots
+            .as_ref()
+            .map(|snapshots| snapshots.diff(model_id))
+            .unwrap_or_default())
     }
 
     /// This method provides a convenient foundation for operating on the
@@ -151,45 +1536,383 @@ impl Simulation {
         self.models.iter_mut().collect()
     }
 
-    /// This method constructs a list of target IDs for a given source model
-    /// ID and port.  This message target information is derived from the
-    /// connectors configuration.
-    fn get_message_target_ids(&self, source_id: &str, source_port: &str) -> Vec<String> {
-        self.connectors
-            .iter()
-            .filter_map(|connector| {
-                if connector.source_id() == source_id && connector.source_port() == source_port {
-                    Some(connector.target_id().to_string())
-                } else {
-                    None
-                }
-            })
-            .collect()
+    /// This method constructs a list of target IDs and ports for a given
+    /// source model ID and port.  This message target information is derived
+    /// from the connectors configuration, through the compiled routing table.
+    fn get_message_targets(&self, source_id: &str, source_port: &str) -> Vec<(String, String)> {
+        match &self.routes {
+            Some(routes) => routes.targets(source_id, source_port),
+            None => RoutingTable::compile(&self.models, &self.connectors)
+                .targets(source_id, source_port),
+        }
     }
 
-    /// This method constructs a list of target ports for a given source model
-    /// ID and port.  This message target information is derived from the
-    /// connectors configuration.
-    fn get_message_target_ports(&self, source_id: &str, source_port: &str) -> Vec<String> {
-        self.connectors
-            .iter()
-            .filter_map(|connector| {
-                if connector.source_id() == source_id && connector.source_port() == source_port {
-                    Some(connector.target_port().to_string())
-                } else {
-                    None
+    /// Subscribe to a kind of simulation event.  Events are delivered over
+    /// the channel as they occur, during simulation stepping and input
+    /// injection.  Dropping the receiver ends the subscription.
+    /// Subscriptions are not carried over to clones of the simulation.
+    pub fn subscribe(&mut self, kind: EventKind, sender: Sender<SimulationEvent>) {
+        self.subscriptions.subscribe(kind, sender);
+    }
+
+    /// Attach an observer of the step lifecycle.  Observers are called
+    /// synchronously during simulation stepping, in attachment order, and
+    /// are not carried over to clones of the simulation.
+    pub fn attach_observer(&mut self, observer: Box<dyn SimulationObserver>) {
+        self.observers.attach(observer);
+    }
+
+    /// Detach and return the attached observers.
+    pub fn detach_observers(&mut self) -> Vec<Box<dyn SimulationObserver>> {
+        self.observers.detach()
+    }
+
+    /// Release the correlation contexts of the jobs consumed or dropped by
+    /// their target models on arrival.
+    fn release_unheld_jobs(&mut self) {
+        let mut correlations = std::mem::take(&mut self.correlations);
+        correlations.release_unheld(|model_id| {
+            self.model_index(model_id)
+                .map(|model_index| self.models[model_index].held_jobs())
+                .unwrap_or_default()
+        });
+        self.correlations = correlations;
+    }
+
+    /// Notify the observers, and the trace, of the message deliveries and
+    /// external transitions of a step - the messages with target models
+    /// are delivered, and each target model transitions once.
+    fn observe_deliveries(&mut self, messages: &[Message]) {
+        if self.observers.is_empty() && self.trace.is_none() {
+            return;
+        }
+        let time = self.services.global_time();
+        let mut target_ports: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        messages.iter().for_each(|message| {
+            if let Ok(model_index) = self.model_index(message.target_id()) {
+                let ports = target_ports.entry(model_index).or_default();
+                if !ports.iter().any(|port| port == message.target_port()) {
+                    ports.push(message.target_port().to_string());
                 }
-            })
-            .collect()
+                self.observers
+                    .notify(|observer| observer.on_message_delivered(message));
+            }
+        });
+        target_ports.into_iter().for_each(|(model_index, ports)| {
+            let model = &self.models[model_index];
+            self.observers.notify(|observer| {
+                observer.on_model_transition(model.id(), TransitionKind::External, time)
+            });
+            if let Some(trace) = &mut self.trace {
+                trace.record(TraceEvent {
+                    time,
+                    model_id: model.id().to_string(),
+                    kind: TransitionKind::External,
+                    ports,
+                    status: model.status(),
+                });
+            }
+        });
+    }
+
+    /// The simulation time of the next step's events - the current time,
+    /// when messages are pending, and otherwise the time of the earliest
+    /// scheduled internal event or input injection.
+    pub fn next_event_time(&self) -> f64 {
+        self.services.global_time() + self.until_next_event()
+    }
+
+    /// The time until the next step's events, considering the internal
+    /// events of the models and the scheduled input injections.
+    fn until_next_event(&self) -> f64 {
+        if !self.messages.is_empty() {
+            return 0.0;
+        }
+        let until_next_injection = self
+            .scheduled_inputs
+            .first()
+            .map_or(f64::INFINITY, |message| {
+                f64::max(0.0, message.time() - self.services.global_time())
+            });
+        self.models.iter().fold(until_next_injection, |min, model| {
+            f64::min(min, model.until_next_event())
+        })
     }
 
     /// Input injection creates a message during simulation execution,
     /// without needing to create that message through the standard
     /// simulation constructs.  This enables live simulation interaction,
     /// disruption, and manipulation - all through the standard simulation
-    /// message system.
+    /// message system.  Injected messages may carry a correlation ID, which
+    /// is then propagated to all the resulting downstream messages.  The
+    /// injected message is delivered in the next step, ordered among the
+    /// model-generated messages by the injection priority.
     pub fn inject_input(&mut self, message: Message) {
-        self.messages.push(message);
+        self.audit("Inject Input", || {
+            serde_json::to_string(&message).unwrap_or_default()
+        });
+        if self.history.is_some() {
+            let reported = self.verbosity(message.source_id()).report(&message);
+            if let Some(history) = &mut self.history {
+                history.record_messages(reported.as_slice());
+            }
+        }
+        if self.subscriptions.wants(EventKind::InputInjected) {
+            self.subscriptions
+                .publish(SimulationEvent::InputInjected(message.clone()));
+        }
+        self.enqueue_injection(message);
+    }
+
+    /// Add an injected message to the active messages, at the position
+    /// given by the injection priority.
+    fn enqueue_injection(&mut self, message: Message) {
+        match self.injection_priority {
+            InjectionPriority::Generated => self.messages.push(message),
+            InjectionPriority::Injected => {
+                self.messages.insert(self.injected_count, message);
+                self.injected_count += 1;
+            }
+            InjectionPriority::Timestamp => {
+                let index = self
+                    .messages
+                    .partition_point(|active| active.time() <= message.time());
+                self.messages.insert(index, message);
+            }
+        }
+    }
+
+    /// Check that every model schedules its next event at a non-negative
+    /// time advance (or never, with an infinite time advance), so a faulty
+    /// model cannot move the simulation clock backwards or to NaN.
+    fn validate_time_advances(&self) -> Result<(), SimulationError> {
+        match self.models.iter().find(|model| {
+            let until_next_event = model.until_next_event();
+            until_next_event.is_nan() || until_next_event < 0.0
+        }) {
+            Some(model) => Err(SimulationError::InvalidTimeAdvance {
+                model_id: model.id().to_string(),
+                value: model.until_next_event(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Schedule an input injection at a future simulation time, for
+    /// scenario scripts and external feeds queueing inputs ahead of time.
+    /// The message is injected when the simulation reaches the scheduled
+    /// time, and delivered to the target model at that time, as with
+    /// `inject_input`.  Injections scheduled before the current simulation
+    /// time are rejected.
+    pub fn inject_input_at(&mut self, message: Message, time: f64) -> Result<(), SimulationError> {
+        if time.is_nan() || time < self.services.global_time() {
+            return Err(SimulationError::InjectionInPast(time));
+        }
+        let message = message.with_time(time);
+        self.audit("Inject Input At", || {
+            serde_json::to_string(&message).unwrap_or_default()
+        });
+        // Injections at the same time keep their scheduling order
+        let index = self
+            .scheduled_inputs
+            .partition_point(|scheduled| *scheduled.time() <= time);
+        self.scheduled_inputs.insert(index, message);
+        Ok(())
+    }
+
+    /// The input injections scheduled for future simulation times, in
+    /// injection time order.
+    pub fn scheduled_inputs(&self) -> &[Message] {
+        &self.scheduled_inputs
+    }
+
+    /// Release the scheduled input injections that are due, as of the
+    /// current simulation time.
+    fn release_scheduled_inputs(&mut self) -> Vec<Message> {
+        let global_time = self.services.global_time();
+        let due = self
+            .scheduled_inputs
+            .partition_point(|scheduled| *scheduled.time() <= global_time);
+        let released: Vec<Message> = self.scheduled_inputs.drain(..due).collect();
+        if self.subscriptions.wants(EventKind::InputInjected) {
+            released.iter().for_each(|message| {
+                self.subscriptions
+                    .publish(SimulationEvent::InputInjected(message.clone()))
+            });
+        }
+        released
+    }
+
+    /// Enable or disable the parallel evaluation of model state
+    /// transitions within each step.  Messages are delivered and routed in
+    /// the same order as serial steps, and blackboard writes are merged in
+    /// delivery order.  Models without their own random number generator
+    /// draw from per-model streams in parallel steps, regardless of the
+    /// stream assignment - so parallel runs are reproducible, and match
+    /// serial runs with per-model streams.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.audit("Set Parallel", || parallel.to_string());
+        self.parallel = parallel;
+    }
+
+    /// Deliver the messages to their target models, as external events.
+    /// Higher priority messages are delivered first, across all models -
+    /// within a priority, messages are delivered in model order, and then
+    /// in message order.
+    fn external_events(&mut self, messages: &[Message]) -> Result<(), SimulationError> {
+        // Messages are matched to their target models through the model
+        // index, in a single pass over the messages
+        let mut model_messages: Vec<Vec<ModelMessage>> = vec![Vec::new(); self.models.len()];
+        messages.iter().for_each(|message| {
+            if let Ok(model_index) = self.model_index(message.target_id()) {
+                model_messages[model_index].push(message.to_model_message());
+            }
+        });
+        model_messages
+            .iter_mut()
+            .for_each(|messages| messages.sort_by_key(|message| Reverse(message.priority)));
+        let time = self.services.global_time();
+        let execution = &mut self.execution;
+        self.models
+            .iter()
+            .zip(model_messages.iter())
+            .for_each(|(model, messages)| {
+                execution.record_events(time, model.id(), messages.len())
+            });
+        let deliveries = prioritized_deliveries(model_messages.iter().enumerate());
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            return parallel::external_events(&mut self.models, &deliveries, &mut self.services);
+        }
+        let services = &mut self.services;
+        let models = &mut self.models;
+        deliveries
+            .into_iter()
+            .try_for_each(|(model_index, message)| {
+                models[model_index].events_ext(message, services)
+            })
+    }
+
+    /// Execute the internal events of the imminent models, returning the
+    /// model index and outgoing messages of each imminent model, in model
+    /// order.
+    fn internal_events(&mut self) -> Result<Vec<(usize, Vec<ModelMessage>)>, SimulationError> {
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            return parallel::internal_events(&mut self.models, &mut self.services);
+        }
+        let services = &mut self.services;
+        self.models
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, model)| model.until_next_event() == 0.0)
+            .map(|(model_index, model)| Ok((model_index, model.events_int(services)?)))
+            .collect()
+    }
+
+    /// Deliver the messages to their target models, as external events,
+    /// under future event list scheduling - returning the indices of the
+    /// target models.
+    fn scheduled_external_events(
+        &mut self,
+        messages: &[Message],
+        event_list: &mut FutureEventList,
+    ) -> Result<Vec<usize>, SimulationError> {
+        let mut model_messages: BTreeMap<usize, Vec<ModelMessage>> = BTreeMap::new();
+        messages.iter().for_each(|message| {
+            if let Ok(model_index) = self.model_index(message.target_id()) {
+                model_messages
+                    .entry(model_index)
+                    .or_default()
+                    .push(message.to_model_message());
+            }
+        });
+        let time = self.services.global_time();
+        model_messages.iter().for_each(|(model_index, messages)| {
+            self.execution
+                .record_events(time, self.models[*model_index].id(), messages.len());
+            event_list.advance(&mut self.models, *model_index, time);
+        });
+        prioritized_deliveries(
+            model_messages
+                .iter()
+                .map(|(model_index, messages)| (*model_index, messages)),
+        )
+        .into_iter()
+        .try_for_each(|(model_index, message)| {
+            self.models[model_index].events_ext(message, &mut self.services)
+        })?;
+        model_messages
+            .into_keys()
+            .map(|model_index| {
+                event_list.schedule(&self.models, model_index)?;
+                Ok(model_index)
+            })
+            .collect()
+    }
+
+    /// Advance the global time to the next event time, and execute the
+    /// internal events of the imminent models, under future event list
+    /// scheduling.
+    fn scheduled_internal_events(
+        &mut self,
+        event_list: &mut FutureEventList,
+    ) -> Result<Vec<(usize, Vec<ModelMessage>)>, SimulationError> {
+        let global_time = self.services.global_time();
+        let next_time = if self.messages.is_empty() {
+            let next_injection = self
+                .scheduled_inputs
+                .first()
+                .map_or(f64::INFINITY, |message| *message.time());
+            f64::max(
+                global_time,
+                f64::min(event_list.next_time(), next_injection),
+            )
+        } else {
+            global_time
+        };
+        // Without any pending events, the clock runs to infinity, with the
+        // models left at the last event time
+        if !next_time.is_finite() {
+            event_list.synchronize(&mut self.models, global_time);
+        }
+        self.services.set_global_time(next_time);
+        event_list
+            .pop_imminent(next_time)
+            .into_iter()
+            .map(|model_index| {
+                event_list.advance_imminent(&mut self.models, model_index, next_time);
+                let outgoing_messages = self.models[model_index].events_int(&mut self.services)?;
+                event_list.schedule(&self.models, model_index)?;
+                Ok((model_index, outgoing_messages))
+            })
+            .collect()
+    }
+
+    /// Randomize the initial model states, by injecting the messages of
+    /// each initial condition at time 0 (e.g. pre-populating processor
+    /// queues, or closing gates).  Initial conditions are drawn from the
+    /// global random number generator, and only apply before the
+    /// simulation starts.
+    pub fn randomize_initial_state(
+        &mut self,
+        conditions: &[InitialCondition],
+    ) -> Result<(), SimulationError> {
+        if self.services.global_time() != 0.0 {
+            return Err(SimulationError::InitializationAfterStart(
+                self.services.global_time(),
+            ));
+        }
+        for condition in conditions {
+            self.model_index(&condition.model_id)?;
+            let mut condition = condition.clone();
+            condition
+                .messages(self.services.global_rng())?
+                .into_iter()
+                .for_each(|message| self.inject_input(message));
+        }
+        Ok(())
     }
 
     /// The simulation step is foundational for a discrete event simulation.
@@ -198,110 +1921,364 @@ impl Simulation {
     /// message orchestration, global time accounting, and step messages
     /// output.
     pub fn step(&mut self) -> Result<Vec<Message>, SimulationError> {
+        self.check_quotas()?;
+        self.quotas.check_steps(1)?;
+        self.begin_steps()?;
+        let result = self.step_events().and_then(|_| self.check_memory_quota());
+        self.end_steps();
+        result?;
+        Ok(self.reported_messages())
+    }
+
+    /// Build the future event list for a series of steps, under future
+    /// event list scheduling.
+    fn begin_steps(&mut self) -> Result<(), SimulationError> {
+        if self.event_scheduling == EventScheduling::FutureEventList {
+            self.event_list = Some(FutureEventList::new(
+                &self.models,
+                self.services.global_time(),
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Synchronize the models to the global time, after a series of steps
+    /// under future event list scheduling.
+    fn end_steps(&mut self) {
+        if let Some(mut event_list) = self.event_list.take() {
+            event_list.synchronize(&mut self.models, self.services.global_time());
+        }
+    }
+
+    /// Synchronize the models to the global time mid-run, under future
+    /// event list scheduling, before the model states are observed.
+    fn synchronize_models(&mut self) {
+        if let Some(event_list) = &mut self.event_list {
+            event_list.synchronize(&mut self.models, self.services.global_time());
+        }
+    }
+
+    fn step_events(&mut self) -> Result<(), SimulationError> {
+        let mut event_list = self.event_list.take();
+        let result = self.step_events_with(event_list.as_mut());
+        self.event_list = event_list;
+        result
+    }
+
+    fn step_events_with(
+        &mut self,
+        mut event_list: Option<&mut FutureEventList>,
+    ) -> Result<(), SimulationError> {
+        let started_at = wall_clock_time();
+        let started_at_time = self.services.global_time();
+        // Deserialized simulations are compiled on their first step
+        if self.routes.is_none() || self.model_indices.is_none() {
+            self.compile_topology();
+        }
+        if let Some(snapshots) = &mut self.state_snapshots {
+            snapshots.capture_before(&self.models)?;
+        }
         let messages = self.messages.clone();
+        let time = self.services.global_time();
+        self.observers
+            .notify(|observer| observer.on_step_start(time, &messages));
         let mut next_messages: Vec<Message> = Vec::new();
-        // Process external events
-        if !messages.is_empty() {
-            (0..self.models.len()).try_for_each(|model_index| -> Result<(), SimulationError> {
-                let model_messages: Vec<ModelMessage> = messages
-                    .iter()
-                    .filter_map(|message| {
-                        if message.target_id() == self.models[model_index].id() {
-                            Some(ModelMessage {
-                                port_name: message.target_port().to_string(),
-                                content: message.content().to_string(),
-                            })
-                        } else {
-                            None
+        // The outgoing message counts of the models with energy accounting
+        let mut sent_messages: Vec<(usize, usize)> = Vec::new();
+        messages
+            .iter()
+            .for_each(|message| self.correlations.receive(message));
+        let internal_events = match event_list.as_deref_mut() {
+            Some(event_list) => {
+                // Only the models with transitions since the last
+                // observation are observed
+                let mut observed = self.scheduled_external_events(&messages, event_list)?;
+                self.observe_deliveries(&messages);
+                self.release_unheld_jobs();
+                observed.extend(event_list.take_transitioned());
+                observed.sort_unstable();
+                observed.dedup();
+                let models = &self.models;
+                self.execution.observe(
+                    observed.iter().map(|model_index| &models[*model_index]),
+                    self.services.global_time(),
+                );
+                let internal_events = self.scheduled_internal_events(event_list)?;
+                event_list.set_transitioned(
+                    internal_events
+                        .iter()
+                        .map(|(model_index, _)| *model_index)
+                        .collect(),
+                );
+                internal_events
+            }
+            None => {
+                // Process external events
+                if !messages.is_empty() {
+                    self.external_events(&messages)?;
+                    self.observe_deliveries(&messages);
+                    self.release_unheld_jobs();
+                }
+                self.execution
+                    .observe(&self.models, self.services.global_time());
+                // Process internal events and gather associated messages
+                self.validate_time_advances()?;
+                let until_next_event = self.until_next_event();
+                // Without any pending events, the clock runs to infinity,
+                // but the models are not advanced (which would leave
+                // infinite time advances as NaN)
+                if until_next_event.is_finite() {
+                    self.models().iter_mut().for_each(|model| {
+                        model.time_advance(until_next_event);
+                    });
+                }
+                self.services
+                    .set_global_time(self.services.global_time() + until_next_event);
+                self.internal_events()?
+            }
+        };
+        internal_events
+            .into_iter()
+            .for_each(|(model_index, outgoing_messages)| {
+                self.execution.record_events(
+                    self.services.global_time(),
+                    self.models[model_index].id(),
+                    1,
+                );
+                if self
+                    .energy_coefficients
+                    .contains_key(self.models[model_index].id())
+                {
+                    sent_messages.push((model_index, outgoing_messages.len()));
+                }
+                if self.subscriptions.wants(EventKind::InternalTransition) {
+                    self.subscriptions
+                        .publish(SimulationEvent::InternalTransition {
+                            model_id: self.models[model_index].id().to_string(),
+                            time: self.services.global_time(),
+                        });
+                }
+                let (model, time) = (&self.models[model_index], self.services.global_time());
+                self.observers.notify(|observer| {
+                    observer.on_model_transition(model.id(), TransitionKind::Internal, time)
+                });
+                if let Some(trace) = &mut self.trace {
+                    let mut ports: Vec<String> = Vec::new();
+                    outgoing_messages.iter().for_each(|outgoing_message| {
+                        if !ports.contains(&outgoing_message.port_name) {
+                            ports.push(outgoing_message.port_name.clone());
                         }
-                    })
-                    .collect();
-                model_messages
+                    });
+                    trace.record(TraceEvent {
+                        time,
+                        model_id: model.id().to_string(),
+                        kind: TransitionKind::Internal,
+                        ports,
+                        status: model.status(),
+                    });
+                }
+                let contexts = self
+                    .correlations
+                    .correlate(self.models[model_index].id(), &outgoing_messages);
+                outgoing_messages
                     .iter()
-                    .try_for_each(|model_message| -> Result<(), SimulationError> {
-                        self.models[model_index].events_ext(model_message, &mut self.services)
-                    })
-            })?;
-        }
-        // Process internal events and gather associated messages
-        let until_next_event: f64;
-        if self.messages.is_empty() {
-            until_next_event = self.models().iter().fold(INFINITY, |min, model| {
-                f64::min(min, model.until_next_event())
-            });
-        } else {
-            until_next_event = 0.0;
-        }
-        self.models().iter_mut().for_each(|model| {
-            model.time_advance(until_next_event);
-        });
-        self.services
-            .set_global_time(self.services.global_time() + until_next_event);
-        let errors: Result<Vec<()>, SimulationError> = (0..self.models.len())
-            .map(|model_index| -> Result<(), SimulationError> {
-                if self.models[model_index].until_next_event() == 0.0 {
-                    self.models[model_index]
-                        .events_int(&mut self.services)?
-                        .iter()
-                        .for_each(|outgoing_message| {
-                            let target_ids = self.get_message_target_ids(
-                                self.models[model_index].id(), // Outgoing message source model ID
-                                &outgoing_message.port_name,   // Outgoing message source model port
-                            );
-                            let target_ports = self.get_message_target_ports(
-                                self.models[model_index].id(), // Outgoing message source model ID
-                                &outgoing_message.port_name,   // Outgoing message source model port
-                            );
-                            target_ids.iter().zip(target_ports.iter()).for_each(
-                                |(target_id, target_port)| {
-                                    next_messages.push(Message::new(
-                                        self.models[model_index].id().to_string(),
-                                        outgoing_message.port_name.clone(),
-                                        target_id.clone(),
-                                        target_port.clone(),
-                                        self.services.global_time(),
-                                        outgoing_message.content.clone(),
-                                    ));
-                                },
+                    .zip(contexts)
+                    .for_each(|(outgoing_message, context)| {
+                        // Metadata set by the model takes precedence over
+                        // inherited metadata
+                        let mut metadata = context.metadata;
+                        metadata.extend(outgoing_message.metadata.clone());
+                        let payload = outgoing_message.payload.clone().or(context.payload);
+                        let targets = self.get_message_targets(
+                            self.models[model_index].id(), // Outgoing message source model ID
+                            &outgoing_message.port_name,   // Outgoing message source model port
+                        );
+                        targets.iter().for_each(|(target_id, target_port)| {
+                            next_messages.push(
+                                Message::new(
+                                    self.models[model_index].id().to_string(),
+                                    outgoing_message.port_name.clone(),
+                                    target_id.clone(),
+                                    target_port.clone(),
+                                    self.services.global_time(),
+                                    outgoing_message.content.clone(),
+                                )
+                                .with_metadata(metadata.clone())
+                                .with_job_id(outgoing_message.job_id.clone())
+                                .with_payload(payload.clone())
+                                .with_priority(outgoing_message.priority),
                             );
                         });
-                }
-                Ok(())
-            })
-            .collect();
-        errors?;
+                    });
+            });
+        if self.subscriptions.wants(EventKind::MessageRouted) {
+            next_messages.iter().for_each(|message| {
+                self.subscriptions
+                    .publish(SimulationEvent::MessageRouted(message.clone()))
+            });
+        }
         self.messages = next_messages;
-        Ok(self.get_messages().clone())
+        self.injected_count = 0;
+        if !self.energy_coefficients.is_empty() {
+            self.energy.record_step(
+                started_at_time,
+                self.services.global_time(),
+                &self.models,
+                &self.energy_coefficients,
+                &messages,
+                &sent_messages,
+            );
+        }
+        self.release_scheduled_inputs()
+            .into_iter()
+            .for_each(|message| self.enqueue_injection(message));
+        // State snapshots and history observe every model in every step
+        if self.state_snapshots.is_some()
+            || self.history.as_ref().is_some_and(History::retains_states)
+        {
+            if let Some(event_list) = event_list {
+                event_list.synchronize(&mut self.models, self.services.global_time());
+            }
+        }
+        if let Some(snapshots) = &mut self.state_snapshots {
+            snapshots.capture_after(&self.models)?;
+        }
+        if self.history.is_some() {
+            let reported = self.reported_messages();
+            if let Some(history) = &mut self.history {
+                history.record_messages(&reported);
+                history.record_step(self.services.global_time(), &self.models)?;
+            }
+        }
+        if self.subscriptions.wants(EventKind::StepCompleted) {
+            self.subscriptions.publish(SimulationEvent::StepCompleted {
+                time: self.services.global_time(),
+                message_count: self.messages.len(),
+            });
+        }
+        let (time, messages) = (self.services.global_time(), &self.messages);
+        self.observers
+            .notify(|observer| observer.on_step_end(time, messages));
+        self.execution
+            .record_step(self.services.global_time(), wall_clock_time() - started_at);
+        Ok(())
     }
 
     /// This method executes simulation `step` calls, until a global time
     /// has been exceeded.  At which point, the messages from all the
     /// simulation steps are returned.
     pub fn step_until(&mut self, until: f64) -> Result<Vec<Message>, SimulationError> {
+        self.check_quotas()?;
+        self.begin_steps()?;
         let mut message_records: Vec<Message> = Vec::new();
-        loop {
-            self.step()?;
+        let mut steps = 0;
+        let result = loop {
+            steps += 1;
+            if let Err(error) = self
+                .quotas
+                .check_steps(steps)
+                .and_then(|_| self.step_events())
+                .and_then(|_| self.check_memory_quota())
+            {
+                break Err(error);
+            }
             if self.services.global_time() < until {
-                message_records.extend(self.get_messages().clone());
+                message_records.extend(self.reported_messages());
             } else {
-                break;
+                break Ok(message_records);
             }
-        }
-        Ok(message_records)
+        };
+        self.end_steps();
+        result
+    }
+
+    /// This method executes simulation `step` calls, until the stop
+    /// condition holds after a step, or no events remain.  The messages
+    /// from all the simulation steps, including the step meeting the
+    /// condition, are returned.
+    pub fn step_until_stop(
+        &mut self,
+        condition: &StopCondition,
+    ) -> Result<Vec<Message>, SimulationError> {
+        let mut monitor = StopMonitor::new(condition);
+        self.step_while(|simulation| monitor.observe(simulation))
+    }
+
+    /// This method executes simulation `step` calls, until the predicate
+    /// holds for the simulation after a step, or no events remain - for
+    /// stopping on arbitrary domain conditions.  The messages from all the
+    /// simulation steps, including the step meeting the predicate, are
+    /// returned.
+    pub fn step_until_condition(
+        &mut self,
+        mut predicate: impl FnMut(&Simulation) -> bool,
+    ) -> Result<Vec<Message>, SimulationError> {
+        self.step_while(|simulation| Ok(predicate(simulation)))
+    }
+
+    fn step_while(
+        &mut self,
+        mut stop: impl FnMut(&Simulation) -> Result<bool, SimulationError>,
+    ) -> Result<Vec<Message>, SimulationError> {
+        self.check_quotas()?;
+        self.begin_steps()?;
+        let mut message_records: Vec<Message> = Vec::new();
+        let mut steps = 0;
+        let result = loop {
+            steps += 1;
+            if let Err(error) = self
+                .quotas
+                .check_steps(steps)
+                .and_then(|_| self.step_events())
+                .and_then(|_| self.check_memory_quota())
+            {
+                break Err(error);
+            }
+            message_records.extend(self.reported_messages());
+            // The stop condition observes the model states at the global time
+            self.synchronize_models();
+            match stop(self) {
+                Ok(false) if self.services.global_time().is_finite() => {}
+                Ok(_) => break Ok(message_records),
+                Err(error) => break Err(error),
+            }
+        };
+        self.end_steps();
+        result
     }
 
     /// This method executes the specified number of simulation steps, `n`.
     /// Upon execution of the n steps, the messages from all the steps are
     /// returned.
     pub fn step_n(&mut self, n: usize) -> Result<Vec<Message>, SimulationError> {
+        self.check_quotas()?;
+        self.quotas.check_steps(n)?;
+        self.begin_steps()?;
         let mut message_records: Vec<Message> = Vec::new();
-        (0..n)
-            .map(|_| -> Result<Vec<Message>, SimulationError> {
-                self.step()?;
-                message_records.extend(self.messages.clone());
-                Ok(Vec::new())
+        let result = (0..n)
+            .try_for_each(|_| {
+                self.step_events()?;
+                self.check_memory_quota()?;
+                message_records.extend(self.reported_messages());
+                Ok(())
             })
-            .find(Result::is_err)
-            .unwrap_or(Ok(message_records))
+            .map(|_| message_records);
+        self.end_steps();
+        result
     }
 }
+
+/// The external event deliveries of the target models, ordered by message
+/// priority (highest first).  The sort is stable, so deliveries of the same
+/// priority remain in model order, and then in message order.
+fn prioritized_deliveries<'a>(
+    model_messages: impl Iterator<Item = (usize, &'a Vec<ModelMessage>)>,
+) -> Vec<(usize, &'a ModelMessage)> {
+    let mut deliveries: Vec<(usize, &ModelMessage)> = model_messages
+        .flat_map(|(model_index, messages)| {
+            messages.iter().map(move |message| (model_index, message))
+        })
+        .collect();
+    deliveries.sort_by_key(|(_, message)| Reverse(message.priority));
+    deliveries
+}
@@ -0,0 +1,79 @@
+//! Observers hook into the step lifecycle, for live telemetry, tracing, and
+//! custom metric collection inside the stepping loop.  Unlike
+//! subscriptions, which deliver events over channels to independent
+//! consumers, observers are called synchronously, as the events occur, with
+//! borrowed event data.  Every callback has a no-op default, so observers
+//! implement only the callbacks of interest.
+
+use serde::{Deserialize, Serialize};
+
+use super::coupling::Message;
+
+/// The kind of a model state transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransitionKind {
+    /// A transition on the model's own schedule, emitting its outgoing
+    /// messages
+    Internal,
+    /// A transition on the delivery of incoming messages
+    External,
+}
+
+/// A step lifecycle observer, attached with `Simulation::attach_observer`.
+/// Within a step, the callbacks are ordered as the step - the step start,
+/// the deliveries of the pending messages and the resulting external
+/// transitions, the internal transitions, and the step end.
+pub trait SimulationObserver: Send + Sync {
+    /// A step starts at the simulation time, with the pending messages to
+    /// deliver.
+    fn on_step_start(&mut self, _time: f64, _pending_messages: &[Message]) {}
+
+    /// A message was delivered to its target model.
+    fn on_message_delivered(&mut self, _message: &Message) {}
+
+    /// A model executed a state transition at the simulation time.
+    /// External transitions are reported once per model and step, after
+    /// the deliveries to the model.
+    fn on_model_transition(&mut self, _model_id: &str, _kind: TransitionKind, _time: f64) {}
+
+    /// A step completed at the simulation time, with the messages routed
+    /// for the next step.
+    fn on_step_end(&mut self, _time: f64, _messages: &[Message]) {}
+}
+
+/// The observers of a simulation.  Observers are tied to a single
+/// simulation instance, and so are not carried over to clones.
+#[derive(Default)]
+pub(crate) struct Observers {
+    observers: Vec<Box<dyn SimulationObserver>>,
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Observers {
+    pub(crate) fn attach(&mut self, observer: Box<dyn SimulationObserver>) {
+        self.observers.push(observer);
+    }
+
+    pub(crate) fn detach(&mut self) -> Vec<Box<dyn SimulationObserver>> {
+        std::mem::take(&mut self.observers)
+    }
+
+    /// Whether any observers are attached, so event data gathering can be
+    /// skipped otherwise.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    /// Notify every observer, in attachment order.
+    pub(crate) fn notify(&mut self, mut callback: impl FnMut(&mut dyn SimulationObserver)) {
+        self.observers
+            .iter_mut()
+            .for_each(|observer| callback(observer.as_mut()));
+    }
+}
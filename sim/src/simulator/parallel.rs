@@ -0,0 +1,105 @@
+//! Parallel evaluation of the model state transitions within a simulation
+//! step, with rayon.  Each model with events in the step transitions on its
+//! own fork of the simulation services, and the forks are joined in model
+//! order afterwards - so blackboard writes and variate records are merged
+//! deterministically, and the outgoing messages are routed in the same
+//! order as a serial step.  The forks draw from per-model random number
+//! streams, rather than the shared global generator, so parallel runs are
+//! reproducible, and match serial runs with per-model streams.
+
+use rayon::prelude::*;
+
+use super::Services;
+use crate::models::{DevsModel, Model, ModelMessage};
+use crate::utils::errors::SimulationError;
+
+// The services fork of a model transition, and the outgoing messages of an
+// internal transition
+type Transition = Result<Option<(Services, Vec<ModelMessage>)>, SimulationError>;
+
+/// Deliver the prioritized external event deliveries (model index and
+/// message), in parallel across models.  Each model receives its messages
+/// in delivery order, and the forks are joined in the order of each
+/// model's final delivery - so the last blackboard write wins, as in a
+/// serial step.
+pub(crate) fn external_events(
+    models: &mut [Model],
+    deliveries: &[(usize, &ModelMessage)],
+    services: &mut Services,
+) -> Result<(), SimulationError> {
+    let mut model_deliveries: Vec<Vec<&ModelMessage>> = vec![Vec::new(); models.len()];
+    let mut final_deliveries = vec![0; models.len()];
+    deliveries
+        .iter()
+        .enumerate()
+        .for_each(|(position, (model_index, message))| {
+            model_deliveries[*model_index].push(*message);
+            final_deliveries[*model_index] = position;
+        });
+    let base = &*services;
+    let mut forks: Vec<(usize, Result<Option<Services>, SimulationError>)> = models
+        .par_iter_mut()
+        .zip(model_deliveries.par_iter())
+        .enumerate()
+        .map(|(model_index, (model, messages))| {
+            if messages.is_empty() {
+                return (model_index, Ok(None));
+            }
+            let mut fork = base.fork();
+            let delivery = messages
+                .iter()
+                .try_for_each(|message| model.events_ext(message, &mut fork));
+            (model_index, delivery.map(|_| Some(fork)))
+        })
+        .collect();
+    forks.sort_by_key(|(model_index, _)| final_deliveries[*model_index]);
+    join(services, forks.into_iter().map(|(_, fork)| fork).collect())
+}
+
+/// Execute the internal events of the imminent models, in parallel across
+/// models, returning the model index and outgoing messages of each imminent
+/// model.
+pub(crate) fn internal_events(
+    models: &mut [Model],
+    services: &mut Services,
+) -> Result<Vec<(usize, Vec<ModelMessage>)>, SimulationError> {
+    let base = &*services;
+    let transitions: Vec<Transition> = models
+        .par_iter_mut()
+        .map(|model| {
+            if model.until_next_event() != 0.0 {
+                return Ok(None);
+            }
+            let mut fork = base.fork();
+            let outgoing_messages = model.events_int(&mut fork)?;
+            Ok(Some((fork, outgoing_messages)))
+        })
+        .collect();
+    let mut outgoing = Vec::new();
+    let mut forks = Vec::new();
+    transitions
+        .into_iter()
+        .enumerate()
+        .try_for_each(|(model_index, transition)| {
+            if let Some((fork, outgoing_messages)) = transition? {
+                forks.push(Ok(Some(fork)));
+                outgoing.push((model_index, outgoing_messages));
+            }
+            Ok::<(), SimulationError>(())
+        })?;
+    join(services, forks)?;
+    Ok(outgoing)
+}
+
+fn join(
+    services: &mut Services,
+    forks: Vec<Result<Option<Services>, SimulationError>>,
+) -> Result<(), SimulationError> {
+    let base = services.blackboard.clone();
+    forks.into_iter().try_for_each(|fork| {
+        if let Some(fork) = fork? {
+            services.join(&base, fork);
+        }
+        Ok(())
+    })
+}
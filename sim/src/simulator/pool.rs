@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+
+use super::coupling::Message;
+use super::Simulation;
+use crate::utils::errors::SimulationError;
+
+/// The order in which a pool steps its simulations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PoolScheduling {
+    /// Step each simulation in turn, in insertion order
+    #[default]
+    RoundRobin,
+    /// Step the simulation with the earliest next event time, so the
+    /// simulations advance through simulation time together
+    EarliestEvent,
+}
+
+/// A message of a pooled simulation, tagged with the simulation ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PooledMessage {
+    pub simulation_id: String,
+    #[serde(flatten)]
+    pub message: Message,
+}
+
+/// The simulation pool owns many independent simulations (e.g. one per
+/// customer scenario), and steps them according to the pool scheduling.
+/// The messages of the pooled simulations are multiplexed into a single
+/// output, tagged with the simulation IDs - for services hosting many small,
+/// concurrent simulations.
+#[derive(Clone, Default)]
+pub struct SimulationPool {
+    simulations: Vec<(String, Simulation)>,
+    scheduling: PoolScheduling,
+    cursor: usize,
+}
+
+impl SimulationPool {
+    pub fn new(scheduling: PoolScheduling) -> Self {
+        Self {
+            simulations: Vec::new(),
+            scheduling,
+            cursor: 0,
+        }
+    }
+
+    /// Add a simulation to the pool, returning any simulation previously
+    /// pooled under the same ID.
+    pub fn insert(&mut self, simulation_id: &str, simulation: Simulation) -> Option<Simulation> {
+        match self.position(simulation_id) {
+            Some(index) => Some(std::mem::replace(
+                &mut self.simulations[index].1,
+                simulation,
+            )),
+            None => {
+                self.simulations
+                    .push((simulation_id.to_string(), simulation));
+                None
+            }
+        }
+    }
+
+    /// Remove a simulation from the pool.
+    pub fn remove(&mut self, simulation_id: &str) -> Option<Simulation> {
+        let index = self.position(simulation_id)?;
+        if index < self.cursor {
+            self.cursor -= 1;
+        }
+        Some(self.simulations.remove(index).1)
+    }
+
+    pub fn get(&self, simulation_id: &str) -> Option<&Simulation> {
+        self.position(simulation_id)
+            .map(|index| &self.simulations[index].1)
+    }
+
+    pub fn get_mut(&mut self, simulation_id: &str) -> Option<&mut Simulation> {
+        self.position(simulation_id)
+            .map(move |index| &mut self.simulations[index].1)
+    }
+
+    /// The IDs of the pooled simulations, in insertion order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.simulations.iter().map(|(id, _)| id.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.simulations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.simulations.is_empty()
+    }
+
+    fn position(&self, simulation_id: &str) -> Option<usize> {
+        self.simulations
+            .iter()
+            .position(|(id, _)| id == simulation_id)
+    }
+
+    /// The index of the simulation to step next, if any.
+    fn next_index(&mut self) -> Option<usize> {
+        if self.simulations.is_empty() {
+            return None;
+        }
+        match self.scheduling {
+            PoolScheduling::RoundRobin => {
+                let index = self.cursor % self.simulations.len();
+                self.cursor = index + 1;
+                Some(index)
+            }
+            PoolScheduling::EarliestEvent => self
+                .simulations
+                .iter()
+                .enumerate()
+                .min_by(|(_, (_, a)), (_, (_, b))| {
+                    a.next_event_time().total_cmp(&b.next_event_time())
+                })
+                .map(|(index, _)| index),
+        }
+    }
+
+    /// Step a single pooled simulation, chosen by the pool scheduling,
+    /// returning its messages tagged with the simulation ID.
+    pub fn step(&mut self) -> Result<Vec<PooledMessage>, SimulationError> {
+        let index = match self.next_index() {
+            Some(index) => index,
+            None => return Ok(Vec::new()),
+        };
+        let (simulation_id, simulation) = &mut self.simulations[index];
+        Ok(simulation
+            .step()?
+            .into_iter()
+            .map(|message| PooledMessage {
+                simulation_id: simulation_id.clone(),
+                message,
+            })
+            .collect())
+    }
+
+    /// Execute `n` pool steps, returning the multiplexed messages of all
+    /// the steps.
+    pub fn step_n(&mut self, n: usize) -> Result<Vec<PooledMessage>, SimulationError> {
+        let mut messages = Vec::new();
+        for _ in 0..n {
+            messages.extend(self.step()?);
+        }
+        Ok(messages)
+    }
+
+    /// Step every pooled simulation until its global time exceeds `until`,
+    /// returning the multiplexed messages generated before `until`.  With
+    /// earliest event scheduling, the messages are ordered by simulation
+    /// time across the pool.
+    pub fn step_until(&mut self, until: f64) -> Result<Vec<PooledMessage>, SimulationError> {
+        let mut messages = Vec::new();
+        let mut active: Vec<bool> = vec![true; self.simulations.len()];
+        while active.iter().any(|active| *active) {
+            let index = match self.scheduling {
+                PoolScheduling::RoundRobin => (0..self.simulations.len())
+                    .map(|offset| (self.cursor + offset) % self.simulations.len())
+                    .find(|index| active[*index]),
+                PoolScheduling::EarliestEvent => self
+                    .simulations
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| active[*index])
+                    .min_by(|(_, (_, a)), (_, (_, b))| {
+                        a.next_event_time().total_cmp(&b.next_event_time())
+                    })
+                    .map(|(index, _)| index),
+            };
+            let index = match index {
+                Some(index) => index,
+                None => break,
+            };
+            self.cursor = index + 1;
+            let (simulation_id, simulation) = &mut self.simulations[index];
+            let step_messages = simulation.step()?;
+            if simulation.get_global_time() < until {
+                messages.extend(step_messages.into_iter().map(|message| PooledMessage {
+                    simulation_id: simulation_id.clone(),
+                    message,
+                }));
+            } else {
+                active[index] = false;
+            }
+        }
+        Ok(messages)
+    }
+}
@@ -0,0 +1,124 @@
+//! Resource quotas bound the size and work of a single simulation, so a
+//! hosted deployment (e.g. a simulation service with a `SimulationPool` of
+//! customer scenarios) can run untrusted user configurations without one
+//! simulation exhausting the host.  Quotas are set by the host, and are not
+//! part of the serialized simulation, so user-supplied configurations and
+//! snapshots cannot lift them.  Exceeding a quota fails the request with a
+//! `SimulationError::QuotaExceeded`, describing the quota, its limit, and
+//! the offending amount.
+
+use std::fmt;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use super::Message;
+use crate::models::Model;
+use crate::utils::errors::SimulationError;
+
+/// A bounded resource of a simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaKind {
+    Models,
+    StepsPerRequest,
+    MemoryBytes,
+}
+
+impl fmt::Display for QuotaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaKind::Models => write!(f, "model count"),
+            QuotaKind::StepsPerRequest => write!(f, "steps per request"),
+            QuotaKind::MemoryBytes => write!(f, "estimated memory (bytes)"),
+        }
+    }
+}
+
+/// The resource quotas of a simulation - unlimited by default.  Steps are
+/// counted per `step`, `step_until`, or `step_n` request.  The memory
+/// estimate is the serialized size of the models (configurations and
+/// states, such as processor queues) and the pending and recorded
+/// messages, and is checked after every step while the quota is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quotas {
+    #[serde(default)]
+    pub max_models: Option<usize>,
+    #[serde(default)]
+    pub max_steps_per_request: Option<usize>,
+    #[serde(default)]
+    pub max_memory_bytes: Option<usize>,
+}
+
+impl Quotas {
+    pub fn with_max_models(mut self, max_models: usize) -> Self {
+        self.max_models = Some(max_models);
+        self
+    }
+
+    pub fn with_max_steps_per_request(mut self, max_steps: usize) -> Self {
+        self.max_steps_per_request = Some(max_steps);
+        self
+    }
+
+    pub fn with_max_memory_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_memory_bytes = Some(max_bytes);
+        self
+    }
+
+    fn check(quota: QuotaKind, limit: Option<usize>, actual: usize) -> Result<(), SimulationError> {
+        match limit {
+            Some(limit) if actual > limit => Err(SimulationError::QuotaExceeded {
+                quota,
+                limit,
+                actual,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_models(&self, models: usize) -> Result<(), SimulationError> {
+        Self::check(QuotaKind::Models, self.max_models, models)
+    }
+
+    pub(crate) fn check_steps(&self, steps: usize) -> Result<(), SimulationError> {
+        Self::check(
+            QuotaKind::StepsPerRequest,
+            self.max_steps_per_request,
+            steps,
+        )
+    }
+
+    pub(crate) fn check_memory(&self, bytes: usize) -> Result<(), SimulationError> {
+        Self::check(QuotaKind::MemoryBytes, self.max_memory_bytes, bytes)
+    }
+}
+
+/// A writer counting the bytes written, for serialized sizes without
+/// buffering the serialization.
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The estimated memory of the models and messages, as their serialized
+/// size in bytes.
+pub(crate) fn memory_estimate<'a>(
+    models: &[Model],
+    mut messages: impl Iterator<Item = &'a Message>,
+) -> Result<usize, SimulationError> {
+    let mut counter = ByteCounter::default();
+    serde_json::to_writer(&mut counter, models)?;
+    messages.try_for_each(|message| serde_json::to_writer(&mut counter, message))?;
+    Ok(counter.0)
+}
@@ -0,0 +1,133 @@
+//! Real-time execution paces a simulation against the wall clock, and
+//! interleaves the simulated events with external events from the host
+//! application - for human-in-the-loop simulators, such as operator
+//! training consoles.  Selected input ports are bound to `std::sync::mpsc`
+//! channels, fed by the application (e.g. from UI handlers on other
+//! threads).  Each external event is injected at the simulation time
+//! mapped from its wall clock arrival, and the simulated events are
+//! executed as the mapped simulation time reaches them.
+//!
+//! Real-time execution relies on the standard library clock and thread
+//! sleeping, and so is unavailable for WASM targets.
+
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::coupling::Message;
+use super::Simulation;
+use crate::utils::errors::SimulationError;
+
+/// The source ID of messages injected from host-bound ports.
+pub const HOST_SOURCE_ID: &str = "host";
+
+/// An input port bound to a host-side event source.  Each received string
+/// is injected as the content of a message to the port.
+#[derive(Debug)]
+struct PortBinding {
+    target_id: String,
+    target_port: String,
+    receiver: Receiver<String>,
+}
+
+/// The real-time executor, mapping wall clock time to simulation time with
+/// a fixed time scale - the simulation time units per wall clock second.
+/// A scale of 1.0 runs the simulation at wall clock speed, while a scale of
+/// 60.0 runs a simulation in minutes at one simulated minute per second.
+/// Between events, the executor sleeps for at most the poll interval, so
+/// external events are picked up promptly.  A simulation that falls behind
+/// the wall clock (e.g. from expensive steps) catches up as fast as
+/// possible, rather than skipping events.
+#[derive(Debug)]
+pub struct RealTimeExecutor {
+    time_scale: f64,
+    poll_interval: Duration,
+    bindings: Vec<PortBinding>,
+}
+
+impl RealTimeExecutor {
+    pub fn new(time_scale: f64) -> Self {
+        Self {
+            time_scale,
+            poll_interval: Duration::from_millis(10),
+            bindings: Vec::new(),
+        }
+    }
+
+    /// The maximum wall clock time between checks of the bound ports.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Bind a model input port to a host-side event source.  Several
+    /// sources may feed the same port.  Disconnected sources are ignored.
+    pub fn bind(mut self, target_id: &str, target_port: &str, receiver: Receiver<String>) -> Self {
+        self.bindings.push(PortBinding {
+            target_id: target_id.to_string(),
+            target_port: target_port.to_string(),
+            receiver,
+        });
+        self
+    }
+
+    /// Run the simulation in real time, from its current simulation time
+    /// until the mapped simulation time reaches `until`, returning the
+    /// messages of the executed steps.  External events are injected at
+    /// their mapped arrival time, or the current simulation time if the
+    /// simulation is behind, and events received at or after `until` are
+    /// left scheduled for subsequent execution.
+    pub fn run_until(
+        &mut self,
+        simulation: &mut Simulation,
+        until: f64,
+    ) -> Result<Vec<Message>, SimulationError> {
+        if !self.time_scale.is_finite() || self.time_scale <= 0.0 || until.is_nan() {
+            return Err(SimulationError::InvalidExperimentConfiguration);
+        }
+        let wall_start = Instant::now();
+        let simulation_start = simulation.get_global_time();
+        let mut messages = Vec::new();
+        loop {
+            let now = simulation_start + wall_start.elapsed().as_secs_f64() * self.time_scale;
+            self.inject_received(simulation, now)?;
+            let next_event_time = simulation.next_event_time();
+            if next_event_time <= now && next_event_time < until {
+                messages.extend(simulation.step()?);
+                continue;
+            }
+            if now >= until {
+                return Ok(messages);
+            }
+            let until_next_event = (next_event_time.min(until) - now) / self.time_scale;
+            thread::sleep(
+                self.poll_interval
+                    .min(Duration::from_secs_f64(until_next_event)),
+            );
+        }
+    }
+
+    fn inject_received(
+        &self,
+        simulation: &mut Simulation,
+        now: f64,
+    ) -> Result<(), SimulationError> {
+        for binding in &self.bindings {
+            // Drain each source, until it is empty or disconnected
+            while let Ok(content) = binding.receiver.try_recv() {
+                simulation.inject_input_at(
+                    Message::new(
+                        HOST_SOURCE_ID.to_string(),
+                        binding.target_port.clone(),
+                        binding.target_id.clone(),
+                        binding.target_port.clone(),
+                        now,
+                        content,
+                    ),
+                    now.max(simulation.get_global_time()),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
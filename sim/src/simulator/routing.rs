@@ -0,0 +1,232 @@
+//! Connector routing rules resolve the targets of outgoing messages.  The
+//! source model ID, source port, and target model ID of a connector may be
+//! patterns, where `*` matches any sequence of characters, so a single
+//! connector covers many nearly identical connections.  A target model ID
+//! pattern broadcasts each message to every matching model (other than the
+//! source model), and a target port of `*` delivers each message to the
+//! port of the same name as the source port.  For example, a connector
+//! from `processor-*` port `*` to `sink-01` port `*` collects the output of
+//! every processor port into the same-named sink port.
+//!
+//! The connectors are compiled into a routing table, so literal connectors
+//! are resolved with a lookup, and target model ID patterns are expanded
+//! once, rather than at every message.  Targets are in connector order.
+
+use std::collections::HashMap;
+
+use super::Connector;
+use crate::models::Model;
+
+/// The wildcard of connector patterns, matching any sequence of characters.
+pub const WILDCARD: char = '*';
+
+/// The target port passing the source port through.
+const PASS_THROUGH_PORT: &str = "*";
+
+/// Whether the connector field is a pattern, rather than a literal.
+pub fn is_pattern(value: &str) -> bool {
+    value.contains(WILDCARD)
+}
+
+/// Whether the value matches the pattern - literals match only themselves.
+pub fn matches(pattern: &str, value: &str) -> bool {
+    Matcher::compile(pattern).matches(value)
+}
+
+/// A compiled connector field - a literal, or the literal segments between
+/// the wildcards of a pattern.
+#[derive(Clone, Debug)]
+enum Matcher {
+    Literal(String),
+    Pattern(Vec<String>),
+}
+
+impl Matcher {
+    fn compile(pattern: &str) -> Self {
+        if is_pattern(pattern) {
+            Matcher::Pattern(pattern.split(WILDCARD).map(String::from).collect())
+        } else {
+            Matcher::Literal(pattern.to_string())
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        let segments = match self {
+            Matcher::Literal(literal) => return literal == value,
+            Matcher::Pattern(segments) => segments,
+        };
+        // A pattern has at least two segments - before the first wildcard,
+        // and after the last
+        let (first, last) = (&segments[0], &segments[segments.len() - 1]);
+        if value.len() < first.len() + last.len()
+            || !value.starts_with(first.as_str())
+            || !value.ends_with(last.as_str())
+        {
+            return false;
+        }
+        let mut remainder = &value[first.len()..value.len() - last.len()];
+        segments[1..segments.len() - 1].iter().all(|segment| {
+            match remainder.find(segment.as_str()) {
+                Some(index) => {
+                    remainder = &remainder[index + segment.len()..];
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+}
+
+/// A resolved message target - the connector index, for ordering, and the
+/// target model ID and port.
+type Target = (usize, String, String);
+
+/// A connector with a source model ID or port pattern, with its target
+/// model IDs expanded.  A target port of `None` passes the source port
+/// through, and broadcasts (target model ID patterns) skip the source
+/// model.
+#[derive(Clone, Debug)]
+struct PatternRule {
+    index: usize,
+    source_id: Matcher,
+    source_port: Matcher,
+    target_ids: Vec<String>,
+    target_port: Option<String>,
+    broadcast: bool,
+}
+
+/// The compiled connectors - the targets of literal source model ID and
+/// port pairs, and the rules of source patterns.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RoutingTable {
+    // Keyed by source model ID, then source port, for lookups without
+    // allocating keys
+    literal_routes: HashMap<String, HashMap<String, Vec<Target>>>,
+    pattern_rules: Vec<PatternRule>,
+}
+
+impl RoutingTable {
+    pub(crate) fn compile(models: &[Model], connectors: &[Connector]) -> Self {
+        let mut table = Self::default();
+        connectors
+            .iter()
+            .enumerate()
+            .for_each(|(index, connector)| {
+                let target_ids = expand_model_ids(models, connector.target_id());
+                let broadcast = is_pattern(connector.target_id());
+                let target_port = if connector.target_port() == PASS_THROUGH_PORT {
+                    None
+                } else {
+                    Some(connector.target_port().to_string())
+                };
+                if is_pattern(connector.source_id()) || is_pattern(connector.source_port()) {
+                    table.pattern_rules.push(PatternRule {
+                        index,
+                        source_id: Matcher::compile(connector.source_id()),
+                        source_port: Matcher::compile(connector.source_port()),
+                        target_ids,
+                        target_port,
+                        broadcast,
+                    });
+                    return;
+                }
+                let target_port =
+                    target_port.unwrap_or_else(|| connector.source_port().to_string());
+                table
+                    .literal_routes
+                    .entry(connector.source_id().to_string())
+                    .or_default()
+                    .entry(connector.source_port().to_string())
+                    .or_default()
+                    .extend(
+                        target_ids
+                            .into_iter()
+                            .filter(|target_id| !broadcast || target_id != connector.source_id())
+                            .map(|target_id| (index, target_id, target_port.clone())),
+                    );
+            });
+        table
+    }
+
+    /// The target model IDs and ports of messages from the source model
+    /// port.
+    pub(crate) fn targets(&self, source_id: &str, source_port: &str) -> Vec<(String, String)> {
+        let mut targets: Vec<Target> = self
+            .literal_routes
+            .get(source_id)
+            .and_then(|ports| ports.get(source_port))
+            .cloned()
+            .unwrap_or_default();
+        if self.pattern_rules.is_empty() {
+            return targets
+                .into_iter()
+                .map(|(_, target_id, target_port)| (target_id, target_port))
+                .collect();
+        }
+        self.pattern_rules
+            .iter()
+            .filter(|rule| {
+                rule.source_id.matches(source_id) && rule.source_port.matches(source_port)
+            })
+            .for_each(|rule| {
+                let target_port = rule
+                    .target_port
+                    .clone()
+                    .unwrap_or_else(|| source_port.to_string());
+                targets.extend(
+                    rule.target_ids
+                        .iter()
+                        .filter(|target_id| !rule.broadcast || *target_id != source_id)
+                        .map(|target_id| (rule.index, target_id.clone(), target_port.clone())),
+                );
+            });
+        // Literal and pattern targets interleave in connector order
+        targets.sort_by_key(|(index, _, _)| *index);
+        targets
+            .into_iter()
+            .map(|(_, target_id, target_port)| (target_id, target_port))
+            .collect()
+    }
+}
+
+/// The IDs of the models matching a model ID pattern, in model order.  A
+/// literal model ID is kept as-is, whether or not the model exists.
+pub(crate) fn expand_model_ids(models: &[Model], model_id: &str) -> Vec<String> {
+    if !is_pattern(model_id) {
+        return vec![model_id.to_string()];
+    }
+    let matcher = Matcher::compile(model_id);
+    models
+        .iter()
+        .filter(|model| matcher.matches(model.id()))
+        .map(|model| model.id().to_string())
+        .collect()
+}
+
+/// The connectors with model ID patterns expanded into a connector per
+/// matching source and target model pair, for structural analyses of the
+/// topology.  Port patterns are kept as-is.
+pub(crate) fn expand_connectors(models: &[Model], connectors: &[Connector]) -> Vec<Connector> {
+    let mut expanded = Vec::new();
+    connectors.iter().for_each(|connector| {
+        let broadcast = is_pattern(connector.target_id());
+        let target_ids = expand_model_ids(models, connector.target_id());
+        expand_model_ids(models, connector.source_id())
+            .iter()
+            .for_each(|source_id| {
+                target_ids
+                    .iter()
+                    .filter(|target_id| !broadcast || *target_id != source_id)
+                    .for_each(|target_id| {
+                        expanded.push(Connector::new(
+                            connector.id().to_string(),
+                            source_id.clone(),
+                            target_id.clone(),
+                            connector.source_port().to_string(),
+                            connector.target_port().to_string(),
+                        ))
+                    })
+            });
+    });
+    expanded
+}
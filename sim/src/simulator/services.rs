@@ -1,15 +1,94 @@
+use std::collections::{HashMap, VecDeque};
+
 use serde::{Deserialize, Serialize};
 
-use crate::input_modeling::dynamic_rng::{default_rng, DynRng};
+use crate::input_modeling::dynamic_rng::{
+    builtin_rng_state, default_rng, dyn_rng, seeded_rng, stream_rng, DynRng, DEFAULT_SEED,
+};
+use crate::input_modeling::random_variable::SamplingContext;
+use crate::input_modeling::variance_reduction::antithetic_rng;
+use crate::input_modeling::Globals;
+
+use super::blackboard::{Blackboard, BlackboardEntry, BlackboardValue};
+
+/// The assignment of random number streams to models.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RngStreams {
+    /// Every model draws from the global random number generator, in step
+    /// order
+    #[default]
+    Shared,
+    /// Each model draws from its own stream, seeded from the global seed
+    /// and the model ID (or the model's pinned stream index).  Adding or
+    /// removing a model leaves the sample paths of the other models
+    /// unchanged, for common random numbers across scenario variants.
+    PerModel,
+}
+
+impl RngStreams {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
 
-/// The simulator provides a uniform random number generator and simulation
-/// clock to models during the execution of a simulation
+/// The simulator provides a uniform random number generator, simulation
+/// clock, and shared blackboard to models during the execution of a
+/// simulation
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Services {
     #[serde(skip, default = "default_rng")]
     pub(crate) global_rng: DynRng,
     pub(crate) global_time: f64,
+    #[serde(default = "default_seed", skip_serializing_if = "is_default_seed")]
+    pub(crate) rng_seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "RngStreams::is_default")]
+    pub(crate) rng_streams: RngStreams,
+    #[serde(skip)]
+    pub(crate) current_model_id: Option<String>,
+    #[serde(skip)]
+    pub(crate) current_stream_index: Option<u64>,
+    // The persistent generators of the models' own streams, by stream key
+    // and seed
+    #[serde(skip)]
+    pub(crate) own_streams: HashMap<(String, u64), OwnStream>,
+    #[serde(skip)]
+    pub(crate) variate_log: Option<VariateLog>,
+    #[serde(skip)]
+    pub(crate) deterministic_mode: bool,
+    #[serde(skip)]
+    pub(crate) antithetic: bool,
+    #[serde(default, skip_serializing_if = "Globals::is_empty")]
+    pub(crate) globals: Globals,
+    #[serde(default, skip_serializing_if = "Blackboard::is_empty")]
+    pub(crate) blackboard: Blackboard,
+}
+
+fn default_seed() -> Option<u64> {
+    Some(DEFAULT_SEED)
+}
+
+fn is_default_seed(seed: &Option<u64>) -> bool {
+    *seed == default_seed()
+}
+
+/// A model's own random number stream - a persistent generator, seeded once
+/// from the stream key, and the stream position the generator has reached.
+pub(crate) struct OwnStream {
+    pub(crate) position: u64,
+    pub(crate) rng: DynRng,
+}
+
+// Cloned services (e.g. of a cloned simulation) continue the streams
+// independently
+impl Clone for OwnStream {
+    fn clone(&self) -> Self {
+        Self {
+            position: self.position,
+            rng: builtin_rng_state(&self.rng).map_or_else(|| self.rng.clone(), dyn_rng),
+        }
+    }
 }
 
 impl Default for Services {
@@ -17,13 +96,29 @@ impl Default for Services {
         Self {
             global_rng: default_rng(),
             global_time: 0.0,
+            rng_seed: default_seed(),
+            rng_streams: RngStreams::default(),
+            current_model_id: None,
+            current_stream_index: None,
+            own_streams: HashMap::new(),
+            variate_log: None,
+            deterministic_mode: false,
+            antithetic: false,
+            globals: Globals::default(),
+            blackboard: Blackboard::default(),
         }
     }
 }
 
 impl Services {
+    /// The global random number generator - or its antithetic counterpart,
+    /// for antithetic replications.
     pub fn global_rng(&self) -> DynRng {
-        self.global_rng.clone()
+        if self.antithetic {
+            antithetic_rng(self.global_rng.clone())
+        } else {
+            self.global_rng.clone()
+        }
     }
 
     pub fn global_time(&self) -> f64 {
@@ -33,4 +128,254 @@ impl Services {
     pub fn set_global_time(&mut self, time: f64) {
         self.global_time = time;
     }
+
+    /// The seed of the global random number generator, if known.  The seed
+    /// is unknown when a user-supplied random number generator is in use.
+    pub fn rng_seed(&self) -> Option<u64> {
+        self.rng_seed
+    }
+
+    /// Replace the global random number generator with the built-in
+    /// generator, seeded from a `u64` seed, for fully deterministic
+    /// replications.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.global_rng = seeded_rng(seed);
+        self.rng_seed = Some(seed);
+        self.own_streams.clear();
+    }
+
+    /// The random number generator for a model's next draw.  A model pinned
+    /// to an explicit seed, or drawing from a named stream, overrides the
+    /// global stream assignment - each draw advances the model's stream
+    /// position.  Draws from a named stream are at that position, so the
+    /// models on the stream share the same uniforms.  A pinned seed makes
+    /// the model's draws independent of the global seed, so the model
+    /// behaves identically across replications.  Otherwise, the model's own
+    /// random number generator, if supplied, or the global generator is
+    /// used - except with per-model streams, where models without their own
+    /// generator draw from their own stream.  A model's own stream is keyed
+    /// by its pinned stream index, if any, and by its model ID otherwise,
+    /// and is a persistent generator, seeded once from the key.  A model
+    /// resuming at a later stream position (e.g. restored from a snapshot)
+    /// resumes its stream from that position.
+    pub fn model_rng(
+        &mut self,
+        seed: Option<u64>,
+        rng_stream: Option<&str>,
+        stream_position: &mut u64,
+        rng: Option<&DynRng>,
+    ) -> DynRng {
+        let own_stream = self.rng_streams == RngStreams::PerModel && rng.is_none();
+        if seed.is_none() && rng_stream.is_none() && !own_stream {
+            return rng.cloned().unwrap_or_else(|| self.global_rng());
+        }
+        *stream_position += 1;
+        let seed = seed.or(self.rng_seed).unwrap_or(DEFAULT_SEED);
+        let rng = match rng_stream {
+            Some(rng_stream) => stream_rng(rng_stream, seed, *stream_position),
+            None => {
+                let stream_key = self
+                    .current_stream_index
+                    .map(|stream_index| format!["#{}", stream_index])
+                    .or_else(|| self.current_model_id.clone())
+                    .unwrap_or_default();
+                // The stream is seeded at the position it starts from - so
+                // a stream restarting from the start (e.g. after a reset)
+                // draws as in a new run, and a stream resuming at a later
+                // position (e.g. restored from a snapshot) draws
+                // reproducibly from that position
+                let start = *stream_position - 1;
+                let stream = self
+                    .own_streams
+                    .entry((stream_key.clone(), seed))
+                    .or_insert_with(|| OwnStream {
+                        position: start,
+                        rng: stream_rng(&stream_key, seed, start),
+                    });
+                if stream.position != start {
+                    stream.rng = stream_rng(&stream_key, seed, start);
+                }
+                stream.position = *stream_position;
+                stream.rng.clone()
+            }
+        };
+        if self.antithetic {
+            antithetic_rng(rng)
+        } else {
+            rng
+        }
+    }
+
+    /// The assignment of random number streams to models.
+    pub fn rng_streams(&self) -> RngStreams {
+        self.rng_streams
+    }
+
+    /// In deterministic mode, models replace random variates with their
+    /// distribution means, where defined, for perfectly predictable
+    /// timings.  Boolean and index variates (e.g. routing decisions) remain
+    /// stochastic, as the mean is not a valid outcome.
+    pub fn deterministic_mode(&self) -> bool {
+        self.deterministic_mode
+    }
+
+    /// Whether the simulation draws antithetic random numbers - the
+    /// complements of the random numbers otherwise drawn.
+    pub fn antithetic(&self) -> bool {
+        self.antithetic
+    }
+
+    /// The named global variables of the simulation, which distribution
+    /// parameters may reference.
+    pub fn globals(&self) -> &Globals {
+        &self.globals
+    }
+
+    /// The context for random variate draws at the current simulation
+    /// time - models pass the context to `random_variate_in`.
+    pub fn sampling_context(&self) -> SamplingContext<'_> {
+        SamplingContext {
+            time: self.global_time,
+            globals: &self.globals,
+        }
+    }
+
+    /// The shared blackboard, for coordination across models.
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.blackboard
+    }
+
+    /// Write a blackboard entry, timestamped with the current simulation
+    /// time.
+    pub fn write_blackboard(&mut self, key: &str, value: impl Into<BlackboardValue>) {
+        self.blackboard.write(key, value.into(), self.global_time);
+    }
+
+    /// Remove a blackboard entry, returning the removed entry.
+    pub fn remove_blackboard(&mut self, key: &str) -> Option<BlackboardEntry> {
+        self.blackboard.remove(key)
+    }
+
+    /// The ID of the model currently undergoing a state transition, if any.
+    pub fn current_model_id(&self) -> Option<&str> {
+        self.current_model_id.as_deref()
+    }
+
+    /// The pinned random number stream index of the model currently
+    /// undergoing a state transition, if any.
+    pub fn current_stream_index(&self) -> Option<u64> {
+        self.current_stream_index
+    }
+
+    /// A copy of the services for a single model's state transitions, in a
+    /// parallel step.  Models draw from their own random number streams, so
+    /// the draws do not depend on the thread schedule, while the blackboard,
+    /// variate log, and new stream changes are merged back through `join`.
+    /// The existing streams are shared with the fork, rather than copied.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            rng_streams: RngStreams::PerModel,
+            own_streams: self
+                .own_streams
+                .iter()
+                .map(|(key, stream)| {
+                    let stream = OwnStream {
+                        position: stream.position,
+                        rng: stream.rng.clone(),
+                    };
+                    (key.clone(), stream)
+                })
+                .collect(),
+            variate_log: self
+                .variate_log
+                .as_ref()
+                .map(|variate_log| VariateLog::new(variate_log.capacity)),
+            ..self.clone()
+        }
+    }
+
+    /// Merge the blackboard changes and variate records of forked services,
+    /// relative to the blackboard at the time of the fork.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn join(&mut self, base: &Blackboard, forked: Services) {
+        self.blackboard.merge_changes(base, &forked.blackboard);
+        // Each stream is drawn by a single model, in a single fork, so the
+        // furthest position is the drawn stream
+        forked.own_streams.into_iter().for_each(|(key, stream)| {
+            if self
+                .own_streams
+                .get(&key)
+                .is_none_or(|own_stream| own_stream.position < stream.position)
+            {
+                self.own_streams.insert(key, stream);
+            }
+        });
+        if let (Some(variate_log), Some(forked_log)) = (&mut self.variate_log, forked.variate_log) {
+            forked_log
+                .records
+                .into_iter()
+                .for_each(|record| variate_log.push(record));
+        }
+    }
+
+    /// Models report each random variate they draw through this method.
+    /// When variate recording is enabled for the simulation, the variate is
+    /// logged alongside the drawing model, the distribution, and the global
+    /// time.  Otherwise, the call is a no-op.
+    pub fn record_variate<D: Serialize>(&mut self, distribution: &D, value: f64) {
+        if let Some(variate_log) = &mut self.variate_log {
+            variate_log.push(VariateRecord {
+                time: self.global_time,
+                model_id: self.current_model_id.clone().unwrap_or_default(),
+                distribution: serde_json::to_string(distribution).unwrap_or_default(),
+                value,
+            });
+        }
+    }
+}
+
+/// A single random variate draw, as reported by a model during a state
+/// transition.  Boolean, discrete, and index variates are recorded as their
+/// `f64` equivalents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariateRecord {
+    pub time: f64,
+    pub model_id: String,
+    pub distribution: String,
+    pub value: f64,
+}
+
+/// The variate log is a bounded buffer of random variate records.  Once the
+/// capacity is reached, the oldest records are discarded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariateLog {
+    capacity: usize,
+    records: VecDeque<VariateRecord>,
+}
+
+impl VariateLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, record: VariateRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// The retained variate records, from oldest to newest.
+    pub fn records(&self) -> &VecDeque<VariateRecord> {
+        &self.records
+    }
 }
@@ -0,0 +1,154 @@
+//! Snapshots persist a complete simulation - configuration and state - for
+//! later restoration.  The snapshot format embeds a format version and an
+//! integrity checksum, and the payload may be compressed with gzip or zstd
+//! (behind the `gzip` and `zstd` features, respectively).  Loading
+//! auto-detects the format, and additionally accepts plain simulation YAML
+//! or JSON, as well as raw gzip- or zstd-compressed simulation YAML or JSON.
+//!
+//! The payload is YAML, rather than JSON, as JSON cannot represent the
+//! infinite time advances of passive models.  The snapshot layout is the magic bytes, the format version (u16, little
+//! endian), the compression tag (u8), the FNV-1a checksum of the
+//! uncompressed payload (u64, little endian), and then the payload.
+
+use super::Simulation;
+use crate::utils::errors::SimulationError;
+use crate::utils::fnv1a;
+
+/// The magic bytes identifying a versioned snapshot.
+const MAGIC: &[u8; 8] = b"SIMSNAP\0";
+/// The current snapshot format version.
+pub const SNAPSHOT_FORMAT_VERSION: u16 = 1;
+const HEADER_LENGTH: usize = MAGIC.len() + 2 + 1 + 8;
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// The compression of a snapshot payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotCompression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl SnapshotCompression {
+    fn tag(self) -> u8 {
+        match self {
+            SnapshotCompression::None => 0,
+            SnapshotCompression::Gzip => 1,
+            SnapshotCompression::Zstd => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, SimulationError> {
+        match tag {
+            0 => Ok(SnapshotCompression::None),
+            1 => Ok(SnapshotCompression::Gzip),
+            2 => Ok(SnapshotCompression::Zstd),
+            _ => Err(SimulationError::InvalidSnapshot),
+        }
+    }
+
+    fn compress(self, payload: Vec<u8>) -> Result<Vec<u8>, SimulationError> {
+        match self {
+            SnapshotCompression::None => Ok(payload),
+            SnapshotCompression::Gzip => {
+                #[cfg(feature = "gzip")]
+                {
+                    use std::io::Write;
+                    let mut encoder =
+                        flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(&payload)?;
+                    Ok(encoder.finish()?)
+                }
+                #[cfg(not(feature = "gzip"))]
+                {
+                    Err(SimulationError::UnsupportedCompression)
+                }
+            }
+            SnapshotCompression::Zstd => {
+                #[cfg(feature = "zstd")]
+                {
+                    Ok(zstd::encode_all(payload.as_slice(), 0)?)
+                }
+                #[cfg(not(feature = "zstd"))]
+                {
+                    Err(SimulationError::UnsupportedCompression)
+                }
+            }
+        }
+    }
+
+    fn decompress(self, payload: &[u8]) -> Result<Vec<u8>, SimulationError> {
+        match self {
+            SnapshotCompression::None => Ok(payload.to_vec()),
+            SnapshotCompression::Gzip => {
+                #[cfg(feature = "gzip")]
+                {
+                    use std::io::Read;
+                    let mut decompressed = Vec::new();
+                    flate2::read::GzDecoder::new(payload).read_to_end(&mut decompressed)?;
+                    Ok(decompressed)
+                }
+                #[cfg(not(feature = "gzip"))]
+                {
+                    Err(SimulationError::UnsupportedCompression)
+                }
+            }
+            SnapshotCompression::Zstd => {
+                #[cfg(feature = "zstd")]
+                {
+                    Ok(zstd::decode_all(payload)?)
+                }
+                #[cfg(not(feature = "zstd"))]
+                {
+                    Err(SimulationError::UnsupportedCompression)
+                }
+            }
+        }
+    }
+}
+
+/// Encode the simulation as a versioned snapshot.
+pub(crate) fn encode(
+    simulation: &Simulation,
+    compression: SnapshotCompression,
+) -> Result<Vec<u8>, SimulationError> {
+    let payload = serde_yaml::to_string(simulation)?.into_bytes();
+    let checksum = fnv1a(&payload);
+    let mut snapshot = Vec::with_capacity(HEADER_LENGTH + payload.len());
+    snapshot.extend_from_slice(MAGIC);
+    snapshot.extend_from_slice(&SNAPSHOT_FORMAT_VERSION.to_le_bytes());
+    snapshot.push(compression.tag());
+    snapshot.extend_from_slice(&checksum.to_le_bytes());
+    snapshot.extend(compression.compress(payload)?);
+    Ok(snapshot)
+}
+
+/// Decode a snapshot, auto-detecting the format.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Simulation, SimulationError> {
+    let payload = if bytes.starts_with(MAGIC) {
+        if bytes.len() < HEADER_LENGTH {
+            return Err(SimulationError::InvalidSnapshot);
+        }
+        let version = u16::from_le_bytes([bytes[8], bytes[9]]);
+        if version == 0 || version > SNAPSHOT_FORMAT_VERSION {
+            return Err(SimulationError::InvalidSnapshot);
+        }
+        let compression = SnapshotCompression::from_tag(bytes[10])?;
+        let mut checksum = [0; 8];
+        checksum.copy_from_slice(&bytes[11..HEADER_LENGTH]);
+        let payload = compression.decompress(&bytes[HEADER_LENGTH..])?;
+        if fnv1a(&payload) != u64::from_le_bytes(checksum) {
+            return Err(SimulationError::InvalidSnapshot);
+        }
+        payload
+    } else if bytes.starts_with(GZIP_MAGIC) {
+        SnapshotCompression::Gzip.decompress(bytes)?
+    } else if bytes.starts_with(ZSTD_MAGIC) {
+        SnapshotCompression::Zstd.decompress(bytes)?
+    } else {
+        bytes.to_vec()
+    };
+    // JSON is a subset of YAML
+    Ok(serde_yaml::from_slice(&payload)?)
+}
@@ -0,0 +1,290 @@
+//! SQLite output writes simulation messages, model records, and key
+//! performance indicators into a SQLite file, for analysis with SQL.  Each
+//! writer adds a run to the file, so several runs (e.g. replications or
+//! scenarios) may share a file.  Stepping through the writer stores the
+//! messages of each step as it completes, rather than accumulating them in
+//! memory.  The schema is:
+//!
+//! - `runs (id INTEGER PRIMARY KEY, label TEXT, version TEXT, rng_seed
+//!   INTEGER, config_hash TEXT, end_time REAL, steps INTEGER)`
+//! - `messages (run_id INTEGER, step INTEGER, time REAL, source_id TEXT,
+//!   source_port TEXT, target_id TEXT, target_port TEXT, content TEXT,
+//!   job_id TEXT, correlation_id TEXT)`
+//! - `records (run_id INTEGER, model_id TEXT, time REAL, action TEXT,
+//!   subject TEXT)`
+//! - `kpis (run_id INTEGER, model_id TEXT, name TEXT, time REAL, value
+//!   REAL)` - simulation-level KPIs have a `NULL` model ID
+//!
+//! The SQLite library is bundled (behind the `sqlite` feature), and is
+//! unavailable for WASM targets.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use super::coupling::Message;
+use super::manifest;
+use super::Simulation;
+use crate::models::Reportable;
+use crate::utils::errors::SimulationError;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        label TEXT NOT NULL,
+        version TEXT NOT NULL,
+        rng_seed INTEGER,
+        config_hash TEXT NOT NULL,
+        end_time REAL,
+        steps INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS messages (
+        run_id INTEGER NOT NULL REFERENCES runs (id),
+        step INTEGER NOT NULL,
+        time REAL NOT NULL,
+        source_id TEXT NOT NULL,
+        source_port TEXT NOT NULL,
+        target_id TEXT NOT NULL,
+        target_port TEXT NOT NULL,
+        content TEXT NOT NULL,
+        job_id TEXT,
+        correlation_id TEXT
+    );
+    CREATE TABLE IF NOT EXISTS records (
+        run_id INTEGER NOT NULL REFERENCES runs (id),
+        model_id TEXT NOT NULL,
+        time REAL NOT NULL,
+        action TEXT NOT NULL,
+        subject TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS kpis (
+        run_id INTEGER NOT NULL REFERENCES runs (id),
+        model_id TEXT,
+        name TEXT NOT NULL,
+        time REAL NOT NULL,
+        value REAL NOT NULL
+    );
+";
+
+/// A writer of a single simulation run into a SQLite file.
+#[derive(Debug)]
+pub struct SqliteWriter {
+    connection: Connection,
+    run_id: i64,
+    // The steps executed through the writer
+    steps: usize,
+    // The records already written, by model ID
+    written_records: HashMap<String, usize>,
+}
+
+impl SqliteWriter {
+    /// Open (or create) the SQLite file, and add a run for the simulation.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        simulation: &Simulation,
+        label: &str,
+    ) -> Result<Self, SimulationError> {
+        Self::new(Connection::open(path)?, simulation, label)
+    }
+
+    /// An in-memory SQLite database, for tests and transient analyses.
+    pub fn open_in_memory(simulation: &Simulation, label: &str) -> Result<Self, SimulationError> {
+        Self::new(Connection::open_in_memory()?, simulation, label)
+    }
+
+    fn new(
+        connection: Connection,
+        simulation: &Simulation,
+        label: &str,
+    ) -> Result<Self, SimulationError> {
+        connection.execute_batch(SCHEMA)?;
+        connection.execute(
+            "INSERT INTO runs (label, version, rng_seed, config_hash) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                label,
+                env!("CARGO_PKG_VERSION"),
+                // SQLite integers are signed, so seeds are stored by bits
+                simulation.get_rng_seed().map(|seed| seed as i64),
+                manifest::config_hash(
+                    &simulation.models,
+                    &simulation.connectors,
+                    simulation.services.globals()
+                )?,
+            ],
+        )?;
+        let run_id = connection.last_insert_rowid();
+        Ok(Self {
+            connection,
+            run_id,
+            steps: 0,
+            written_records: HashMap::new(),
+        })
+    }
+
+    pub fn run_id(&self) -> i64 {
+        self.run_id
+    }
+
+    /// The underlying connection, for queries over the written output.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Write messages, as those of the next step.
+    pub fn write_messages(&mut self, messages: &[Message]) -> Result<(), SimulationError> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO messages (run_id, step, time, source_id, source_port, target_id, \
+                 target_port, content, job_id, correlation_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for message in messages {
+                statement.execute(params![
+                    self.run_id,
+                    self.steps as i64,
+                    message.time(),
+                    message.source_id(),
+                    message.source_port(),
+                    message.target_id(),
+                    message.target_port(),
+                    message.content(),
+                    message.job_id().map(ToString::to_string),
+                    message.correlation_id(),
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Write the model records added since the last write.
+    pub fn write_records(&mut self, simulation: &Simulation) -> Result<(), SimulationError> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO records (run_id, model_id, time, action, subject) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for model in &simulation.models {
+                let written = self
+                    .written_records
+                    .entry(model.id().to_string())
+                    .or_insert(0);
+                // Records cleared since the last write are written anew
+                let records = model.records();
+                let unwritten = records.get(*written..).unwrap_or(&records[..]);
+                for record in unwritten {
+                    statement.execute(params![
+                        self.run_id,
+                        model.id(),
+                        record.time,
+                        record.action,
+                        record.subject,
+                    ])?;
+                }
+                *written = records.len();
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Write a single KPI, at the simulation time.
+    pub fn write_kpi(
+        &self,
+        model_id: Option<&str>,
+        name: &str,
+        time: f64,
+        value: f64,
+    ) -> Result<(), SimulationError> {
+        self.connection.execute(
+            "INSERT INTO kpis (run_id, model_id, name, time, value) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![self.run_id, model_id, name, time, value],
+        )?;
+        Ok(())
+    }
+
+    /// Write the standard KPIs as of the current simulation time - the
+    /// step count, the utilization and busy time of models with service,
+    /// the arrival count and throughput of sinks, and the queue depth
+    /// high-watermarks.  The run's end time and step count are updated.
+    pub fn write_kpis(&mut self, simulation: &Simulation) -> Result<(), SimulationError> {
+        let time = simulation.get_global_time();
+        let stats = simulation.execution_stats();
+        let mut kpis: Vec<(Option<&str>, &str, f64)> = vec![(None, "steps", stats.steps as f64)];
+        for model in &simulation.models {
+            if let Some(utilization) = model.utilization(time) {
+                kpis.push((Some(model.id()), "busy_time", utilization.busy_time));
+                if let Some(value) = utilization.utilization {
+                    kpis.push((Some(model.id()), "utilization", value));
+                }
+            }
+            if let Some(summary) = model.sink_summary(time) {
+                kpis.push((Some(model.id()), "sink_count", summary.count as f64));
+                kpis.push((Some(model.id()), "throughput", summary.throughput));
+                if let Some(value) = summary.mean_interarrival {
+                    kpis.push((Some(model.id()), "mean_interarrival", value));
+                }
+            }
+        }
+        stats
+            .queue_watermarks
+            .iter()
+            .for_each(|(model_id, watermark)| {
+                kpis.push((
+                    Some(model_id.as_str()),
+                    "max_queue_depth",
+                    watermark.depth as f64,
+                ))
+            });
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO kpis (run_id, model_id, name, time, value) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (model_id, name, value) in kpis {
+                statement.execute(params![self.run_id, model_id, name, time, value])?;
+            }
+            transaction.execute(
+                "UPDATE runs SET end_time = ?1, steps = ?2 WHERE id = ?3",
+                params![time, stats.steps as i64, self.run_id],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Execute simulation steps until a global time has been exceeded, as
+    /// with `Simulation::step_until`, writing the messages of each step as
+    /// it completes.  The new model records are written at the end of the
+    /// call.  The number of written messages is returned.
+    pub fn step_until(
+        &mut self,
+        simulation: &mut Simulation,
+        until: f64,
+    ) -> Result<usize, SimulationError> {
+        simulation.begin_steps()?;
+        let mut written = 0;
+        let result = loop {
+            if let Err(error) = simulation.step_events() {
+                break Err(error);
+            }
+            if simulation.services.global_time() >= until {
+                break Ok(written);
+            }
+            let messages = simulation.reported_messages();
+            if let Err(error) = self.write_messages(&messages) {
+                break Err(error);
+            }
+            written += messages.len();
+        };
+        simulation.end_steps();
+        let written = result?;
+        self.write_records(simulation)?;
+        Ok(written)
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::Model;
+use crate::utils::errors::SimulationError;
+
+/// A single field changed by a model state transition.  The path is a JSON
+/// Pointer into the serialized model.  A `None` before (after) value
+/// indicates the field was added (removed) by the transition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChange {
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Serialized model states, captured before and after the latest
+/// simulation step.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StateSnapshots {
+    before: HashMap<String, Value>,
+    after: HashMap<String, Value>,
+}
+
+/// Serialize the state of every model, keyed by model ID.
+pub(crate) fn snapshot(models: &[Model]) -> Result<HashMap<String, Value>, SimulationError> {
+    models
+        .iter()
+        .map(|model| {
+            serde_json::to_value(model)
+                .map(|value| (model.id().to_string(), value))
+                .map_err(|_| SimulationError::SerializationError)
+        })
+        .collect()
+}
+
+impl StateSnapshots {
+    pub(crate) fn capture_before(&mut self, models: &[Model]) -> Result<(), SimulationError> {
+        self.before = snapshot(models)?;
+        self.after = HashMap::new();
+        Ok(())
+    }
+
+    pub(crate) fn capture_after(&mut self, models: &[Model]) -> Result<(), SimulationError> {
+        self.after = snapshot(models)?;
+        Ok(())
+    }
+
+    pub(crate) fn diff(&self, model_id: &str) -> Vec<StateChange> {
+        let mut changes = Vec::new();
+        diff_values(
+            String::new(),
+            self.before.get(model_id),
+            self.after.get(model_id),
+            &mut changes,
+        );
+        changes
+    }
+}
+
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Apply a state change to a serialized value, as the inverse of
+/// `diff_values`.  Array element removals truncate the array, so the
+/// changes of a diff may be applied in order.
+pub(crate) fn apply_change(value: &mut Value, change: &StateChange) {
+    let mut tokens: Vec<String> = change
+        .path
+        .split('/')
+        .skip(1)
+        .map(unescape_pointer_token)
+        .collect();
+    let last_token = match tokens.pop() {
+        Some(token) => token,
+        None => {
+            *value = change.after.clone().unwrap_or(Value::Null);
+            return;
+        }
+    };
+    let parent = tokens.iter().try_fold(value, |node, token| match node {
+        Value::Object(map) => map.get_mut(token),
+        Value::Array(array) => token
+            .parse::<usize>()
+            .ok()
+            .and_then(move |index| array.get_mut(index)),
+        _ => None,
+    });
+    match (parent, &change.after) {
+        (Some(Value::Object(map)), Some(after)) => {
+            map.insert(last_token, after.clone());
+        }
+        (Some(Value::Object(map)), None) => {
+            map.remove(&last_token);
+        }
+        (Some(Value::Array(array)), after) => {
+            if let Ok(index) = last_token.parse::<usize>() {
+                match after {
+                    Some(after) if index < array.len() => array[index] = after.clone(),
+                    Some(after) => array.push(after.clone()),
+                    None => array.truncate(index),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Recursively compare two JSON values, collecting the changed leaves.
+/// Objects are compared key by key, arrays index by index, and any other
+/// differing values are reported as a whole.
+pub(crate) fn diff_values(
+    path: String,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<StateChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(before_map)), Some(Value::Object(after_map))) => {
+            let mut keys: Vec<&String> = before_map.keys().chain(after_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            keys.into_iter().for_each(|key| {
+                diff_values(
+                    format!["{}/{}", path, escape_pointer_token(key)],
+                    before_map.get(key),
+                    after_map.get(key),
+                    changes,
+                )
+            });
+        }
+        (Some(Value::Array(before_array)), Some(Value::Array(after_array))) => {
+            (0..usize::max(before_array.len(), after_array.len())).for_each(|index| {
+                diff_values(
+                    format!["{}/{}", path, index],
+                    before_array.get(index),
+                    after_array.get(index),
+                    changes,
+                )
+            });
+        }
+        (before, after) => {
+            if before != after {
+                changes.push(StateChange {
+                    path,
+                    before: before.cloned(),
+                    after: after.cloned(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Stop conditions end a run on domain conditions, rather than a fixed
+//! simulation time or step count - e.g. after 1000 messages, once a gate
+//! closes, once the throughput of a sink settles, or after a wall-clock
+//! budget.  Conditions compose with `Any` and `All`, and are serializable,
+//! for experiment and scenario configurations.  For example, "stop once
+//! the throughput of sink-01 settles, or after 5 seconds" is:
+//!
+//! ```yaml
+//! any:
+//!   - steadyState:
+//!       modelId: sink-01
+//!       window: 100
+//!       tolerance: 0.01
+//!   - wallClock: 5000.0
+//! ```
+//!
+//! Arbitrary predicates over the simulation are supported through
+//! `Simulation::step_until_condition`.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::Simulation;
+use crate::models::Reportable;
+use crate::utils::errors::SimulationError;
+use crate::utils::wall_clock_time;
+
+/// A condition ending a run, checked after every step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StopCondition {
+    /// The simulation time reaches the given time
+    Time(f64),
+    /// The messages sent during the run reach the count - all messages, or
+    /// only the messages of a source model
+    #[serde(rename_all = "camelCase")]
+    MessageCount {
+        count: usize,
+        #[serde(default)]
+        model_id: Option<String>,
+    },
+    /// The status of a model matches the given status
+    #[serde(rename_all = "camelCase")]
+    ModelStatus { model_id: String, status: String },
+    /// The throughput of a sink, or the utilization of a processor, varies
+    /// by at most the relative tolerance over the most recent steps
+    #[serde(rename_all = "camelCase")]
+    SteadyState {
+        model_id: String,
+        window: usize,
+        tolerance: f64,
+    },
+    /// The wall-clock time of the run reaches the budget, in milliseconds
+    WallClock(f64),
+    /// Any of the conditions holds
+    Any(Vec<StopCondition>),
+    /// All of the conditions hold
+    All(Vec<StopCondition>),
+}
+
+/// The run state of a stop condition - the messages counted, and the
+/// metric window of steady state detection.
+#[derive(Debug, Clone)]
+enum MonitorState {
+    Stateless,
+    Messages(usize),
+    Window(VecDeque<f64>),
+    Composite(Vec<StopMonitor>),
+}
+
+/// A stop condition under evaluation, over the steps of a single run.
+#[derive(Debug, Clone)]
+pub(crate) struct StopMonitor {
+    condition: StopCondition,
+    started_at: f64,
+    state: MonitorState,
+}
+
+impl StopMonitor {
+    pub(crate) fn new(condition: &StopCondition) -> Self {
+        let state = match condition {
+            StopCondition::MessageCount { .. } => MonitorState::Messages(0),
+            StopCondition::SteadyState { .. } => MonitorState::Window(VecDeque::new()),
+            StopCondition::Any(conditions) | StopCondition::All(conditions) => {
+                MonitorState::Composite(conditions.iter().map(StopMonitor::new).collect())
+            }
+            _ => MonitorState::Stateless,
+        };
+        Self {
+            condition: condition.clone(),
+            started_at: wall_clock_time(),
+            state,
+        }
+    }
+
+    /// Observe the simulation after a step, returning whether the run
+    /// should stop.  Every nested condition observes every step, so message
+    /// counts and steady state windows stay current.
+    pub(crate) fn observe(&mut self, simulation: &Simulation) -> Result<bool, SimulationError> {
+        let time = simulation.get_global_time();
+        match (&self.condition, &mut self.state) {
+            (StopCondition::Time(until), _) => Ok(time >= *until),
+            (StopCondition::MessageCount { count, model_id }, MonitorState::Messages(sent)) => {
+                *sent += simulation
+                    .get_messages()
+                    .iter()
+                    .filter(|message| {
+                        model_id
+                            .as_ref()
+                            .is_none_or(|model_id| message.source_id() == model_id)
+                    })
+                    .count();
+                Ok(*sent >= *count)
+            }
+            (StopCondition::ModelStatus { model_id, status }, _) => {
+                Ok(simulation.model(model_id)?.status() == *status)
+            }
+            (
+                StopCondition::SteadyState {
+                    model_id,
+                    window,
+                    tolerance,
+                },
+                MonitorState::Window(values),
+            ) => {
+                let model = simulation.model(model_id)?;
+                let value = model
+                    .sink_summary(time)
+                    .map(|summary| summary.throughput)
+                    .or_else(|| {
+                        model
+                            .utilization(time)
+                            .and_then(|summary| summary.utilization)
+                    });
+                let value = match value {
+                    Some(value) if value.is_finite() => value,
+                    _ => return Ok(false),
+                };
+                values.push_back(value);
+                if values.len() > *window {
+                    values.pop_front();
+                }
+                if *window == 0 || values.len() < *window {
+                    return Ok(false);
+                }
+                let (min, max) = values
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                        (min.min(*value), max.max(*value))
+                    });
+                let mean = values.iter().sum::<f64>() / values.len() as f64;
+                Ok(max - min <= tolerance * mean.abs())
+            }
+            (StopCondition::WallClock(budget), _) => {
+                Ok(wall_clock_time() - self.started_at >= *budget)
+            }
+            (StopCondition::Any(_), MonitorState::Composite(monitors)) => {
+                let stops = monitors
+                    .iter_mut()
+                    .map(|monitor| monitor.observe(simulation))
+                    .collect::<Result<Vec<bool>, SimulationError>>()?;
+                Ok(stops.into_iter().any(|stop| stop))
+            }
+            (StopCondition::All(_), MonitorState::Composite(monitors)) => {
+                let stops = monitors
+                    .iter_mut()
+                    .map(|monitor| monitor.observe(simulation))
+                    .collect::<Result<Vec<bool>, SimulationError>>()?;
+                Ok(stops.into_iter().all(|stop| stop))
+            }
+            _ => Err(SimulationError::PrerequisiteCalcError),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Subscriptions deliver simulation events to the host application over
+//! `std::sync::mpsc` channels, as the events occur.  UI and analytics
+//! consumers then run independently of the stepping loop - the simulation
+//! never blocks on a subscriber, and subscribers whose receivers are dropped
+//! are removed on the next delivery.
+
+use std::sync::mpsc::Sender;
+
+use super::coupling::Message;
+
+/// The kinds of simulation events available for subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A message was routed from a model output port to a model input port
+    MessageRouted,
+    /// A message was injected into the simulation
+    InputInjected,
+    /// A model executed an internal state transition
+    InternalTransition,
+    /// A simulation step completed
+    StepCompleted,
+}
+
+/// A simulation event, as delivered to subscribers.
+#[derive(Debug, Clone)]
+pub enum SimulationEvent {
+    MessageRouted(Message),
+    InputInjected(Message),
+    InternalTransition { model_id: String, time: f64 },
+    StepCompleted { time: f64, message_count: usize },
+}
+
+impl SimulationEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            SimulationEvent::MessageRouted(_) => EventKind::MessageRouted,
+            SimulationEvent::InputInjected(_) => EventKind::InputInjected,
+            SimulationEvent::InternalTransition { .. } => EventKind::InternalTransition,
+            SimulationEvent::StepCompleted { .. } => EventKind::StepCompleted,
+        }
+    }
+}
+
+/// The event subscribers of a simulation.  Subscriptions are tied to a
+/// single simulation instance, and so are not carried over to clones.
+#[derive(Debug, Default)]
+pub(crate) struct Subscriptions {
+    subscribers: Vec<(EventKind, Sender<SimulationEvent>)>,
+}
+
+impl Clone for Subscriptions {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Subscriptions {
+    pub(crate) fn subscribe(&mut self, kind: EventKind, sender: Sender<SimulationEvent>) {
+        self.subscribers.push((kind, sender));
+    }
+
+    /// Whether any subscriber is interested in the kind of event, so event
+    /// construction can be skipped otherwise.
+    pub(crate) fn wants(&self, kind: EventKind) -> bool {
+        self.subscribers
+            .iter()
+            .any(|(subscribed_kind, _)| *subscribed_kind == kind)
+    }
+
+    /// Deliver the event to every subscriber of its kind, dropping the
+    /// subscribers that have disconnected.
+    pub(crate) fn publish(&mut self, event: SimulationEvent) {
+        let kind = event.kind();
+        self.subscribers.retain(|(subscribed_kind, sender)| {
+            *subscribed_kind != kind || sender.send(event.clone()).is_ok()
+        });
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A one-line overview of a single model in a simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSummary {
+    pub id: String,
+    pub model_type: String,
+    pub status: String,
+}
+
+/// The simulation summary is a quick, human-readable overview of a
+/// simulation - for inspection in CLIs and logs.  The summary is also
+/// serializable, for structured logging.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationSummary {
+    pub global_time: f64,
+    pub rng_seed: Option<u64>,
+    pub model_counts: BTreeMap<String, usize>,
+    pub connector_count: usize,
+    pub message_count: usize,
+    pub models: Vec<ModelSummary>,
+}
+
+impl fmt::Display for SimulationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Simulation summary")?;
+        writeln!(f, "  Global time: {:.3}", self.global_time)?;
+        match self.rng_seed {
+            Some(seed) => writeln!(f, "  RNG seed: {}", seed)?,
+            None => writeln!(f, "  RNG seed: custom")?,
+        }
+        let model_counts: Vec<String> = self
+            .model_counts
+            .iter()
+            .map(|(model_type, count)| format!["{}: {}", model_type, count])
+            .collect();
+        writeln!(
+            f,
+            "  Models: {} ({})",
+            self.models.len(),
+            model_counts.join(", ")
+        )?;
+        writeln!(f, "  Connectors: {}", self.connector_count)?;
+        writeln!(f, "  Active messages: {}", self.message_count)?;
+        write!(f, "  Model status:")?;
+        self.models.iter().try_for_each(|model| {
+            write!(
+                f,
+                "\n    {} ({}): {}",
+                model.id, model.model_type, model.status
+            )
+        })
+    }
+}
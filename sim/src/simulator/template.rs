@@ -0,0 +1,298 @@
+//! Model templates expand a parameterized sub-network (e.g. a queue and
+//! server pair) into many instances, in place of hand-written copies of
+//! the same models and connectors.  The models of instance `n` (from 1) of
+//! a template are prefixed with the template ID prefix and the instance
+//! number - `processor-01` of the second `station` instance is
+//! `station-02-processor-01` - and the template connectors between the
+//! template models are rewritten to the instance models.  Template
+//! connectors to other models (e.g. a shared sink) are kept as-is.
+//!
+//! Model and connector fields are parameterized with `{{name}}`
+//! placeholders.  A field consisting of a placeholder alone takes the
+//! parameter value, of any type, and placeholders within text are replaced
+//! by the parameter value as text.  Every instance has the `index` (the
+//! instance number) and `instance` (e.g. `station-02`) parameters, along
+//! with the template parameters, overridden per instance.  For example:
+//!
+//! ```yaml
+//! template:
+//!   idPrefix: station
+//!   parameters:
+//!     serviceRate: 0.5
+//!   models:
+//!     - id: processor-01
+//!       type: Processor
+//!       serviceTime:
+//!         exp:
+//!           lambda: "{{serviceRate}}"
+//!       ...
+//!   connectors:
+//!     - id: processed
+//!       sourceID: processor-01
+//!       targetID: sink-01
+//!       sourcePort: processed
+//!       targetPort: job
+//! count: 3
+//! parameters:
+//!   - serviceRate: 0.4
+//! ```
+//!
+//! Templates are instantiated by `Simulation::instantiate_template`, or at
+//! deserialization, as entries of model configurations loaded by
+//! `Simulation::load_config`.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use super::Connector;
+use crate::models::Model;
+use crate::utils::errors::SimulationError;
+
+const PLACEHOLDER_OPEN: &str = "{{";
+const PLACEHOLDER_CLOSE: &str = "}}";
+
+/// A parameterized sub-network of models and connectors, with
+/// template-local model IDs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTemplate {
+    pub id_prefix: String,
+    #[serde(default)]
+    pub parameters: BTreeMap<String, Value>,
+    pub models: Vec<Value>,
+    #[serde(default)]
+    pub connectors: Vec<Value>,
+}
+
+impl ModelTemplate {
+    /// A template of the model configurations and connectors, without
+    /// parameters beyond the built-in `index` and `instance`.  Model state is
+    /// left out, so instances start in their initial state.
+    pub fn from_models(
+        id_prefix: &str,
+        models: &[Model],
+        connectors: &[Connector],
+    ) -> Result<Self, SimulationError> {
+        Ok(Self {
+            id_prefix: id_prefix.to_string(),
+            parameters: BTreeMap::new(),
+            models: models
+                .iter()
+                .map(|model| {
+                    let mut model = serde_json::to_value(model)?;
+                    if let Value::Object(fields) = &mut model {
+                        fields.remove("state");
+                    }
+                    Ok(model)
+                })
+                .collect::<Result<_, SimulationError>>()?,
+            connectors: connectors
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn with_parameter(mut self, name: &str, default: Value) -> Self {
+        self.parameters.insert(name.to_string(), default);
+        self
+    }
+
+    /// The ID prefix of an instance, e.g. `station-02`.
+    pub fn instance_prefix(&self, index: usize) -> String {
+        format!["{}-{:02}", self.id_prefix, index]
+    }
+
+    /// The ID of a template model in an instance, e.g.
+    /// `station-02-processor-01`.
+    pub fn instance_id(&self, index: usize, model_id: &str) -> String {
+        format!["{}-{}", self.instance_prefix(index), model_id]
+    }
+
+    /// Expand an instance of the template, with the parameter overrides.
+    pub fn instantiate(
+        &self,
+        index: usize,
+        overrides: &BTreeMap<String, Value>,
+    ) -> Result<(Vec<Model>, Vec<Connector>), SimulationError> {
+        let mut parameters = self.parameters.clone();
+        parameters.extend(overrides.clone());
+        parameters.insert(String::from("index"), Value::from(index));
+        parameters.insert(
+            String::from("instance"),
+            Value::from(self.instance_prefix(index)),
+        );
+        let mut model_ids: HashSet<String> = HashSet::new();
+        let models = self
+            .models
+            .iter()
+            .map(|model| {
+                let mut model = substitute(model, &parameters)?;
+                let model_id = field(&model, "id")?;
+                model["id"] = Value::from(self.instance_id(index, &model_id));
+                model_ids.insert(model_id);
+                Ok(serde_json::from_value(model)?)
+            })
+            .collect::<Result<Vec<Model>, SimulationError>>()?;
+        let connectors = self
+            .connectors
+            .iter()
+            .map(|connector| {
+                let mut connector = substitute(connector, &parameters)?;
+                let connector_id = field(&connector, "id")?;
+                connector["id"] = Value::from(self.instance_id(index, &connector_id));
+                ["sourceID", "targetID"].iter().try_for_each(|key| {
+                    let model_id = field(&connector, key)?;
+                    if model_ids.contains(&model_id) {
+                        connector[*key] = Value::from(self.instance_id(index, &model_id));
+                    }
+                    Ok::<(), SimulationError>(())
+                })?;
+                Ok(serde_json::from_value(connector)?)
+            })
+            .collect::<Result<Vec<Connector>, SimulationError>>()?;
+        Ok((models, connectors))
+    }
+}
+
+/// The instances of a template - `count` instances, numbered from 1, with
+/// the parameter overrides of each instance, in instance order.  Instances
+/// beyond the overrides take the template parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInstances {
+    pub template: ModelTemplate,
+    pub count: usize,
+    #[serde(default)]
+    pub parameters: Vec<BTreeMap<String, Value>>,
+}
+
+impl TemplateInstances {
+    /// Expand the instances into their models and connectors.
+    pub fn expand(&self) -> Result<(Vec<Model>, Vec<Connector>), SimulationError> {
+        let no_overrides = BTreeMap::new();
+        let mut models = Vec::new();
+        let mut connectors = Vec::new();
+        for index in 1..=self.count {
+            let overrides = self.parameters.get(index - 1).unwrap_or(&no_overrides);
+            let (instance_models, instance_connectors) =
+                self.template.instantiate(index, overrides)?;
+            models.extend(instance_models);
+            connectors.extend(instance_connectors);
+        }
+        Ok((models, connectors))
+    }
+}
+
+/// An entry of a model configuration - a model, or template instances,
+/// expanded at deserialization.  Entries with a `template` field are
+/// template instances.
+#[derive(Clone)]
+pub enum ModelEntry {
+    Model(Model),
+    Instances {
+        models: Vec<Model>,
+        connectors: Vec<Connector>,
+    },
+}
+
+impl<'de> Deserialize<'de> for ModelEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        if value.get("template").is_none() {
+            return serde_json::from_value(value)
+                .map(ModelEntry::Model)
+                .map_err(serde::de::Error::custom);
+        }
+        let instances: TemplateInstances =
+            serde_json::from_value(value).map_err(serde::de::Error::custom)?;
+        let (models, connectors) = instances.expand().map_err(serde::de::Error::custom)?;
+        Ok(ModelEntry::Instances { models, connectors })
+    }
+}
+
+/// Split model configuration entries into the models, and the connectors
+/// of any template instances.
+pub fn split_entries(entries: Vec<ModelEntry>) -> (Vec<Model>, Vec<Connector>) {
+    let mut models = Vec::new();
+    let mut connectors = Vec::new();
+    entries.into_iter().for_each(|entry| match entry {
+        ModelEntry::Model(model) => models.push(model),
+        ModelEntry::Instances {
+            models: instance_models,
+            connectors: instance_connectors,
+        } => {
+            models.extend(instance_models);
+            connectors.extend(instance_connectors);
+        }
+    });
+    (models, connectors)
+}
+
+fn field(value: &Value, key: &str) -> Result<String, SimulationError> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or(SimulationError::InvalidModelConfiguration)
+}
+
+/// Replace the parameter placeholders of the value, recursively.
+fn substitute(
+    value: &Value,
+    parameters: &BTreeMap<String, Value>,
+) -> Result<Value, SimulationError> {
+    Ok(match value {
+        Value::String(text) => substitute_text(text, parameters)?,
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| substitute(value, parameters))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), substitute(value, parameters)?)))
+                .collect::<Result<_, SimulationError>>()?,
+        ),
+        value => value.clone(),
+    })
+}
+
+fn substitute_text(
+    text: &str,
+    parameters: &BTreeMap<String, Value>,
+) -> Result<Value, SimulationError> {
+    let parameter = |name: &str| {
+        parameters
+            .get(name.trim())
+            .ok_or_else(|| SimulationError::UnknownTemplateParameter(name.trim().to_string()))
+    };
+    // A placeholder alone takes the parameter value, of any type
+    if let Some(name) = text
+        .strip_prefix(PLACEHOLDER_OPEN)
+        .and_then(|rest| rest.strip_suffix(PLACEHOLDER_CLOSE))
+        .filter(|name| !name.contains(PLACEHOLDER_OPEN))
+    {
+        return Ok(parameter(name)?.clone());
+    }
+    let mut substituted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(PLACEHOLDER_OPEN) {
+        let end = match rest[start..].find(PLACEHOLDER_CLOSE) {
+            Some(end) => start + end,
+            None => break,
+        };
+        substituted.push_str(&rest[..start]);
+        match parameter(&rest[start + PLACEHOLDER_OPEN.len()..end])? {
+            Value::String(value) => substituted.push_str(value),
+            value => substituted.push_str(&value.to_string()),
+        }
+        rest = &rest[end + PLACEHOLDER_CLOSE.len()..];
+    }
+    substituted.push_str(rest);
+    Ok(Value::from(substituted))
+}
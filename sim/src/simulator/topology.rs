@@ -0,0 +1,68 @@
+//! Topology helpers generate the connectors of common wiring patterns -
+//! pipelines, stars, and meshes - in place of hand-written connector lists.
+//! Generated connector IDs take the form `{source ID}-{target ID}`, so the
+//! connectors of separate patterns may be concatenated, as long as each
+//! model pair is only wired once.
+
+use super::coupling::Connector;
+
+fn connect(source_id: &str, target_id: &str, source_port: &str, target_port: &str) -> Connector {
+    Connector::new(
+        format!["{}-{}", source_id, target_id],
+        source_id.to_string(),
+        target_id.to_string(),
+        source_port.to_string(),
+        target_port.to_string(),
+    )
+}
+
+/// Connect the models in sequence, from the `source_port` of each model to
+/// the `target_port` of the next model.
+pub fn pipeline(model_ids: &[&str], source_port: &str, target_port: &str) -> Vec<Connector> {
+    model_ids
+        .windows(2)
+        .map(|pair| connect(pair[0], pair[1], source_port, target_port))
+        .collect()
+}
+
+/// Connect the `source_port` of the hub to the `target_port` of every leaf,
+/// as a broadcast or fan-out.
+pub fn star(
+    hub_id: &str,
+    leaf_ids: &[&str],
+    source_port: &str,
+    target_port: &str,
+) -> Vec<Connector> {
+    leaf_ids
+        .iter()
+        .map(|leaf_id| connect(hub_id, leaf_id, source_port, target_port))
+        .collect()
+}
+
+/// Connect the `source_port` of every leaf to the `target_port` of the hub,
+/// as a fan-in.
+pub fn gather(
+    leaf_ids: &[&str],
+    hub_id: &str,
+    source_port: &str,
+    target_port: &str,
+) -> Vec<Connector> {
+    leaf_ids
+        .iter()
+        .map(|leaf_id| connect(leaf_id, hub_id, source_port, target_port))
+        .collect()
+}
+
+/// Connect the `source_port` of every model to the `target_port` of every
+/// other model.
+pub fn mesh(model_ids: &[&str], source_port: &str, target_port: &str) -> Vec<Connector> {
+    model_ids
+        .iter()
+        .flat_map(|source_id| {
+            model_ids
+                .iter()
+                .filter(move |target_id| *target_id != source_id)
+                .map(move |target_id| connect(source_id, target_id, source_port, target_port))
+        })
+        .collect()
+}
@@ -0,0 +1,282 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use super::coupling::Connector;
+use super::routing::expand_connectors;
+use crate::models::Model;
+
+/// The connectivity of a single model - the number of distinct models
+/// sending messages to it (fan-in), and receiving messages from it
+/// (fan-out).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDegree {
+    pub id: String,
+    pub fan_in: usize,
+    pub fan_out: usize,
+}
+
+/// Graph metrics of the simulation topology, with the models as nodes and
+/// the connectors as directed edges.  The connected components disregard
+/// the edge direction.  Only the strongly connected components with a
+/// cycle (more than one model, or a model connected to itself) are
+/// reported, and each has one representative feedback loop - the shortest
+/// cycle through its first model.  Zero-delay loops, where messages cycle
+/// without advancing the simulation time, can only occur along feedback
+/// loops.  The longest acyclic path disregards the connectors within the
+/// strongly connected components, and its depth is the number of
+/// connections along the path.  Models and components are in model order,
+/// and connectors referencing unknown models are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologyReport {
+    pub models: Vec<ModelDegree>,
+    pub connected_components: Vec<Vec<String>>,
+    pub strongly_connected_components: Vec<Vec<String>>,
+    pub feedback_loops: Vec<Vec<String>>,
+    pub longest_acyclic_path: Vec<String>,
+    pub depth: usize,
+}
+
+/// The directed model graph, as adjacency sets of model indices.
+struct Graph {
+    successors: Vec<BTreeSet<usize>>,
+    predecessors: Vec<BTreeSet<usize>>,
+}
+
+impl Graph {
+    fn new(models: &[Model], connectors: &[Connector]) -> Self {
+        let indices: HashMap<&str, usize> = models
+            .iter()
+            .enumerate()
+            .map(|(index, model)| (model.id(), index))
+            .collect();
+        let mut graph = Self {
+            successors: vec![BTreeSet::new(); models.len()],
+            predecessors: vec![BTreeSet::new(); models.len()],
+        };
+        // Connector patterns connect every pair of matching models
+        expand_connectors(models, connectors)
+            .iter()
+            .for_each(|connector| {
+                if let (Some(source), Some(target)) = (
+                    indices.get(connector.source_id()),
+                    indices.get(connector.target_id()),
+                ) {
+                    graph.successors[*source].insert(*target);
+                    graph.predecessors[*target].insert(*source);
+                }
+            });
+        graph
+    }
+
+    fn len(&self) -> usize {
+        self.successors.len()
+    }
+
+    /// The weakly connected components, by breadth-first search.
+    fn connected_components(&self) -> Vec<Vec<usize>> {
+        let mut component_of = vec![None; self.len()];
+        let mut components = Vec::new();
+        (0..self.len()).for_each(|start| {
+            if component_of[start].is_some() {
+                return;
+            }
+            let mut component = Vec::new();
+            let mut queue = VecDeque::from(vec![start]);
+            component_of[start] = Some(components.len());
+            while let Some(node) = queue.pop_front() {
+                component.push(node);
+                self.successors[node]
+                    .iter()
+                    .chain(self.predecessors[node].iter())
+                    .for_each(|neighbor| {
+                        if component_of[*neighbor].is_none() {
+                            component_of[*neighbor] = Some(components.len());
+                            queue.push_back(*neighbor);
+                        }
+                    });
+            }
+            component.sort_unstable();
+            components.push(component);
+        });
+        components
+    }
+
+    /// The strongly connected component of every node, by an iterative
+    /// form of Tarjan's algorithm, so deep topologies cannot overflow the
+    /// stack.  Components are numbered in reverse topological order.
+    fn strongly_connected_components(&self) -> Vec<usize> {
+        let unvisited = usize::MAX;
+        let mut index = vec![unvisited; self.len()];
+        let mut low_link = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = Vec::new();
+        let mut component_of = vec![0; self.len()];
+        let mut component_count = 0;
+        let mut next_index = 0;
+        for start in 0..self.len() {
+            if index[start] != unvisited {
+                continue;
+            }
+            // The nodes under visit, with their remaining successors
+            let mut visits: Vec<(usize, Vec<usize>)> = Vec::new();
+            index[start] = next_index;
+            low_link[start] = next_index;
+            next_index += 1;
+            stack.push(start);
+            on_stack[start] = true;
+            visits.push((
+                start,
+                self.successors[start].iter().rev().copied().collect(),
+            ));
+            while let Some((node, successors)) = visits.last_mut() {
+                let node = *node;
+                if let Some(successor) = successors.pop() {
+                    if index[successor] == unvisited {
+                        index[successor] = next_index;
+                        low_link[successor] = next_index;
+                        next_index += 1;
+                        stack.push(successor);
+                        on_stack[successor] = true;
+                        visits.push((
+                            successor,
+                            self.successors[successor].iter().rev().copied().collect(),
+                        ));
+                    } else if on_stack[successor] {
+                        low_link[node] = low_link[node].min(index[successor]);
+                    }
+                    continue;
+                }
+                visits.pop();
+                if let Some((parent, _)) = visits.last() {
+                    low_link[*parent] = low_link[*parent].min(low_link[node]);
+                }
+                if low_link[node] == index[node] {
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component_of[member] = component_count;
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component_count += 1;
+                }
+            }
+        }
+        component_of
+    }
+
+    /// The shortest cycle through a node, within its strongly connected
+    /// component, by breadth-first search.
+    fn shortest_cycle(&self, start: usize, component_of: &[usize]) -> Vec<usize> {
+        let mut parents: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::from(vec![start]);
+        while let Some(node) = queue.pop_front() {
+            for successor in &self.successors[node] {
+                if *successor == start {
+                    let mut cycle = vec![node];
+                    while let Some(parent) = cycle.last().and_then(|node| parents.get(node)) {
+                        cycle.push(*parent);
+                    }
+                    cycle.reverse();
+                    return cycle;
+                }
+                if component_of[*successor] == component_of[start]
+                    && !parents.contains_key(successor)
+                {
+                    parents.insert(*successor, node);
+                    queue.push_back(*successor);
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    /// The longest path along the connectors between strongly connected
+    /// components.  The components are numbered in reverse topological
+    /// order, so sources are processed by descending component number.
+    fn longest_acyclic_path(&self, component_of: &[usize]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by_key(|node| std::cmp::Reverse(component_of[*node]));
+        let mut lengths = vec![0; self.len()];
+        let mut parents: Vec<Option<usize>> = vec![None; self.len()];
+        order.iter().for_each(|node| {
+            self.successors[*node]
+                .iter()
+                .filter(|successor| component_of[**successor] != component_of[*node])
+                .for_each(|successor| {
+                    if lengths[*node] + 1 > lengths[*successor] {
+                        lengths[*successor] = lengths[*node] + 1;
+                        parents[*successor] = Some(*node);
+                    }
+                });
+        });
+        let end = (0..self.len()).fold(None, |longest: Option<usize>, node| match longest {
+            Some(longest) if lengths[longest] >= lengths[node] => Some(longest),
+            _ => Some(node),
+        });
+        let mut path: Vec<usize> = end.into_iter().collect();
+        while let Some(parent) = path.last().and_then(|node| parents[*node]) {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+}
+
+pub(crate) fn analyze(models: &[Model], connectors: &[Connector]) -> TopologyReport {
+    let graph = Graph::new(models, connectors);
+    let ids = |nodes: &[usize]| -> Vec<String> {
+        nodes
+            .iter()
+            .map(|node| models[*node].id().to_string())
+            .collect()
+    };
+    let component_of = graph.strongly_connected_components();
+    let mut strong_components: Vec<Vec<usize>> = Vec::new();
+    let mut component_positions: HashMap<usize, usize> = HashMap::new();
+    (0..graph.len()).for_each(|node| {
+        let position = *component_positions
+            .entry(component_of[node])
+            .or_insert_with(|| {
+                strong_components.push(Vec::new());
+                strong_components.len() - 1
+            });
+        strong_components[position].push(node);
+    });
+    let cyclic_components: Vec<Vec<usize>> = strong_components
+        .into_iter()
+        .filter(|component| {
+            component.len() > 1 || graph.successors[component[0]].contains(&component[0])
+        })
+        .collect();
+    let longest_acyclic_path = graph.longest_acyclic_path(&component_of);
+    TopologyReport {
+        models: models
+            .iter()
+            .enumerate()
+            .map(|(node, model)| ModelDegree {
+                id: model.id().to_string(),
+                fan_in: graph.predecessors[node].len(),
+                fan_out: graph.successors[node].len(),
+            })
+            .collect(),
+        connected_components: graph
+            .connected_components()
+            .iter()
+            .map(|component| ids(component))
+            .collect(),
+        strongly_connected_components: cyclic_components
+            .iter()
+            .map(|component| ids(component))
+            .collect(),
+        feedback_loops: cyclic_components
+            .iter()
+            .map(|component| ids(&graph.shortest_cycle(component[0], &component_of)))
+            .collect(),
+        depth: longest_acyclic_path.len().saturating_sub(1),
+        longest_acyclic_path: ids(&longest_acyclic_path),
+    }
+}
@@ -0,0 +1,196 @@
+//! Tracing records every model state transition - the model, the kind of
+//! transition, the ports involved, the simulation time, and the model
+//! status after the transition - for visualizing model activity over
+//! simulated time.  Traces export as Chrome `trace_event` JSON (for
+//! `chrome://tracing` or Perfetto), or as OpenTelemetry (OTLP/JSON) spans.
+//!
+//! In both exports, each transition opens a span on its model's track,
+//! named for the model status, and lasting until the model's next
+//! transition (or the end of the trace).  Simulation time maps to seconds,
+//! so a time unit spans 1,000,000 microseconds of Chrome trace time, and
+//! 1,000,000,000 nanoseconds of OTLP span time.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::observer::TransitionKind;
+
+const MICROSECONDS_PER_TIME_UNIT: f64 = 1.0e6;
+const NANOSECONDS_PER_TIME_UNIT: f64 = 1.0e9;
+
+/// A single traced model state transition.  The ports are the input ports
+/// of the delivered messages, for external transitions, and the output
+/// ports of the outgoing messages, for internal transitions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEvent {
+    pub time: f64,
+    pub model_id: String,
+    pub kind: TransitionKind,
+    pub ports: Vec<String>,
+    pub status: String,
+}
+
+/// The recorded transitions of a simulation, in execution order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trace {
+    events: Vec<TraceEvent>,
+}
+
+/// A traced transition, as a span on its model's track.
+struct Span<'a> {
+    event: &'a TraceEvent,
+    track: usize,
+    end: f64,
+}
+
+impl Trace {
+    pub(crate) fn record(&mut self, event: TraceEvent) {
+        self.events.push(event);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// The recorded transitions, in execution order.
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// The model tracks, in order of first transition, and the span of
+    /// each transition - ending at the next transition of the model, or the
+    /// end time.
+    fn spans(&self, end_time: f64) -> (Vec<&str>, Vec<Span<'_>>) {
+        let mut tracks: Vec<&str> = Vec::new();
+        let mut track_indices: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut spans: Vec<Span> = Vec::with_capacity(self.events.len());
+        let mut open: BTreeMap<usize, usize> = BTreeMap::new();
+        self.events.iter().for_each(|event| {
+            let track = *track_indices
+                .entry(event.model_id.as_str())
+                .or_insert_with(|| {
+                    tracks.push(event.model_id.as_str());
+                    tracks.len() - 1
+                });
+            if let Some(previous) = open.insert(track, spans.len()) {
+                spans[previous].end = event.time;
+            }
+            spans.push(Span {
+                event,
+                track,
+                end: end_time.max(event.time),
+            });
+        });
+        (tracks, spans)
+    }
+
+    /// The trace as Chrome `trace_event` JSON, with a thread per model.
+    /// Open spans end at the end time (e.g. the current simulation time).
+    pub fn to_chrome_trace(&self, end_time: f64) -> Value {
+        let (tracks, spans) = self.spans(end_time);
+        let mut trace_events: Vec<Value> = tracks
+            .iter()
+            .enumerate()
+            .map(|(track, model_id)| {
+                json!({
+                    "name": "thread_name",
+                    "ph": "M",
+                    "pid": 1,
+                    "tid": track + 1,
+                    "args": { "name": model_id },
+                })
+            })
+            .collect();
+        trace_events.extend(spans.iter().map(|span| {
+            json!({
+                "name": span.event.status,
+                "cat": transition_name(span.event.kind),
+                "ph": "X",
+                "pid": 1,
+                "tid": span.track + 1,
+                "ts": span.event.time * MICROSECONDS_PER_TIME_UNIT,
+                "dur": (span.end - span.event.time) * MICROSECONDS_PER_TIME_UNIT,
+                "args": {
+                    "modelId": span.event.model_id,
+                    "transition": transition_name(span.event.kind),
+                    "ports": span.event.ports,
+                    "time": span.event.time,
+                },
+            })
+        }));
+        json!({
+            "traceEvents": trace_events,
+            "displayTimeUnit": "ms",
+        })
+    }
+
+    /// The trace as OpenTelemetry spans, in the OTLP/JSON encoding of an
+    /// export request.  Every span belongs to the given trace ID, and span
+    /// IDs follow the transition order.  Open spans end at the end time.
+    pub fn to_otlp(&self, end_time: f64, trace_id: u128) -> Value {
+        let (_, spans) = self.spans(end_time);
+        let spans: Vec<Value> = spans
+            .iter()
+            .enumerate()
+            .map(|(index, span)| {
+                json!({
+                    "traceId": format!["{:032x}", trace_id],
+                    "spanId": format!["{:016x}", index + 1],
+                    "name": span.event.status,
+                    "kind": 1,
+                    "startTimeUnixNano": nanoseconds(span.event.time),
+                    "endTimeUnixNano": nanoseconds(span.end),
+                    "attributes": [
+                        string_attribute("sim.model_id", &span.event.model_id),
+                        string_attribute("sim.transition", transition_name(span.event.kind)),
+                        {
+                            "key": "sim.ports",
+                            "value": {
+                                "arrayValue": {
+                                    "values": span.event.ports.iter().map(|port| {
+                                        json!({ "stringValue": port })
+                                    }).collect::<Vec<Value>>()
+                                }
+                            }
+                        },
+                        {
+                            "key": "sim.time",
+                            "value": { "doubleValue": span.event.time }
+                        },
+                    ],
+                })
+            })
+            .collect();
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [string_attribute("service.name", "sim")],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "sim", "version": env!("CARGO_PKG_VERSION") },
+                    "spans": spans,
+                }],
+            }],
+        })
+    }
+}
+
+fn transition_name(kind: TransitionKind) -> &'static str {
+    match kind {
+        TransitionKind::Internal => "internal",
+        TransitionKind::External => "external",
+    }
+}
+
+/// OTLP/JSON encodes 64-bit integers as strings.
+fn nanoseconds(time: f64) -> String {
+    ((time * NANOSECONDS_PER_TIME_UNIT).round() as u64).to_string()
+}
+
+fn string_attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
@@ -0,0 +1,437 @@
+//! Validation checks the assembled configuration of a simulation, before
+//! messages are silently dropped at runtime - duplicate model or connector
+//! IDs, connectors referencing unknown models or ports, model ports without
+//! any connectors (orphan ports), cycles of models passing messages on
+//! without advancing the simulation time, and pending messages addressed to
+//! missing models.  Orphan ports are warnings, as they are often
+//! intentional (e.g. unused outputs), while the other problems are errors.
+//! Validation optionally reports the statistical and behavioral assumptions
+//! of the simulation and its models, for model reviews and V&V
+//! documentation.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::routing::{expand_connectors, is_pattern, matches};
+use super::services::RngStreams;
+use super::topology_analysis::analyze;
+use super::{Connector, Message};
+use crate::models::{Assumption, AssumptionTopic, Model, Reportable};
+
+/// The direction of a model port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PortDirection {
+    In,
+    Out,
+}
+
+/// Whether a validation problem prevents a sound simulation (an error), or
+/// only merits review (a warning).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidationSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem with the configuration of a simulation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum ValidationError {
+    #[serde(rename_all = "camelCase")]
+    DuplicateModelId { model_id: String },
+    #[serde(rename_all = "camelCase")]
+    DuplicateConnectorId { connector_id: String },
+    #[serde(rename_all = "camelCase")]
+    UnknownModel {
+        connector_id: String,
+        model_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    UnknownPort {
+        connector_id: String,
+        model_id: String,
+        port: String,
+        direction: PortDirection,
+    },
+    #[serde(rename_all = "camelCase")]
+    OrphanPort {
+        model_id: String,
+        port: String,
+        direction: PortDirection,
+    },
+    /// Models passing messages on to one another without advancing the
+    /// simulation time, so messages may cycle forever at a single instant
+    #[serde(rename_all = "camelCase")]
+    ZeroTimeCycle { model_ids: Vec<String> },
+    /// An in-flight or scheduled message addressed to a missing model
+    #[serde(rename_all = "camelCase")]
+    UndeliverableMessage { model_id: String },
+}
+
+impl ValidationError {
+    pub fn severity(&self) -> ValidationSeverity {
+        match self {
+            ValidationError::OrphanPort { .. } => ValidationSeverity::Warning,
+            _ => ValidationSeverity::Error,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity() == ValidationSeverity::Error
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = |direction: &PortDirection| match direction {
+            PortDirection::In => "input",
+            PortDirection::Out => "output",
+        };
+        match self {
+            ValidationError::DuplicateModelId { model_id } => {
+                write!(f, "Duplicate model ID {}", model_id)
+            }
+            ValidationError::DuplicateConnectorId { connector_id } => {
+                write!(f, "Duplicate connector ID {}", connector_id)
+            }
+            ValidationError::UnknownModel {
+                connector_id,
+                model_id,
+            } => write!(
+                f,
+                "Connector {} references the missing model {}",
+                connector_id, model_id
+            ),
+            ValidationError::UnknownPort {
+                connector_id,
+                model_id,
+                port,
+                direction: port_direction,
+            } => write!(
+                f,
+                "Connector {} references the missing {} port {} of model {}",
+                connector_id,
+                direction(port_direction),
+                port,
+                model_id
+            ),
+            ValidationError::OrphanPort {
+                model_id,
+                port,
+                direction: port_direction,
+            } => write!(
+                f,
+                "The {} port {} of model {} is not connected",
+                direction(port_direction),
+                port,
+                model_id
+            ),
+            ValidationError::ZeroTimeCycle { model_ids } => write!(
+                f,
+                "The models {} form a cycle without any time advance",
+                model_ids.join(", ")
+            ),
+            ValidationError::UndeliverableMessage { model_id } => write!(
+                f,
+                "A pending message is addressed to the missing model {}",
+                model_id
+            ),
+        }
+    }
+}
+
+/// The optional parts of a validation report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationOptions {
+    #[serde(default)]
+    pub assumptions: bool,
+}
+
+/// The assumptions of a single model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelAssumptions {
+    pub model_id: String,
+    pub model_type: String,
+    pub assumptions: Vec<Assumption>,
+}
+
+/// The assumptions of the assembled simulation - the simulation-wide
+/// assumptions (e.g. message passing), and the assumptions of each model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssumptionsReport {
+    pub simulation: Vec<Assumption>,
+    pub models: Vec<ModelAssumptions>,
+}
+
+/// The result of validating a simulation - the errors and warnings, and
+/// any optional parts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assumptions: Option<AssumptionsReport>,
+}
+
+impl ValidationReport {
+    /// Whether the configuration is free of errors (warnings aside).
+    pub fn is_valid(&self) -> bool {
+        !self.errors.iter().any(ValidationError::is_error)
+    }
+}
+
+/// The structural errors of a topology - duplicate model or connector IDs,
+/// connectors referencing missing models, and in-flight or scheduled
+/// messages addressed to missing models.
+pub(crate) fn topology_errors(
+    models: &[Model],
+    connectors: &[Connector],
+    pending_messages: &[&Message],
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let mut model_ids = HashSet::new();
+    models.iter().for_each(|model| {
+        if !model_ids.insert(model.id()) {
+            errors.push(ValidationError::DuplicateModelId {
+                model_id: model.id().to_string(),
+            });
+        }
+    });
+    let mut connector_ids = HashSet::new();
+    connectors.iter().for_each(|connector| {
+        if !connector_ids.insert(connector.id()) {
+            errors.push(ValidationError::DuplicateConnectorId {
+                connector_id: connector.id().to_string(),
+            });
+        }
+        [connector.source_id(), connector.target_id()]
+            .iter()
+            .filter(|model_id| {
+                if is_pattern(model_id) {
+                    !model_ids.iter().any(|id| matches(model_id, id))
+                } else {
+                    !model_ids.contains(*model_id)
+                }
+            })
+            .for_each(|model_id| {
+                errors.push(ValidationError::UnknownModel {
+                    connector_id: connector.id().to_string(),
+                    model_id: model_id.to_string(),
+                })
+            });
+    });
+    pending_messages
+        .iter()
+        .filter(|message| !model_ids.contains(message.target_id()))
+        .for_each(|message| {
+            errors.push(ValidationError::UndeliverableMessage {
+                model_id: message.target_id().to_string(),
+            })
+        });
+    errors
+}
+
+/// The port names of a model configuration field (`portsIn` or
+/// `portsOut`), or `None` for models without the field (e.g. custom
+/// models), whose ports are not checked.
+fn model_ports(config: &Value, field: &str) -> Option<BTreeSet<String>> {
+    fn collect(value: &Value, ports: &mut BTreeSet<String>) {
+        match value {
+            Value::String(port) => {
+                ports.insert(port.clone());
+            }
+            Value::Array(values) => values.iter().for_each(|value| collect(value, ports)),
+            Value::Object(fields) => fields.values().for_each(|value| collect(value, ports)),
+            _ => {}
+        }
+    }
+    config.get(field).map(|value| {
+        let mut ports = BTreeSet::new();
+        collect(value, &mut ports);
+        ports
+    })
+}
+
+/// The declared ports of a model in one direction, if determinable.
+type Ports = Option<BTreeSet<String>>;
+
+/// The port errors of a topology - connectors referencing missing ports,
+/// and model ports without any connectors.  Port patterns, and target
+/// ports passing the source port through, are not checked.
+fn port_errors(models: &[Model], connectors: &[Connector]) -> Vec<ValidationError> {
+    let ports: Vec<(Ports, Ports)> = models
+        .iter()
+        .map(|model| match serde_json::to_value(model) {
+            Ok(config) => (
+                model_ports(&config, "portsIn"),
+                model_ports(&config, "portsOut"),
+            ),
+            Err(_) => (None, None),
+        })
+        .collect();
+    let model_ports = |model_id: &str| {
+        models
+            .iter()
+            .position(|model| model.id() == model_id)
+            .map(|index| &ports[index])
+    };
+    let mut errors = Vec::new();
+    let mut connected: HashSet<(String, String, PortDirection)> = HashSet::new();
+    expand_connectors(models, connectors)
+        .iter()
+        .for_each(|connector| {
+            let endpoints = [
+                (
+                    connector.source_id(),
+                    connector.source_port(),
+                    PortDirection::Out,
+                ),
+                (
+                    connector.target_id(),
+                    connector.target_port(),
+                    PortDirection::In,
+                ),
+            ];
+            endpoints.iter().for_each(|(model_id, port, direction)| {
+                let known_ports = match (model_ports(model_id), direction) {
+                    (Some((ports_in, _)), PortDirection::In) => ports_in,
+                    (Some((_, ports_out)), PortDirection::Out) => ports_out,
+                    (None, _) => return,
+                };
+                // A pass-through target port is the source port
+                let port = if *direction == PortDirection::In && *port == "*" {
+                    connector.source_port()
+                } else {
+                    port
+                };
+                if is_pattern(port) {
+                    if let Some(known_ports) = known_ports {
+                        known_ports
+                            .iter()
+                            .filter(|known_port| matches(port, known_port))
+                            .for_each(|known_port| {
+                                connected.insert((
+                                    model_id.to_string(),
+                                    known_port.clone(),
+                                    *direction,
+                                ));
+                            });
+                    }
+                    return;
+                }
+                connected.insert((model_id.to_string(), port.to_string(), *direction));
+                let error = ValidationError::UnknownPort {
+                    connector_id: connector.id().to_string(),
+                    model_id: model_id.to_string(),
+                    port: port.to_string(),
+                    direction: *direction,
+                };
+                if known_ports
+                    .as_ref()
+                    .is_some_and(|known_ports| !known_ports.contains(port))
+                    && !errors.contains(&error)
+                {
+                    errors.push(error);
+                }
+            });
+        });
+    models
+        .iter()
+        .zip(ports.iter())
+        .for_each(|(model, (ports_in, ports_out))| {
+            [
+                (ports_in, PortDirection::In),
+                (ports_out, PortDirection::Out),
+            ]
+            .iter()
+            .filter_map(|(ports, direction)| ports.as_ref().map(|ports| (ports, direction)))
+            .for_each(|(ports, direction)| {
+                ports
+                    .iter()
+                    .filter(|port| {
+                        !connected.contains(&(model.id().to_string(), port.to_string(), *direction))
+                    })
+                    .for_each(|port| {
+                        errors.push(ValidationError::OrphanPort {
+                            model_id: model.id().to_string(),
+                            port: port.clone(),
+                            direction: *direction,
+                        })
+                    });
+            });
+        });
+    errors
+}
+
+/// The cycles of models passing messages on without advancing the
+/// simulation time (e.g. gateways connected in a loop).
+fn zero_time_cycles(models: &[Model], connectors: &[Connector]) -> Vec<ValidationError> {
+    let instantaneous: Vec<Model> = models
+        .iter()
+        .filter(|model| model.zero_time_transit())
+        .cloned()
+        .collect();
+    if instantaneous.is_empty() {
+        return Vec::new();
+    }
+    // Connectors to and from the other models are ignored by the analysis
+    analyze(&instantaneous, connectors)
+        .strongly_connected_components
+        .into_iter()
+        .map(|model_ids| ValidationError::ZeroTimeCycle { model_ids })
+        .collect()
+}
+
+/// All the errors and warnings of a simulation configuration.
+pub(crate) fn validation_errors(
+    models: &[Model],
+    connectors: &[Connector],
+    pending_messages: &[&Message],
+) -> Vec<ValidationError> {
+    let mut errors = topology_errors(models, connectors, pending_messages);
+    errors.extend(port_errors(models, connectors));
+    errors.extend(zero_time_cycles(models, connectors));
+    errors
+}
+
+/// The assumptions of the simulation and its models.
+pub(crate) fn assumptions_report(models: &[Model], rng_streams: RngStreams) -> AssumptionsReport {
+    let random_numbers = match rng_streams {
+        RngStreams::Shared => Assumption::new(
+            AssumptionTopic::Independence,
+            "shared",
+            "Models draw from the shared global random number generator, so the random numbers of each model depend on the event order of the whole simulation",
+        ),
+        RngStreams::PerModel => Assumption::new(
+            AssumptionTopic::Independence,
+            "per-model",
+            "Models draw from their own random number streams, so the random numbers of each model are unaffected by other models",
+        ),
+    };
+    AssumptionsReport {
+        simulation: vec![
+            Assumption::new(
+                AssumptionTopic::MessagePassing,
+                "instantaneous",
+                "Messages are delivered without transport delay or loss, in the step after they are sent",
+            ),
+            random_numbers,
+        ],
+        models: models
+            .iter()
+            .map(|model| ModelAssumptions {
+                model_id: model.id().to_string(),
+                model_type: model.model_type().to_string(),
+                assumptions: model.assumptions(),
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use super::coupling::Message;
+
+/// The level of record and message detail a model produces.  Models at the
+/// summary or none levels keep their running summaries (status, port
+/// statistics, and sink summaries), so large experiments can run lean,
+/// while a model under investigation produces full traces.  Verbosity only
+/// affects what is reported - the messages are still routed in full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Verbosity {
+    /// Keep records, and report the model's messages in full
+    #[default]
+    Full,
+    /// Keep no records, and report the model's messages without their
+    /// correlation IDs, metadata (other than job IDs and priorities), and
+    /// payloads
+    Summary,
+    /// Keep no records, and omit the model's messages from the step
+    /// outputs and the message history
+    None,
+}
+
+impl Verbosity {
+    /// The reported form of a message sent by a model at this verbosity.
+    pub(crate) fn report(&self, message: &Message) -> Option<Message> {
+        match self {
+            Self::Full => Some(message.clone()),
+            Self::Summary => Some(
+                message
+                    .clone()
+                    .with_metadata(Default::default())
+                    .with_job_id(message.job_id())
+                    .with_priority(message.priority())
+                    .with_payload(None),
+            ),
+            Self::None => None,
+        }
+    }
+}
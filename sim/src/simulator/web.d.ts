@@ -0,0 +1,208 @@
+/**
+ * A JSON string encoding a value of type `T`.  Strings returned by the web
+ * `Simulation` carry the type of their content, and remain plain strings, so
+ * they pass back into JSON parameters as-is.  A typed parsing helper moves
+ * from JSON strings to values, e.g.
+ * `const parseJson = <T>(json: JsonString<T>): T => JSON.parse(json);`.
+ */
+export type JsonString<T> = string & { readonly __jsonContent?: T };
+
+/** A serialized model configuration - the model ID and type, and the fields of the model type. */
+export interface ModelConfig {
+    id: string;
+    type: string;
+    [field: string]: unknown;
+}
+
+/** A connector between model ports.  Model IDs and the source port may be `*` patterns. */
+export interface Connector {
+    id: string;
+    sourceID: string;
+    targetID: string;
+    sourcePort: string;
+    targetPort: string;
+}
+
+/** A parameterized sub-network, with `{{name}}` placeholders in model and connector fields. */
+export interface ModelTemplate {
+    idPrefix: string;
+    parameters?: Record<string, unknown>;
+    models: ModelConfig[];
+    connectors?: Connector[];
+}
+
+/** Numbered template instances, with per-instance parameter overrides. */
+export interface TemplateInstances {
+    template: ModelTemplate;
+    count: number;
+    parameters?: Record<string, unknown>[];
+}
+
+export type Payload = { json: unknown } | { bytes: number[] };
+
+/** The JSON representation of a `Message`. */
+export interface MessageData {
+    sourceId: string;
+    sourcePort: string;
+    targetId: string;
+    targetPort: string;
+    time: number;
+    content: string;
+    /**
+     * Includes the reserved `sim.jobId` (`<source ID>/<sequence>`),
+     * `sim.correlationId`, and `sim.priority` (an integer) entries
+     */
+    metadata?: Record<string, string>;
+    payload?: Payload;
+}
+
+export interface MessageFilter {
+    sourceId?: string;
+    sourcePort?: string;
+    targetId?: string;
+    targetPort?: string;
+    start?: number;
+    end?: number;
+}
+
+export interface ModelRecord {
+    time: number;
+    action: string;
+    subject: string;
+}
+
+export interface PortActivity {
+    messagesIn: number;
+    messagesOut: number;
+    lastActivity: number | null;
+}
+
+export type PortStats = Record<string, PortActivity>;
+
+export interface SinkSummary {
+    time: number;
+    count: number;
+    firstArrival: number | null;
+    lastArrival: number | null;
+    throughput: number;
+    meanInterarrival: number | null;
+    interarrivalStdDev: number | null;
+    window: number;
+    windowCount: number;
+    windowThroughput: number;
+}
+
+export interface AuditRecord {
+    wallTime: number;
+    simulationTime: number;
+    action: string;
+    detail: string;
+}
+
+export interface ModelDegree {
+    id: string;
+    fanIn: number;
+    fanOut: number;
+}
+
+export interface TopologyReport {
+    models: ModelDegree[];
+    connectedComponents: string[][];
+    stronglyConnectedComponents: string[][];
+    feedbackLoops: string[][];
+    longestAcyclicPath: string[];
+    depth: number;
+}
+
+export interface Assumption {
+    topic: string;
+    value: string;
+    description: string;
+}
+
+export interface ModelAssumptions {
+    modelId: string;
+    modelType: string;
+    assumptions: Assumption[];
+}
+
+export interface AssumptionsReport {
+    simulation: Assumption[];
+    models: ModelAssumptions[];
+}
+
+export type PortDirection = "in" | "out";
+
+export type ValidationError =
+    | { kind: "duplicateModelId"; modelId: string }
+    | { kind: "duplicateConnectorId"; connectorId: string }
+    | { kind: "unknownModel"; connectorId: string; modelId: string }
+    | { kind: "unknownPort"; connectorId: string; modelId: string; port: string; direction: PortDirection }
+    | { kind: "orphanPort"; modelId: string; port: string; direction: PortDirection }
+    | { kind: "zeroTimeCycle"; modelIds: string[] }
+    | { kind: "undeliverableMessage"; modelId: string };
+
+export interface ValidationReport {
+    errors: ValidationError[];
+    assumptions?: AssumptionsReport;
+}
+
+export type StopCondition =
+    | { time: number }
+    | { messageCount: { count: number; modelId?: string } }
+    | { modelStatus: { modelId: string; status: string } }
+    | { steadyState: { modelId: string; window: number; tolerance: number } }
+    | { wallClock: number }
+    | { any: StopCondition[] }
+    | { all: StopCondition[] };
+
+export type TransitionKind = "internal" | "external";
+
+export interface TraceEvent {
+    time: number;
+    modelId: string;
+    kind: TransitionKind;
+    ports: string[];
+    status: string;
+}
+
+/** A Chrome `trace_event` document, with a thread per model. */
+export interface ChromeTrace {
+    traceEvents: Record<string, unknown>[];
+    displayTimeUnit: string;
+}
+
+export interface StateChange {
+    path: string;
+    before: unknown | null;
+    after: unknown | null;
+}
+
+export type BlackboardValue = boolean | number | string;
+
+export interface BlackboardEntry {
+    value: BlackboardValue;
+    changedAt: number;
+}
+
+export type EditOperation =
+    | { addModel: ModelConfig }
+    | { removeModel: { modelId: string } }
+    | { addConnector: Connector }
+    | { removeConnector: { connectorId: string } }
+    | { setParameter: { modelId: string; pointer: string; value: unknown } };
+
+export interface ConfidenceInterval {
+    lower: number;
+    upper: number;
+}
+
+export interface BuildInfo {
+    version: string;
+    features: string[];
+}
+
+export type InjectionPriority = "generated" | "injected" | "timestamp";
+export type Verbosity = "full" | "summary" | "none";
+export type EventScheduling = "scan" | "futureEventList";
+export type TimeRounding = "round" | "truncate";
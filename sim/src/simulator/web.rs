@@ -3,17 +3,53 @@ use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 use crate::utils::set_panic_hook;
+#[cfg(feature = "yaml")]
+use crate::utils::yaml;
 
+use super::export::{TimePrecision, TimeRounding};
 use super::Simulation as CoreSimulation;
+use super::{
+    BlackboardValue, Checkpoint, InitialCondition, Message, MessageFilter, StopCondition,
+    ValidationOptions,
+};
+use crate::models::ModelRecord;
+use crate::utils::errors::SimulationError;
 
 /// The web `Simulation` provides JS/WASM-compatible interfaces to the core
 /// `Simulation` struct.  For additional insight on these methods, refer to
 /// the associated core `Simulation` methods.  Errors are unwrapped, instead
-/// of returned, in the web `Simulation` methods.
+/// of returned, in the web `Simulation` methods.  An optional time
+/// precision applies to the exported message and record times.  With the
+/// `typescript` feature, the generated TypeScript bindings type the JSON
+/// strings by their content (see `web.d.ts`).
+// TypeScript definitions of the JSON representations, for typed JSON
+// strings in the generated bindings
+#[cfg(feature = "typescript")]
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_DEFINITIONS: &str = include_str!("web.d.ts");
+
 #[wasm_bindgen]
 #[derive(Default, Serialize, Deserialize)]
 pub struct Simulation {
     simulation: CoreSimulation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_precision: Option<TimePrecision>,
+}
+
+impl Simulation {
+    fn export_messages<'a>(&self, messages: impl IntoIterator<Item = &'a Message>) -> Vec<Message> {
+        match &self.time_precision {
+            Some(precision) => precision.messages(messages),
+            None => messages.into_iter().cloned().collect(),
+        }
+    }
+
+    fn export_records(&self, records: &[ModelRecord]) -> Vec<ModelRecord> {
+        match &self.time_precision {
+            Some(precision) => precision.records(records),
+            None => records.to_vec(),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -27,6 +63,21 @@ impl Simulation {
                 serde_json::from_str(models).unwrap(),
                 serde_json::from_str(connectors).unwrap(),
             ),
+            time_precision: None,
+        }
+    }
+
+    /// A JS/WASM interface for `Simulation.post_with_seed`, which uses JSON
+    /// representations of the simulation models and connectors.
+    pub fn post_json_with_seed(models: &str, connectors: &str, seed: u64) -> Self {
+        set_panic_hook();
+        Self {
+            simulation: CoreSimulation::post_with_seed(
+                serde_json::from_str(models).unwrap(),
+                serde_json::from_str(connectors).unwrap(),
+                seed,
+            ),
+            time_precision: None,
         }
     }
 
@@ -46,37 +97,76 @@ impl Simulation {
 
     /// A JS/WASM interface for `Simulation.post`, which uses YAML
     /// representations of the simulation models and connectors.
+    #[cfg(feature = "yaml")]
     pub fn post_yaml(models: &str, connectors: &str) -> Simulation {
         set_panic_hook();
         Self {
             simulation: CoreSimulation::post(
-                serde_yaml::from_str(models).unwrap(),
-                serde_yaml::from_str(connectors).unwrap(),
+                yaml::from_str(models).unwrap(),
+                yaml::from_str(connectors).unwrap(),
+            ),
+            time_precision: None,
+        }
+    }
+
+    /// A JS/WASM interface for `Simulation.post_with_seed`, which uses YAML
+    /// representations of the simulation models and connectors.
+    #[cfg(feature = "yaml")]
+    pub fn post_yaml_with_seed(models: &str, connectors: &str, seed: u64) -> Simulation {
+        set_panic_hook();
+        Self {
+            simulation: CoreSimulation::post_with_seed(
+                yaml::from_str(models).unwrap(),
+                yaml::from_str(connectors).unwrap(),
+                seed,
             ),
+            time_precision: None,
         }
     }
 
     /// A JS/WASM interface for `Simulation.put`, which uses YAML
     /// representations of the simulation models and connectors.
+    #[cfg(feature = "yaml")]
     pub fn put_yaml(&mut self, models: &str, connectors: &str) {
         self.simulation.put(
-            serde_yaml::from_str(models).unwrap(),
-            serde_yaml::from_str(connectors).unwrap(),
+            yaml::from_str(models).unwrap(),
+            yaml::from_str(connectors).unwrap(),
         );
     }
 
     /// Get a YAML representation of the full `Simulation` configuration.
+    #[cfg(feature = "yaml")]
     pub fn get_yaml(&self) -> String {
         serde_yaml::to_string(&self.simulation).unwrap()
     }
 
+    /// A JS/WASM interface for `Simulation.checkpoint`, which returns the
+    /// checkpoint in the portable checkpoint format.
+    pub fn checkpoint(&self) -> String {
+        String::from_utf8(self.simulation.checkpoint().unwrap().to_bytes().unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.restore`, which accepts a
+    /// checkpoint in the portable checkpoint format.
+    pub fn restore(checkpoint: &str) -> Simulation {
+        Self {
+            simulation: CoreSimulation::restore(
+                &Checkpoint::from_bytes(checkpoint.as_bytes()).unwrap(),
+            )
+            .unwrap(),
+            time_precision: None,
+        }
+    }
+
     /// A JS/WASM interface for `Simulation.get_messages`, which converts the
     /// messages to a JavaScript Array.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "Message[]")
+    )]
     pub fn get_messages_js(&self) -> Array {
         // Workaround for https://github.com/rustwasm/wasm-bindgen/issues/111
-        self.simulation
-            .get_messages()
-            .clone()
+        self.export_messages(self.simulation.get_messages())
             .into_iter()
             .map(JsValue::from)
             .collect()
@@ -84,14 +174,19 @@ impl Simulation {
 
     /// A JS/WASM interface for `Simulation.get_messages`, which converts the
     /// messages to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
     pub fn get_messages_json(&self) -> String {
-        serde_json::to_string(&self.simulation.get_messages()).unwrap()
+        serde_json::to_string(&self.export_messages(self.simulation.get_messages())).unwrap()
     }
 
     /// A JS/WASM interface for `Simulation.get_messages`, which converts the
     /// messages to a YAML string.
+    #[cfg(feature = "yaml")]
     pub fn get_messages_yaml(&self) -> String {
-        serde_yaml::to_string(&self.simulation.get_messages()).unwrap()
+        serde_yaml::to_string(&self.export_messages(self.simulation.get_messages())).unwrap()
     }
 
     /// An interface to `Simulation.get_global_time`.
@@ -106,14 +201,364 @@ impl Simulation {
 
     /// A JS/WASM interface for `Simulation.records`, which converts the
     /// records to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<ModelRecord[]>")
+    )]
     pub fn get_records_json(&self, model_id: &str) -> String {
-        serde_json::to_string(self.simulation.get_records(model_id).unwrap()).unwrap()
+        serde_json::to_string(&self.export_records(self.simulation.get_records(model_id).unwrap()))
+            .unwrap()
     }
 
     /// A JS/WASM interface for `Simulation.records`, which converts the
     /// records to a YAML string.
+    #[cfg(feature = "yaml")]
     pub fn get_records_yaml(&self, model_id: &str) -> String {
-        serde_yaml::to_string(self.simulation.get_records(model_id).unwrap()).unwrap()
+        serde_yaml::to_string(&self.export_records(self.simulation.get_records(model_id).unwrap()))
+            .unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.get_port_stats`, which converts
+    /// the port statistics to a JSON string (`null` for models without port
+    /// statistics).
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<PortStats | null>")
+    )]
+    pub fn get_port_stats_json(&self, model_id: &str) -> String {
+        serde_json::to_string(&self.simulation.get_port_stats(model_id).unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.get_sink_summary`, which converts
+    /// the sink summary to a JSON string (`null` for models other than
+    /// sinks).  Only the running statistics are transferred, rather than the
+    /// message history, so the summary is suitable for per-frame dashboard
+    /// polling.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<SinkSummary | null>")
+    )]
+    pub fn get_sink_summary_json(&self, model_id: &str) -> String {
+        serde_json::to_string(&self.simulation.get_sink_summary(model_id).unwrap()).unwrap()
+    }
+
+    /// An interface to `Simulation.set_queue_depth_threshold`.
+    pub fn set_queue_depth_threshold(&mut self, threshold: Option<usize>) {
+        self.simulation.set_queue_depth_threshold(threshold);
+    }
+
+    /// A JS/WASM interface for `Simulation.execution_stats`, which converts
+    /// the execution statistics to a JSON string.
+    pub fn get_execution_stats_json(&self) -> String {
+        serde_json::to_string(self.simulation.execution_stats()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.event_density`, which uses a
+    /// JSON representation of the report.
+    pub fn get_event_density_json(&self, bucket_width: f64) -> String {
+        serde_json::to_string(&self.simulation.event_density(bucket_width).unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.set_energy_coefficients`, which
+    /// accepts the coefficients as a JSON string.
+    pub fn set_energy_coefficients_json(&mut self, model_id: &str, coefficients: &str) {
+        self.simulation
+            .set_energy_coefficients(model_id, serde_json::from_str(coefficients).unwrap())
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.energy_report`, which uses a
+    /// JSON representation of the report.
+    pub fn get_energy_report_json(&self, bucket_width: f64) -> String {
+        serde_json::to_string(&self.simulation.energy_report(bucket_width).unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.check_assertions`, which uses a
+    /// JSON representation of the assertion report.
+    pub fn check_assertions_json(&self) -> String {
+        serde_json::to_string(&self.simulation.check_assertions().unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.manifest`, which converts the
+    /// reproducibility manifest to a JSON string.
+    pub fn get_manifest_json(&self) -> String {
+        serde_json::to_string(&self.simulation.manifest().unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.randomize_initial_state`, which
+    /// accepts the initial conditions as a JSON string.
+    pub fn randomize_initial_state_json(&mut self, conditions: &str) {
+        let conditions: Vec<InitialCondition> = serde_json::from_str(conditions).unwrap();
+        self.simulation
+            .randomize_initial_state(&conditions)
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.summary`, which converts the
+    /// summary to a human-readable string.
+    pub fn get_summary(&self) -> String {
+        self.simulation.summary().to_string()
+    }
+
+    /// A JS/WASM interface for `Simulation.analyze_topology`, which converts
+    /// the topology report to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<TopologyReport>")
+    )]
+    pub fn analyze_topology_json(&self) -> String {
+        serde_json::to_string(&self.simulation.analyze_topology()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.validate_with`, which converts
+    /// the validation report (optionally with the assumptions report) to a
+    /// JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<ValidationReport>")
+    )]
+    pub fn validate_json(&self, assumptions: bool) -> String {
+        serde_json::to_string(
+            &self
+                .simulation
+                .validate_with(&ValidationOptions { assumptions }),
+        )
+        .unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.dry_run`, which converts the dry
+    /// run report to a JSON string.
+    pub fn dry_run_json(&self, duration_estimate: f64) -> String {
+        serde_json::to_string(&self.simulation.dry_run(duration_estimate).unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.summary`, which converts the
+    /// summary to a JSON string.
+    pub fn get_summary_json(&self) -> String {
+        serde_json::to_string(&self.simulation.summary()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.get_audit_log`, which converts
+    /// the audit records to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<AuditRecord[]>")
+    )]
+    pub fn get_audit_log_json(&self) -> String {
+        serde_json::to_string(&self.simulation.get_audit_log().unwrap()).unwrap()
+    }
+
+    /// An interface to `Simulation.enable_audit_log`.
+    pub fn enable_audit_log(&mut self, capacity: usize) {
+        self.simulation.enable_audit_log(capacity);
+    }
+
+    /// An interface to `Simulation.disable_audit_log`.
+    pub fn disable_audit_log(&mut self) {
+        self.simulation.disable_audit_log();
+    }
+
+    /// An interface to `Simulation.enable_history`.
+    pub fn enable_history(&mut self, checkpoint_interval: usize) {
+        self.simulation.enable_history(checkpoint_interval).unwrap();
+    }
+
+    /// An interface to `Simulation.disable_history`.
+    pub fn disable_history(&mut self) {
+        self.simulation.disable_history();
+    }
+
+    /// A JS/WASM interface for `Simulation.messages_between`, which converts
+    /// the messages to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
+    pub fn messages_between_json(&self, start: f64, end: f64) -> String {
+        let messages = self.simulation.messages_between(start, end).unwrap();
+        serde_json::to_string(&self.export_messages(messages)).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.messages_for_model`, which
+    /// converts the messages to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
+    pub fn messages_for_model_json(&self, model_id: &str) -> String {
+        let messages = self.simulation.messages_for_model(model_id).unwrap();
+        serde_json::to_string(&self.export_messages(messages)).unwrap()
+    }
+
+    /// An interface to `Simulation.enable_message_history`.
+    pub fn enable_message_history(&mut self) {
+        self.simulation.enable_message_history();
+    }
+
+    /// An interface to `Simulation.disable_message_history`.
+    pub fn disable_message_history(&mut self) {
+        self.simulation.disable_message_history();
+    }
+
+    /// A JS/WASM interface for `Simulation.get_message_history`, which
+    /// converts the messages to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
+    pub fn get_message_history_json(&self) -> String {
+        let messages = self.simulation.get_message_history().unwrap();
+        serde_json::to_string(&self.export_messages(messages)).unwrap()
+    }
+
+    /// An interface to `Simulation.enable_tracing`.
+    pub fn enable_tracing(&mut self) {
+        self.simulation.enable_tracing();
+    }
+
+    /// An interface to `Simulation.disable_tracing`.
+    pub fn disable_tracing(&mut self) {
+        self.simulation.disable_tracing();
+    }
+
+    /// A JS/WASM interface for `Simulation.get_trace`, which converts the
+    /// traced transitions to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<TraceEvent[]>")
+    )]
+    pub fn get_trace_json(&self) -> String {
+        serde_json::to_string(self.simulation.get_trace().unwrap().events()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.chrome_trace`, which converts
+    /// the Chrome `trace_event` document to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<ChromeTrace>")
+    )]
+    pub fn chrome_trace_json(&self) -> String {
+        serde_json::to_string(&self.simulation.chrome_trace().unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.otlp_trace`, which accepts the
+    /// trace ID as a hexadecimal string (of up to 32 digits), and converts
+    /// the OTLP/JSON export request to a JSON string.
+    pub fn otlp_trace_json(&self, trace_id: &str) -> String {
+        let trace_id = u128::from_str_radix(trace_id, 16).unwrap();
+        serde_json::to_string(&self.simulation.otlp_trace(trace_id).unwrap()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.query_messages`, which uses JSON
+    /// representations of the filter (e.g. `{"targetId": "sink-01",
+    /// "start": 10.0}`) and of the matching messages.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
+    pub fn query_messages_json(&self, filter: &str) -> String {
+        let filter: MessageFilter = serde_json::from_str(filter).unwrap();
+        let messages = self.simulation.query_messages(&filter).unwrap();
+        serde_json::to_string(&self.export_messages(messages)).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.state_at`, which converts the
+    /// models to a JSON string.
+    pub fn state_at_json(&self, time: f64) -> String {
+        serde_json::to_string(&self.simulation.state_at(time).unwrap()).unwrap()
+    }
+
+    /// An interface to `Simulation.enable_state_diffs`.
+    pub fn enable_state_diffs(&mut self) {
+        self.simulation.enable_state_diffs();
+    }
+
+    /// An interface to `Simulation.disable_state_diffs`.
+    pub fn disable_state_diffs(&mut self) {
+        self.simulation.disable_state_diffs();
+    }
+
+    /// A JS/WASM interface for `Simulation.diff_last_step`, which converts
+    /// the state changes to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<StateChange[]>")
+    )]
+    pub fn diff_last_step_json(&self, model_id: &str) -> String {
+        serde_json::to_string(&self.simulation.diff_last_step(model_id).unwrap()).unwrap()
+    }
+
+    /// An interface to `Simulation.enable_variate_recording`.
+    pub fn enable_variate_recording(&mut self, capacity: usize) {
+        self.simulation.enable_variate_recording(capacity);
+    }
+
+    /// An interface to `Simulation.disable_variate_recording`.
+    pub fn disable_variate_recording(&mut self) {
+        self.simulation.disable_variate_recording();
+    }
+
+    /// An interface to `Simulation.set_seed`.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.simulation.set_seed(seed);
+    }
+
+    /// An interface to `Simulation.enable_deterministic_mode`.
+    pub fn enable_deterministic_mode(&mut self) {
+        self.simulation.enable_deterministic_mode();
+    }
+
+    /// An interface to `Simulation.disable_deterministic_mode`.
+    pub fn disable_deterministic_mode(&mut self) {
+        self.simulation.disable_deterministic_mode();
+    }
+
+    /// An interface to `Simulation.set_global`.
+    pub fn set_global(&mut self, name: &str, value: f64) {
+        self.simulation.set_global(name, value);
+    }
+
+    /// An interface to `Simulation.remove_global`.
+    pub fn remove_global(&mut self, name: &str) -> Option<f64> {
+        self.simulation.remove_global(name)
+    }
+
+    /// An interface to `Simulation.get_global`.
+    pub fn get_global(&self, name: &str) -> Option<f64> {
+        self.simulation.get_global(name)
+    }
+
+    /// A JS/WASM interface for `Simulation.get_globals`, which converts the
+    /// global variables to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<Record<string, number>>")
+    )]
+    pub fn get_globals_json(&self) -> String {
+        serde_json::to_string(self.simulation.get_globals()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.get_blackboard`, which converts
+    /// the blackboard entries to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<Record<string, BlackboardEntry>>")
+    )]
+    pub fn get_blackboard_json(&self) -> String {
+        serde_json::to_string(self.simulation.get_blackboard()).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.write_blackboard`, which accepts
+    /// the value as a JSON string (a boolean, number, or string).
+    pub fn write_blackboard_json(&mut self, key: &str, value: &str) {
+        let value: BlackboardValue = serde_json::from_str(value).unwrap();
+        self.simulation.write_blackboard(key, value);
+    }
+
+    /// A JS/WASM interface for `Simulation.get_variate_records`, which
+    /// converts the variate records to a JSON string.
+    pub fn get_variate_records_json(&self) -> String {
+        serde_json::to_string(&self.simulation.get_variate_records()).unwrap()
     }
 
     /// An interface to `Simulation.reset`.
@@ -140,17 +585,162 @@ impl Simulation {
 
     /// A JS/WASM interface for `Simulation.inject_input`, which uses a YAML
     /// representation of the injected messages.
+    #[cfg(feature = "yaml")]
     pub fn inject_input_yaml(&mut self, message: &str) {
         self.simulation
-            .inject_input(serde_yaml::from_str(message).unwrap());
+            .inject_input(yaml::from_str(message).unwrap());
+    }
+
+    /// A JS/WASM interface for `Simulation.inject_input_at`, which uses a
+    /// JSON representation of the injected message.
+    pub fn inject_input_at_json(&mut self, message: &str, time: f64) {
+        self.simulation
+            .inject_input_at(serde_json::from_str(message).unwrap(), time)
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.set_injection_priority`, which
+    /// accepts "generated", "injected", or "timestamp".
+    pub fn set_injection_priority(&mut self, priority: &str) {
+        self.simulation
+            .set_injection_priority(serde_json::from_value(priority.into()).unwrap());
+    }
+
+    /// A JS/WASM interface for `Simulation.set_verbosity`, which accepts
+    /// "full", "summary", or "none".
+    pub fn set_verbosity(&mut self, model_id: &str, verbosity: &str) {
+        self.simulation
+            .set_verbosity(model_id, serde_json::from_value(verbosity.into()).unwrap())
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.set_event_scheduling`, which
+    /// accepts "scan" or "futureEventList".
+    pub fn set_event_scheduling(&mut self, scheduling: &str) {
+        self.simulation
+            .set_event_scheduling(serde_json::from_value(scheduling.into()).unwrap());
+    }
+
+    /// A JS/WASM interface for `Simulation.set_parameter`, which uses a
+    /// JSON representation of the parameter value - for adjusting model
+    /// parameters mid-run (e.g. from browser UI sliders).
+    pub fn set_parameter(&mut self, model_id: &str, json_pointer: &str, value: &str) {
+        self.simulation
+            .set_parameter(model_id, json_pointer, serde_json::from_str(value).unwrap())
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.replace_model`, which uses a
+    /// JSON representation of the replacement model.
+    pub fn replace_model_json(&mut self, model: &str) {
+        self.simulation
+            .replace_model(serde_json::from_str(model).unwrap())
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.add_model`, which uses a JSON
+    /// representation of the added model.
+    pub fn add_model_json(&mut self, model: &str) {
+        self.simulation
+            .add_model(serde_json::from_str(model).unwrap())
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.add_template_instances`, which
+    /// uses a JSON representation of the template instances, and returns
+    /// the added model IDs as a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<string[]>")
+    )]
+    pub fn add_template_instances_json(&mut self, instances: &str) -> String {
+        let added = self
+            .simulation
+            .add_template_instances(&serde_json::from_str(instances).unwrap())
+            .unwrap();
+        serde_json::to_string(&added).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.remove_model`, which returns the
+    /// undelivered messages of the removed model as a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
+    pub fn remove_model_json(&mut self, model_id: &str) -> String {
+        let removed = self.simulation.remove_model(model_id).unwrap();
+        serde_json::to_string(&self.export_messages(&removed.undelivered)).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.add_connector`, which uses a
+    /// JSON representation of the added connector.
+    pub fn add_connector_json(&mut self, connector: &str) {
+        self.simulation
+            .add_connector(serde_json::from_str(connector).unwrap())
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.remove_connector`.
+    pub fn remove_connector(&mut self, connector_id: &str) {
+        self.simulation.remove_connector(connector_id).unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.begin_edit`.
+    pub fn begin_edit(&mut self) {
+        self.simulation.begin_edit().unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.apply_edit`, which uses a JSON
+    /// representation of the edit operation.
+    pub fn apply_edit_json(&mut self, operation: &str) {
+        self.simulation
+            .apply_edit(serde_json::from_str(operation).unwrap())
+            .unwrap();
+    }
+
+    /// A JS/WASM interface for `Simulation.commit_edit`, which returns the
+    /// validation issues as a JSON array - empty when the edit is
+    /// committed.  An edit with issues remains open.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<string[]>")
+    )]
+    pub fn commit_edit_json(&mut self) -> String {
+        let issues = match self.simulation.commit_edit() {
+            Ok(()) => Vec::new(),
+            Err(SimulationError::InvalidEdit(issues)) => issues,
+            Err(error) => panic!("{}", error),
+        };
+        serde_json::to_string(&issues).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.rollback_edit`.
+    pub fn rollback_edit(&mut self) {
+        self.simulation.rollback_edit().unwrap();
+    }
+
+    /// Set the precision of exported message and record times, in decimal
+    /// places.  The rounding is "round" (to the nearest exported time) or
+    /// "truncate" (decimation).
+    pub fn set_time_precision(&mut self, decimals: u32, rounding: &str) {
+        let rounding: TimeRounding = serde_json::from_value(rounding.into()).unwrap();
+        self.time_precision = Some(TimePrecision::new(decimals, rounding));
+    }
+
+    /// Export message and record times at full precision.
+    pub fn clear_time_precision(&mut self) {
+        self.time_precision = None;
     }
 
     /// A JS/WASM interface for `Simulation.step`, which converts the
     /// returned messages to a JavaScript Array.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "Message[]")
+    )]
     pub fn step_js(&mut self) -> Array {
-        self.simulation
-            .step()
-            .unwrap()
+        let messages = self.simulation.step().unwrap();
+        self.export_messages(&messages)
             .into_iter()
             .map(JsValue::from)
             .collect()
@@ -158,22 +748,32 @@ impl Simulation {
 
     /// A JS/WASM interface for `Simulation.step`, which converts the
     /// returned messages to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
     pub fn step_json(&mut self) -> String {
-        serde_json::to_string(&self.simulation.step().unwrap()).unwrap()
+        let messages = self.simulation.step().unwrap();
+        serde_json::to_string(&self.export_messages(&messages)).unwrap()
     }
 
     /// A JS/WASM interface for `Simulation.step`, which converts the
     /// returned messages to a YAML string.
+    #[cfg(feature = "yaml")]
     pub fn step_yaml(&mut self) -> String {
-        serde_yaml::to_string(&self.simulation.step().unwrap()).unwrap()
+        let messages = self.simulation.step().unwrap();
+        serde_yaml::to_string(&self.export_messages(&messages)).unwrap()
     }
 
     /// A JS/WASM interface for `Simulation.step_until`, which converts the
     /// returned messages to a JavaScript Array.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "Message[]")
+    )]
     pub fn step_until_js(&mut self, until: f64) -> Array {
-        self.simulation
-            .step_until(until)
-            .unwrap()
+        let messages = self.simulation.step_until(until).unwrap();
+        self.export_messages(&messages)
             .into_iter()
             .map(JsValue::from)
             .collect()
@@ -181,22 +781,45 @@ impl Simulation {
 
     /// A JS/WASM interface for `Simulation.step_until`, which converts the
     /// returned messages to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
     pub fn step_until_json(&mut self, until: f64) -> String {
-        serde_json::to_string(&self.simulation.step_until(until).unwrap()).unwrap()
+        let messages = self.simulation.step_until(until).unwrap();
+        serde_json::to_string(&self.export_messages(&messages)).unwrap()
     }
 
     /// A JS/WASM interface for `Simulation.step_until`, which converts the
     /// returned messages to a YAML string.
+    #[cfg(feature = "yaml")]
     pub fn step_until_yaml(&mut self, until: f64) -> String {
-        serde_yaml::to_string(&self.simulation.step_until(until).unwrap()).unwrap()
+        let messages = self.simulation.step_until(until).unwrap();
+        serde_yaml::to_string(&self.export_messages(&messages)).unwrap()
+    }
+
+    /// A JS/WASM interface for `Simulation.step_until_stop`, which accepts
+    /// a JSON stop condition, and converts the returned messages to a JSON
+    /// string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
+    pub fn step_until_stop_json(&mut self, condition: &str) -> String {
+        let condition: StopCondition = serde_json::from_str(condition).unwrap();
+        let messages = self.simulation.step_until_stop(&condition).unwrap();
+        serde_json::to_string(&self.export_messages(&messages)).unwrap()
     }
 
     /// A JS/WASM interface for `Simulation.step_n`, which converts the
     /// returned messages to a JavaScript Array.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "Message[]")
+    )]
     pub fn step_n_js(&mut self, n: usize) -> Array {
-        self.simulation
-            .step_n(n)
-            .unwrap()
+        let messages = self.simulation.step_n(n).unwrap();
+        self.export_messages(&messages)
             .into_iter()
             .map(JsValue::from)
             .collect()
@@ -204,13 +827,20 @@ impl Simulation {
 
     /// A JS/WASM interface for `Simulation.step_n`, which converts the
     /// returned messages to a JSON string.
+    #[cfg_attr(
+        feature = "typescript",
+        wasm_bindgen(unchecked_return_type = "JsonString<MessageData[]>")
+    )]
     pub fn step_n_json(&mut self, n: usize) -> String {
-        serde_json::to_string(&self.simulation.step_n(n).unwrap()).unwrap()
+        let messages = self.simulation.step_n(n).unwrap();
+        serde_json::to_string(&self.export_messages(&messages)).unwrap()
     }
 
     /// A JS/WASM interface for `Simulation.step_n`, which converts the
     /// returned messages to a YAML string.
+    #[cfg(feature = "yaml")]
     pub fn step_n_yaml(&mut self, n: usize) -> String {
-        serde_yaml::to_string(&self.simulation.step_n(n).unwrap()).unwrap()
+        let messages = self.simulation.step_n(n).unwrap();
+        serde_yaml::to_string(&self.export_messages(&messages)).unwrap()
     }
 }
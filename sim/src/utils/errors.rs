@@ -1,5 +1,74 @@
+use std::fmt;
+
 use thiserror::Error;
 
+/// The context of an error - the originating module, and the model, port,
+/// and simulation time involved, where known.  Errors raised in model state
+/// transitions carry the model ID, the simulation time, and the model type
+/// as the module, along with the port of the incoming message for external
+/// transitions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorContext {
+    pub module: Option<String>,
+    pub model_id: Option<String>,
+    pub port: Option<String>,
+    pub time: Option<f64>,
+}
+
+impl ErrorContext {
+    pub fn new(module: &str) -> Self {
+        Self {
+            module: Some(module.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = Some(model_id.to_string());
+        self
+    }
+
+    pub fn with_port(mut self, port: &str) -> Self {
+        self.port = Some(port.to_string());
+        self
+    }
+
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Fill the unknown fields from the outer context, keeping the more
+    /// specific fields of this (inner) context.
+    fn or(self, outer: ErrorContext) -> Self {
+        Self {
+            module: self.module.or(outer.module),
+            model_id: self.model_id.or(outer.model_id),
+            port: self.port.or(outer.port),
+            time: self.time.or(outer.time),
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(model_id) = &self.model_id {
+            parts.push(format!["model {}", model_id]);
+        }
+        if let Some(port) = &self.port {
+            parts.push(format!["port {}", port]);
+        }
+        if let Some(time) = self.time {
+            parts.push(format!["time {}", time]);
+        }
+        if let Some(module) = &self.module {
+            parts.push(format!["module {}", module]);
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
 /// `SimulationError` enumerates all possible errors returned by sim
 #[derive(Error, Debug)]
 pub enum SimulationError {
@@ -8,12 +77,12 @@ pub enum SimulationError {
     InvalidModelConfiguration,
 
     /// Represents an operation requested on a model that does not exist
-    #[error("A specified model cannot be found in the simulation")]
-    ModelNotFound,
+    #[error("The model {0} cannot be found in the simulation")]
+    ModelNotFound(String),
 
     /// Represents an operation requested on a model port that does not exist
-    #[error("A specified model port cannot be found in the simulation")]
-    PortNotFound,
+    #[error("The port {port} of model {model_id} cannot be found in the simulation")]
+    PortNotFound { model_id: String, port: String },
 
     /// Represents a failed clone operation on a model
     #[error("A model failed to clone during simulation")]
@@ -47,10 +116,214 @@ pub enum SimulationError {
     #[error("Failed to convert to a Float value")]
     FloatConvError,
 
+    /// Represents an invalid experiment configuration, such as an empty set of
+    /// parameters or an evaluation with an unexpected number of outputs
+    #[error("An invalid experiment configuration was encountered")]
+    InvalidExperimentConfiguration,
+
+    /// Represents a history query on a simulation without retained history,
+    /// or for a time before the history retention began
+    #[error("The requested simulation history is not retained")]
+    HistoryUnavailable,
+
+    /// Represents a message history query, without message history (or
+    /// history) enabled
+    #[error("Message history is not enabled")]
+    MessageHistoryUnavailable,
+
+    /// Represents an audit log query, without the audit log enabled
+    #[error("The audit log is not enabled")]
+    AuditLogUnavailable,
+
+    /// Represents a trace query, without tracing enabled
+    #[error("Tracing is not enabled")]
+    TraceUnavailable,
+
+    /// Represents a missing message payload, or a binary payload decoded as
+    /// JSON
+    #[error("A message payload is missing, or is not a JSON payload")]
+    InvalidPayload,
+
     /// Represents a message unexpectedly lost/dropped/stuck during simulation execution
     #[error("A message was unexpectedly lost, dropped, or stuck during simulation execution")]
     DroppedMessageError,
 
+    /// Represents an experiment composition that references itself, directly
+    /// or through its sub-experiments
+    #[error("An experiment references itself through its sub-experiments")]
+    CyclicExperimentError,
+
+    /// Represents a KPI or KPI time series that is not in the experiment
+    /// results
+    #[error("The experiment output {0} is not in the results")]
+    UnknownOutput(String),
+
+    /// Represents an analysis across replications, without any replications
+    #[error("The replication set is empty")]
+    EmptyReplicationSet,
+
+    /// Represents a distribution parameter referencing a global variable
+    /// that is not defined in the simulation
+    #[error("The global variable {0} is not defined in the simulation")]
+    UnknownGlobalVariable(String),
+
+    /// Represents a snapshot that is corrupt (failing the integrity
+    /// checksum), or of an unsupported format version
+    #[error("The snapshot is corrupt, or of an unsupported format version")]
+    InvalidSnapshot,
+
+    /// Represents a checkpoint of a simulation with a user-supplied global
+    /// random number generator, whose state cannot be captured
+    #[error("The state of a user-supplied random number generator cannot be checkpointed")]
+    OpaqueRngState,
+
+    /// Represents a snapshot compression format that is not enabled in the
+    /// build, through the `gzip` or `zstd` features
+    #[error("The snapshot compression format is not enabled in this build")]
+    UnsupportedCompression,
+
+    /// Represents an input injection scheduled before the current
+    /// simulation time
+    #[error("The input injection time {0} is before the current simulation time")]
+    InjectionInPast(f64),
+
+    /// Represents the initialization of model states after the simulation
+    /// has started, at the given simulation time
+    #[error("Initial states cannot be set after the simulation has started (at time {0})")]
+    InitializationAfterStart(f64),
+
+    /// Represents a model type registered more than once with the model
+    /// factory
+    #[error("The model type {0} is already registered")]
+    DuplicateModelType(String),
+
+    /// Represents a model type name that is not a valid, optionally
+    /// namespaced, identifier
+    #[error("The model type {0} is not a valid model type name")]
+    InvalidModelType(String),
+
+    /// Represents a configuration referencing an environment variable that
+    /// is not set, and has no default
+    #[error("The environment variable {0} is not set")]
+    UndefinedEnvironmentVariable(String),
+
+    /// Represents a configuration file that includes itself, directly or
+    /// through other included files
+    #[error("The configuration file {0} includes itself")]
+    CyclicInclude(String),
+
+    /// Represents a model parameter that does not exist, or cannot be set
+    #[error("The model parameter {0} does not exist, or cannot be set")]
+    InvalidParameter(String),
+
+    /// Represents a model template placeholder without a parameter value
+    #[error("The template parameter {0} is not defined")]
+    UnknownTemplateParameter(String),
+
+    /// Represents a model scheduling its next event at a negative or NaN
+    /// time advance, which would corrupt the simulation clock
+    #[error("The model {model_id} scheduled an invalid time advance of {value}")]
+    InvalidTimeAdvance { model_id: String, value: f64 },
+
+    /// Represents a report bucket width that is not positive and finite
+    #[error("The bucket width {0} is not positive and finite")]
+    InvalidBucketWidth(f64),
+
+    /// Represents an order statistic of a sample without any points
+    #[error("The sample is empty")]
+    EmptySample,
+
+    /// Represents a quantile outside of the unit interval
+    #[error("The quantile {0} is not between 0 and 1")]
+    InvalidQuantile(f64),
+
+    /// Represents a connector that cannot be found in the simulation
+    #[error("The connector {0} cannot be found in the simulation")]
+    ConnectorNotFound(String),
+
+    /// Represents opening an edit transaction while another is open
+    #[error("An edit transaction is already in progress")]
+    EditInProgress,
+
+    /// Represents an edit operation without an open edit transaction
+    #[error("No edit transaction is in progress")]
+    NoEditInProgress,
+
+    /// Represents adding a model with the ID of an existing model
+    #[error("The model ID {0} is already in the simulation")]
+    DuplicateModelId(String),
+
+    /// Represents adding a connector with the ID of an existing connector
+    #[error("The connector ID {0} is already in the simulation")]
+    DuplicateConnectorId(String),
+
+    /// Represents a connector referencing a model that is not in the
+    /// simulation
+    #[error("The connector {connector_id} references the missing model {model_id}")]
+    DanglingConnector {
+        connector_id: String,
+        model_id: String,
+    },
+
+    /// Represents an edit transaction failing validation
+    #[error("The edit is invalid - {}", .0.join("; "))]
+    InvalidEdit(Vec<String>),
+
+    /// Represents paired samples of different lengths
+    #[error("The paired samples have different lengths, {0} and {1}")]
+    UnpairedSamples(usize, usize),
+
+    /// Represents an exponential smoothing factor outside of (0, 1]
+    #[error("The smoothing factor {0} is not in (0, 1]")]
+    InvalidSmoothingFactor(f64),
+
+    /// Represents an observed duration that is negative or not finite
+    #[error("The observation {0} is not a non-negative, finite duration")]
+    InvalidObservation(f64),
+
+    /// Represents a simulation configuration failing validation
+    #[error(
+        "The simulation configuration is invalid - {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    )]
+    InvalidConfiguration(Vec<crate::simulator::ValidationError>),
+
+    /// Represents a simulation exceeding one of its resource quotas
+    #[error("The {quota} quota of {limit} is exceeded, with {actual}")]
+    QuotaExceeded {
+        quota: crate::simulator::QuotaKind,
+        limit: usize,
+        actual: usize,
+    },
+
+    /// Represents invalid rows of imported CSV tables
+    #[error(
+        "The CSV import failed, with invalid rows - {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    )]
+    CsvImportError(Vec<crate::import::CsvRowError>),
+
+    /// Represents an error with the context of its origin, such as the
+    /// model state transition raising it
+    #[error("{source} ({context})")]
+    WithContext {
+        context: ErrorContext,
+        source: Box<SimulationError>,
+    },
+
+    /// Transparent I/O errors
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+
+    /// Transparent SQLite errors
+    #[cfg(feature = "sqlite")]
+    #[error(transparent)]
+    SqliteError(#[from] rusqlite::Error),
+
+    /// Transparent serde_yaml errors
+    #[error(transparent)]
+    YAMLError(#[from] serde_yaml::Error),
+
     /// Transparent serde_json errors
     #[error(transparent)]
     JSONError(#[from] serde_json::error::Error),
@@ -95,3 +368,101 @@ pub enum SimulationError {
     #[error(transparent)]
     WeightedError(#[from] rand_distr::WeightedError),
 }
+
+impl SimulationError {
+    /// Attach the context of the error origin.  Context is attached once -
+    /// an error with context keeps its (more specific) inner context, with
+    /// any unknown fields filled from the outer context.
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            SimulationError::WithContext {
+                context: inner,
+                source,
+            } => SimulationError::WithContext {
+                context: inner.or(context),
+                source,
+            },
+            error => SimulationError::WithContext {
+                context,
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// Prefix the model ID of the attached context with the ID of the
+    /// parent (coupled) model, for the path of a nested component.
+    pub(crate) fn nested_in(mut self, parent_id: &str) -> Self {
+        if let SimulationError::WithContext { context, .. } = &mut self {
+            context.model_id = context
+                .model_id
+                .take()
+                .map(|model_id| format!["{}/{}", parent_id, model_id]);
+        }
+        self
+    }
+
+    /// The context of the error origin, if attached.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            SimulationError::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, without any attached context.
+    pub fn root_cause(&self) -> &SimulationError {
+        match self {
+            SimulationError::WithContext { source, .. } => source.root_cause(),
+            error => error,
+        }
+    }
+
+    /// The ID of the model involved in the error, from the attached context
+    /// or the error itself.
+    pub fn model_id(&self) -> Option<&str> {
+        if let Some(model_id) = self
+            .context()
+            .and_then(|context| context.model_id.as_deref())
+        {
+            return Some(model_id);
+        }
+        match self.root_cause() {
+            SimulationError::ModelNotFound(model_id)
+            | SimulationError::PortNotFound { model_id, .. }
+            | SimulationError::InvalidTimeAdvance { model_id, .. }
+            | SimulationError::DuplicateModelId(model_id)
+            | SimulationError::DanglingConnector { model_id, .. } => Some(model_id),
+            _ => None,
+        }
+    }
+
+    /// The model port involved in the error, from the attached context or
+    /// the error itself.
+    pub fn port(&self) -> Option<&str> {
+        if let Some(port) = self.context().and_then(|context| context.port.as_deref()) {
+            return Some(port);
+        }
+        match self.root_cause() {
+            SimulationError::PortNotFound { port, .. } => Some(port),
+            _ => None,
+        }
+    }
+
+    /// The simulation time of the error, from the attached context or the
+    /// error itself.
+    pub fn time(&self) -> Option<f64> {
+        if let Some(time) = self.context().and_then(|context| context.time) {
+            return Some(time);
+        }
+        match self.root_cause() {
+            SimulationError::InjectionInPast(time)
+            | SimulationError::InitializationAfterStart(time) => Some(*time),
+            _ => None,
+        }
+    }
+
+    /// The module originating the error, if known.
+    pub fn module(&self) -> Option<&str> {
+        self.context().and_then(|context| context.module.as_deref())
+    }
+}
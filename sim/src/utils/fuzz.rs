@@ -0,0 +1,599 @@
+//! The fuzz module generates random, valid simulation topologies - models,
+//! connectors, and distributions within safe parameter ranges - and runs
+//! them briefly, to harden the simulation engine against panics.  Each
+//! fuzz case is fully determined by its seed, so any failing case can be
+//! regenerated and debugged in isolation.  Panics are caught per case, and
+//! reported with the seed of the failing case.
+//!
+//! Downstream model authors can include their own models in the fuzz mix,
+//! by registering a fuzz model generator with the `TopologyFuzzer`.
+
+use std::any::Any;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::input_modeling::dynamic_rng::{lock_rng, seeded_rng, DynRng};
+#[cfg(feature = "stochastic-gate")]
+use crate::input_modeling::BooleanRandomVariable;
+use crate::input_modeling::ContinuousRandomVariable;
+#[cfg(feature = "exclusive-gateway")]
+use crate::input_modeling::IndexRandomVariable;
+#[cfg(feature = "stopwatch")]
+use crate::models::stopwatch::Metric;
+#[cfg(feature = "batcher")]
+use crate::models::Batcher;
+#[cfg(feature = "exclusive-gateway")]
+use crate::models::ExclusiveGateway;
+#[cfg(feature = "gate")]
+use crate::models::Gate;
+#[cfg(feature = "load-balancer")]
+use crate::models::LoadBalancer;
+#[cfg(feature = "parallel-gateway")]
+use crate::models::ParallelGateway;
+#[cfg(feature = "stochastic-gate")]
+use crate::models::StochasticGate;
+#[cfg(feature = "stopwatch")]
+use crate::models::Stopwatch;
+use crate::models::{Generator, Model, Processor, ReportableModel, Sink, Storage};
+use crate::simulator::{Connector, Message, Simulation};
+use crate::utils::errors::SimulationError;
+
+/// A randomly configured model, along with the ports available for random
+/// connections.
+pub struct FuzzModel {
+    pub model: Box<dyn ReportableModel>,
+    pub ports_in: Vec<String>,
+    pub ports_out: Vec<String>,
+}
+
+/// A fuzz model generator randomly configures a single model, drawing any
+/// parameters from the supplied random number generator.
+pub type FuzzModelGenerator = fn(&DynRng) -> Result<FuzzModel, SimulationError>;
+
+/// A single, reproducible fuzz case - a random topology and the messages
+/// injected to kick off the simulation.
+#[derive(Clone)]
+pub struct FuzzCase {
+    pub seed: u64,
+    pub models: Vec<Model>,
+    pub connectors: Vec<Connector>,
+    pub injections: Vec<Message>,
+}
+
+/// The outcome of a single fuzz case.  Simulation errors are expected and
+/// recorded, as fuzzed topologies routinely violate model assumptions - it
+/// is panics that indicate engine defects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuzzReport {
+    pub seed: u64,
+    pub model_count: usize,
+    pub connector_count: usize,
+    pub steps: usize,
+    pub message_count: usize,
+    pub error: Option<String>,
+    /// The panic message, if the fuzz case panicked
+    #[serde(default)]
+    pub panic: Option<String>,
+}
+
+impl FuzzReport {
+    /// The report of a fuzz case that panicked during generation, before
+    /// any topology was available.
+    fn generation_panic(seed: u64, panic: String) -> Self {
+        Self {
+            seed,
+            model_count: 0,
+            connector_count: 0,
+            steps: 0,
+            message_count: 0,
+            error: None,
+            panic: Some(panic),
+        }
+    }
+}
+
+impl FuzzCase {
+    /// Run the fuzz case for up to `max_steps` simulation steps, stopping
+    /// early at the first simulation error or panic.
+    pub fn run(&self, max_steps: usize) -> FuzzReport {
+        let mut steps = 0;
+        let mut message_count = 0;
+        let mut error = None;
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut simulation =
+                Simulation::post_with_seed(self.models.clone(), self.connectors.clone(), self.seed);
+            self.injections
+                .iter()
+                .for_each(|message| simulation.inject_input(message.clone()));
+            while steps < max_steps {
+                match simulation.step() {
+                    Ok(messages) => message_count += messages.len(),
+                    Err(err) => {
+                        error = Some(err.to_string());
+                        break;
+                    }
+                }
+                steps += 1;
+            }
+        }));
+        FuzzReport {
+            seed: self.seed,
+            model_count: self.models.len(),
+            connector_count: self.connectors.len(),
+            steps,
+            message_count,
+            error,
+            panic: outcome.err().map(panic_message),
+        }
+    }
+}
+
+/// The message of a caught panic, for the `&str` and `String` payloads of
+/// `panic!`.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown panic"))
+}
+
+/// The topology fuzzer holds the registered fuzz model generators, and the
+/// bounds on the size and duration of each fuzz case.
+pub struct TopologyFuzzer {
+    generators: Vec<(String, FuzzModelGenerator)>,
+    max_models: usize,
+    max_injections: usize,
+    max_steps: usize,
+}
+
+impl Default for TopologyFuzzer {
+    fn default() -> Self {
+        Self::new(8, 4, 200)
+    }
+}
+
+impl TopologyFuzzer {
+    /// This constructor creates a fuzzer with all the pre-built atomic
+    /// models registered.
+    pub fn new(max_models: usize, max_injections: usize, max_steps: usize) -> Self {
+        #[allow(unused_mut)]
+        let mut generators: Vec<(String, FuzzModelGenerator)> = vec![
+            (String::from("Generator"), fuzz_generator),
+            (String::from("Processor"), fuzz_processor),
+            (String::from("Sink"), fuzz_sink),
+            (String::from("Storage"), fuzz_storage),
+        ];
+        #[cfg(feature = "batcher")]
+        generators.push((String::from("Batcher"), fuzz_batcher));
+        #[cfg(feature = "exclusive-gateway")]
+        generators.push((String::from("ExclusiveGateway"), fuzz_exclusive_gateway));
+        #[cfg(feature = "gate")]
+        generators.push((String::from("Gate"), fuzz_gate));
+        #[cfg(feature = "load-balancer")]
+        generators.push((String::from("LoadBalancer"), fuzz_load_balancer));
+        #[cfg(feature = "parallel-gateway")]
+        generators.push((String::from("ParallelGateway"), fuzz_parallel_gateway));
+        #[cfg(feature = "stochastic-gate")]
+        generators.push((String::from("StochasticGate"), fuzz_stochastic_gate));
+        #[cfg(feature = "stopwatch")]
+        generators.push((String::from("Stopwatch"), fuzz_stopwatch));
+        Self {
+            generators,
+            max_models,
+            max_injections,
+            max_steps,
+        }
+    }
+
+    /// Include a custom model in the fuzz mix.
+    pub fn register(&mut self, model_type: &str, generator: FuzzModelGenerator) {
+        self.generators.push((model_type.to_string(), generator));
+    }
+
+    /// Generate the fuzz case for a seed.  The same seed always produces
+    /// the same fuzz case, for a given set of registered generators.
+    pub fn generate(&self, seed: u64) -> Result<FuzzCase, SimulationError> {
+        if self.generators.is_empty() || self.max_models == 0 {
+            return Err(SimulationError::InvalidModelConfiguration);
+        }
+        let rng = seeded_rng(seed);
+        let model_count = lock_rng(&rng).gen_range(1..=self.max_models);
+        let fuzz_models = (0..model_count)
+            .map(|index| {
+                let generator_index = lock_rng(&rng).gen_range(0..self.generators.len());
+                let (model_type, generator) = &self.generators[generator_index];
+                let id = format!["{}-{:02}", model_type.to_lowercase(), index];
+                generator(&rng).map(|fuzz_model| (id, fuzz_model))
+            })
+            .collect::<Result<Vec<(String, FuzzModel)>, SimulationError>>()?;
+        let targets: Vec<(&String, &String)> = fuzz_models
+            .iter()
+            .flat_map(|(id, fuzz_model)| fuzz_model.ports_in.iter().map(move |port| (id, port)))
+            .collect();
+        let mut connectors = Vec::new();
+        let mut injections = Vec::new();
+        if !targets.is_empty() {
+            fuzz_models.iter().for_each(|(source_id, fuzz_model)| {
+                fuzz_model.ports_out.iter().for_each(|source_port| {
+                    // Leave some output ports unconnected, as a sink
+                    if lock_rng(&rng).gen_bool(0.8) {
+                        let (target_id, target_port) =
+                            targets[lock_rng(&rng).gen_range(0..targets.len())];
+                        connectors.push(Connector::new(
+                            format!["connector-{:02}", connectors.len()],
+                            source_id.clone(),
+                            target_id.clone(),
+                            source_port.clone(),
+                            target_port.clone(),
+                        ));
+                    }
+                });
+            });
+            let injection_count = lock_rng(&rng).gen_range(0..=self.max_injections);
+            (0..injection_count).for_each(|index| {
+                let (target_id, target_port) = targets[lock_rng(&rng).gen_range(0..targets.len())];
+                injections.push(Message::new(
+                    String::from("fuzzer"),
+                    String::from("fuzzer"),
+                    target_id.clone(),
+                    target_port.clone(),
+                    0.0,
+                    format!["fuzz-job-{}", index],
+                ));
+            });
+        }
+        Ok(FuzzCase {
+            seed,
+            models: fuzz_models
+                .into_iter()
+                .map(|(id, fuzz_model)| Model::new(id, fuzz_model.model))
+                .collect(),
+            connectors,
+            injections,
+        })
+    }
+
+    /// Generate and run a fuzz case for every seed in the range.  A panic
+    /// in the simulation engine or a registered model (or generator) is
+    /// caught, and recorded in the report of the failing case, and the
+    /// remaining cases still run.
+    pub fn run(&self, seeds: Range<u64>) -> Result<Vec<FuzzReport>, SimulationError> {
+        seeds
+            .map(
+                |seed| match panic::catch_unwind(AssertUnwindSafe(|| self.generate(seed))) {
+                    Ok(case) => Ok(case?.run(self.max_steps)),
+                    Err(payload) => Ok(FuzzReport::generation_panic(seed, panic_message(payload))),
+                },
+            )
+            .collect()
+    }
+}
+
+fn safe_rate(rng: &DynRng) -> f64 {
+    lock_rng(rng).gen_range(0.1..2.0)
+}
+
+#[cfg(any(
+    feature = "exclusive-gateway",
+    feature = "load-balancer",
+    feature = "parallel-gateway"
+))]
+fn flow_paths(rng: &DynRng, prefix: &str) -> Vec<String> {
+    let count = lock_rng(rng).gen_range(1..=3);
+    (0..count)
+        .map(|index| format!["{}-{}", prefix, index])
+        .collect()
+}
+
+#[cfg(feature = "batcher")]
+fn fuzz_batcher(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    let max_batch_time = lock_rng(rng).gen_range(0.1..5.0);
+    let max_batch_size = lock_rng(rng).gen_range(1..=5);
+    Ok(FuzzModel {
+        model: Box::new(Batcher::new(
+            String::from("job"),
+            String::from("job"),
+            max_batch_time,
+            max_batch_size,
+            true,
+        )),
+        ports_in: vec![String::from("job")],
+        ports_out: vec![String::from("job")],
+    })
+}
+
+#[cfg(feature = "exclusive-gateway")]
+fn fuzz_exclusive_gateway(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    let ports_in = flow_paths(rng, "in");
+    let ports_out = flow_paths(rng, "out");
+    let port_weights = IndexRandomVariable::Uniform {
+        min: 0,
+        max: ports_out.len(),
+    };
+    Ok(FuzzModel {
+        model: Box::new(ExclusiveGateway::new(
+            ports_in.clone(),
+            ports_out.clone(),
+            port_weights,
+            true,
+            None,
+        )),
+        ports_in,
+        ports_out,
+    })
+}
+
+#[cfg(feature = "gate")]
+fn fuzz_gate(_rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    Ok(FuzzModel {
+        model: Box::new(Gate::new(
+            String::from("job"),
+            String::from("activation"),
+            String::from("deactivation"),
+            String::from("job"),
+            true,
+        )),
+        ports_in: vec![
+            String::from("job"),
+            String::from("activation"),
+            String::from("deactivation"),
+        ],
+        ports_out: vec![String::from("job")],
+    })
+}
+
+fn fuzz_generator(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    Ok(FuzzModel {
+        model: Box::new(Generator::new(
+            ContinuousRandomVariable::Exp {
+                lambda: safe_rate(rng),
+            },
+            None,
+            String::from("job"),
+            true,
+            None,
+        )),
+        ports_in: Vec::new(),
+        ports_out: vec![String::from("job")],
+    })
+}
+
+#[cfg(feature = "load-balancer")]
+fn fuzz_load_balancer(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    let ports_out = flow_paths(rng, "out");
+    Ok(FuzzModel {
+        model: Box::new(LoadBalancer::new(
+            String::from("job"),
+            ports_out.clone(),
+            true,
+        )),
+        ports_in: vec![String::from("job")],
+        ports_out,
+    })
+}
+
+#[cfg(feature = "parallel-gateway")]
+fn fuzz_parallel_gateway(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    let ports_in = flow_paths(rng, "in");
+    let ports_out = flow_paths(rng, "out");
+    Ok(FuzzModel {
+        model: Box::new(ParallelGateway::new(
+            ports_in.clone(),
+            ports_out.clone(),
+            true,
+        )),
+        ports_in,
+        ports_out,
+    })
+}
+
+fn fuzz_processor(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    let queue_capacity = if lock_rng(rng).gen_bool(0.5) {
+        Some(lock_rng(rng).gen_range(1..=10))
+    } else {
+        None
+    };
+    Ok(FuzzModel {
+        model: Box::new(Processor::new(
+            ContinuousRandomVariable::Exp {
+                lambda: safe_rate(rng),
+            },
+            queue_capacity,
+            String::from("job"),
+            String::from("processed"),
+            true,
+            None,
+        )),
+        ports_in: vec![String::from("job")],
+        ports_out: vec![String::from("processed")],
+    })
+}
+
+#[cfg(feature = "stochastic-gate")]
+fn fuzz_stochastic_gate(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    let p = lock_rng(rng).gen_range(0.0..=1.0);
+    Ok(FuzzModel {
+        model: Box::new(StochasticGate::new(
+            BooleanRandomVariable::Bernoulli { p },
+            String::from("job"),
+            String::from("job"),
+            true,
+            None,
+        )),
+        ports_in: vec![String::from("job")],
+        ports_out: vec![String::from("job")],
+    })
+}
+
+#[cfg(feature = "stopwatch")]
+fn fuzz_stopwatch(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    let metric = if lock_rng(rng).gen_bool(0.5) {
+        Metric::Minimum
+    } else {
+        Metric::Maximum
+    };
+    Ok(FuzzModel {
+        model: Box::new(Stopwatch::new(
+            String::from("start"),
+            String::from("stop"),
+            String::from("metric"),
+            String::from("job"),
+            metric,
+            true,
+        )),
+        ports_in: vec![
+            String::from("start"),
+            String::from("stop"),
+            String::from("metric"),
+        ],
+        ports_out: vec![String::from("job")],
+    })
+}
+
+fn fuzz_sink(rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    Ok(FuzzModel {
+        model: Box::new(Sink::new(
+            String::from("job"),
+            lock_rng(rng).gen_range(1.0..100.0),
+            true,
+        )),
+        ports_in: vec![String::from("job")],
+        ports_out: Vec::new(),
+    })
+}
+
+fn fuzz_storage(_rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+    Ok(FuzzModel {
+        model: Box::new(Storage::new(
+            String::from("put"),
+            String::from("get"),
+            String::from("stored"),
+            true,
+        )),
+        ports_in: vec![String::from("put"), String::from("get")],
+        ports_out: vec![String::from("stored")],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model_trait::SerializableModel;
+    use crate::models::{DevsModel, ModelMessage, ModelRecord, Reportable};
+    use crate::simulator::Services;
+
+    #[test]
+    fn fuzz_cases_are_reproducible() {
+        let fuzzer = TopologyFuzzer::default();
+        let first = fuzzer.generate(7).unwrap();
+        let second = fuzzer.generate(7).unwrap();
+        assert_eq!(
+            serde_json::to_string(&first.models).unwrap(),
+            serde_json::to_string(&second.models).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_string(&first.run(50)).unwrap(),
+            serde_json::to_string(&second.run(50)).unwrap()
+        );
+    }
+
+    #[test]
+    fn fuzz_pre_built_models() {
+        let reports = TopologyFuzzer::default().run(0..200).unwrap();
+        assert_eq!(reports.len(), 200);
+        assert!(reports.iter().any(|report| report.message_count > 0));
+        assert!(reports.iter().all(|report| report.panic.is_none()));
+    }
+
+    /// A model with an external transition defect, for panic reporting
+    #[derive(Clone)]
+    struct Faulty {
+        records: Vec<ModelRecord>,
+    }
+
+    impl SerializableModel for Faulty {}
+
+    impl DevsModel for Faulty {
+        fn events_ext(
+            &mut self,
+            _incoming_message: &ModelMessage,
+            _services: &mut Services,
+        ) -> Result<(), SimulationError> {
+            panic!("faulty external transition")
+        }
+
+        fn events_int(
+            &mut self,
+            _services: &mut Services,
+        ) -> Result<Vec<ModelMessage>, SimulationError> {
+            Ok(Vec::new())
+        }
+
+        fn time_advance(&mut self, _time_delta: f64) {}
+
+        fn until_next_event(&self) -> f64 {
+            f64::INFINITY
+        }
+
+        #[cfg(feature = "simx")]
+        fn event_rules_scheduling(&self) -> &str {
+            ""
+        }
+
+        #[cfg(feature = "simx")]
+        fn event_rules(&self) -> String {
+            String::new()
+        }
+    }
+
+    impl Reportable for Faulty {
+        fn status(&self) -> String {
+            String::from("Faulty")
+        }
+
+        fn records(&self) -> &Vec<ModelRecord> {
+            &self.records
+        }
+    }
+
+    impl ReportableModel for Faulty {}
+
+    fn fuzz_faulty(_rng: &DynRng) -> Result<FuzzModel, SimulationError> {
+        Ok(FuzzModel {
+            model: Box::new(Faulty {
+                records: Vec::new(),
+            }),
+            ports_in: vec![String::from("job")],
+            ports_out: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn panics_are_reported_with_the_failing_seed() {
+        let mut fuzzer = TopologyFuzzer::default();
+        fuzzer.register("Faulty", fuzz_faulty);
+        let reports = fuzzer.run(0..50).unwrap();
+        assert_eq!(reports.len(), 50);
+        let panicked: Vec<&FuzzReport> = reports
+            .iter()
+            .filter(|report| report.panic.is_some())
+            .collect();
+        assert!(!panicked.is_empty());
+        assert!(panicked.len() < reports.len());
+        panicked.iter().for_each(|report| {
+            assert_eq!(report.panic.as_deref(), Some("faulty external transition"));
+            // The failing case regenerates from the reported seed
+            let case = fuzzer.generate(report.seed).unwrap();
+            assert!(case
+                .models
+                .iter()
+                .any(|model| model.id().starts_with("faulty-")));
+            assert_eq!(case.run(200).panic, report.panic);
+        });
+    }
+}
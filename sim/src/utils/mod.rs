@@ -4,6 +4,8 @@
 //! arithmetic.
 
 pub mod errors;
+pub mod fuzz;
+pub mod yaml;
 
 use errors::SimulationError;
 
@@ -21,6 +23,17 @@ pub fn evaluate_polynomial(coefficients: &[f64], x: f64) -> Result<f64, Simulati
         }))
 }
 
+/// The 64-bit FNV-1a hash of the bytes.  Unlike the standard library
+/// hashers, the hash is stable across processes and toolchains, for use in
+/// persisted cache keys and checksums.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
 /// When the `console_error_panic_hook` feature is enabled, we can call the
 /// `set_panic_hook` function at least once during initialization, and then
 /// we will get better error messages if our code ever panics.
@@ -32,6 +45,23 @@ pub fn set_panic_hook() {
     console_error_panic_hook::set_once();
 }
 
+/// The current wall clock time, in milliseconds since the Unix epoch.  The
+/// JavaScript clock is used for WASM targets, where the standard library
+/// clock is unavailable.
+pub fn wall_clock_time() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+}
+
 /// Integer square root calculation, using the Babylonian square-root
 /// algorithm.
 pub fn usize_sqrt(n: usize) -> usize {
@@ -57,4 +87,11 @@ mod tests {
         assert![3 == usize_sqrt(9)];
         assert![3 == usize_sqrt(15)];
     }
+
+    #[test]
+    fn fnv1a_matches_reference_values() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
 }
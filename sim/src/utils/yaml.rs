@@ -0,0 +1,296 @@
+//! YAML configuration loading, with support for anchors, aliases, and merge
+//! keys.  Anchors and aliases are resolved by the YAML parser, but merge
+//! keys (`<<: *defaults`) are not - without normalization, a merge key is
+//! read as an ordinary `<<` field, and the merged fields are silently
+//! missing from the configuration.  Configurations are therefore loaded as
+//! YAML values, normalized with `apply_merge`, and then deserialized.
+//!
+//! Configuration files are additionally preprocessed, so large projects can
+//! split configurations across files, and parameterize them per
+//! environment:
+//!
+//! - `${NAME}` is replaced by the value of the `NAME` environment variable,
+//!   and `${NAME:-default}` falls back to the default when the variable is
+//!   not set.  `$${` escapes a literal `${`.
+//! - `!include other.yaml` is replaced by the configuration of the other
+//!   file, resolved relative to the including file.  An included sequence
+//!   in a sequence is spliced into the including sequence, so a list of
+//!   models may be composed from several files.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde_yaml::{Mapping, Value};
+
+use super::errors::SimulationError;
+
+const MERGE_KEY: &str = "<<";
+const INCLUDE_TAG: &str = "!include";
+// Include tags are rewritten as single-entry mappings before parsing
+const INCLUDE_KEY: &str = "$include";
+
+/// Resolve the merge keys of a YAML value, recursively.  The value of a
+/// merge key is a mapping, or a sequence of mappings, whose fields are
+/// merged into the enclosing mapping.  Fields of the enclosing mapping take
+/// precedence over merged fields, and earlier mappings in a merge sequence
+/// take precedence over later mappings.
+pub fn apply_merge(value: &mut Value) -> Result<(), SimulationError> {
+    match value {
+        Value::Sequence(sequence) => sequence.iter_mut().try_for_each(apply_merge),
+        Value::Mapping(mapping) => {
+            mapping
+                .iter_mut()
+                .try_for_each(|(_, field)| apply_merge(field))?;
+            if let Some(merge) = mapping.remove(&Value::from(MERGE_KEY)) {
+                merge_into(mapping, merge)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn merge_into(mapping: &mut Mapping, merge: Value) -> Result<(), SimulationError> {
+    match merge {
+        Value::Mapping(merged) => {
+            merged.into_iter().for_each(|(key, field)| {
+                if !mapping.contains_key(&key) {
+                    mapping.insert(key, field);
+                }
+            });
+            Ok(())
+        }
+        Value::Sequence(sequence) => sequence.into_iter().try_for_each(|merged| match merged {
+            Value::Mapping(_) => merge_into(mapping, merged),
+            _ => Err(SimulationError::InvalidModelConfiguration),
+        }),
+        _ => Err(SimulationError::InvalidModelConfiguration),
+    }
+}
+
+/// Deserialize a YAML (or JSON) configuration, after resolving merge keys.
+pub fn from_str<T: DeserializeOwned>(configuration: &str) -> Result<T, SimulationError> {
+    let mut value: Value = serde_yaml::from_str(configuration)?;
+    apply_merge(&mut value)?;
+    Ok(serde_yaml::from_value(value)?)
+}
+
+/// Deserialize a YAML (or JSON) configuration file, after interpolating
+/// environment variables, resolving includes, and resolving merge keys.
+pub fn from_file<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T, SimulationError> {
+    let mut value = load_file(path.as_ref(), &mut Vec::new())?;
+    apply_merge(&mut value)?;
+    Ok(serde_yaml::from_value(value)?)
+}
+
+fn load_file(path: &Path, including: &mut Vec<PathBuf>) -> Result<Value, SimulationError> {
+    let path = fs::canonicalize(path)?;
+    if including.contains(&path) {
+        return Err(SimulationError::CyclicInclude(path.display().to_string()));
+    }
+    let configuration = tag_includes(&interpolate_env(&fs::read_to_string(&path)?)?);
+    let mut value: Value = serde_yaml::from_str(&configuration)?;
+    let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    including.push(path);
+    resolve_includes(&mut value, &directory, including)?;
+    including.pop();
+    Ok(value)
+}
+
+/// Replace the `${NAME}` and `${NAME:-default}` references in a
+/// configuration with the values of the environment variables.
+pub fn interpolate_env(configuration: &str) -> Result<String, SimulationError> {
+    let mut interpolated = String::with_capacity(configuration.len());
+    let mut remainder = configuration;
+    while let Some(start) = remainder.find("${") {
+        if remainder[..start].ends_with('$') {
+            interpolated.push_str(&remainder[..start - 1]);
+            interpolated.push_str("${");
+            remainder = &remainder[start + 2..];
+            continue;
+        }
+        interpolated.push_str(&remainder[..start]);
+        let end = remainder[start..]
+            .find('}')
+            .ok_or(SimulationError::InvalidModelConfiguration)?;
+        let reference = &remainder[start + 2..start + end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        match (env::var(name), default) {
+            (Ok(value), _) => interpolated.push_str(&value),
+            (Err(_), Some(default)) => interpolated.push_str(default),
+            (Err(_), None) => {
+                return Err(SimulationError::UndefinedEnvironmentVariable(
+                    name.to_string(),
+                ))
+            }
+        }
+        remainder = &remainder[start + end + 1..];
+    }
+    interpolated.push_str(remainder);
+    Ok(interpolated)
+}
+
+/// Rewrite each `!include path` as a `{"$include": "path"}` mapping, which
+/// is resolved after parsing.
+fn tag_includes(configuration: &str) -> String {
+    configuration
+        .lines()
+        .map(|line| {
+            let index = match line.find(INCLUDE_TAG) {
+                Some(index) => index,
+                None => return line.to_string(),
+            };
+            let path = &line[index + INCLUDE_TAG.len()..];
+            let is_tag = line[..index].chars().last().is_none_or(char::is_whitespace)
+                && path.starts_with(char::is_whitespace);
+            if !is_tag {
+                return line.to_string();
+            }
+            let path = path.split(" #").next().unwrap_or_default().trim();
+            let path = path.trim_matches(|character| character == '"' || character == '\'');
+            format![
+                "{}{{{}: {}}}",
+                &line[..index],
+                serde_json::Value::from(INCLUDE_KEY),
+                serde_json::Value::from(path)
+            ]
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn include_path(value: &Value) -> Option<String> {
+    match value {
+        Value::Mapping(mapping) if mapping.len() == 1 => mapping
+            .get(&Value::from(INCLUDE_KEY))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+fn resolve_includes(
+    value: &mut Value,
+    directory: &Path,
+    including: &mut Vec<PathBuf>,
+) -> Result<(), SimulationError> {
+    if let Some(path) = include_path(value) {
+        *value = load_file(&directory.join(path), including)?;
+        return Ok(());
+    }
+    match value {
+        Value::Sequence(sequence) => {
+            let mut resolved = Vec::with_capacity(sequence.len());
+            for mut element in sequence.drain(..) {
+                let is_include = include_path(&element).is_some();
+                resolve_includes(&mut element, directory, including)?;
+                match element {
+                    Value::Sequence(elements) if is_include => resolved.extend(elements),
+                    element => resolved.push(element),
+                }
+            }
+            *sequence = resolved;
+            Ok(())
+        }
+        Value::Mapping(mapping) => mapping
+            .iter_mut()
+            .try_for_each(|(_, field)| resolve_includes(field, directory, including)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_keys_resolve_with_explicit_fields_taking_precedence() {
+        let mut value: Value = serde_yaml::from_str(
+            r#"
+base: &base
+  a: 1
+  b: 2
+other: &other
+  b: 3
+  c: 4
+single:
+  <<: *base
+  a: 10
+multiple:
+  <<: [*base, *other]
+nested:
+  - inner:
+      <<: *other
+"#,
+        )
+        .unwrap();
+        apply_merge(&mut value).unwrap();
+        let expected: Value = serde_yaml::from_str(
+            r#"
+base: {a: 1, b: 2}
+other: {b: 3, c: 4}
+single: {a: 10, b: 2}
+multiple: {a: 1, b: 2, c: 4}
+nested:
+  - inner: {b: 3, c: 4}
+"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn merge_of_a_scalar_is_invalid() {
+        let mut value: Value = serde_yaml::from_str("merged:\n  <<: 1\n").unwrap();
+        assert!(apply_merge(&mut value).is_err());
+    }
+
+    #[test]
+    fn environment_variables_are_interpolated() {
+        env::set_var("SIM_YAML_TEST_RATE", "0.25");
+        let configuration = "rate: ${SIM_YAML_TEST_RATE}\ncapacity: ${SIM_YAML_TEST_UNSET:-10}\nliteral: $${SIM_YAML_TEST_RATE}\n";
+        assert_eq!(
+            interpolate_env(configuration).unwrap(),
+            "rate: 0.25\ncapacity: 10\nliteral: ${SIM_YAML_TEST_RATE}\n"
+        );
+        assert!(matches!(
+            interpolate_env("rate: ${SIM_YAML_TEST_UNSET}"),
+            Err(SimulationError::UndefinedEnvironmentVariable(name)) if name == "SIM_YAML_TEST_UNSET"
+        ));
+    }
+
+    #[test]
+    fn includes_compose_configuration_files() {
+        let directory = env::temp_dir().join(format!["sim-yaml-{}", std::process::id()]);
+        fs::create_dir_all(directory.join("shared")).unwrap();
+        fs::write(
+            directory.join("main.yaml"),
+            "- name: first\n- !include shared/more.yaml # spliced\n- settings: !include \"shared/settings.yaml\"\n",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("shared/more.yaml"),
+            "- name: second\n- name: third\n",
+        )
+        .unwrap();
+        fs::write(directory.join("shared/settings.yaml"), "level: 3\n").unwrap();
+        let value: Value = from_file(directory.join("main.yaml")).unwrap();
+        let expected: Value = serde_yaml::from_str(
+            "- name: first\n- name: second\n- name: third\n- settings:\n    level: 3\n",
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+        // Include cycles are errors
+        fs::write(directory.join("cycle.yaml"), "- !include cycle.yaml\n").unwrap();
+        assert!(matches!(
+            from_file::<Value, _>(directory.join("cycle.yaml")),
+            Err(SimulationError::CyclicInclude(_))
+        ));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
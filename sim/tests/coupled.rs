@@ -1,9 +1,10 @@
 use sim::input_modeling::ContinuousRandomVariable;
 use sim::models::{
-    Coupled, ExternalOutputCoupling, Generator, InternalCoupling, Model, Processor, Storage,
+    model_factory, Coupled, ExternalInputCoupling, ExternalOutputCoupling, Generator,
+    InternalCoupling, Model, Processor, Sink, Storage,
 };
 use sim::output_analysis::{ConfidenceInterval, SteadyStateOutput};
-use sim::simulator::{Connector, Message, Simulation};
+use sim::simulator::{topology, Connector, Message, Simulation};
 use sim::utils::errors::SimulationError;
 
 fn get_message_number(message: &str) -> Option<&str> {
@@ -183,7 +184,7 @@ fn closure_under_coupling() -> Result<(), SimulationError> {
                         - arrivals
                             .iter()
                             .find(|arrival| {
-                                get_message_number(&arrival.1) == get_message_number(&departure.1)
+                                get_message_number(arrival.1) == get_message_number(departure.1)
                             })
                             .ok_or(SimulationError::DroppedMessageError)?
                             .0)
@@ -205,3 +206,568 @@ fn closure_under_coupling() -> Result<(), SimulationError> {
     ];
     Ok(())
 }
+
+fn processing_line() -> Model {
+    Model::new(
+        String::from("line-01"),
+        Box::new(Coupled::new(
+            vec![String::from("job")],
+            vec![String::from("processed")],
+            vec![Model::new(
+                String::from("processor-01"),
+                Box::new(Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: 0.5 },
+                    None,
+                    String::from("job"),
+                    String::from("processed"),
+                    false,
+                    None,
+                )),
+            )],
+            vec![ExternalInputCoupling {
+                target_id: String::from("processor-01"),
+                source_port: String::from("job"),
+                target_port: String::from("job"),
+            }],
+            vec![ExternalOutputCoupling {
+                source_id: String::from("processor-01"),
+                source_port: String::from("processed"),
+                target_port: String::from("processed"),
+            }],
+            Vec::new(),
+        )),
+    )
+}
+
+#[test]
+fn nested_coupled_models_route_like_flat_models() -> Result<(), SimulationError> {
+    let generator = Model::new(
+        String::from("generator-01"),
+        Box::new(Generator::new(
+            ContinuousRandomVariable::Exp { lambda: 0.4 },
+            None,
+            String::from("job"),
+            false,
+            None,
+        )),
+    );
+    let storage = Model::new(
+        String::from("storage-01"),
+        Box::new(Storage::new(
+            String::from("store"),
+            String::from("read"),
+            String::from("stored"),
+            false,
+        )),
+    );
+    let flat_models = vec![
+        generator.clone(),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        storage.clone(),
+    ];
+    // The processing line, nested in a plant
+    let nested_models = vec![
+        generator,
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Coupled::new(
+                vec![String::from("job")],
+                vec![String::from("processed")],
+                vec![processing_line()],
+                vec![ExternalInputCoupling {
+                    target_id: String::from("line-01"),
+                    source_port: String::from("job"),
+                    target_port: String::from("job"),
+                }],
+                vec![ExternalOutputCoupling {
+                    source_id: String::from("line-01"),
+                    source_port: String::from("processed"),
+                    target_port: String::from("processed"),
+                }],
+                Vec::new(),
+            )),
+        ),
+        storage,
+    ];
+    let connectors = vec![
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("processor-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("processor-01"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    let departures = |models: Vec<Model>| -> Result<Vec<(f64, String)>, SimulationError> {
+        let mut simulation = Simulation::post(models, connectors.clone());
+        Ok(simulation
+            .step_until(500.0)?
+            .iter()
+            .filter(|message| message.target_id() == "storage-01")
+            .map(|message| (*message.time(), message.content().to_string()))
+            .collect())
+    };
+    let flat_departures = departures(flat_models)?;
+    assert!(flat_departures.len() > 100);
+    assert_eq!(departures(nested_models)?, flat_departures);
+    Ok(())
+}
+
+#[test]
+fn couplings_to_unknown_components_are_invalid() {
+    let models = vec![Model::new(
+        String::from("coupled-01"),
+        Box::new(Coupled::new(
+            vec![String::from("job")],
+            Vec::new(),
+            vec![processing_line()],
+            vec![ExternalInputCoupling {
+                target_id: String::from("line-02"),
+                source_port: String::from("job"),
+                target_port: String::from("job"),
+            }],
+            Vec::new(),
+            Vec::new(),
+        )),
+    )];
+    let mut simulation = Simulation::post(models, Vec::new());
+    simulation.inject_input(Message::new(
+        String::from("manual"),
+        String::from("manual"),
+        String::from("coupled-01"),
+        String::from("job"),
+        0.0,
+        String::from("job 1"),
+    ));
+    let error = simulation.step().unwrap_err();
+    assert!(matches!(
+        error.root_cause(),
+        SimulationError::ModelNotFound(component_id) if component_id == "line-02"
+    ));
+    assert_eq!(error.module(), Some("Coupled"));
+}
+
+/// A reusable generator and processor sub-network, with the processed jobs
+/// on its output port.
+fn generation_line(id: &str, interarrival: f64, service: f64) -> Model {
+    Model::new(
+        String::from(id),
+        Box::new(Coupled::new(
+            Vec::new(),
+            vec![String::from("processed")],
+            vec![
+                Model::new(
+                    String::from("generator-01"),
+                    Box::new(Generator::new(
+                        ContinuousRandomVariable::Constant {
+                            value: interarrival,
+                        },
+                        None,
+                        String::from("job"),
+                        false,
+                        None,
+                    )),
+                ),
+                Model::new(
+                    String::from("processor-01"),
+                    Box::new(Processor::new(
+                        ContinuousRandomVariable::Constant { value: service },
+                        None,
+                        String::from("job"),
+                        String::from("processed"),
+                        false,
+                        None,
+                    )),
+                ),
+            ],
+            Vec::new(),
+            vec![ExternalOutputCoupling {
+                source_id: String::from("processor-01"),
+                source_port: String::from("processed"),
+                target_port: String::from("processed"),
+            }],
+            vec![InternalCoupling {
+                source_id: String::from("generator-01"),
+                target_id: String::from("processor-01"),
+                source_port: String::from("job"),
+                target_port: String::from("job"),
+            }],
+        )),
+    )
+}
+
+#[test]
+fn sub_network_instances_execute_like_flat_models() -> Result<(), SimulationError> {
+    let flat_models = vec![
+        Model::new(
+            String::from("generator-a"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-a"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 0.25 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("generator-b"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 2.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-b"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 0.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let mut flat_connectors = topology::pipeline(&["generator-a", "processor-a"], "job", "job");
+    flat_connectors.extend(topology::pipeline(
+        &["generator-b", "processor-b"],
+        "job",
+        "job",
+    ));
+    flat_connectors.extend(topology::pipeline(
+        &["processor-a", "sink-01"],
+        "processed",
+        "job",
+    ));
+    flat_connectors.extend(topology::pipeline(
+        &["processor-b", "sink-01"],
+        "processed",
+        "job",
+    ));
+    // Two instances of the same sub-network, with the same component IDs
+    let nested_models = vec![
+        generation_line("line-a", 1.0, 0.25),
+        generation_line("line-b", 2.0, 0.5),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let mut nested_connectors = topology::pipeline(&["line-a", "sink-01"], "processed", "job");
+    nested_connectors.extend(topology::pipeline(
+        &["line-b", "sink-01"],
+        "processed",
+        "job",
+    ));
+    let mut flat = Simulation::post(flat_models, flat_connectors);
+    let mut nested = Simulation::post(nested_models, nested_connectors);
+    let arrival_times = |simulation: &mut Simulation| -> Result<Vec<f64>, SimulationError> {
+        Ok(simulation
+            .step_until(20.0)?
+            .iter()
+            .filter(|message| message.target_id() == "sink-01")
+            .map(|message| *message.time())
+            .collect())
+    };
+    let flat_arrivals = arrival_times(&mut flat)?;
+    assert_eq!(flat_arrivals[..3], [1.25, 2.25, 2.5]);
+    assert_eq!(arrival_times(&mut nested)?, flat_arrivals);
+    // Nested components are addressable by path
+    assert_eq!(
+        nested.get_utilization("line-a/processor-01")?,
+        flat.get_utilization("processor-a")?
+    );
+    assert_eq!(
+        nested.get_utilization("line-b/processor-01")?,
+        flat.get_utilization("processor-b")?
+    );
+    assert!(matches!(
+        nested.get_utilization("line-a/processor-02"),
+        Err(SimulationError::ModelNotFound(path)) if path == "line-a/processor-02"
+    ));
+    Ok(())
+}
+
+#[test]
+fn nested_errors_report_component_paths() {
+    // The line couples its input to a missing component
+    let line = Model::new(
+        String::from("line-01"),
+        Box::new(Coupled::new(
+            vec![String::from("job")],
+            Vec::new(),
+            Vec::new(),
+            vec![ExternalInputCoupling {
+                target_id: String::from("processor-01"),
+                source_port: String::from("job"),
+                target_port: String::from("job"),
+            }],
+            Vec::new(),
+            Vec::new(),
+        )),
+    );
+    let models = vec![Model::new(
+        String::from("plant-01"),
+        Box::new(Coupled::new(
+            vec![String::from("job")],
+            Vec::new(),
+            vec![line],
+            vec![ExternalInputCoupling {
+                target_id: String::from("line-01"),
+                source_port: String::from("job"),
+                target_port: String::from("job"),
+            }],
+            Vec::new(),
+            Vec::new(),
+        )),
+    )];
+    let mut simulation = Simulation::post(models, Vec::new());
+    simulation.inject_input(Message::new(
+        String::from("manual"),
+        String::from("manual"),
+        String::from("plant-01"),
+        String::from("job"),
+        0.0,
+        String::from("job 1"),
+    ));
+    let error = simulation.step().unwrap_err();
+    assert_eq!(error.model_id(), Some("plant-01/line-01"));
+    assert_eq!(error.port(), Some("job"));
+}
+
+/// A processor, wrapped in the given number of coupled model layers.
+fn layered_processor(id: &str, layers: usize) -> Model {
+    let processor = Model::new(
+        String::from(id),
+        Box::new(Processor::new(
+            ContinuousRandomVariable::Exp { lambda: 0.5 },
+            None,
+            String::from("job"),
+            String::from("processed"),
+            false,
+            None,
+        )),
+    );
+    (0..layers).fold(processor, |component, layer| {
+        let component_id = component.id().to_string();
+        Model::new(
+            if layer + 1 == layers {
+                String::from(id)
+            } else {
+                format!["{}-layer-{}", id, layer + 1]
+            },
+            Box::new(Coupled::new(
+                vec![String::from("job")],
+                vec![String::from("processed")],
+                vec![component],
+                vec![ExternalInputCoupling {
+                    target_id: component_id.clone(),
+                    source_port: String::from("job"),
+                    target_port: String::from("job"),
+                }],
+                vec![ExternalOutputCoupling {
+                    source_id: component_id,
+                    source_port: String::from("processed"),
+                    target_port: String::from("processed"),
+                }],
+                Vec::new(),
+            )),
+        )
+    })
+}
+
+#[test]
+fn deep_hierarchies_route_like_flat_models() -> Result<(), SimulationError> {
+    let generator = Model::new(
+        String::from("generator-01"),
+        Box::new(Generator::new(
+            ContinuousRandomVariable::Exp { lambda: 0.4 },
+            None,
+            String::from("job"),
+            false,
+            None,
+        )),
+    );
+    let storage = Model::new(
+        String::from("storage-01"),
+        Box::new(Storage::new(
+            String::from("store"),
+            String::from("read"),
+            String::from("stored"),
+            false,
+        )),
+    );
+    let flat_models = vec![
+        generator.clone(),
+        layered_processor("processor-01", 0),
+        layered_processor("processor-02", 0),
+        storage.clone(),
+    ];
+    let flat_connectors = vec![
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("processor-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("processor-01"),
+            String::from("processor-02"),
+            String::from("processed"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-03"),
+            String::from("processor-02"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    // The processors, each under three coupling layers, coupled in series
+    // in a plant - messages between them cross six layers
+    let nested_models = vec![
+        generator,
+        Model::new(
+            String::from("plant-01"),
+            Box::new(Coupled::new(
+                vec![String::from("job")],
+                vec![String::from("processed")],
+                vec![
+                    layered_processor("processor-01", 3),
+                    layered_processor("processor-02", 3),
+                ],
+                vec![ExternalInputCoupling {
+                    target_id: String::from("processor-01"),
+                    source_port: String::from("job"),
+                    target_port: String::from("job"),
+                }],
+                vec![ExternalOutputCoupling {
+                    source_id: String::from("processor-02"),
+                    source_port: String::from("processed"),
+                    target_port: String::from("processed"),
+                }],
+                vec![InternalCoupling {
+                    source_id: String::from("processor-01"),
+                    target_id: String::from("processor-02"),
+                    source_port: String::from("processed"),
+                    target_port: String::from("job"),
+                }],
+            )),
+        ),
+        storage,
+    ];
+    let nested_connectors = vec![
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("plant-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("plant-01"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    let departures = |models: Vec<Model>,
+                      connectors: Vec<Connector>|
+     -> Result<Vec<(f64, String)>, SimulationError> {
+        let mut simulation = Simulation::post(models, connectors);
+        Ok(simulation
+            .step_until(500.0)?
+            .iter()
+            .filter(|message| message.target_id() == "storage-01")
+            .map(|message| (*message.time(), message.content().to_string()))
+            .collect())
+    };
+    let flat_departures = departures(flat_models, flat_connectors)?;
+    assert!(flat_departures.len() > 100);
+    assert_eq!(
+        departures(nested_models, nested_connectors)?,
+        flat_departures
+    );
+    Ok(())
+}
+
+#[test]
+fn parked_messages_to_unknown_components_are_errors() {
+    // Coupled models are not configurable by default - register the type,
+    // to restore a coupled model with a parked message
+    model_factory::register("Coupled", Coupled::from_value).unwrap();
+    let models: Vec<Model> = serde_yaml::from_str(
+        r#"
+- type: "Coupled"
+  id: "line-01"
+  portsIn:
+    flowPaths: []
+  portsOut:
+    flowPaths: []
+  components:
+    - type: "Storage"
+      id: "storage-01"
+      portsIn:
+        put: "store"
+        get: "read"
+      portsOut:
+        stored: "stored"
+  externalInputCouplings: []
+  externalOutputCouplings: []
+  internalCouplings: []
+  state:
+    parkedMessages:
+      - componentId: "storage-02"
+        port: "store"
+        content: "job 1"
+    records: []
+"#,
+    )
+    .unwrap();
+    let mut simulation = Simulation::post(models, Vec::new());
+    let error = simulation.step().unwrap_err();
+    assert!(matches!(
+        error.root_cause(),
+        SimulationError::ModelNotFound(component_id) if component_id == "storage-02"
+    ));
+    assert_eq!(error.model_id(), Some("line-01"));
+}
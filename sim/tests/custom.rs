@@ -1,11 +1,15 @@
-use std::f64::INFINITY;
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 use sim::input_modeling::ContinuousRandomVariable;
+use sim::models::model_factory;
 use sim::models::model_trait::{DevsModel, Reportable, ReportableModel, SerializableModel};
-use sim::models::{Generator, Model, ModelMessage, ModelRecord};
-use sim::simulator::{Connector, Message, Services, Simulation, WebSimulation};
+use sim::models::{Generator, Model, ModelMessage, ModelRecord, Processor};
+use sim::simulator::{
+    BlackboardValue, Connector, JobId, Message, Payload, Services, Simulation, WebSimulation,
+};
 use sim::utils::errors::SimulationError;
+use sim::utils::fuzz::{FuzzModel, TopologyFuzzer};
 use sim_derive::{register, SerializableModel};
 use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
 
@@ -69,7 +73,7 @@ impl DevsModel for Passive {
     fn until_next_event(&self) -> f64 {
         // No future events list, as a source of finite until_next_event
         // values
-        INFINITY
+        f64::INFINITY
     }
 }
 
@@ -85,6 +89,227 @@ impl Reportable for Passive {
 
 impl ReportableModel for Passive {}
 
+/// The tally model publishes its count of arrivals to the blackboard
+#[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
+#[serde(rename_all = "camelCase")]
+pub struct Tally {
+    key: String,
+    #[serde(default)]
+    state: State,
+}
+
+impl DevsModel for Tally {
+    fn events_ext(
+        &mut self,
+        _incoming_message: &ModelMessage,
+        services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        let arrivals = services.blackboard().number(&self.key).unwrap_or(0.0);
+        services.write_blackboard(&self.key, arrivals + 1.0);
+        Ok(())
+    }
+
+    fn events_int(
+        &mut self,
+        _services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        Ok(Vec::new())
+    }
+
+    fn time_advance(&mut self, _time_delta: f64) {}
+
+    fn until_next_event(&self) -> f64 {
+        f64::INFINITY
+    }
+}
+
+impl Reportable for Tally {
+    fn status(&self) -> String {
+        "Tallying".into()
+    }
+
+    fn records(&self) -> &Vec<ModelRecord> {
+        &self.state.records
+    }
+}
+
+impl ReportableModel for Tally {}
+
+/// The journal model appends the content of its arrivals to a shared
+/// blackboard entry, recording the delivery order across models
+#[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
+#[serde(rename_all = "camelCase")]
+pub struct Journal {
+    #[serde(default)]
+    state: State,
+}
+
+impl DevsModel for Journal {
+    fn events_ext(
+        &mut self,
+        incoming_message: &ModelMessage,
+        services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        let journal = match services.blackboard().text("journal") {
+            Some(journal) => format!["{} {}", journal, incoming_message.content],
+            None => incoming_message.content.clone(),
+        };
+        services.write_blackboard("journal", journal);
+        Ok(())
+    }
+
+    fn events_int(
+        &mut self,
+        _services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        Ok(Vec::new())
+    }
+
+    fn time_advance(&mut self, _time_delta: f64) {}
+
+    fn until_next_event(&self) -> f64 {
+        f64::INFINITY
+    }
+}
+
+impl Reportable for Journal {
+    fn status(&self) -> String {
+        "Journaling".into()
+    }
+
+    fn records(&self) -> &Vec<ModelRecord> {
+        &self.state.records
+    }
+}
+
+impl ReportableModel for Journal {}
+
+/// The faulty model schedules its next event at a fixed, possibly invalid,
+/// time advance
+#[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
+#[serde(rename_all = "camelCase")]
+pub struct Faulty {
+    until_next_event: f64,
+    #[serde(default)]
+    state: State,
+}
+
+impl DevsModel for Faulty {
+    fn events_ext(
+        &mut self,
+        _incoming_message: &ModelMessage,
+        _services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        Ok(())
+    }
+
+    fn events_int(
+        &mut self,
+        _services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        Ok(Vec::new())
+    }
+
+    fn time_advance(&mut self, _time_delta: f64) {}
+
+    fn until_next_event(&self) -> f64 {
+        self.until_next_event
+    }
+}
+
+impl Reportable for Faulty {
+    fn status(&self) -> String {
+        "Faulty".into()
+    }
+
+    fn records(&self) -> &Vec<ModelRecord> {
+        &self.state.records
+    }
+}
+
+impl ReportableModel for Faulty {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    sku: String,
+    quantity: u32,
+    unit_price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Invoice {
+    sku: String,
+    total: f64,
+}
+
+/// The pricer model invoices the orders carried in typed message payloads
+#[derive(Debug, Clone, Serialize, Deserialize, SerializableModel)]
+#[serde(rename_all = "camelCase")]
+pub struct Pricer {
+    #[serde(default)]
+    invoices: Vec<Invoice>,
+    #[serde(default)]
+    state: State,
+}
+
+impl DevsModel for Pricer {
+    fn events_ext(
+        &mut self,
+        incoming_message: &ModelMessage,
+        _services: &mut Services,
+    ) -> Result<(), SimulationError> {
+        let order: Order = incoming_message.payload_as()?;
+        self.invoices.push(Invoice {
+            total: f64::from(order.quantity) * order.unit_price,
+            sku: order.sku,
+        });
+        Ok(())
+    }
+
+    fn events_int(
+        &mut self,
+        _services: &mut Services,
+    ) -> Result<Vec<ModelMessage>, SimulationError> {
+        self.invoices
+            .drain(..)
+            .map(|invoice| {
+                Ok(ModelMessage {
+                    port_name: String::from("invoice"),
+                    content: invoice.sku.clone(),
+                    job_id: None,
+                    metadata: HashMap::new(),
+                    payload: Some(Payload::json(&invoice)?),
+                    priority: 0,
+                })
+            })
+            .collect()
+    }
+
+    fn time_advance(&mut self, _time_delta: f64) {}
+
+    fn until_next_event(&self) -> f64 {
+        if self.invoices.is_empty() {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Reportable for Pricer {
+    fn status(&self) -> String {
+        "Pricing".into()
+    }
+
+    fn records(&self) -> &Vec<ModelRecord> {
+        &self.state.records
+    }
+}
+
+impl ReportableModel for Pricer {}
+
 #[test]
 fn step_n_with_custom_passive_model() -> Result<(), SimulationError> {
     let models = [
@@ -143,11 +368,276 @@ fn step_n_with_custom_passive_model_wasm() {
   sourcePort: "job"
   targetPort: "job"
 "#;
-    register![Passive];
-    let mut simulation = WebSimulation::post_yaml(&models, &connectors);
+    register![Passive].unwrap();
+    let mut simulation = WebSimulation::post_yaml(models, connectors);
     // 1 initialization event, and 2 events per generation
     let messages: Vec<Message> = serde_json::from_str(&simulation.step_n_json(9)).unwrap();
     let generations_count = messages.len();
     let expected = 4; // 4 interarrivals from 9 steps
     assert_eq!(generations_count, expected);
 }
+
+#[test]
+fn namespaced_model_registration() -> Result<(), SimulationError> {
+    model_factory::register_namespaced("myco", "Passive", Passive::from_value)?;
+    assert!(model_factory::is_registered("myco::Passive"));
+    let registered = model_factory::registered();
+    assert!(registered.contains(&String::from("myco::Passive")));
+    assert!(registered.contains(&String::from("Processor")));
+    // Duplicate registrations, including of built-in models, are errors
+    assert!(matches!(
+        model_factory::register_namespaced("myco", "Passive", Passive::from_value),
+        Err(SimulationError::DuplicateModelType(model_type)) if model_type == "myco::Passive"
+    ));
+    assert!(matches!(
+        model_factory::register("Processor", Passive::from_value),
+        Err(SimulationError::DuplicateModelType(_))
+    ));
+    assert!(matches!(
+        model_factory::register("myco::", Passive::from_value),
+        Err(SimulationError::InvalidModelType(_))
+    ));
+    let models: Vec<Model> = serde_yaml::from_str(
+        r#"
+- type: "myco::Passive"
+  id: "passive-01"
+  portsIn:
+    job: "job"
+"#,
+    )?;
+    assert_eq!(models[0].model_type(), "myco::Passive");
+    // Namespaced model types are retained through serialization
+    let roundtrip: Vec<Model> = serde_yaml::from_str(&serde_yaml::to_string(&models)?)?;
+    assert_eq!(roundtrip[0].model_type(), "myco::Passive");
+    let unknown: Result<Vec<Model>, _> = serde_yaml::from_str(
+        r#"
+- type: "otherco::Passive"
+  id: "passive-01"
+"#,
+    );
+    assert!(unknown
+        .err()
+        .is_some_and(|error| error.to_string().contains("myco::Passive")));
+    Ok(())
+}
+
+#[test]
+fn fuzz_with_custom_passive_model() -> Result<(), SimulationError> {
+    let mut fuzzer = TopologyFuzzer::new(6, 4, 100);
+    fuzzer.register("Passive", |_rng| {
+        Ok(FuzzModel {
+            model: Box::new(Passive::new(String::from("job"))),
+            ports_in: vec![String::from("job")],
+            ports_out: Vec::new(),
+        })
+    });
+    let cases = (0..100)
+        .map(|seed| fuzzer.generate(seed))
+        .collect::<Result<Vec<_>, SimulationError>>()?;
+    assert!(cases.iter().any(|case| case
+        .models
+        .iter()
+        .any(|model| model.id().starts_with("passive"))));
+    cases.iter().for_each(|case| {
+        case.run(100);
+    });
+    Ok(())
+}
+
+#[test]
+fn models_coordinate_through_the_blackboard() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("tally-01"),
+            Box::new(Tally {
+                key: String::from("arrivals"),
+                state: State::default(),
+            }),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("generator-01"),
+        String::from("tally-01"),
+        String::from("job"),
+        String::from("job"),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    // 1 initialization event, and 2 events per generation
+    simulation.step_n(9)?;
+    let entry = simulation.get_blackboard().get("arrivals").unwrap();
+    assert_eq!(entry.value, BlackboardValue::Number(4.0));
+    assert!(entry.changed_at > 0.0 && entry.changed_at <= simulation.get_global_time());
+    assert_eq!(simulation.get_blackboard().text("arrivals"), None);
+    simulation.write_blackboard("shift", "night");
+    assert_eq!(simulation.get_blackboard().text("shift"), Some("night"));
+    simulation.reset();
+    assert!(simulation.get_blackboard().is_empty());
+    Ok(())
+}
+
+#[test]
+fn message_priorities_order_deliveries_across_models() -> Result<(), SimulationError> {
+    let models: Vec<Model> = ["journal-01", "journal-02"]
+        .iter()
+        .map(|model_id| {
+            Model::new(
+                model_id.to_string(),
+                Box::new(Journal {
+                    state: State::default(),
+                }),
+            )
+        })
+        .collect();
+    let mut simulation = Simulation::post(models, Vec::new());
+    vec![
+        ("journal-01", "data-1", 0),
+        ("journal-02", "interrupt-1", 10),
+        ("journal-01", "interrupt-2", 10),
+        ("journal-02", "data-2", 0),
+        ("journal-02", "control", 5),
+    ]
+    .into_iter()
+    .for_each(|(target_id, content, priority)| {
+        simulation.inject_input(
+            Message::new(
+                String::from("manual"),
+                String::from("manual"),
+                target_id.to_string(),
+                String::from("job"),
+                0.0,
+                content.to_string(),
+            )
+            .with_priority(priority),
+        )
+    });
+    simulation.step()?;
+    // Within a priority, deliveries are in model order, and then in message
+    // order
+    assert_eq!(
+        simulation.get_blackboard().text("journal"),
+        Some("interrupt-2 interrupt-1 control data-1 data-2")
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_time_advances_are_errors() {
+    [-1.0, f64::NAN].iter().for_each(|until_next_event| {
+        let models = [Model::new(
+            String::from("faulty-01"),
+            Box::new(Faulty {
+                until_next_event: *until_next_event,
+                state: State::default(),
+            }),
+        )];
+        let mut simulation = Simulation::post(models.to_vec(), Vec::new());
+        match simulation.step() {
+            Err(SimulationError::InvalidTimeAdvance { model_id, value }) => {
+                assert_eq!(model_id, "faulty-01");
+                assert!(value.is_nan() || value == -1.0);
+            }
+            _ => panic!("expected an invalid time advance error"),
+        }
+        assert_eq!(simulation.get_global_time(), 0.0);
+    });
+}
+
+#[test]
+fn models_exchange_typed_payloads() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("pricer-01"),
+            Box::new(Pricer {
+                invoices: Vec::new(),
+                state: State::default(),
+            }),
+        ),
+        Model::new(
+            String::from("passive-01"),
+            Box::new(Passive::new(String::from("invoice"))),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("processor-01"),
+            String::from("pricer-01"),
+            String::from("processed"),
+            String::from("order"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("pricer-01"),
+            String::from("passive-01"),
+            String::from("invoice"),
+            String::from("invoice"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    let order = Order {
+        sku: String::from("A-1"),
+        quantity: 3,
+        unit_price: 2.5,
+    };
+    simulation.inject_input(
+        Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from("processor-01"),
+            String::from("job"),
+            0.0,
+            String::from("order-01"),
+        )
+        .with_job_id(Some(JobId::new(String::from("manual"), 1)))
+        .with_payload(Some(Payload::json(&order)?)),
+    );
+    let messages = simulation.step_n(4)?;
+    // The processor does not set a payload, so the order payload is
+    // inherited through the processor, by job ID
+    let processed = messages
+        .iter()
+        .find(|message| message.source_id() == "processor-01")
+        .unwrap();
+    assert_eq!(processed.payload_as::<Order>()?.sku, "A-1");
+    let invoice = messages
+        .iter()
+        .find(|message| message.source_id() == "pricer-01")
+        .unwrap();
+    assert_eq!(
+        invoice.payload_as::<Invoice>()?,
+        Invoice {
+            sku: String::from("A-1"),
+            total: 7.5,
+        }
+    );
+    // Payloads survive serialization
+    let serialized: Message = serde_json::from_str(&serde_json::to_string(invoice)?)?;
+    assert_eq!(serialized.payload(), invoice.payload());
+    assert!(matches!(
+        Payload::Bytes(vec![1, 2]).decode::<Invoice>(),
+        Err(SimulationError::InvalidPayload)
+    ));
+    Ok(())
+}
@@ -1,12 +1,30 @@
-use sim::input_modeling::{BooleanRandomVariable, ContinuousRandomVariable, IndexRandomVariable};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use sim::input_modeling::random_variable::ScheduleEntry;
+use sim::input_modeling::{
+    BooleanRandomVariable, ContinuousRandomVariable, IndexRandomVariable, Thinning,
+};
+use sim::models::model_trait::Reportable;
+use sim::models::processor::ServiceTimeThreshold;
 use sim::models::stopwatch::Metric as StopwatchMetric;
 use sim::models::{
-    Batcher, ExclusiveGateway, Gate, Generator, LoadBalancer, Model, ParallelGateway, Processor,
-    StochasticGate, Stopwatch, Storage,
+    AssumptionTopic, Batcher, Delay, ExclusiveGateway, Gate, Generator, LoadBalancer, Model,
+    ParallelGateway, Processor, Query, ScheduleRule, Scheduler, Sink, StochasticGate, Stopwatch,
+    Storage,
 };
 use sim::output_analysis::{IndependentSample, SteadyStateOutput};
-use sim::simulator::{Connector, Message, Simulation};
-use sim::utils::errors::SimulationError;
+use sim::reference::{self, ReferenceModel};
+use sim::simulator::template::split_entries;
+use sim::simulator::{
+    topology, Assertion, Checkpoint, Connector, EditOperation, EnergyCoefficients, EventKind,
+    EventScheduling, InitialCondition, InjectionPriority, JobId, Message, MessageFilter, Messages,
+    ModelEntry, ModelTemplate, PoolScheduling, PortDirection, QuotaKind, Quotas, RealTimeExecutor,
+    RngStreams, RunManifest, Simulation, SimulationEvent, SimulationObserver, SimulationPool,
+    SnapshotCompression, StopCondition, TransitionKind, ValidationError, ValidationOptions,
+    ValidationSeverity, Verbosity, CORRELATION_ID_KEY, JOB_ID_KEY,
+};
+use sim::utils::errors::{ErrorContext, SimulationError};
 
 fn epsilon() -> f64 {
     0.34
@@ -90,7 +108,7 @@ fn poisson_generator_processor_with_capacity() -> Result<(), SimulationError> {
                 - arrivals
                     .iter()
                     .find(|arrival| {
-                        get_message_number(&arrival.1) == get_message_number(&departure.1)
+                        get_message_number(arrival.1) == get_message_number(departure.1)
                     })
                     .ok_or(SimulationError::DroppedMessageError)?
                     .0)
@@ -109,7 +127,7 @@ fn poisson_generator_processor_with_capacity() -> Result<(), SimulationError> {
 
     // Effective Arrival Rate
     let last_processed_job = get_message_number(
-        &departures
+        departures
             .iter()
             .last()
             .ok_or(SimulationError::DroppedMessageError)?
@@ -117,7 +135,7 @@ fn poisson_generator_processor_with_capacity() -> Result<(), SimulationError> {
     );
     let count_generated = arrivals
         .iter()
-        .position(|arrival| get_message_number(&arrival.1) == last_processed_job)
+        .position(|arrival| get_message_number(arrival.1) == last_processed_job)
         .ok_or(SimulationError::DroppedMessageError)?
         + 1;
     let count_processed = departures.len();
@@ -349,7 +367,7 @@ fn exclusive_gateway_proportions_chi_square() -> Result<(), SimulationError> {
         let messages_set: Vec<Message> = simulation.step()?;
         message_records.extend(messages_set);
     }
-    let outputs = vec![
+    let outputs = [
         message_records
             .iter()
             .filter(|message_record| message_record.target_id() == "storage-01")
@@ -584,7 +602,7 @@ fn load_balancer_round_robin_outputs() -> Result<(), SimulationError> {
     // 3 steps per processed job
     // 1 step for initialization
     let message_records: Vec<Message> = simulation.step_n(28)?;
-    let outputs = vec![
+    let outputs = [
         message_records
             .iter()
             .filter(|message_record| message_record.target_id() == "storage-01")
@@ -954,7 +972,7 @@ fn batch_sizing() -> Result<(), SimulationError> {
     // Partial batches should exist
     let exists_partial_batch = batch_sizes.iter().any(|batch_size| *batch_size < 10);
     // Full batches should exist
-    let exists_full_batch = batch_sizes.iter().any(|batch_size| *batch_size == 10);
+    let exists_full_batch = batch_sizes.contains(&10);
     // Batches larger than the max batch size should not exist
     let exists_oversized_batch = batch_sizes.iter().any(|batch_size| *batch_size > 10);
     assert![exists_partial_batch];
@@ -1102,3 +1120,5255 @@ fn min_and_max_stopwatch() -> Result<(), SimulationError> {
     assert![responses[0].content() != responses[1].content()];
     Ok(())
 }
+
+#[test]
+fn reporting_models_honor_query_protocol() -> Result<(), SimulationError> {
+    assert!(Query::validate("summary").is_ok());
+    assert!(Query::validate("42").is_err());
+    let models = [
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("query"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+        Model::new(
+            String::from("stopwatch-01"),
+            Box::new(Stopwatch::new(
+                String::from("start"),
+                String::from("stop"),
+                String::from("query"),
+                String::from("job"),
+                StopwatchMetric::Minimum,
+                false,
+            )),
+        ),
+        Model::new(
+            String::from("storage-02"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("storage-01"),
+            String::from("storage-02"),
+            String::from("stored"),
+            String::from("store"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("stopwatch-01"),
+            String::from("storage-02"),
+            String::from("job"),
+            String::from("store"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    let request = |target: &str, port: &str, content: &str| {
+        Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from(target),
+            String::from(port),
+            0.0,
+            String::from(content),
+        )
+    };
+    simulation.inject_input(request("storage-01", "store", "job 1"));
+    simulation.inject_input(request("stopwatch-01", "start", "job 1"));
+    simulation.step()?;
+    simulation.inject_input(request("stopwatch-01", "stop", "job 1"));
+    simulation.step()?;
+    // Every reporting model responds to the same queries
+    simulation.inject_input(request("storage-01", "query", "summary"));
+    simulation.inject_input(request("stopwatch-01", "query", "summary"));
+    let responses: Vec<String> = simulation
+        .step()?
+        .iter()
+        .map(|response| response.content().to_string())
+        .collect();
+    assert_eq!(responses.len(), 2);
+    assert!(responses.contains(&String::from("{\"stored\":\"job 1\"}")));
+    assert!(responses.contains(&String::from(
+        "{\"count\":1,\"average\":0.0,\"minimum\":0.0,\"maximum\":0.0}"
+    )));
+    simulation.inject_input(request("storage-01", "query", "clear"));
+    simulation.inject_input(request("stopwatch-01", "query", "clear"));
+    let responses = simulation.step()?;
+    assert_eq!(responses.len(), 2);
+    assert!(responses
+        .iter()
+        .all(|response| response.content() == "cleared"));
+    assert_eq!(simulation.get_status("storage-01")?, "Empty");
+    assert_eq!(
+        simulation.get_status("stopwatch-01")?,
+        "Measuring durations"
+    );
+    // Get requests, including unknown requests, keep the original behavior
+    simulation.inject_input(request("storage-01", "store", "job 2"));
+    simulation.step()?;
+    simulation.inject_input(request("storage-01", "query", "42"));
+    let responses = simulation.step()?;
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].content(), "job 2");
+    Ok(())
+}
+
+#[test]
+fn deterministic_mode_uses_distribution_means() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Uniform { min: 0.5, max: 1.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("processor-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("processor-01"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    simulation.enable_deterministic_mode();
+    let messages = simulation.step_until(20.0)?;
+    // Jobs are generated every 2.0 time units, and processed in 1.0
+    messages
+        .iter()
+        .for_each(|message| match message.source_id() {
+            "generator-01" => assert_eq!(message.time() % 2.0, 0.0),
+            _ => assert_eq!(message.time() % 2.0, 1.0),
+        });
+    assert!(!messages.is_empty());
+    simulation.disable_deterministic_mode();
+    let messages = simulation.step_until(40.0)?;
+    assert!(messages.iter().any(|message| message.time().fract() != 0.0));
+    Ok(())
+}
+
+#[test]
+fn variate_recording_is_bounded_and_attributed() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Uniform { min: 1.0, max: 2.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("generator-01"),
+        String::from("processor-01"),
+        String::from("job"),
+        String::from("job"),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    simulation.step_n(10)?;
+    assert!(simulation.get_variate_records().is_empty());
+    simulation.enable_variate_recording(25);
+    simulation.step_n(200)?;
+    let records = simulation.get_variate_records();
+    assert_eq!(records.len(), 25);
+    assert!(records
+        .windows(2)
+        .all(|window| window[0].time <= window[1].time));
+    assert!(records
+        .iter()
+        .any(|record| record.model_id == "generator-01"));
+    records
+        .iter()
+        .filter(|record| record.model_id == "processor-01")
+        .for_each(|record| {
+            assert!(record.distribution.contains("uniform"));
+            assert!(record.value >= 1.0 && record.value < 2.0);
+        });
+    simulation.disable_variate_recording();
+    assert!(simulation.get_variate_records().is_empty());
+    Ok(())
+}
+
+#[test]
+fn simulation_summary_overview() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+        Model::new(
+            String::from("storage-02"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("generator-01"),
+        String::from("storage-01"),
+        String::from("job"),
+        String::from("store"),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    simulation.step_n(2)?;
+    let summary = simulation.summary();
+    assert_eq!(summary.model_counts["Generator"], 1);
+    assert_eq!(summary.model_counts["Storage"], 2);
+    assert_eq!(summary.connector_count, 1);
+    assert_eq!(summary.message_count, 1);
+    assert_eq!(summary.rng_seed, Some(42));
+    assert_eq!(summary.global_time, simulation.get_global_time());
+    assert_eq!(summary.models[0].status, "Generating jobs");
+    let text = summary.to_string();
+    assert!(text.contains("Models: 3 (Generator: 1, Storage: 2)"));
+    assert!(text.contains("storage-01 (Storage): Empty"));
+    Ok(())
+}
+
+#[test]
+fn state_diff_reports_changed_fields() -> Result<(), SimulationError> {
+    let models = [Model::new(
+        String::from("storage-01"),
+        Box::new(Storage::new(
+            String::from("store"),
+            String::from("read"),
+            String::from("stored"),
+            false,
+        )),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), Vec::new());
+    simulation.enable_state_diffs();
+    simulation.inject_input(Message::new(
+        String::from("manual"),
+        String::from("manual"),
+        String::from("storage-01"),
+        String::from("store"),
+        simulation.get_global_time(),
+        String::from("42"),
+    ));
+    simulation.step()?;
+    let changes = simulation.diff_last_step("storage-01")?;
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].path, "/state/job");
+    assert_eq!(changes[0].before, Some(serde_json::Value::Null));
+    assert_eq!(changes[0].after, Some(serde_json::json!("42")));
+    assert_eq!(changes[1].path, "/state/portStats");
+    assert_eq!(changes[1].before, None);
+    assert!(matches!(
+        simulation.diff_last_step("storage-02"),
+        Err(SimulationError::ModelNotFound(_))
+    ));
+    simulation.disable_state_diffs();
+    assert!(simulation.diff_last_step("storage-01")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn job_ids_follow_jobs_through_processors() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.333333 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("processor-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("processor-01"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    let messages = simulation.step_n(500)?;
+    let generated: Vec<JobId> = messages
+        .iter()
+        .filter(|message| message.source_id() == "generator-01")
+        .map(|message| message.job_id().unwrap())
+        .collect();
+    let processed: Vec<JobId> = messages
+        .iter()
+        .filter(|message| message.source_id() == "processor-01")
+        .map(|message| message.job_id().unwrap())
+        .collect();
+    assert!(!processed.is_empty());
+    generated.iter().enumerate().for_each(|(index, job_id)| {
+        assert_eq!(job_id.source_id, "generator-01");
+        assert_eq!(job_id.sequence, index + 1);
+    });
+    // FIFO processing preserves the job order
+    assert_eq!(processed[..], generated[..processed.len()]);
+    // Job IDs are carried as message metadata
+    let first_job = messages
+        .iter()
+        .find(|message| message.source_id() == "generator-01")
+        .unwrap();
+    assert_eq!(
+        first_job.metadata().get(JOB_ID_KEY),
+        Some(&String::from("generator-01/1"))
+    );
+    assert_eq!(
+        "network/generator-01/3".parse::<JobId>()?,
+        JobId::new(String::from("network/generator-01"), 3)
+    );
+    assert!("generator-01".parse::<JobId>().is_err());
+    Ok(())
+}
+
+#[test]
+fn correlation_ids_propagate_through_models() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("load-balancer-01"),
+            Box::new(LoadBalancer::new(
+                String::from("request"),
+                vec![String::from("server-1"), String::from("server-2")],
+                false,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("processor-01"),
+            String::from("load-balancer-01"),
+            String::from("processed"),
+            String::from("request"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("load-balancer-01"),
+            String::from("storage-01"),
+            String::from("server-1"),
+            String::from("store"),
+        ),
+        Connector::new(
+            String::from("connector-03"),
+            String::from("load-balancer-01"),
+            String::from("storage-01"),
+            String::from("server-2"),
+            String::from("store"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    ["order-1", "order-2", "order-3"]
+        .iter()
+        .enumerate()
+        .for_each(|(index, order)| {
+            simulation.inject_input(
+                Message::new(
+                    String::from("manual"),
+                    String::from("manual"),
+                    String::from("processor-01"),
+                    String::from("job"),
+                    simulation.get_global_time(),
+                    format!["{} contents", order],
+                )
+                .with_job_id(Some(JobId::new(String::from("manual"), index + 1)))
+                .with_correlation_id(Some(order.to_string())),
+            );
+        });
+    let messages = simulation.step_n(20)?;
+    let stored: Vec<&Message> = messages
+        .iter()
+        .filter(|message| message.target_id() == "storage-01")
+        .collect();
+    assert_eq!(stored.len(), 3);
+    stored.iter().for_each(|message| {
+        let correlation_id = message.correlation_id().unwrap();
+        assert_eq!(message.content(), format!["{} contents", correlation_id]);
+        // Correlation IDs are carried in the message metadata
+        assert_eq!(
+            message
+                .metadata()
+                .get(CORRELATION_ID_KEY)
+                .map(String::as_str),
+            Some(correlation_id)
+        );
+    });
+    Ok(())
+}
+
+#[test]
+fn correlation_contexts_are_released_for_consumed_jobs() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                Some(2),
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 100.0, false)),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("processor-01"),
+        String::from("sink-01"),
+        String::from("processed"),
+        String::from("job"),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    let pending_count = |simulation: &Simulation| {
+        serde_json::to_value(simulation).unwrap()["correlations"]["pending"]
+            .as_object()
+            .map_or(0, |pending| pending.len())
+    };
+    (1..=6).for_each(|index| {
+        simulation.inject_input(
+            Message::new(
+                String::from("manual"),
+                String::from("manual"),
+                String::from("processor-01"),
+                String::from("job"),
+                simulation.get_global_time(),
+                format!["job {}", index],
+            )
+            .with_job_id(Some(JobId::new(String::from("manual"), index)))
+            .with_correlation_id(Some(format!["order-{}", index])),
+        );
+    });
+    simulation.step()?;
+    // The queue holds two jobs, and the jobs dropped at the full queue are
+    // released
+    assert_eq!(pending_count(&simulation), 2);
+    let messages = simulation.step_n(10)?;
+    let consumed: Vec<&Message> = messages
+        .iter()
+        .filter(|message| message.target_id() == "sink-01")
+        .collect();
+    assert_eq!(consumed.len(), 2);
+    consumed.iter().for_each(|message| {
+        assert_eq!(
+            message.correlation_id(),
+            Some(format!["order-{}", message.job_id().unwrap().sequence].as_str())
+        );
+    });
+    // The jobs consumed by the sink are released
+    assert_eq!(pending_count(&simulation), 0);
+    // The pending contexts are bounded, for jobs held indefinitely
+    let mut simulation = Simulation::post(
+        vec![Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0e-9 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        )],
+        Vec::new(),
+    );
+    (1..=10_050).for_each(|index| {
+        simulation.inject_input(
+            Message::new(
+                String::from("manual"),
+                String::from("manual"),
+                String::from("processor-01"),
+                String::from("job"),
+                0.0,
+                format!["job {}", index],
+            )
+            .with_job_id(Some(JobId::new(String::from("manual"), index)))
+            .with_correlation_id(Some(format!["order-{}", index])),
+        );
+    });
+    simulation.step()?;
+    assert_eq!(pending_count(&simulation), 10_000);
+    Ok(())
+}
+
+#[test]
+fn message_metadata_propagates_through_models() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("stochastic-gate-01"),
+            Box::new(StochasticGate::new(
+                BooleanRandomVariable::Bernoulli { p: 1.0 },
+                String::from("job"),
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("stochastic-gate-01"),
+            String::from("processor-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("processor-01"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    let metadata: HashMap<String, String> = [
+        (String::from("priority"), String::from("high")),
+        (String::from("class"), String::from("gold")),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    simulation.inject_input(
+        Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from("stochastic-gate-01"),
+            String::from("job"),
+            simulation.get_global_time(),
+            String::from("job 1"),
+        )
+        .with_metadata(metadata.clone()),
+    );
+    let messages = simulation.step_n(10)?;
+    let stored = messages
+        .iter()
+        .find(|message| message.target_id() == "storage-01")
+        .unwrap();
+    assert_eq!(stored.metadata(), &metadata);
+    let serialized = serde_json::to_string(stored).unwrap();
+    let deserialized: Message = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.metadata(), &metadata);
+    Ok(())
+}
+
+#[test]
+fn audit_log_records_mutations() -> Result<(), SimulationError> {
+    let models = [Model::new(
+        String::from("storage-01"),
+        Box::new(Storage::new(
+            String::from("store"),
+            String::from("read"),
+            String::from("stored"),
+            false,
+        )),
+    )];
+    let mut simulation = Simulation::default();
+    assert!(matches!(
+        simulation.get_audit_log(),
+        Err(SimulationError::AuditLogUnavailable)
+    ));
+    simulation.enable_audit_log(16);
+    assert!(simulation.get_audit_log()?.is_empty());
+    simulation.put(models.to_vec(), Vec::new());
+    simulation.inject_input(Message::new(
+        String::from("manual"),
+        String::from("manual"),
+        String::from("storage-01"),
+        String::from("store"),
+        simulation.get_global_time(),
+        String::from("42"),
+    ));
+    simulation.step_n(2)?;
+    simulation.reset();
+    let audit_log = simulation.get_audit_log()?;
+    let actions: Vec<&str> = audit_log
+        .iter()
+        .map(|record| record.action.as_str())
+        .collect();
+    assert_eq!(actions, ["Put", "Inject Input", "Reset"]);
+    assert!(audit_log
+        .windows(2)
+        .all(|records| records[0].wall_time <= records[1].wall_time));
+    let injected: Message = serde_json::from_str(&audit_log[1].detail)?;
+    assert_eq!(injected.content(), "42");
+    let put: serde_json::Value = serde_json::from_str(&audit_log[0].detail)?;
+    assert_eq!(put["models"][0]["id"], "storage-01");
+    Ok(())
+}
+
+#[test]
+fn audit_log_is_bounded_and_serialized_only_when_enabled() -> Result<(), SimulationError> {
+    let models = [Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("job"), 10.0, false)),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), Vec::new());
+    let inject = |simulation: &mut Simulation, content: usize| {
+        simulation.inject_input(Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from("sink-01"),
+            String::from("job"),
+            simulation.get_global_time(),
+            content.to_string(),
+        ))
+    };
+    // Disabled audit logs record nothing, and are not serialized
+    (0..10).for_each(|content| inject(&mut simulation, content));
+    assert!(!serde_json::to_string(&simulation)?.contains("auditLog"));
+    // Enabled audit logs retain the latest records, up to the capacity
+    simulation.enable_audit_log(3);
+    (10..20).for_each(|content| inject(&mut simulation, content));
+    let contents = |simulation: &Simulation| -> Result<Vec<String>, SimulationError> {
+        simulation
+            .get_audit_log()?
+            .iter()
+            .map(|record| {
+                Ok(serde_json::from_str::<Message>(&record.detail)?
+                    .content()
+                    .to_string())
+            })
+            .collect()
+    };
+    assert_eq!(contents(&simulation)?, ["17", "18", "19"]);
+    let restored: Simulation = serde_json::from_str(&serde_json::to_string(&simulation)?)?;
+    assert_eq!(contents(&restored)?, ["17", "18", "19"]);
+    simulation.enable_audit_log(1);
+    assert_eq!(contents(&simulation)?, ["19"]);
+    simulation.disable_audit_log();
+    assert!(!serde_json::to_string(&simulation)?.contains("auditLog"));
+    Ok(())
+}
+
+#[test]
+fn history_supports_time_travel_queries() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.333333 },
+                Some(5),
+                String::from("job"),
+                String::from("processed"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                true,
+            )),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("processor-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("processor-01"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    assert!(matches!(
+        simulation.state_at(0.0),
+        Err(SimulationError::HistoryUnavailable)
+    ));
+    simulation.enable_history(7)?;
+    // Live snapshots of the model states, after each step
+    let mut snapshots: Vec<(f64, serde_json::Value)> = Vec::new();
+    let mut messages: Vec<Message> = Vec::new();
+    for _ in 0..100 {
+        messages.extend(simulation.step()?);
+        let time = simulation.get_global_time();
+        let models: serde_json::Map<String, serde_json::Value> = simulation
+            .models()
+            .iter()
+            .map(|model| Ok((model.id().to_string(), serde_json::to_value(model)?)))
+            .collect::<Result<_, SimulationError>>()?;
+        snapshots.push((time, serde_json::Value::Object(models)));
+    }
+    // The latest snapshot at each distinct time is the state at that time
+    snapshots
+        .windows(2)
+        .filter(|pair| pair[0].0 < pair[1].0)
+        .map(|pair| &pair[0])
+        .try_for_each(|(time, snapshot)| -> Result<(), SimulationError> {
+            let reconstructed = simulation.state_at(*time)?;
+            assert_eq!(&serde_json::to_value(&reconstructed)?, snapshot);
+            Ok(())
+        })?;
+    let final_time = simulation.get_global_time();
+    assert_eq!(
+        simulation.messages_between(0.0, final_time)?.len(),
+        messages.len()
+    );
+    assert_eq!(
+        simulation.messages_between(0.0, final_time / 2.0)?.len(),
+        messages
+            .iter()
+            .filter(|message| *message.time() <= final_time / 2.0)
+            .count()
+    );
+    assert_eq!(
+        simulation.messages_for_model("storage-01")?.len(),
+        messages
+            .iter()
+            .filter(|message| message.target_id() == "storage-01")
+            .count()
+    );
+    Ok(())
+}
+
+#[test]
+fn build_info_reports_compiled_features() {
+    let build_info = sim::build_info();
+    assert_eq!(build_info.version, env!("CARGO_PKG_VERSION"));
+    assert!(build_info.has_feature("batcher"));
+    assert!(build_info.has_feature("stopwatch"));
+    assert!(!build_info.has_feature("wasm-small"));
+    assert_eq!(build_info.has_feature("gzip"), cfg!(feature = "gzip"));
+    assert_eq!(build_info.has_feature("zstd"), cfg!(feature = "zstd"));
+}
+
+#[test]
+fn simulations_run_across_threads() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("generator-01"),
+        String::from("storage-01"),
+        String::from("job"),
+        String::from("store"),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    simulation.step_n(10)?;
+    // Execute a simulation on a worker thread
+    let simulation = std::thread::spawn(move || {
+        simulation.step_n(10)?;
+        Ok::<Simulation, SimulationError>(simulation)
+    })
+    .join()
+    .unwrap()?;
+    // Share the simulation across threads for read access
+    let simulation = std::sync::Arc::new(simulation);
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            let simulation = simulation.clone();
+            std::thread::spawn(move || simulation.get_status("storage-01"))
+        })
+        .collect();
+    readers.into_iter().try_for_each(|reader| {
+        assert!(reader.join().unwrap()?.starts_with("Storing job"));
+        Ok(())
+    })
+}
+
+#[test]
+fn port_stats_track_traffic_per_port() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.25 },
+                Some(2),
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("processor-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("processor-01"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    let messages = simulation.step_n(100)?;
+    let count = |source_id: &str| {
+        messages
+            .iter()
+            .filter(|message| message.source_id() == source_id)
+            .count()
+    };
+    // Messages sent during the final step are not yet received
+    let delivered = |source_id: &str| {
+        count(source_id)
+            - simulation
+                .get_messages()
+                .iter()
+                .filter(|message| message.source_id() == source_id)
+                .count()
+    };
+    let generator_stats = simulation.get_port_stats("generator-01")?.unwrap();
+    assert_eq!(
+        generator_stats.port("job").unwrap().messages_out,
+        count("generator-01")
+    );
+    let processor_stats = simulation.get_port_stats("processor-01")?.unwrap();
+    let processor_in = processor_stats.port("job").unwrap();
+    let processor_out = processor_stats.port("processed").unwrap();
+    assert_eq!(processor_in.messages_in, delivered("generator-01"));
+    assert_eq!(processor_out.messages_out, count("processor-01"));
+    // The processor is saturated, with the slower service dropping jobs
+    assert!(processor_in.messages_in > processor_out.messages_out);
+    let storage_stats = simulation.get_port_stats("storage-01")?.unwrap();
+    assert_eq!(
+        storage_stats.port("store").unwrap().messages_in,
+        delivered("processor-01")
+    );
+    assert!(storage_stats.port("read").is_none());
+    Ok(())
+}
+
+#[test]
+fn dry_run_estimates_flows_and_bottlenecks() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("exclusive-01"),
+            Box::new(ExclusiveGateway::new(
+                vec![String::from("in")],
+                vec![String::from("s01"), String::from("s02")],
+                IndexRandomVariable::WeightedIndex {
+                    weights: vec![3, 1],
+                },
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 2.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-02"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.2 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+    ];
+    let connectors = [
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("exclusive-01"),
+            String::from("job"),
+            String::from("in"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("exclusive-01"),
+            String::from("processor-01"),
+            String::from("s01"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-03"),
+            String::from("exclusive-01"),
+            String::from("processor-02"),
+            String::from("s02"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-04"),
+            String::from("processor-01"),
+            String::from("storage-01"),
+            String::from("processed"),
+            String::from("store"),
+        ),
+    ];
+    let simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    let report = simulation.dry_run(1000.0)?;
+    let rate = |connector_id: &str| {
+        report
+            .connectors
+            .iter()
+            .find(|estimate| estimate.id == connector_id)
+            .unwrap()
+            .rate
+    };
+    assert!((rate("connector-01") - 1.0).abs() < 1.0e-9);
+    assert!((rate("connector-02") - 0.75).abs() < 1.0e-9);
+    assert!((rate("connector-03") - 0.25).abs() < 1.0e-9);
+    assert!((rate("connector-04") - 0.75).abs() < 1.0e-9);
+    assert!((report.expected_messages - 2750.0).abs() < 1.0e-6);
+    let processor_02 = report
+        .models
+        .iter()
+        .find(|estimate| estimate.id == "processor-02")
+        .unwrap();
+    assert!((processor_02.utilization.unwrap() - 1.25).abs() < 1.0e-9);
+    assert_eq!(report.bottleneck.as_deref(), Some("processor-02"));
+    assert!(report
+        .warnings
+        .iter()
+        .any(|warning| warning.contains("processor-02 is overloaded")));
+    assert!(report
+        .warnings
+        .iter()
+        .any(|warning| warning.contains("unknown model storage-01")));
+    // The dry run does not execute any transitions
+    assert_eq!(simulation.get_global_time(), 0.0);
+    Ok(())
+}
+
+#[test]
+fn schedule_variates_depend_on_simulation_time() -> Result<(), SimulationError> {
+    // Jobs every time unit until time 10, and then every 5 time units
+    let models = [Model::new(
+        String::from("generator-01"),
+        Box::new(Generator::new(
+            ContinuousRandomVariable::Schedule {
+                entries: vec![
+                    ScheduleEntry {
+                        start: 0.0,
+                        value: 1.0,
+                    },
+                    ScheduleEntry {
+                        start: 10.0,
+                        value: 5.0,
+                    },
+                ],
+                period: None,
+            },
+            None,
+            String::from("job"),
+            false,
+            None,
+        )),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), Vec::new());
+    // The first step initializes the generator, at time 0
+    let event_times = (0..13)
+        .map(|_| {
+            simulation.step()?;
+            Ok(simulation.get_global_time())
+        })
+        .collect::<Result<Vec<f64>, SimulationError>>()?;
+    assert_eq!(
+        event_times,
+        vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 15.0, 20.0]
+    );
+    Ok(())
+}
+
+#[test]
+fn global_variables_adjust_referencing_models() -> Result<(), SimulationError> {
+    let interdeparture_time: ContinuousRandomVariable =
+        serde_json::from_str(r#"{"exp": {"lambda": "$arrival_rate"}}"#).unwrap();
+    let models = [Model::new(
+        String::from("generator-01"),
+        Box::new(Generator::new(
+            interdeparture_time,
+            None,
+            String::from("job"),
+            false,
+            None,
+        )),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), Vec::new());
+    simulation.enable_deterministic_mode();
+    assert!(matches!(
+        simulation.step().as_ref().map_err(SimulationError::root_cause),
+        Err(SimulationError::UnknownGlobalVariable(name)) if name == "arrival_rate"
+    ));
+    let mut simulation = Simulation::post(models.to_vec(), Vec::new());
+    simulation.enable_deterministic_mode();
+    simulation.set_global("arrival_rate", 1.0);
+    simulation.step_n(3)?;
+    assert_eq!(simulation.get_global_time(), 2.0);
+    // The next interdeparture time is already drawn, so the change applies
+    // from the following generation
+    simulation.set_global("arrival_rate", 0.5);
+    simulation.step()?;
+    assert_eq!(simulation.get_global_time(), 3.0);
+    simulation.step()?;
+    assert_eq!(simulation.get_global_time(), 5.0);
+    assert_eq!(simulation.get_global("arrival_rate"), Some(0.5));
+    // Global variables are part of the simulation configuration
+    let configuration = serde_json::to_string(&simulation).unwrap();
+    assert!(configuration.contains(r#""globals":{"arrival_rate":0.5}"#));
+    let restored: Simulation = serde_json::from_str(&configuration).unwrap();
+    assert_eq!(restored.get_global("arrival_rate"), Some(0.5));
+    Ok(())
+}
+
+#[test]
+fn topology_helpers_wire_common_patterns() -> Result<(), SimulationError> {
+    let processor = || {
+        Box::new(Processor::new(
+            ContinuousRandomVariable::Exp { lambda: 2.0 },
+            None,
+            String::from("job"),
+            String::from("job"),
+            false,
+            None,
+        ))
+    };
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(String::from("processor-01"), processor()),
+        Model::new(String::from("processor-02"), processor()),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("job"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = topology::pipeline(
+        &["generator-01", "processor-01", "processor-02", "storage-01"],
+        "job",
+        "job",
+    );
+    assert_eq!(
+        connectors
+            .iter()
+            .map(|connector| connector.id())
+            .collect::<Vec<&str>>(),
+        vec![
+            "generator-01-processor-01",
+            "processor-01-processor-02",
+            "processor-02-storage-01"
+        ]
+    );
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    simulation.step_n(100)?;
+    assert!(simulation
+        .get_port_stats("storage-01")?
+        .and_then(|port_stats| port_stats.port("job"))
+        .is_some());
+
+    let star = topology::star("hub", &["a", "b", "c"], "out", "in");
+    assert_eq!(star.len(), 3);
+    assert!(star
+        .iter()
+        .all(|connector| connector.source_id() == "hub" && connector.source_port() == "out"));
+    let gather = topology::gather(&["a", "b", "c"], "hub", "out", "in");
+    assert!(gather
+        .iter()
+        .all(|connector| connector.target_id() == "hub" && connector.target_port() == "in"));
+    let mesh = topology::mesh(&["a", "b", "c"], "out", "in");
+    assert_eq!(mesh.len(), 6);
+    assert!(mesh
+        .iter()
+        .all(|connector| connector.source_id() != connector.target_id()));
+    Ok(())
+}
+
+#[test]
+fn subscribers_receive_simulation_events() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("generator-01"),
+        String::from("storage-01"),
+        String::from("job"),
+        String::from("store"),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    let (routed_sender, routed_receiver) = std::sync::mpsc::channel();
+    let (steps_sender, steps_receiver) = std::sync::mpsc::channel();
+    simulation.subscribe(EventKind::MessageRouted, routed_sender);
+    simulation.subscribe(EventKind::StepCompleted, steps_sender);
+    // Consumers run independently of the stepping loop
+    let consumer = std::thread::spawn(move || routed_receiver.iter().count());
+    let messages = simulation.step_n(20)?;
+    // Subscriptions are not carried over to clones
+    simulation.clone().step_n(20)?;
+    drop(simulation);
+    assert_eq!(consumer.join().unwrap(), messages.len());
+    let steps: Vec<SimulationEvent> = steps_receiver.iter().collect();
+    assert_eq!(steps.len(), 20);
+    assert!(steps
+        .iter()
+        .all(|event| event.kind() == EventKind::StepCompleted));
+    Ok(())
+}
+
+fn snapshot_simulation() -> Simulation {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("generator-01"),
+        String::from("storage-01"),
+        String::from("job"),
+        String::from("store"),
+    )];
+    Simulation::post(models.to_vec(), connectors.to_vec())
+}
+
+fn assert_snapshot_round_trip(compression: SnapshotCompression) -> Result<(), SimulationError> {
+    let mut simulation = snapshot_simulation();
+    simulation.step_n(25)?;
+    let path = std::env::temp_dir().join(format![
+        "sim-snapshot-{:?}-{}.snap",
+        compression,
+        std::process::id()
+    ]);
+    simulation.save_snapshot(&path, compression)?;
+    let restored = Simulation::load_snapshot(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(restored.get_global_time(), simulation.get_global_time());
+    assert_eq!(
+        serde_yaml::to_string(&restored).unwrap(),
+        serde_yaml::to_string(&simulation).unwrap()
+    );
+    Ok(())
+}
+
+#[test]
+fn snapshots_round_trip_with_integrity_checks() -> Result<(), SimulationError> {
+    assert_snapshot_round_trip(SnapshotCompression::None)?;
+    let simulation = snapshot_simulation();
+    // Plain simulation YAML is auto-detected
+    let plain = serde_yaml::to_string(&simulation).unwrap().into_bytes();
+    assert!(Simulation::from_snapshot(&plain).is_ok());
+    let mut snapshot = simulation.to_snapshot(SnapshotCompression::None)?;
+    let last = snapshot.len() - 2;
+    snapshot[last] ^= 0x01;
+    assert!(matches!(
+        Simulation::from_snapshot(&snapshot),
+        Err(SimulationError::InvalidSnapshot)
+    ));
+    #[cfg(not(feature = "gzip"))]
+    assert!(matches!(
+        simulation.to_snapshot(SnapshotCompression::Gzip),
+        Err(SimulationError::UnsupportedCompression)
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_snapshots_round_trip() -> Result<(), SimulationError> {
+    assert_snapshot_round_trip(SnapshotCompression::Gzip)
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_snapshots_round_trip() -> Result<(), SimulationError> {
+    assert_snapshot_round_trip(SnapshotCompression::Zstd)
+}
+
+#[test]
+fn initial_conditions_randomize_model_states() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+        Model::new(
+            String::from("gate-01"),
+            Box::new(Gate::new(
+                String::from("job"),
+                String::from("activation"),
+                String::from("deactivation"),
+                String::from("job"),
+                false,
+            )),
+        ),
+        Model::new(
+            String::from("gate-02"),
+            Box::new(Gate::new(
+                String::from("job"),
+                String::from("activation"),
+                String::from("deactivation"),
+                String::from("job"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("processor-01"),
+        String::from("storage-01"),
+        String::from("processed"),
+        String::from("store"),
+    )];
+    let conditions: Vec<InitialCondition> = serde_yaml::from_str(
+        r#"
+- modelId: "processor-01"
+  port: "job"
+  jobs:
+    count:
+      uniform:
+        min: 5
+        max: 6
+- modelId: "gate-01"
+  port: "deactivation"
+  signal:
+    probability:
+      bernoulli:
+        p: 1.0
+- modelId: "gate-02"
+  port: "deactivation"
+  signal:
+    probability:
+      bernoulli:
+        p: 0.0
+"#,
+    )
+    .unwrap();
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    simulation.randomize_initial_state(&conditions)?;
+    let messages = simulation.step_until(100.0)?;
+    let processed = messages
+        .iter()
+        .filter(|message| message.source_id() == "processor-01")
+        .count();
+    assert_eq!(processed, 5);
+    assert_eq!(simulation.get_status("gate-01")?, "Closed");
+    assert_eq!(simulation.get_status("gate-02")?, "Open");
+    // Initial conditions only apply before the simulation starts
+    let error = simulation.randomize_initial_state(&conditions).unwrap_err();
+    assert!(matches!(
+        error,
+        SimulationError::InitializationAfterStart(time) if time == simulation.get_global_time()
+    ));
+    assert!(error
+        .to_string()
+        .contains("after the simulation has started"));
+    Ok(())
+}
+
+fn assert_reference_response_time(reference: ReferenceModel) -> Result<(), SimulationError> {
+    let mut simulation = reference.simulation();
+    let messages = simulation.step_until(10000.0)?;
+    let mut response_times = SteadyStateOutput::post(reference.response_times(&messages));
+    let confidence_interval = response_times.confidence_interval_mean(0.001)?;
+    let expected = reference.expectations.mean_response_time;
+    assert!(confidence_interval.lower() < expected);
+    assert!(confidence_interval.upper() > expected);
+    Ok(())
+}
+
+#[test]
+fn reference_models_match_analytic_expectations() -> Result<(), SimulationError> {
+    let mm1 = ReferenceModel::mm1(reference::MM1_ARRIVAL_RATE, reference::MM1_SERVICE_RATE)?;
+    assert_eq!(
+        mm1.expectations.mean_response_time,
+        reference::MM1_MEAN_RESPONSE_TIME
+    );
+    assert_reference_response_time(mm1)?;
+    let tandem = ReferenceModel::tandem(
+        reference::TANDEM_ARRIVAL_RATE,
+        &reference::TANDEM_SERVICE_RATES,
+    )?;
+    assert_eq!(tandem.models.len(), 4);
+    assert_reference_response_time(tandem)?;
+    assert!(ReferenceModel::mm1(1.0, 0.5).is_err());
+    Ok(())
+}
+
+#[test]
+fn processor_service_times_depend_on_queue_length() -> Result<(), SimulationError> {
+    // Servers speed up with 2+ jobs waiting, and switch to express service
+    // with 4+ jobs waiting
+    let thresholds: Vec<ServiceTimeThreshold> = serde_yaml::from_str(
+        r#"
+- queueLength: 2
+  scale: 0.5
+- queueLength: 4
+  serviceTime:
+    exp:
+      lambda: 10.0
+"#,
+    )
+    .unwrap();
+    let models = [
+        Model::new(
+            String::from("processor-01"),
+            Box::new(
+                Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: 1.0 },
+                    None,
+                    String::from("job"),
+                    String::from("processed"),
+                    false,
+                    None,
+                )
+                .with_service_time_thresholds(thresholds),
+            ),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("processor-01"),
+        String::from("storage-01"),
+        String::from("processed"),
+        String::from("store"),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    simulation.enable_deterministic_mode();
+    (1..=6).for_each(|index| {
+        simulation.inject_input(Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from("processor-01"),
+            String::from("job"),
+            0.0,
+            format!["job {}", index],
+        ))
+    });
+    let departures: Vec<f64> = simulation
+        .step_until(10.0)?
+        .iter()
+        .filter(|message| message.source_id() == "processor-01")
+        .map(|message| *message.time())
+        .collect();
+    // The first job starts service on arrival, and then the jobs waiting at
+    // each service start are 4, 3, 2, 1, and 0
+    let expected = [1.0, 1.1, 1.6, 2.1, 3.1, 4.1];
+    assert_eq!(departures.len(), expected.len());
+    departures
+        .iter()
+        .zip(expected.iter())
+        .for_each(|(departure, expected)| assert!((departure - expected).abs() < 1.0e-9));
+    Ok(())
+}
+
+#[test]
+fn generators_on_a_shared_stream_are_correlated() -> Result<(), SimulationError> {
+    let models: Vec<Model> = serde_yaml::from_str(
+        r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+  rngStream: "demand"
+- type: "Generator"
+  id: "generator-02"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 2.0
+  rngStream: "demand"
+- type: "Generator"
+  id: "generator-03"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+- type: "Storage"
+  id: "storage-01"
+  portsIn:
+    put: "store"
+    get: "read"
+  portsOut:
+    stored: "stored"
+"#,
+    )
+    .unwrap();
+    let connectors = topology::gather(
+        &["generator-01", "generator-02", "generator-03"],
+        "storage-01",
+        "job",
+        "store",
+    );
+    let mut simulation = Simulation::post(models, connectors);
+    let messages = simulation.step_until(50.0)?;
+    let generation_times = |generator_id: &str| -> Vec<f64> {
+        messages
+            .iter()
+            .filter(|message| message.source_id() == generator_id)
+            .map(|message| *message.time())
+            .collect()
+    };
+    let (first, second, independent) = (
+        generation_times("generator-01"),
+        generation_times("generator-02"),
+        generation_times("generator-03"),
+    );
+    // The same uniforms, scaled by the rates, yield proportional arrivals
+    assert!(first.len() > 10);
+    first
+        .iter()
+        .zip(second.iter())
+        .for_each(|(first, second)| assert!((first / 2.0 - second).abs() < 1.0e-9));
+    assert!(first
+        .iter()
+        .zip(independent.iter())
+        .any(|(first, independent)| (first - independent).abs() > 1.0e-9));
+    Ok(())
+}
+
+#[test]
+fn pinned_model_seeds_are_independent_of_the_global_seed() -> Result<(), SimulationError> {
+    let models: Vec<Model> = serde_yaml::from_str(
+        r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+  seed: 7
+- type: "Processor"
+  id: "processor-01"
+  portsIn:
+    job: "job"
+  portsOut:
+    job: "processed"
+  serviceTime:
+    exp:
+      lambda: 2.0
+- type: "Storage"
+  id: "storage-01"
+  portsIn:
+    put: "store"
+    get: "read"
+  portsOut:
+    stored: "stored"
+"#,
+    )
+    .unwrap();
+    let mut connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    connectors.extend(topology::pipeline(
+        &["processor-01", "storage-01"],
+        "processed",
+        "store",
+    ));
+    let event_times = |global_seed: u64, model_id: &str| -> Result<Vec<f64>, SimulationError> {
+        let mut simulation = Simulation::post(models.clone(), connectors.clone());
+        simulation.set_seed(global_seed);
+        Ok(simulation
+            .step_until(50.0)?
+            .iter()
+            .filter(|message| message.source_id() == model_id)
+            .map(|message| *message.time())
+            .collect())
+    };
+    // The pinned generator repeats across global seeds (up to the rounding
+    // of the accumulated simulation time), while the processor varies
+    let arrivals = event_times(1, "generator-01")?;
+    let repeated_arrivals = event_times(2, "generator-01")?;
+    assert!(arrivals.len() > 10);
+    assert_eq!(arrivals.len(), repeated_arrivals.len());
+    arrivals
+        .iter()
+        .zip(repeated_arrivals.iter())
+        .for_each(|(arrival, repeated)| assert!((arrival - repeated).abs() < 1.0e-9));
+    assert_ne!(
+        event_times(1, "processor-01")?,
+        event_times(2, "processor-01")?
+    );
+    // The pinned seed is retained through serialization
+    let serialized = serde_yaml::to_string(&models[0]).unwrap();
+    assert!(serialized.contains("seed: 7"));
+    Ok(())
+}
+
+#[test]
+fn queue_watermarks_track_peak_depths() -> Result<(), SimulationError> {
+    // An overloaded processor, with arrivals at 4x the service rate
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 2.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("job"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = topology::pipeline(
+        &["generator-01", "processor-01", "storage-01"],
+        "job",
+        "job",
+    );
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    simulation.set_queue_depth_threshold(Some(5));
+    simulation.step_n(200)?;
+    let stats = simulation.execution_stats();
+    let watermark = stats.queue_watermarks["processor-01"];
+    assert!(watermark.depth > 5);
+    assert!(watermark.time <= simulation.get_global_time());
+    // Only models with internal queues report a queue depth
+    assert_eq!(stats.queue_watermarks.len(), 1);
+    assert_eq!(stats.watermark_alerts.len(), 1);
+    assert_eq!(stats.watermark_alerts[0].model_id, "processor-01");
+    assert_eq!(stats.watermark_alerts[0].depth, 6);
+    simulation.reset();
+    assert!(simulation.execution_stats().queue_watermarks.is_empty());
+    Ok(())
+}
+
+#[test]
+fn run_manifests_trace_results_to_inputs() -> Result<(), SimulationError> {
+    let reference = ReferenceModel::mm1(reference::MM1_ARRIVAL_RATE, reference::MM1_SERVICE_RATE)?;
+    let mut simulation = reference.simulation();
+    simulation.step_n(50)?;
+    let manifest = simulation.manifest()?;
+    assert_eq!(manifest.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(manifest.rng_seed, Some(42));
+    assert_eq!(manifest.steps, 50);
+    assert_eq!(manifest.global_time, simulation.get_global_time());
+    assert!(manifest.wall_time >= 0.0);
+    // The configuration hash excludes model state, so it is unaffected by
+    // the run, but reflects configuration changes
+    let fresh = reference.simulation().manifest()?;
+    assert_eq!(fresh.config_hash, manifest.config_hash);
+    assert_eq!(fresh.steps, 0);
+    simulation.set_global("demand", 2.0);
+    assert_ne!(simulation.manifest()?.config_hash, manifest.config_hash);
+    let json = serde_json::to_string(&manifest).unwrap();
+    let restored = serde_json::from_str::<RunManifest>(&json).unwrap();
+    // Wall clock times may lose their last bit through JSON
+    assert!((restored.created_at - manifest.created_at).abs() <= 1.0e-3);
+    assert!((restored.wall_time - manifest.wall_time).abs() <= 1.0e-3);
+    assert_eq!(
+        restored,
+        RunManifest {
+            created_at: restored.created_at,
+            wall_time: restored.wall_time,
+            ..manifest
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn simulation_pools_multiplex_independent_simulations() -> Result<(), SimulationError> {
+    let mm1 = ReferenceModel::mm1(reference::MM1_ARRIVAL_RATE, reference::MM1_SERVICE_RATE)?;
+    let tandem = ReferenceModel::tandem(
+        reference::TANDEM_ARRIVAL_RATE,
+        &reference::TANDEM_SERVICE_RATES,
+    )?;
+    let mut pool = SimulationPool::new(PoolScheduling::EarliestEvent);
+    assert!(pool.insert("customer-a", mm1.simulation()).is_none());
+    assert!(pool.insert("customer-b", tandem.simulation()).is_none());
+    assert!(pool.insert("customer-c", mm1.simulation()).is_none());
+    assert!(pool.remove("customer-c").is_some());
+    assert_eq!(pool.ids().collect::<Vec<_>>(), ["customer-a", "customer-b"]);
+    let messages = pool.step_until(20.0)?;
+    // Earliest event scheduling interleaves the simulations in time order
+    assert!(messages
+        .windows(2)
+        .all(|pair| pair[0].message.time() <= pair[1].message.time()));
+    ["customer-a", "customer-b"].iter().for_each(|id| {
+        assert!(messages.iter().any(|message| message.simulation_id == *id));
+        assert!(pool.get(id).unwrap().get_global_time() >= 20.0);
+    });
+    let json = serde_json::to_value(&messages[0]).unwrap();
+    assert!(json["simulationId"].is_string() && json["sourceId"].is_string());
+    // Round robin scheduling steps the simulations in turn
+    let mut pool = SimulationPool::new(PoolScheduling::RoundRobin);
+    pool.insert("customer-a", mm1.simulation());
+    pool.insert("customer-b", mm1.simulation());
+    pool.step_n(5)?;
+    assert_eq!(pool.get("customer-a").unwrap().execution_stats().steps, 3);
+    assert_eq!(pool.get("customer-b").unwrap().execution_stats().steps, 2);
+    Ok(())
+}
+
+#[test]
+fn scheduled_injections_are_delivered_at_their_times() -> Result<(), SimulationError> {
+    let models = vec![Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("job"), 10.0, true)),
+    )];
+    let mut simulation = Simulation::post(models, Vec::new());
+    let injection = |content: &str| {
+        Message::new(
+            String::from("feed"),
+            String::from("job"),
+            String::from("sink-01"),
+            String::from("job"),
+            0.0,
+            String::from(content),
+        )
+    };
+    simulation.inject_input_at(injection("second"), 5.0)?;
+    simulation.inject_input_at(injection("first"), 2.5)?;
+    simulation.inject_input_at(injection("third"), 5.0)?;
+    assert_eq!(simulation.scheduled_inputs().len(), 3);
+    assert_eq!(simulation.next_event_time(), 2.5);
+    simulation.step_until(4.0)?;
+    assert_eq!(simulation.get_global_time(), 5.0);
+    assert!(matches!(
+        simulation.inject_input_at(injection("late"), 1.0),
+        Err(SimulationError::InjectionInPast(_))
+    ));
+    simulation.step_until(10.0)?;
+    assert!(simulation.scheduled_inputs().is_empty());
+    let arrivals: Vec<(f64, String)> = simulation
+        .get_records("sink-01")?
+        .iter()
+        .map(|record| (record.time, record.subject.clone()))
+        .collect();
+    assert_eq!(
+        arrivals,
+        [
+            (2.5, String::from("first")),
+            (5.0, String::from("second")),
+            (5.0, String::from("third"))
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn injection_priority_orders_injected_and_generated_messages() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, true)),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("processor-01"),
+        String::from("sink-01"),
+        String::from("processed"),
+        String::from("job"),
+    )];
+    let arrival_order = |priority: InjectionPriority,
+                         injection_time: f64|
+     -> Result<Vec<String>, SimulationError> {
+        let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+        simulation.enable_deterministic_mode();
+        simulation.set_injection_priority(priority);
+        simulation.inject_input(Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from("processor-01"),
+            String::from("job"),
+            0.0,
+            String::from("generated"),
+        ));
+        // The processed job is pending delivery to the sink at time 1.0
+        simulation.step_n(2)?;
+        assert_eq!(simulation.get_messages().len(), 1);
+        simulation.inject_input(Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from("sink-01"),
+            String::from("job"),
+            injection_time,
+            String::from("injected"),
+        ));
+        simulation.step()?;
+        Ok(simulation
+            .get_records("sink-01")?
+            .iter()
+            .map(|record| record.subject.clone())
+            .collect())
+    };
+    assert_eq!(
+        arrival_order(InjectionPriority::Generated, 1.0)?,
+        ["generated", "injected"]
+    );
+    assert_eq!(
+        arrival_order(InjectionPriority::Injected, 1.0)?,
+        ["injected", "generated"]
+    );
+    // Ties in message time favor the model-generated messages
+    assert_eq!(
+        arrival_order(InjectionPriority::Timestamp, 1.0)?,
+        ["generated", "injected"]
+    );
+    assert_eq!(
+        arrival_order(InjectionPriority::Timestamp, 0.5)?,
+        ["injected", "generated"]
+    );
+    Ok(())
+}
+
+#[test]
+fn simulations_load_from_preprocessed_config_files() -> Result<(), SimulationError> {
+    let directory = std::env::temp_dir().join(format!["sim-config-{}", std::process::id()]);
+    std::fs::create_dir_all(&directory)?;
+    std::fs::write(
+        directory.join("models.yaml"),
+        r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: ${SIM_CONFIG_TEST_ARRIVAL_RATE:-0.5}
+- !include sinks.yaml
+"#,
+    )?;
+    std::fs::write(
+        directory.join("sinks.yaml"),
+        r#"
+- type: "Sink"
+  id: "sink-01"
+  window: 5.0
+  portsIn:
+    job: "job"
+"#,
+    )?;
+    std::fs::write(
+        directory.join("connectors.yaml"),
+        r#"
+- id: "connector-01"
+  sourceID: "generator-01"
+  targetID: "sink-01"
+  sourcePort: "job"
+  targetPort: "job"
+"#,
+    )?;
+    let mut simulation = Simulation::load_config(
+        directory.join("models.yaml"),
+        directory.join("connectors.yaml"),
+    )?;
+    std::fs::remove_dir_all(&directory)?;
+    assert_eq!(simulation.models().len(), 2);
+    simulation.enable_deterministic_mode();
+    simulation.step_until(10.5)?;
+    // Generation every 2.0 time units, with the default arrival rate
+    assert_eq!(
+        simulation
+            .get_sink_summary("sink-01")?
+            .map(|summary| summary.count),
+        Some(5)
+    );
+    Ok(())
+}
+
+#[test]
+fn simulations_import_from_csv_tables() -> Result<(), SimulationError> {
+    let models_csv = "\
+id,type,portsIn.job,portsOut.job,messageInterdepartureTime.exp.lambda,window
+generator-01,Generator,,job,0.5,
+sink-01,Sink,job,,,5.0
+";
+    let connectors_csv = "\
+id,sourceID,targetID,sourcePort,targetPort
+connector-01,generator-01,sink-01,job,job
+";
+    let mut simulation = sim::import::from_csv(models_csv, connectors_csv)?;
+    simulation.enable_deterministic_mode();
+    simulation.step_until(10.5)?;
+    assert_eq!(
+        simulation
+            .get_sink_summary("sink-01")?
+            .map(|summary| summary.count),
+        Some(5)
+    );
+    // All the invalid rows are reported, with spreadsheet row numbers
+    let invalid_models_csv = "\
+id,type,portsIn.job,window:number
+sink-01,Sink,job,5.0
+sink-01,Sink,job,5.0
+sink-02,Sink,job,soon
+sink-03,Sink
+";
+    let invalid_connectors_csv = "\
+id,sourceID,targetID,sourcePort,targetPort
+connector-01,generator-01,sink-01,job,job
+";
+    match sim::import::from_csv(invalid_models_csv, invalid_connectors_csv) {
+        Err(SimulationError::CsvImportError(errors)) => {
+            let rows: Vec<(&str, usize)> = errors
+                .iter()
+                .map(|error| (error.table.as_str(), error.row))
+                .collect();
+            assert_eq!(
+                rows,
+                [
+                    ("models", 4),
+                    ("models", 5),
+                    ("models", 3),
+                    ("connectors", 2)
+                ]
+            );
+        }
+        _ => panic!("expected a CSV import error"),
+    }
+    Ok(())
+}
+
+#[test]
+fn set_parameter_validates_the_patched_model() -> Result<(), SimulationError> {
+    let mm1 = ReferenceModel::mm1(reference::MM1_ARRIVAL_RATE, reference::MM1_SERVICE_RATE)?;
+    let mut simulation = mm1.simulation();
+    simulation.step_n(10)?;
+    simulation.enable_audit_log(16);
+    let generator_id = ReferenceModel::GENERATOR_ID;
+    simulation.set_parameter(
+        generator_id,
+        "/messageInterdepartureTime/exp/lambda",
+        serde_json::json!(3.0),
+    )?;
+    assert!(simulation
+        .get_audit_log()?
+        .last()
+        .is_some_and(|record| record.action == "Set Parameter"));
+    assert!(matches!(
+        simulation.set_parameter("generator-99", "/portsOut/job", serde_json::json!("out")),
+        Err(SimulationError::ModelNotFound(_))
+    ));
+    assert!(matches!(
+        simulation.set_parameter(generator_id, "/missing/field", serde_json::json!(1.0)),
+        Err(SimulationError::InvalidParameter(_))
+    ));
+    assert!(matches!(
+        simulation.set_parameter(generator_id, "/id", serde_json::json!("generator-02")),
+        Err(SimulationError::InvalidParameter(_))
+    ));
+    assert!(matches!(
+        simulation.set_parameter(
+            generator_id,
+            "/messageInterdepartureTime",
+            serde_json::json!("fast")
+        ),
+        Err(SimulationError::InvalidModelConfiguration)
+    ));
+    simulation.step_n(10)?;
+    Ok(())
+}
+
+#[test]
+fn failed_model_swaps_are_not_audited() -> Result<(), SimulationError> {
+    let models = [Model::new(
+        String::from("storage-01"),
+        Box::new(Storage::new(
+            String::from("store"),
+            String::from("read"),
+            String::from("stored"),
+            false,
+        )),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), Vec::new());
+    simulation.enable_audit_log(16);
+    // The storage state cannot be migrated to a processor
+    let processor = Model::new(
+        String::from("storage-01"),
+        Box::new(Processor::new(
+            ContinuousRandomVariable::Exp { lambda: 1.0 },
+            None,
+            String::from("store"),
+            String::from("stored"),
+            false,
+            None,
+        )),
+    );
+    assert!(simulation.replace_model(processor).is_err());
+    assert!(simulation.get_audit_log()?.is_empty());
+    assert_eq!(simulation.get_status("storage-01")?, "Empty");
+    Ok(())
+}
+
+#[test]
+fn replaced_models_migrate_state() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("storage-01"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        ),
+    ];
+    let connectors = [Connector::new(
+        String::from("connector-01"),
+        String::from("processor-01"),
+        String::from("storage-01"),
+        String::from("processed"),
+        String::from("store"),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), connectors.to_vec());
+    simulation.enable_deterministic_mode();
+    (1..=6).for_each(|index| {
+        simulation.inject_input(Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from("processor-01"),
+            String::from("job"),
+            0.0,
+            format!["job {}", index],
+        ))
+    });
+    let departure_times = |messages: Vec<Message>| -> Vec<f64> {
+        messages
+            .iter()
+            .filter(|message| message.source_id() == "processor-01")
+            .map(|message| *message.time())
+            .collect()
+    };
+    assert_eq!(departure_times(simulation.step_until(2.5)?), [1.0, 2.0]);
+    // The simulation stops at the departure of job 3, at 3.0
+    assert_eq!(simulation.get_global_time(), 3.0);
+    // The processor keeps its queue when the service rate doubles, and the
+    // remaining jobs are served at the new rate
+    simulation.set_parameter(
+        "processor-01",
+        "/serviceTime/exp/lambda",
+        serde_json::json!(2.0),
+    )?;
+    assert_eq!(
+        departure_times(simulation.step_until(10.0)?),
+        [3.5, 4.0, 4.5]
+    );
+    // By default, replaced models reset their state
+    let previous = simulation.replace_model(models[1].clone())?;
+    assert_eq!(previous.status(), "Storing job 6");
+    assert_eq!(simulation.get_status("storage-01")?, "Empty");
+    assert!(matches!(
+        simulation.replace_model(Model::new(
+            String::from("storage-99"),
+            Box::new(Storage::new(
+                String::from("store"),
+                String::from("read"),
+                String::from("stored"),
+                false,
+            )),
+        )),
+        Err(SimulationError::ModelNotFound(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn event_density_reports_bursts_and_idle_spans() -> Result<(), SimulationError> {
+    let models = vec![Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("job"), 10.0, false)),
+    )];
+    let mut simulation = Simulation::post(models, Vec::new());
+    let injection = Message::new(
+        String::from("feed"),
+        String::from("job"),
+        String::from("sink-01"),
+        String::from("job"),
+        0.0,
+        String::from("job"),
+    );
+    (0..10).try_for_each(|_| simulation.inject_input_at(injection.clone(), 2.0))?;
+    simulation.inject_input_at(injection, 9.0)?;
+    simulation.step_n(4)?;
+    assert_eq!(simulation.get_global_time(), 9.0);
+    let report = simulation.event_density(1.0)?;
+    assert_eq!(report.buckets.len(), 10);
+    assert_eq!(report.buckets[2].events, 10);
+    assert_eq!(report.buckets[9].model_events["sink-01"], 1);
+    assert_eq!(report.model_events["sink-01"], 11);
+    assert_eq!(report.bursts, [2]);
+    let idle_spans: Vec<(f64, f64)> = report
+        .idle_spans
+        .iter()
+        .map(|span| (span.start, span.end))
+        .collect();
+    assert_eq!(idle_spans, [(0.0, 2.0), (3.0, 9.0)]);
+    assert!(matches!(
+        simulation.event_density(0.0),
+        Err(SimulationError::InvalidBucketWidth(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn message_history_is_retained_and_queried() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 4.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let mut connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    connectors.extend(topology::pipeline(
+        &["processor-01", "sink-01"],
+        "processed",
+        "job",
+    ));
+    let mut simulation = Simulation::post(models, connectors);
+    simulation.enable_deterministic_mode();
+    assert!(matches!(
+        simulation.get_message_history(),
+        Err(SimulationError::MessageHistoryUnavailable)
+    ));
+    simulation.enable_message_history();
+    let injected = Message::new(
+        String::from("manual"),
+        String::from("manual"),
+        String::from("sink-01"),
+        String::from("job"),
+        0.0,
+        String::from("injected job"),
+    );
+    simulation.inject_input(injected);
+    let mut stepped = simulation.step_until(5.5)?;
+    stepped.extend(simulation.get_messages().clone());
+    let history = simulation.get_message_history()?;
+    assert_eq!(history.len(), stepped.len() + 1);
+    assert_eq!(history[0].source_id(), "manual");
+    // Jobs generated at 1.0 through 5.0, and processed 0.25 later
+    let processed = simulation.query_messages(&MessageFilter {
+        source_id: Some(String::from("processor-01")),
+        target_port: Some(String::from("job")),
+        ..MessageFilter::default()
+    })?;
+    assert_eq!(processed.len(), 5);
+    assert_eq!(*processed[0].time(), 1.25);
+    let window = simulation.query_messages(&MessageFilter {
+        start: Some(2.0),
+        end: Some(3.0),
+        ..MessageFilter::default()
+    })?;
+    assert_eq!(
+        window
+            .iter()
+            .map(|message| *message.time())
+            .collect::<Vec<f64>>(),
+        [2.0, 2.25, 3.0]
+    );
+    assert_eq!(simulation.messages_between(2.0, 3.0)?.len(), window.len());
+    // Model states are not retained by the message history
+    assert!(matches!(
+        simulation.state_at(1.0),
+        Err(SimulationError::HistoryUnavailable)
+    ));
+    // Resets discard the retained messages
+    simulation.reset();
+    assert!(simulation.get_message_history()?.is_empty());
+    simulation.disable_message_history();
+    assert!(simulation
+        .query_messages(&MessageFilter::default())
+        .is_err());
+    Ok(())
+}
+
+// A user-supplied generator (xorshift64*), deliberately without a `Debug`
+// implementation
+struct XorShiftRng {
+    state: u64,
+}
+
+impl rand_core::RngCore for XorShiftRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl rand_core::SeedableRng for XorShiftRng {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        // The state must be non-zero
+        Self {
+            state: u64::from_le_bytes(seed) | 1,
+        }
+    }
+}
+
+#[test]
+fn user_supplied_seedable_rngs_drive_simulations() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "sink-01"], "job", "job");
+    let arrivals = |simulation: &mut Simulation| -> Result<Vec<f64>, SimulationError> {
+        Ok(simulation
+            .step_until(20.0)?
+            .iter()
+            .map(|message| *message.time())
+            .collect())
+    };
+    let mut simulation =
+        Simulation::post_with_seedable_rng::<XorShiftRng>(models.clone(), connectors.clone(), 7);
+    let seeded = arrivals(&mut simulation)?;
+    assert!(!seeded.is_empty());
+    assert_eq!(simulation.manifest()?.rng_seed, Some(7));
+    // The same seed reproduces the run, and other seeds vary it
+    let mut reseeded = Simulation::post(models.clone(), connectors.clone());
+    reseeded.set_seedable_rng::<XorShiftRng>(7);
+    assert_eq!(arrivals(&mut reseeded)?, seeded);
+    let mut other = Simulation::post_with_seedable_rng::<XorShiftRng>(models, connectors, 8);
+    assert_ne!(arrivals(&mut other)?, seeded);
+    Ok(())
+}
+
+#[test]
+fn model_verbosity_controls_record_and_message_detail() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 4.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, true)),
+        ),
+    ];
+    let mut connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    connectors.extend(topology::pipeline(
+        &["processor-01", "sink-01"],
+        "processed",
+        "job",
+    ));
+    let mut simulation = Simulation::post(models, connectors);
+    simulation.enable_deterministic_mode();
+    // The history retains the messages under the same verbosity rule as the
+    // step outputs
+    simulation.enable_history(4)?;
+    simulation.set_verbosity("generator-01", Verbosity::None)?;
+    simulation.set_verbosity("processor-01", Verbosity::Summary)?;
+    assert_eq!(simulation.verbosity("sink-01"), Verbosity::Full);
+    assert!(simulation
+        .set_verbosity("missing", Verbosity::None)
+        .is_err());
+    simulation.inject_input(
+        Message::new(
+            String::from("manual"),
+            String::from("manual"),
+            String::from("processor-01"),
+            String::from("job"),
+            0.0,
+            String::from("traced job"),
+        )
+        .with_correlation_id(Some(String::from("trace-01"))),
+    );
+    let messages = simulation.step_until(3.5)?;
+    // Generated jobs are routed, but only the processed jobs are reported
+    assert!(messages
+        .iter()
+        .all(|message| message.source_id() == "processor-01"));
+    assert_eq!(messages.len(), 4);
+    assert!(messages
+        .iter()
+        .all(|message| message.correlation_id().is_none()));
+    assert_eq!(
+        simulation
+            .get_sink_summary("sink-01")?
+            .map(|summary| summary.count),
+        Some(4)
+    );
+    assert!(simulation.get_records("generator-01")?.is_empty());
+    assert!(simulation.get_records("processor-01")?.is_empty());
+    assert!(!simulation.get_records("sink-01")?.is_empty());
+    assert!(simulation
+        .get_message_history()?
+        .iter()
+        .all(|message| message.source_id() != "generator-01"));
+    assert!(simulation.messages_for_model("generator-01")?.is_empty());
+    let processed = simulation.query_messages(&MessageFilter {
+        source_id: Some(String::from("processor-01")),
+        ..MessageFilter::default()
+    })?;
+    assert_eq!(processed.len(), 4);
+    assert!(processed
+        .iter()
+        .all(|message| message.correlation_id().is_none()));
+    // The processor under investigation switches to full traces mid-run
+    simulation.set_verbosity("processor-01", Verbosity::Full)?;
+    simulation.step_until(5.5)?;
+    assert!(!simulation.get_records("processor-01")?.is_empty());
+    assert!(simulation.get_records("generator-01")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn seeded_simulations_are_reproducible() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "sink-01"], "job", "job");
+    let arrivals = |simulation: &mut Simulation| -> Result<Vec<f64>, SimulationError> {
+        Ok(simulation
+            .step_until(20.0)?
+            .iter()
+            .map(|message| *message.time())
+            .collect())
+    };
+    let mut seeded = Simulation::post_with_seed(models.clone(), connectors.clone(), 7);
+    let replication = arrivals(&mut seeded)?;
+    assert_eq!(seeded.manifest()?.rng_seed, Some(7));
+    let mut reseeded = Simulation::post(models.clone(), connectors.clone());
+    reseeded.set_seed(7);
+    assert_eq!(arrivals(&mut reseeded)?, replication);
+    let mut other = Simulation::post_with_seed(models.clone(), connectors.clone(), 8);
+    assert_ne!(arrivals(&mut other)?, replication);
+    // The default simulation seed is an ordinary seed
+    let mut default_seeded = Simulation::post_with_seed(
+        models.clone(),
+        connectors.clone(),
+        sim::input_modeling::dynamic_rng::DEFAULT_SEED,
+    );
+    let mut unseeded = Simulation::post(models, connectors);
+    assert_eq!(arrivals(&mut default_seeded)?, arrivals(&mut unseeded)?);
+    Ok(())
+}
+
+#[test]
+fn adjacent_seeds_produce_distinct_streams() -> Result<(), SimulationError> {
+    let models = vec![Model::new(
+        String::from("generator-01"),
+        Box::new(Generator::new(
+            ContinuousRandomVariable::Exp { lambda: 1.0 },
+            None,
+            String::from("job"),
+            false,
+            None,
+        )),
+    )];
+    let arrivals = |seed: u64| -> Result<Vec<f64>, SimulationError> {
+        let mut simulation = Simulation::post_with_seed(models.clone(), Vec::new(), seed);
+        (0..10)
+            .map(|_| {
+                simulation.step()?;
+                Ok(simulation.get_global_time())
+            })
+            .collect()
+    };
+    for seed in [2, 42, 1000] {
+        assert_ne!(arrivals(seed)?, arrivals(seed + 1)?);
+        assert_eq!(arrivals(seed)?, arrivals(seed)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn restored_checkpoints_resume_exactly() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("processed"), 10.0, false)),
+        ),
+    ];
+    let connectors = vec![
+        Connector::new(
+            String::from("connector-01"),
+            String::from("generator-01"),
+            String::from("processor-01"),
+            String::from("job"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("processor-01"),
+            String::from("sink-01"),
+            String::from("processed"),
+            String::from("processed"),
+        ),
+    ];
+    let mut simulation = Simulation::post_with_seed(models.clone(), connectors.clone(), 7);
+    simulation.step_until(25.0)?;
+    // Pause, with messages in flight, and resume from the portable format
+    let checkpoint = simulation.checkpoint()?;
+    assert_eq!(checkpoint.global_time(), simulation.get_global_time());
+    let mut restored = Simulation::restore(&Checkpoint::from_bytes(&checkpoint.to_bytes()?)?)?;
+    assert_eq!(
+        restored.get_messages().len(),
+        simulation.get_messages().len()
+    );
+    assert_eq!(restored.manifest()?.rng_seed, Some(7));
+    let resumed: Vec<(String, f64)> = restored
+        .step_until(50.0)?
+        .iter()
+        .map(|message| (message.source_id().to_string(), *message.time()))
+        .collect();
+    let original: Vec<(String, f64)> = simulation
+        .step_until(50.0)?
+        .iter()
+        .map(|message| (message.source_id().to_string(), *message.time()))
+        .collect();
+    assert!(!original.is_empty());
+    assert_eq!(resumed, original);
+    // The state of a user-supplied generator is opaque
+    let custom = Simulation::post_with_seedable_rng::<XorShiftRng>(models, connectors, 7);
+    assert!(matches!(
+        custom.checkpoint(),
+        Err(SimulationError::OpaqueRngState)
+    ));
+    Ok(())
+}
+
+#[test]
+fn topology_analysis_reports_loops_and_depth() {
+    let processor = || {
+        Box::new(Processor::new(
+            ContinuousRandomVariable::Exp { lambda: 1.0 },
+            None,
+            String::from("job"),
+            String::from("processed"),
+            false,
+            None,
+        ))
+    };
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(String::from("processor-01"), processor()),
+        Model::new(String::from("processor-02"), processor()),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("processed"), 10.0, false)),
+        ),
+        Model::new(
+            String::from("sink-02"),
+            Box::new(Sink::new(String::from("processed"), 10.0, false)),
+        ),
+    ];
+    let mut connectors = topology::pipeline(
+        &["generator-01", "processor-01", "processor-02", "sink-01"],
+        "processed",
+        "job",
+    );
+    // Rework loops back, and a second connection to the same model does not
+    // add to the fan-out
+    connectors.extend(topology::pipeline(
+        &["processor-02", "processor-01"],
+        "processed",
+        "job",
+    ));
+    connectors.push(Connector::new(
+        String::from("rework-audit"),
+        String::from("processor-02"),
+        String::from("sink-01"),
+        String::from("processed"),
+        String::from("processed"),
+    ));
+    let report = Simulation::post(models, connectors).analyze_topology();
+    let degrees: Vec<(&str, usize, usize)> = report
+        .models
+        .iter()
+        .map(|model| (model.id.as_str(), model.fan_in, model.fan_out))
+        .collect();
+    assert_eq!(
+        degrees,
+        vec![
+            ("generator-01", 0, 1),
+            ("processor-01", 2, 1),
+            ("processor-02", 1, 2),
+            ("sink-01", 1, 0),
+            ("sink-02", 0, 0),
+        ]
+    );
+    assert_eq!(
+        report.connected_components,
+        vec![
+            vec!["generator-01", "processor-01", "processor-02", "sink-01"],
+            vec!["sink-02"],
+        ]
+    );
+    assert_eq!(
+        report.strongly_connected_components,
+        vec![vec!["processor-01", "processor-02"]]
+    );
+    assert_eq!(
+        report.feedback_loops,
+        vec![vec!["processor-01", "processor-02"]]
+    );
+    assert_eq!(
+        report.longest_acyclic_path,
+        vec!["generator-01", "processor-01"]
+    );
+    assert_eq!(report.depth, 1);
+    // Connectors referencing unknown models are ignored
+    let unknown = Simulation::post(
+        Vec::new(),
+        topology::pipeline(&["generator-01", "sink-01"], "job", "job"),
+    )
+    .analyze_topology();
+    assert!(unknown.models.is_empty() && unknown.longest_acyclic_path.is_empty());
+}
+
+#[test]
+fn scheduler_emits_one_shot_and_recurring_messages() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("scheduler-01"),
+            Box::new(Scheduler::new(
+                vec![
+                    ScheduleRule::once(String::from("control"), String::from("open"), 2.5),
+                    ScheduleRule::recurring(
+                        String::from("control"),
+                        String::from("tick"),
+                        0.0,
+                        10.0,
+                    )
+                    .count(3),
+                    ScheduleRule::recurring(
+                        String::from("shift"),
+                        String::from("change"),
+                        5.0,
+                        0.1,
+                    )
+                    .until(5.35),
+                ],
+                true,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let connectors = vec![
+        Connector::new(
+            String::from("connector-01"),
+            String::from("scheduler-01"),
+            String::from("sink-01"),
+            String::from("control"),
+            String::from("job"),
+        ),
+        Connector::new(
+            String::from("connector-02"),
+            String::from("scheduler-01"),
+            String::from("sink-01"),
+            String::from("shift"),
+            String::from("job"),
+        ),
+    ];
+    let mut simulation = Simulation::post(models, connectors);
+    let messages = simulation.step_until(100.0)?;
+    let emissions: Vec<(f64, &str)> = messages
+        .iter()
+        .map(|message| ((*message.time() * 100.0).round() / 100.0, message.content()))
+        .collect();
+    assert_eq!(
+        emissions,
+        vec![
+            (0.0, "tick"),
+            (2.5, "open"),
+            (5.0, "change"),
+            (5.1, "change"),
+            (5.2, "change"),
+            (5.3, "change"),
+            (10.0, "tick"),
+            (20.0, "tick"),
+        ]
+    );
+    assert_eq!(simulation.get_records("scheduler-01")?.len(), 8);
+    assert_eq!(
+        simulation.get_status("scheduler-01")?,
+        "Emitted 8 scheduled messages"
+    );
+    // Periods must be positive, so recurring rules cannot emit endlessly at
+    // a single point in time
+    let mut invalid = Simulation::post(
+        vec![Model::new(
+            String::from("scheduler-01"),
+            Box::new(Scheduler::new(
+                vec![ScheduleRule::recurring(
+                    String::from("control"),
+                    String::from("tick"),
+                    0.0,
+                    0.0,
+                )],
+                false,
+            )),
+        )],
+        Vec::new(),
+    );
+    assert!(matches!(
+        invalid.step().as_ref().map_err(SimulationError::root_cause),
+        Err(SimulationError::InvalidModelConfiguration)
+    ));
+    Ok(())
+}
+
+#[test]
+fn delay_holds_concurrent_jobs_without_contention() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 5.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("delay-01"),
+            Box::new(Delay::new(
+                ContinuousRandomVariable::Uniform { min: 1.0, max: 2.0 },
+                String::from("job"),
+                String::from("job"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "delay-01", "sink-01"], "job", "job");
+    let mut simulation = Simulation::post(models, connectors);
+    let messages = simulation.step_until(50.0)?;
+    let arrivals: HashMap<&str, f64> = messages
+        .iter()
+        .filter(|message| message.source_id() == "generator-01")
+        .map(|message| (message.content(), *message.time()))
+        .collect();
+    let departures: Vec<(&str, f64)> = messages
+        .iter()
+        .filter(|message| message.source_id() == "delay-01")
+        .map(|message| (message.content(), *message.time()))
+        .collect();
+    // Every job is delayed by its own draw, regardless of the jobs in flight
+    assert!(departures.len() > 100);
+    departures.iter().for_each(|(job, departure)| {
+        let delay = departure - arrivals[job];
+        assert!((1.0..=2.0).contains(&delay));
+    });
+    let overtaken = departures
+        .windows(2)
+        .any(|pair| arrivals[pair[0].0] > arrivals[pair[1].0]);
+    assert!(overtaken);
+    // The messages of the final step, beyond the stopping time, are not
+    // reported, so the jobs in flight are counted from the records
+    let records = simulation.get_records("delay-01")?;
+    let count = |action: &str| {
+        records
+            .iter()
+            .filter(|record| record.action == action)
+            .count()
+    };
+    let in_flight = count("Arrival") - count("Departure");
+    assert_eq!(
+        simulation.get_status("delay-01")?,
+        format!["Delaying {} jobs", in_flight]
+    );
+    assert!(in_flight > 1);
+    Ok(())
+}
+
+#[test]
+fn higher_priority_messages_are_delivered_first() -> Result<(), SimulationError> {
+    let arrivals = |scheduling: EventScheduling| -> Result<Vec<String>, SimulationError> {
+        let models = vec![
+            Model::new(
+                String::from("processor-01"),
+                Box::new(Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: 1.0 },
+                    None,
+                    String::from("job"),
+                    String::from("processed"),
+                    true,
+                    None,
+                )),
+            ),
+            Model::new(
+                String::from("processor-02"),
+                Box::new(Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: 1.0 },
+                    None,
+                    String::from("job"),
+                    String::from("processed"),
+                    true,
+                    None,
+                )),
+            ),
+        ];
+        let mut simulation = Simulation::post(models, Vec::new());
+        simulation.set_event_scheduling(scheduling);
+        let injection = |target_id: &str, content: &str, priority: i32| {
+            Message::new(
+                String::from("manual"),
+                String::from("manual"),
+                target_id.to_string(),
+                String::from("job"),
+                0.0,
+                content.to_string(),
+            )
+            .with_priority(priority)
+        };
+        simulation.inject_input(injection("processor-01", "data 1", 0));
+        simulation.inject_input(injection("processor-02", "data 2", 0));
+        simulation.inject_input(injection("processor-02", "interrupt 1", 10));
+        simulation.inject_input(injection("processor-01", "interrupt 2", 5));
+        simulation.step()?;
+        let mut arrivals: Vec<String> = Vec::new();
+        for model_id in ["processor-01", "processor-02"].iter() {
+            arrivals.extend(
+                simulation
+                    .get_records(model_id)?
+                    .iter()
+                    .filter(|record| record.action == "Arrival")
+                    .map(|record| record.subject.clone()),
+            );
+        }
+        Ok(arrivals)
+    };
+    // Each processor queues its interrupt ahead of its data job
+    let expected = vec!["interrupt 2", "data 1", "interrupt 1", "data 2"];
+    assert_eq!(arrivals(EventScheduling::Scan)?, expected);
+    assert_eq!(arrivals(EventScheduling::FutureEventList)?, expected);
+    // Priorities are carried in the message metadata
+    let message: Message = serde_json::from_str(
+        r#"{"sourceId": "a", "sourcePort": "b", "targetId": "c", "targetPort": "d", "time": 0.0, "content": "e", "metadata": {"sim.priority": "3"}}"#,
+    )
+    .unwrap();
+    assert_eq!(message.priority(), 3);
+    assert_eq!(message.clone().with_priority(0).priority(), 0);
+    assert!(message.with_priority(0).metadata().is_empty());
+    Ok(())
+}
+
+#[test]
+fn real_time_execution_interleaves_host_events() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 10.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("processed"), 10.0, false)),
+        ),
+    ];
+    let connectors = topology::pipeline(&["processor-01", "sink-01"], "processed", "processed");
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    // An operator submitting jobs every 20 milliseconds
+    let operator = std::thread::spawn(move || {
+        (0..3).for_each(|job| {
+            sender.send(format!["job {}", job]).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        })
+    });
+    // One simulation time unit per wall clock millisecond
+    let mut executor = RealTimeExecutor::new(1000.0)
+        .with_poll_interval(std::time::Duration::from_millis(1))
+        .bind("processor-01", "job", receiver);
+    let messages = executor.run_until(&mut simulation, 500.0)?;
+    operator.join().unwrap();
+    let submissions: Vec<f64> = messages
+        .iter()
+        .filter(|message| message.source_id() == "host")
+        .map(|message| *message.time())
+        .collect();
+    assert_eq!(submissions.len(), 3);
+    // Submissions are mapped to the simulation time of their wall clock
+    // arrival
+    assert!(submissions.windows(2).all(|pair| pair[1] - pair[0] >= 10.0));
+    assert_eq!(
+        messages
+            .iter()
+            .filter(|message| message.source_id() == "processor-01")
+            .count(),
+        3
+    );
+    assert!(simulation.get_global_time() < 500.0);
+    Ok(())
+}
+
+#[test]
+fn columnar_messages_flatten_message_fields() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("processed"), 10.0, false)),
+        ),
+    ];
+    let mut connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    connectors.extend(topology::pipeline(
+        &["processor-01", "sink-01"],
+        "processed",
+        "processed",
+    ));
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    let step_messages = simulation.step_n(100)?;
+    let messages = Messages::from(step_messages.as_slice());
+    assert_eq!(messages.len(), step_messages.len());
+    assert_eq!(messages.get(0), Some(step_messages[0].as_tuple()));
+    assert!(messages
+        .rows()
+        .zip(step_messages.iter())
+        .all(|(row, message)| row == message.as_tuple()));
+    let sink_arrivals = messages.filter(&MessageFilter {
+        target_id: Some(String::from("sink-01")),
+        ..MessageFilter::default()
+    });
+    assert!(!sink_arrivals.is_empty());
+    assert!(sink_arrivals
+        .target_ports()
+        .iter()
+        .all(|port| port == "processed"));
+    let expected_times: Vec<f64> = step_messages
+        .iter()
+        .filter(|message| message.target_id() == "sink-01")
+        .map(|message| *message.time())
+        .collect();
+    assert_eq!(sink_arrivals.times(), expected_times.as_slice());
+    let (source_id, _, target_id, _, time, content) = step_messages[0].clone().into();
+    assert_eq!(
+        (
+            source_id.as_str(),
+            target_id.as_str(),
+            time,
+            content.as_str()
+        ),
+        (
+            messages.source_ids()[0].as_str(),
+            messages.target_ids()[0].as_str(),
+            messages.times()[0],
+            messages.contents()[0].as_str()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn processor_utilization_matches_analytic_traffic_intensity() -> Result<(), SimulationError> {
+    // An M/M/1 queue, with a traffic intensity of 0.5 / 0.8
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(
+                Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: 0.8 },
+                    None,
+                    String::from("job"),
+                    String::from("processed"),
+                    true,
+                    None,
+                )
+                .with_warm_up(1000.0),
+            ),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    simulation.step_until(500.0)?;
+    let warming_up = simulation.get_utilization("processor-01")?.unwrap();
+    assert_eq!(warming_up.utilization, None);
+    assert!(simulation
+        .get_status_detail("processor-01")?
+        .ends_with("warming up until 1000"));
+    simulation.step_until(50000.0)?;
+    let summary = simulation.get_utilization("processor-01")?.unwrap();
+    assert_eq!(summary.observed_time, summary.time - 1000.0);
+    assert!(summary.busy_time <= summary.observed_time);
+    assert!((summary.utilization.unwrap() - 0.625).abs() < 0.02);
+    assert!(simulation
+        .get_status_detail("processor-01")?
+        .contains("% utilization since 1000"));
+    // The running utilization is recorded at each departure after the
+    // warm-up period
+    let records = simulation.get_records("processor-01")?;
+    let utilization_records: Vec<f64> = records
+        .iter()
+        .filter(|record| record.action == "Utilization")
+        .map(|record| record.subject.parse().unwrap())
+        .collect();
+    assert!(records
+        .iter()
+        .filter(|record| record.action == "Utilization")
+        .all(|record| record.time > 1000.0));
+    assert!((utilization_records.last().unwrap() - 0.625).abs() < 0.02);
+    // Models without service report no utilization
+    assert_eq!(simulation.get_utilization("generator-01")?, None);
+    Ok(())
+}
+
+#[test]
+fn energy_report_accounts_for_busy_time_and_messages() -> Result<(), SimulationError> {
+    // A job every 4 time units, each served for 1 time unit
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 4.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    simulation.set_energy_coefficients(
+        "processor-01",
+        EnergyCoefficients {
+            per_busy_time: 10.0,
+            per_message: 1.0,
+        },
+    )?;
+    simulation.step_until(100.0)?;
+    let report = simulation.energy_report(4.0)?;
+    assert_eq!(report.models.len(), 1);
+    let processor = &report.models["processor-01"];
+    let utilization = simulation.get_utilization("processor-01")?.unwrap();
+    assert_eq!(processor.busy_time, utilization.busy_time);
+    assert_eq!(processor.busy_energy, 10.0 * processor.busy_time);
+    // 24 jobs arrive at 4, 8, ..., 96, and each is received and then sent on
+    assert_eq!(processor.busy_time, 24.0);
+    assert_eq!(processor.messages, 48);
+    assert_eq!(processor.message_energy, processor.messages as f64);
+    assert_eq!(report.energy, processor.energy);
+    let bucket_energy: f64 = report.buckets.iter().map(|bucket| bucket.energy).sum();
+    assert!((bucket_energy - report.energy).abs() < 1.0e-9);
+    // Each full bucket holds one service, and its arrival and departure
+    assert!(report.buckets[1..report.buckets.len() - 1]
+        .iter()
+        .all(|bucket| (bucket.energy - 12.0).abs() < 1.0e-9));
+    assert!(matches!(
+        simulation.set_energy_coefficients("processor-02", EnergyCoefficients::default()),
+        Err(SimulationError::ModelNotFound(_))
+    ));
+    // A replacement model of the same ID starts without energy accounting
+    simulation.remove_model("processor-01")?;
+    assert_eq!(
+        simulation.energy_coefficients("processor-01"),
+        EnergyCoefficients::default()
+    );
+    simulation.add_model(models[1].clone())?;
+    simulation.step_until(200.0)?;
+    assert!(simulation.energy_report(4.0)?.models.is_empty());
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_output_matches_stepped_output() -> Result<(), SimulationError> {
+    use sim::simulator::SqliteWriter;
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("processed"), 10.0, true)),
+        ),
+    ];
+    let mut connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    connectors.extend(topology::pipeline(
+        &["processor-01", "sink-01"],
+        "processed",
+        "processed",
+    ));
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    // Clones share the random number generator, so each run is reseeded
+    let mut stepped = simulation.clone();
+    stepped.set_seed(7);
+    let messages = stepped.step_until(100.0)?;
+    simulation.set_seed(7);
+    let mut writer = SqliteWriter::open_in_memory(&simulation, "baseline")?;
+    assert_eq!(writer.step_until(&mut simulation, 100.0)?, messages.len());
+    writer.write_kpis(&simulation)?;
+    let connection = writer.connection();
+    let count = |query: &str| -> i64 { connection.query_row(query, [], |row| row.get(0)).unwrap() };
+    assert_eq!(
+        count("SELECT COUNT(*) FROM messages") as usize,
+        messages.len()
+    );
+    assert_eq!(
+        count("SELECT COUNT(*) FROM messages WHERE target_id = 'sink-01'") as usize,
+        messages
+            .iter()
+            .filter(|message| message.target_id() == "sink-01")
+            .count()
+    );
+    let records = ["generator-01", "processor-01", "sink-01"]
+        .iter()
+        .map(|model_id| Ok(simulation.get_records(model_id)?.len()))
+        .sum::<Result<usize, SimulationError>>()?;
+    assert_eq!(count("SELECT COUNT(*) FROM records") as usize, records);
+    let sink_count: f64 = connection
+        .query_row(
+            "SELECT value FROM kpis WHERE model_id = 'sink-01' AND name = 'sink_count'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(
+        sink_count,
+        simulation.get_sink_summary("sink-01")?.unwrap().count as f64
+    );
+    assert_eq!(
+        count("SELECT steps FROM runs WHERE label = 'baseline'") as usize,
+        simulation.execution_stats().steps
+    );
+    Ok(())
+}
+
+#[test]
+fn configured_assertions_gate_on_queue_outputs() -> Result<(), SimulationError> {
+    // An M/M/1 queue, with a mean waiting time of 1.0, and a 95th
+    // percentile waiting time of 2 ln(10)
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 0.5 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                true,
+                None,
+            )),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    let assertions: Vec<Assertion> = serde_yaml::from_str(
+        r#"
+- name: p95 wait
+  metric:
+    waitingTime:
+      modelId: processor-01
+  statistic:
+    quantile: 0.95
+  comparison: "<"
+  threshold: 10.0
+  alpha: 0.05
+  warmUp: 100.0
+- name: mean wait
+  metric:
+    waitingTime:
+      modelId: processor-01
+  comparison: ">"
+  threshold: 2.0
+- name: utilization
+  metric:
+    utilization:
+      modelId: processor-01
+  comparison: ">="
+  threshold: 0.4
+- name: completions
+  metric:
+    responseTime:
+      modelId: processor-01
+  statistic: count
+  comparison: ">"
+  threshold: 1000.0
+"#,
+    )?;
+    assertions
+        .into_iter()
+        .for_each(|assertion| simulation.add_assertion(assertion));
+    // Assertions are part of the simulation configuration
+    let restored: Simulation = serde_yaml::from_str(&serde_yaml::to_string(&simulation)?)?;
+    assert_eq!(restored.assertions(), simulation.assertions());
+    let report = simulation.run_assertions_until(5000.0)?;
+    assert!(!report.passed());
+    let passed: Vec<bool> = report.results.iter().map(|result| result.passed).collect();
+    assert_eq!(passed, vec![true, false, true, true]);
+    let p95 = &report.results[0];
+    assert!((p95.estimate.unwrap() - 2.0 * 10.0f64.ln()).abs() < 1.0);
+    // The upper confidence bound is compared, for a "<" assertion
+    assert!(p95.compared.unwrap() >= p95.estimate.unwrap());
+    assert!((report.results[1].estimate.unwrap() - 1.0).abs() < 0.25);
+    assert_eq!(
+        report
+            .failures()
+            .map(|result| result.assertion.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["mean wait"]
+    );
+    Ok(())
+}
+
+#[test]
+fn thinning_schedules_shape_generation() -> Result<(), SimulationError> {
+    // Candidate jobs every 0.1 time units, kept at a fifth of the rate
+    // overnight (before 12.0) and at the full rate during the day
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 0.1 },
+                Some(Thinning::schedule(
+                    vec![(0.0, 0.2), (12.0, 1.0)],
+                    false,
+                    Some(24.0),
+                )),
+                String::from("job"),
+                true,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "sink-01"], "job", "job");
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    simulation.set_seed(7);
+    simulation.step_until(240.0)?;
+    let generations: Vec<f64> = simulation
+        .get_records("generator-01")?
+        .iter()
+        .filter(|record| record.action == "Generation")
+        .map(|record| record.time)
+        .collect();
+    let night = generations
+        .iter()
+        .filter(|time| time.rem_euclid(24.0) < 12.0)
+        .count() as f64;
+    let day = generations.len() as f64 - night;
+    // 1200 day candidates, all kept, and 1200 night candidates, a fifth kept
+    assert!((day - 1200.0).abs() <= 1.0);
+    assert!((night - 240.0).abs() < 50.0);
+    // Thinned candidates are not numbered as jobs
+    let last_generation = simulation
+        .get_records("generator-01")?
+        .iter()
+        .rev()
+        .find(|record| record.action == "Generation")
+        .unwrap();
+    assert_eq!(
+        last_generation.subject,
+        format!["job {}", generations.len()]
+    );
+    Ok(())
+}
+
+#[test]
+fn per_model_rng_streams_isolate_sample_paths() -> Result<(), SimulationError> {
+    let generator = |id: &str| {
+        Model::new(
+            String::from(id),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                true,
+                None,
+            )),
+        )
+    };
+    let processor = || {
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                true,
+                None,
+            )),
+        )
+    };
+    let record_times = |simulation: &Simulation, model_id: &str| -> Vec<f64> {
+        simulation
+            .get_records(model_id)
+            .unwrap()
+            .iter()
+            .map(|record| record.time)
+            .filter(|time| *time < 45.0)
+            .collect()
+    };
+    // Intermediate steps of other models accumulate floating point error in
+    // the event times
+    let same_times = |a: &[f64], b: &[f64]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1.0e-9)
+    };
+    let run = |models: Vec<Model>, rng_streams: RngStreams| -> Simulation {
+        let connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+        let mut simulation = Simulation::post(models, connectors);
+        simulation.set_seed(11);
+        simulation.set_rng_streams(rng_streams);
+        simulation.step_until(50.0).unwrap();
+        simulation
+    };
+    // An unrelated generator perturbs the shared stream, but not the
+    // per-model streams
+    for rng_streams in [RngStreams::Shared, RngStreams::PerModel] {
+        let base = run(vec![generator("generator-01"), processor()], rng_streams);
+        let extended = run(
+            vec![
+                generator("generator-00"),
+                generator("generator-01"),
+                processor(),
+            ],
+            rng_streams,
+        );
+        let isolated = ["generator-01", "processor-01"].iter().all(|model_id| {
+            same_times(
+                &record_times(&base, model_id),
+                &record_times(&extended, model_id),
+            )
+        });
+        assert_eq!(isolated, rng_streams == RngStreams::PerModel);
+    }
+    // A pinned stream index follows the model across renames
+    let pinned = run(
+        vec![
+            generator("generator-01").with_rng_stream_index(3),
+            processor(),
+        ],
+        RngStreams::PerModel,
+    );
+    let renamed = run(
+        vec![
+            generator("generator-02").with_rng_stream_index(3),
+            generator("generator-01"),
+            processor(),
+        ],
+        RngStreams::PerModel,
+    );
+    assert!(same_times(
+        &record_times(&pinned, "generator-01"),
+        &record_times(&renamed, "generator-02")
+    ));
+    assert!(!same_times(
+        &record_times(&pinned, "generator-01"),
+        &record_times(&renamed, "generator-01")
+    ));
+    // The stream assignment and pinned indices are part of the configuration
+    let restored: Simulation = serde_yaml::from_str(&serde_yaml::to_string(&pinned)?)?;
+    assert_eq!(restored.rng_streams(), RngStreams::PerModel);
+    let restored_models: Vec<Model> =
+        serde_yaml::from_str(&serde_yaml::to_string(&[
+            generator("generator-01").with_rng_stream_index(3)
+        ])?)?;
+    assert_eq!(restored_models[0].rng_stream_index(), Some(3));
+    Ok(())
+}
+
+#[test]
+fn per_model_rng_streams_resume_from_restored_simulations() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    simulation.set_seed(11);
+    simulation.set_rng_streams(RngStreams::PerModel);
+    // The seed of the per-model streams is part of the serialized simulation
+    let mut restored: Simulation = serde_yaml::from_str(&serde_yaml::to_string(&simulation)?)?;
+    assert_eq!(restored.get_rng_seed(), Some(11));
+    assert_eq!(
+        serde_json::to_string(&restored.step_until(20.0)?)?,
+        serde_json::to_string(&simulation.step_until(20.0)?)?
+    );
+    // Checkpoints capture the streams mid-run, for exact resumption
+    let mut resumed = Simulation::restore(&simulation.checkpoint()?)?;
+    assert_eq!(
+        serde_json::to_string(&resumed.step_until(40.0)?)?,
+        serde_json::to_string(&simulation.step_until(40.0)?)?
+    );
+    Ok(())
+}
+
+#[test]
+fn edit_transactions_stage_validate_and_commit() -> Result<(), SimulationError> {
+    let processor = |id: &str| {
+        Model::new(
+            String::from(id),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 0.5 },
+                None,
+                String::from("job"),
+                String::from("job"),
+                false,
+                None,
+            )),
+        )
+    };
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        processor("processor-01"),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "processor-01", "sink-01"], "job", "job");
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    simulation.step_until(5.2)?;
+    simulation.enable_audit_log(16);
+    // Insert a second processor between the first processor and the sink
+    simulation.begin_edit()?;
+    assert!(matches!(
+        simulation.begin_edit(),
+        Err(SimulationError::EditInProgress)
+    ));
+    simulation.apply_edit(EditOperation::RemoveConnector {
+        connector_id: String::from("processor-01-sink-01"),
+    })?;
+    simulation.apply_edit(EditOperation::AddConnector(Connector::new(
+        String::from("processor-01-processor-02"),
+        String::from("processor-01"),
+        String::from("processor-02"),
+        String::from("job"),
+        String::from("job"),
+    )))?;
+    assert!(matches!(
+        simulation.apply_edit(EditOperation::RemoveModel {
+            model_id: String::from("processor-03"),
+        }),
+        Err(SimulationError::ModelNotFound(_))
+    ));
+    // The connector references a model not yet added, so the commit is
+    // rejected, and the transaction remains open
+    match simulation.commit_edit() {
+        Err(SimulationError::InvalidEdit(issues)) => assert_eq!(
+            issues,
+            vec!["Connector processor-01-processor-02 references the missing model processor-02"]
+        ),
+        _ => panic!("the edit should be invalid"),
+    }
+    assert!(simulation.edit_in_progress());
+    // The live topology keeps running while the edit is open
+    simulation.step_until(10.2)?;
+    let before_commit = simulation.get_sink_summary("sink-01")?.unwrap().count;
+    assert_eq!(before_commit, 9);
+    let operations: Vec<EditOperation> = serde_json::from_str(
+        r#"[
+            {"addModel": {"id": "processor-02", "type": "Processor", "serviceTime": {"constant": {"value": 0.25}}, "portsIn": {"job": "job"}, "portsOut": {"job": "job"}}},
+            {"addConnector": {"id": "processor-02-sink-01", "sourceID": "processor-02", "targetID": "sink-01", "sourcePort": "job", "targetPort": "job"}},
+            {"setParameter": {"modelId": "processor-01", "pointer": "/serviceTime/constant/value", "value": 0.25}}
+        ]"#,
+    )?;
+    operations
+        .into_iter()
+        .try_for_each(|operation| simulation.apply_edit(operation))?;
+    simulation.validate_edit()?;
+    simulation.commit_edit()?;
+    assert!(!simulation.edit_in_progress());
+    assert_eq!(
+        simulation.get_status("processor-02")?,
+        simulation.get_status("processor-01")?
+    );
+    // Jobs now flow through both processors to the sink
+    let messages = simulation.step_until(20.2)?;
+    let routed_jobs = messages
+        .iter()
+        .filter(|message| message.source_id() == "processor-02" && message.target_id() == "sink-01")
+        .count();
+    assert_eq!(routed_jobs, 9);
+    assert!(messages
+        .iter()
+        .all(|message| message.source_id() != "processor-01" || message.target_id() != "sink-01"));
+    assert!(simulation
+        .get_audit_log()?
+        .iter()
+        .any(|record| record.action == "Commit Edit"));
+    // Rolled back edits leave the live topology unchanged
+    simulation.begin_edit()?;
+    simulation.apply_edit(EditOperation::RemoveModel {
+        model_id: String::from("processor-02"),
+    })?;
+    simulation.rollback_edit()?;
+    assert!(simulation.get_status("processor-02").is_ok());
+    assert!(matches!(
+        simulation.rollback_edit(),
+        Err(SimulationError::NoEditInProgress)
+    ));
+    Ok(())
+}
+
+#[test]
+fn wildcard_connectors_broadcast_and_pass_ports_through() -> Result<(), SimulationError> {
+    let processor = |id: &str, service_time: f64| {
+        Model::new(
+            String::from(id),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant {
+                    value: service_time,
+                },
+                None,
+                String::from("job"),
+                String::from("job"),
+                false,
+                None,
+            )),
+        )
+    };
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        processor("processor-01", 0.25),
+        processor("processor-02", 0.5),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 100.0, false)),
+        ),
+    ];
+    let connectors: Vec<Connector> = serde_yaml::from_str(
+        "
+- id: broadcast
+  sourceID: generator-01
+  targetID: processor-*
+  sourcePort: job
+  targetPort: job
+- id: collect
+  sourceID: processor-*
+  targetID: sink-01
+  sourcePort: \"*\"
+  targetPort: \"*\"
+",
+    )?;
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    let messages = simulation.step_until(10.8)?;
+    // Every job is broadcast to both processors, and both processors'
+    // outputs reach the sink port of the same name
+    (1..=2).for_each(|processor| {
+        let processor_id = format!["processor-0{}", processor];
+        assert_eq!(
+            messages
+                .iter()
+                .filter(|message| message.target_id() == processor_id)
+                .count(),
+            10
+        );
+        assert_eq!(
+            messages
+                .iter()
+                .filter(|message| message.source_id() == processor_id
+                    && message.target_id() == "sink-01"
+                    && message.target_port() == "job")
+                .count(),
+            10
+        );
+    });
+    assert_eq!(simulation.get_sink_summary("sink-01")?.unwrap().count, 20);
+    let report = simulation.analyze_topology();
+    let sink = report
+        .models
+        .iter()
+        .find(|model| model.id == "sink-01")
+        .unwrap();
+    assert_eq!(sink.fan_in, 2);
+    assert_eq!(report.depth, 2);
+    Ok(())
+}
+
+#[test]
+fn large_pipelines_route_through_the_routing_table() -> Result<(), SimulationError> {
+    let processor_ids: Vec<String> = (1..=500)
+        .map(|index| format!["processor-{:04}", index])
+        .collect();
+    let mut models = vec![Model::new(
+        String::from("generator-01"),
+        Box::new(Generator::new(
+            ContinuousRandomVariable::Constant { value: 1000.0 },
+            None,
+            String::from("job"),
+            false,
+            None,
+        )),
+    )];
+    models.extend(processor_ids.iter().map(|id| {
+        Model::new(
+            id.clone(),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 0.001 },
+                None,
+                String::from("job"),
+                String::from("job"),
+                false,
+                None,
+            )),
+        )
+    }));
+    models.push(Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("job"), 1000.0, false)),
+    ));
+    let mut ids = vec!["generator-01"];
+    ids.extend(processor_ids.iter().map(String::as_str));
+    ids.push("sink-01");
+    let connectors = topology::pipeline(&ids, "job", "job");
+    let mut simulation = Simulation::post(models, connectors);
+    // The first job leaves the generator at time 1000, and takes half a
+    // simulated time unit to traverse the pipeline
+    simulation.step_until(1001.0)?;
+    assert_eq!(simulation.get_sink_summary("sink-01")?.unwrap().count, 1);
+    Ok(())
+}
+
+#[test]
+fn model_lookups_follow_topology_changes() -> Result<(), SimulationError> {
+    let generator = || {
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        )
+    };
+    let sink = |id: &str| {
+        Model::new(
+            String::from(id),
+            Box::new(Sink::new(String::from("job"), 100.0, false)),
+        )
+    };
+    let mut simulation = Simulation::post(
+        vec![generator(), sink("sink-01")],
+        topology::pipeline(&["generator-01", "sink-01"], "job", "job"),
+    );
+    simulation.step_until(3.5)?;
+    assert_eq!(simulation.get_sink_summary("sink-01")?.unwrap().count, 3);
+    // Reordered models are re-indexed, so messages still reach their
+    // targets - the replacement generator departs at 5.0, 6.0, and so on
+    simulation.put(
+        vec![sink("sink-02"), sink("sink-01"), generator()],
+        topology::pipeline(&["generator-01", "sink-02"], "job", "job"),
+    );
+    simulation.step_until(7.5)?;
+    assert_eq!(simulation.get_sink_summary("sink-02")?.unwrap().count, 3);
+    // The replacement sink-01 only receives the message in flight at the
+    // put
+    assert_eq!(simulation.get_sink_summary("sink-01")?.unwrap().count, 1);
+    simulation.begin_edit()?;
+    simulation.apply_edit(EditOperation::AddModel(sink("sink-03")))?;
+    simulation.apply_edit(EditOperation::AddConnector(Connector::new(
+        String::from("generator-01-sink-03"),
+        String::from("generator-01"),
+        String::from("sink-03"),
+        String::from("job"),
+        String::from("job"),
+    )))?;
+    assert!(matches!(
+        simulation.get_status("sink-03"),
+        Err(SimulationError::ModelNotFound(_))
+    ));
+    simulation.commit_edit()?;
+    // The departure at 8.0 was in flight at the commit
+    simulation.step_until(9.5)?;
+    assert_eq!(simulation.get_sink_summary("sink-03")?.unwrap().count, 1);
+    // Deserialized simulations are re-indexed
+    let mut restored =
+        Simulation::from_snapshot(&simulation.to_snapshot(SnapshotCompression::None)?)?;
+    assert!(restored.get_status("sink-03").is_ok());
+    restored.step_until(10.5)?;
+    assert_eq!(restored.get_sink_summary("sink-03")?.unwrap().count, 2);
+    Ok(())
+}
+
+#[test]
+fn quotas_bound_models_steps_and_memory() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        // A slow processor, with a growing queue
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 100.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    let mut simulation = Simulation::post(models, connectors);
+    assert!(matches!(
+        simulation.set_quotas(Quotas::default().with_max_models(1)),
+        Err(SimulationError::QuotaExceeded {
+            quota: QuotaKind::Models,
+            limit: 1,
+            actual: 2
+        })
+    ));
+    assert!(simulation.step().is_err());
+    simulation.set_quotas(Quotas::default().with_max_steps_per_request(10))?;
+    assert!(matches!(
+        simulation.step_n(11),
+        Err(SimulationError::QuotaExceeded {
+            quota: QuotaKind::StepsPerRequest,
+            ..
+        })
+    ));
+    simulation.step_n(10)?;
+    // Requests are bounded, while the simulation may continue over many
+    // requests
+    let error = simulation.step_until(100.0).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The steps per request quota of 10 is exceeded, with 11"
+    );
+    simulation.step_until(simulation.get_global_time() + 2.0)?;
+    let memory_estimate = simulation.memory_estimate()?;
+    simulation.set_quotas(Quotas::default().with_max_memory_bytes(memory_estimate + 500))?;
+    assert!(matches!(
+        simulation.step_until(1000.0),
+        Err(SimulationError::QuotaExceeded {
+            quota: QuotaKind::MemoryBytes,
+            ..
+        })
+    ));
+    assert!(simulation.memory_estimate()? > memory_estimate + 500);
+    // Edits beyond the model quota are not committed
+    simulation.set_quotas(Quotas::default().with_max_models(2))?;
+    simulation.begin_edit()?;
+    simulation.apply_edit(EditOperation::AddModel(Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("processed"), 100.0, false)),
+    )))?;
+    assert!(simulation.commit_edit().is_err());
+    assert!(simulation.edit_in_progress());
+    // Quotas are not part of the serialized simulation
+    let restored = Simulation::from_snapshot(&simulation.to_snapshot(SnapshotCompression::None)?)?;
+    assert_eq!(restored.quotas(), Quotas::default());
+    Ok(())
+}
+
+#[test]
+fn models_and_connectors_are_added_and_removed_mid_run() -> Result<(), SimulationError> {
+    let processor = |id: &str| {
+        Model::new(
+            String::from(id),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 0.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        )
+    };
+    let connector = |source_id: &str, target_id: &str, source_port: &str| {
+        Connector::new(
+            format!["{}-{}", source_id, target_id],
+            String::from(source_id),
+            String::from(target_id),
+            String::from(source_port),
+            String::from("job"),
+        )
+    };
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        processor("processor-01"),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 100.0, false)),
+        ),
+    ];
+    let connectors = vec![
+        connector("generator-01", "processor-01", "job"),
+        connector("processor-01", "sink-01", "processed"),
+    ];
+    let mut simulation = Simulation::post(models, connectors);
+    simulation.step_until(3.5)?;
+    // Scale out, with a second processor
+    assert!(matches!(
+        simulation.add_connector(connector("generator-01", "processor-02", "job")),
+        Err(SimulationError::DanglingConnector { model_id, .. }) if model_id == "processor-02"
+    ));
+    simulation.add_model(processor("processor-02"))?;
+    assert!(matches!(
+        simulation.add_model(processor("processor-02")),
+        Err(SimulationError::DuplicateModelId(_))
+    ));
+    simulation.add_connector(connector("generator-01", "processor-02", "job"))?;
+    simulation.add_connector(connector("processor-02", "sink-01", "processed"))?;
+    assert!(matches!(
+        simulation.add_connector(connector("processor-02", "sink-01", "processed")),
+        Err(SimulationError::DuplicateConnectorId(_))
+    ));
+    let messages = simulation.step_until(6.75)?;
+    assert!(messages
+        .iter()
+        .any(|message| message.target_id() == "processor-02"));
+    // Scale in, while a job is in flight to the first processor
+    let removed = simulation.remove_model("processor-01")?;
+    assert_eq!(removed.model.id(), "processor-01");
+    assert_eq!(removed.connectors.len(), 2);
+    assert_eq!(removed.undelivered.len(), 1);
+    assert_eq!(removed.undelivered[0].target_id(), "processor-01");
+    assert!(matches!(
+        simulation.get_status("processor-01"),
+        Err(SimulationError::ModelNotFound(_))
+    ));
+    let messages = simulation.step_until(10.5)?;
+    assert!(messages
+        .iter()
+        .all(|message| message.source_id() != "processor-01"
+            && message.target_id() != "processor-01"));
+    assert!(messages
+        .iter()
+        .any(|message| message.source_id() == "processor-02"));
+    simulation.remove_connector("generator-01-processor-02")?;
+    assert!(matches!(
+        simulation.remove_connector("generator-01-processor-02"),
+        Err(SimulationError::ConnectorNotFound(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn validation_reports_topology_issues_and_assumptions() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 2.0 },
+                Some(5),
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+    ];
+    let mut connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    connectors.push(Connector::new(
+        String::from("processor-01-sink-01"),
+        String::from("processor-01"),
+        String::from("sink-01"),
+        String::from("processed"),
+        String::from("job"),
+    ));
+    let mut simulation = Simulation::post(models, connectors);
+    let report = simulation.validate_with(&ValidationOptions::default());
+    assert!(!report.is_valid());
+    assert_eq!(
+        report
+            .errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>(),
+        vec!["Connector processor-01-sink-01 references the missing model sink-01"]
+    );
+    assert!(report.assumptions.is_none());
+    simulation.add_model(Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("job"), 10.0, false)),
+    ))?;
+    let report = simulation.validate_with(&ValidationOptions { assumptions: true });
+    assert!(report.is_valid());
+    let assumptions = report.assumptions.unwrap();
+    assert!(assumptions
+        .simulation
+        .iter()
+        .any(|assumption| assumption.topic == AssumptionTopic::MessagePassing));
+    let processor = &assumptions.models[1];
+    assert_eq!(processor.model_type, "Processor");
+    let value = |topic: AssumptionTopic| {
+        processor
+            .assumptions
+            .iter()
+            .find(|assumption| assumption.topic == topic)
+            .map(|assumption| assumption.value.as_str())
+    };
+    assert_eq!(value(AssumptionTopic::QueueDiscipline), Some("fifo"));
+    assert_eq!(value(AssumptionTopic::QueueCapacity), Some("5"));
+    assert_eq!(value(AssumptionTopic::Patience), Some("infinite"));
+    assert_eq!(value(AssumptionTopic::ServiceTimes), Some("iid"));
+    assert_eq!(
+        assumptions.models[0].assumptions[0].value,
+        String::from("renewal")
+    );
+    Ok(())
+}
+
+#[test]
+fn validation_reports_structured_configuration_errors() -> Result<(), SimulationError> {
+    let models = || {
+        vec![
+            Model::new(
+                String::from("generator-01"),
+                Box::new(Generator::new(
+                    ContinuousRandomVariable::Exp { lambda: 1.0 },
+                    None,
+                    String::from("job"),
+                    false,
+                    None,
+                )),
+            ),
+            Model::new(
+                String::from("load-balancer-01"),
+                Box::new(LoadBalancer::new(
+                    String::from("job"),
+                    vec![String::from("a"), String::from("b")],
+                    false,
+                )),
+            ),
+            Model::new(
+                String::from("gate-01"),
+                Box::new(Gate::new(
+                    String::from("job"),
+                    String::from("activation"),
+                    String::from("deactivation"),
+                    String::from("job"),
+                    false,
+                )),
+            ),
+            Model::new(
+                String::from("processor-01"),
+                Box::new(Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: 2.0 },
+                    None,
+                    String::from("job"),
+                    String::from("processed"),
+                    false,
+                    None,
+                )),
+            ),
+        ]
+    };
+    let connector = |id: &str, source: (&str, &str), target: (&str, &str)| {
+        Connector::new(
+            String::from(id),
+            String::from(source.0),
+            String::from(target.0),
+            String::from(source.1),
+            String::from(target.1),
+        )
+    };
+    // The gate feeds back into the load balancer, without any time
+    // advance, and the processor port is misspelled
+    let connectors = vec![
+        connector(
+            "connector-01",
+            ("generator-01", "job"),
+            ("load-balancer-01", "job"),
+        ),
+        connector(
+            "connector-02",
+            ("load-balancer-01", "a"),
+            ("gate-01", "job"),
+        ),
+        connector(
+            "connector-03",
+            ("gate-01", "job"),
+            ("load-balancer-01", "job"),
+        ),
+        connector(
+            "connector-03",
+            ("load-balancer-01", "b"),
+            ("processor-01", "jobs"),
+        ),
+    ];
+    let simulation = Simulation::post(models(), connectors.clone());
+    let orphan =
+        |model_id: &str, port: &str, direction: PortDirection| ValidationError::OrphanPort {
+            model_id: String::from(model_id),
+            port: String::from(port),
+            direction,
+        };
+    let errors = vec![
+        ValidationError::DuplicateConnectorId {
+            connector_id: String::from("connector-03"),
+        },
+        ValidationError::UnknownPort {
+            connector_id: String::from("connector-03"),
+            model_id: String::from("processor-01"),
+            port: String::from("jobs"),
+            direction: PortDirection::In,
+        },
+        orphan("gate-01", "activation", PortDirection::In),
+        orphan("gate-01", "deactivation", PortDirection::In),
+        orphan("processor-01", "job", PortDirection::In),
+        orphan("processor-01", "processed", PortDirection::Out),
+        ValidationError::ZeroTimeCycle {
+            model_ids: vec![String::from("load-balancer-01"), String::from("gate-01")],
+        },
+    ];
+    assert_eq!(simulation.configuration_errors(), &errors[..]);
+    assert_eq!(simulation.validate(), errors);
+    assert_eq!(errors[2].severity(), ValidationSeverity::Warning);
+    match Simulation::try_post(models(), connectors) {
+        Err(SimulationError::InvalidConfiguration(errors)) => {
+            assert_eq!(errors.len(), 3);
+            assert_eq!(
+                errors[2].to_string(),
+                "The models load-balancer-01, gate-01 form a cycle without any time advance"
+            );
+        }
+        _ => panic!("The invalid configuration was posted"),
+    }
+    // Warnings alone do not fail the configuration
+    let connectors = vec![
+        connector(
+            "connector-01",
+            ("generator-01", "job"),
+            ("load-balancer-01", "job"),
+        ),
+        connector(
+            "connector-02",
+            ("load-balancer-01", "a"),
+            ("gate-01", "job"),
+        ),
+        connector(
+            "connector-03",
+            ("load-balancer-01", "b"),
+            ("processor-01", "job"),
+        ),
+    ];
+    let mut simulation = Simulation::try_post(models(), connectors)?;
+    assert!(simulation
+        .configuration_errors()
+        .iter()
+        .all(|error| !error.is_error()));
+    assert!(simulation.try_put(models(), Vec::new()).is_ok());
+    let mut duplicated = models();
+    duplicated.extend(models());
+    assert!(simulation.try_put(duplicated, Vec::new()).is_err());
+    Ok(())
+}
+
+#[test]
+fn errors_carry_model_port_time_and_module_context() -> Result<(), SimulationError> {
+    let models = [Model::new(
+        String::from("processor-01"),
+        Box::new(Processor::new(
+            ContinuousRandomVariable::Exp { lambda: 1.0 },
+            None,
+            String::from("job"),
+            String::from("processed"),
+            false,
+            None,
+        )),
+    )];
+    let mut simulation = Simulation::post(models.to_vec(), Vec::new());
+    match simulation.get_utilization("processor-02") {
+        Err(error) => {
+            assert!(matches!(error, SimulationError::ModelNotFound(_)));
+            assert_eq!(error.model_id(), Some("processor-02"));
+            assert_eq!(
+                error.to_string(),
+                "The model processor-02 cannot be found in the simulation"
+            );
+        }
+        Ok(_) => panic!("expected a missing model error"),
+    }
+    simulation.inject_input(Message::new(
+        String::from("manual"),
+        String::from("manual"),
+        String::from("processor-01"),
+        String::from("jobs"),
+        simulation.get_global_time(),
+        String::from("job-01"),
+    ));
+    match simulation.step() {
+        Err(error) => {
+            assert!(matches!(
+                error.root_cause(),
+                SimulationError::InvalidMessage
+            ));
+            assert_eq!(error.model_id(), Some("processor-01"));
+            assert_eq!(error.port(), Some("jobs"));
+            assert_eq!(error.time(), Some(0.0));
+            assert_eq!(error.module(), Some("Processor"));
+            assert_eq!(
+                error.to_string(),
+                "An invalid inter-model message was encountered (model processor-01, port jobs, time 0, module Processor)"
+            );
+            assert!(std::error::Error::source(&error).is_some());
+        }
+        Ok(_) => panic!("expected an invalid message error"),
+    }
+    // Context is attached once, keeping the innermost details
+    let error = SimulationError::InvalidModelState
+        .with_context(ErrorContext::new("processor").with_model_id("processor-01"))
+        .with_context(
+            ErrorContext::new("simulator")
+                .with_model_id("coupled-01")
+                .with_time(2.5),
+        );
+    assert_eq!(error.model_id(), Some("processor-01"));
+    assert_eq!(error.module(), Some("processor"));
+    assert_eq!(error.time(), Some(2.5));
+    assert_eq!(error.port(), None);
+    Ok(())
+}
+
+#[test]
+fn stop_conditions_end_runs_on_domain_conditions() -> Result<(), SimulationError> {
+    // A job every 4 time units, each served for 1 time unit
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 4.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    let simulation = || Simulation::post(models.to_vec(), connectors.clone());
+    // The first job starts service on delivery, at time 4
+    let mut busy = simulation();
+    busy.step_until_stop(&StopCondition::ModelStatus {
+        model_id: String::from("processor-01"),
+        status: String::from("Processing"),
+    })?;
+    assert_eq!(busy.get_global_time(), 4.0);
+    // The third generated job is sent at time 12
+    let mut counted = simulation();
+    let messages = counted.step_until_stop(&StopCondition::MessageCount {
+        count: 3,
+        model_id: Some(String::from("generator-01")),
+    })?;
+    assert_eq!(counted.get_global_time(), 12.0);
+    assert_eq!(
+        messages
+            .iter()
+            .filter(|message| message.source_id() == "generator-01")
+            .count(),
+        3
+    );
+    // Composite conditions
+    let mut any = simulation();
+    any.step_until_stop(&StopCondition::Any(vec![
+        StopCondition::Time(50.0),
+        StopCondition::WallClock(1.0e9),
+    ]))?;
+    assert!(any.get_global_time() >= 50.0 && any.get_global_time() < 54.0);
+    let mut all = simulation();
+    let messages = all.step_until_stop(&StopCondition::All(vec![
+        StopCondition::Time(50.0),
+        StopCondition::MessageCount {
+            count: 100,
+            model_id: None,
+        },
+    ]))?;
+    assert_eq!(messages.len(), 100);
+    assert!(all.get_global_time() > 190.0);
+    // Utilization settles at 0.25
+    let mut settled = simulation();
+    settled.step_until_stop(&StopCondition::SteadyState {
+        model_id: String::from("processor-01"),
+        window: 10,
+        tolerance: 0.01,
+    })?;
+    let utilization = settled
+        .get_utilization("processor-01")?
+        .unwrap()
+        .utilization
+        .unwrap();
+    assert!(settled.get_global_time().is_finite());
+    assert!((utilization - 0.25).abs() < 0.01);
+    // Arbitrary predicates
+    let mut predicated = simulation();
+    predicated.step_until_condition(|simulation| {
+        simulation
+            .get_utilization("processor-01")
+            .ok()
+            .flatten()
+            .is_some_and(|summary| summary.busy_time >= 2.0)
+    })?;
+    assert_eq!(predicated.get_global_time(), 9.0);
+    assert!(matches!(
+        simulation().step_until_stop(&StopCondition::ModelStatus {
+            model_id: String::from("processor-02"),
+            status: String::from("Processing"),
+        }),
+        Err(SimulationError::ModelNotFound(_))
+    ));
+    let condition: StopCondition = serde_yaml::from_str(
+        "
+any:
+  - steadyState:
+      modelId: sink-01
+      window: 100
+      tolerance: 0.01
+  - wallClock: 5000.0
+",
+    )?;
+    assert_eq!(
+        condition,
+        StopCondition::Any(vec![
+            StopCondition::SteadyState {
+                model_id: String::from("sink-01"),
+                window: 100,
+                tolerance: 0.01,
+            },
+            StopCondition::WallClock(5000.0),
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn observers_follow_the_step_lifecycle() -> Result<(), SimulationError> {
+    struct Recorder {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl SimulationObserver for Recorder {
+        fn on_step_start(&mut self, time: f64, pending_messages: &[Message]) {
+            self.events.lock().unwrap().push(format![
+                "start {} with {}",
+                time,
+                pending_messages.len()
+            ]);
+        }
+
+        fn on_message_delivered(&mut self, message: &Message) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!["deliver to {}", message.target_id()]);
+        }
+
+        fn on_model_transition(&mut self, model_id: &str, kind: TransitionKind, time: f64) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!["{:?} {} at {}", kind, model_id, time]);
+        }
+
+        fn on_step_end(&mut self, time: f64, messages: &[Message]) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!["end {} with {}", time, messages.len()]);
+        }
+    }
+
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "sink-01"], "job", "job");
+    // The generator schedules its first job at time 0, without a message
+    let expected = [
+        "start 0 with 0",
+        "Internal generator-01 at 0",
+        "end 0 with 0",
+        "start 0 with 0",
+        "Internal generator-01 at 1",
+        "end 1 with 1",
+        "start 1 with 1",
+        "deliver to sink-01",
+        "External sink-01 at 1",
+        "end 1 with 0",
+    ];
+    [EventScheduling::Scan, EventScheduling::FutureEventList]
+        .iter()
+        .try_for_each(|event_scheduling| {
+            let events = Arc::new(Mutex::new(Vec::new()));
+            let mut simulation = Simulation::post(models.to_vec(), connectors.clone());
+            simulation.set_event_scheduling(*event_scheduling);
+            simulation.attach_observer(Box::new(Recorder {
+                events: events.clone(),
+            }));
+            simulation.step_n(3)?;
+            assert_eq!(*events.lock().unwrap(), expected);
+            // Observers are not carried over to clones
+            let mut clone = simulation.clone();
+            clone.step()?;
+            assert_eq!(events.lock().unwrap().len(), expected.len());
+            assert_eq!(simulation.detach_observers().len(), 1);
+            simulation.step()?;
+            assert_eq!(events.lock().unwrap().len(), expected.len());
+            Ok::<(), SimulationError>(())
+        })?;
+    Ok(())
+}
+
+#[test]
+fn traces_export_as_chrome_and_otlp_spans() -> Result<(), SimulationError> {
+    let models = [
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "sink-01"], "job", "job");
+    let mut simulation = Simulation::post(models.to_vec(), connectors);
+    assert!(matches!(
+        simulation.get_trace(),
+        Err(SimulationError::TraceUnavailable)
+    ));
+    simulation.enable_tracing();
+    simulation.step_until(2.5)?;
+    let events = simulation.get_trace()?.events();
+    let transitions: Vec<(f64, &str, TransitionKind, Vec<String>)> = events
+        .iter()
+        .map(|event| {
+            (
+                event.time,
+                event.model_id.as_str(),
+                event.kind,
+                event.ports.clone(),
+            )
+        })
+        .collect();
+    let job = || vec![String::from("job")];
+    assert_eq!(
+        transitions,
+        [
+            (0.0, "generator-01", TransitionKind::Internal, Vec::new()),
+            (1.0, "generator-01", TransitionKind::Internal, job()),
+            (1.0, "sink-01", TransitionKind::External, job()),
+            (2.0, "generator-01", TransitionKind::Internal, job()),
+            (2.0, "sink-01", TransitionKind::External, job()),
+            (3.0, "generator-01", TransitionKind::Internal, job()),
+        ]
+    );
+    // A thread per model, and a span per transition, until the model's
+    // next transition
+    let chrome = simulation.chrome_trace()?;
+    let trace_events = chrome["traceEvents"].as_array().unwrap();
+    assert_eq!(trace_events.len(), 2 + events.len());
+    assert_eq!(trace_events[0]["args"]["name"], "generator-01");
+    assert_eq!(trace_events[1]["args"]["name"], "sink-01");
+    assert_eq!(trace_events[2]["ph"], "X");
+    assert_eq!(trace_events[2]["ts"], 0.0);
+    assert_eq!(trace_events[2]["dur"], 1.0e6);
+    assert_eq!(trace_events[4]["tid"], 2);
+    assert_eq!(trace_events[4]["cat"], "external");
+    assert_eq!(trace_events[4]["dur"], 1.0e6);
+    // The open spans end at the current simulation time
+    assert_eq!(trace_events[7]["dur"], 0.0);
+    let otlp = simulation.otlp_trace(0xabc)?;
+    let spans = otlp["resourceSpans"][0]["scopeSpans"][0]["spans"]
+        .as_array()
+        .unwrap();
+    assert_eq!(spans.len(), events.len());
+    assert_eq!(spans[1]["traceId"], "00000000000000000000000000000abc");
+    assert_eq!(spans[1]["spanId"], "0000000000000002");
+    assert_eq!(spans[1]["startTimeUnixNano"], "1000000000");
+    assert_eq!(spans[1]["endTimeUnixNano"], "2000000000");
+    assert_eq!(
+        spans[1]["attributes"][0]["value"]["stringValue"],
+        "generator-01"
+    );
+    // Resets clear the trace
+    simulation.reset();
+    assert!(simulation.get_trace()?.events().is_empty());
+    simulation.disable_tracing();
+    assert!(simulation.chrome_trace().is_err());
+    Ok(())
+}
+
+#[test]
+fn templates_expand_parameterized_sub_networks() -> Result<(), SimulationError> {
+    let entries: Vec<ModelEntry> = sim::utils::yaml::from_str(
+        r#"
+- type: "Sink"
+  id: "sink-01"
+  window: 10.0
+  portsIn:
+    job: "job"
+- template:
+    idPrefix: "station"
+    parameters:
+      serviceTime: 2.0
+    models:
+      - type: "Generator"
+        id: "generator-01"
+        portsIn: {}
+        portsOut:
+          job: "job"
+        messageInterdepartureTime:
+          constant:
+            value: 1.0
+      - type: "Processor"
+        id: "processor-01"
+        serviceTime:
+          constant:
+            value: "{{serviceTime}}"
+        portsIn:
+          job: "job"
+        portsOut:
+          job: "processed"
+    connectors:
+      - id: "arrivals"
+        sourceID: "generator-01"
+        targetID: "processor-01"
+        sourcePort: "job"
+        targetPort: "job"
+      - id: "departures-{{index}}"
+        sourceID: "processor-01"
+        targetID: "sink-01"
+        sourcePort: "processed"
+        targetPort: "job"
+  count: 2
+  parameters:
+    - {}
+    - serviceTime: 0.5
+"#,
+    )?;
+    let (models, connectors) = split_entries(entries);
+    let model_ids: Vec<&str> = models.iter().map(|model| model.id()).collect();
+    assert_eq![
+        model_ids,
+        [
+            "sink-01",
+            "station-01-generator-01",
+            "station-01-processor-01",
+            "station-02-generator-01",
+            "station-02-processor-01",
+        ]
+    ];
+    let departures = connectors
+        .iter()
+        .find(|connector| connector.id() == "station-02-departures-2")
+        .unwrap();
+    assert_eq!(departures.source_id(), "station-02-processor-01");
+    assert_eq!(departures.target_id(), "sink-01");
+    let mut simulation = Simulation::post(models, connectors);
+    let messages = simulation.step_until(10.0)?;
+    let departures = |processor_id: &str| {
+        messages
+            .iter()
+            .filter(|message| {
+                message.source_id() == processor_id && message.target_id() == "sink-01"
+            })
+            .count()
+    };
+    // The second station's service time override outpaces the first station
+    assert_eq!(departures("station-01-processor-01"), 4);
+    assert_eq!(departures("station-02-processor-01"), 9);
+
+    // Instantiating more stations adds uniquely prefixed models, connected
+    // to the shared sink
+    let template = ModelTemplate::from_models(
+        "overflow",
+        &[Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        )],
+        &[Connector::new(
+            String::from("departures"),
+            String::from("processor-01"),
+            String::from("sink-01"),
+            String::from("processed"),
+            String::from("job"),
+        )],
+    )?;
+    assert_eq![
+        simulation.instantiate_template(&template, 2)?,
+        ["overflow-01-processor-01", "overflow-02-processor-01"]
+    ];
+    assert!(matches!(
+        simulation.instantiate_template(&template, 1),
+        Err(SimulationError::DuplicateModelId(_))
+    ));
+    assert!(matches!(
+        simulation.instantiate_template(
+            &ModelTemplate {
+                id_prefix: String::from("orphan"),
+                models: Vec::new(),
+                ..template.clone()
+            },
+            1
+        ),
+        Err(SimulationError::DanglingConnector { .. })
+    ));
+    let mut unknown = template.clone();
+    unknown.id_prefix = String::from("unknown");
+    unknown.models[0]["serviceTime"] = serde_json::json!({
+        "constant": { "value": "{{undefined}}" }
+    });
+    assert!(matches!(
+        simulation.instantiate_template(&unknown, 1),
+        Err(SimulationError::UnknownTemplateParameter(parameter)) if parameter == "undefined"
+    ));
+    Ok(())
+}
+
+#[test]
+fn future_event_list_stop_conditions_observe_current_model_states() -> Result<(), SimulationError> {
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 1.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant { value: 2.5 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ),
+    ];
+    let connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    let observed_next_events = |scheduling: EventScheduling| {
+        let mut simulation = Simulation::post(models.clone(), connectors.clone());
+        simulation.set_event_scheduling(scheduling);
+        let mut next_event_times = Vec::new();
+        simulation.step_until_condition(|simulation| {
+            // The processor's time advance is only current once synchronized
+            next_event_times.push(simulation.next_event_time());
+            simulation.get_global_time() >= 10.0
+        })?;
+        Ok::<Vec<f64>, SimulationError>(next_event_times)
+    };
+    assert_eq!(
+        observed_next_events(EventScheduling::FutureEventList)?,
+        observed_next_events(EventScheduling::Scan)?
+    );
+    Ok(())
+}
+
+#[test]
+fn future_event_list_delivers_to_the_indexed_model() -> Result<(), SimulationError> {
+    let processor = |service_time: f64| {
+        Model::new(
+            String::from("processor-01"),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Constant {
+                    value: service_time,
+                },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        )
+    };
+    let models = vec![
+        Model::new(
+            String::from("generator-01"),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Constant { value: 4.0 },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ),
+        // A duplicate model ID, which messages resolve to the first model
+        processor(1.0),
+        processor(3.0),
+        Model::new(
+            String::from("sink-01"),
+            Box::new(Sink::new(String::from("job"), 10.0, false)),
+        ),
+    ];
+    let mut connectors = topology::pipeline(&["generator-01", "processor-01"], "job", "job");
+    connectors.extend(topology::pipeline(
+        &["processor-01", "sink-01"],
+        "processed",
+        "job",
+    ));
+    let departures = |scheduling: EventScheduling| {
+        let mut simulation = Simulation::post(models.clone(), connectors.clone());
+        simulation.set_event_scheduling(scheduling);
+        Ok::<Vec<f64>, SimulationError>(
+            simulation
+                .step_until(20.0)?
+                .iter()
+                .filter(|message| message.source_id() == "processor-01")
+                .map(|message| *message.time())
+                .collect(),
+        )
+    };
+    let scanned = departures(EventScheduling::Scan)?;
+    assert_eq!(scanned, [5.0, 9.0, 13.0, 17.0]);
+    assert_eq!(departures(EventScheduling::FutureEventList)?, scanned);
+    Ok(())
+}
+
+#[test]
+fn future_event_list_scheduling_matches_scanning() -> Result<(), SimulationError> {
+    let mut models = Vec::new();
+    let mut connectors = Vec::new();
+    // Rates with exact binary floating point interdeparture times, so both
+    // schedulers compute identical simulation times
+    [0.5, 1.0, 2.0, 4.0]
+        .iter()
+        .enumerate()
+        .for_each(|(line, lambda)| {
+            let generator_id = format!["generator-{:02}", line];
+            let processor_id = format!["processor-{:02}", line];
+            models.push(Model::new(
+                generator_id.clone(),
+                Box::new(Generator::new(
+                    ContinuousRandomVariable::Exp { lambda: *lambda },
+                    None,
+                    String::from("job"),
+                    false,
+                    None,
+                )),
+            ));
+            models.push(Model::new(
+                processor_id.clone(),
+                Box::new(Processor::new(
+                    ContinuousRandomVariable::Exp { lambda: 4.0 },
+                    Some(4),
+                    String::from("job"),
+                    String::from("processed"),
+                    true,
+                    None,
+                )),
+            ));
+            connectors.extend(topology::pipeline(
+                &[&generator_id, &processor_id],
+                "job",
+                "job",
+            ));
+            connectors.extend(topology::gather(
+                &[&processor_id],
+                "sink-01",
+                "processed",
+                "job",
+            ));
+        });
+    (0..50).for_each(|index| {
+        models.push(Model::new(
+            format!["idle-{:02}", index],
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 4.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                true,
+                None,
+            )),
+        ))
+    });
+    models.push(Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("job"), 10.0, true)),
+    ));
+    let run = |scheduling: EventScheduling| -> Result<Vec<String>, SimulationError> {
+        let mut simulation = Simulation::post(models.clone(), connectors.clone());
+        simulation.enable_deterministic_mode();
+        simulation.set_event_scheduling(scheduling);
+        // An idle model is woken mid-run by a scheduled injection
+        simulation.inject_input_at(
+            Message::new(
+                String::from("manual"),
+                String::from("manual"),
+                String::from("idle-07"),
+                String::from("job"),
+                0.0,
+                String::from("injected job"),
+            ),
+            10.25,
+        )?;
+        let mut outputs = vec![serde_json::to_string(&simulation.step_until(20.0)?)?];
+        // Single steps synchronize the idle models
+        outputs.push(serde_json::to_string(&simulation.step()?)?);
+        outputs.push(serde_json::to_string(&simulation.models())?);
+        outputs.push(serde_json::to_string(&simulation.step_n(25)?)?);
+        outputs.push(serde_json::to_string(&simulation.models())?);
+        outputs.push(serde_json::to_string(
+            &simulation.execution_stats().queue_watermarks,
+        )?);
+        Ok(outputs)
+    };
+    let scanned = run(EventScheduling::Scan)?;
+    assert!(scanned[0].contains("idle-07"));
+    assert_eq!(run(EventScheduling::FutureEventList)?, scanned);
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_steps_match_serial_steps() -> Result<(), SimulationError> {
+    let lines = 8;
+    let mut models = Vec::new();
+    let mut connectors = Vec::new();
+    (0..lines).for_each(|line| {
+        let generator_id = format!["generator-{:02}", line];
+        let processor_id = format!["processor-{:02}", line];
+        models.push(Model::new(
+            generator_id.clone(),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp {
+                    lambda: 0.1 * (line + 1) as f64,
+                },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ));
+        models.push(Model::new(
+            processor_id.clone(),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ));
+        connectors.extend(topology::pipeline(
+            &[&generator_id, &processor_id],
+            "job",
+            "job",
+        ));
+        connectors.extend(topology::gather(
+            &[&processor_id],
+            "sink-01",
+            "processed",
+            "job",
+        ));
+    });
+    models.push(Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("job"), 10.0, false)),
+    ));
+    let run = |parallel: bool| -> Result<(String, Option<usize>), SimulationError> {
+        let mut simulation = Simulation::post(models.clone(), connectors.clone());
+        simulation.enable_deterministic_mode();
+        simulation.set_parallel(parallel);
+        let messages = simulation.step_until(50.0)?;
+        Ok((
+            serde_json::to_string(&messages)?,
+            simulation
+                .get_sink_summary("sink-01")?
+                .map(|summary| summary.count),
+        ))
+    };
+    let serial = run(false)?;
+    assert!(serial.1.is_some_and(|count| count > 100));
+    assert_eq!(run(true)?, serial);
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_stochastic_steps_match_serial_per_model_streams() -> Result<(), SimulationError> {
+    let lines = 8;
+    let mut models = Vec::new();
+    let mut connectors = Vec::new();
+    (0..lines).for_each(|line| {
+        let generator_id = format!["generator-{:02}", line];
+        let processor_id = format!["processor-{:02}", line];
+        models.push(Model::new(
+            generator_id.clone(),
+            Box::new(Generator::new(
+                ContinuousRandomVariable::Exp {
+                    lambda: 0.1 * (line + 1) as f64,
+                },
+                None,
+                String::from("job"),
+                false,
+                None,
+            )),
+        ));
+        models.push(Model::new(
+            processor_id.clone(),
+            Box::new(Processor::new(
+                ContinuousRandomVariable::Exp { lambda: 1.0 },
+                None,
+                String::from("job"),
+                String::from("processed"),
+                false,
+                None,
+            )),
+        ));
+        connectors.extend(topology::pipeline(
+            &[&generator_id, &processor_id],
+            "job",
+            "job",
+        ));
+        connectors.extend(topology::gather(
+            &[&processor_id],
+            "sink-01",
+            "processed",
+            "job",
+        ));
+    });
+    models.push(Model::new(
+        String::from("sink-01"),
+        Box::new(Sink::new(String::from("job"), 10.0, true)),
+    ));
+    let run = |parallel: bool| -> Result<(String, String), SimulationError> {
+        let mut simulation = Simulation::post(models.clone(), connectors.clone());
+        // Parallel steps draw from per-model streams under any assignment
+        if !parallel {
+            simulation.set_rng_streams(RngStreams::PerModel);
+        }
+        simulation.set_parallel(parallel);
+        // Initial jobs across the processors, with odd lines prioritized
+        (0..lines).rev().for_each(|line| {
+            simulation.inject_input(
+                Message::new(
+                    String::from("manual"),
+                    String::from("manual"),
+                    format!["processor-{:02}", line],
+                    String::from("job"),
+                    0.0,
+                    format!["initial job {}", line],
+                )
+                .with_priority(line % 2),
+            )
+        });
+        let messages = simulation.step_until(50.0)?;
+        Ok((
+            serde_json::to_string(&messages)?,
+            serde_json::to_string(simulation.get_records("sink-01")?)?,
+        ))
+    };
+    let serial = run(false)?;
+    assert!(serial.1.matches("job").count() > 100);
+    assert_eq!(run(true)?, serial);
+    assert_eq!(run(true)?, run(true)?);
+    Ok(())
+}
@@ -1,13 +1,14 @@
 use std::collections::HashMap;
-use std::f64::INFINITY;
 
-use sim::models::{Model, ModelRecord};
+use sim::models::{Model, ModelRecord, SinkSummary};
 use sim::output_analysis::IndependentSample;
 use sim::simulator::{Connector, Message, WebSimulation};
 use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
 
 wasm_bindgen_test_configure!(run_in_browser);
 
+type JobTimestamps = (Option<f64>, Option<f64>, Option<f64>);
+
 #[test]
 #[wasm_bindgen_test]
 fn processor_from_queue_response_time_is_correct() {
@@ -85,7 +86,7 @@ fn processor_from_queue_response_time_is_correct() {
             match expected_output {
                 None => {
                     assert![messages_set.is_empty()];
-                    INFINITY
+                    f64::INFINITY
                 }
                 Some(output) => {
                     let first_message = messages_set.first().unwrap();
@@ -571,8 +572,7 @@ fn ci_half_width_for_average_waiting_time() {
                 let records: Vec<ModelRecord> =
                     serde_json::from_str(&web.get_records_json(processor)).unwrap();
                 // Times: Arrival, Processing Start, Departure
-                let mut job_timestamps: HashMap<String, (Option<f64>, Option<f64>, Option<f64>)> =
-                    HashMap::new();
+                let mut job_timestamps: HashMap<String, JobTimestamps> = HashMap::new();
                 records.iter().for_each(|record| {
                     let job = job_timestamps
                         .entry(record.subject.clone())
@@ -615,3 +615,306 @@ fn ci_half_width_for_average_waiting_time() {
         }
     }
 }
+
+#[test]
+#[wasm_bindgen_test]
+fn sink_summary_reports_running_statistics() {
+    let models = r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+- type: "Sink"
+  id: "sink-01"
+  window: 4.5
+  portsIn:
+    job: "job"
+"#;
+    let connectors = r#"
+- id: "connector-01"
+  sourceID: "generator-01"
+  targetID: "sink-01"
+  sourcePort: "job"
+  targetPort: "job"
+"#;
+    let mut web = WebSimulation::post_yaml(models, connectors);
+    // Generation every 1.0 time units, with distribution means
+    web.enable_deterministic_mode();
+    web.step_until_json(10.5);
+    let summary: SinkSummary = serde_json::from_str(&web.get_sink_summary_json("sink-01")).unwrap();
+    assert_eq!(summary.count, 10);
+    assert_eq!(summary.first_arrival, Some(1.0));
+    assert_eq!(summary.last_arrival, Some(10.0));
+    assert_eq!(summary.mean_interarrival, Some(1.0));
+    assert_eq!(summary.interarrival_std_dev, Some(0.0));
+    // The simulation stops at the first event beyond 10.5, and the
+    // arrivals at 7.0 through 10.0 are within the window
+    assert_eq!(summary.time, 11.0);
+    assert_eq!(summary.window_count, 4);
+    assert!((summary.window_throughput - 4.0 / 4.5).abs() < 1.0e-9);
+    assert_eq!(web.get_sink_summary_json("generator-01"), "null");
+}
+
+#[test]
+#[wasm_bindgen_test]
+fn yaml_anchors_and_merge_keys_configure_similar_models() {
+    let models = r#"
+- &generator
+  type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+- <<: *generator
+  id: "generator-02"
+  messageInterdepartureTime:
+    exp:
+      lambda: 0.5
+- &sink
+  type: "Sink"
+  id: "sink-01"
+  window: 5.0
+  portsIn:
+    job: "job"
+- <<: *sink
+  id: "sink-02"
+"#;
+    let connectors = r#"
+- &connector
+  id: "connector-01"
+  sourceID: "generator-01"
+  targetID: "sink-01"
+  sourcePort: "job"
+  targetPort: "job"
+- <<: *connector
+  id: "connector-02"
+  sourceID: "generator-02"
+  targetID: "sink-02"
+"#;
+    let mut web = WebSimulation::post_yaml(models, connectors);
+    web.enable_deterministic_mode();
+    web.step_until_json(10.5);
+    let counts: Vec<usize> = ["sink-01", "sink-02"]
+        .iter()
+        .map(|sink_id| {
+            serde_json::from_str::<SinkSummary>(&web.get_sink_summary_json(sink_id))
+                .unwrap()
+                .count
+        })
+        .collect();
+    assert_eq!(counts, [10, 5]);
+    assert!(!web.get_json().contains("<<"));
+}
+
+#[test]
+#[wasm_bindgen_test]
+fn parameters_are_tuned_mid_run() {
+    let models = r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+- type: "Sink"
+  id: "sink-01"
+  window: 4.5
+  portsIn:
+    job: "job"
+"#;
+    let connectors = r#"
+- id: "connector-01"
+  sourceID: "generator-01"
+  targetID: "sink-01"
+  sourcePort: "job"
+  targetPort: "job"
+"#;
+    let mut web = WebSimulation::post_yaml(models, connectors);
+    web.enable_deterministic_mode();
+    web.step_until_json(5.5);
+    // Halve the arrival rate, keeping the pending generation at 7.0
+    web.set_parameter(
+        "generator-01",
+        "/messageInterdepartureTime/exp/lambda",
+        "0.5",
+    );
+    assert!(web.get_json().contains("0.5"));
+    web.step_until_json(15.5);
+    let summary: SinkSummary = serde_json::from_str(&web.get_sink_summary_json("sink-01")).unwrap();
+    // Arrivals at 1.0 through 7.0, and then at 9.0 through 15.0
+    assert_eq!(summary.count, 11);
+    assert_eq!(summary.last_arrival, Some(15.0));
+}
+
+#[test]
+#[wasm_bindgen_test]
+fn exported_times_are_rounded_to_the_time_precision() {
+    let models = r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 3.0
+- type: "Sink"
+  id: "sink-01"
+  window: 10.0
+  portsIn:
+    job: "job"
+"#;
+    let connectors = r#"
+- id: "connector-01"
+  sourceID: "generator-01"
+  targetID: "sink-01"
+  sourcePort: "job"
+  targetPort: "job"
+"#;
+    let times = |messages: &str| -> Vec<f64> {
+        serde_json::from_str::<Vec<Message>>(messages)
+            .unwrap()
+            .iter()
+            .map(|message| *message.time())
+            .collect()
+    };
+    let mut web = WebSimulation::post_yaml(models, connectors);
+    web.enable_deterministic_mode();
+    web.set_time_precision(3, "round");
+    assert_eq!(times(&web.step_until_json(1.5)), [0.333, 0.667, 1.0, 1.333]);
+    // The accumulated time of the next job is slightly below 2.0
+    web.set_time_precision(3, "truncate");
+    assert_eq!(times(&web.step_n_json(2)), [1.999]);
+    // Execution keeps the full precision times
+    assert!(web.get_global_time() > 1.999);
+    web.set_time_precision(3, "round");
+    assert_eq!(times(&web.step_n_json(2)), [2.333]);
+    web.clear_time_precision();
+    let full_precision = times(&web.step_n_json(2));
+    assert!((full_precision[0] - 8.0 / 3.0).abs() < 1e-9);
+    assert_ne!(full_precision[0], 2.667);
+}
+
+#[test]
+#[wasm_bindgen_test]
+fn seeded_web_simulations_are_reproducible() {
+    let models = r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+- type: "Sink"
+  id: "sink-01"
+  window: 10.0
+  portsIn:
+    job: "job"
+"#;
+    let connectors = r#"
+- id: "connector-01"
+  sourceID: "generator-01"
+  targetID: "sink-01"
+  sourcePort: "job"
+  targetPort: "job"
+"#;
+    let mut seeded = WebSimulation::post_yaml_with_seed(models, connectors, 11);
+    let replication = seeded.step_until_json(10.0);
+    let mut reseeded = WebSimulation::post_yaml(models, connectors);
+    reseeded.set_seed(11);
+    assert_eq!(reseeded.step_until_json(10.0), replication);
+    let mut other = WebSimulation::post_yaml_with_seed(models, connectors, 12);
+    assert_ne!(other.step_until_json(10.0), replication);
+}
+
+#[test]
+#[wasm_bindgen_test]
+fn restored_web_checkpoints_resume_exactly() {
+    let models = r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+- type: "Sink"
+  id: "sink-01"
+  window: 10.0
+  portsIn:
+    job: "job"
+"#;
+    let connectors = r#"
+- id: "connector-01"
+  sourceID: "generator-01"
+  targetID: "sink-01"
+  sourcePort: "job"
+  targetPort: "job"
+"#;
+    let mut simulation = WebSimulation::post_yaml_with_seed(models, connectors, 11);
+    simulation.step_until_json(10.0);
+    let mut restored = WebSimulation::restore(&simulation.checkpoint());
+    assert_eq!(restored.get_global_time(), simulation.get_global_time());
+    assert_eq!(
+        restored.step_until_json(20.0),
+        simulation.step_until_json(20.0)
+    );
+}
+
+#[test]
+fn typescript_definitions_cover_the_json_fields() {
+    let definitions = include_str!("../src/simulator/web.d.ts");
+    let models = r#"
+- type: "Generator"
+  id: "generator-01"
+  portsIn: {}
+  portsOut:
+    job: "job"
+  messageInterdepartureTime:
+    exp:
+      lambda: 1.0
+- type: "Sink"
+  id: "sink-01"
+  window: 10.0
+  portsIn:
+    job: "job"
+"#;
+    let connectors = r#"
+- id: "connector-01"
+  sourceID: "generator-01"
+  targetID: "sink-01"
+  sourcePort: "job"
+  targetPort: "job"
+"#;
+    let mut web = WebSimulation::post_yaml(models, connectors);
+    let messages: Vec<serde_json::Value> = serde_json::from_str(&web.step_until_json(5.0)).unwrap();
+    let summary: serde_json::Value =
+        serde_json::from_str(&web.get_sink_summary_json("sink-01")).unwrap();
+    let build_info: serde_json::Value =
+        serde_json::from_str(&sim::build_info::build_info_json()).unwrap();
+    messages
+        .iter()
+        .chain([summary, build_info].iter())
+        .filter_map(|value| value.as_object())
+        .flat_map(|fields| fields.keys())
+        .for_each(|field| {
+            assert!(
+                definitions.contains(&format!["{}: ", field])
+                    || definitions.contains(&format!["{}?: ", field]),
+                "{} is missing from the TypeScript definitions",
+                field
+            )
+        });
+}
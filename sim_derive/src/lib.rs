@@ -38,7 +38,7 @@ pub fn register(item: TokenStream) -> TokenStream {
         sim::models::model_factory::register(
             stringify!(#name),
             #name::from_value as sim::models::model_factory::ModelConstructor
-        );
+        )
     };
     tokens.into()
 }
@@ -0,0 +1,757 @@
+//! Sim Node provides Node.js N-API bindings for the Sim discrete event
+//! simulation package, as an alternative to the WASM package for
+//! server-side Node.js users.  The bindings expose the same `Simulation`
+//! surface as the WASM `WebSimulation`, but message batches are transferred
+//! as JSON-encoded Node.js `Buffer`s.  The buffers are handed to Node.js
+//! without copying, avoiding the string marshalling overhead of WASM for
+//! high-volume runs.
+//!
+//! Errors are thrown as JavaScript exceptions, instead of panicking.
+
+use std::fmt::Display;
+
+use napi::bindgen_prelude::{BigInt, Buffer};
+use napi::{Error, Result};
+use napi_derive::napi;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use sim::models::ModelRecord;
+use sim::simulator::{
+    BlackboardValue, Checkpoint, InitialCondition, Message, MessageFilter,
+    Simulation as CoreSimulation, StopCondition, TimePrecision, TimeRounding, ValidationOptions,
+};
+use sim::utils::errors::SimulationError;
+
+fn js_error<E: Display>(error: E) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    serde_json::from_str(json).map_err(js_error)
+}
+
+/// Parse a unit enum variant (e.g. a verbosity) from its JSON name.
+fn from_name<T: DeserializeOwned>(name: &str) -> Result<T> {
+    serde_json::from_value(name.into()).map_err(js_error)
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(js_error)
+}
+
+fn to_buffer<T: Serialize + ?Sized>(value: &T) -> Result<Buffer> {
+    Ok(serde_json::to_vec(value).map_err(js_error)?.into())
+}
+
+/// The Node.js `Simulation` wraps the core `Simulation` struct.  For
+/// additional insight on these methods, refer to the associated core
+/// `Simulation` methods.  An optional time precision applies to the
+/// exported message and record times.
+#[napi]
+#[derive(Default)]
+pub struct Simulation {
+    simulation: CoreSimulation,
+    time_precision: Option<TimePrecision>,
+}
+
+impl Simulation {
+    fn export_messages<'a>(
+        &self,
+        messages: impl IntoIterator<Item = &'a Message>,
+    ) -> Result<Buffer> {
+        match &self.time_precision {
+            Some(precision) => to_buffer(&precision.messages(messages)),
+            None => to_buffer(&messages.into_iter().collect::<Vec<_>>()),
+        }
+    }
+
+    fn export_records(&self, records: &[ModelRecord]) -> Result<Buffer> {
+        match &self.time_precision {
+            Some(precision) => to_buffer(&precision.records(records)),
+            None => to_buffer(records),
+        }
+    }
+}
+
+#[napi]
+impl Simulation {
+    /// A Node.js interface for `Simulation.post`, which uses JSON
+    /// representations of the simulation models and connectors.
+    #[napi(factory)]
+    pub fn post_json(models: String, connectors: String) -> Result<Self> {
+        Ok(Self {
+            simulation: CoreSimulation::post(from_json(&models)?, from_json(&connectors)?),
+            time_precision: None,
+        })
+    }
+
+    /// A Node.js interface for `Simulation.post_with_seed`, which uses JSON
+    /// representations of the simulation models and connectors.
+    #[napi(factory)]
+    pub fn post_json_with_seed(models: String, connectors: String, seed: BigInt) -> Result<Self> {
+        Ok(Self {
+            simulation: CoreSimulation::post_with_seed(
+                from_json(&models)?,
+                from_json(&connectors)?,
+                seed.get_u64().1,
+            ),
+            time_precision: None,
+        })
+    }
+
+    /// A Node.js interface for `Simulation.put`, which uses JSON
+    /// representations of the simulation models and connectors.
+    #[napi]
+    pub fn put_json(&mut self, models: String, connectors: String) -> Result<()> {
+        self.simulation
+            .put(from_json(&models)?, from_json(&connectors)?);
+        Ok(())
+    }
+
+    /// Get a JSON representation of the full `Simulation` configuration.
+    #[napi]
+    pub fn get_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&self.simulation).map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.checkpoint`, which returns the
+    /// checkpoint in the portable checkpoint format, as a buffer.
+    #[napi]
+    pub fn checkpoint(&self) -> Result<Buffer> {
+        let checkpoint = self.simulation.checkpoint().map_err(js_error)?;
+        Ok(checkpoint.to_bytes().map_err(js_error)?.into())
+    }
+
+    /// A Node.js interface for `Simulation.restore`, which accepts a
+    /// checkpoint in the portable checkpoint format, as a buffer.
+    #[napi(factory)]
+    pub fn restore(checkpoint: Buffer) -> Result<Self> {
+        let checkpoint = Checkpoint::from_bytes(&checkpoint).map_err(js_error)?;
+        Ok(Self {
+            simulation: CoreSimulation::restore(&checkpoint).map_err(js_error)?,
+            time_precision: None,
+        })
+    }
+
+    /// A Node.js interface for `Simulation.get_messages`, which provides the
+    /// messages as a JSON-encoded buffer.
+    #[napi]
+    pub fn get_messages(&self) -> Result<Buffer> {
+        self.export_messages(self.simulation.get_messages())
+    }
+
+    /// An interface to `Simulation.get_global_time`.
+    #[napi]
+    pub fn get_global_time(&self) -> f64 {
+        self.simulation.get_global_time()
+    }
+
+    /// An interface to `Simulation.get_status`.
+    #[napi]
+    pub fn get_status(&self, model_id: String) -> Result<String> {
+        self.simulation.get_status(&model_id).map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.get_records`, which provides the
+    /// records as a JSON-encoded buffer.
+    #[napi]
+    pub fn get_records(&self, model_id: String) -> Result<Buffer> {
+        self.export_records(self.simulation.get_records(&model_id).map_err(js_error)?)
+    }
+
+    /// A Node.js interface for `Simulation.get_port_stats`, which converts
+    /// the port statistics to a JSON string (`null` for models without port
+    /// statistics).
+    #[napi]
+    pub fn get_port_stats_json(&self, model_id: String) -> Result<String> {
+        to_json(
+            &self
+                .simulation
+                .get_port_stats(&model_id)
+                .map_err(js_error)?,
+        )
+    }
+
+    /// A Node.js interface for `Simulation.get_sink_summary`, which converts
+    /// the sink summary to a JSON string (`null` for models other than
+    /// sinks).
+    #[napi]
+    pub fn get_sink_summary_json(&self, model_id: String) -> Result<String> {
+        to_json(
+            &self
+                .simulation
+                .get_sink_summary(&model_id)
+                .map_err(js_error)?,
+        )
+    }
+
+    /// An interface to `Simulation.set_queue_depth_threshold`.
+    #[napi]
+    pub fn set_queue_depth_threshold(&mut self, threshold: Option<u32>) {
+        self.simulation
+            .set_queue_depth_threshold(threshold.map(|threshold| threshold as usize));
+    }
+
+    /// A Node.js interface for `Simulation.execution_stats`, which converts
+    /// the execution statistics to a JSON string.
+    #[napi]
+    pub fn get_execution_stats_json(&self) -> Result<String> {
+        to_json(self.simulation.execution_stats())
+    }
+
+    /// A Node.js interface for `Simulation.event_density`, which uses a
+    /// JSON representation of the report.
+    #[napi]
+    pub fn get_event_density_json(&self, bucket_width: f64) -> Result<String> {
+        to_json(
+            &self
+                .simulation
+                .event_density(bucket_width)
+                .map_err(js_error)?,
+        )
+    }
+
+    /// A Node.js interface for `Simulation.set_energy_coefficients`, which
+    /// accepts the coefficients as a JSON string.
+    #[napi]
+    pub fn set_energy_coefficients_json(
+        &mut self,
+        model_id: String,
+        coefficients: String,
+    ) -> Result<()> {
+        self.simulation
+            .set_energy_coefficients(&model_id, from_json(&coefficients)?)
+            .map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.energy_report`, which uses a
+    /// JSON representation of the report.
+    #[napi]
+    pub fn get_energy_report_json(&self, bucket_width: f64) -> Result<String> {
+        to_json(
+            &self
+                .simulation
+                .energy_report(bucket_width)
+                .map_err(js_error)?,
+        )
+    }
+
+    /// A Node.js interface for `Simulation.check_assertions`, which uses a
+    /// JSON representation of the assertion report.
+    #[napi]
+    pub fn check_assertions_json(&self) -> Result<String> {
+        to_json(&self.simulation.check_assertions().map_err(js_error)?)
+    }
+
+    /// A Node.js interface for `Simulation.manifest`, which converts the
+    /// reproducibility manifest to a JSON string.
+    #[napi]
+    pub fn get_manifest_json(&self) -> Result<String> {
+        to_json(&self.simulation.manifest().map_err(js_error)?)
+    }
+
+    /// A Node.js interface for `Simulation.randomize_initial_state`, which
+    /// accepts the initial conditions as a JSON string.
+    #[napi]
+    pub fn randomize_initial_state_json(&mut self, conditions: String) -> Result<()> {
+        let conditions: Vec<InitialCondition> = from_json(&conditions)?;
+        self.simulation
+            .randomize_initial_state(&conditions)
+            .map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.summary`, which converts the
+    /// summary to a human-readable string.
+    #[napi]
+    pub fn get_summary(&self) -> String {
+        self.simulation.summary().to_string()
+    }
+
+    /// A Node.js interface for `Simulation.summary`, which converts the
+    /// summary to a JSON string.
+    #[napi]
+    pub fn get_summary_json(&self) -> Result<String> {
+        to_json(&self.simulation.summary())
+    }
+
+    /// A Node.js interface for `Simulation.analyze_topology`, which converts
+    /// the topology report to a JSON string.
+    #[napi]
+    pub fn analyze_topology_json(&self) -> Result<String> {
+        to_json(&self.simulation.analyze_topology())
+    }
+
+    /// A Node.js interface for `Simulation.validate_with`, which converts
+    /// the validation report (optionally with the assumptions report) to a
+    /// JSON string.
+    #[napi]
+    pub fn validate_json(&self, assumptions: bool) -> Result<String> {
+        to_json(
+            &self
+                .simulation
+                .validate_with(&ValidationOptions { assumptions }),
+        )
+    }
+
+    /// A Node.js interface for `Simulation.dry_run`, which converts the dry
+    /// run report to a JSON string.
+    #[napi]
+    pub fn dry_run_json(&self, duration_estimate: f64) -> Result<String> {
+        to_json(
+            &self
+                .simulation
+                .dry_run(duration_estimate)
+                .map_err(js_error)?,
+        )
+    }
+
+    /// A Node.js interface for `Simulation.get_audit_log`, which converts
+    /// the audit records to a JSON string.
+    #[napi]
+    pub fn get_audit_log_json(&self) -> Result<String> {
+        to_json(&self.simulation.get_audit_log().map_err(js_error)?)
+    }
+
+    /// An interface to `Simulation.enable_audit_log`.
+    #[napi]
+    pub fn enable_audit_log(&mut self, capacity: u32) {
+        self.simulation.enable_audit_log(capacity as usize);
+    }
+
+    /// An interface to `Simulation.disable_audit_log`.
+    #[napi]
+    pub fn disable_audit_log(&mut self) {
+        self.simulation.disable_audit_log();
+    }
+
+    /// An interface to `Simulation.enable_history`.
+    #[napi]
+    pub fn enable_history(&mut self, checkpoint_interval: u32) -> Result<()> {
+        self.simulation
+            .enable_history(checkpoint_interval as usize)
+            .map_err(js_error)
+    }
+
+    /// An interface to `Simulation.disable_history`.
+    #[napi]
+    pub fn disable_history(&mut self) {
+        self.simulation.disable_history();
+    }
+
+    /// A Node.js interface for `Simulation.messages_between`, which provides
+    /// the messages as a JSON-encoded buffer.
+    #[napi]
+    pub fn messages_between(&self, start: f64, end: f64) -> Result<Buffer> {
+        self.export_messages(
+            self.simulation
+                .messages_between(start, end)
+                .map_err(js_error)?,
+        )
+    }
+
+    /// A Node.js interface for `Simulation.messages_for_model`, which
+    /// provides the messages as a JSON-encoded buffer.
+    #[napi]
+    pub fn messages_for_model(&self, model_id: String) -> Result<Buffer> {
+        self.export_messages(
+            self.simulation
+                .messages_for_model(&model_id)
+                .map_err(js_error)?,
+        )
+    }
+
+    /// An interface to `Simulation.enable_message_history`.
+    #[napi]
+    pub fn enable_message_history(&mut self) {
+        self.simulation.enable_message_history();
+    }
+
+    /// An interface to `Simulation.disable_message_history`.
+    #[napi]
+    pub fn disable_message_history(&mut self) {
+        self.simulation.disable_message_history();
+    }
+
+    /// A Node.js interface for `Simulation.get_message_history`, which
+    /// provides the messages as a JSON-encoded buffer.
+    #[napi]
+    pub fn get_message_history(&self) -> Result<Buffer> {
+        self.export_messages(self.simulation.get_message_history().map_err(js_error)?)
+    }
+
+    /// A Node.js interface for `Simulation.query_messages`, which uses a
+    /// JSON representation of the filter (e.g. `{"targetId": "sink-01",
+    /// "start": 10.0}`), and provides the matching messages as a
+    /// JSON-encoded buffer.
+    #[napi]
+    pub fn query_messages(&self, filter: String) -> Result<Buffer> {
+        let filter: MessageFilter = from_json(&filter)?;
+        self.export_messages(self.simulation.query_messages(&filter).map_err(js_error)?)
+    }
+
+    /// An interface to `Simulation.enable_tracing`.
+    #[napi]
+    pub fn enable_tracing(&mut self) {
+        self.simulation.enable_tracing();
+    }
+
+    /// An interface to `Simulation.disable_tracing`.
+    #[napi]
+    pub fn disable_tracing(&mut self) {
+        self.simulation.disable_tracing();
+    }
+
+    /// A Node.js interface for `Simulation.get_trace`, which provides the
+    /// traced transitions as a JSON-encoded buffer.
+    #[napi]
+    pub fn get_trace(&self) -> Result<Buffer> {
+        to_buffer(self.simulation.get_trace().map_err(js_error)?.events())
+    }
+
+    /// A Node.js interface for `Simulation.chrome_trace`, which converts
+    /// the Chrome `trace_event` document to a JSON string.
+    #[napi]
+    pub fn chrome_trace_json(&self) -> Result<String> {
+        to_json(&self.simulation.chrome_trace().map_err(js_error)?)
+    }
+
+    /// A Node.js interface for `Simulation.otlp_trace`, which accepts the
+    /// trace ID as a hexadecimal string (of up to 32 digits), and converts
+    /// the OTLP/JSON export request to a JSON string.
+    #[napi]
+    pub fn otlp_trace_json(&self, trace_id: String) -> Result<String> {
+        let trace_id = u128::from_str_radix(&trace_id, 16).map_err(js_error)?;
+        to_json(&self.simulation.otlp_trace(trace_id).map_err(js_error)?)
+    }
+
+    /// A Node.js interface for `Simulation.state_at`, which converts the
+    /// models to a JSON string.
+    #[napi]
+    pub fn state_at_json(&self, time: f64) -> Result<String> {
+        to_json(&self.simulation.state_at(time).map_err(js_error)?)
+    }
+
+    /// An interface to `Simulation.enable_state_diffs`.
+    #[napi]
+    pub fn enable_state_diffs(&mut self) {
+        self.simulation.enable_state_diffs();
+    }
+
+    /// An interface to `Simulation.disable_state_diffs`.
+    #[napi]
+    pub fn disable_state_diffs(&mut self) {
+        self.simulation.disable_state_diffs();
+    }
+
+    /// A Node.js interface for `Simulation.diff_last_step`, which converts
+    /// the state changes to a JSON string.
+    #[napi]
+    pub fn diff_last_step_json(&self, model_id: String) -> Result<String> {
+        to_json(
+            &self
+                .simulation
+                .diff_last_step(&model_id)
+                .map_err(js_error)?,
+        )
+    }
+
+    /// An interface to `Simulation.enable_variate_recording`.
+    #[napi]
+    pub fn enable_variate_recording(&mut self, capacity: u32) {
+        self.simulation.enable_variate_recording(capacity as usize);
+    }
+
+    /// An interface to `Simulation.disable_variate_recording`.
+    #[napi]
+    pub fn disable_variate_recording(&mut self) {
+        self.simulation.disable_variate_recording();
+    }
+
+    /// A Node.js interface for `Simulation.get_variate_records`, which
+    /// converts the variate records to a JSON string.
+    #[napi]
+    pub fn get_variate_records_json(&self) -> Result<String> {
+        to_json(&self.simulation.get_variate_records())
+    }
+
+    /// An interface to `Simulation.set_seed`.
+    #[napi]
+    pub fn set_seed(&mut self, seed: BigInt) {
+        self.simulation.set_seed(seed.get_u64().1);
+    }
+
+    /// An interface to `Simulation.enable_deterministic_mode`.
+    #[napi]
+    pub fn enable_deterministic_mode(&mut self) {
+        self.simulation.enable_deterministic_mode();
+    }
+
+    /// An interface to `Simulation.disable_deterministic_mode`.
+    #[napi]
+    pub fn disable_deterministic_mode(&mut self) {
+        self.simulation.disable_deterministic_mode();
+    }
+
+    /// An interface to `Simulation.set_global`.
+    #[napi]
+    pub fn set_global(&mut self, name: String, value: f64) {
+        self.simulation.set_global(&name, value);
+    }
+
+    /// An interface to `Simulation.remove_global`.
+    #[napi]
+    pub fn remove_global(&mut self, name: String) -> Option<f64> {
+        self.simulation.remove_global(&name)
+    }
+
+    /// An interface to `Simulation.get_global`.
+    #[napi]
+    pub fn get_global(&self, name: String) -> Option<f64> {
+        self.simulation.get_global(&name)
+    }
+
+    /// A Node.js interface for `Simulation.get_globals`, which converts the
+    /// global variables to a JSON string.
+    #[napi]
+    pub fn get_globals_json(&self) -> Result<String> {
+        to_json(self.simulation.get_globals())
+    }
+
+    /// A Node.js interface for `Simulation.get_blackboard`, which converts
+    /// the blackboard entries to a JSON string.
+    #[napi]
+    pub fn get_blackboard_json(&self) -> Result<String> {
+        to_json(self.simulation.get_blackboard())
+    }
+
+    /// A Node.js interface for `Simulation.write_blackboard`, which accepts
+    /// the value as a JSON string (a boolean, number, or string).
+    #[napi]
+    pub fn write_blackboard_json(&mut self, key: String, value: String) -> Result<()> {
+        let value: BlackboardValue = from_json(&value)?;
+        self.simulation.write_blackboard(&key, value);
+        Ok(())
+    }
+
+    /// An interface to `Simulation.reset`.
+    #[napi]
+    pub fn reset(&mut self) {
+        self.simulation.reset();
+    }
+
+    /// An interface to `Simulation.reset_messages`.
+    #[napi]
+    pub fn reset_messages(&mut self) {
+        self.simulation.reset_messages();
+    }
+
+    /// An interface to `Simulation.reset_global_time`.
+    #[napi]
+    pub fn reset_global_time(&mut self) {
+        self.simulation.reset_global_time();
+    }
+
+    /// A Node.js interface for `Simulation.inject_input`, which accepts a
+    /// JSON-encoded message, as a buffer.  The buffer is read in place.
+    #[napi]
+    pub fn inject_input(&mut self, message: Buffer) -> Result<()> {
+        self.simulation
+            .inject_input(serde_json::from_slice(&message).map_err(js_error)?);
+        Ok(())
+    }
+
+    /// A Node.js interface for `Simulation.inject_input_at`, which accepts
+    /// a JSON-encoded message, as a buffer.
+    #[napi]
+    pub fn inject_input_at(&mut self, message: Buffer, time: f64) -> Result<()> {
+        self.simulation
+            .inject_input_at(serde_json::from_slice(&message).map_err(js_error)?, time)
+            .map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.set_injection_priority`, which
+    /// accepts "generated", "injected", or "timestamp".
+    #[napi]
+    pub fn set_injection_priority(&mut self, priority: String) -> Result<()> {
+        self.simulation
+            .set_injection_priority(from_name(&priority)?);
+        Ok(())
+    }
+
+    /// A Node.js interface for `Simulation.set_verbosity`, which accepts
+    /// "full", "summary", or "none".
+    #[napi]
+    pub fn set_verbosity(&mut self, model_id: String, verbosity: String) -> Result<()> {
+        self.simulation
+            .set_verbosity(&model_id, from_name(&verbosity)?)
+            .map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.set_event_scheduling`, which
+    /// accepts "scan" or "futureEventList".
+    #[napi]
+    pub fn set_event_scheduling(&mut self, scheduling: String) -> Result<()> {
+        self.simulation
+            .set_event_scheduling(from_name(&scheduling)?);
+        Ok(())
+    }
+
+    /// A Node.js interface for `Simulation.set_parameter`, which uses a
+    /// JSON representation of the parameter value.
+    #[napi]
+    pub fn set_parameter(
+        &mut self,
+        model_id: String,
+        json_pointer: String,
+        value: String,
+    ) -> Result<()> {
+        self.simulation
+            .set_parameter(&model_id, &json_pointer, from_json(&value)?)
+            .map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.replace_model`, which uses a
+    /// JSON representation of the replacement model.
+    #[napi]
+    pub fn replace_model_json(&mut self, model: String) -> Result<()> {
+        self.simulation
+            .replace_model(from_json(&model)?)
+            .map_err(js_error)?;
+        Ok(())
+    }
+
+    /// A Node.js interface for `Simulation.add_model`, which uses a JSON
+    /// representation of the added model.
+    #[napi]
+    pub fn add_model_json(&mut self, model: String) -> Result<()> {
+        self.simulation
+            .add_model(from_json(&model)?)
+            .map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.add_template_instances`, which
+    /// uses a JSON representation of the template instances, and returns
+    /// the added model IDs as a JSON string.
+    #[napi]
+    pub fn add_template_instances_json(&mut self, instances: String) -> Result<String> {
+        let added = self
+            .simulation
+            .add_template_instances(&from_json(&instances)?)
+            .map_err(js_error)?;
+        to_json(&added)
+    }
+
+    /// A Node.js interface for `Simulation.remove_model`, which provides the
+    /// undelivered messages of the removed model as a JSON-encoded buffer.
+    #[napi]
+    pub fn remove_model(&mut self, model_id: String) -> Result<Buffer> {
+        let removed = self.simulation.remove_model(&model_id).map_err(js_error)?;
+        self.export_messages(&removed.undelivered)
+    }
+
+    /// A Node.js interface for `Simulation.add_connector`, which uses a
+    /// JSON representation of the added connector.
+    #[napi]
+    pub fn add_connector_json(&mut self, connector: String) -> Result<()> {
+        self.simulation
+            .add_connector(from_json(&connector)?)
+            .map_err(js_error)
+    }
+
+    /// An interface to `Simulation.remove_connector`.
+    #[napi]
+    pub fn remove_connector(&mut self, connector_id: String) -> Result<()> {
+        self.simulation
+            .remove_connector(&connector_id)
+            .map_err(js_error)?;
+        Ok(())
+    }
+
+    /// An interface to `Simulation.begin_edit`.
+    #[napi]
+    pub fn begin_edit(&mut self) -> Result<()> {
+        self.simulation.begin_edit().map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.apply_edit`, which uses a JSON
+    /// representation of the edit operation.
+    #[napi]
+    pub fn apply_edit_json(&mut self, operation: String) -> Result<()> {
+        self.simulation
+            .apply_edit(from_json(&operation)?)
+            .map_err(js_error)
+    }
+
+    /// A Node.js interface for `Simulation.commit_edit`, which returns the
+    /// validation issues as a JSON array - empty when the edit is
+    /// committed.  An edit with issues remains open.
+    #[napi]
+    pub fn commit_edit_json(&mut self) -> Result<String> {
+        let issues = match self.simulation.commit_edit() {
+            Ok(()) => Vec::new(),
+            Err(SimulationError::InvalidEdit(issues)) => issues,
+            Err(error) => return Err(js_error(error)),
+        };
+        to_json(&issues)
+    }
+
+    /// An interface to `Simulation.rollback_edit`.
+    #[napi]
+    pub fn rollback_edit(&mut self) -> Result<()> {
+        self.simulation.rollback_edit().map_err(js_error)
+    }
+
+    /// Set the precision of exported message and record times, in decimal
+    /// places.  The rounding is "round" (to the nearest exported time) or
+    /// "truncate" (decimation).
+    #[napi]
+    pub fn set_time_precision(&mut self, decimals: u32, rounding: String) -> Result<()> {
+        let rounding: TimeRounding = from_name(&rounding)?;
+        self.time_precision = Some(TimePrecision::new(decimals, rounding));
+        Ok(())
+    }
+
+    /// Export message and record times at full precision.
+    #[napi]
+    pub fn clear_time_precision(&mut self) {
+        self.time_precision = None;
+    }
+
+    /// A Node.js interface for `Simulation.step`, which provides the
+    /// resulting messages as a JSON-encoded buffer.
+    #[napi]
+    pub fn step(&mut self) -> Result<Buffer> {
+        let messages = self.simulation.step().map_err(js_error)?;
+        self.export_messages(&messages)
+    }
+
+    /// A Node.js interface for `Simulation.step_until`, which provides the
+    /// resulting messages as a JSON-encoded buffer.
+    #[napi]
+    pub fn step_until(&mut self, until: f64) -> Result<Buffer> {
+        let messages = self.simulation.step_until(until).map_err(js_error)?;
+        self.export_messages(&messages)
+    }
+
+    /// A Node.js interface for `Simulation.step_until_stop`, which accepts
+    /// a JSON stop condition, and provides the resulting messages as a
+    /// JSON-encoded buffer.
+    #[napi]
+    pub fn step_until_stop(&mut self, condition: String) -> Result<Buffer> {
+        let condition: StopCondition = from_json(&condition)?;
+        let messages = self
+            .simulation
+            .step_until_stop(&condition)
+            .map_err(js_error)?;
+        self.export_messages(&messages)
+    }
+
+    /// A Node.js interface for `Simulation.step_n`, which provides the
+    /// resulting messages as a JSON-encoded buffer.
+    #[napi]
+    pub fn step_n(&mut self, n: u32) -> Result<Buffer> {
+        let messages = self.simulation.step_n(n as usize).map_err(js_error)?;
+        self.export_messages(&messages)
+    }
+}
@@ -0,0 +1,177 @@
+use napi::bindgen_prelude::{BigInt, Buffer};
+use sim::models::ModelRecord;
+use sim::simulator::Message;
+use sim_node::Simulation;
+
+const MODELS: &str = r#"
+[
+    {
+        "type": "Generator",
+        "id": "generator-01",
+        "portsIn": {},
+        "portsOut": {
+            "job": "job"
+        },
+        "messageInterdepartureTime": {
+            "exp": {
+                "lambda": 0.5
+            }
+        },
+        "storeRecords": true
+    },
+    {
+        "type": "Storage",
+        "id": "storage-01",
+        "portsIn": {
+            "put": "store",
+            "get": "read"
+        },
+        "portsOut": {
+            "stored": "stored"
+        }
+    }
+]"#;
+
+const CONNECTORS: &str = r#"
+[
+    {
+        "id": "connector-01",
+        "sourceID": "generator-01",
+        "targetID": "storage-01",
+        "sourcePort": "job",
+        "targetPort": "store"
+    }
+]"#;
+
+fn post(seed: u64) -> Simulation {
+    Simulation::post_json_with_seed(MODELS.into(), CONNECTORS.into(), BigInt::from(seed)).unwrap()
+}
+
+fn messages(buffer: Buffer) -> Vec<Message> {
+    serde_json::from_slice(&buffer).unwrap()
+}
+
+#[test]
+fn message_batches_are_json_encoded_buffers() {
+    let mut simulation = post(7);
+    let stepped: Vec<Message> = (0..10)
+        .flat_map(|_| messages(simulation.step().unwrap()))
+        .collect();
+    assert_eq!(stepped.len(), 5);
+    assert!(stepped.iter().all(
+        |message| message.source_id() == "generator-01" && message.target_id() == "storage-01"
+    ));
+    let last = stepped.last().unwrap();
+    let current = messages(simulation.get_messages().unwrap());
+    assert_eq!(current.last().unwrap().content(), last.content());
+    assert!(simulation.get_global_time() >= *last.time());
+
+    let batch = messages(simulation.step_n(10).unwrap());
+    assert_eq!(batch.len(), 5);
+    let until = simulation.get_global_time() + 10.0;
+    let batch = messages(simulation.step_until(until).unwrap());
+    assert!(batch.iter().all(|message| *message.time() <= until));
+}
+
+#[test]
+fn injected_buffers_are_delivered() {
+    let mut simulation = post(7);
+    let message = Message::new(
+        "manual".to_string(),
+        "manual".to_string(),
+        "storage-01".to_string(),
+        "store".to_string(),
+        simulation.get_global_time(),
+        "injected job".to_string(),
+    );
+    simulation
+        .inject_input(serde_json::to_vec(&message).unwrap().into())
+        .unwrap();
+    simulation.step().unwrap();
+    assert_eq!(
+        simulation.get_status("storage-01".into()).unwrap(),
+        "Storing injected job"
+    );
+}
+
+#[test]
+fn checkpoints_restore_the_simulation() {
+    let mut simulation = post(11);
+    simulation.step_n(6).unwrap();
+    let mut restored = Simulation::restore(simulation.checkpoint().unwrap()).unwrap();
+    assert_eq!(restored.get_global_time(), simulation.get_global_time());
+    assert_eq!(
+        &restored.step_n(6).unwrap()[..],
+        &simulation.step_n(6).unwrap()[..]
+    );
+}
+
+#[test]
+fn history_queries_are_json_encoded_buffers() {
+    let mut simulation = post(3);
+    simulation.enable_message_history();
+    simulation.step_n(20).unwrap();
+    let history = messages(
+        simulation
+            .query_messages(r#"{"targetId": "storage-01"}"#.into())
+            .unwrap(),
+    );
+    assert_eq!(history.len(), 10);
+    let between = messages(
+        simulation
+            .messages_between(0.0, *history[4].time())
+            .unwrap(),
+    );
+    assert_eq!(between.len(), 5);
+    assert_eq!(between[4].content(), history[4].content());
+}
+
+#[test]
+fn time_precision_applies_to_buffers() {
+    let mut simulation = post(5);
+    simulation.set_time_precision(0, "truncate".into()).unwrap();
+    let batch = messages(simulation.step_n(10).unwrap());
+    assert!(batch.iter().all(|message| message.time().fract() == 0.0));
+    let records: Vec<ModelRecord> =
+        serde_json::from_slice(&simulation.get_records("generator-01".into()).unwrap()).unwrap();
+    assert!(!records.is_empty());
+    assert!(records.iter().all(|record| record.time.fract() == 0.0));
+    simulation.clear_time_precision();
+    assert!(messages(simulation.get_messages().unwrap())
+        .iter()
+        .any(|message| message.time().fract() != 0.0));
+}
+
+#[test]
+fn errors_are_returned_instead_of_panicking() {
+    assert!(Simulation::post_json("not json".into(), "[]".into()).is_err());
+    let mut simulation = post(1);
+    assert!(simulation.get_status("missing-01".into()).is_err());
+    assert!(simulation
+        .set_verbosity("storage-01".into(), "loud".into())
+        .is_err());
+    assert!(simulation.inject_input(b"{".to_vec().into()).is_err());
+    assert!(Simulation::restore(b"not a checkpoint".to_vec().into()).is_err());
+    // A failed call leaves the simulation usable
+    assert_eq!(messages(simulation.step_n(2).unwrap()).len(), 1);
+}
+
+#[test]
+fn reports_are_json_strings() {
+    let mut simulation = post(9);
+    simulation.set_global("rate".into(), 2.0);
+    assert_eq!(simulation.get_global("rate".into()), Some(2.0));
+    simulation.step_n(4).unwrap();
+    let summary: serde_json::Value =
+        serde_json::from_str(&simulation.get_summary_json().unwrap()).unwrap();
+    assert_eq!(
+        summary["messageCount"],
+        messages(simulation.get_messages().unwrap()).len()
+    );
+    let validation: serde_json::Value =
+        serde_json::from_str(&simulation.validate_json(false).unwrap()).unwrap();
+    assert!(validation.is_object());
+    let configuration: serde_json::Value =
+        serde_json::from_str(&simulation.get_json().unwrap()).unwrap();
+    assert_eq!(configuration["models"].as_array().unwrap().len(), 2);
+}